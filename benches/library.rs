@@ -0,0 +1,144 @@
+use std::{fs, path::Path, time::Duration};
+
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use koto_learning::{
+    examples::{
+        self,
+        tests::{self as example_tests, ExampleTestSuite},
+    },
+    tasks,
+};
+use tempfile::TempDir;
+
+fn library_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("library");
+    group.sample_size(20);
+    group.measurement_time(Duration::from_secs(4));
+
+    for size in catalog_sizes() {
+        let catalog = synthetic_catalog(size);
+
+        let benchmark_id = BenchmarkId::new("load_examples_from_dir", format!("examples={size}"));
+        group.bench_with_input(benchmark_id, catalog.path(), |b, dir| {
+            b.iter(|| black_box(load_catalog(dir).len()));
+        });
+
+        // Every tenth script changes, as if a few files were saved between refreshes.
+        let old = load_catalog(catalog.path());
+        let mut new = old.clone();
+        for example in new.values_mut().step_by(10) {
+            example.script.push_str("\n# edited\n");
+        }
+        let benchmark_id = BenchmarkId::new("diff_examples", format!("examples={size}"));
+        group.bench_with_input(benchmark_id, &(old, new), |b, (old, new)| {
+            b.iter(|| black_box(examples::diff_examples(old, new).len()));
+        });
+
+        let suite = synthetic_suite(size);
+        let benchmark_id = BenchmarkId::new("run_suite", format!("cases={size}"));
+        group.bench_with_input(benchmark_id, &suite, |b, suite| {
+            b.iter(|| {
+                let result = example_tests::run_suite(suite).expect("failed to run suite");
+                black_box(result.passed)
+            });
+        });
+
+        // Many small suites, where setting up a runtime per suite dominates.
+        let suites = vec![synthetic_suite(5); size];
+        for (name, runner) in [
+            (
+                "run_suites_fresh",
+                example_tests::SuiteRunner::new().threads(1),
+            ),
+            (
+                "run_suites_shared_runtime",
+                example_tests::SuiteRunner::new().shared_runtime(true),
+            ),
+        ] {
+            let benchmark_id = BenchmarkId::new(name, format!("suites={size}"));
+            group.bench_with_input(benchmark_id, &suites, |b, suites| {
+                b.iter(|| {
+                    let results = runner.run_all(suites).expect("failed to run suites");
+                    black_box(results.len())
+                });
+            });
+        }
+    }
+
+    group.finish();
+}
+
+fn load_catalog(dir: &Path) -> std::collections::BTreeMap<String, examples::Example> {
+    let mut issues = Vec::new();
+    tasks::run_blocking("Benchmark catalog load", |task| {
+        examples::load_examples_from_dir(dir, task, &mut issues)
+    })
+    .expect("failed to load synthetic catalog")
+}
+
+/// Writes `size` example folders, each with metadata, a script, and a small test suite.
+fn synthetic_catalog(size: usize) -> TempDir {
+    let dir = tempfile::tempdir().expect("failed to create catalog dir");
+    for index in 0..size {
+        let example_dir = dir.path().join(format!("example_{index:04}"));
+        fs::create_dir_all(example_dir.join("tests")).expect("failed to create example dir");
+        fs::write(
+            example_dir.join("meta.json"),
+            format!(
+                r#"{{"id":"example_{index:04}","title":"Example {index}","description":"Synthetic example","categories":["Bench"]}}"#
+            ),
+        )
+        .expect("failed to write meta.json");
+        fs::write(
+            example_dir.join("script.koto"),
+            format!("x = {index}\nprint 'example {{x}}'\nx * 2\n"),
+        )
+        .expect("failed to write script.koto");
+        fs::write(
+            example_dir.join("tests").join("basics.koto"),
+            "export tests =\n  @test doubles: || assert_eq 2 * 2, 4\n",
+        )
+        .expect("failed to write test suite");
+    }
+    dir
+}
+
+fn synthetic_suite(cases: usize) -> ExampleTestSuite {
+    let mut script = String::from("# Title: Synthetic suite\n\nexport tests =\n");
+    for index in 0..cases {
+        script.push_str(&format!(
+            "  @test case_{index}: || assert_eq {index} + 1, {}\n",
+            index + 1
+        ));
+    }
+    ExampleTestSuite {
+        id: "synthetic".to_string(),
+        name: "Synthetic suite".to_string(),
+        description: None,
+        path: "synthetic.koto".into(),
+        script,
+        timeout: None,
+        tags: Vec::new(),
+        fixtures: Vec::new(),
+        example: None,
+    }
+}
+
+/// `KOTO_BENCH_CATALOG_SIZE` picks a single size; otherwise small and medium catalogs are
+/// measured, plus a large one for extended runs.
+fn catalog_sizes() -> Vec<usize> {
+    if let Some(size) = std::env::var("KOTO_BENCH_CATALOG_SIZE")
+        .ok()
+        .and_then(|value| value.parse().ok())
+    {
+        return vec![size];
+    }
+    let mut sizes = vec![10, 100];
+    if cfg!(feature = "bench-extended") || std::env::var_os("KOTO_BENCH_EXTENDED").is_some() {
+        sizes.push(1000);
+    }
+    sizes
+}
+
+criterion_group!(benches, library_benchmarks);
+criterion_main!(benches);
@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
-use koto_learning::runtime::Executor;
+use koto_learning::runtime::{Executor, Runtime};
 
 fn performance_benchmarks(c: &mut Criterion) {
     let mut group = c.benchmark_group("performance");
@@ -15,7 +15,8 @@ fn performance_benchmarks(c: &mut Criterion) {
         .collect();
 
     for (n, script) in &scripts {
-        let benchmark_id = BenchmarkId::new("koto_recursive_fib", format!("n={n}"));
+        // Execution only: every iteration after the first reuses the cached chunk.
+        let benchmark_id = BenchmarkId::new("koto_execute_fib_cached", format!("n={n}"));
         let exec = executor;
         group.bench_with_input(benchmark_id, script, |b, script| {
             b.iter(|| {
@@ -24,6 +25,26 @@ fn performance_benchmarks(c: &mut Criterion) {
             });
         });
 
+        // Compilation alone, bypassing the compile cache.
+        let benchmark_id = BenchmarkId::new("koto_compile_fib_uncached", format!("n={n}"));
+        group.bench_with_input(benchmark_id, script, |b, script| {
+            b.iter(|| {
+                Runtime::check_syntax(script).expect("failed to compile Koto fibonacci script");
+            });
+        });
+
+        // Compilation plus storing the chunk in the compile cache, as `precompile` does.
+        let benchmark_id = BenchmarkId::new("koto_precompile_fib_cache_insert", format!("n={n}"));
+        group.bench_with_input(benchmark_id, script, |b, script| {
+            b.iter(|| {
+                let artifact = exec
+                    .runtime()
+                    .precompile(script)
+                    .expect("failed to precompile Koto fibonacci script");
+                black_box(artifact.compile_time)
+            });
+        });
+
         let benchmark_id = BenchmarkId::new("rust_iterative_fib", format!("n={n}"));
         group.bench_with_input(benchmark_id, n, |b, &n| {
             b.iter(|| black_box(rust_fibonacci(n)));
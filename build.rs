@@ -0,0 +1,45 @@
+//! Records build details shown in the explorer's About window.
+
+use std::{fs, process::Command};
+
+fn main() {
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+
+    let git_hash = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .filter(|hash| !hash.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=KOTO_LEARNING_GIT_HASH={git_hash}");
+
+    let lock = fs::read_to_string("Cargo.lock").unwrap_or_default();
+    for (package, variable) in [
+        ("koto", "KOTO_LEARNING_KOTO_VERSION"),
+        ("egui", "KOTO_LEARNING_EGUI_VERSION"),
+        ("eframe", "KOTO_LEARNING_EFRAME_VERSION"),
+    ] {
+        let version = locked_version(&lock, package).unwrap_or("unknown");
+        println!("cargo:rustc-env={variable}={version}");
+    }
+}
+
+/// Finds the version of `package` in the lock file without pulling in a TOML parser.
+fn locked_version<'a>(lock: &'a str, package: &str) -> Option<&'a str> {
+    let name_line = format!("name = \"{package}\"");
+    let mut lines = lock.lines();
+    while let Some(line) = lines.next() {
+        if line.trim() == name_line {
+            return lines
+                .next()?
+                .trim()
+                .strip_prefix("version = \"")?
+                .strip_suffix('"');
+        }
+    }
+    None
+}
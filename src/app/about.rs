@@ -0,0 +1,93 @@
+use eframe::egui;
+use egui::{Grid, RichText};
+
+use super::{ExplorerApp, SnackbarKind};
+use crate::runtime;
+
+const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
+const KOTO_VERSION: &str = env!("KOTO_LEARNING_KOTO_VERSION");
+const EGUI_VERSION: &str = env!("KOTO_LEARNING_EGUI_VERSION");
+const EFRAME_VERSION: &str = env!("KOTO_LEARNING_EFRAME_VERSION");
+const BUILD_HASH: &str = env!("KOTO_LEARNING_GIT_HASH");
+const KOTO_WEBSITE: &str = "https://koto.dev";
+
+impl ExplorerApp {
+    pub(super) fn about_ui(&mut self, ctx: &egui::Context) {
+        if !self.show_about {
+            return;
+        }
+        let mut open = true;
+        let mut copy = false;
+        egui::Window::new("About Koto Learning Explorer")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("An interactive explorer for learning the Koto scripting language.");
+                ui.add_space(8.0);
+                Grid::new("about_versions").num_columns(2).show(ui, |grid| {
+                    for (label, value) in [
+                        ("Version", APP_VERSION),
+                        ("Build", BUILD_HASH),
+                        ("Koto", KOTO_VERSION),
+                        ("egui", EGUI_VERSION),
+                        ("eframe", EFRAME_VERSION),
+                    ] {
+                        grid.label(RichText::new(label).strong());
+                        grid.label(RichText::new(value).monospace());
+                        grid.end_row();
+                    }
+                });
+                ui.add_space(8.0);
+                ui.hyperlink_to("Koto website", KOTO_WEBSITE);
+                ui.hyperlink_to("Koto language reference", &self.docs_base_url);
+                ui.add_space(8.0);
+                copy = ui
+                    .button("Copy diagnostic info")
+                    .on_hover_text("Copies versions and runtime settings for bug reports")
+                    .clicked();
+            });
+        if copy {
+            ctx.copy_text(self.diagnostic_info());
+            self.push_snackbar("Diagnostic info copied", SnackbarKind::Success);
+        }
+        if !open {
+            self.show_about = false;
+        }
+    }
+
+    /// A plain-text summary of the build and runtime configuration for bug reports.
+    fn diagnostic_info(&self) -> String {
+        let examples_dir = self
+            .example_library
+            .and_then(|library| library.examples_dir())
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_else(|| "unavailable".to_string());
+        let runtime = &runtime::RUNTIME;
+        [
+            format!("koto_learning {APP_VERSION} ({BUILD_HASH})"),
+            format!("koto {KOTO_VERSION}, egui {EGUI_VERSION}, eframe {EFRAME_VERSION}"),
+            format!("os: {} ({})", std::env::consts::OS, std::env::consts::ARCH),
+            format!("examples: {} in {examples_dir}", self.examples.len()),
+            format!("runtime profile: {}", runtime.profile().label()),
+            format!("deterministic: {}", runtime.is_deterministic()),
+            format!(
+                "memory tracking: {}",
+                runtime::memory::is_tracking_available()
+            ),
+            format!("docs base url: {}", self.docs_base_url),
+            format!(
+                "features: {}",
+                runtime::features::FEATURES
+                    .iter()
+                    .map(|(name, enabled)| format!(
+                        "{name}{}",
+                        if *enabled { "" } else { " (off)" }
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ]
+        .join("\n")
+    }
+}
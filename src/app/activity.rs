@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+use eframe::egui;
+use egui::{Align2, RichText};
+
+use super::ExplorerApp;
+use crate::tasks;
+
+const BUSY_REPAINT_INTERVAL: Duration = Duration::from_millis(100);
+
+impl ExplorerApp {
+    /// Shows a spinner with the active operations in the top-right corner while work is running.
+    pub(super) fn activity_indicator_ui(&self, ctx: &egui::Context) {
+        let active = tasks::active();
+        if active.is_empty() {
+            return;
+        }
+        ctx.request_repaint_after(BUSY_REPAINT_INTERVAL);
+
+        egui::Area::new(egui::Id::new("activity_indicator"))
+            .anchor(Align2::RIGHT_TOP, egui::vec2(-12.0, 12.0))
+            .order(egui::Order::Foreground)
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_max_width(280.0);
+                    for task in &active {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(&task.label);
+                        });
+                        if let Some(message) = &task.message {
+                            ui.label(RichText::new(message).small().weak());
+                        }
+                        if let Some(fraction) = task.fraction() {
+                            ui.add(
+                                egui::ProgressBar::new(fraction)
+                                    .desired_width(240.0)
+                                    .show_percentage(),
+                            );
+                        }
+                    }
+                });
+            });
+    }
+}
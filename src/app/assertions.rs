@@ -0,0 +1,142 @@
+use eframe::egui;
+use egui::{Color32, RichText};
+use koto::prelude::KValue;
+
+use super::{ExplorerApp, example_session_id};
+use crate::{examples::Example, runtime};
+
+/// How the example's assertion fared after its latest run.
+#[derive(Clone)]
+pub(super) struct AssertionOutcome {
+    expression: String,
+    /// `None` when the assertion held, or why it didn't.
+    failure: Option<String>,
+}
+
+impl ExplorerApp {
+    /// The assertion checked after each run: the one typed into the assertion bar, or else the
+    /// `assertion` from the example's `meta.json`.
+    fn assertion_for(&self, example: &Example) -> Option<String> {
+        self.assertion_overrides
+            .get(&example.metadata.id)
+            .or(example.metadata.assertion.as_ref())
+            .map(|expression| expression.trim().to_string())
+            .filter(|expression| !expression.is_empty())
+    }
+
+    /// Checks `example_id`'s assertion against the run that just finished. It holds when it
+    /// evaluates to `true`; a failed run fails it too.
+    pub(super) fn check_assertion(
+        &mut self,
+        example_id: &str,
+        run_succeeded: bool,
+        value: Option<KValue>,
+    ) {
+        let Some(example) = self
+            .examples
+            .iter()
+            .find(|example| example.metadata.id == example_id)
+        else {
+            return;
+        };
+        let Some(expression) = self.assertion_for(example) else {
+            self.assertion_outcomes.remove(example_id);
+            return;
+        };
+
+        let failure = if run_succeeded {
+            let session = self.session_mode.then(|| example_session_id(example_id));
+            match runtime::RUNTIME.evaluate_watches(
+                std::slice::from_ref(&expression),
+                value,
+                session.as_deref(),
+            ) {
+                Ok(mut results) => match results.pop() {
+                    Some(Ok(text)) if text == "true" => None,
+                    Some(Ok(text)) => Some(format!("evaluated to {text}")),
+                    Some(Err(error)) => Some(error.lines().next().unwrap_or_default().to_string()),
+                    None => Some("wasn't evaluated".to_string()),
+                },
+                Err(error) => Some(format!("{error:#}")),
+            }
+        } else {
+            Some("the run failed".to_string())
+        };
+        self.assertion_outcomes.insert(
+            example_id.to_string(),
+            AssertionOutcome {
+                expression,
+                failure,
+            },
+        );
+    }
+
+    /// An editable assertion for the example, with a pass/fail badge from the latest run.
+    pub(super) fn assertion_bar_ui(&mut self, ui: &mut egui::Ui, example: &Example) {
+        let id = &example.metadata.id;
+        // The raw text, so spaces typed at the end survive until the next word.
+        let mut expression = self
+            .assertion_overrides
+            .get(id)
+            .or(example.metadata.assertion.as_ref())
+            .cloned()
+            .unwrap_or_default();
+        ui.horizontal(|ui| {
+            ui.label("Assert");
+            let response = ui
+                .add(
+                    egui::TextEdit::singleline(&mut expression)
+                        .code_editor()
+                        .hint_text("result == 42")
+                        .desired_width(220.0),
+                )
+                .on_hover_text(
+                    "Checked after every run; `result` is the return value and exports are in \
+                     scope",
+                );
+            if response.changed() {
+                self.assertion_overrides
+                    .insert(id.clone(), expression.clone());
+            }
+            if self.assertion_overrides.contains_key(id)
+                && ui
+                    .small_button("Reset")
+                    .on_hover_text("Go back to the assertion from meta.json")
+                    .clicked()
+            {
+                self.assertion_overrides.remove(id);
+            }
+
+            // An outcome for a different expression is out of date until the next run.
+            match self
+                .assertion_outcomes
+                .get(id)
+                .filter(|outcome| outcome.expression == expression.trim())
+            {
+                Some(AssertionOutcome { failure: None, .. }) => {
+                    ui.label(
+                        RichText::new("✔ Passed")
+                            .strong()
+                            .color(Color32::from_rgb(120, 200, 120)),
+                    );
+                }
+                Some(AssertionOutcome {
+                    failure: Some(reason),
+                    ..
+                }) => {
+                    ui.label(
+                        RichText::new("✖ Failed")
+                            .strong()
+                            .color(Color32::from_rgb(220, 100, 100)),
+                    )
+                    .on_hover_text(reason);
+                    ui.label(RichText::new(reason).small().weak());
+                }
+                None if !expression.trim().is_empty() => {
+                    ui.label(RichText::new("Run to check").small().weak());
+                }
+                None => {}
+            }
+        });
+    }
+}
@@ -0,0 +1,266 @@
+use std::{
+    path::PathBuf,
+    sync::mpsc::{self, Receiver},
+    time::Duration,
+};
+
+use eframe::egui;
+use serde_json::json;
+
+use super::{ConsoleEntry, ExplorerApp, SnackbarKind};
+use crate::{
+    runtime::{
+        ExecutionOutput, RunOptions, Runtime,
+        automation::{self, AutomationCommand, AutomationRequest},
+    },
+    tasks::{self, Task},
+};
+
+const AUTOMATION_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A Koto script driving the explorer through the `app` module. It runs in a runtime of its
+/// own, so it can wait on example runs in the shared one.
+pub(super) struct Automation {
+    name: String,
+    task: Task<ExecutionOutput>,
+    requests: Receiver<AutomationRequest>,
+    /// The `app.run()` call waiting for the current example run to finish.
+    pending_run: Option<AutomationRequest>,
+    /// Quits with the script's outcome as the exit status once it finishes.
+    exit_when_done: bool,
+}
+
+impl ExplorerApp {
+    pub(super) fn is_automating(&self) -> bool {
+        self.automation.is_some()
+    }
+
+    /// Starts the automation script at `path`.
+    pub(super) fn start_automation(&mut self, path: PathBuf, exit_when_done: bool) {
+        if self.is_automating() {
+            self.push_snackbar(
+                "An automation script is already running",
+                SnackbarKind::Info,
+            );
+            return;
+        }
+        let script = match std::fs::read_to_string(&path) {
+            Ok(script) => script,
+            Err(error) => {
+                let message = format!("Failed to read {}: {error}", path.display());
+                if exit_when_done {
+                    eprintln!("{message}");
+                    std::process::exit(2);
+                }
+                self.push_console_entry(ConsoleEntry::error(message));
+                return;
+            }
+        };
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        let (sender, requests) = mpsc::channel();
+        let task = tasks::spawn(format!("Automation '{name}'"), move |_| {
+            let runtime = Runtime::new()?;
+            runtime.register_host_module("app", automation::app_module(sender))?;
+            runtime.execute(&script, &RunOptions::new())
+        });
+        self.push_console_entry(ConsoleEntry::info(format!(
+            "Running automation script '{name}'"
+        )));
+        self.automation = Some(Automation {
+            name,
+            task,
+            requests,
+            pending_run: None,
+            exit_when_done,
+        });
+    }
+
+    /// Answers the script's `app` calls and reports its outcome once it finishes.
+    pub(super) fn poll_automation(&mut self, ctx: &egui::Context) {
+        let Some(automation) = &self.automation else {
+            return;
+        };
+        ctx.request_repaint_after(AUTOMATION_POLL_INTERVAL);
+        let requests: Vec<AutomationRequest> = automation.requests.try_iter().collect();
+        for request in requests {
+            self.handle_automation_request(request);
+        }
+
+        let Some(automation) = &self.automation else {
+            return;
+        };
+        let Some(result) = automation.task.poll() else {
+            return;
+        };
+        let Some(automation) = self.automation.take() else {
+            return;
+        };
+        let succeeded = result.is_ok();
+        match result {
+            Ok(output) => {
+                if !output.stdout.is_empty() {
+                    self.push_console_entry(ConsoleEntry::log(output.stdout.trim_end()));
+                }
+                self.push_console_entry(ConsoleEntry::info(format!(
+                    "Automation script '{}' finished",
+                    automation.name
+                )));
+                self.push_snackbar("Automation script passed", SnackbarKind::Success);
+                if automation.exit_when_done {
+                    print!("{}", output.stdout);
+                }
+            }
+            Err(error) => {
+                self.push_console_entry(ConsoleEntry::error(format!(
+                    "Automation script '{}' failed: {error:#}",
+                    automation.name
+                )));
+                self.push_snackbar("Automation script failed", SnackbarKind::Error);
+                if automation.exit_when_done {
+                    eprintln!("{}: {error:#}", automation.name);
+                }
+            }
+        }
+        if automation.exit_when_done {
+            std::process::exit(if succeeded { 0 } else { 1 });
+        }
+    }
+
+    fn handle_automation_request(&mut self, request: AutomationRequest) {
+        let answer = match &request.command {
+            AutomationCommand::ListExamples => Ok(json!(
+                self.examples
+                    .iter()
+                    .map(|example| example.metadata.id.clone())
+                    .collect::<Vec<_>>()
+            )),
+            AutomationCommand::Selected => Ok(json!(self.selected_example_id)),
+            AutomationCommand::Select(id) => {
+                if self
+                    .examples
+                    .iter()
+                    .any(|example| &example.metadata.id == id)
+                {
+                    self.select_example(id);
+                    Ok(json!(null))
+                } else {
+                    Err(format!("There is no example with the id '{id}'"))
+                }
+            }
+            AutomationCommand::SetInput { name, value } => match self.selected_example() {
+                Some(example)
+                    if example
+                        .metadata
+                        .inputs
+                        .iter()
+                        .any(|input| &input.name == name) =>
+                {
+                    self.input_values.insert(name.clone(), value.clone());
+                    Ok(json!(null))
+                }
+                Some(example) => Err(format!(
+                    "'{}' has no input named '{name}'",
+                    example.metadata.id
+                )),
+                None => Err("Select an example before setting its inputs".to_string()),
+            },
+            AutomationCommand::SetArgs(text) => {
+                self.args_input = text.clone();
+                Ok(json!(null))
+            }
+            AutomationCommand::Run => {
+                if self.selected_example().is_none() {
+                    Err("Select an example before running it".to_string())
+                } else if self.is_script_running() || self.is_repeating_runs() {
+                    Err("Another run is still in progress".to_string())
+                } else {
+                    self.run_selected_example();
+                    if self.is_script_running() {
+                        // Answered by `reply_to_automation_run` once the run finishes.
+                        if let Some(automation) = &mut self.automation {
+                            automation.pending_run = Some(request);
+                        }
+                        return;
+                    }
+                    Err("The example didn't start; check its inputs".to_string())
+                }
+            }
+            AutomationCommand::Console => Ok(json!(
+                self.console_entries
+                    .iter()
+                    .map(ConsoleEntry::text)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            )),
+            AutomationCommand::ClearConsole => {
+                self.console_entries.clear();
+                Ok(json!(null))
+            }
+        };
+        request.reply(answer);
+    }
+
+    /// Answers a waiting `app.run()` call with the outcome of the run that just finished.
+    pub(super) fn reply_to_automation_run(&mut self, result: &anyhow::Result<ExecutionOutput>) {
+        let Some(request) = self
+            .automation
+            .as_mut()
+            .and_then(|automation| automation.pending_run.take())
+        else {
+            return;
+        };
+        let outcome = match result {
+            Ok(output) => json!({
+                "succeeded": true,
+                "stdout": output.stdout,
+                "stderr": output.stderr,
+                "return_value": output.return_value,
+                "error": null,
+                "duration_ms": output.duration.as_secs_f64() * 1000.0,
+            }),
+            Err(error) => json!({
+                "succeeded": false,
+                "stdout": "",
+                "stderr": "",
+                "return_value": null,
+                "error": format!("{error:#}"),
+                "duration_ms": null,
+            }),
+        };
+        request.reply(Ok(outcome));
+    }
+
+    /// Asks for the path of an automation script to run.
+    pub(super) fn automation_dialog_ui(&mut self, ctx: &egui::Context) {
+        let Some(path) = &mut self.automation_path else {
+            return;
+        };
+        let mut open = true;
+        let mut start = false;
+        let mut cancel = false;
+        egui::Window::new("Run automation script")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("Koto script using the app module");
+                ui.add(egui::TextEdit::singleline(path).desired_width(360.0));
+                ui.horizontal(|ui| {
+                    start = ui
+                        .add_enabled(!path.trim().is_empty(), egui::Button::new("Run"))
+                        .clicked();
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+        if start {
+            let path = PathBuf::from(path.trim());
+            self.automation_path = None;
+            self.start_automation(path, false);
+        } else if !open || cancel {
+            self.automation_path = None;
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use eframe::egui;
+use egui::{Color32, EventFilter, FontId, Pos2, Rect, RichText, Stroke, Vec2};
+
+use super::{ConsolePane, ExplorerApp};
+use crate::runtime::{
+    self,
+    gfx::{Frame, Rgba, Shape},
+    input::InputEvent,
+};
+
+impl ExplorerApp {
+    /// Shows the frame a script presented, switching to the canvas when a run starts drawing.
+    pub(super) fn show_canvas_frame(&mut self, frame: Frame) {
+        if self.canvas_frame.is_none() && self.active_console_pane == ConsolePane::Console {
+            self.active_console_pane = ConsolePane::Canvas;
+        }
+        self.canvas_frame = Some(frame);
+    }
+
+    pub(super) fn canvas_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(frame) = &self.canvas_frame else {
+            ui.label("Nothing drawn yet.");
+            ui.label(
+                RichText::new("Draw with gfx.rect, gfx.circle, gfx.line, and gfx.text.").small(),
+            );
+            return;
+        };
+
+        let running = self.is_script_running();
+        let can_capture = cfg!(feature = "image")
+            && self.selected_example().is_some()
+            && self.thumbnail_capture.is_none();
+        let mut capture = false;
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new(format!(
+                    "Frame {} · {} x {} · {} shapes",
+                    frame.number,
+                    frame.width,
+                    frame.height,
+                    frame.shapes.len()
+                ))
+                .small()
+                .weak(),
+            );
+            if running {
+                ui.label(
+                    RichText::new("Click the canvas to send keys to the script")
+                        .small()
+                        .weak(),
+                );
+            }
+            capture = ui
+                .add_enabled(can_capture, egui::Button::new("Save as thumbnail").small())
+                .on_hover_text("Use this frame as the example's picture in the catalog")
+                .clicked();
+        });
+        let available = ui.available_size();
+        let scale = (available.x / frame.width)
+            .min(available.y / frame.height)
+            .max(0.01);
+        let (response, painter) = ui.allocate_painter(
+            Vec2::new(frame.width, frame.height) * scale,
+            egui::Sense::click_and_drag(),
+        );
+        let canvas_rect = response.rect;
+        let origin = response.rect.min;
+        let to_screen = |x: f32, y: f32| origin + Vec2::new(x, y) * scale;
+        if running {
+            forward_input(ui, &response, scale);
+        }
+
+        let painter = painter.with_clip_rect(response.rect);
+        painter.rect_filled(response.rect, 0.0, color(frame.background));
+        for shape in &frame.shapes {
+            match shape {
+                Shape::Rect {
+                    x,
+                    y,
+                    width,
+                    height,
+                    color: fill,
+                } => {
+                    let rect =
+                        Rect::from_min_size(to_screen(*x, *y), Vec2::new(*width, *height) * scale);
+                    painter.rect_filled(rect, 0.0, color(*fill));
+                }
+                Shape::Circle {
+                    x,
+                    y,
+                    radius,
+                    color: fill,
+                } => {
+                    painter.circle_filled(to_screen(*x, *y), radius * scale, color(*fill));
+                }
+                Shape::Line {
+                    from,
+                    to,
+                    width,
+                    color: stroke,
+                } => {
+                    let points: [Pos2; 2] = [to_screen(from.0, from.1), to_screen(to.0, to.1)];
+                    painter.line_segment(points, Stroke::new(width * scale, color(*stroke)));
+                }
+                Shape::Text {
+                    x,
+                    y,
+                    text,
+                    size,
+                    color: fill,
+                } => {
+                    painter.text(
+                        to_screen(*x, *y),
+                        egui::Align2::LEFT_TOP,
+                        text,
+                        FontId::monospace(size * scale),
+                        color(*fill),
+                    );
+                }
+            }
+        }
+        if capture {
+            self.capture_canvas_thumbnail(ui.ctx(), canvas_rect);
+        }
+    }
+}
+
+/// Sends the canvas's pointer events, and key events while it has focus, to the running script.
+fn forward_input(ui: &egui::Ui, response: &egui::Response, scale: f32) {
+    if response.clicked() {
+        response.request_focus();
+    }
+    let focused = response.has_focus();
+    if focused {
+        // Arrow keys and tab are game controls here, not focus navigation.
+        ui.memory_mut(|memory| {
+            memory.set_focus_lock_filter(
+                response.id,
+                EventFilter {
+                    tab: true,
+                    horizontal_arrows: true,
+                    vertical_arrows: true,
+                    escape: false,
+                },
+            );
+        });
+    }
+    let rect = response.rect;
+    let to_canvas = |pos: Pos2| {
+        let offset = (pos - rect.min) / scale;
+        (offset.x, offset.y)
+    };
+    let events = ui.input(|input| input.events.clone());
+    for event in events {
+        let forwarded = match event {
+            egui::Event::Key {
+                key,
+                pressed,
+                repeat: false,
+                ..
+            } if focused => Some(if pressed {
+                InputEvent::KeyDown(key.name().to_string())
+            } else {
+                InputEvent::KeyUp(key.name().to_string())
+            }),
+            egui::Event::PointerMoved(pos) if rect.contains(pos) => {
+                let (x, y) = to_canvas(pos);
+                Some(InputEvent::PointerMove { x, y })
+            }
+            egui::Event::PointerButton {
+                pos,
+                button,
+                pressed,
+                ..
+            } if rect.contains(pos) => {
+                let (x, y) = to_canvas(pos);
+                let button = button_name(button).to_string();
+                Some(if pressed {
+                    InputEvent::PointerDown { x, y, button }
+                } else {
+                    InputEvent::PointerUp { x, y, button }
+                })
+            }
+            _ => None,
+        };
+        if let Some(event) = forwarded {
+            runtime::RUNTIME.push_input_event(event);
+        }
+    }
+}
+
+fn button_name(button: egui::PointerButton) -> &'static str {
+    match button {
+        egui::PointerButton::Primary => "primary",
+        egui::PointerButton::Secondary => "secondary",
+        egui::PointerButton::Middle => "middle",
+        egui::PointerButton::Extra1 => "extra1",
+        egui::PointerButton::Extra2 => "extra2",
+    }
+}
+
+fn color([r, g, b, a]: Rgba) -> Color32 {
+    Color32::from_rgba_unmultiplied(r, g, b, a)
+}
@@ -0,0 +1,108 @@
+use eframe::egui;
+use egui::RichText;
+
+use super::{ConsoleEntry, ExplorerApp, notebook::markdown_ui};
+use crate::examples::categories::{self, CATEGORIES_DIR_NAME, CATEGORY_PAGE_FILE_NAME};
+
+/// The group the sidebar puts examples without categories in.
+pub(super) const UNCATEGORIZED: &str = "Uncategorized";
+
+impl ExplorerApp {
+    /// Reads the landing pages of the current catalog's categories.
+    pub(super) fn reload_category_pages(&mut self) {
+        let Some(dir) = self
+            .example_library
+            .and_then(|library| library.examples_dir())
+        else {
+            self.category_pages.clear();
+            return;
+        };
+        match categories::load_pages(&dir) {
+            Ok(pages) => self.category_pages = pages,
+            Err(error) => {
+                self.category_pages.clear();
+                self.push_console_entry(ConsoleEntry::error(format!(
+                    "Failed to load category pages: {error:#}"
+                )));
+            }
+        }
+    }
+
+    /// A sidebar group header: the arrow folds the group, and clicking the name opens the
+    /// category's landing page.
+    pub(super) fn category_header_ui(&mut self, ui: &mut egui::Ui, category: &str) {
+        let selected = self.selected_category.as_deref() == Some(category);
+        let mut text = RichText::new(category).strong();
+        if selected {
+            text = text.background_color(ui.visuals().selection.bg_fill);
+        }
+        let mut response = ui
+            .add(egui::Label::new(text).sense(egui::Sense::click()))
+            .on_hover_cursor(egui::CursorIcon::PointingHand);
+        if self.category_page(category).is_some() {
+            response = response.on_hover_text("Open the introduction to this category");
+        }
+        if response.clicked() {
+            self.selected_category = Some(category.to_string());
+        }
+    }
+
+    fn category_page(&self, category: &str) -> Option<&categories::CategoryPage> {
+        self.category_pages
+            .get(&categories::category_slug(category))
+    }
+
+    /// The landing page of `category`: its `category.md`, then the examples filed under it.
+    pub(super) fn category_page_ui(&mut self, ui: &mut egui::Ui, category: &str) {
+        ui.horizontal(|ui| {
+            ui.heading(category);
+            if ui.small_button("Close").clicked() {
+                self.selected_category = None;
+            }
+        });
+        match self.category_page(category) {
+            Some(page) => {
+                let markdown = page.markdown.clone();
+                markdown_ui(ui, &markdown);
+            }
+            None => {
+                ui.label(
+                    RichText::new(format!(
+                        "Introduce this category in {CATEGORIES_DIR_NAME}/{}/{CATEGORY_PAGE_FILE_NAME}",
+                        categories::category_slug(category)
+                    ))
+                    .weak(),
+                );
+            }
+        }
+        ui.add_space(10.0);
+        ui.separator();
+
+        let mut open = None;
+        for example in &self.examples {
+            let in_category = if example.metadata.categories.is_empty() {
+                category == UNCATEGORIZED
+            } else {
+                example
+                    .metadata
+                    .categories
+                    .iter()
+                    .any(|name| name == category)
+            };
+            if !in_category || !self.passes_filters(example) {
+                continue;
+            }
+            ui.add_space(4.0);
+            if ui
+                .link(RichText::new(&example.metadata.title).strong())
+                .clicked()
+            {
+                open = Some(example.metadata.id.clone());
+            }
+            ui.label(&example.metadata.description);
+        }
+        if let Some(id) = open {
+            self.select_example(&id);
+        }
+    }
+}
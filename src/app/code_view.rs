@@ -0,0 +1,412 @@
+use std::{ops::RangeInclusive, sync::Arc};
+
+use eframe::egui;
+use egui::{Color32, RichText, text::CCursor};
+use egui_extras::syntax_highlighting;
+
+use super::{
+    ExplorerApp,
+    inline_eval::{inline_eval_requested, paint_inline_annotations},
+    lsp::{char_index_for_position, lsp_position, severity_color},
+};
+use crate::{
+    examples::{
+        Example, HowItWorksStep,
+        tests::{self, ExampleTestSuite},
+    },
+    language::{
+        self,
+        symbols::{Symbol, SymbolIndex, SymbolKind},
+    },
+    runtime::coverage::LineCoverage,
+};
+
+const CODE_HIGHLIGHT_COLOR: Color32 = Color32::from_rgba_premultiplied(60, 50, 20, 40);
+const COVERED_LINE_COLOR: Color32 = Color32::from_rgb(120, 200, 120);
+const UNCOVERED_LINE_COLOR: Color32 = Color32::from_rgb(220, 100, 100);
+
+/// Lines of an example's script picked out by one of its "How it works" paragraphs.
+pub(super) struct CodeHighlight {
+    example_id: String,
+    /// Which paragraph picked the lines.
+    step: usize,
+    lines: RangeInclusive<usize>,
+    /// Set until the code view has scrolled the lines into view.
+    scroll_pending: bool,
+}
+
+impl ExplorerApp {
+    /// Shows one "How it works" paragraph. Paragraphs linked to script lines can be clicked to
+    /// highlight those lines in the code view, and clicked again to clear it.
+    pub(super) fn how_it_works_step_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        example: &Example,
+        index: usize,
+        step: &HowItWorksStep,
+    ) {
+        let Some(lines) = step.line_range(&example.script) else {
+            ui.label(&step.text);
+            return;
+        };
+        let selected = self.code_highlight.as_ref().is_some_and(|highlight| {
+            highlight.example_id == example.metadata.id && highlight.step == index
+        });
+        let mut text = RichText::new(&step.text);
+        if selected {
+            text = text.background_color(ui.visuals().selection.bg_fill);
+        }
+        let response = ui
+            .add(egui::Label::new(text).sense(egui::Sense::click()))
+            .on_hover_cursor(egui::CursorIcon::PointingHand)
+            .on_hover_text(format!(
+                "Highlight lines {}–{} in the code",
+                lines.start() + 1,
+                lines.end() + 1
+            ));
+        if response.clicked() {
+            self.code_highlight = (!selected).then(|| CodeHighlight {
+                example_id: example.metadata.id.clone(),
+                step: index,
+                lines,
+                scroll_pending: true,
+            });
+        }
+    }
+
+    /// Coverage of `example`'s script, combined over the latest run of each suite that loads
+    /// it with an `# Example:` header.
+    pub(super) fn example_coverage(&self, example: &Example) -> Option<LineCoverage> {
+        let mut combined: Option<LineCoverage> = None;
+        for suite in &example.test_suites {
+            let script_path = tests::example_script_path(suite).ok().flatten();
+            if script_path.as_deref() != Some(example.script_path.as_path()) {
+                continue;
+            }
+            let key = format!("{}::{}", example.metadata.id, suite.id);
+            let Some(coverage) = self
+                .test_runs
+                .get(&key)
+                .and_then(|result| result.coverage.as_ref())
+            else {
+                continue;
+            };
+            match &mut combined {
+                Some(combined) => combined.merge(coverage),
+                None => combined = Some(coverage.clone()),
+            }
+        }
+        combined
+    }
+
+    /// Renders the highlighted script (or the editor when editing) and links core library
+    /// references to the Koto docs.
+    pub(super) fn code_view_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        ctx: &egui::Context,
+        example: &Example,
+    ) {
+        ui.group(|ui| {
+            let editing = self.is_editing(example);
+            ui.horizontal(|ui| {
+                ui.label("Code");
+                if editing {
+                    let dirty = self
+                        .editor
+                        .as_ref()
+                        .is_some_and(|editor| editor.is_dirty(example));
+                    if dirty {
+                        ui.label(RichText::new("● modified").small());
+                    }
+                    if ui.button("Save").clicked() {
+                        self.save_editor(example);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.editor = None;
+                    }
+                    ui.label(RichText::new("Ctrl+Space for completions").small().weak());
+                } else if ui.button("Edit").clicked() {
+                    self.open_editor(example);
+                }
+                ui.label(
+                    RichText::new("Ctrl+Enter evaluates the line")
+                        .small()
+                        .weak(),
+                );
+                if self.inline_annotations.contains_key(&example.metadata.id)
+                    && ui.small_button("Clear results").clicked()
+                {
+                    self.clear_inline_annotations(&example.metadata.id);
+                }
+            });
+
+            if self.is_editing(example) {
+                self.editor_ui(ui, ctx);
+                return;
+            }
+
+            let theme = syntax_highlighting::CodeTheme::from_memory(ctx, ui.style());
+            let mut layouter = |ui: &egui::Ui, text: &dyn egui::TextBuffer, wrap_width: f32| {
+                let mut job = syntax_highlighting::highlight(
+                    ui.ctx(),
+                    ui.style(),
+                    &theme,
+                    text.as_str(),
+                    "koto",
+                );
+                job.wrap.max_width = wrap_width;
+                ui.fonts(|fonts| fonts.layout_job(job))
+            };
+
+            self.sync_language_server_document(&example.script_path, &example.script);
+            let diagnostics = self.language_server_diagnostics(&example.script_path);
+            let annotations = self.inline_annotations_for(&example.metadata.id, &example.script);
+            let evaluate = inline_eval_requested(ui);
+            let highlight = self
+                .code_highlight
+                .as_ref()
+                .filter(|highlight| highlight.example_id == example.metadata.id)
+                .map(|highlight| (highlight.lines.clone(), highlight.scroll_pending));
+            let coverage = self.example_coverage(example);
+
+            let mut script = example.script.as_str();
+            let output = egui::ScrollArea::both()
+                .id_salt("code_view")
+                .show(ui, |ui| {
+                    let output = egui::TextEdit::multiline(&mut script)
+                        .code_editor()
+                        .desired_width(f32::INFINITY)
+                        .layouter(&mut layouter)
+                        .show(ui);
+                    for diagnostic in &diagnostics {
+                        let start = char_index_for_position(
+                            &example.script,
+                            diagnostic.line,
+                            diagnostic.start_character,
+                        );
+                        let end = char_index_for_position(
+                            &example.script,
+                            diagnostic.line,
+                            diagnostic.end_character,
+                        )
+                        .max(start + 1);
+                        let from = output.galley.pos_from_cursor(CCursor::new(start));
+                        let to = output.galley.pos_from_cursor(CCursor::new(end));
+                        let y = output.galley_pos.y + from.bottom();
+                        ui.painter().line_segment(
+                            [
+                                egui::pos2(output.galley_pos.x + from.left(), y),
+                                egui::pos2(output.galley_pos.x + to.left(), y),
+                            ],
+                            egui::Stroke::new(1.5, severity_color(diagnostic.severity)),
+                        );
+                    }
+                    paint_inline_annotations(ui, &example.script, &output, &annotations);
+                    if let Some(coverage) = &coverage {
+                        paint_line_coverage(ui, &example.script, &output, coverage);
+                    }
+                    if let Some((lines, scroll)) = &highlight {
+                        let rect = lines_rect(&example.script, &output, lines);
+                        ui.painter().rect_filled(rect, 2.0, CODE_HIGHLIGHT_COLOR);
+                        if *scroll {
+                            ui.scroll_to_rect(rect, Some(egui::Align::Center));
+                        }
+                    }
+                    output
+                })
+                .inner;
+            theme.store_in_memory(ctx);
+            if let Some(highlight) = &mut self.code_highlight {
+                if highlight.example_id == example.metadata.id {
+                    highlight.scroll_pending = false;
+                }
+            }
+
+            if evaluate {
+                if let Some(selection) = output.cursor_range {
+                    self.evaluate_inline(&example.metadata.id, &example.script, selection);
+                }
+            }
+
+            self.symbol_hover(
+                ctx,
+                example,
+                &output.response,
+                &output.galley,
+                output.galley_pos,
+            );
+            self.language_server_results_ui(ui, &example.script_path);
+            self.core_reference_links_ui(ui, &example.script);
+        });
+    }
+
+    fn symbol_hover(
+        &mut self,
+        ctx: &egui::Context,
+        example: &Example,
+        response: &egui::Response,
+        galley: &Arc<egui::Galley>,
+        galley_pos: egui::Pos2,
+    ) {
+        let Some(pointer) = response.hover_pos() else {
+            return;
+        };
+        let script = example.script.as_str();
+        let cursor = galley.cursor_from_pos(pointer - galley_pos);
+        let Some(offset) = char_to_byte_offset(script, cursor.index) else {
+            return;
+        };
+        let (line, character) = lsp_position(script, offset);
+        let index = SymbolIndex::for_script(script);
+        let ctrl_clicked = response.clicked() && ctx.input(|input| input.modifiers.command);
+        let Some(symbol) = index.symbol_at(script, offset) else {
+            // Symbols the built-in index doesn't know about are left to the language server.
+            if let Some(text) = self.language_server_hover(&example.script_path, line, character) {
+                response.clone().on_hover_ui_at_pointer(|ui| {
+                    ui.label(RichText::new(text).monospace());
+                });
+            }
+            if ctrl_clicked {
+                self.request_language_server_definition(&example.script_path, line, character);
+            }
+            return;
+        };
+
+        let doc_url = match symbol.kind {
+            SymbolKind::CoreFunction => symbol.name.split_once('.').map(|(module, function)| {
+                language::core_doc_url(&self.docs_base_url, module, Some(function))
+            }),
+            SymbolKind::CoreModule => Some(language::core_doc_url(
+                &self.docs_base_url,
+                &symbol.name,
+                None,
+            )),
+            SymbolKind::HostBinding
+            | SymbolKind::Export
+            | SymbolKind::Local
+            | SymbolKind::Keyword => None,
+        };
+        let server_hover = if doc_url.is_none() {
+            self.language_server_hover(&example.script_path, line, character)
+        } else {
+            None
+        };
+
+        response.clone().on_hover_ui_at_pointer(|ui| {
+            symbol_tooltip_ui(ui, symbol);
+            if let Some(text) = &server_hover {
+                ui.separator();
+                ui.label(RichText::new(text).monospace());
+            }
+            if doc_url.is_some() {
+                ui.label(RichText::new("Ctrl+click to open the core library docs").small());
+            } else if self.lsp.is_some() {
+                ui.label(RichText::new("Ctrl+click to go to the definition").small());
+            }
+        });
+        if ctrl_clicked {
+            match doc_url {
+                Some(url) => ctx.open_url(egui::OpenUrl::new_tab(url)),
+                None => {
+                    self.request_language_server_definition(&example.script_path, line, character);
+                }
+            }
+        }
+    }
+
+    fn core_reference_links_ui(&self, ui: &mut egui::Ui, script: &str) {
+        let mut references = language::find_core_references(script);
+        if references.is_empty() {
+            return;
+        }
+        references.sort_by_key(|reference| (reference.module, reference.function));
+        references.dedup_by_key(|reference| (reference.module, reference.function));
+
+        ui.add_space(4.0);
+        ui.horizontal_wrapped(|ui| {
+            ui.label(RichText::new("Core library:").small());
+            for reference in references {
+                let url = reference.doc_url(&self.docs_base_url);
+                ui.hyperlink_to(RichText::new(reference.qualified_name()).monospace(), &url)
+                    .on_hover_text(url);
+            }
+        });
+    }
+}
+
+/// The area covered by `lines` (0-based) of the laid-out script, across the full width of
+/// the code view.
+fn lines_rect(
+    script: &str,
+    output: &egui::text_edit::TextEditOutput,
+    lines: &RangeInclusive<usize>,
+) -> egui::Rect {
+    let first = char_index_for_position(script, *lines.start() as u32, 0);
+    let last = char_index_for_position(script, *lines.end() as u32, 0);
+    let top = output.galley.pos_from_cursor(CCursor::new(first)).top();
+    let bottom = output.galley.pos_from_cursor(CCursor::new(last)).bottom();
+    egui::Rect::from_x_y_ranges(
+        output.response.rect.x_range(),
+        output.galley_pos.y + top..=output.galley_pos.y + bottom,
+    )
+}
+
+pub(super) fn symbol_tooltip_ui(ui: &mut egui::Ui, symbol: &Symbol) {
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(&symbol.name).monospace().strong());
+        ui.label(RichText::new(symbol.kind.label()).small().weak());
+    });
+    if let Some(signature) = &symbol.signature {
+        ui.label(RichText::new(signature).monospace());
+    }
+    if let Some(summary) = &symbol.summary {
+        ui.label(summary);
+    }
+}
+
+/// The share of its `# Example:` script that `suite` reached; the lines themselves are marked
+/// in the code view.
+pub(super) fn suite_coverage_ui(
+    ui: &mut egui::Ui,
+    suite: &ExampleTestSuite,
+    coverage: &LineCoverage,
+) {
+    let script = suite.example.as_deref().unwrap_or("the example");
+    ui.label(format!(
+        "Coverage of {script}: {:.0}% ({}/{} lines)",
+        coverage.percent(),
+        coverage.covered(),
+        coverage.lines.len()
+    ))
+    .on_hover_text("Lines that ran are marked green in the code view, and lines that didn't red");
+}
+
+/// Marks each executable line of `script` in the gutter: green if it ran, red if it didn't.
+pub(super) fn paint_line_coverage(
+    ui: &egui::Ui,
+    script: &str,
+    output: &egui::text_edit::TextEditOutput,
+    coverage: &LineCoverage,
+) {
+    let line_count = script.lines().count();
+    for &line in &coverage.lines {
+        if line == 0 || line > line_count {
+            continue;
+        }
+        let rect = lines_rect(script, output, &(line - 1..=line - 1));
+        let marker = egui::Rect::from_min_size(rect.min, egui::vec2(3.0, rect.height()));
+        let color = if coverage.hit.contains(&line) {
+            COVERED_LINE_COLOR
+        } else {
+            UNCOVERED_LINE_COLOR
+        };
+        ui.painter().rect_filled(marker, 0.0, color);
+    }
+}
+
+fn char_to_byte_offset(text: &str, char_index: usize) -> Option<usize> {
+    text.char_indices()
+        .map(|(offset, _)| offset)
+        .nth(char_index)
+}
@@ -0,0 +1,276 @@
+use eframe::egui;
+use egui::{Key, Modifiers, RichText, text::CCursor, text::CCursorRange};
+use egui_extras::syntax_highlighting;
+
+use super::{
+    ConsoleEntry, ExplorerApp, SnackbarKind,
+    code_view::paint_line_coverage,
+    inline_eval::{inline_eval_requested, paint_inline_annotations},
+};
+use crate::{
+    examples::Example,
+    language::symbols::{Symbol, SymbolIndex},
+    runtime,
+};
+
+const MAX_COMPLETIONS: usize = 8;
+
+/// An in-progress edit of an example script.
+pub(super) struct EditorState {
+    pub(super) example_id: String,
+    pub(super) buffer: String,
+    host_bindings: Vec<String>,
+    completion: Option<CompletionPopup>,
+}
+
+struct CompletionPopup {
+    /// Character index where the identifier being completed starts.
+    prefix_start: usize,
+    candidates: Vec<Symbol>,
+    selected: usize,
+}
+
+#[derive(Default)]
+struct CompletionKeys {
+    accept: bool,
+    step: isize,
+    close: bool,
+}
+
+impl EditorState {
+    pub(super) fn new(example: &Example) -> Self {
+        Self {
+            example_id: example.metadata.id.clone(),
+            buffer: example.script.clone(),
+            host_bindings: runtime::RUNTIME.host_binding_names().unwrap_or_default(),
+            completion: None,
+        }
+    }
+
+    pub(super) fn is_dirty(&self, example: &Example) -> bool {
+        self.buffer != example.script
+    }
+}
+
+impl ExplorerApp {
+    pub(super) fn is_editing(&self, example: &Example) -> bool {
+        self.editor
+            .as_ref()
+            .is_some_and(|editor| editor.example_id == example.metadata.id)
+    }
+
+    pub(super) fn open_editor(&mut self, example: &Example) {
+        if self.runtime_busy() {
+            return;
+        }
+        self.editor = Some(EditorState::new(example));
+    }
+
+    pub(super) fn save_editor(&mut self, example: &Example) {
+        let Some(editor) = self.editor.take() else {
+            return;
+        };
+        match std::fs::write(&example.script_path, &editor.buffer) {
+            Ok(()) => {
+                self.push_console_entry(ConsoleEntry::info(format!(
+                    "Saved '{}'",
+                    example.script_path.display()
+                )));
+                self.refresh_examples_from_library();
+            }
+            Err(error) => {
+                self.push_console_entry(ConsoleEntry::error(format!(
+                    "Failed to save '{}': {error}",
+                    example.script_path.display()
+                )));
+                self.push_snackbar("Failed to save script", SnackbarKind::Error);
+                self.editor = Some(editor);
+            }
+        }
+    }
+
+    pub(super) fn editor_ui(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        let annotations = match &self.editor {
+            Some(editor) => self.inline_annotations_for(&editor.example_id, &editor.buffer),
+            None => return,
+        };
+        let evaluate = inline_eval_requested(ui);
+        // Coverage lines refer to the saved script, so they're only shown until it's edited.
+        let coverage = self.editor.as_ref().and_then(|editor| {
+            self.examples
+                .iter()
+                .find(|example| example.metadata.id == editor.example_id)
+                .filter(|example| !editor.is_dirty(example))
+                .and_then(|example| self.example_coverage(example))
+        });
+        let Some(editor) = self.editor.as_mut() else {
+            return;
+        };
+
+        let keys = if editor.completion.is_some() {
+            ui.input_mut(|input| CompletionKeys {
+                step: if input.consume_key(Modifiers::NONE, Key::ArrowDown) {
+                    1
+                } else if input.consume_key(Modifiers::NONE, Key::ArrowUp) {
+                    -1
+                } else {
+                    0
+                },
+                accept: input.consume_key(Modifiers::NONE, Key::Enter)
+                    || input.consume_key(Modifiers::NONE, Key::Tab),
+                close: input.consume_key(Modifiers::NONE, Key::Escape),
+            })
+        } else {
+            CompletionKeys::default()
+        };
+        let requested = ui.input_mut(|input| input.consume_key(Modifiers::CTRL, Key::Space));
+
+        let theme = syntax_highlighting::CodeTheme::from_memory(ctx, ui.style());
+        let mut layouter = |ui: &egui::Ui, text: &dyn egui::TextBuffer, wrap_width: f32| {
+            let mut job =
+                syntax_highlighting::highlight(ui.ctx(), ui.style(), &theme, text.as_str(), "koto");
+            job.wrap.max_width = wrap_width;
+            ui.fonts(|fonts| fonts.layout_job(job))
+        };
+
+        let editor_id = egui::Id::new(("script_editor", editor.example_id.as_str()));
+        let mut output = egui::ScrollArea::both()
+            .id_salt("code_editor")
+            .show(ui, |ui| {
+                let output = egui::TextEdit::multiline(&mut editor.buffer)
+                    .id(editor_id)
+                    .code_editor()
+                    .desired_width(f32::INFINITY)
+                    .desired_rows(16)
+                    .layouter(&mut layouter)
+                    .show(ui);
+                paint_inline_annotations(ui, &editor.buffer, &output, &annotations);
+                if let Some(coverage) = &coverage {
+                    paint_line_coverage(ui, &editor.buffer, &output, coverage);
+                }
+                output
+            })
+            .inner;
+        theme.store_in_memory(ctx);
+
+        let evaluation = if evaluate {
+            output
+                .cursor_range
+                .map(|selection| (editor.example_id.clone(), editor.buffer.clone(), selection))
+        } else {
+            None
+        };
+
+        let Some(cursor) = output.cursor_range.map(|range| range.primary.index) else {
+            editor.completion = None;
+            return;
+        };
+
+        if keys.close {
+            editor.completion = None;
+        }
+        if keys.step != 0 {
+            if let Some(popup) = editor.completion.as_mut() {
+                let len = popup.candidates.len() as isize;
+                popup.selected = (popup.selected as isize + keys.step).rem_euclid(len) as usize;
+            }
+        }
+
+        let mut accepted = None;
+        if keys.accept {
+            accepted = editor
+                .completion
+                .as_ref()
+                .map(|popup| (popup.prefix_start, popup.selected));
+        } else if output.response.changed() || requested {
+            let prefix_start = identifier_start(&editor.buffer, cursor);
+            let prefix = char_slice(&editor.buffer, prefix_start, cursor);
+            editor.completion = if prefix.is_empty() && !requested {
+                None
+            } else {
+                let index = SymbolIndex::for_script(&editor.buffer)
+                    .with_host_bindings(editor.host_bindings.iter().cloned());
+                let candidates = index.completions(prefix, MAX_COMPLETIONS);
+                (!candidates.is_empty()).then_some(CompletionPopup {
+                    prefix_start,
+                    candidates,
+                    selected: 0,
+                })
+            };
+        } else if let Some(popup) = &editor.completion {
+            if cursor < popup.prefix_start {
+                editor.completion = None;
+            }
+        }
+
+        if let Some(popup) = &editor.completion {
+            let anchor = output.galley.pos_from_cursor(CCursor::new(cursor));
+            let position = output.galley_pos + anchor.left_bottom().to_vec2();
+            egui::Area::new(editor_id.with("completion"))
+                .order(egui::Order::Foreground)
+                .fixed_pos(position)
+                .show(ctx, |ui| {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        for (index, candidate) in popup.candidates.iter().enumerate() {
+                            let text = format!("{}  ·  {}", candidate.name, candidate.kind.label());
+                            let response = ui.selectable_label(
+                                index == popup.selected,
+                                RichText::new(text).monospace(),
+                            );
+                            if response.clicked() {
+                                accepted = Some((popup.prefix_start, index));
+                            }
+                        }
+                        ui.label(RichText::new("Enter/Tab to accept · Esc to close").small());
+                    });
+                });
+        }
+
+        if let Some((prefix_start, selected)) = accepted {
+            let completion = editor
+                .completion
+                .take()
+                .and_then(|popup| popup.candidates.into_iter().nth(selected));
+            if let Some(symbol) = completion {
+                let start = byte_offset(&editor.buffer, prefix_start);
+                let end = byte_offset(&editor.buffer, cursor);
+                editor.buffer.replace_range(start..end, &symbol.name);
+                let new_cursor = CCursor::new(prefix_start + symbol.name.chars().count());
+                output
+                    .state
+                    .cursor
+                    .set_char_range(Some(CCursorRange::one(new_cursor)));
+                output.state.store(ctx, editor_id);
+            }
+        }
+
+        if let Some((example_id, script, selection)) = evaluation {
+            self.evaluate_inline(&example_id, &script, selection);
+        }
+    }
+}
+
+/// Finds where the dotted identifier ending at `cursor` (a char index) begins.
+fn identifier_start(text: &str, cursor: usize) -> usize {
+    let chars: Vec<char> = text.chars().take(cursor).collect();
+    let mut start = chars.len();
+    while start > 0 {
+        let c = chars[start - 1];
+        if c.is_alphanumeric() || c == '_' || c == '.' {
+            start -= 1;
+        } else {
+            break;
+        }
+    }
+    start
+}
+
+fn char_slice(text: &str, start: usize, end: usize) -> &str {
+    &text[byte_offset(text, start)..byte_offset(text, end)]
+}
+
+fn byte_offset(text: &str, char_index: usize) -> usize {
+    text.char_indices()
+        .nth(char_index)
+        .map_or(text.len(), |(offset, _)| offset)
+}
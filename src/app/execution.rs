@@ -0,0 +1,268 @@
+use std::time::Duration;
+
+use eframe::egui;
+
+use super::{
+    ConsoleEntry, ConsolePane, ExecutionSummary, ExplorerApp, SnackbarKind, format_duration,
+};
+use crate::{
+    runtime::{self, ExecutionOutput},
+    tasks::{self, Task},
+};
+
+const RUNNING_REPAINT_INTERVAL: Duration = Duration::from_millis(50);
+
+/// An example run executing on a background thread.
+pub(super) struct RunningScript {
+    task: Task<ExecutionOutput>,
+    title: String,
+    /// The example being run, which owns the run's console output.
+    example_id: Option<String>,
+    /// Whether hot reload started the run; see `ExplorerApp::record_return_value`.
+    hot_reload: bool,
+    /// Length of the console when the run started, so its output can be picked out later.
+    console_start: usize,
+    /// When the run is ended if Stop can't reach it; see `ExplorerApp::stop_running_script`.
+    time_limit: Option<Duration>,
+    /// What the run was started with, saved alongside its output by "Export result".
+    context: serde_json::Value,
+}
+
+impl ExplorerApp {
+    pub(super) fn is_script_running(&self) -> bool {
+        self.running_script.is_some()
+    }
+
+    /// The example new console entries belong to: the one being run, or else the selected one.
+    pub(super) fn console_owner(&self) -> Option<String> {
+        match &self.running_script {
+            Some(running) => running.example_id.clone(),
+            None => self.selected_example_id.clone(),
+        }
+    }
+
+    /// Returns `true` (and tells the user) when a running script holds the shared runtime,
+    /// so actions that need the VM can back off instead of freezing the UI.
+    pub(super) fn runtime_busy(&mut self) -> bool {
+        if self.is_script_running() || self.is_repeating_runs() || self.is_comparing_profiles() {
+            self.push_snackbar(
+                "A script is still running; stop it or wait for it to finish",
+                SnackbarKind::Info,
+            );
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Starts a background run. When `options` name a session, the run keeps its top-level
+    /// bindings for the next run in the same session. Runs without a timeout of their own get
+    /// the time limit from Runtime settings, unless they are daemons.
+    pub(super) fn start_script_run(
+        &mut self,
+        title: String,
+        script: String,
+        mut options: runtime::RunOptions,
+    ) {
+        if options.timeout.is_none() && !options.daemon && self.run_time_limit_secs > 0 {
+            options.timeout = Some(Duration::from_secs(self.run_time_limit_secs));
+        }
+        let time_limit = options.timeout;
+        let console_start = self.console_entries.len();
+        self.active_console_pane = ConsolePane::Console;
+        self.worker_streams.clear();
+        self.canvas_frame = None;
+        self.push_console_entry(ConsoleEntry::info(format!("Running '{title}'")));
+        let context = run_context(&title, &options);
+        // The run prints its own summary, so the task doesn't log a completion event. Cancelling
+        // it, from Stop or the Tasks tab, sets the run's cancel flag.
+        let task = tasks::spawn_quietly(format!("Running '{title}'"), move |task| {
+            runtime::RUNTIME.execute(&script, &options.cancel_flag(task.cancel_flag()))
+        });
+        self.running_script = Some(RunningScript {
+            task,
+            title,
+            example_id: self.selected_example_id.clone(),
+            hot_reload: self.hot_reload_rerun,
+            console_start,
+            time_limit,
+            context,
+        });
+    }
+
+    pub(super) fn stop_running_script(&mut self) {
+        let Some(running) = &self.running_script else {
+            return;
+        };
+        if running.task.is_cancelled() {
+            return;
+        }
+        running.task.cancel();
+        running.task.set_message("Stopping at the next host call…");
+        // Koto can't be interrupted from outside, so a script only sees the request when it
+        // prints, sleeps, or otherwise calls into the host.
+        let message = match running.time_limit {
+            Some(limit) => format!(
+                "Stopping '{}' at its next host call; a loop that never makes one ends at the {} time limit",
+                running.title,
+                format_duration(limit)
+            ),
+            None => format!(
+                "Stopping '{}' at its next host call; a loop that never makes one keeps running",
+                running.title
+            ),
+        };
+        self.push_console_entry(ConsoleEntry::info(message));
+    }
+
+    /// Applies the result of the background run once it has finished.
+    pub(super) fn poll_running_script(&mut self, ctx: &egui::Context) {
+        let Some(running) = &self.running_script else {
+            return;
+        };
+        let Some(result) = running.task.poll() else {
+            ctx.request_repaint_after(RUNNING_REPAINT_INTERVAL);
+            return;
+        };
+        // Output streamed by the observer belongs to the run and comes before its summary.
+        self.poll_execution_events();
+        let Some(running) = self.running_script.take() else {
+            return;
+        };
+        let cancelled = running.task.is_cancelled();
+        self.reply_to_automation_run(&result);
+        let value = result.as_ref().ok().map(|output| output.value.clone());
+        self.finish_script_run(result, cancelled, running.context);
+        if let Some(example_id) = &running.example_id {
+            // Taken before the watches run, so anything they print isn't recorded as well.
+            self.last_recording = Some(self.output_recorder.take());
+            self.record_return_value(example_id, running.hot_reload);
+            if let Some(value) = &value {
+                self.evaluate_watches(example_id, value.clone());
+            }
+            self.check_assertion(example_id, value.is_some(), value.flatten());
+        }
+        if self.is_presenting() {
+            self.capture_presentation_output(running.console_start);
+        }
+    }
+
+    fn finish_script_run(
+        &mut self,
+        result: anyhow::Result<ExecutionOutput>,
+        cancelled: bool,
+        context: serde_json::Value,
+    ) {
+        match result {
+            Ok(output) => {
+                let mut export = output.to_json();
+                if let (Some(export), serde_json::Value::Object(context)) =
+                    (export.as_object_mut(), context)
+                {
+                    export.extend(context);
+                }
+                // Only daemon runs end successfully when stopped.
+                if cancelled {
+                    self.push_console_entry(ConsoleEntry::info(format!(
+                        "Stopped after {}",
+                        format_duration(output.duration)
+                    )));
+                }
+                if let Some(value) = &output.return_value {
+                    self.push_console_entry(ConsoleEntry::result(format!("Return value: {value}")));
+                }
+                if output.clipboard_accessed {
+                    self.push_console_entry(ConsoleEntry::info("Script accessed the clipboard"));
+                }
+                if !output.tones.is_empty()
+                    && cfg!(feature = "audio")
+                    && !runtime::RUNTIME.audio_enabled()
+                {
+                    self.push_console_entry(ConsoleEntry::info(format!(
+                        "Script played {} tones; enable Audio output in Runtime settings to hear them",
+                        output.tones.len()
+                    )));
+                }
+                if !output.generator_trace.is_empty() {
+                    self.push_console_entry(ConsoleEntry::info(format!(
+                        "Recorded {} generator events (see the Generators tab)",
+                        output.generator_trace.len()
+                    )));
+                }
+                if output.stdout.is_empty()
+                    && output.stderr.is_empty()
+                    && output.return_value.is_none()
+                {
+                    self.push_console_entry(ConsoleEntry::info("Example executed with no output"));
+                }
+
+                self.last_execution = Some(ExecutionSummary {
+                    duration: output.duration,
+                    phases: Some((output.compile_time, output.execute_time)),
+                    return_value: output.return_value,
+                    value: output.value_as_json(),
+                    succeeded: true,
+                    clipboard_accessed: output.clipboard_accessed,
+                    generator_trace: output.generator_trace,
+                    peak_memory: output.peak_memory,
+                    random_seed: output.random_seed,
+                    cache_hit: output.cache_hit,
+                    export: Some(export),
+                });
+                if cancelled {
+                    self.push_snackbar("Example stopped", SnackbarKind::Info);
+                } else {
+                    self.push_snackbar("Example executed successfully", SnackbarKind::Success);
+                }
+            }
+            Err(error) => {
+                let timeout = error.downcast_ref::<runtime::TimeoutError>();
+                self.last_execution = Some(ExecutionSummary {
+                    duration: timeout.map(|timeout| timeout.elapsed).unwrap_or_default(),
+                    phases: None,
+                    return_value: None,
+                    value: None,
+                    succeeded: false,
+                    clipboard_accessed: false,
+                    generator_trace: Vec::new(),
+                    peak_memory: None,
+                    random_seed: None,
+                    cache_hit: false,
+                    export: None,
+                });
+                if cancelled {
+                    self.push_console_entry(ConsoleEntry::info("Execution stopped"));
+                    self.push_snackbar("Example execution stopped", SnackbarKind::Info);
+                } else if let Some(timeout) = timeout {
+                    // The partial output was already streamed to the console.
+                    self.push_console_entry(ConsoleEntry::error(format!(
+                        "{timeout}; output above is what it printed before stopping"
+                    )));
+                    self.push_snackbar("Example timed out", SnackbarKind::Error);
+                } else {
+                    self.push_console_entry(ConsoleEntry::error(format!(
+                        "Execution error: {error}"
+                    )));
+                    self.push_snackbar("Example execution failed", SnackbarKind::Error);
+                }
+            }
+        }
+    }
+}
+
+/// The title, inputs, and arguments of a run, as recorded in its exported result.
+fn run_context(title: &str, options: &runtime::RunOptions) -> serde_json::Value {
+    let inputs = options
+        .bindings
+        .iter()
+        .find_map(|(name, binding)| match (name.as_str(), binding) {
+            ("input", runtime::RunBinding::Value(inputs)) => Some(inputs.clone()),
+            _ => None,
+        })
+        .unwrap_or_else(|| serde_json::json!({}));
+    serde_json::json!({
+        "title": title,
+        "inputs": inputs,
+        "args": options.args,
+    })
+}
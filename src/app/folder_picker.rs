@@ -0,0 +1,322 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use eframe::egui;
+use egui::RichText;
+
+use super::{ConsoleEntry, ExplorerApp, SnackbarKind};
+
+/// A minimal in-app directory browser used to point the explorer at an examples folder.
+pub(super) struct FolderPicker {
+    current: PathBuf,
+    path_input: String,
+    entries: Vec<PathBuf>,
+    error: Option<String>,
+}
+
+enum FolderPickerAction {
+    None,
+    Cancel,
+    Choose(PathBuf),
+}
+
+impl FolderPicker {
+    pub(super) fn new(start: PathBuf) -> Self {
+        // The configured directory may be the one that went missing, so start from the nearest
+        // ancestor that still exists.
+        let start = start
+            .ancestors()
+            .find(|path| path.is_dir())
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_else(|| PathBuf::from("."));
+        let mut picker = Self {
+            current: PathBuf::new(),
+            path_input: String::new(),
+            entries: Vec::new(),
+            error: None,
+        };
+        picker.navigate(start);
+        picker
+    }
+
+    fn navigate(&mut self, path: PathBuf) {
+        match read_subdirectories(&path) {
+            Ok(entries) => {
+                self.path_input = path.display().to_string();
+                self.current = path;
+                self.entries = entries;
+                self.error = None;
+            }
+            Err(error) => self.error = Some(format!("Can't open {}: {error}", path.display())),
+        }
+    }
+
+    fn ui(&mut self, ctx: &egui::Context) -> FolderPickerAction {
+        let mut action = FolderPickerAction::None;
+        let mut open = true;
+        egui::Window::new("Choose examples directory")
+            .open(&mut open)
+            .collapsible(false)
+            .default_size([420.0, 360.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let parent = self.current.parent().map(Path::to_path_buf);
+                    if ui
+                        .add_enabled(parent.is_some(), egui::Button::new("⬆ Up"))
+                        .clicked()
+                    {
+                        if let Some(parent) = parent {
+                            self.navigate(parent);
+                        }
+                    }
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.path_input)
+                            .desired_width(f32::INFINITY),
+                    );
+                    if response.lost_focus()
+                        && ui.input(|input| input.key_pressed(egui::Key::Enter))
+                    {
+                        self.navigate(PathBuf::from(self.path_input.trim()));
+                    }
+                });
+                if let Some(error) = &self.error {
+                    ui.colored_label(egui::Color32::from_rgb(240, 140, 140), error);
+                }
+                ui.separator();
+                let mut target = None;
+                egui::ScrollArea::vertical()
+                    .max_height(240.0)
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        if self.entries.is_empty() {
+                            ui.label(RichText::new("No subfolders").italics());
+                        }
+                        for entry in &self.entries {
+                            let name = entry
+                                .file_name()
+                                .map(|name| name.to_string_lossy().into_owned())
+                                .unwrap_or_else(|| entry.display().to_string());
+                            if ui.selectable_label(false, format!("📁 {name}")).clicked() {
+                                target = Some(entry.clone());
+                            }
+                        }
+                    });
+                if let Some(target) = target {
+                    self.navigate(target);
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Use this folder").clicked() {
+                        action = FolderPickerAction::Choose(self.current.clone());
+                    }
+                    if ui.button("Cancel").clicked() {
+                        action = FolderPickerAction::Cancel;
+                    }
+                });
+            });
+        if !open {
+            action = FolderPickerAction::Cancel;
+        }
+        action
+    }
+}
+
+fn read_subdirectories(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(path)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    entries.sort();
+    Ok(entries)
+}
+
+impl ExplorerApp {
+    pub(super) fn open_examples_folder_picker(&mut self) {
+        let start = self
+            .example_library
+            .and_then(|library| library.examples_dir())
+            .unwrap_or_else(|| PathBuf::from("."));
+        self.folder_picker = Some(FolderPicker::new(start));
+    }
+
+    pub(super) fn folder_picker_ui(&mut self, ctx: &egui::Context) {
+        let Some(picker) = &mut self.folder_picker else {
+            return;
+        };
+        match picker.ui(ctx) {
+            FolderPickerAction::None => {}
+            FolderPickerAction::Cancel => self.folder_picker = None,
+            FolderPickerAction::Choose(path) => {
+                if self.switch_examples_dir(path) {
+                    self.folder_picker = None;
+                }
+            }
+        }
+    }
+
+    /// Points the library at `path` and reloads the catalog. Returns whether the switch worked.
+    pub(super) fn switch_examples_dir(&mut self, path: PathBuf) -> bool {
+        let Some(library) = self.example_library else {
+            self.push_snackbar("The example library isn't available", SnackbarKind::Error);
+            return false;
+        };
+        if self.runtime_busy() {
+            return false;
+        }
+        let has_unsaved_edits = self.editor.as_ref().is_some_and(|editor| {
+            self.examples
+                .iter()
+                .find(|example| example.metadata.id == editor.example_id)
+                .is_some_and(|example| editor.is_dirty(example))
+        });
+        if has_unsaved_edits {
+            self.push_snackbar(
+                "Save or discard your script edits before switching folders",
+                SnackbarKind::Info,
+            );
+            return false;
+        }
+        if let Err(error) = library.set_examples_dir(path.clone()) {
+            self.push_console_entry(ConsoleEntry::error(format!(
+                "Failed to open examples directory: {error}"
+            )));
+            self.push_snackbar("Failed to open examples directory", SnackbarKind::Error);
+            return false;
+        }
+        // Results and sessions belong to the previous catalog, whose ids may be reused.
+        self.test_runs.clear();
+        self.inline_annotations.clear();
+        self.notebook_sessions.clear();
+        self.hot_reload_notices.clear();
+        self.editor = None;
+        self.pending_hot_reload_run = None;
+        self.category_filters.clear();
+        self.examples = library.snapshot();
+        self.examples_version = library.version();
+        self.selected_example_id = None;
+        self.selected_category = None;
+        self.on_examples_changed(false);
+        self.push_console_entry(ConsoleEntry::info(format!(
+            "Loaded examples from {}",
+            path.display()
+        )));
+        self.remember_current_catalog();
+        self.push_snackbar(
+            format!("Loaded {} examples", self.examples.len()),
+            SnackbarKind::Success,
+        );
+        true
+    }
+
+    pub(super) fn remember_current_catalog(&mut self) {
+        let Some(dir) = self
+            .example_library
+            .and_then(|library| library.examples_dir())
+        else {
+            return;
+        };
+        if let Err(error) = self.recent_catalogs.record(&dir) {
+            log::warn!("Failed to save recent catalogs: {error}");
+        }
+    }
+
+    /// Lists recently opened catalogs other than the current one; clicking one switches to it.
+    pub(super) fn recent_catalogs_ui(&mut self, ui: &mut egui::Ui) {
+        let current = self
+            .example_library
+            .and_then(|library| library.examples_dir())
+            .map(|dir| dir.canonicalize().unwrap_or(dir));
+        let mut open = None;
+        let mut forget = None;
+        for entry in self.recent_catalogs.entries() {
+            if Some(entry) == current.as_ref() {
+                continue;
+            }
+            let name = entry
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| entry.display().to_string());
+            ui.horizontal(|ui| {
+                let exists = entry.is_dir();
+                let response = ui
+                    .add_enabled(exists, egui::Button::new(format!("📁 {name}")).frame(false))
+                    .on_hover_text(entry.display().to_string())
+                    .on_disabled_hover_text(format!("{} no longer exists", entry.display()));
+                if response.clicked() {
+                    open = Some(entry.clone());
+                }
+                if ui
+                    .small_button("✖")
+                    .on_hover_text("Remove from recent catalogs")
+                    .clicked()
+                {
+                    forget = Some(entry.clone());
+                }
+            });
+        }
+        if let Some(entry) = forget {
+            if let Err(error) = self.recent_catalogs.remove(&entry) {
+                log::warn!("Failed to save recent catalogs: {error}");
+            }
+        }
+        if let Some(entry) = open {
+            self.switch_examples_dir(entry);
+        }
+    }
+
+    fn add_starter_examples(&mut self) {
+        let Some(library) = self.example_library else {
+            return;
+        };
+        match library.init_samples() {
+            Ok(written) => {
+                self.push_console_entry(ConsoleEntry::info(format!(
+                    "Added {} starter examples",
+                    written.len()
+                )));
+                self.push_snackbar("Starter examples added", SnackbarKind::Success);
+            }
+            Err(error) => {
+                self.push_console_entry(ConsoleEntry::error(format!(
+                    "Failed to add starter examples: {error:#}"
+                )));
+                self.push_snackbar("Failed to add starter examples", SnackbarKind::Error);
+            }
+        }
+    }
+
+    /// Shown instead of an example when the current catalog is empty.
+    pub(super) fn welcome_ui(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Welcome to the Koto Learning Explorer");
+        ui.label("This folder has no examples yet. Open a course catalog or your own workspace.");
+        ui.add_space(8.0);
+        if ui
+            .add_enabled(
+                self.example_library.is_some(),
+                egui::Button::new("Add starter examples"),
+            )
+            .on_hover_text("Hello world, collections, iterators, errors, tests, and timing")
+            .clicked()
+        {
+            self.add_starter_examples();
+        }
+        if ui
+            .add_enabled(
+                self.example_library.is_some(),
+                egui::Button::new("Open examples folder…"),
+            )
+            .clicked()
+        {
+            self.open_examples_folder_picker();
+        }
+        if self.recent_catalogs.entries().len() > 1 {
+            ui.add_space(8.0);
+            ui.label(RichText::new("Recent catalogs").strong());
+            self.recent_catalogs_ui(ui);
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use eframe::egui;
+use egui::{Color32, CornerRadius, RichText};
+
+use super::ExplorerApp;
+use crate::runtime::generators::{GeneratorEvent, GeneratorEventKind};
+
+impl ExplorerApp {
+    pub(super) fn generators_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(summary) = &self.last_execution else {
+            ui.label("Run an example to record generator activity.");
+            return;
+        };
+
+        if summary.generator_trace.is_empty() {
+            ui.label("No traced generators in the last run.");
+            ui.label(
+                RichText::new("Wrap an iterator with host.trace('name', iterable) to record it.")
+                    .small(),
+            );
+            return;
+        }
+
+        egui::ScrollArea::vertical()
+            .id_salt("generator_trace")
+            .show(ui, |ui| {
+                for (label, events) in group_by_label(&summary.generator_trace) {
+                    let yields = events
+                        .iter()
+                        .filter(|event| matches!(event.kind, GeneratorEventKind::Yielded(_)))
+                        .count();
+                    ui.label(RichText::new(format!("{label} — {yields} values")).strong());
+                    ui.horizontal_wrapped(|ui| {
+                        for event in events {
+                            generator_event_chip(ui, event);
+                        }
+                    });
+                    ui.separator();
+                }
+            });
+    }
+}
+
+fn group_by_label(trace: &[GeneratorEvent]) -> Vec<(String, Vec<&GeneratorEvent>)> {
+    let mut groups: Vec<(String, Vec<&GeneratorEvent>)> = Vec::new();
+    for event in trace {
+        match groups.iter_mut().find(|(label, _)| label == &event.label) {
+            Some((_, events)) => events.push(event),
+            None => groups.push((event.label.clone(), vec![event])),
+        }
+    }
+    groups
+}
+
+fn generator_event_chip(ui: &mut egui::Ui, event: &GeneratorEvent) {
+    let offset = format!("{:.2} ms", event.offset.as_secs_f64() * 1000.0);
+    match &event.kind {
+        GeneratorEventKind::Resumed => {
+            ui.label(RichText::new("▶").weak())
+                .on_hover_text(format!("Resumed at {offset}"));
+        }
+        GeneratorEventKind::Yielded(value) => {
+            egui::Frame::new()
+                .fill(Color32::from_rgb(120, 180, 240).gamma_multiply(0.2))
+                .corner_radius(CornerRadius::same(4))
+                .inner_margin(egui::Margin::symmetric(6, 2))
+                .show(ui, |ui| {
+                    ui.monospace(value);
+                })
+                .response
+                .on_hover_text(format!("Yielded at {offset}"));
+        }
+        GeneratorEventKind::Finished => {
+            ui.label(RichText::new("■ done").color(Color32::from_rgb(120, 200, 120)))
+                .on_hover_text(format!("Finished at {offset}"));
+        }
+        GeneratorEventKind::Failed(error) => {
+            ui.label(RichText::new("✖ error").color(Color32::from_rgb(220, 100, 100)))
+                .on_hover_text(error);
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use eframe::egui;
+use egui::RichText;
+
+use super::{ConsoleEntry, ExplorerApp};
+use crate::examples::glossary::{Glossary, TextSpan};
+
+/// The Concepts panel: the catalog's glossary, searchable, with links to the examples that
+/// show each term.
+#[derive(Default)]
+pub(super) struct ConceptsPanel {
+    filter: String,
+    /// The term whose definition is shown.
+    selected: Option<String>,
+}
+
+impl ExplorerApp {
+    /// Reads the glossary of the current catalog, keeping no terms if it can't be loaded.
+    pub(super) fn reload_glossary(&mut self) {
+        let Some(dir) = self
+            .example_library
+            .and_then(|library| library.examples_dir())
+        else {
+            self.glossary = Glossary::default();
+            return;
+        };
+        match Glossary::load(&dir) {
+            Ok(glossary) => self.glossary = glossary,
+            Err(error) => {
+                self.glossary = Glossary::default();
+                self.push_console_entry(ConsoleEntry::error(format!(
+                    "Failed to load the glossary: {error:#}"
+                )));
+            }
+        }
+    }
+
+    /// Opens the Concepts panel, showing `term` when given.
+    pub(super) fn open_concepts(&mut self, term: Option<&str>) {
+        let concepts = self.concepts.get_or_insert_with(ConceptsPanel::default);
+        if let Some(term) = term.and_then(|name| self.glossary.get(name)) {
+            concepts.selected = Some(term.term.clone());
+        }
+    }
+
+    /// Shows `text` with mentions of glossary terms as links that open them in the Concepts
+    /// panel, and their definitions on hover.
+    pub(super) fn glossary_text_ui(&mut self, ui: &mut egui::Ui, text: &str) {
+        let spans = self.glossary.link_terms(text);
+        if !spans
+            .iter()
+            .any(|span| matches!(span, TextSpan::Term { .. }))
+        {
+            ui.label(text);
+            return;
+        }
+        let mut clicked = None;
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing.x = 0.0;
+            for span in spans {
+                match span {
+                    TextSpan::Plain(text) => {
+                        ui.label(text);
+                    }
+                    TextSpan::Term { text, term } => {
+                        if ui.link(text).on_hover_text(&term.definition).clicked() {
+                            clicked = Some(term.term.clone());
+                        }
+                    }
+                }
+            }
+        });
+        if let Some(term) = clicked {
+            self.open_concepts(Some(&term));
+        }
+    }
+
+    /// Links to the glossary terms that list `example_id` among their examples.
+    pub(super) fn example_concepts_ui(&mut self, ui: &mut egui::Ui, example_id: &str) {
+        let terms = self.glossary.terms_for_example(example_id);
+        if terms.is_empty() {
+            return;
+        }
+        let mut clicked = None;
+        ui.add_space(6.0);
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Concepts:");
+            for term in terms {
+                if ui
+                    .link(&term.term)
+                    .on_hover_text(&term.definition)
+                    .clicked()
+                {
+                    clicked = Some(term.term.clone());
+                }
+            }
+        });
+        if let Some(term) = clicked {
+            self.open_concepts(Some(&term));
+        }
+    }
+
+    pub(super) fn concepts_ui(&mut self, ctx: &egui::Context) {
+        let Some(concepts) = &mut self.concepts else {
+            return;
+        };
+        let mut open = true;
+        let mut open_example = None;
+        egui::Window::new("Concepts")
+            .open(&mut open)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                if self.glossary.is_empty() {
+                    ui.label("This catalog has no glossary yet. Add terms to glossary.json:");
+                    ui.monospace(
+                        r#"{ "terms": [{ "term": "closure", "definition": "...", "examples": ["basics"] }] }"#,
+                    );
+                    return;
+                }
+                ui.add(
+                    egui::TextEdit::singleline(&mut concepts.filter)
+                        .hint_text("Search terms")
+                        .desired_width(f32::INFINITY),
+                );
+                ui.separator();
+                let filter = concepts.filter.trim().to_lowercase();
+                ui.columns(2, |columns| {
+                    egui::ScrollArea::vertical()
+                        .id_salt("concepts_terms")
+                        .max_height(360.0)
+                        .show(&mut columns[0], |ui| {
+                            for term in self.glossary.sorted() {
+                                let matches = filter.is_empty()
+                                    || term.term.to_lowercase().contains(&filter)
+                                    || term.definition.to_lowercase().contains(&filter);
+                                if !matches {
+                                    continue;
+                                }
+                                let selected = concepts.selected.as_ref() == Some(&term.term);
+                                if ui.selectable_label(selected, &term.term).clicked() {
+                                    concepts.selected = Some(term.term.clone());
+                                }
+                            }
+                        });
+
+                    let ui = &mut columns[1];
+                    let Some(term) = concepts
+                        .selected
+                        .as_deref()
+                        .and_then(|name| self.glossary.get(name))
+                    else {
+                        ui.label(RichText::new("Pick a term to see what it means").weak());
+                        return;
+                    };
+                    ui.label(RichText::new(&term.term).strong());
+                    if !term.aliases.is_empty() {
+                        ui.label(
+                            RichText::new(format!("Also: {}", term.aliases.join(", ")))
+                                .small()
+                                .weak(),
+                        );
+                    }
+                    ui.add_space(4.0);
+                    ui.label(&term.definition);
+                    if term.examples.is_empty() {
+                        return;
+                    }
+                    ui.add_space(8.0);
+                    ui.label(RichText::new("Examples").strong());
+                    for id in &term.examples {
+                        match self.examples.iter().find(|example| &example.metadata.id == id) {
+                            Some(example) => {
+                                if ui.link(&example.metadata.title).clicked() {
+                                    open_example = Some(id.clone());
+                                }
+                            }
+                            None => {
+                                ui.label(RichText::new(format!("{id} (not in this catalog)")).weak());
+                            }
+                        }
+                    }
+                });
+            });
+        if let Some(id) = open_example {
+            self.select_example(&id);
+        }
+        if !open {
+            self.concepts = None;
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use eframe::egui;
+use egui::{Color32, Key, Modifiers, text::CCursor, text::CCursorRange, text_edit::TextEditOutput};
+
+use super::{ConsoleEntry, ExplorerApp};
+use crate::runtime;
+
+const MAX_ANNOTATION_CHARS: usize = 80;
+
+/// The result of an inline evaluation, drawn as ghost text after the line it belongs to.
+#[derive(Clone)]
+pub(super) struct InlineAnnotation {
+    /// Zero-based line the annotation is attached to.
+    line: usize,
+    /// The line's contents when it was evaluated; the annotation is hidden once it changes.
+    line_text: String,
+    text: String,
+    is_error: bool,
+}
+
+/// Consumes the inline evaluation shortcut (Ctrl+Enter) before a text edit sees it.
+pub(super) fn inline_eval_requested(ui: &egui::Ui) -> bool {
+    ui.input_mut(|input| input.consume_key(Modifiers::COMMAND, Key::Enter))
+}
+
+impl ExplorerApp {
+    /// Evaluates the selection, or the cursor's line when nothing is selected, in the shared
+    /// runtime and records the result against the last selected line.
+    pub(super) fn evaluate_inline(
+        &mut self,
+        example_id: &str,
+        script: &str,
+        selection: CCursorRange,
+    ) {
+        if self.runtime_busy() {
+            return;
+        }
+        let [start, end] = selection.sorted_cursors();
+        let (source, line) = if start.index == end.index {
+            let line = line_of_char(script, start.index);
+            (
+                script.lines().nth(line).unwrap_or_default().to_string(),
+                line,
+            )
+        } else {
+            let source: String = script
+                .chars()
+                .skip(start.index)
+                .take(end.index - start.index)
+                .collect();
+            (source, line_of_char(script, end.index.saturating_sub(1)))
+        };
+        if source.trim().is_empty() {
+            return;
+        }
+        let line_text = script.lines().nth(line).unwrap_or_default().to_string();
+
+        let annotation =
+            match runtime::RUNTIME.execute_in_session(&inline_session_id(example_id), &source) {
+                Ok(output) => {
+                    let mut text = output.return_value.unwrap_or_else(|| "null".to_string());
+                    let printed = output.stdout.trim_end();
+                    if !printed.is_empty() {
+                        text = format!("{text}  ⟨{}⟩", printed.replace('\n', " ⏎ "));
+                    }
+                    InlineAnnotation {
+                        line,
+                        line_text,
+                        text,
+                        is_error: false,
+                    }
+                }
+                Err(error) => InlineAnnotation {
+                    line,
+                    line_text,
+                    text: error
+                        .to_string()
+                        .lines()
+                        .next()
+                        .unwrap_or_default()
+                        .to_string(),
+                    is_error: true,
+                },
+            };
+
+        let annotations = self
+            .inline_annotations
+            .entry(example_id.to_string())
+            .or_default();
+        annotations.retain(|existing| existing.line != line);
+        annotations.push(annotation);
+    }
+
+    /// Returns the annotations whose lines are unchanged in `script`.
+    pub(super) fn inline_annotations_for(
+        &self,
+        example_id: &str,
+        script: &str,
+    ) -> Vec<InlineAnnotation> {
+        self.inline_annotations
+            .get(example_id)
+            .map(|annotations| {
+                annotations
+                    .iter()
+                    .filter(|annotation| {
+                        script.lines().nth(annotation.line) == Some(annotation.line_text.as_str())
+                    })
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Drops the annotations together with the bindings the evaluations built up.
+    pub(super) fn clear_inline_annotations(&mut self, example_id: &str) {
+        self.inline_annotations.remove(example_id);
+        if let Err(error) = runtime::RUNTIME.reset_session(&inline_session_id(example_id)) {
+            self.push_console_entry(ConsoleEntry::error(format!(
+                "Failed to reset the session: {error}"
+            )));
+        }
+    }
+}
+
+/// Inline evaluations of one example share a session, so later lines see earlier bindings.
+fn inline_session_id(example_id: &str) -> String {
+    format!("inline:{example_id}")
+}
+
+/// Paints annotations as faint text after the end of their lines.
+pub(super) fn paint_inline_annotations(
+    ui: &egui::Ui,
+    script: &str,
+    output: &TextEditOutput,
+    annotations: &[InlineAnnotation],
+) {
+    let font = egui::TextStyle::Monospace.resolve(ui.style());
+    for annotation in annotations {
+        let Some(line_end) = line_end_char(script, annotation.line) else {
+            continue;
+        };
+        let rect = output.galley.pos_from_cursor(CCursor::new(line_end));
+        let position = output.galley_pos + rect.right_top().to_vec2() + egui::vec2(16.0, 0.0);
+        let color = if annotation.is_error {
+            Color32::from_rgb(220, 80, 80).gamma_multiply(0.8)
+        } else {
+            ui.visuals().weak_text_color()
+        };
+        ui.painter().text(
+            position,
+            egui::Align2::LEFT_TOP,
+            format!("⇒ {}", truncate(&annotation.text)),
+            font.clone(),
+            color,
+        );
+    }
+}
+
+fn line_of_char(text: &str, char_index: usize) -> usize {
+    text.chars().take(char_index).filter(|c| *c == '\n').count()
+}
+
+fn line_end_char(text: &str, line: usize) -> Option<usize> {
+    let mut char_index = 0;
+    for (index, line_text) in text.split('\n').enumerate() {
+        char_index += line_text.chars().count();
+        if index == line {
+            return Some(char_index);
+        }
+        char_index += 1;
+    }
+    None
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_ANNOTATION_CHARS {
+        text.to_string()
+    } else {
+        let mut truncated: String = text.chars().take(MAX_ANNOTATION_CHARS).collect();
+        truncated.push('…');
+        truncated
+    }
+}
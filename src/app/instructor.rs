@@ -0,0 +1,183 @@
+use std::{
+    fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use eframe::egui;
+use egui::{Color32, RichText};
+use egui_extras::syntax_highlighting;
+
+use super::{ConsoleEntry, ExplorerApp, SnackbarKind};
+use crate::examples::{Example, ExampleMetadata, SOLUTION_FILE_NAME};
+
+/// An open edit of an example's `meta.json`.
+pub(super) struct MetadataEditor {
+    title: String,
+    path: PathBuf,
+    buffer: String,
+    /// Why the buffer can't be saved, checked whenever it changes.
+    error: Option<String>,
+}
+
+impl ExplorerApp {
+    /// Instructor mode unlocks the authoring tools; turning it off closes any that are open.
+    pub(super) fn set_instructor_mode(&mut self, enabled: bool) {
+        self.instructor_mode = enabled;
+        if !enabled {
+            self.metadata_editor = None;
+            self.new_example_title = None;
+        }
+    }
+
+    /// Authoring tools shown above an example's code in instructor mode: the metadata
+    /// editor and the example's reference solution.
+    pub(super) fn instructor_tools_ui(&mut self, ui: &mut egui::Ui, example: &Example) {
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("Instructor").strong());
+                if ui.button("Edit metadata").clicked() {
+                    self.metadata_editor = Some(MetadataEditor {
+                        title: example.metadata.title.clone(),
+                        path: example.metadata_path(),
+                        buffer: example.metadata_source.clone(),
+                        error: None,
+                    });
+                }
+                if example.solution.is_none()
+                    && ui
+                        .button("Save script as solution")
+                        .on_hover_text(format!(
+                            "Copy the current script to {SOLUTION_FILE_NAME}; student packs leave it out"
+                        ))
+                        .clicked()
+                {
+                    self.create_solution(example);
+                }
+            });
+            if let Some(path) = &example.solution {
+                egui::CollapsingHeader::new("Solution")
+                    .default_open(false)
+                    .show(ui, |ui| match fs::read_to_string(path) {
+                        Ok(solution) => {
+                            let theme =
+                                syntax_highlighting::CodeTheme::from_memory(ui.ctx(), ui.style());
+                            syntax_highlighting::code_view_ui(
+                                ui,
+                                &theme,
+                                solution.trim_end(),
+                                "koto",
+                            );
+                        }
+                        Err(error) => {
+                            ui.colored_label(
+                                Color32::from_rgb(220, 100, 100),
+                                format!("Failed to read {}: {error}", path.display()),
+                            );
+                        }
+                    });
+            }
+        });
+    }
+
+    fn create_solution(&mut self, example: &Example) {
+        let path = example.script_path.with_file_name(SOLUTION_FILE_NAME);
+        match fs::write(&path, example.source()) {
+            Ok(()) => {
+                self.push_console_entry(ConsoleEntry::info(format!(
+                    "Saved the solution to {}",
+                    path.display()
+                )));
+                self.refresh_examples_from_library();
+            }
+            Err(error) => {
+                self.push_console_entry(ConsoleEntry::error(format!(
+                    "Failed to write {}: {error}",
+                    path.display()
+                )));
+                self.push_snackbar("Failed to save the solution", SnackbarKind::Error);
+            }
+        }
+    }
+
+    /// Edits `meta.json` as text, refusing to save until it parses as example metadata.
+    pub(super) fn metadata_editor_ui(&mut self, ctx: &egui::Context) {
+        let Some(editor) = &mut self.metadata_editor else {
+            return;
+        };
+        let mut open = true;
+        let mut save = false;
+        let mut cancel = false;
+        egui::Window::new(format!("Metadata: {}", editor.title))
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical()
+                    .id_salt("metadata_editor")
+                    .max_height(360.0)
+                    .show(ui, |ui| {
+                        let response = ui.add(
+                            egui::TextEdit::multiline(&mut editor.buffer)
+                                .code_editor()
+                                .desired_rows(16)
+                                .desired_width(f32::INFINITY),
+                        );
+                        if response.changed() {
+                            editor.error = serde_json::from_str::<ExampleMetadata>(&editor.buffer)
+                                .err()
+                                .map(|error| error.to_string());
+                        }
+                    });
+                if let Some(error) = &editor.error {
+                    ui.colored_label(Color32::from_rgb(220, 100, 100), error);
+                }
+                ui.horizontal(|ui| {
+                    save = ui
+                        .add_enabled(editor.error.is_none(), egui::Button::new("Save"))
+                        .clicked();
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+        if save {
+            let result = fs::write(&editor.path, &editor.buffer);
+            let path = editor.path.display().to_string();
+            match result {
+                Ok(()) => {
+                    self.metadata_editor = None;
+                    self.push_console_entry(ConsoleEntry::info(format!("Saved '{path}'")));
+                    self.refresh_examples_from_library();
+                }
+                Err(error) => {
+                    self.push_console_entry(ConsoleEntry::error(format!(
+                        "Failed to save '{path}': {error}"
+                    )));
+                    self.push_snackbar("Failed to save metadata", SnackbarKind::Error);
+                }
+            }
+        } else if !open || cancel {
+            self.metadata_editor = None;
+        }
+    }
+
+    /// Zips the catalog without solutions into `exports/student_pack_<timestamp>.zip`.
+    pub(super) fn export_student_pack(&mut self) {
+        let Some(library) = self.example_library else {
+            return;
+        };
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        let path = PathBuf::from("exports").join(format!("student_pack_{timestamp}.zip"));
+        self.export_in_background("the student pack", move || {
+            let report = library.export_student_pack(&path)?;
+            Ok(format!(
+                "Exported {} examples to {} ({} solutions left out)",
+                report.examples,
+                path.display(),
+                report.solutions_removed
+            ))
+        });
+    }
+}
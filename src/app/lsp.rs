@@ -0,0 +1,332 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use eframe::egui;
+use egui::{Color32, RichText};
+
+use super::{ConsoleEntry, ExplorerApp, SnackbarKind};
+use crate::language::lsp::{LspClient, LspDiagnostic, LspLocation, LspResponse, LspSeverity};
+
+const RESPONSE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A connected language server plus the requests the code view is waiting on.
+pub(super) struct LspSession {
+    client: LspClient,
+    /// The document (path and text) most recently sent to the server.
+    synced: Option<(PathBuf, String)>,
+    hover: Option<HoverState>,
+    definition_request: Option<u64>,
+    definition: Option<LspLocation>,
+}
+
+struct HoverState {
+    path: PathBuf,
+    line: u32,
+    character: u32,
+    request: Option<u64>,
+    text: Option<String>,
+}
+
+impl LspSession {
+    fn has_pending_requests(&self) -> bool {
+        self.definition_request.is_some()
+            || self
+                .hover
+                .as_ref()
+                .is_some_and(|hover| hover.request.is_some())
+    }
+}
+
+impl ExplorerApp {
+    pub(super) fn connect_language_server(&mut self) {
+        self.disconnect_language_server();
+        let command = self.lsp_command.trim().to_string();
+        if command.is_empty() {
+            return;
+        }
+
+        let root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        match LspClient::spawn(&command, &root) {
+            Ok(client) => {
+                self.push_console_entry(ConsoleEntry::info(format!(
+                    "Connected to language server '{command}'"
+                )));
+                self.lsp = Some(LspSession {
+                    client,
+                    synced: None,
+                    hover: None,
+                    definition_request: None,
+                    definition: None,
+                });
+            }
+            Err(error) => {
+                self.push_console_entry(ConsoleEntry::error(format!(
+                    "{error:#}; using built-in symbol information instead"
+                )));
+                self.push_snackbar("Language server unavailable", SnackbarKind::Error);
+            }
+        }
+    }
+
+    pub(super) fn disconnect_language_server(&mut self) {
+        if let Some(session) = self.lsp.take() {
+            session.client.shutdown();
+        }
+    }
+
+    /// Applies server messages and resolves pending hover and definition requests.
+    pub(super) fn poll_language_server(&mut self, ctx: &egui::Context) {
+        let Some(session) = self.lsp.as_mut() else {
+            return;
+        };
+
+        if !session.client.is_running() {
+            let command = session.client.command().to_string();
+            self.lsp = None;
+            self.push_console_entry(ConsoleEntry::error(format!(
+                "Language server '{command}' exited; using built-in symbol information instead"
+            )));
+            return;
+        }
+
+        session.client.poll();
+
+        if let Some(hover) = session.hover.as_mut() {
+            if let Some(response) = hover
+                .request
+                .and_then(|id| session.client.take_response(id))
+            {
+                hover.request = None;
+                hover.text = match response {
+                    LspResponse::Hover(text) => text,
+                    _ => None,
+                };
+            }
+        }
+
+        let mut notice = None;
+        if let Some(response) = session
+            .definition_request
+            .and_then(|id| session.client.take_response(id))
+        {
+            session.definition_request = None;
+            match response {
+                LspResponse::Definition(locations) => match locations.into_iter().next() {
+                    Some(location) => {
+                        notice = Some(ConsoleEntry::info(format!(
+                            "Definition: {}",
+                            location_label(&location)
+                        )));
+                        session.definition = Some(location);
+                    }
+                    None => notice = Some(ConsoleEntry::info("No definition found")),
+                },
+                LspResponse::Error(message) => {
+                    notice = Some(ConsoleEntry::error(format!(
+                        "Go to definition failed: {message}"
+                    )));
+                }
+                LspResponse::Hover(_) => {}
+            }
+        }
+
+        if session.has_pending_requests() {
+            ctx.request_repaint_after(RESPONSE_POLL_INTERVAL);
+        }
+        if let Some(entry) = notice {
+            self.push_console_entry(entry);
+        }
+    }
+
+    /// Sends the displayed script to the server when it differs from what was last synced.
+    pub(super) fn sync_language_server_document(&mut self, path: &Path, text: &str) {
+        let Some(session) = self.lsp.as_mut() else {
+            return;
+        };
+        let current = session
+            .synced
+            .as_ref()
+            .is_some_and(|(synced_path, synced_text)| synced_path == path && synced_text == text);
+        if current {
+            return;
+        }
+        if session.client.sync_document(path, text).is_ok() {
+            session.synced = Some((path.to_path_buf(), text.to_string()));
+            session.definition = None;
+        }
+    }
+
+    pub(super) fn language_server_diagnostics(&self, path: &Path) -> Vec<LspDiagnostic> {
+        self.lsp
+            .as_ref()
+            .map(|session| session.client.diagnostics(path).to_vec())
+            .unwrap_or_default()
+    }
+
+    /// Returns the server's hover text for a position, requesting it when the position changes.
+    pub(super) fn language_server_hover(
+        &mut self,
+        path: &Path,
+        line: u32,
+        character: u32,
+    ) -> Option<String> {
+        let session = self.lsp.as_mut()?;
+        if !session.client.is_initialized() {
+            return None;
+        }
+
+        let same_position = session.hover.as_ref().is_some_and(|hover| {
+            hover.path == path && hover.line == line && hover.character == character
+        });
+        if !same_position {
+            let request = session.client.request_hover(path, line, character).ok();
+            session.hover = Some(HoverState {
+                path: path.to_path_buf(),
+                line,
+                character,
+                request,
+                text: None,
+            });
+        }
+        session.hover.as_ref().and_then(|hover| hover.text.clone())
+    }
+
+    /// Asks the server for the definition at a position; returns `false` without a server.
+    pub(super) fn request_language_server_definition(
+        &mut self,
+        path: &Path,
+        line: u32,
+        character: u32,
+    ) -> bool {
+        let Some(session) = self.lsp.as_mut() else {
+            return false;
+        };
+        if !session.client.is_initialized() {
+            return false;
+        }
+        session.definition_request = session
+            .client
+            .request_definition(path, line, character)
+            .ok();
+        session.definition_request.is_some()
+    }
+
+    pub(super) fn language_server_status_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label("Language server command");
+        ui.add(egui::TextEdit::singleline(&mut self.lsp_command).hint_text("koto-ls"));
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !self.lsp_command.trim().is_empty(),
+                    egui::Button::new("Connect"),
+                )
+                .clicked()
+            {
+                self.connect_language_server();
+            }
+            if self.lsp.is_some() && ui.button("Disconnect").clicked() {
+                self.disconnect_language_server();
+                self.push_console_entry(ConsoleEntry::info("Language server disconnected"));
+            }
+        });
+        let status = match &self.lsp {
+            Some(session) if session.client.is_initialized() => {
+                format!("Connected to {}", session.client.command())
+            }
+            Some(_) => "Starting…".to_string(),
+            None => "Not connected; using built-in symbol information".to_string(),
+        };
+        ui.label(RichText::new(status).small());
+    }
+
+    /// Lists the server's diagnostics and the last go-to-definition result for a script.
+    pub(super) fn language_server_results_ui(&mut self, ui: &mut egui::Ui, path: &Path) {
+        let diagnostics = self.language_server_diagnostics(path);
+        for diagnostic in &diagnostics {
+            ui.label(
+                RichText::new(format!(
+                    "{} line {}:{} {}",
+                    severity_icon(diagnostic.severity),
+                    diagnostic.line + 1,
+                    diagnostic.start_character + 1,
+                    diagnostic.message
+                ))
+                .color(severity_color(diagnostic.severity))
+                .small(),
+            );
+        }
+
+        let definition = self
+            .lsp
+            .as_ref()
+            .and_then(|session| session.definition.clone());
+        if let Some(location) = definition {
+            ui.horizontal(|ui| {
+                ui.label(
+                    RichText::new(format!("Definition: {}", location_label(&location))).small(),
+                );
+                if ui.small_button("✖").clicked() {
+                    if let Some(session) = self.lsp.as_mut() {
+                        session.definition = None;
+                    }
+                }
+            });
+        }
+    }
+}
+
+pub(super) fn severity_color(severity: LspSeverity) -> Color32 {
+    match severity {
+        LspSeverity::Error => Color32::from_rgb(220, 80, 80),
+        LspSeverity::Warning => Color32::from_rgb(220, 170, 60),
+        LspSeverity::Information | LspSeverity::Hint => Color32::from_rgb(100, 150, 220),
+    }
+}
+
+fn severity_icon(severity: LspSeverity) -> &'static str {
+    match severity {
+        LspSeverity::Error => "✖",
+        LspSeverity::Warning => "⚠",
+        LspSeverity::Information | LspSeverity::Hint => "ℹ",
+    }
+}
+
+fn location_label(location: &LspLocation) -> String {
+    format!(
+        "{}:{}:{}",
+        location.path.display(),
+        location.line + 1,
+        location.character + 1
+    )
+}
+
+/// Converts a byte offset into an LSP position (zero-based line and UTF-16 column).
+pub(super) fn lsp_position(text: &str, offset: usize) -> (u32, u32) {
+    let prefix = &text[..offset.min(text.len())];
+    let line_start = prefix.rfind('\n').map_or(0, |index| index + 1);
+    let line = prefix.matches('\n').count() as u32;
+    let character = prefix[line_start..].encode_utf16().count() as u32;
+    (line, character)
+}
+
+/// Converts an LSP position back into a char index for galley lookups.
+pub(super) fn char_index_for_position(text: &str, line: u32, character: u32) -> usize {
+    let mut char_index = 0;
+    for (index, line_text) in text.split('\n').enumerate() {
+        if index as u32 == line {
+            let mut units = 0;
+            for c in line_text.chars() {
+                if units >= character as usize {
+                    break;
+                }
+                units += c.len_utf16();
+                char_index += 1;
+            }
+            return char_index;
+        }
+        char_index += line_text.chars().count() + 1;
+    }
+    char_index
+}
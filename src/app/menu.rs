@@ -0,0 +1,593 @@
+use std::{
+    fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use eframe::egui;
+
+use super::{ConsoleEntry, ConsolePane, ExplorerApp, SnackbarKind, view_export::ViewExportFormat};
+use crate::examples::{
+    self,
+    paste::{PasteSource, PasteTarget},
+};
+
+/// What is typed into the "Import from clipboard/URL" dialog, while it is open.
+#[derive(Default)]
+pub(super) struct PasteImportDialog {
+    /// Koto code, or a gist or raw file URL.
+    text: String,
+    title: String,
+    /// Adds a new example to the catalog instead of replacing the scratch example.
+    to_library: bool,
+}
+
+impl ExplorerApp {
+    /// The application menus. Every entry mirrors an action that is also reachable elsewhere
+    /// in the UI, so the menus double as an index of what the explorer can do.
+    pub(super) fn menu_bar_ui(&mut self, ctx: &egui::Context) {
+        egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            egui::MenuBar::new().ui(ui, |ui| {
+                ui.menu_button("File", |ui| self.file_menu_ui(ui));
+                ui.menu_button("Run", |ui| self.run_menu_ui(ui));
+                ui.menu_button("View", |ui| self.view_menu_ui(ui, ctx));
+                ui.menu_button("Help", |ui| self.help_menu_ui(ui, ctx));
+            });
+        });
+    }
+
+    fn file_menu_ui(&mut self, ui: &mut egui::Ui) {
+        let has_library = self.example_library.is_some();
+        if ui
+            .add_enabled(has_library, egui::Button::new("Open examples folder…"))
+            .clicked()
+        {
+            self.open_examples_folder_picker();
+        }
+        ui.add_enabled_ui(self.recent_catalogs.entries().len() > 1, |ui| {
+            ui.menu_button("Recent catalogs", |ui| self.recent_catalogs_ui(ui));
+        });
+        if ui
+            .add_enabled(
+                has_library && self.instructor_mode,
+                egui::Button::new("New example…"),
+            )
+            .on_disabled_hover_text("Turn on View → Instructor mode to author examples")
+            .clicked()
+        {
+            self.new_example_title = Some(String::new());
+        }
+        if ui
+            .add_enabled(
+                has_library && !self.is_importing_koto_scripts(),
+                egui::Button::new("Import from Koto repository…"),
+            )
+            .clicked()
+        {
+            self.koto_import_source = Some(examples::importer::KOTO_REPOSITORY_URL.to_string());
+        }
+        if ui
+            .add_enabled(
+                has_library && !self.is_importing_paste(),
+                egui::Button::new("Import from clipboard/URL…"),
+            )
+            .clicked()
+        {
+            self.paste_import = Some(PasteImportDialog {
+                text: clipboard_text().unwrap_or_default(),
+                ..Default::default()
+            });
+        }
+        if ui
+            .add_enabled(
+                !self.is_refreshing_catalog(),
+                egui::Button::new("Refresh catalog"),
+            )
+            .clicked()
+        {
+            self.refresh_catalog_in_background();
+        }
+        ui.separator();
+        if ui.button("Export console output").clicked() {
+            self.export_console_output();
+        }
+        let has_selection = self.selected_example().is_some();
+        if ui
+            .add_enabled(has_selection, egui::Button::new("Export example as HTML"))
+            .on_hover_text("A standalone page with the docs, code, last output, and benchmarks")
+            .clicked()
+        {
+            self.export_example_html();
+        }
+        ui.add_enabled_ui(has_selection && cfg!(feature = "image"), |ui| {
+            ui.menu_button("Export view", |ui| {
+                if ui
+                    .button("As PNG")
+                    .on_hover_text("Save the docs, code, and console as an image in exports/")
+                    .clicked()
+                {
+                    self.export_view(ui.ctx(), ViewExportFormat::Png);
+                }
+                if ui
+                    .button("As PDF")
+                    .on_hover_text("Save the docs, code, and console as a one-page PDF in exports/")
+                    .clicked()
+                {
+                    self.export_view(ui.ctx(), ViewExportFormat::Pdf);
+                }
+            });
+        });
+        if self.instructor_mode
+            && ui
+                .add_enabled(
+                    has_library && cfg!(feature = "archive"),
+                    egui::Button::new("Export student pack"),
+                )
+                .on_hover_text("Zip the catalog into exports/ without solution files")
+                .clicked()
+        {
+            self.export_student_pack();
+        }
+        ui.separator();
+        if ui.button("Quit").clicked() {
+            ui.ctx().send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+    }
+
+    fn run_menu_ui(&mut self, ui: &mut egui::Ui) {
+        let running = self.is_script_running();
+        let selected = self.selected_example().cloned();
+        if ui
+            .add_enabled(
+                !running && selected.is_some(),
+                egui::Button::new("Run example"),
+            )
+            .clicked()
+        {
+            self.run_selected_example();
+        }
+        if ui
+            .add_enabled(
+                !running && selected.is_some(),
+                egui::Button::new("Run with fresh VM")
+                    .shortcut_text(ui.ctx().format_shortcut(&super::RUN_FRESH_SHORTCUT)),
+            )
+            .clicked()
+        {
+            self.run_selected_example_fresh();
+        }
+        if ui
+            .add_enabled(
+                !running && selected.is_some() && !self.is_repeating_runs(),
+                egui::Button::new(format!("Run {}x", super::REPEATED_RUNS)),
+            )
+            .clicked()
+        {
+            self.run_selected_example_repeatedly();
+        }
+        if ui
+            .add_enabled(
+                !running && selected.is_some(),
+                egui::Button::new("Compare profiles…"),
+            )
+            .on_hover_text("Run under two runtime profiles and diff the output")
+            .clicked()
+        {
+            self.open_profile_compare();
+        }
+        if ui
+            .add_enabled(
+                selected.is_some(),
+                egui::Button::new("Koto version matrix…"),
+            )
+            .on_hover_text("Run the example and its tests under other Koto builds")
+            .clicked()
+        {
+            self.open_version_matrix();
+        }
+        if ui.add_enabled(running, egui::Button::new("Stop")).clicked() {
+            self.stop_running_script();
+        }
+        let suites_available = selected.as_ref().is_some_and(|example| {
+            !example.test_suites.is_empty() && !self.is_running_suites(&example.metadata.id)
+        });
+        if ui
+            .add_enabled(suites_available, egui::Button::new("Run tests"))
+            .clicked()
+        {
+            if let Some(example) = &selected {
+                self.active_console_pane = ConsolePane::Tests;
+                self.run_all_suites(example);
+            }
+        }
+        if ui
+            .add_enabled(!running, egui::Button::new("Sandbox audit"))
+            .on_hover_text("Check that each runtime profile blocks what it should")
+            .clicked()
+        {
+            self.open_sandbox_audit();
+        }
+        if ui
+            .add_enabled(
+                !self.is_automating(),
+                egui::Button::new("Run automation script…"),
+            )
+            .on_hover_text("Drive the explorer from a Koto script using the app module")
+            .clicked()
+        {
+            self.automation_path = Some(String::new());
+        }
+        ui.separator();
+        ui.menu_button("Benchmarks", |ui| {
+            let report_url = selected
+                .as_ref()
+                .and_then(|example| example.benchmark_summary.as_ref())
+                .and_then(|summary| summary.report_url.clone());
+            if ui
+                .add_enabled(
+                    report_url.is_some(),
+                    egui::Button::new("Open Criterion report"),
+                )
+                .on_disabled_hover_text("Run `cargo bench` to generate a report")
+                .clicked()
+            {
+                if let Some(url) = report_url {
+                    ui.ctx().open_url(egui::OpenUrl::new_tab(url));
+                }
+            }
+            if ui.button("Reload benchmark results").clicked() {
+                self.refresh_catalog_in_background();
+            }
+        });
+    }
+
+    fn view_menu_ui(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        ui.checkbox(&mut self.show_sidebar, "Sidebar");
+        ui.checkbox(&mut self.show_console, "Console");
+        let mut instructor_mode = self.instructor_mode;
+        if ui
+            .checkbox(&mut instructor_mode, "Instructor mode")
+            .on_hover_text("Show the tools for authoring examples and packing them for students")
+            .changed()
+        {
+            self.set_instructor_mode(instructor_mode);
+        }
+        if ui
+            .add_enabled(
+                self.selected_example_id.is_some(),
+                egui::Button::new("Example gallery"),
+            )
+            .clicked()
+        {
+            self.selected_example_id = None;
+            self.selected_category = None;
+        }
+        ui.menu_button("Console pane", |ui| {
+            for (pane, label) in [
+                (ConsolePane::Console, "Console"),
+                (ConsolePane::Tests, "Tests"),
+                (ConsolePane::Generators, "Generators"),
+                (ConsolePane::Tasks, "Tasks"),
+                (ConsolePane::Workers, "Workers"),
+                (ConsolePane::Canvas, "Canvas"),
+            ] {
+                if ui
+                    .selectable_label(self.active_console_pane == pane, label)
+                    .clicked()
+                {
+                    self.active_console_pane = pane;
+                    self.show_console = true;
+                }
+            }
+        });
+        ui.separator();
+        ui.menu_button("Theme", |ui| {
+            let current = ctx.options(|options| options.theme_preference);
+            for (preference, label) in [
+                (egui::ThemePreference::System, "Follow system"),
+                (egui::ThemePreference::Dark, "Dark"),
+                (egui::ThemePreference::Light, "Light"),
+            ] {
+                if ui.selectable_label(current == preference, label).clicked() {
+                    ctx.set_theme(preference);
+                }
+            }
+        });
+        if ui.button("Presentation mode (F5)").clicked() {
+            self.toggle_presentation(ctx);
+        }
+    }
+
+    fn help_menu_ui(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        if ui.button("Koto language reference").clicked() {
+            ctx.open_url(egui::OpenUrl::new_tab(self.docs_base_url.clone()));
+        }
+        if let Some(doc_url) = self
+            .selected_example()
+            .and_then(|example| example.metadata.doc_url.clone())
+        {
+            if ui.button("Docs for this example").clicked() {
+                ctx.open_url(egui::OpenUrl::new_tab(doc_url));
+            }
+        }
+        if ui.button("Concepts").clicked() {
+            self.open_concepts(None);
+        }
+        if ui.button("Prelude inspector").clicked() {
+            self.open_prelude_inspector();
+        }
+        if ui.button("Module playground").clicked() {
+            self.open_module_playground(None);
+        }
+        ui.separator();
+        if ui.button("About").clicked() {
+            self.show_about = true;
+        }
+    }
+
+    /// Writes the console entries shown in the Console tab to `exports/console_<timestamp>.txt`.
+    fn export_console_output(&mut self) {
+        let text = self
+            .visible_console_entries()
+            .map(ConsoleEntry::text)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        let path = PathBuf::from("exports").join(format!("console_{timestamp}.txt"));
+        self.export_in_background("console output", move || {
+            fs::create_dir_all("exports")?;
+            fs::write(&path, text)?;
+            Ok(format!("Exported console output to {}", path.display()))
+        });
+    }
+
+    /// Saves the last successful run as JSON under `exports/`.
+    pub(super) fn export_last_result(&mut self, example_id: &str) {
+        let Some(export) = self
+            .last_execution
+            .as_ref()
+            .and_then(|summary| summary.export.as_ref())
+        else {
+            return;
+        };
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        let path = PathBuf::from("exports").join(format!("result_{example_id}_{timestamp}.json"));
+        let result = serde_json::to_string_pretty(export)
+            .map_err(std::io::Error::other)
+            .and_then(|text| fs::create_dir_all("exports").and_then(|_| fs::write(&path, text)));
+        match result {
+            Ok(()) => {
+                self.push_console_entry(ConsoleEntry::info(format!(
+                    "Exported the result to {}",
+                    path.display()
+                )));
+                self.push_snackbar("Result exported", SnackbarKind::Success);
+            }
+            Err(error) => {
+                self.push_console_entry(ConsoleEntry::error(format!(
+                    "Failed to export the result: {error}"
+                )));
+                self.push_snackbar("Failed to export the result", SnackbarKind::Error);
+            }
+        }
+    }
+
+    /// Saves the selected example as a standalone page under `exports/`, with the output of
+    /// the last run when it was a run of this example.
+    fn export_example_html(&mut self) {
+        let Some(example) = self.selected_example().cloned() else {
+            return;
+        };
+        let output = self
+            .last_execution
+            .as_ref()
+            .and_then(|summary| summary.export.as_ref())
+            .filter(|export| export["title"] == example.metadata.title.as_str())
+            .map(|export| examples::html::SampleOutput {
+                stdout: export["stdout"].as_str().unwrap_or_default().to_string(),
+                stderr: export["stderr"].as_str().unwrap_or_default().to_string(),
+                return_value: export["return_value"].as_str().map(str::to_string),
+            });
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        let path = PathBuf::from("exports")
+            .join(format!("example_{}_{timestamp}.html", example.metadata.id));
+        self.export_in_background("the example", move || {
+            examples::html::export_html(&example, output.as_ref(), &path)?;
+            Ok(format!(
+                "Exported '{}' to {}",
+                example.metadata.title,
+                path.display()
+            ))
+        });
+    }
+
+    /// Asks for a Koto checkout or repository URL to import scripts from.
+    pub(super) fn koto_import_dialog_ui(&mut self, ctx: &egui::Context) {
+        let Some(source) = &mut self.koto_import_source else {
+            return;
+        };
+        let mut open = true;
+        let mut import = false;
+        let mut cancel = false;
+        egui::Window::new("Import from Koto repository")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("Local checkout or git URL");
+                ui.add(egui::TextEdit::singleline(source).desired_width(360.0));
+                ui.label(
+                    egui::RichText::new(
+                        "Scripts under examples/ and the language tests become examples in the \
+                         Imported category. URLs are cloned with git.",
+                    )
+                    .small()
+                    .weak(),
+                );
+                ui.horizontal(|ui| {
+                    import = ui
+                        .add_enabled(!source.trim().is_empty(), egui::Button::new("Import"))
+                        .clicked();
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+        if import {
+            let source = examples::importer::ImportSource::parse(source);
+            self.koto_import_source = None;
+            self.import_koto_scripts_in_background(source);
+        } else if !open || cancel {
+            self.koto_import_source = None;
+        }
+    }
+
+    /// Takes pasted Koto code or a link to it and turns it into the scratch example or a new
+    /// catalog example.
+    pub(super) fn paste_import_dialog_ui(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.paste_import else {
+            return;
+        };
+        let mut open = true;
+        let mut import = false;
+        let mut cancel = false;
+        egui::Window::new("Import from clipboard/URL")
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(480.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Koto code, or a gist or raw file URL");
+                    // Without the `clipboard` feature, Ctrl+V into the text box still works.
+                    if cfg!(feature = "clipboard")
+                        && ui.small_button("Paste from clipboard").clicked()
+                    {
+                        if let Some(text) = clipboard_text() {
+                            dialog.text = text;
+                        }
+                    }
+                });
+                egui::ScrollArea::vertical()
+                    .id_salt("paste_import_text")
+                    .max_height(240.0)
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut dialog.text)
+                                .code_editor()
+                                .desired_rows(10)
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+                ui.radio_value(
+                    &mut dialog.to_library,
+                    false,
+                    "Scratch example (replaced by the next scratch import)",
+                );
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut dialog.to_library, true, "New example titled");
+                    ui.add_enabled(
+                        dialog.to_library,
+                        egui::TextEdit::singleline(&mut dialog.title).desired_width(200.0),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    let ready = !dialog.text.trim().is_empty()
+                        && (!dialog.to_library || !dialog.title.trim().is_empty());
+                    import = ui.add_enabled(ready, egui::Button::new("Import")).clicked();
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+        if import {
+            let source = PasteSource::parse(&dialog.text);
+            let target = if dialog.to_library {
+                PasteTarget::Library {
+                    title: dialog.title.trim().to_string(),
+                }
+            } else {
+                PasteTarget::Scratch
+            };
+            self.paste_import = None;
+            self.import_paste_in_background(source, target);
+        } else if !open || cancel {
+            self.paste_import = None;
+        }
+    }
+
+    /// Asks for a title and scaffolds a new example in the current catalog.
+    pub(super) fn new_example_dialog_ui(&mut self, ctx: &egui::Context) {
+        let Some(title) = &mut self.new_example_title else {
+            return;
+        };
+        let mut open = true;
+        let mut create = false;
+        let mut cancel = false;
+        egui::Window::new("New example")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("Title");
+                let response = ui.text_edit_singleline(title);
+                if response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+                    create = true;
+                }
+                ui.horizontal(|ui| {
+                    create |= ui
+                        .add_enabled(!title.trim().is_empty(), egui::Button::new("Create"))
+                        .clicked();
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+        if !open || cancel {
+            self.new_example_title = None;
+        } else if create {
+            let title = title.clone();
+            self.create_example(&title);
+        }
+    }
+
+    fn create_example(&mut self, title: &str) {
+        let Some(library) = self.example_library else {
+            return;
+        };
+        match library.create_example(title) {
+            Ok(id) => {
+                self.new_example_title = None;
+                self.refresh_examples_from_library();
+                self.select_example(&id);
+                self.push_console_entry(ConsoleEntry::info(format!(
+                    "Created example '{title}' ({id})"
+                )));
+            }
+            Err(error) => {
+                self.push_console_entry(ConsoleEntry::error(format!(
+                    "Failed to create example: {error}"
+                )));
+                self.push_snackbar("Failed to create example", SnackbarKind::Error);
+            }
+        }
+    }
+}
+
+/// The clipboard's text, if it holds any.
+#[cfg(feature = "clipboard")]
+fn clipboard_text() -> Option<String> {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .ok()
+        .filter(|text| !text.trim().is_empty())
+}
+
+/// Builds without the `clipboard` feature can't read the clipboard, so pasted code starts
+/// empty.
+#[cfg(not(feature = "clipboard"))]
+fn clipboard_text() -> Option<String> {
+    None
+}
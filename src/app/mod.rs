@@ -1,20 +1,65 @@
 use crate::{
     examples::{self, Example},
-    runtime,
+    language, runtime,
 };
 use eframe::egui;
 use egui::{Align2, Color32, CornerRadius, Grid, RichText};
-use egui_extras::syntax_highlighting;
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     fs::File,
     io::{Read, Seek, SeekFrom},
     path::PathBuf,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
+mod about;
+mod activity;
+mod assertions;
+mod automation;
+mod canvas;
+mod categories;
+mod code_view;
+mod editor;
+mod execution;
+mod folder_picker;
+mod generators;
+mod glossary;
+mod inline_eval;
+mod instructor;
+mod lsp;
+mod menu;
+mod notebook;
+mod offscreen;
+mod output;
+mod playground;
+mod prelude_inspector;
+mod presentation;
+mod profile_compare;
+mod replay;
+mod return_history;
+mod sandbox_audit;
+mod settings;
+mod tasks;
+mod thumbnails;
+mod version_matrix;
+mod view_export;
+mod watches;
+mod workers;
+
 const LOG_POLL_INTERVAL: Duration = Duration::from_millis(500);
 const MAX_CONSOLE_ENTRIES: usize = 400;
+const DEFAULT_HOT_RELOAD_DELAY: Duration = Duration::from_millis(500);
+/// Time limit for runs started from the UI, in seconds. Stop only takes effect when a script
+/// calls into the host, so this is what ends a loop that never does.
+const DEFAULT_RUN_TIME_LIMIT_SECS: u64 = 30;
+/// How many times "Run 10x" runs the selected example.
+const REPEATED_RUNS: usize = 10;
+/// Where the "Max failures" limit for suite runs starts when it's turned on.
+const DEFAULT_MAX_FAILURES: usize = 5;
+const RUN_FRESH_SHORTCUT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(
+    egui::Modifiers::COMMAND.plus(egui::Modifiers::SHIFT),
+    egui::Key::R,
+);
 
 pub struct ExplorerApp {
     example_library: Option<&'static examples::ExampleLibrary>,
@@ -23,35 +68,145 @@ pub struct ExplorerApp {
     selected_example_id: Option<String>,
     search_query: String,
     category_filters: BTreeSet<String>,
+    /// Landing pages from `categories/<category>/category.md`, keyed by folder name.
+    category_pages: BTreeMap<String, examples::categories::CategoryPage>,
+    /// The category whose landing page fills the main panel, after clicking its header.
+    selected_category: Option<String>,
+    /// Examples the user allowed to run programs this session, for runs outside the trusted
+    /// profile; see `process_allowed_for`.
+    process_approved: HashSet<String>,
     console_entries: Vec<ConsoleEntry>,
+    /// Whether the Console tab shows only the selected example's entries instead of all.
+    console_per_example: bool,
     last_execution: Option<ExecutionSummary>,
     input_values: HashMap<String, String>,
+    /// Text fed to the script's `io.stdin` when it runs.
+    stdin_input: String,
+    /// Positional arguments passed to the script as `args`, separated by whitespace.
+    args_input: String,
     watch_mode_enabled: bool,
     hot_reload_enabled: bool,
+    /// Runs of an example share a session, so bindings persist from one run to the next.
+    session_mode: bool,
     has_loaded_examples_once: bool,
-    pending_hot_reload_run: bool,
+    /// When the last change that should trigger a hot reload run was seen.
+    pending_hot_reload_run: Option<Instant>,
+    /// Quiet period after the last change before the automatic re-run fires.
+    hot_reload_delay: Duration,
+    /// Per-run memory limit in MiB; 0 disables it.
+    memory_limit_mb: u64,
+    /// Time limit for runs started from the UI in seconds; 0 disables it. Daemon runs, which
+    /// run until stopped, don't get one.
+    run_time_limit_secs: u64,
+    /// Seed every run starts `host.random` from; a fresh seed is used per run when unset.
+    pinned_random_seed: Option<u64>,
     runtime_log_path: PathBuf,
     runtime_log_size: u64,
     last_log_poll: Option<Instant>,
     snackbars: Vec<Snackbar>,
     active_console_pane: ConsolePane,
     test_runs: HashMap<String, examples::tests::TestSuiteResult>,
+    test_history: examples::test_history::TestHistory,
+    /// Tags typed into the Tests pane; "Run all suites" only runs the suites they match.
+    test_tag_filter: String,
+    /// Whether suite runs also measure line coverage of the example scripts they load.
+    suite_coverage: bool,
+    /// Stops suite runs at the first failing case.
+    suite_fail_fast: bool,
+    /// Stops suite runs once this many cases have failed, when set.
+    suite_max_failures: Option<usize>,
     hot_reload_notices: Vec<HotReloadNotice>,
+    env_allowlist_input: String,
+    docs_base_url: String,
+    editor: Option<editor::EditorState>,
+    lsp_command: String,
+    lsp: Option<lsp::LspSession>,
+    inline_annotations: HashMap<String, Vec<inline_eval::InlineAnnotation>>,
+    /// Terms from the catalog's `glossary.json`, linked wherever they appear in descriptions.
+    glossary: examples::glossary::Glossary,
+    concepts: Option<glossary::ConceptsPanel>,
+    /// Script lines picked out by clicking a linked "How it works" paragraph.
+    code_highlight: Option<code_view::CodeHighlight>,
+    return_histories: HashMap<String, return_history::ReturnHistory>,
+    watches: HashMap<String, Vec<watches::Watch>>,
+    /// Assertions typed into the assertion bar, replacing the ones from `meta.json`.
+    assertion_overrides: HashMap<String, String>,
+    assertion_outcomes: HashMap<String, assertions::AssertionOutcome>,
+    watch_input: String,
+    /// Set while a hot reload starts its re-run, so the run's return value gets diffed.
+    hot_reload_rerun: bool,
+    notebook_sessions: HashMap<String, notebook::NotebookSession>,
+    presentation: Option<presentation::PresentationState>,
+    background_jobs: Vec<tasks::BackgroundJob>,
+    execution_events: std::sync::mpsc::Receiver<runtime::observer::ExecutionEvent>,
+    /// Timestamps the output of every run so the last one can be replayed.
+    output_recorder: runtime::replay::OutputRecorder,
+    /// The timestamped output of the last example run.
+    last_recording: Option<runtime::replay::OutputRecording>,
+    output_replay: Option<replay::OutputReplay>,
+    running_script: Option<execution::RunningScript>,
+    folder_picker: Option<folder_picker::FolderPicker>,
+    recent_catalogs: examples::recent::RecentCatalogs,
+    show_sidebar: bool,
+    show_console: bool,
+    /// Title being typed into the "New example" dialog, while it is open.
+    new_example_title: Option<String>,
+    /// The path or URL typed into the Koto import dialog, while it is open.
+    koto_import_source: Option<String>,
+    paste_import: Option<menu::PasteImportDialog>,
+    /// Unlocks the authoring tools: new examples, metadata editing, and solutions.
+    instructor_mode: bool,
+    metadata_editor: Option<instructor::MetadataEditor>,
+    /// The Koto script driving the app through the `app` module, while it runs.
+    automation: Option<automation::Automation>,
+    /// The script path typed into the automation dialog, while it is open.
+    automation_path: Option<String>,
+    show_about: bool,
+    prelude_inspector: Option<prelude_inspector::PreludeInspector>,
+    module_playground: Option<playground::ModulePlayground>,
+    sandbox_audit: Option<sandbox_audit::SandboxAudit>,
+    profile_compare: Option<profile_compare::ProfileCompare>,
+    version_matrix: Option<version_matrix::VersionMatrixDialog>,
+    /// Output from each `concurrent` worker in the current or last run.
+    worker_streams: Vec<workers::WorkerStream>,
+    /// The last frame the running or last run drew with `gfx`.
+    canvas_frame: Option<runtime::gfx::Frame>,
+    thumbnail_capture: Option<thumbnails::ThumbnailCapture>,
+    /// The example panel and console on the last frame, for exporting the view.
+    view_rect: Option<egui::Rect>,
+    /// Height of the console panel in the last frame, when it's open; see `view_rect`.
+    console_height: Option<f32>,
 }
 
 impl ExplorerApp {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let library = examples::library()
+            .inspect_err(|error| log::error!("Failed to initialize example library: {error}"))
+            .ok();
+        Self::from_library(&cc.egui_ctx, library)
+    }
+
+    /// Builds the app around `library` instead of the shared one from
+    /// [`examples::library`], e.g. an [`ExampleLibrary::in_memory`] catalog in UI tests.
+    ///
+    /// [`ExampleLibrary::in_memory`]: examples::ExampleLibrary::in_memory
+    pub fn with_library(
+        cc: &eframe::CreationContext<'_>,
+        library: &'static examples::ExampleLibrary,
+    ) -> Self {
+        Self::from_library(&cc.egui_ctx, Some(library))
+    }
+
+    fn from_library(
+        ctx: &egui::Context,
+        example_library: Option<&'static examples::ExampleLibrary>,
+    ) -> Self {
         log::info!("Initializing ExplorerApp");
+        egui_extras::install_image_loaders(ctx);
 
-        let (example_library, examples, examples_version) = match examples::library() {
-            Ok(library) => {
-                let snapshot = library.snapshot();
-                (Some(library), snapshot, library.version())
-            }
-            Err(error) => {
-                log::error!("Failed to initialize example library: {error}");
-                (None, Vec::new(), 0)
-            }
+        let (examples, examples_version) = match example_library {
+            Some(library) => (library.snapshot(), library.version()),
+            None => (Vec::new(), 0),
         };
 
         let selected_example_id = examples.first().map(|example| example.metadata.id.clone());
@@ -62,22 +217,112 @@ impl ExplorerApp {
             selected_example_id,
             search_query: String::new(),
             category_filters: BTreeSet::new(),
+            category_pages: BTreeMap::new(),
+            selected_category: None,
+            process_approved: HashSet::new(),
             console_entries: vec![ConsoleEntry::info("Ready to explore Koto scripts")],
+            console_per_example: false,
             last_execution: None,
             input_values: HashMap::new(),
+            stdin_input: String::new(),
+            args_input: String::new(),
             watch_mode_enabled: true,
             hot_reload_enabled: false,
+            session_mode: false,
             has_loaded_examples_once: false,
-            pending_hot_reload_run: false,
+            pending_hot_reload_run: None,
+            hot_reload_delay: std::env::var("KOTO_HOT_RELOAD_DELAY_MS")
+                .ok()
+                .and_then(|value| value.trim().parse().ok())
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_HOT_RELOAD_DELAY),
+            memory_limit_mb: std::env::var("KOTO_MEMORY_LIMIT_MB")
+                .ok()
+                .and_then(|value| value.trim().parse().ok())
+                .unwrap_or(0),
+            run_time_limit_secs: std::env::var("KOTO_RUN_TIME_LIMIT_SECS")
+                .ok()
+                .and_then(|value| value.trim().parse().ok())
+                .unwrap_or(DEFAULT_RUN_TIME_LIMIT_SECS),
+            pinned_random_seed: std::env::var("KOTO_RANDOM_SEED")
+                .ok()
+                .and_then(|value| value.trim().parse().ok()),
             runtime_log_path: PathBuf::from("logs").join("runtime.log"),
             runtime_log_size: 0,
             last_log_poll: None,
             snackbars: Vec::new(),
             active_console_pane: ConsolePane::Console,
             test_runs: HashMap::new(),
+            test_tag_filter: String::new(),
+            suite_coverage: false,
+            suite_fail_fast: false,
+            suite_max_failures: None,
             hot_reload_notices: Vec::new(),
+            env_allowlist_input: std::env::var("KOTO_ENV_ALLOWLIST").unwrap_or_default(),
+            docs_base_url: std::env::var("KOTO_DOCS_BASE_URL")
+                .unwrap_or_else(|_| language::DEFAULT_DOCS_BASE_URL.to_string()),
+            editor: None,
+            lsp_command: std::env::var("KOTO_LSP_COMMAND").unwrap_or_default(),
+            lsp: None,
+            inline_annotations: HashMap::new(),
+            glossary: examples::glossary::Glossary::default(),
+            concepts: None,
+            code_highlight: None,
+            return_histories: HashMap::new(),
+            watches: HashMap::new(),
+            assertion_overrides: HashMap::new(),
+            assertion_outcomes: HashMap::new(),
+            watch_input: String::new(),
+            hot_reload_rerun: false,
+            notebook_sessions: HashMap::new(),
+            presentation: None,
+            background_jobs: Vec::new(),
+            execution_events: output::subscribe_to_runtime(),
+            output_recorder: output::record_runtime_output(),
+            last_recording: None,
+            output_replay: None,
+            running_script: None,
+            folder_picker: None,
+            recent_catalogs: examples::recent::RecentCatalogs::load(),
+            test_history: examples::test_history::TestHistory::load(),
+            show_sidebar: true,
+            show_console: true,
+            new_example_title: None,
+            koto_import_source: None,
+            paste_import: None,
+            instructor_mode: matches!(
+                std::env::var("KOTO_INSTRUCTOR").as_deref(),
+                Ok("1" | "true")
+            ),
+            metadata_editor: None,
+            automation: None,
+            automation_path: None,
+            show_about: false,
+            prelude_inspector: None,
+            module_playground: None,
+            sandbox_audit: None,
+            profile_compare: None,
+            version_matrix: None,
+            worker_streams: Vec::new(),
+            canvas_frame: None,
+            thumbnail_capture: None,
+            view_rect: None,
+            console_height: None,
         };
 
+        runtime::RUNTIME.set_env_allowlist(settings::parse_name_list(&app.env_allowlist_input));
+        runtime::RUNTIME.set_audio_enabled(matches!(
+            std::env::var("KOTO_AUDIO").as_deref(),
+            Ok("1" | "true")
+        ));
+        app.apply_memory_limit();
+        runtime::RUNTIME.set_random_seed(app.pinned_random_seed);
+        app.remember_current_catalog();
+        app.reload_glossary();
+        app.reload_category_pages();
+        app.connect_language_server();
+        app.warm_compile_examples();
+
         if let Some(metadata) = app.examples.first().map(|example| example.metadata.clone()) {
             app.apply_input_defaults(&metadata);
         }
@@ -88,6 +333,13 @@ impl ExplorerApp {
         app
     }
 
+    /// Runs the automation script at `path` once the app starts, then quits with its outcome
+    /// as the exit status.
+    pub fn with_automation(mut self, path: PathBuf) -> Self {
+        self.start_automation(path, true);
+        self
+    }
+
     fn selected_example(&self) -> Option<&Example> {
         self.selected_example_id.as_ref().and_then(|id| {
             self.examples
@@ -103,12 +355,13 @@ impl ExplorerApp {
 
         if let Some(library) = self.example_library {
             let version = library.version();
+            let changes = library.take_recent_changes();
             if version != self.examples_version {
                 self.examples = library.snapshot();
                 self.examples_version = version;
+                self.remap_renamed_examples(&changes);
                 self.on_examples_changed(true);
             }
-            let changes = library.take_recent_changes();
             if !changes.is_empty() {
                 self.handle_script_changes(changes);
             }
@@ -133,6 +386,10 @@ impl ExplorerApp {
                 let key = format!("{}::{suite_id}", change.example_id);
                 self.test_runs.remove(&key);
             }
+            examples::ScriptChangeKind::MetadataUpdated { previous, .. } => {
+                self.apply_metadata_change(&change.example_id, previous.as_deref());
+            }
+            examples::ScriptChangeKind::ExampleRenamed { .. } => {}
         }
 
         let message = describe_change(change);
@@ -140,6 +397,67 @@ impl ExplorerApp {
         self.push_snackbar(message, SnackbarKind::Info);
     }
 
+    /// Moves per-example state (selection, test results, sessions) from an example's old id to
+    /// its new one, so a renamed folder doesn't look like a removal followed by an addition.
+    fn remap_renamed_examples(&mut self, changes: &[examples::ScriptChange]) {
+        for change in changes {
+            let examples::ScriptChangeKind::ExampleRenamed { previous_id, .. } = &change.kind
+            else {
+                continue;
+            };
+            let new_id = &change.example_id;
+            if self.selected_example_id.as_ref() == Some(previous_id) {
+                self.selected_example_id = Some(new_id.clone());
+            }
+
+            let prefix = format!("{previous_id}::");
+            let keys: Vec<String> = self
+                .test_runs
+                .keys()
+                .filter(|key| key.starts_with(&prefix))
+                .cloned()
+                .collect();
+            for key in keys {
+                if let Some(run) = self.test_runs.remove(&key) {
+                    let suite_id = &key[prefix.len()..];
+                    self.test_runs.insert(format!("{new_id}::{suite_id}"), run);
+                }
+            }
+            if let Err(error) = self.test_history.rename_example(previous_id, new_id) {
+                self.push_console_entry(ConsoleEntry::error(format!("{error:#}")));
+            }
+
+            if let Some(annotations) = self.inline_annotations.remove(previous_id) {
+                self.inline_annotations.insert(new_id.clone(), annotations);
+            }
+            if let Some(history) = self.return_histories.remove(previous_id) {
+                self.return_histories.insert(new_id.clone(), history);
+            }
+            if let Some(watches) = self.watches.remove(previous_id) {
+                self.watches.insert(new_id.clone(), watches);
+            }
+            if let Some(assertion) = self.assertion_overrides.remove(previous_id) {
+                self.assertion_overrides.insert(new_id.clone(), assertion);
+            }
+            if let Some(outcome) = self.assertion_outcomes.remove(previous_id) {
+                self.assertion_outcomes.insert(new_id.clone(), outcome);
+            }
+            if let Some(session) = self.notebook_sessions.remove(previous_id) {
+                self.notebook_sessions.insert(new_id.clone(), session);
+            }
+            if let Some(editor) = self.editor.as_mut() {
+                if &editor.example_id == previous_id {
+                    editor.example_id = new_id.clone();
+                }
+            }
+            for notice in &mut self.hot_reload_notices {
+                if &notice.change.example_id == previous_id {
+                    notice.change.example_id = new_id.clone();
+                }
+            }
+        }
+    }
+
     fn prune_test_runs(&mut self) {
         let valid: HashSet<String> = self
             .examples
@@ -170,7 +488,15 @@ impl ExplorerApp {
 
     fn refresh_examples_from_library(&mut self) {
         if let Some(library) = self.example_library {
-            if let Err(error) = library.refresh() {
+            let result = library.refresh();
+            self.apply_catalog_refresh(result);
+        }
+    }
+
+    /// Picks up the library state after a refresh, whether it ran inline or as a task.
+    fn apply_catalog_refresh(&mut self, result: anyhow::Result<()>) {
+        if let Some(library) = self.example_library {
+            if let Err(error) = result {
                 self.push_console_entry(ConsoleEntry::error(format!(
                     "Failed to refresh examples: {error}"
                 )));
@@ -178,10 +504,11 @@ impl ExplorerApp {
                 return;
             }
 
+            let changes = library.take_recent_changes();
             self.examples = library.snapshot();
             self.examples_version = library.version();
+            self.remap_renamed_examples(&changes);
             self.on_examples_changed(false);
-            let changes = library.take_recent_changes();
             if !changes.is_empty() {
                 self.handle_script_changes(changes);
             }
@@ -191,6 +518,8 @@ impl ExplorerApp {
 
     fn on_examples_changed(&mut self, triggered_by_watch: bool) {
         let previous_selection = self.selected_example_id.clone();
+        self.reload_glossary();
+        self.reload_category_pages();
 
         if let Some(selected_id) = &self.selected_example_id {
             if !self
@@ -209,18 +538,19 @@ impl ExplorerApp {
                 .map(|example| example.metadata.id.clone());
         }
 
+        // An example that stays selected keeps what the user typed; its metadata change event
+        // refines the values further, see `apply_metadata_change`.
         if let Some(metadata) = self
-            .selected_example_id
-            .as_ref()
-            .and_then(|id| {
-                self.examples
-                    .iter()
-                    .find(|example| &example.metadata.id == id)
-            })
+            .selected_example()
             .map(|example| example.metadata.clone())
         {
-            self.apply_input_defaults(&metadata);
+            if self.selected_example_id == previous_selection {
+                self.merge_input_defaults(&metadata);
+            } else {
+                self.apply_input_defaults(&metadata);
+            }
         }
+        self.prune_category_filters();
 
         if triggered_by_watch && self.has_loaded_examples_once && self.hot_reload_enabled {
             if let Some(previous) = previous_selection {
@@ -230,7 +560,8 @@ impl ExplorerApp {
                     .map(|current| current == &previous)
                     .unwrap_or(false)
                 {
-                    self.pending_hot_reload_run = true;
+                    // Each further change restarts the quiet period.
+                    self.pending_hot_reload_run = Some(Instant::now());
                 }
             }
         }
@@ -240,7 +571,101 @@ impl ExplorerApp {
         self.has_loaded_examples_once = true;
     }
 
+    /// Re-runs the selected example once no changes have arrived for `hot_reload_delay`.
+    fn run_pending_hot_reload(&mut self, ctx: &egui::Context) {
+        let Some(changed_at) = self.pending_hot_reload_run else {
+            return;
+        };
+        if self.is_script_running() {
+            // Try again once the current run has finished.
+            return;
+        }
+        let quiet_for = changed_at.elapsed();
+        if quiet_for < self.hot_reload_delay {
+            ctx.request_repaint_after(self.hot_reload_delay - quiet_for);
+            return;
+        }
+        self.pending_hot_reload_run = None;
+        self.hot_reload_rerun = true;
+        self.run_selected_example();
+        self.hot_reload_rerun = false;
+    }
+
+    /// Updates the UI after an example's `meta.json` changed. Inputs the user edited keep their
+    /// values as long as they still exist; the others take the new defaults.
+    fn apply_metadata_change(&mut self, example_id: &str, previous_source: Option<&str>) {
+        if self.selected_example_id.as_deref() != Some(example_id) {
+            return;
+        }
+        let Some(metadata) = self
+            .selected_example()
+            .map(|example| example.metadata.clone())
+        else {
+            return;
+        };
+        let previous = previous_source
+            .and_then(|source| serde_json::from_str::<examples::ExampleMetadata>(source).ok());
+        if previous.as_ref().is_none_or(|previous| {
+            previous.stdin.as_deref().unwrap_or_default() == self.stdin_input
+        }) {
+            self.stdin_input = metadata.stdin.clone().unwrap_or_default();
+        }
+        if previous
+            .as_ref()
+            .is_none_or(|previous| join_args(&previous.args) == self.args_input)
+        {
+            self.args_input = join_args(&metadata.args);
+        }
+        let previous_defaults: HashMap<String, String> = previous
+            .map(|previous| {
+                previous
+                    .inputs
+                    .into_iter()
+                    .map(|input| (input.name, input.default.unwrap_or_default()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut values = HashMap::new();
+        for input in &metadata.inputs {
+            let default = input.default.clone().unwrap_or_default();
+            let value = match self.input_values.get(&input.name) {
+                Some(current) if previous_defaults.get(&input.name) != Some(current) => {
+                    current.clone()
+                }
+                _ => default,
+            };
+            values.insert(input.name.clone(), value);
+        }
+        self.input_values = values;
+    }
+
+    /// Drops category filters that no example uses any more.
+    fn prune_category_filters(&mut self) {
+        let categories: HashSet<&String> = self
+            .examples
+            .iter()
+            .flat_map(|example| &example.metadata.categories)
+            .collect();
+        self.category_filters
+            .retain(|category| categories.contains(category));
+    }
+
+    /// Keeps current values for inputs that still exist, adds defaults for new inputs, and
+    /// drops values for inputs that were removed.
+    fn merge_input_defaults(&mut self, metadata: &examples::ExampleMetadata) {
+        let mut current = std::mem::take(&mut self.input_values);
+        for input in &metadata.inputs {
+            let value = current
+                .remove(&input.name)
+                .unwrap_or_else(|| input.default.clone().unwrap_or_default());
+            self.input_values.insert(input.name.clone(), value);
+        }
+    }
+
     fn apply_input_defaults(&mut self, metadata: &examples::ExampleMetadata) {
+        self.stdin_input = metadata.stdin.clone().unwrap_or_default();
+        self.args_input = join_args(&metadata.args);
         self.input_values.clear();
         for input in &metadata.inputs {
             let value = input.default.clone().unwrap_or_default();
@@ -249,6 +674,7 @@ impl ExplorerApp {
     }
 
     fn select_example(&mut self, example_id: &str) {
+        self.selected_category = None;
         if self.selected_example_id.as_deref() == Some(example_id) {
             return;
         }
@@ -275,66 +701,158 @@ impl ExplorerApp {
             }
         };
 
-        let script = self.prepare_script(&example);
-        self.push_console_entry(ConsoleEntry::info(format!(
-            "Running '{}'",
-            example.metadata.title
-        )));
-
-        match runtime::RUNTIME.execute_script(&script) {
-            Ok(output) => {
-                if let Some(value) = &output.return_value {
-                    self.push_console_entry(ConsoleEntry::result(format!("Return value: {value}")));
-                }
-                if !output.stdout.is_empty() {
-                    self.push_console_entry(ConsoleEntry::stdout(output.stdout.clone()));
-                }
-                if !output.stderr.is_empty() {
-                    self.push_console_entry(ConsoleEntry::stderr(output.stderr.clone()));
-                }
-                if output.stdout.is_empty()
-                    && output.stderr.is_empty()
-                    && output.return_value.is_none()
-                {
-                    self.push_console_entry(ConsoleEntry::info("Example executed with no output"));
-                }
+        if self.runtime_busy() {
+            return;
+        }
 
-                self.last_execution = Some(ExecutionSummary {
-                    duration: output.duration,
-                    return_value: output.return_value,
-                    succeeded: true,
-                });
-                self.push_snackbar("Example executed successfully", SnackbarKind::Success);
+        let (script, mut options) = match self.prepare_example_run(&example) {
+            Ok(prepared) => prepared,
+            Err(error) => {
+                self.report_invalid_inputs(error);
+                return;
             }
+        };
+        if self.session_mode {
+            options = options.session(example_session_id(&example.metadata.id));
+        }
+        self.start_script_run(example.metadata.title.clone(), script, options);
+    }
+
+    /// Runs the selected example several times in the background and reports how the first,
+    /// uncached run compares with the rest.
+    fn run_selected_example_repeatedly(&mut self) {
+        let Some(example) = self.selected_example().cloned() else {
+            return;
+        };
+        if example.metadata.daemon {
+            self.push_snackbar(
+                "Daemon examples run until stopped, so they can't be timed repeatedly",
+                SnackbarKind::Info,
+            );
+            return;
+        }
+        if self.runtime_busy() {
+            return;
+        }
+        let (script, options) = match self.prepare_example_run(&example) {
+            Ok(prepared) => prepared,
             Err(error) => {
-                self.push_console_entry(ConsoleEntry::error(format!("Execution error: {error}")));
-                self.last_execution = Some(ExecutionSummary {
-                    duration: Duration::default(),
-                    return_value: None,
-                    succeeded: false,
-                });
-                self.push_snackbar("Example execution failed", SnackbarKind::Error);
+                self.report_invalid_inputs(error);
+                return;
             }
+        };
+        self.start_repeated_runs(example.metadata.title.clone(), script, options);
+    }
+
+    /// Sets up the runtime for a run of `example` and returns the script with its options.
+    fn prepare_example_run(
+        &self,
+        example: &Example,
+    ) -> anyhow::Result<(String, runtime::RunOptions)> {
+        let input = example.metadata.coerce_inputs(&self.input_values)?;
+        runtime::RUNTIME.set_stdin(self.stdin_input.clone());
+        runtime::RUNTIME.set_scratch_dir(Some(runtime::scratch::scratch_dir_for(
+            &example.metadata.id,
+        )));
+        let mut options = runtime::RunOptions::new()
+            .args(split_args(&self.args_input))
+            .bindings(example.metadata.bindings.to_run_bindings())
+            .env_allowlist(example.metadata.env.iter().cloned())
+            .daemon(example.metadata.daemon)
+            .allow_process(self.process_allowed_for(example));
+        if !input.is_empty() {
+            // Added as a map in the prelude rather than spliced into the source, so values need no
+            // quoting and error line numbers match the script.
+            options = options.binding(
+                "input",
+                runtime::RunBinding::Value(serde_json::Value::Object(input)),
+            );
         }
+        Ok((example.script.clone(), options))
+    }
+
+    /// Whether runs of `example` may use `process.run`: it has to opt in through its
+    /// `meta.json`, and the trusted profile or the user has to allow it too.
+    fn process_allowed_for(&self, example: &Example) -> bool {
+        example.metadata.permissions.process
+            && (runtime::RUNTIME.profile() == runtime::RuntimeProfile::Trusted
+                || self.process_approved.contains(&example.metadata.id))
     }
 
-    fn prepare_script(&self, example: &Example) -> String {
-        if self.input_values.is_empty() {
-            return example.script.clone();
+    /// Reports inputs that can't be converted to their declared type instead of running.
+    fn report_invalid_inputs(&mut self, error: anyhow::Error) {
+        self.push_console_entry(ConsoleEntry::error(format!("{error:#}")));
+        self.push_snackbar(
+            "Fix the example's inputs before running",
+            SnackbarKind::Error,
+        );
+    }
+
+    /// Runs the selected example in a newly built VM, to tell leftover global state from
+    /// genuine script behaviour.
+    fn run_selected_example_fresh(&mut self) {
+        if self.selected_example().is_none() || self.runtime_busy() {
+            return;
         }
+        if let Err(error) = runtime::RUNTIME.reset_vm() {
+            self.push_console_entry(ConsoleEntry::error(format!(
+                "Failed to rebuild the VM: {error}"
+            )));
+            return;
+        }
+        self.push_console_entry(ConsoleEntry::info("Rebuilt the VM before running"));
+        self.run_selected_example();
+    }
 
-        let json = serde_json::to_string(&self.input_values).unwrap_or_default();
-        let escaped_json = json.replace('\\', "\\\\").replace('"', "\\\"");
-        let mut prefix = String::from("import serde\n");
-        prefix.push_str(&format!("input = serde.from_json(\"{}\")\n", escaped_json));
-        format!("{prefix}{}", example.script)
+    fn reset_example_session(&mut self, example_id: &str) {
+        if self.runtime_busy() {
+            return;
+        }
+        match runtime::RUNTIME.reset_session(&example_session_id(example_id)) {
+            Ok(()) => self.push_snackbar("Session cleared", SnackbarKind::Info),
+            Err(error) => self.push_console_entry(ConsoleEntry::error(format!(
+                "Failed to reset the session: {error}"
+            ))),
+        }
     }
 
-    fn push_console_entry(&mut self, entry: ConsoleEntry) {
+    /// Adds `entry` to the console, or counts it against the last entry when that one has the
+    /// same kind and text and comes from the same example.
+    fn push_console_entry(&mut self, mut entry: ConsoleEntry) {
+        entry.example_id = self.console_owner();
+        if let Some(last) = self.console_entries.last_mut() {
+            if last.kind == entry.kind
+                && last.message == entry.message
+                && last.example_id == entry.example_id
+            {
+                last.repeats += 1;
+                return;
+            }
+        }
         self.console_entries.push(entry);
         self.trim_console_history();
     }
 
+    /// The console entries shown in the Console tab: all of them, or only the selected
+    /// example's when the console is scoped to it.
+    fn visible_console_entries(&self) -> impl Iterator<Item = &ConsoleEntry> {
+        self.console_entries.iter().filter(|entry| {
+            !self.console_per_example || entry.example_id == self.selected_example_id
+        })
+    }
+
+    /// Clears the entries the Console tab shows, keeping other examples' output when the
+    /// console is scoped to the selected example.
+    fn clear_console(&mut self) {
+        if self.console_per_example {
+            let selected = self.selected_example_id.clone();
+            self.console_entries
+                .retain(|entry| entry.example_id != selected);
+        } else {
+            self.console_entries.clear();
+        }
+    }
+
     fn trim_console_history(&mut self) {
         if self.console_entries.len() > MAX_CONSOLE_ENTRIES {
             let excess = self.console_entries.len() - MAX_CONSOLE_ENTRIES;
@@ -407,12 +925,13 @@ impl ExplorerApp {
 
             if example.metadata.categories.is_empty() {
                 groups
-                    .entry("Uncategorized".to_string())
+                    .entry(categories::UNCATEGORIZED.to_string())
                     .or_default()
                     .push(ExampleListEntry {
                         id: example.metadata.id.clone(),
                         title: example.metadata.title.clone(),
                         note: example.metadata.note.clone(),
+                        thumbnail: example.thumbnail.clone(),
                     });
             } else {
                 for category in &example.metadata.categories {
@@ -423,6 +942,7 @@ impl ExplorerApp {
                             id: example.metadata.id.clone(),
                             title: example.metadata.title.clone(),
                             note: example.metadata.note.clone(),
+                            thumbnail: example.thumbnail.clone(),
                         });
                 }
             }
@@ -431,6 +951,10 @@ impl ExplorerApp {
     }
 
     fn passes_filters(&self, example: &Example) -> bool {
+        // Examples that need a subsystem this build leaves out can't run, so they're hidden.
+        if !runtime::features::missing(&example.metadata.features).is_empty() {
+            return false;
+        }
         if !self.category_filters.is_empty()
             && !example
                 .metadata
@@ -514,9 +1038,40 @@ impl ExplorerApp {
             ui.separator();
         }
 
-        if ui.button("Refresh catalog").clicked() {
-            self.refresh_examples_from_library();
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !self.is_refreshing_catalog(),
+                    egui::Button::new("Refresh catalog"),
+                )
+                .clicked()
+            {
+                self.refresh_catalog_in_background();
+            }
+            if ui
+                .add_enabled(
+                    self.example_library.is_some(),
+                    egui::Button::new("Open examples folder…"),
+                )
+                .clicked()
+            {
+                self.open_examples_folder_picker();
+            }
+        });
+        if let Some(dir) = self
+            .example_library
+            .and_then(|library| library.examples_dir())
+        {
+            ui.label(RichText::new(dir.display().to_string()).small().weak())
+                .on_hover_text("Examples directory");
         }
+        if self.recent_catalogs.entries().len() > 1 {
+            egui::CollapsingHeader::new("Recent catalogs")
+                .default_open(false)
+                .show(ui, |ui| self.recent_catalogs_ui(ui));
+        }
+
+        self.runtime_settings_ui(ui);
 
         if self.examples.is_empty() {
             ui.label("No examples available yet.");
@@ -529,33 +1084,48 @@ impl ExplorerApp {
             .id_salt("example_list")
             .show(ui, |ui| {
                 for (category, entries) in grouped_examples {
-                    egui::CollapsingHeader::new(category)
-                        .default_open(true)
-                        .show(ui, |ui| {
-                            for entry in entries {
-                                let selected = self
-                                    .selected_example_id
-                                    .as_ref()
-                                    .map(|id| id == &entry.id)
-                                    .unwrap_or(false);
-                                let mut response =
-                                    ui.selectable_label(selected, entry.title.as_str());
-                                if let Some(note) = &entry.note {
-                                    response = response.on_hover_text(note);
-                                }
-                                if response.clicked() {
-                                    self.select_example(&entry.id);
-                                }
+                    egui::collapsing_header::CollapsingState::load_with_default_open(
+                        ui.ctx(),
+                        ui.make_persistent_id(("example_category", &category)),
+                        true,
+                    )
+                    .show_header(ui, |ui| self.category_header_ui(ui, &category))
+                    .body(|ui| {
+                        for entry in entries {
+                            let selected = self
+                                .selected_example_id
+                                .as_ref()
+                                .map(|id| id == &entry.id)
+                                .unwrap_or(false);
+                            let mut response = ui
+                                .horizontal(|ui| {
+                                    thumbnails::sidebar_thumbnail(ui, entry.thumbnail.as_deref());
+                                    ui.selectable_label(selected, entry.title.as_str())
+                                })
+                                .inner;
+                            if let Some(note) = &entry.note {
+                                response = response.on_hover_text(note);
                             }
-                        });
+                            if response.clicked() {
+                                self.select_example(&entry.id);
+                            }
+                        }
+                    });
                 }
             });
     }
 
     fn main_panel_ui(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        self.reload_error_banner_ui(ui);
+
+        if let Some(category) = self.selected_category.clone() {
+            self.category_page_ui(ui, &category);
+            return;
+        }
+
         if let Some(example) = self.selected_example().cloned() {
             ui.heading(&example.metadata.title);
-            ui.label(&example.metadata.description);
+            self.glossary_text_ui(ui, &example.metadata.description);
 
             if let Some(note) = &example.metadata.note {
                 ui.add_space(6.0);
@@ -571,6 +1141,39 @@ impl ExplorerApp {
                     }
                 });
             }
+            self.example_concepts_ui(ui, &example.metadata.id);
+
+            if example.metadata.permissions.process {
+                ui.add_space(6.0);
+                ui.horizontal_wrapped(|ui| {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(220, 140, 60),
+                        "⚠ This example can run external programs through process.run",
+                    );
+                    if runtime::RUNTIME.profile() == runtime::RuntimeProfile::Trusted {
+                        return;
+                    }
+                    let id = &example.metadata.id;
+                    if self.process_approved.contains(id) {
+                        if ui
+                            .small_button("Revoke")
+                            .on_hover_text("Stop this example from running programs")
+                            .clicked()
+                        {
+                            self.process_approved.remove(id);
+                        }
+                    } else if ui
+                        .small_button("Allow")
+                        .on_hover_text(
+                            "Let this example run programs until the app closes; \
+                             the trusted profile allows it for every example that opts in",
+                        )
+                        .clicked()
+                    {
+                        self.process_approved.insert(id.clone());
+                    }
+                });
+            }
 
             if let Some(instructions) = &example.metadata.run_instructions {
                 ui.add_space(6.0);
@@ -579,7 +1182,7 @@ impl ExplorerApp {
 
             if let Some(docs) = &example.docs {
                 ui.add_space(6.0);
-                ui.label(&docs.summary);
+                self.glossary_text_ui(ui, &docs.summary);
                 let link_target = example
                     .metadata
                     .doc_url
@@ -600,24 +1203,23 @@ impl ExplorerApp {
                 egui::CollapsingHeader::new("How it works")
                     .default_open(true)
                     .show(ui, |ui| {
-                        for paragraph in &example.metadata.how_it_works {
-                            ui.label(paragraph);
+                        for (index, step) in example.metadata.how_it_works.iter().enumerate() {
+                            self.how_it_works_step_ui(ui, &example, index, step);
                             ui.add_space(4.0);
                         }
                     });
             }
 
             ui.add_space(10.0);
-            ui.group(|ui| {
-                ui.label("Code");
-                let theme = syntax_highlighting::CodeTheme::from_memory(ctx, ui.style());
-                egui::ScrollArea::both()
-                    .id_salt("code_view")
-                    .show(ui, |ui| {
-                        syntax_highlighting::code_view_ui(ui, &theme, &example.script, "koto");
-                    });
-                theme.store_in_memory(ctx);
-            });
+            if self.instructor_mode {
+                self.instructor_tools_ui(ui, &example);
+                ui.add_space(10.0);
+            }
+            if example.notebook.is_some() {
+                self.notebook_ui(ui, ctx, &example);
+            } else {
+                self.code_view_ui(ui, ctx, &example);
+            }
 
             ui.add_space(10.0);
             if !example.metadata.inputs.is_empty() {
@@ -630,13 +1232,33 @@ impl ExplorerApp {
                             .or_insert_with(|| input.default.clone().unwrap_or_default());
                         ui.horizontal(|ui| {
                             let label = input.label.as_deref().unwrap_or(input.name.as_str());
+                            if input.kind == examples::InputType::Bool {
+                                let mut checked = input
+                                    .coerce(value)
+                                    .ok()
+                                    .and_then(|value| value.as_bool())
+                                    .unwrap_or(false);
+                                if ui.checkbox(&mut checked, label).changed() {
+                                    *value = checked.to_string();
+                                }
+                                return;
+                            }
                             ui.label(label);
                             let mut text_edit = egui::TextEdit::singleline(value);
+                            if input.kind == examples::InputType::Json {
+                                text_edit = text_edit.code_editor();
+                            }
                             if let Some(placeholder) = &input.placeholder {
                                 text_edit = text_edit.hint_text(placeholder);
                             }
                             ui.add(text_edit);
                         });
+                        if let Err(error) = input.coerce(value) {
+                            ui.colored_label(
+                                Color32::from_rgb(220, 110, 110),
+                                RichText::new(format!("{error:#}")).small(),
+                            );
+                        }
                         if let Some(description) = &input.description {
                             ui.label(RichText::new(description).small());
                         }
@@ -644,21 +1266,115 @@ impl ExplorerApp {
                 });
             }
 
+            ui.horizontal(|ui| {
+                ui.label("Arguments");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.args_input)
+                        .code_editor()
+                        .desired_width(f32::INFINITY)
+                        .hint_text("Passed to the script as args; quote values with spaces"),
+                );
+            });
+
+            egui::CollapsingHeader::new("Standard input")
+                .id_salt(("stdin", &example.metadata.id))
+                .default_open(example.metadata.stdin.is_some())
+                .show(ui, |ui| {
+                    ui.add(
+                        egui::TextEdit::multiline(&mut self.stdin_input)
+                            .code_editor()
+                            .desired_rows(3)
+                            .desired_width(f32::INFINITY)
+                            .hint_text("Text the script reads through io.stdin"),
+                    );
+                });
+
             ui.add_space(10.0);
             ui.horizontal(|ui| {
-                if ui.button("Run example").clicked() {
+                let running = self.is_script_running();
+                let daemon = example.metadata.daemon;
+                let run_label = if daemon { "▶ Start" } else { "Run example" };
+                if ui
+                    .add_enabled(!running, egui::Button::new(run_label))
+                    .on_hover_text(if daemon {
+                        "Start the example; its callbacks keep running until you stop it"
+                    } else {
+                        "Run the example once"
+                    })
+                    .clicked()
+                {
                     self.run_selected_example();
                 }
+                if ui
+                    .add_enabled(!running, egui::Button::new("Run with fresh VM"))
+                    .on_hover_text(format!(
+                        "Rebuild the VM first, discarding global state from earlier runs ({})",
+                        ui.ctx().format_shortcut(&RUN_FRESH_SHORTCUT)
+                    ))
+                    .clicked()
+                {
+                    self.run_selected_example_fresh();
+                }
+                if ui
+                    .add_enabled(
+                        !running && !daemon && !self.is_repeating_runs(),
+                        egui::Button::new(format!("Run {REPEATED_RUNS}x")),
+                    )
+                    .on_hover_text(
+                        "Run repeatedly and compare the first, uncached run with the rest",
+                    )
+                    .clicked()
+                {
+                    self.run_selected_example_repeatedly();
+                }
+                if ui
+                    .add_enabled(!running && !daemon, egui::Button::new("Compare profiles"))
+                    .on_hover_text("Run under two runtime profiles and diff the output")
+                    .clicked()
+                {
+                    self.open_profile_compare();
+                }
+                if running {
+                    if ui
+                        .button("⏹ Stop")
+                        .on_hover_text("Stop the running script at its next host call")
+                        .clicked()
+                    {
+                        self.stop_running_script();
+                    }
+                    ui.spinner();
+                    if daemon {
+                        ui.label(RichText::new("Running until stopped").small().weak());
+                    }
+                }
                 if ui.button("Clear output").clicked() {
-                    self.console_entries.clear();
+                    self.clear_console();
+                }
+                ui.toggle_value(&mut self.session_mode, "Session mode")
+                    .on_hover_text("Keep variables defined by a run for the next run, like a REPL");
+                if self.session_mode
+                    && ui
+                        .add_enabled(!running, egui::Button::new("Reset session"))
+                        .clicked()
+                {
+                    self.reset_example_session(&example.metadata.id);
                 }
                 ui.toggle_value(&mut self.watch_mode_enabled, "Watch examples");
                 ui.toggle_value(&mut self.hot_reload_enabled, "Hot reload");
+                if ui
+                    .button("Present")
+                    .on_hover_text("Fullscreen presentation mode (F5)")
+                    .clicked()
+                {
+                    self.toggle_presentation(ctx);
+                }
             });
 
             self.hot_reload_notice_ui(ui, &example);
 
-            if example.metadata.benchmarks.is_some() || example.benchmark_summary.is_some() {
+            if cfg!(feature = "benchmarks")
+                && (example.metadata.benchmarks.is_some() || example.benchmark_summary.is_some())
+            {
                 ui.add_space(6.0);
                 self.benchmark_summary_ui(ui, &example);
             }
@@ -666,6 +1382,8 @@ impl ExplorerApp {
                 self.resource_row(ui, "🧪 Tests", tests);
             }
 
+            let mut pin_seed = None;
+            let mut export_result = false;
             if let Some(summary) = &self.last_execution {
                 ui.add_space(8.0);
                 let status = if summary.succeeded {
@@ -675,16 +1393,137 @@ impl ExplorerApp {
                     RichText::new("Last execution failed").color(Color32::from_rgb(220, 80, 80))
                 };
                 ui.label(status);
-                ui.label(format!("Duration: {} ms", summary.duration.as_millis()));
-                if let Some(return_value) = &summary.return_value {
-                    ui.label(format!("Return value: {return_value}"));
+                let duration = match summary.phases {
+                    Some((compile, execute)) => format!(
+                        "Duration: {} (compile {}{}, execute {})",
+                        format_duration(summary.duration),
+                        format_duration(compile),
+                        if summary.cache_hit { ", cached" } else { "" },
+                        format_duration(execute)
+                    ),
+                    None => format!("Duration: {}", format_duration(summary.duration)),
+                };
+                ui.label(duration).on_hover_text(if summary.cache_hit {
+                    "Compilation was skipped; the script was unchanged since an earlier run"
+                } else {
+                    "Includes compiling the script"
+                });
+                if let Some(peak) = summary.peak_memory {
+                    ui.label(format!(
+                        "Peak memory: {}",
+                        runtime::memory::format_bytes(peak)
+                    ));
+                }
+                if let Some(seed) = summary.random_seed {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Random seed: {seed}"));
+                        if self.pinned_random_seed == Some(seed) {
+                            ui.label(RichText::new("(pinned)").small().weak());
+                        } else if ui
+                            .small_button("Pin")
+                            .on_hover_text("Start later runs from this seed to replay them")
+                            .clicked()
+                        {
+                            pin_seed = Some(seed);
+                        }
+                    });
+                }
+                match (&summary.value, &summary.return_value) {
+                    (Some(value), _) if output::is_nested(value) => {
+                        output::value_tree_ui(ui, "Return value", value);
+                    }
+                    (_, Some(return_value)) => {
+                        ui.label(format!("Return value: {return_value}"));
+                    }
+                    _ => {}
+                }
+                self.return_history_ui(ui, &example.metadata.id);
+                if summary.export.is_some()
+                    && ui
+                        .button("Export result")
+                        .on_hover_text(
+                            "Save the return value, output, timings, and inputs as a JSON file",
+                        )
+                        .clicked()
+                {
+                    export_result = true;
                 }
+                if summary.clipboard_accessed {
+                    ui.colored_label(
+                        Color32::from_rgb(180, 140, 50),
+                        "📋 This run accessed the clipboard",
+                    );
+                }
+            }
+            ui.add_space(6.0);
+            self.assertion_bar_ui(ui, &example);
+            self.watch_panel_ui(ui, &example.metadata.id);
+            if pin_seed.is_some() {
+                self.set_pinned_random_seed(pin_seed);
             }
+            if export_result {
+                self.export_last_result(&example.metadata.id);
+            }
+        } else if self.examples.is_empty() {
+            self.welcome_ui(ui);
         } else {
-            ui.label("Select an example from the sidebar to get started.");
+            self.example_gallery_ui(ui);
         }
     }
 
+    /// Shows why the example catalog is failing to reload, with a button to try again.
+    fn reload_error_banner_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(error) = self
+            .example_library
+            .and_then(|library| library.reload_error())
+        else {
+            return;
+        };
+        let directory_missing = self
+            .example_library
+            .is_some_and(|library| library.is_directory_missing());
+        let heading = if directory_missing {
+            error
+        } else {
+            format!("Example reload failing: {error}")
+        };
+        egui::Frame::group(ui.style())
+            .fill(Color32::from_rgb(70, 30, 30))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(Color32::from_rgb(240, 140, 140), "⚠");
+                    ui.label(
+                        RichText::new(heading)
+                            .strong()
+                            .color(Color32::from_rgb(240, 200, 200)),
+                    );
+                });
+                if directory_missing {
+                    ui.label(
+                        RichText::new("Watching resumes automatically when the folder comes back.")
+                            .small()
+                            .color(Color32::from_rgb(240, 200, 200)),
+                    );
+                }
+                ui.horizontal(|ui| {
+                    if directory_missing && ui.button("Choose examples directory…").clicked() {
+                        self.open_examples_folder_picker();
+                    }
+                    let refreshing = self.is_refreshing_catalog();
+                    if ui
+                        .add_enabled(!refreshing, egui::Button::new("Retry"))
+                        .clicked()
+                    {
+                        self.refresh_catalog_in_background();
+                    }
+                    if refreshing {
+                        ui.spinner();
+                    }
+                });
+            });
+        ui.add_space(8.0);
+    }
+
     fn resource_row(&self, ui: &mut egui::Ui, label: &str, resource: &examples::ExampleResource) {
         ui.horizontal(|ui| {
             ui.label(RichText::new(label).strong());
@@ -708,6 +1547,7 @@ impl ExplorerApp {
                     let grid_id = format!("benchmark_summary_{}", summary.example_id);
                     Grid::new(grid_id).striped(true).show(ui, |grid| {
                         grid.label(RichText::new("Implementation").strong());
+                        grid.label(RichText::new("Phase").strong());
                         grid.label(RichText::new("Input").strong());
                         grid.label(RichText::new("Mean (ms)").strong());
                         grid.label(RichText::new("CI (ms)").strong());
@@ -715,6 +1555,7 @@ impl ExplorerApp {
 
                         for measurement in &summary.measurements {
                             grid.label(&measurement.benchmark_id);
+                            grid.label(measurement.phase());
                             grid.label(measurement.parameter.as_deref().unwrap_or("—"));
 
                             let mean_response =
@@ -769,18 +1610,47 @@ impl ExplorerApp {
                 "Console",
             );
             ui.selectable_value(&mut self.active_console_pane, ConsolePane::Tests, "Tests");
+            ui.selectable_value(
+                &mut self.active_console_pane,
+                ConsolePane::Generators,
+                "Generators",
+            );
+            ui.selectable_value(&mut self.active_console_pane, ConsolePane::Tasks, "Tasks");
+            ui.selectable_value(
+                &mut self.active_console_pane,
+                ConsolePane::Workers,
+                "Workers",
+            );
+            ui.selectable_value(&mut self.active_console_pane, ConsolePane::Canvas, "Canvas");
             if matches!(self.active_console_pane, ConsolePane::Console) {
+                ui.toggle_value(&mut self.console_per_example, "This example only")
+                    .on_hover_text(
+                        "Show only output from the selected example; turn off to see all output",
+                    );
                 if ui.button("Copy").clicked() {
                     let text = self
-                        .console_entries
-                        .iter()
-                        .map(|entry| entry.message.clone())
+                        .visible_console_entries()
+                        .map(ConsoleEntry::text)
                         .collect::<Vec<_>>()
                         .join("\n");
                     ctx.copy_text(text);
                 }
                 if ui.button("Clear").clicked() {
-                    self.console_entries.clear();
+                    self.clear_console();
+                }
+                if ui
+                    .add_enabled(self.last_recording.is_some(), egui::Button::new("Replay"))
+                    .on_hover_text("Play the last run's output back at the pace it was printed")
+                    .clicked()
+                {
+                    self.open_output_replay();
+                }
+                if self.is_script_running() {
+                    ui.spinner();
+                    ui.label(RichText::new("Streaming output…").small().weak());
+                    if ui.small_button("⏹ Stop").clicked() {
+                        self.stop_running_script();
+                    }
                 }
             }
         });
@@ -792,17 +1662,36 @@ impl ExplorerApp {
                     .stick_to_bottom(true)
                     .id_salt("console_scroll")
                     .show(ui, |ui| {
-                        for entry in &self.console_entries {
+                        for entry in self.visible_console_entries() {
                             let visuals = ui.visuals();
                             let color = entry.kind.color(visuals);
                             let message = RichText::new(&entry.message).color(color);
-                            ui.label(message);
+                            if entry.repeats > 1 {
+                                ui.horizontal_wrapped(|ui| {
+                                    ui.label(message);
+                                    entry.repeat_badge_ui(ui);
+                                });
+                            } else {
+                                ui.label(message);
+                            }
                         }
                     });
             }
             ConsolePane::Tests => {
                 self.tests_ui(ui);
             }
+            ConsolePane::Generators => {
+                self.generators_ui(ui);
+            }
+            ConsolePane::Tasks => {
+                self.tasks_ui(ui);
+            }
+            ConsolePane::Workers => {
+                self.workers_ui(ui);
+            }
+            ConsolePane::Canvas => {
+                self.canvas_ui(ui);
+            }
         }
     }
 
@@ -817,9 +1706,68 @@ impl ExplorerApp {
             return;
         }
 
-        if ui.button("Run all suites").clicked() {
-            self.run_all_suites(&example);
-        }
+        let running = self.is_running_suites(&example.metadata.id);
+        let filter = examples::tests::TagFilter::parse(&self.test_tag_filter);
+        let has_tags = example
+            .test_suites
+            .iter()
+            .any(|suite| !suite.tags.is_empty());
+        ui.horizontal(|ui| {
+            let label = if filter.is_empty() {
+                "Run all suites"
+            } else {
+                "Run matching suites"
+            };
+            if ui.add_enabled(!running, egui::Button::new(label)).clicked() {
+                self.run_all_suites(&example);
+            }
+            let has_results = example.test_suites.iter().any(|suite| {
+                self.test_runs
+                    .contains_key(&format!("{}::{}", example.metadata.id, suite.id))
+            });
+            if ui
+                .add_enabled(has_results, egui::Button::new("Export JUnit XML"))
+                .on_hover_text("Save the latest results to exports/ for CI systems and editors")
+                .clicked()
+            {
+                self.export_junit_report(&example);
+            }
+            ui.checkbox(&mut self.suite_coverage, "Coverage")
+                .on_hover_text(
+                    "Record which lines of the example script run, for suites with an \
+                     `# Example:` header",
+                );
+            ui.checkbox(&mut self.suite_fail_fast, "Fail fast")
+                .on_hover_text("Stop at the first failing case");
+            ui.add_enabled_ui(!self.suite_fail_fast, |ui| {
+                let mut limited = self.suite_max_failures.is_some();
+                if ui
+                    .checkbox(&mut limited, "Max failures")
+                    .on_hover_text("Stop once this many cases have failed")
+                    .changed()
+                {
+                    self.suite_max_failures = limited.then_some(DEFAULT_MAX_FAILURES);
+                }
+                if let Some(max_failures) = &mut self.suite_max_failures {
+                    ui.add(egui::DragValue::new(max_failures).range(1..=999));
+                }
+            });
+            if has_tags {
+                ui.label("Tags:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.test_tag_filter)
+                        .hint_text("io, !slow")
+                        .desired_width(140.0),
+                )
+                .on_hover_text(
+                    "Run only suites with one of these tags; prefix a tag with ! to skip it",
+                );
+            }
+            if running {
+                ui.spinner();
+                ui.label("Running…");
+            }
+        });
         ui.separator();
 
         for suite in &example.test_suites {
@@ -827,14 +1775,43 @@ impl ExplorerApp {
             let result = self.test_runs.get(&key).cloned();
             ui.group(|ui| {
                 ui.horizontal(|ui| {
-                    ui.heading(&suite.name);
-                    if ui.button("Run").clicked() {
+                    if filter.matches(suite) {
+                        ui.heading(&suite.name);
+                    } else {
+                        ui.heading(RichText::new(&suite.name).weak())
+                            .on_hover_text("Skipped by the tag filter");
+                    }
+                    for tag in &suite.tags {
+                        ui.label(RichText::new(tag).small().monospace());
+                    }
+                    if ui.add_enabled(!running, egui::Button::new("Run")).clicked() {
                         self.run_suite_for_example(&example, suite);
                     }
+                    if let Some(result) = result.as_ref().filter(|result| !result.passed) {
+                        let failed = result.failed_cases().len();
+                        if failed > 0
+                            && ui
+                                .add_enabled(!running, egui::Button::new("Re-run failures"))
+                                .on_hover_text(format!("Run only the {failed} failed cases again"))
+                                .clicked()
+                        {
+                            self.push_console_entry(ConsoleEntry::info(format!(
+                                "Re-running {failed} failed cases of suite '{}'",
+                                suite.name
+                            )));
+                            self.start_case_rerun(
+                                &example,
+                                suite.clone(),
+                                result.clone(),
+                                result.failed_cases(),
+                            );
+                        }
+                    }
                 });
                 if let Some(description) = &suite.description {
                     ui.label(description);
                 }
+                self.test_history_ui(ui, &key);
 
                 if let Some(result) = result.as_ref() {
                     let status_text = if result.passed {
@@ -844,10 +1821,41 @@ impl ExplorerApp {
                     };
                     ui.label(status_text);
                     ui.label(format!(
-                        "Suites: {} tests, {} ms total",
+                        "Suites: {} tests, {} total",
                         result.cases.len(),
-                        result.total_duration.as_millis()
+                        format_duration(result.total_duration)
                     ));
+                    if result.stopped_early {
+                        ui.label(
+                            RichText::new(
+                                "Stopped early at the failure limit; later cases didn't run",
+                            )
+                            .color(Color32::from_rgb(220, 180, 80)),
+                        );
+                    }
+                    if let Some(coverage) = &result.coverage {
+                        code_view::suite_coverage_ui(ui, suite, coverage);
+                    }
+                    if !result.pending_snapshots.is_empty() {
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                RichText::new(format!(
+                                    "{} new or changed snapshots",
+                                    result.pending_snapshots.len()
+                                ))
+                                .color(Color32::from_rgb(220, 180, 80)),
+                            );
+                            if ui
+                                .add_enabled(!running, egui::Button::new("Accept new snapshots"))
+                                .on_hover_text(
+                                    "Make the new output the golden files and run the suite again",
+                                )
+                                .clicked()
+                            {
+                                self.accept_suite_snapshots(&example, suite);
+                            }
+                        });
+                    }
 
                     if !result.setup_stdout.is_empty() {
                         ui.collapsing("Suite stdout", |ui| {
@@ -861,21 +1869,30 @@ impl ExplorerApp {
                     }
 
                     for case in &result.cases {
-                        let header = egui::CollapsingHeader::new(format!(
-                            "{} ({:.0} ms)",
-                            case.name,
-                            case.duration.as_secs_f32() * 1000.0
-                        ))
-                        .default_open(matches!(case.status, examples::tests::TestStatus::Failed));
-
-                        header.show(ui, |ui| {
-                            let status =
-                                match case.status {
-                                    examples::tests::TestStatus::Passed => RichText::new("Passed")
-                                        .color(Color32::from_rgb(120, 200, 120)),
-                                    examples::tests::TestStatus::Failed => RichText::new("Failed")
-                                        .color(Color32::from_rgb(220, 100, 100)),
-                                };
+                        let failed = matches!(case.status, examples::tests::TestStatus::Failed);
+                        let mut run_case = false;
+                        egui::collapsing_header::CollapsingState::load_with_default_open(
+                            ui.ctx(),
+                            ui.make_persistent_id((&key, &case.name)),
+                            failed,
+                        )
+                        .show_header(ui, |ui| {
+                            ui.label(format!(
+                                "{} ({})",
+                                case.name,
+                                format_duration(case.duration)
+                            ));
+                            run_case = ui
+                                .add_enabled(!running, egui::Button::new("Run").small())
+                                .on_hover_text("Run just this case")
+                                .clicked();
+                        })
+                        .body(|ui| {
+                            let status = if failed {
+                                RichText::new("Failed").color(Color32::from_rgb(220, 100, 100))
+                            } else {
+                                RichText::new("Passed").color(Color32::from_rgb(120, 200, 120))
+                            };
                             ui.label(status);
                             if let Some(error) = &case.error {
                                 ui.label(
@@ -889,6 +1906,14 @@ impl ExplorerApp {
                                 ui.collapsing("Stderr", |ui| ui.monospace(&case.stderr));
                             }
                         });
+                        if run_case {
+                            self.start_case_rerun(
+                                &example,
+                                suite.clone(),
+                                result.clone(),
+                                vec![case.name.clone()],
+                            );
+                        }
                     }
                 } else {
                     ui.label("Run the suite to view results.");
@@ -897,19 +1922,183 @@ impl ExplorerApp {
         }
     }
 
+    /// A strip with one square per recent run (green for passes, red for failures), a
+    /// sparkline of their durations, and the current streak, so flaky suites stand out.
+    fn test_history_ui(&self, ui: &mut egui::Ui, key: &str) {
+        let runs = self.test_history.runs(key);
+        if runs.len() < 2 {
+            return;
+        }
+        let passed_color = Color32::from_rgb(120, 200, 120);
+        let failed_color = Color32::from_rgb(220, 100, 100);
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 2.0;
+            for run in &runs {
+                let (rect, response) =
+                    ui.allocate_exact_size(egui::vec2(8.0, 14.0), egui::Sense::hover());
+                let color = if run.passed {
+                    passed_color
+                } else {
+                    failed_color
+                };
+                ui.painter().rect_filled(rect, 2.0, color);
+                response.on_hover_text(format!(
+                    "{} · {}/{} cases failed · {}",
+                    if run.passed { "Passed" } else { "Failed" },
+                    run.failed_cases,
+                    run.cases,
+                    format_duration(run.duration())
+                ));
+            }
+            ui.add_space(8.0);
+
+            let (rect, response) =
+                ui.allocate_exact_size(egui::vec2(80.0, 14.0), egui::Sense::hover());
+            let slowest = runs
+                .iter()
+                .map(|run| run.duration_ms)
+                .fold(f64::EPSILON, f64::max);
+            let step = rect.width() / (runs.len() - 1) as f32;
+            let points: Vec<egui::Pos2> = runs
+                .iter()
+                .enumerate()
+                .map(|(index, run)| {
+                    let height = (run.duration_ms / slowest) as f32 * rect.height();
+                    egui::pos2(rect.left() + index as f32 * step, rect.bottom() - height)
+                })
+                .collect();
+            ui.painter().add(egui::Shape::line(
+                points,
+                egui::Stroke::new(1.5, ui.visuals().weak_text_color()),
+            ));
+            let average = runs.iter().map(|run| run.duration_ms).sum::<f64>() / runs.len() as f64;
+            response.on_hover_text(format!(
+                "Durations of the last {} runs; average {average:.1} ms, slowest {slowest:.1} ms",
+                runs.len()
+            ));
+            ui.add_space(8.0);
+
+            if let Some((passed, length)) = self.test_history.streak(key) {
+                let text = match (passed, length) {
+                    (true, 1) => "1 pass".to_string(),
+                    (true, _) => format!("{length} passes in a row"),
+                    (false, 1) => "1 failure".to_string(),
+                    (false, _) => format!("{length} failures in a row"),
+                };
+                ui.label(RichText::new(text).small());
+            }
+            if self.test_history.is_flaky(key) {
+                ui.label(
+                    RichText::new("flaky")
+                        .small()
+                        .strong()
+                        .color(Color32::from_rgb(220, 180, 80)),
+                )
+                .on_hover_text("This suite has both passed and failed several times recently");
+            }
+        });
+    }
+
     fn run_suite_for_example(
         &mut self,
         example: &Example,
         suite: &examples::tests::ExampleTestSuite,
     ) {
-        let key = format!("{}::{}", example.metadata.id, suite.id);
-        self.active_console_pane = ConsolePane::Tests;
         self.push_console_entry(ConsoleEntry::info(format!(
             "Running suite '{}' for '{}'",
             suite.name, example.metadata.title
         )));
+        self.start_suite_run(example, vec![suite.clone()]);
+    }
+
+    /// Writes the latest result of each of `example`'s suites to `exports/junit_<id>_<timestamp>.xml`.
+    fn export_junit_report(&mut self, example: &Example) {
+        let results: Vec<_> = example
+            .test_suites
+            .iter()
+            .filter_map(|suite| {
+                self.test_runs
+                    .get(&format!("{}::{}", example.metadata.id, suite.id))
+                    .cloned()
+            })
+            .collect();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        let path =
+            PathBuf::from("exports").join(format!("junit_{}_{timestamp}.xml", example.metadata.id));
+        match examples::tests::export_junit(&results, &path) {
+            Ok(()) => {
+                self.push_console_entry(ConsoleEntry::info(format!(
+                    "Exported {} suite results to {}",
+                    results.len(),
+                    path.display()
+                )));
+                self.push_snackbar("JUnit report exported", SnackbarKind::Success);
+            }
+            Err(error) => {
+                self.push_console_entry(ConsoleEntry::error(format!("{error:#}")));
+                self.push_snackbar("Failed to export the JUnit report", SnackbarKind::Error);
+            }
+        }
+    }
+
+    fn accept_suite_snapshots(
+        &mut self,
+        example: &Example,
+        suite: &examples::tests::ExampleTestSuite,
+    ) {
+        match examples::tests::accept_snapshots(suite) {
+            Ok(count) => {
+                self.push_console_entry(ConsoleEntry::info(format!(
+                    "Accepted {count} snapshots for suite '{}'",
+                    suite.name
+                )));
+                self.run_suite_for_example(example, suite);
+            }
+            Err(error) => {
+                self.push_console_entry(ConsoleEntry::error(format!("{error:#}")));
+                self.push_snackbar("Failed to accept the snapshots", SnackbarKind::Error);
+            }
+        }
+    }
+
+    fn run_all_suites(&mut self, example: &Example) {
+        if example.test_suites.is_empty() {
+            return;
+        }
+
+        let filter = examples::tests::TagFilter::parse(&self.test_tag_filter);
+        let suites: Vec<_> = example
+            .test_suites
+            .iter()
+            .filter(|suite| filter.matches(suite))
+            .cloned()
+            .collect();
+        if suites.is_empty() {
+            self.push_snackbar("No suites match the tag filter", SnackbarKind::Info);
+            return;
+        }
+
+        self.push_console_entry(ConsoleEntry::info(format!(
+            "Running {} suites for '{}'",
+            suites.len(),
+            example.metadata.title
+        )));
+        self.start_suite_run(example, suites);
+    }
 
-        match examples::tests::run_suite(suite) {
+    /// Records the outcome of one suite and reports it; returns whether the suite passed.
+    fn record_suite_result(
+        &mut self,
+        example_id: &str,
+        suite_id: &str,
+        suite_name: &str,
+        result: Result<examples::tests::TestSuiteResult, String>,
+    ) -> bool {
+        let key = format!("{example_id}::{suite_id}");
+        match result {
             Ok(result) => {
                 let passed_count = result
                     .cases
@@ -917,61 +2106,44 @@ impl ExplorerApp {
                     .filter(|case| case.status == examples::tests::TestStatus::Passed)
                     .count();
                 let message = format!(
-                    "Suite '{}' finished: {passed_count}/{} cases passed ({} ms)",
-                    suite.name,
+                    "Suite '{}' finished: {passed_count}/{} cases passed ({})",
+                    suite_name,
                     result.cases.len(),
-                    result.total_duration.as_millis()
+                    format_duration(result.total_duration)
                 );
-                if result.passed {
+                let passed = result.passed;
+                if passed {
                     self.push_console_entry(ConsoleEntry::info(message.clone()));
                     self.push_snackbar(message, SnackbarKind::Success);
                 } else {
                     self.push_console_entry(ConsoleEntry::error(message.clone()));
                     self.push_snackbar(message, SnackbarKind::Error);
                 }
+                if let Err(error) = self.test_history.record(&key, &result) {
+                    self.push_console_entry(ConsoleEntry::error(format!(
+                        "Failed to save the test history: {error:#}"
+                    )));
+                }
                 self.test_runs.insert(key, result);
+                passed
             }
             Err(error) => {
                 self.push_console_entry(ConsoleEntry::error(format!(
-                    "Failed to run suite '{}': {error}",
-                    suite.name
+                    "Failed to run suite '{suite_name}': {error}"
                 )));
                 self.push_snackbar("Test suite failed to run", SnackbarKind::Error);
                 self.test_runs.remove(&key);
+                false
             }
         }
     }
 
-    fn run_all_suites(&mut self, example: &Example) {
-        if example.test_suites.is_empty() {
-            return;
-        }
-
-        self.active_console_pane = ConsolePane::Tests;
-        self.push_console_entry(ConsoleEntry::info(format!(
-            "Running {} suites for '{}'",
-            example.test_suites.len(),
-            example.metadata.title
-        )));
-
-        let mut any_failed = false;
-        for suite in &example.test_suites {
-            self.run_suite_for_example(example, suite);
-            let key = format!("{}::{}", example.metadata.id, suite.id);
-            if let Some(result) = self.test_runs.get(&key) {
-                if !result.passed {
-                    any_failed = true;
-                }
-            }
-        }
-
+    /// Reports the combined outcome after several suites finished in one run.
+    fn finish_suite_run(&mut self, title: &str, any_failed: bool) {
         let summary = if any_failed {
-            format!(
-                "Finished running suites for '{}' with failures",
-                example.metadata.title
-            )
+            format!("Finished running suites for '{title}' with failures")
         } else {
-            format!("All suites for '{}' passed", example.metadata.title)
+            format!("All suites for '{title}' passed")
         };
 
         if any_failed {
@@ -1124,24 +2296,70 @@ impl eframe::App for ExplorerApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         self.ensure_examples_current();
         self.poll_runtime_logs();
-
-        if self.pending_hot_reload_run {
-            self.pending_hot_reload_run = false;
-            self.run_selected_example();
+        self.poll_execution_events();
+        self.poll_language_server(ctx);
+        self.poll_background_jobs();
+        self.poll_running_script(ctx);
+        self.poll_automation(ctx);
+        self.poll_thumbnail_capture(ctx);
+
+        self.run_pending_hot_reload(ctx);
+
+        if self.is_presenting() {
+            self.presentation_ui(ctx);
+            self.show_snackbars(ctx);
+            return;
         }
+        if ctx.input(|input| input.key_pressed(egui::Key::F5)) {
+            self.toggle_presentation(ctx);
+        }
+        if ctx.input_mut(|input| input.consume_shortcut(&RUN_FRESH_SHORTCUT)) {
+            self.run_selected_example_fresh();
+        }
+
+        self.menu_bar_ui(ctx);
 
-        egui::TopBottomPanel::bottom("console_panel")
-            .resizable(true)
-            .default_height(180.0)
-            .show(ctx, |ui| self.console_ui(ui, ctx));
+        let console_rect = self.show_console.then(|| {
+            egui::TopBottomPanel::bottom("console_panel")
+                .resizable(true)
+                .default_height(180.0)
+                .show(ctx, |ui| self.console_ui(ui, ctx))
+                .response
+                .rect
+        });
 
-        egui::SidePanel::left("sidebar")
-            .resizable(true)
-            .default_width(240.0)
-            .show(ctx, |ui| self.sidebar_ui(ui));
+        if self.show_sidebar {
+            egui::SidePanel::left("sidebar")
+                .resizable(true)
+                .default_width(240.0)
+                .show(ctx, |ui| self.sidebar_ui(ui));
+        }
 
-        egui::CentralPanel::default().show(ctx, |ui| self.main_panel_ui(ui, ctx));
+        let main_rect = egui::CentralPanel::default()
+            .show(ctx, |ui| self.main_panel_ui(ui, ctx))
+            .response
+            .rect;
+        self.console_height = console_rect.map(|rect| rect.height());
+        self.view_rect = self.selected_example().map(|_| match console_rect {
+            Some(console_rect) => main_rect.union(console_rect),
+            None => main_rect,
+        });
 
+        self.folder_picker_ui(ctx);
+        self.new_example_dialog_ui(ctx);
+        self.koto_import_dialog_ui(ctx);
+        self.paste_import_dialog_ui(ctx);
+        self.metadata_editor_ui(ctx);
+        self.automation_dialog_ui(ctx);
+        self.about_ui(ctx);
+        self.concepts_ui(ctx);
+        self.output_replay_ui(ctx);
+        self.prelude_inspector_ui(ctx);
+        self.module_playground_ui(ctx);
+        self.sandbox_audit_ui(ctx);
+        self.profile_compare_ui(ctx);
+        self.version_matrix_ui(ctx);
+        self.activity_indicator_ui(ctx);
         self.show_snackbars(ctx);
     }
 }
@@ -1151,12 +2369,17 @@ struct ExampleListEntry {
     id: String,
     title: String,
     note: Option<String>,
+    thumbnail: Option<PathBuf>,
 }
 
 #[derive(Clone)]
 struct ConsoleEntry {
     kind: ConsoleKind,
     message: String,
+    /// How many times the entry was pushed in a row; see `push_console_entry`.
+    repeats: u32,
+    /// The example the entry came from; see `ExplorerApp::console_owner`.
+    example_id: Option<String>,
 }
 
 impl ConsoleEntry {
@@ -1164,6 +2387,24 @@ impl ConsoleEntry {
         Self {
             kind,
             message: message.into(),
+            repeats: 1,
+            example_id: None,
+        }
+    }
+
+    /// The message as copied or exported, with the repeat count when there is one.
+    fn text(&self) -> String {
+        if self.repeats > 1 {
+            format!("{} (×{})", self.message, self.repeats)
+        } else {
+            self.message.clone()
+        }
+    }
+
+    fn repeat_badge_ui(&self, ui: &mut egui::Ui) {
+        if self.repeats > 1 {
+            ui.label(RichText::new(format!("×{}", self.repeats)).small().strong())
+                .on_hover_text(format!("Repeated {} times in a row", self.repeats));
         }
     }
 
@@ -1196,9 +2437,13 @@ impl ConsoleEntry {
 enum ConsolePane {
     Console,
     Tests,
+    Generators,
+    Tasks,
+    Workers,
+    Canvas,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 enum ConsoleKind {
     Info,
     Stdout,
@@ -1223,8 +2468,19 @@ impl ConsoleKind {
 
 struct ExecutionSummary {
     duration: Duration,
+    /// Compile and execute times, when the run got far enough to measure them.
+    phases: Option<(Duration, Duration)>,
     return_value: Option<String>,
+    /// The return value as a JSON tree; see [`runtime::ExecutionOutput::value_as_json`].
+    value: Option<serde_json::Value>,
     succeeded: bool,
+    clipboard_accessed: bool,
+    generator_trace: Vec<runtime::generators::GeneratorEvent>,
+    peak_memory: Option<usize>,
+    cache_hit: bool,
+    random_seed: Option<u64>,
+    /// The run's result as JSON, for successful runs; see [`runtime::ExecutionOutput::to_json`].
+    export: Option<serde_json::Value>,
 }
 
 struct Snackbar {
@@ -1276,6 +2532,16 @@ fn describe_change(change: &examples::ScriptChange) -> String {
             current.is_some(),
             Some(suite_id),
         ),
+        examples::ScriptChangeKind::MetadataUpdated { previous, current } => change_action(
+            "metadata",
+            change,
+            previous.is_some(),
+            current.is_some(),
+            None,
+        ),
+        examples::ScriptChangeKind::ExampleRenamed { previous_id, .. } => {
+            return format!("Example '{previous_id}' renamed to '{}'", change.example_id);
+        }
     };
     action
 }
@@ -1312,6 +2578,69 @@ fn change_action(
     }
 }
 
+fn example_session_id(example_id: &str) -> String {
+    format!("example:{example_id}")
+}
+
+/// Splits an arguments line on whitespace; double quotes group words containing spaces.
+fn split_args(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_arg = false;
+    for c in line.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_arg = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_arg {
+                    args.push(std::mem::take(&mut current));
+                    has_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_arg = true;
+            }
+        }
+    }
+    if has_arg {
+        args.push(current);
+    }
+    args
+}
+
+/// The inverse of [`split_args`], quoting arguments that contain whitespace.
+fn join_args(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| {
+            if arg.is_empty() || arg.contains(char::is_whitespace) {
+                format!("\"{arg}\"")
+            } else {
+                arg.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Renders a run time in the largest unit that keeps it readable, e.g. `840 µs`,
+/// `12.3 ms`, or `1.25 s`.
+fn format_duration(duration: Duration) -> String {
+    let micros = duration.as_secs_f64() * 1_000_000.0;
+    if micros < 1_000.0 {
+        format!("{micros:.0} µs")
+    } else if micros < 10_000.0 {
+        format!("{:.2} ms", micros / 1_000.0)
+    } else if micros < 1_000_000.0 {
+        format!("{:.1} ms", micros / 1_000.0)
+    } else {
+        format!("{:.2} s", micros / 1_000_000.0)
+    }
+}
+
 fn format_elapsed(duration: Duration) -> String {
     if duration.as_secs() >= 3600 {
         let hours = duration.as_secs() / 3600;
@@ -0,0 +1,206 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use eframe::egui;
+use egui::{Color32, RichText};
+use egui_extras::syntax_highlighting;
+
+use super::{ConsoleEntry, ExplorerApp, SnackbarKind, format_duration};
+use crate::{
+    examples::{
+        Example,
+        notebook::{Notebook, NotebookCell},
+    },
+    runtime,
+};
+
+/// Outputs of the code cells that have been run, keyed by cell index.
+#[derive(Default)]
+pub(super) struct NotebookSession {
+    outputs: BTreeMap<usize, CellOutput>,
+}
+
+struct CellOutput {
+    return_value: Option<String>,
+    stdout: String,
+    error: Option<String>,
+    duration: Duration,
+}
+
+impl ExplorerApp {
+    /// Renders a notebook example as a sequence of markdown and runnable code cells.
+    pub(super) fn notebook_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        ctx: &egui::Context,
+        example: &Example,
+    ) {
+        let Some(notebook) = &example.notebook else {
+            return;
+        };
+        let id = example.metadata.id.as_str();
+
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Notebook").strong());
+            if ui.button("▶ Run all").clicked() {
+                self.run_all_notebook_cells(id, notebook);
+            }
+            if ui
+                .button("Restart session")
+                .on_hover_text("Clears cell outputs and everything defined by earlier cells")
+                .clicked()
+            {
+                self.restart_notebook_session(id);
+            }
+        });
+        ui.label(
+            RichText::new("Cells share one session, so run them in order from the top.").small(),
+        );
+        ui.add_space(6.0);
+
+        for (index, cell) in notebook.cells.iter().enumerate() {
+            match cell {
+                NotebookCell::Markdown(text) => markdown_ui(ui, text),
+                NotebookCell::Code(code) => {
+                    let mut run = false;
+                    ui.group(|ui| {
+                        ui.horizontal(|ui| {
+                            run = ui.button("▶ Run").clicked();
+                            ui.label(RichText::new(format!("[{index}]")).monospace().weak());
+                        });
+                        code_cell_ui(ui, ctx, code);
+                        if let Some(output) = self
+                            .notebook_sessions
+                            .get(id)
+                            .and_then(|session| session.outputs.get(&index))
+                        {
+                            cell_output_ui(ui, output);
+                        }
+                    });
+                    if run {
+                        self.run_notebook_cell(id, index, code);
+                    }
+                }
+            }
+            ui.add_space(6.0);
+        }
+    }
+
+    fn run_notebook_cell(&mut self, example_id: &str, index: usize, code: &str) -> bool {
+        if self.runtime_busy() {
+            return false;
+        }
+        let output =
+            match runtime::RUNTIME.execute_in_session(&notebook_session_id(example_id), code) {
+                Ok(output) => CellOutput {
+                    return_value: output.return_value,
+                    stdout: output.stdout,
+                    error: None,
+                    duration: output.duration,
+                },
+                Err(error) => CellOutput {
+                    return_value: None,
+                    stdout: String::new(),
+                    error: Some(error.to_string()),
+                    duration: Duration::default(),
+                },
+            };
+        let succeeded = output.error.is_none();
+        if let Some(error) = &output.error {
+            self.push_console_entry(ConsoleEntry::error(format!(
+                "Cell [{index}] failed: {error}"
+            )));
+        }
+        self.notebook_sessions
+            .entry(example_id.to_string())
+            .or_default()
+            .outputs
+            .insert(index, output);
+        succeeded
+    }
+
+    fn run_all_notebook_cells(&mut self, example_id: &str, notebook: &Notebook) {
+        if self.runtime_busy() {
+            return;
+        }
+        self.restart_notebook_session(example_id);
+        for (index, code) in notebook.code_cells() {
+            if !self.run_notebook_cell(example_id, index, code) {
+                self.push_snackbar("Notebook stopped at a failing cell", SnackbarKind::Error);
+                return;
+            }
+        }
+        self.push_snackbar("Notebook ran successfully", SnackbarKind::Success);
+    }
+
+    fn restart_notebook_session(&mut self, example_id: &str) {
+        if self.runtime_busy() {
+            return;
+        }
+        self.notebook_sessions.remove(example_id);
+        if let Err(error) = runtime::RUNTIME.reset_session(&notebook_session_id(example_id)) {
+            self.push_console_entry(ConsoleEntry::error(format!(
+                "Failed to reset the session: {error}"
+            )));
+        }
+    }
+}
+
+fn notebook_session_id(example_id: &str) -> String {
+    format!("notebook:{example_id}")
+}
+
+fn code_cell_ui(ui: &mut egui::Ui, ctx: &egui::Context, code: &str) {
+    let theme = syntax_highlighting::CodeTheme::from_memory(ctx, ui.style());
+    syntax_highlighting::code_view_ui(ui, &theme, code.trim_end(), "koto");
+}
+
+fn cell_output_ui(ui: &mut egui::Ui, output: &CellOutput) {
+    ui.separator();
+    if !output.stdout.is_empty() {
+        ui.label(RichText::new(output.stdout.trim_end()).monospace());
+    }
+    if let Some(value) = &output.return_value {
+        ui.label(RichText::new(format!("⇒ {value}")).monospace().strong());
+    }
+    if let Some(error) = &output.error {
+        ui.colored_label(Color32::from_rgb(220, 80, 80), error);
+    } else {
+        ui.label(
+            RichText::new(format_duration(output.duration))
+                .small()
+                .weak(),
+        );
+    }
+}
+
+/// Renders the small subset of markdown used by notebooks and docs: headings, bullets, fenced
+/// blocks, and paragraphs.
+pub(super) fn markdown_ui(ui: &mut egui::Ui, text: &str) {
+    let mut in_fence = false;
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            ui.label(RichText::new(line).monospace());
+        } else if let Some(heading) = trimmed.strip_prefix("# ") {
+            ui.heading(heading);
+        } else if let Some(heading) = trimmed
+            .strip_prefix("## ")
+            .or_else(|| trimmed.strip_prefix("### "))
+        {
+            ui.label(RichText::new(heading).strong().size(16.0));
+        } else if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+        {
+            ui.label(format!("• {item}"));
+        } else if trimmed.is_empty() {
+            ui.add_space(4.0);
+        } else {
+            ui.label(trimmed);
+        }
+    }
+}
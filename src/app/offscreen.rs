@@ -0,0 +1,203 @@
+//! Draws part of the UI into an image without a window or a GPU, for view exports.
+//!
+//! A separate egui context lays the contents out with the window's options, so the theme and
+//! zoom match, and the triangles it produces are filled on the CPU. Paint callbacks, which
+//! need the GPU, are left out.
+
+use std::collections::HashMap;
+
+use eframe::egui;
+use egui::{Color32, ColorImage, Pos2, Rect, TextureId, Vec2, epaint};
+
+/// Frames laid out before the one that is drawn, so that sizes measured while laying out the
+/// first frame are in place.
+const WARMUP_FRAMES: usize = 1;
+
+/// Lays out `add_contents` in a `size` points screen, as `window` would, and returns what it
+/// draws at the window's pixels per point.
+pub(super) fn render(
+    window: &egui::Context,
+    size: Vec2,
+    mut add_contents: impl FnMut(&egui::Context),
+) -> ColorImage {
+    let ctx = egui::Context::default();
+    ctx.options_mut(|options| *options = window.options(Clone::clone));
+    let mut input = egui::RawInput {
+        screen_rect: Some(Rect::from_min_size(Pos2::ZERO, size)),
+        system_theme: Some(window.theme()),
+        ..Default::default()
+    };
+    if let Some(viewport) = input.viewports.get_mut(&egui::ViewportId::ROOT) {
+        viewport.native_pixels_per_point = Some(window.pixels_per_point() / window.zoom_factor());
+    }
+
+    let mut textures = HashMap::new();
+    for _ in 0..WARMUP_FRAMES {
+        let output = ctx.run(input.clone(), &mut add_contents);
+        apply_textures(&mut textures, output.textures_delta);
+    }
+    let output = ctx.run(input, &mut add_contents);
+    apply_textures(&mut textures, output.textures_delta);
+
+    let pixels_per_point = output.pixels_per_point;
+    let [width, height] = [size.x, size.y].map(|side| (side * pixels_per_point).round() as usize);
+    let mut image = ColorImage::filled([width, height], ctx.style().visuals.panel_fill);
+    for primitive in ctx.tessellate(output.shapes, pixels_per_point) {
+        if let epaint::Primitive::Mesh(mesh) = &primitive.primitive {
+            let clip = Rect::from_min_max(
+                (primitive.clip_rect.min.to_vec2() * pixels_per_point).to_pos2(),
+                (primitive.clip_rect.max.to_vec2() * pixels_per_point).to_pos2(),
+            );
+            fill_mesh(
+                &mut image,
+                mesh,
+                clip,
+                textures.get(&mesh.texture_id),
+                pixels_per_point,
+            );
+        }
+    }
+    image
+}
+
+/// Applies new and updated textures; ones the context frees are kept until rendering ends.
+fn apply_textures(textures: &mut HashMap<TextureId, ColorImage>, delta: epaint::TexturesDelta) {
+    for (id, delta) in delta.set {
+        let egui::ImageData::Color(patch) = &delta.image;
+        match delta.pos {
+            None => {
+                textures.insert(id, (**patch).clone());
+            }
+            Some([x, y]) => {
+                let Some(texture) = textures.get_mut(&id) else {
+                    continue;
+                };
+                let width = texture.width();
+                for row in 0..patch.height() {
+                    for column in 0..patch.width() {
+                        if let Some(pixel) = texture.pixels.get_mut((y + row) * width + x + column)
+                        {
+                            *pixel = patch[(column, row)];
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Fills the triangles of `mesh` that fall inside `clip`, in pixels, blending them over what
+/// is already in `image`.
+fn fill_mesh(
+    image: &mut ColorImage,
+    mesh: &epaint::Mesh,
+    clip: Rect,
+    texture: Option<&ColorImage>,
+    pixels_per_point: f32,
+) {
+    let [width, height] = image.size;
+    let clip = clip.intersect(Rect::from_min_size(
+        Pos2::ZERO,
+        Vec2::new(width as f32, height as f32),
+    ));
+    if !clip.is_positive() {
+        return;
+    }
+    for triangle in mesh.indices.chunks_exact(3) {
+        let Some(vertices) = triangle
+            .iter()
+            .map(|&index| mesh.vertices.get(index as usize))
+            .collect::<Option<Vec<_>>>()
+        else {
+            continue;
+        };
+        let [a, b, c] = [vertices[0], vertices[1], vertices[2]];
+        let [pa, pb, pc] = [a.pos, b.pos, c.pos].map(|pos| pos * pixels_per_point);
+        let area = edge(pa, pb, pc);
+        if area == 0.0 || !area.is_finite() {
+            continue;
+        }
+        let bounds = Rect::from_points(&[pa, pb, pc]).intersect(clip);
+        if !bounds.is_positive() {
+            continue;
+        }
+        for y in bounds.min.y.floor() as usize..bounds.max.y.ceil() as usize {
+            for x in bounds.min.x.floor() as usize..bounds.max.x.ceil() as usize {
+                let p = Pos2::new(x as f32 + 0.5, y as f32 + 0.5);
+                if !clip.contains(p) {
+                    continue;
+                }
+                // Dividing by the signed area makes the weights positive inside the triangle
+                // whichever way round its corners are.
+                let weights =
+                    [edge(pb, pc, p), edge(pc, pa, p), edge(pa, pb, p)].map(|weight| weight / area);
+                if weights.iter().any(|&weight| weight < 0.0) {
+                    continue;
+                }
+                let color = interpolate([a.color, b.color, c.color], weights);
+                let texel = texture.map_or(Color32::WHITE, |texture| {
+                    let uv = (a.uv.to_vec2() * weights[0]
+                        + b.uv.to_vec2() * weights[1]
+                        + c.uv.to_vec2() * weights[2])
+                        .to_pos2();
+                    sample(texture, uv)
+                });
+                let pixel = &mut image.pixels[y * width + x];
+                *pixel = blend(modulate(color, texel), *pixel);
+            }
+        }
+    }
+}
+
+fn edge(a: Pos2, b: Pos2, p: Pos2) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+fn interpolate(colors: [Color32; 3], weights: [f32; 3]) -> Color32 {
+    let channel = |index: usize| {
+        let value: f32 = colors
+            .iter()
+            .zip(weights)
+            .map(|(color, weight)| color.to_array()[index] as f32 * weight)
+            .sum();
+        value.round().clamp(0.0, 255.0) as u8
+    };
+    Color32::from_rgba_premultiplied(channel(0), channel(1), channel(2), channel(3))
+}
+
+/// The texel nearest to `uv`.
+fn sample(texture: &ColorImage, uv: Pos2) -> Color32 {
+    let [width, height] = texture.size;
+    if width == 0 || height == 0 {
+        return Color32::TRANSPARENT;
+    }
+    let x = ((uv.x * width as f32) as usize).min(width - 1);
+    let y = ((uv.y * height as f32) as usize).min(height - 1);
+    texture.pixels[y * width + x]
+}
+
+fn modulate(color: Color32, texel: Color32) -> Color32 {
+    let [r, g, b, a] = color.to_array();
+    let [tr, tg, tb, ta] = texel.to_array();
+    let channel = |c: u8, t: u8| ((c as u16 * t as u16 + 127) / 255) as u8;
+    Color32::from_rgba_premultiplied(
+        channel(r, tr),
+        channel(g, tg),
+        channel(b, tb),
+        channel(a, ta),
+    )
+}
+
+/// Draws premultiplied `source` over `target`.
+fn blend(source: Color32, target: Color32) -> Color32 {
+    let [sr, sg, sb, sa] = source.to_array();
+    let [tr, tg, tb, ta] = target.to_array();
+    let keep = 255 - sa as u16;
+    let channel = |s: u8, t: u8| (s as u16 + (t as u16 * keep + 127) / 255).min(255) as u8;
+    Color32::from_rgba_premultiplied(
+        channel(sr, tr),
+        channel(sg, tg),
+        channel(sb, tb),
+        channel(sa, ta),
+    )
+}
@@ -0,0 +1,126 @@
+use std::sync::mpsc::{self, Receiver};
+
+use eframe::egui;
+use egui::RichText;
+use serde_json::Value as JsonValue;
+
+use super::{ConsoleEntry, ConsoleKind, ExplorerApp};
+use crate::runtime::{
+    self,
+    observer::{ChannelObserver, ExecutionEvent, OutputStream},
+    replay::OutputRecorder,
+};
+
+/// Subscribes to the shared runtime so that output from every run (examples, notebook cells,
+/// inline evaluation) reaches the console. Output from background runs streams in while the
+/// script is still executing.
+pub(super) fn subscribe_to_runtime() -> Receiver<ExecutionEvent> {
+    let (sender, receiver) = mpsc::channel();
+    runtime::RUNTIME.add_observer(ChannelObserver::new(sender));
+    receiver
+}
+
+/// Registers a recorder that timestamps the output of every run for the replay window.
+pub(super) fn record_runtime_output() -> OutputRecorder {
+    let recorder = OutputRecorder::new();
+    runtime::RUNTIME.add_observer(recorder.clone());
+    recorder
+}
+
+impl ExplorerApp {
+    /// Moves streamed output into the console, merging consecutive chunks from the same stream.
+    pub(super) fn poll_execution_events(&mut self) {
+        let mut pending: Option<(OutputStream, String)> = None;
+        while let Ok(event) = self.execution_events.try_recv() {
+            // Repeated runs would print the same output over and over, and profile
+            // comparisons show their output in their own window.
+            if self.is_repeating_runs() || self.is_comparing_profiles() {
+                continue;
+            }
+            let (stream, chunk) = match event {
+                ExecutionEvent::Output(stream, chunk) => (stream, chunk),
+                ExecutionEvent::WorkerOutput {
+                    worker,
+                    stream,
+                    text,
+                } => {
+                    self.push_worker_output(worker, stream, text);
+                    continue;
+                }
+                ExecutionEvent::Frame(frame) => {
+                    self.show_canvas_frame(frame);
+                    continue;
+                }
+                _ => continue,
+            };
+            match &mut pending {
+                Some((current, text)) if *current == stream => text.push_str(&chunk),
+                _ => {
+                    if let Some((stream, text)) = pending.replace((stream, chunk)) {
+                        self.push_output_entry(stream, text);
+                    }
+                }
+            }
+        }
+        if let Some((stream, text)) = pending {
+            self.push_output_entry(stream, text);
+        }
+    }
+
+    /// While a script is running, output continues the previous entry from the same stream so
+    /// the console reads like a terminal instead of one entry per frame.
+    fn push_output_entry(&mut self, stream: OutputStream, text: String) {
+        let kind = match stream {
+            OutputStream::Stdout => ConsoleKind::Stdout,
+            OutputStream::Stderr => ConsoleKind::Stderr,
+        };
+        if self.is_script_running() {
+            if let Some(last) = self.console_entries.last_mut() {
+                if last.kind == kind && last.repeats == 1 {
+                    last.message.push_str(&text);
+                    return;
+                }
+            }
+        }
+        self.push_console_entry(ConsoleEntry::new(kind, text));
+    }
+}
+
+/// Whether `value` is a non-empty object or array, i.e. worth showing as a tree.
+pub(super) fn is_nested(value: &JsonValue) -> bool {
+    match value {
+        JsonValue::Object(entries) => !entries.is_empty(),
+        JsonValue::Array(items) => !items.is_empty(),
+        _ => false,
+    }
+}
+
+/// Shows a structured value as collapsible rows, one per map entry or list item.
+pub(super) fn value_tree_ui(ui: &mut egui::Ui, label: &str, value: &JsonValue) {
+    match value {
+        JsonValue::Object(entries) if !entries.is_empty() => {
+            egui::CollapsingHeader::new(format!("{label}: {{{} entries}}", entries.len()))
+                .default_open(true)
+                .show(ui, |ui| {
+                    for (key, value) in entries {
+                        value_tree_ui(ui, key, value);
+                    }
+                });
+        }
+        JsonValue::Array(items) if !items.is_empty() => {
+            egui::CollapsingHeader::new(format!("{label}: [{} items]", items.len()))
+                .default_open(items.len() <= 20)
+                .show(ui, |ui| {
+                    for (index, item) in items.iter().enumerate() {
+                        value_tree_ui(ui, &index.to_string(), item);
+                    }
+                });
+        }
+        scalar => {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(format!("{label}:")).weak());
+                ui.monospace(scalar.to_string());
+            });
+        }
+    }
+}
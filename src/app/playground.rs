@@ -0,0 +1,191 @@
+use std::time::Duration;
+
+use eframe::egui;
+use egui::RichText;
+
+use super::{ExplorerApp, SnackbarKind};
+use crate::{
+    runtime::{self, ExecutionOutput, RunOptions, prelude::PreludeSource},
+    tasks::{self, Task},
+};
+
+const CALL_REPAINT_INTERVAL: Duration = Duration::from_millis(50);
+/// Host calls are smoke tests, so a hung binding shouldn't block the runtime for long.
+const CALL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Calls registered host functions with arguments typed into a form, so plugin authors can
+/// try their bindings without writing a script.
+#[derive(Default)]
+pub(super) struct ModulePlayground {
+    /// Paths of callable host bindings, e.g. `host.elapsed`.
+    functions: Vec<String>,
+    selected: String,
+    /// Koto expressions, one per argument.
+    args: Vec<String>,
+    call: Option<Task<ExecutionOutput>>,
+    result: Option<Result<String, String>>,
+}
+
+impl ExplorerApp {
+    /// Opens the playground, preselecting `function` when given.
+    pub(super) fn open_module_playground(&mut self, function: Option<String>) {
+        if self.is_script_running() {
+            self.push_snackbar(
+                "Wait for the running script to finish before opening the playground",
+                SnackbarKind::Info,
+            );
+            return;
+        }
+        let functions = runtime::RUNTIME
+            .prelude_entries()
+            .map(|entries| {
+                let mut functions = Vec::new();
+                for entry in entries
+                    .iter()
+                    .filter(|entry| entry.source == PreludeSource::Host)
+                {
+                    if entry.callable {
+                        functions.push(entry.name.clone());
+                    }
+                    functions.extend(
+                        entry
+                            .members
+                            .iter()
+                            .filter(|member| member.callable)
+                            .map(|member| format!("{}.{}", entry.name, member.name)),
+                    );
+                }
+                functions
+            })
+            .unwrap_or_default();
+        let selected = function
+            .or_else(|| {
+                self.module_playground
+                    .take()
+                    .map(|playground| playground.selected)
+            })
+            .filter(|name| functions.contains(name))
+            .or_else(|| functions.first().cloned())
+            .unwrap_or_default();
+        self.module_playground = Some(ModulePlayground {
+            functions,
+            selected,
+            ..Default::default()
+        });
+    }
+
+    pub(super) fn module_playground_ui(&mut self, ctx: &egui::Context) {
+        let Some(playground) = &mut self.module_playground else {
+            return;
+        };
+        if let Some(result) = playground.call.as_ref().and_then(|call| call.poll()) {
+            playground.call = None;
+            playground.result = Some(
+                result
+                    .map(|output| {
+                        let mut text = output.stdout;
+                        text.push_str(&output.stderr);
+                        let value = output.return_value.unwrap_or_else(|| "null".into());
+                        if text.is_empty() {
+                            value
+                        } else {
+                            format!("{text}\n→ {value}")
+                        }
+                    })
+                    .map_err(|error| error.to_string()),
+            );
+        } else if playground.call.is_some() {
+            ctx.request_repaint_after(CALL_REPAINT_INTERVAL);
+        }
+
+        let mut open = true;
+        let mut call = false;
+        egui::Window::new("Module playground")
+            .open(&mut open)
+            .default_width(380.0)
+            .show(ctx, |ui| {
+                if playground.functions.is_empty() {
+                    ui.label("No host functions are registered.");
+                    return;
+                }
+                egui::ComboBox::from_label("Function")
+                    .selected_text(RichText::new(&playground.selected).monospace())
+                    .show_ui(ui, |ui| {
+                        for function in &playground.functions {
+                            ui.selectable_value(
+                                &mut playground.selected,
+                                function.clone(),
+                                RichText::new(function).monospace(),
+                            );
+                        }
+                    });
+                ui.add_space(6.0);
+                ui.label(
+                    RichText::new("Arguments are Koto expressions, e.g. 42 or 'text'").small(),
+                );
+                let mut remove = None;
+                for (index, arg) in playground.args.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{}", index + 1));
+                        ui.add(egui::TextEdit::singleline(arg).code_editor());
+                        if ui
+                            .small_button("✖")
+                            .on_hover_text("Remove argument")
+                            .clicked()
+                        {
+                            remove = Some(index);
+                        }
+                    });
+                }
+                if let Some(index) = remove {
+                    playground.args.remove(index);
+                }
+                if ui.small_button("Add argument").clicked() {
+                    playground.args.push(String::new());
+                }
+                ui.add_space(6.0);
+                call = ui
+                    .add_enabled(playground.call.is_none(), egui::Button::new("Call"))
+                    .clicked();
+                if playground.call.is_some() {
+                    ui.spinner();
+                }
+                match &playground.result {
+                    Some(Ok(output)) => {
+                        ui.label(RichText::new(output).monospace());
+                    }
+                    Some(Err(error)) => {
+                        ui.colored_label(ui.visuals().error_fg_color, error);
+                    }
+                    None => {}
+                }
+            });
+
+        if call && !self.runtime_busy() {
+            self.call_playground_function();
+        }
+        if !open {
+            self.module_playground = None;
+        }
+    }
+
+    fn call_playground_function(&mut self) {
+        let Some(playground) = &mut self.module_playground else {
+            return;
+        };
+        let args: Vec<&str> = playground.args.iter().map(String::as_str).collect();
+        match runtime::RUNTIME.host_call_script(&playground.selected, &args) {
+            Ok(script) => {
+                playground.result = None;
+                let label = format!("Calling {}", playground.selected);
+                playground.call = Some(tasks::spawn_quietly(label, move |task| {
+                    let options = RunOptions::new()
+                        .timeout(Some(CALL_TIMEOUT))
+                        .cancel_flag(task.cancel_flag());
+                    runtime::RUNTIME.execute(&script, &options)
+                }));
+            }
+            Err(error) => playground.result = Some(Err(error.to_string())),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use eframe::egui;
+use egui::RichText;
+
+use super::ExplorerApp;
+use crate::runtime::{
+    self,
+    prelude::{PreludeEntry, PreludeSource},
+};
+
+/// State of the prelude inspector window while it is open.
+#[derive(Default)]
+pub(super) struct PreludeInspector {
+    entries: Vec<PreludeEntry>,
+    filter: String,
+    error: Option<String>,
+}
+
+impl ExplorerApp {
+    pub(super) fn open_prelude_inspector(&mut self) {
+        let mut inspector = PreludeInspector::default();
+        self.load_prelude_entries(&mut inspector);
+        self.prelude_inspector = Some(inspector);
+    }
+
+    /// The prelude can only be read while no script holds the VM.
+    fn load_prelude_entries(&mut self, inspector: &mut PreludeInspector) {
+        if self.is_script_running() {
+            inspector.error = Some("A script is running; refresh once it has finished".into());
+            return;
+        }
+        match runtime::RUNTIME.prelude_entries() {
+            Ok(entries) => {
+                inspector.entries = entries;
+                inspector.error = None;
+            }
+            Err(error) => inspector.error = Some(error.to_string()),
+        }
+    }
+
+    /// Lists what scripts can call without importing anything.
+    pub(super) fn prelude_inspector_ui(&mut self, ctx: &egui::Context) {
+        let Some(mut inspector) = self.prelude_inspector.take() else {
+            return;
+        };
+        let mut open = true;
+        let mut refresh = false;
+        let mut try_call = None;
+        egui::Window::new("Prelude inspector")
+            .open(&mut open)
+            .default_width(420.0)
+            .default_height(480.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut inspector.filter)
+                            .hint_text("Filter bindings"),
+                    );
+                    refresh = ui.button("Refresh").clicked();
+                });
+                if let Some(error) = &inspector.error {
+                    ui.colored_label(ui.visuals().warn_fg_color, error);
+                }
+                ui.separator();
+                let filter = inspector.filter.trim().to_lowercase();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for entry in &inspector.entries {
+                        if !matches_filter(entry, &filter) {
+                            continue;
+                        }
+                        prelude_entry_ui(ui, entry, &filter, &mut try_call);
+                    }
+                });
+            });
+        if refresh {
+            self.load_prelude_entries(&mut inspector);
+        }
+        if open {
+            self.prelude_inspector = Some(inspector);
+        }
+        if try_call.is_some() {
+            self.open_module_playground(try_call);
+        }
+    }
+}
+
+fn matches_filter(entry: &PreludeEntry, filter: &str) -> bool {
+    filter.is_empty()
+        || entry.name.to_lowercase().contains(filter)
+        || entry
+            .members
+            .iter()
+            .any(|member| member.name.to_lowercase().contains(filter))
+}
+
+/// Host functions get a "Try" button that sets `try_call` to open them in the module
+/// playground.
+fn prelude_entry_ui(
+    ui: &mut egui::Ui,
+    entry: &PreludeEntry,
+    filter: &str,
+    try_call: &mut Option<String>,
+) {
+    let is_host = entry.source == PreludeSource::Host;
+    let heading = format!("{}  ·  {}", entry.name, entry.type_name);
+    let header = RichText::new(heading).monospace();
+    let body = |ui: &mut egui::Ui| {
+        ui.label(RichText::new(entry.source.label()).small().weak());
+        ui.label(entry.description);
+        if is_host && entry.callable && ui.small_button("Try").clicked() {
+            *try_call = Some(entry.name.clone());
+        }
+        for member in &entry.members {
+            ui.horizontal(|ui| {
+                ui.label(
+                    RichText::new(format!(
+                        "{}.{}: {}",
+                        entry.name, member.name, member.type_name
+                    ))
+                    .monospace(),
+                );
+                if is_host && member.callable && ui.small_button("Try").clicked() {
+                    *try_call = Some(format!("{}.{}", entry.name, member.name));
+                }
+            });
+        }
+    };
+    if entry.members.is_empty() {
+        ui.label(header);
+        ui.indent(("prelude", &entry.name), body);
+    } else {
+        egui::CollapsingHeader::new(header)
+            .id_salt(("prelude", &entry.name))
+            .open((!filter.is_empty()).then_some(true))
+            .show(ui, body);
+    }
+}
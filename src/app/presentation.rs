@@ -0,0 +1,227 @@
+use eframe::egui;
+use egui::{Key, RichText, ViewportCommand};
+use egui_extras::syntax_highlighting;
+
+use super::{ConsoleEntry, ConsoleKind, ExplorerApp, notebook::markdown_ui};
+use crate::examples::Example;
+
+const PRESENTATION_ZOOM: f32 = 1.6;
+
+/// State kept while the explorer is in presentation mode.
+pub(super) struct PresentationState {
+    previous_zoom: f32,
+    /// The full `docs.md` of the example on screen, loaded when the slide changes.
+    docs: Option<(String, String)>,
+    output: Vec<ConsoleEntry>,
+}
+
+impl ExplorerApp {
+    pub(super) fn is_presenting(&self) -> bool {
+        self.presentation.is_some()
+    }
+
+    pub(super) fn toggle_presentation(&mut self, ctx: &egui::Context) {
+        match self.presentation.take() {
+            Some(state) => {
+                ctx.set_zoom_factor(state.previous_zoom);
+                ctx.send_viewport_cmd(ViewportCommand::Fullscreen(false));
+            }
+            None => {
+                self.presentation = Some(PresentationState {
+                    previous_zoom: ctx.zoom_factor(),
+                    docs: None,
+                    output: Vec::new(),
+                });
+                ctx.set_zoom_factor(PRESENTATION_ZOOM);
+                ctx.send_viewport_cmd(ViewportCommand::Fullscreen(true));
+            }
+        }
+    }
+
+    /// The visible examples in sidebar order, which presentation mode steps through.
+    fn learning_path(&self) -> Vec<String> {
+        let mut path: Vec<String> = Vec::new();
+        for (_, entries) in self.grouped_examples() {
+            for entry in entries {
+                if !path.contains(&entry.id) {
+                    path.push(entry.id);
+                }
+            }
+        }
+        path
+    }
+
+    fn step_learning_path(&mut self, step: isize) {
+        let path = self.learning_path();
+        if path.is_empty() {
+            return;
+        }
+        let current = self
+            .selected_example_id
+            .as_ref()
+            .and_then(|id| path.iter().position(|entry| entry == id));
+        let next = match current {
+            Some(index) => (index as isize + step).clamp(0, path.len() as isize - 1) as usize,
+            None => 0,
+        };
+        self.select_example(&path[next]);
+    }
+
+    fn run_presented_example(&mut self) {
+        if let Some(state) = self.presentation.as_mut() {
+            state.output.clear();
+        }
+        self.run_selected_example();
+    }
+
+    /// Shows the console entries added since `start` on the current slide.
+    pub(super) fn capture_presentation_output(&mut self, start: usize) {
+        let output = self.console_entries[start.min(self.console_entries.len())..]
+            .iter()
+            .filter(|entry| entry.kind != ConsoleKind::Log)
+            .cloned()
+            .collect();
+        if let Some(state) = self.presentation.as_mut() {
+            state.output = output;
+        }
+    }
+
+    /// Handles presentation shortcuts: arrows and Page Up/Down to navigate, R to run, and
+    /// Escape (or F5) to leave.
+    fn presentation_input(&mut self, ctx: &egui::Context) {
+        let (next, previous, run, exit) = ctx.input(|input| {
+            (
+                input.key_pressed(Key::ArrowRight)
+                    || input.key_pressed(Key::PageDown)
+                    || input.key_pressed(Key::Space),
+                input.key_pressed(Key::ArrowLeft) || input.key_pressed(Key::PageUp),
+                input.key_pressed(Key::R),
+                input.key_pressed(Key::Escape) || input.key_pressed(Key::F5),
+            )
+        });
+        if exit {
+            self.toggle_presentation(ctx);
+            return;
+        }
+        if next || previous {
+            self.step_learning_path(if next { 1 } else { -1 });
+            if let Some(state) = self.presentation.as_mut() {
+                state.output.clear();
+            }
+        }
+        if run {
+            self.run_presented_example();
+        }
+    }
+
+    pub(super) fn presentation_ui(&mut self, ctx: &egui::Context) {
+        self.presentation_input(ctx);
+        let Some(example) = self.selected_example().cloned() else {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.label("Select an example before presenting. Press Esc to leave.");
+            });
+            return;
+        };
+        self.load_presentation_docs(&example);
+
+        let path = self.learning_path();
+        let position = path
+            .iter()
+            .position(|id| *id == example.metadata.id)
+            .map_or(0, |index| index + 1);
+
+        egui::TopBottomPanel::bottom("presentation_footer").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("{position} / {}", path.len()));
+                ui.separator();
+                ui.label(
+                    RichText::new("← → navigate · R run · Esc exit")
+                        .small()
+                        .weak(),
+                );
+            });
+        });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            egui::ScrollArea::vertical()
+                .id_salt("presentation")
+                .show(ui, |ui| {
+                    ui.heading(RichText::new(&example.metadata.title).size(32.0));
+                    ui.label(&example.metadata.description);
+                    ui.add_space(12.0);
+
+                    let docs = self
+                        .presentation
+                        .as_ref()
+                        .and_then(|state| state.docs.as_ref())
+                        .map(|(_, docs)| docs.clone());
+                    if let Some(docs) = docs {
+                        markdown_ui(ui, &docs);
+                        ui.add_space(12.0);
+                    }
+
+                    if example.notebook.is_some() {
+                        self.notebook_ui(ui, ctx, &example);
+                    } else {
+                        let theme = syntax_highlighting::CodeTheme::from_memory(ctx, ui.style());
+                        ui.group(|ui| {
+                            syntax_highlighting::code_view_ui(
+                                ui,
+                                &theme,
+                                example.script.trim_end(),
+                                "koto",
+                            );
+                        });
+                    }
+
+                    let output = self
+                        .presentation
+                        .as_ref()
+                        .map(|state| state.output.clone())
+                        .unwrap_or_default();
+                    if self.is_script_running() {
+                        ui.add_space(12.0);
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Running…");
+                        });
+                    }
+                    if !output.is_empty() {
+                        ui.add_space(12.0);
+                        ui.group(|ui| {
+                            let visuals = ui.visuals().clone();
+                            for entry in &output {
+                                ui.horizontal_wrapped(|ui| {
+                                    ui.label(
+                                        RichText::new(&entry.message)
+                                            .monospace()
+                                            .color(entry.kind.color(&visuals)),
+                                    );
+                                    entry.repeat_badge_ui(ui);
+                                });
+                            }
+                        });
+                    }
+                });
+        });
+    }
+
+    fn load_presentation_docs(&mut self, example: &Example) {
+        let Some(state) = self.presentation.as_mut() else {
+            return;
+        };
+        let current = state
+            .docs
+            .as_ref()
+            .is_some_and(|(id, _)| *id == example.metadata.id);
+        if current {
+            return;
+        }
+        let docs = example
+            .docs
+            .as_ref()
+            .and_then(|docs| std::fs::read_to_string(&docs.path).ok())
+            .unwrap_or_default();
+        state.docs = Some((example.metadata.id.clone(), docs));
+    }
+}
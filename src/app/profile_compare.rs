@@ -0,0 +1,184 @@
+use std::time::Duration;
+
+use eframe::egui;
+use egui::{Color32, RichText};
+
+use super::{ExplorerApp, SnackbarKind, return_history::diff_line_ui};
+use crate::{
+    runtime::{
+        self, RunOptions,
+        profile_compare::{ProfileComparison, RunSetup, SetupRun},
+        value_diff::{DiffKind, DiffLine},
+    },
+    tasks::{self, Task},
+};
+
+const COMPARE_REPAINT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Runs the selected example under two runtime setups and shows both outputs with a diff.
+pub(super) struct ProfileCompare {
+    title: String,
+    script: String,
+    options: RunOptions,
+    left: RunSetup,
+    right: RunSetup,
+    task: Option<Task<ProfileComparison>>,
+    comparison: Option<ProfileComparison>,
+    /// The comparison's diff, worked out once when it arrives.
+    diff: Vec<DiffLine>,
+    error: Option<String>,
+}
+
+impl ExplorerApp {
+    pub(super) fn open_profile_compare(&mut self) {
+        let Some(example) = self.selected_example().cloned() else {
+            return;
+        };
+        if example.metadata.daemon {
+            self.push_snackbar(
+                "Daemon examples run until stopped, so they can't be compared",
+                SnackbarKind::Info,
+            );
+            return;
+        }
+        if self.runtime_busy() {
+            return;
+        }
+        let (script, options) = match self.prepare_example_run(&example) {
+            Ok(prepared) => prepared,
+            Err(error) => {
+                self.report_invalid_inputs(error);
+                return;
+            }
+        };
+        self.profile_compare = Some(ProfileCompare {
+            title: example.metadata.title.clone(),
+            script,
+            options,
+            left: RunSetup::ALL[0],
+            right: RunSetup::ALL[1],
+            task: None,
+            comparison: None,
+            diff: Vec::new(),
+            error: None,
+        });
+        self.start_profile_compare();
+    }
+
+    pub(super) fn is_comparing_profiles(&self) -> bool {
+        self.profile_compare
+            .as_ref()
+            .is_some_and(|compare| compare.task.is_some())
+    }
+
+    fn start_profile_compare(&mut self) {
+        let Some(compare) = &mut self.profile_compare else {
+            return;
+        };
+        if compare.task.is_some() {
+            return;
+        }
+        compare.error = None;
+        let script = compare.script.clone();
+        let options = compare.options.clone();
+        let (left, right) = (compare.left, compare.right);
+        compare.task = Some(tasks::spawn(
+            format!("Comparing '{}' across profiles", compare.title),
+            move |_| runtime::RUNTIME.compare_setups(&script, &options, left, right),
+        ));
+    }
+
+    pub(super) fn profile_compare_ui(&mut self, ctx: &egui::Context) {
+        let Some(compare) = &mut self.profile_compare else {
+            return;
+        };
+        if let Some(result) = compare.task.as_ref().and_then(|task| task.poll()) {
+            compare.task = None;
+            match result {
+                Ok(comparison) => {
+                    compare.diff = comparison.diff();
+                    compare.comparison = Some(comparison);
+                }
+                Err(error) => compare.error = Some(format!("{error:#}")),
+            }
+        } else if compare.task.is_some() {
+            ctx.request_repaint_after(COMPARE_REPAINT_INTERVAL);
+        }
+
+        let mut open = true;
+        let mut rerun = false;
+        egui::Window::new(format!("Compare profiles: {}", compare.title))
+            .open(&mut open)
+            .default_width(720.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    setup_picker_ui(ui, "compare_left", &mut compare.left);
+                    ui.label("vs");
+                    setup_picker_ui(ui, "compare_right", &mut compare.right);
+                    rerun = ui
+                        .add_enabled(compare.task.is_none(), egui::Button::new("Compare"))
+                        .clicked();
+                    if compare.task.is_some() {
+                        ui.spinner();
+                    }
+                });
+                if let Some(error) = &compare.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+                let Some(comparison) = &compare.comparison else {
+                    return;
+                };
+                ui.separator();
+
+                ui.columns(2, |columns| {
+                    setup_run_ui(&mut columns[0], "compare_left_output", &comparison.left);
+                    setup_run_ui(&mut columns[1], "compare_right_output", &comparison.right);
+                });
+                ui.separator();
+
+                if compare.diff.iter().all(|line| line.kind == DiffKind::Same) {
+                    ui.colored_label(
+                        Color32::from_rgb(120, 200, 120),
+                        "Both setups printed and returned the same",
+                    );
+                } else {
+                    ui.label(RichText::new("Differences").strong());
+                    egui::ScrollArea::vertical()
+                        .id_salt("compare_diff")
+                        .max_height(240.0)
+                        .show(ui, |ui| {
+                            ui.spacing_mut().item_spacing.y = 0.0;
+                            for line in &compare.diff {
+                                diff_line_ui(ui, line);
+                            }
+                        });
+                }
+            });
+        if rerun {
+            self.start_profile_compare();
+        }
+        if !open {
+            self.profile_compare = None;
+        }
+    }
+}
+
+fn setup_picker_ui(ui: &mut egui::Ui, id: &str, setup: &mut RunSetup) {
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(setup.label())
+        .show_ui(ui, |ui| {
+            for option in RunSetup::ALL {
+                ui.selectable_value(setup, option, option.label());
+            }
+        });
+}
+
+fn setup_run_ui(ui: &mut egui::Ui, id: &str, run: &SetupRun) {
+    ui.label(RichText::new(run.setup.label()).strong());
+    egui::ScrollArea::vertical()
+        .id_salt(id)
+        .max_height(240.0)
+        .show(ui, |ui| {
+            ui.monospace(run.transcript());
+        });
+}
@@ -0,0 +1,155 @@
+use std::time::{Duration, Instant};
+
+use eframe::egui;
+use egui::RichText;
+
+use super::{ConsoleEntry, ExplorerApp};
+use crate::runtime::{observer::OutputStream, replay::OutputRecording};
+
+const REPLAY_SPEEDS: [f32; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
+
+/// Plays the output of the last run back at the pace it was written.
+pub(super) struct OutputReplay {
+    recording: OutputRecording,
+    position: Duration,
+    speed: f32,
+    /// When playback last advanced, while playing.
+    playing_since: Option<Instant>,
+}
+
+impl OutputReplay {
+    fn new(recording: OutputRecording) -> Self {
+        Self {
+            recording,
+            position: Duration::ZERO,
+            speed: 1.0,
+            playing_since: None,
+        }
+    }
+
+    fn advance(&mut self) {
+        let Some(since) = self.playing_since else {
+            return;
+        };
+        let now = Instant::now();
+        self.position += since.elapsed().mul_f32(self.speed);
+        self.playing_since = Some(now);
+        if self.position >= self.recording.duration() {
+            self.position = self.recording.duration();
+            self.playing_since = None;
+        }
+    }
+}
+
+impl ExplorerApp {
+    /// Opens the replay of the last run's output, or explains why there is nothing to replay.
+    pub(super) fn open_output_replay(&mut self) {
+        match &self.last_recording {
+            Some(recording) if !recording.is_empty() => {
+                self.output_replay = Some(OutputReplay::new(recording.clone()));
+            }
+            _ => self.push_console_entry(ConsoleEntry::info(
+                "The last run didn't print anything to replay",
+            )),
+        }
+    }
+
+    pub(super) fn output_replay_ui(&mut self, ctx: &egui::Context) {
+        let Some(replay) = &mut self.output_replay else {
+            return;
+        };
+        replay.advance();
+        if replay.playing_since.is_some() {
+            ctx.request_repaint();
+        }
+
+        let mut open = true;
+        egui::Window::new("Output replay")
+            .open(&mut open)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                let duration = replay.recording.duration();
+                ui.horizontal(|ui| {
+                    let playing = replay.playing_since.is_some();
+                    if ui
+                        .button(if playing { "⏸ Pause" } else { "▶ Play" })
+                        .clicked()
+                    {
+                        if playing {
+                            replay.playing_since = None;
+                        } else {
+                            if replay.position >= duration {
+                                replay.position = Duration::ZERO;
+                            }
+                            replay.playing_since = Some(Instant::now());
+                        }
+                    }
+                    egui::ComboBox::from_id_salt("replay_speed")
+                        .selected_text(format!("{}×", replay.speed))
+                        .show_ui(ui, |ui| {
+                            for speed in REPLAY_SPEEDS {
+                                ui.selectable_value(&mut replay.speed, speed, format!("{speed}×"));
+                            }
+                        });
+                    ui.label(
+                        RichText::new(format!(
+                            "{:.2}s / {:.2}s",
+                            replay.position.as_secs_f64(),
+                            duration.as_secs_f64()
+                        ))
+                        .monospace(),
+                    );
+                });
+
+                let mut seconds = replay.position.as_secs_f64();
+                let response = ui.add(
+                    egui::Slider::new(&mut seconds, 0.0..=duration.as_secs_f64()).show_value(false),
+                );
+                if response.changed() {
+                    replay.position = Duration::from_secs_f64(seconds);
+                }
+                ui.label(
+                    RichText::new(format!(
+                        "{} of {} chunks",
+                        replay.recording.chunks_at(replay.position).len(),
+                        replay.recording.chunks.len()
+                    ))
+                    .small()
+                    .weak(),
+                );
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .id_salt("replay_output")
+                    .max_height(320.0)
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for (stream, text) in merged_chunks(&replay.recording, replay.position) {
+                            let text = RichText::new(text.trim_end_matches('\n')).monospace();
+                            match stream {
+                                OutputStream::Stdout => ui.label(text),
+                                OutputStream::Stderr => {
+                                    ui.label(text.color(ui.visuals().error_fg_color))
+                                }
+                            };
+                        }
+                    });
+            });
+        if !open {
+            self.output_replay = None;
+        }
+    }
+}
+
+/// The output written by `at`, with consecutive chunks from the same stream joined so text
+/// printed without a newline stays on its line.
+fn merged_chunks(recording: &OutputRecording, at: Duration) -> Vec<(OutputStream, String)> {
+    let mut merged: Vec<(OutputStream, String)> = Vec::new();
+    for chunk in recording.chunks_at(at) {
+        match merged.last_mut() {
+            Some((stream, text)) if *stream == chunk.stream => text.push_str(&chunk.text),
+            _ => merged.push((chunk.stream, chunk.text.clone())),
+        }
+    }
+    merged
+}
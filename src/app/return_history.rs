@@ -0,0 +1,116 @@
+use std::collections::VecDeque;
+
+use eframe::egui;
+use egui::{Color32, RichText};
+
+use super::ExplorerApp;
+use crate::runtime::value_diff::{self, DiffKind, DiffLine};
+
+/// How many return values are kept per example.
+const RETURN_HISTORY_LEN: usize = 10;
+/// Diffs longer than this are cut short in the summary.
+const MAX_DIFF_LINES: usize = 200;
+
+/// The latest return values of one example, newest last, and the diff shown after a hot
+/// reload re-run.
+#[derive(Default)]
+pub(super) struct ReturnHistory {
+    values: VecDeque<String>,
+    diff: Option<Vec<DiffLine>>,
+}
+
+impl ExplorerApp {
+    /// Adds the last run's return value to `example_id`'s history. After a hot reload re-run,
+    /// the value is diffed against the one before it so the summary can show what the edit
+    /// changed; any other run hides the diff.
+    pub(super) fn record_return_value(&mut self, example_id: &str, hot_reload: bool) {
+        let value = self
+            .last_execution
+            .as_ref()
+            .filter(|summary| summary.succeeded)
+            .and_then(|summary| {
+                let display = summary.return_value.as_deref()?;
+                Some(value_diff::value_text(display, summary.value.as_ref()))
+            });
+        let history = self
+            .return_histories
+            .entry(example_id.to_string())
+            .or_default();
+        history.diff = None;
+        let Some(value) = value else {
+            return;
+        };
+        if hot_reload {
+            if let Some(previous) = history.values.back() {
+                history.diff = Some(value_diff::diff_lines(previous, &value));
+            }
+        }
+        history.values.push_back(value);
+        if history.values.len() > RETURN_HISTORY_LEN {
+            history.values.pop_front();
+        }
+    }
+
+    /// What the latest hot reload changed in the return value, then the earlier values.
+    pub(super) fn return_history_ui(&self, ui: &mut egui::Ui, example_id: &str) {
+        let Some(history) = self.return_histories.get(example_id) else {
+            return;
+        };
+
+        if let Some(diff) = &history.diff {
+            ui.add_space(4.0);
+            if diff.iter().all(|line| line.kind == DiffKind::Same) {
+                ui.label(RichText::new("The edit didn't change the return value").weak());
+            } else {
+                ui.label(RichText::new("Return value changed by the edit").strong());
+                egui::ScrollArea::vertical()
+                    .id_salt(("return_diff", example_id))
+                    .max_height(240.0)
+                    .show(ui, |ui| {
+                        ui.spacing_mut().item_spacing.y = 0.0;
+                        for line in diff.iter().take(MAX_DIFF_LINES) {
+                            diff_line_ui(ui, line);
+                        }
+                        if diff.len() > MAX_DIFF_LINES {
+                            ui.label(
+                                RichText::new(format!(
+                                    "… {} more lines",
+                                    diff.len() - MAX_DIFF_LINES
+                                ))
+                                .weak(),
+                            );
+                        }
+                    });
+            }
+        }
+
+        if history.values.len() > 1 {
+            egui::CollapsingHeader::new(format!("Return value history ({})", history.values.len()))
+                .id_salt(("return_history", example_id))
+                .show(ui, |ui| {
+                    for (age, value) in history.values.iter().rev().enumerate() {
+                        let label = match age {
+                            0 => "Latest".to_string(),
+                            1 => "1 run ago".to_string(),
+                            _ => format!("{age} runs ago"),
+                        };
+                        ui.label(RichText::new(label).small().weak());
+                        ui.monospace(value);
+                    }
+                });
+        }
+    }
+}
+
+pub(super) fn diff_line_ui(ui: &mut egui::Ui, line: &DiffLine) {
+    let (prefix, background) = match line.kind {
+        DiffKind::Same => ("  ", None),
+        DiffKind::Removed => ("- ", Some(Color32::from_rgb(110, 40, 40))),
+        DiffKind::Added => ("+ ", Some(Color32::from_rgb(40, 90, 50))),
+    };
+    let text = RichText::new(format!("{prefix}{}", line.text)).monospace();
+    match background {
+        Some(color) => ui.label(text.background_color(color)),
+        None => ui.label(text.weak()),
+    };
+}
@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use eframe::egui;
+use egui::{Color32, Grid, RichText};
+
+use super::ExplorerApp;
+use crate::{
+    runtime::{
+        self,
+        audit::{AuditReport, ProbeOutcome},
+    },
+    tasks::{self, Task},
+};
+
+const AUDIT_REPAINT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Runs the runtime's sandbox probes in the background and lists which were blocked.
+#[derive(Default)]
+pub(super) struct SandboxAudit {
+    task: Option<Task<AuditReport>>,
+    report: Option<AuditReport>,
+    error: Option<String>,
+}
+
+impl ExplorerApp {
+    pub(super) fn open_sandbox_audit(&mut self) {
+        if self.sandbox_audit.is_none() {
+            self.sandbox_audit = Some(SandboxAudit::default());
+        }
+        self.start_sandbox_audit();
+    }
+
+    fn start_sandbox_audit(&mut self) {
+        if self.runtime_busy() {
+            return;
+        }
+        let Some(audit) = &mut self.sandbox_audit else {
+            return;
+        };
+        if audit.task.is_some() {
+            return;
+        }
+        audit.error = None;
+        audit.task = Some(tasks::spawn("Sandbox audit", |_| {
+            Ok(runtime::RUNTIME.run_sandbox_audit())
+        }));
+    }
+
+    pub(super) fn sandbox_audit_ui(&mut self, ctx: &egui::Context) {
+        let Some(audit) = &mut self.sandbox_audit else {
+            return;
+        };
+        if let Some(result) = audit.task.as_ref().and_then(|task| task.poll()) {
+            audit.task = None;
+            match result {
+                Ok(report) => audit.report = Some(report),
+                Err(error) => audit.error = Some(error.to_string()),
+            }
+        } else if audit.task.is_some() {
+            ctx.request_repaint_after(AUDIT_REPAINT_INTERVAL);
+        }
+
+        let mut open = true;
+        let mut rerun = false;
+        egui::Window::new("Sandbox audit")
+            .open(&mut open)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                ui.label(
+                    "Runs scripts that attempt forbidden operations under each runtime profile.",
+                );
+                ui.horizontal(|ui| {
+                    rerun = ui
+                        .add_enabled(audit.task.is_none(), egui::Button::new("Run again"))
+                        .clicked();
+                    if audit.task.is_some() {
+                        ui.spinner();
+                    }
+                });
+                if let Some(error) = &audit.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+                let Some(report) = &audit.report else {
+                    return;
+                };
+                let failures = report.failures().count();
+                if failures == 0 {
+                    ui.colored_label(
+                        Color32::from_rgb(120, 200, 120),
+                        "Every probe behaved as its profile promises.",
+                    );
+                } else {
+                    ui.colored_label(
+                        ui.visuals().error_fg_color,
+                        format!("{failures} probe results differ from what the profile promises."),
+                    );
+                }
+                ui.separator();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    Grid::new("sandbox_audit_grid")
+                        .striped(true)
+                        .show(ui, |grid| {
+                            grid.label(RichText::new("Operation").strong());
+                            grid.label(RichText::new("Profile").strong());
+                            grid.label(RichText::new("Expected").strong());
+                            grid.label(RichText::new("Result").strong());
+                            grid.end_row();
+
+                            for finding in &report.findings {
+                                grid.label(RichText::new(finding.probe.name).monospace())
+                                    .on_hover_text(finding.probe.description);
+                                grid.label(finding.profile.label());
+                                grid.label(if finding.expected_blocked() {
+                                    "Blocked"
+                                } else {
+                                    "Allowed"
+                                });
+                                let (text, detail) = match &finding.outcome {
+                                    ProbeOutcome::Blocked(message) => ("Blocked", message.as_str()),
+                                    ProbeOutcome::Completed => ("Ran", "The script completed"),
+                                    ProbeOutcome::Failed(message) => ("Failed", message.as_str()),
+                                };
+                                let color = if finding.passed() {
+                                    Color32::from_rgb(120, 200, 120)
+                                } else {
+                                    Color32::from_rgb(220, 100, 100)
+                                };
+                                grid.colored_label(color, text).on_hover_text(detail);
+                                grid.end_row();
+                            }
+                        });
+                });
+            });
+
+        if !open {
+            self.sandbox_audit = None;
+        } else if rerun {
+            self.start_sandbox_audit();
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use std::time::Duration;
+
+use eframe::egui;
+use egui::RichText;
+
+use super::{ConsoleEntry, ExplorerApp, SnackbarKind};
+use crate::{
+    language,
+    runtime::{self, RuntimeProfile},
+};
+
+impl ExplorerApp {
+    pub(super) fn runtime_settings_ui(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Runtime settings")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label("Environment allowlist");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.env_allowlist_input)
+                        .hint_text("HOME, LANG"),
+                );
+                ui.label(
+                    RichText::new("Comma-separated variables readable through env.get").small(),
+                );
+                if ui.button("Apply").clicked() {
+                    self.apply_env_allowlist();
+                }
+
+                ui.add_space(6.0);
+                let mut trusted = runtime::RUNTIME.profile() == RuntimeProfile::Trusted;
+                if ui
+                    .checkbox(&mut trusted, "Trusted profile")
+                    .on_hover_text(
+                        "Allows scripts to use privileged modules such as clipboard and process",
+                    )
+                    .changed()
+                {
+                    self.set_runtime_profile(if trusted {
+                        RuntimeProfile::Trusted
+                    } else {
+                        RuntimeProfile::Standard
+                    });
+                }
+
+                ui.add_space(6.0);
+                let mut audio = runtime::RUNTIME.audio_enabled();
+                if ui
+                    .add_enabled(
+                        cfg!(feature = "audio"),
+                        egui::Checkbox::new(&mut audio, "Audio output"),
+                    )
+                    .on_hover_text("Play tones from the audio module through the speakers")
+                    .on_disabled_hover_text("This build was made without the `audio` feature")
+                    .changed()
+                {
+                    runtime::RUNTIME.set_audio_enabled(audio);
+                }
+
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label("Hot reload delay");
+                    let mut millis = self.hot_reload_delay.as_millis() as u64;
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut millis)
+                                .range(0..=10_000)
+                                .speed(10)
+                                .suffix(" ms"),
+                        )
+                        .on_hover_text(
+                            "How long to wait after the last file change before re-running",
+                        )
+                        .changed()
+                    {
+                        self.hot_reload_delay = Duration::from_millis(millis);
+                    }
+                });
+
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label("Memory limit");
+                    let running = self.is_script_running();
+                    let response = ui
+                        .add_enabled(
+                            !running,
+                            egui::DragValue::new(&mut self.memory_limit_mb)
+                                .range(0..=65_536)
+                                .suffix(" MiB"),
+                        )
+                        .on_hover_text("Stops runs that allocate more than this; 0 means no limit");
+                    if response.changed() {
+                        self.apply_memory_limit();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Time limit");
+                    ui.add(
+                        egui::DragValue::new(&mut self.run_time_limit_secs)
+                            .range(0..=3_600)
+                            .suffix(" s"),
+                    )
+                    .on_hover_text(
+                        "Ends runs that take longer than this, including loops that Stop can't \
+                         reach because they never call into the host; 0 means no limit",
+                    );
+                });
+                if self.memory_limit_mb > 0 && !runtime::memory::is_tracking_available() {
+                    ui.label(RichText::new("Memory tracking is unavailable in this build").small());
+                }
+
+                if let Some(seed) = self.pinned_random_seed {
+                    ui.add_space(6.0);
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Random seed pinned to {seed}"));
+                        if ui.small_button("Unpin").clicked() {
+                            self.set_pinned_random_seed(None);
+                        }
+                    });
+                }
+
+                ui.add_space(6.0);
+                number_format_ui(ui);
+
+                ui.add_space(6.0);
+                ui.label("Core docs base URL");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.docs_base_url)
+                        .hint_text(language::DEFAULT_DOCS_BASE_URL),
+                );
+                if self.docs_base_url.trim().is_empty() {
+                    self.docs_base_url = language::DEFAULT_DOCS_BASE_URL.to_string();
+                }
+
+                ui.add_space(6.0);
+                self.language_server_status_ui(ui);
+            });
+    }
+
+    pub(super) fn apply_memory_limit(&mut self) {
+        let limit = (self.memory_limit_mb > 0).then(|| self.memory_limit_mb as usize * 1024 * 1024);
+        if let Err(error) = runtime::RUNTIME.set_memory_limit(limit) {
+            self.push_console_entry(ConsoleEntry::error(format!(
+                "Failed to update memory limit: {error}"
+            )));
+        }
+    }
+
+    pub(super) fn set_pinned_random_seed(&mut self, seed: Option<u64>) {
+        self.pinned_random_seed = seed;
+        runtime::RUNTIME.set_random_seed(seed);
+        let message = match seed {
+            Some(seed) => format!("Runs now start host.random from seed {seed}"),
+            None => "Runs now use a fresh random seed".to_string(),
+        };
+        self.push_console_entry(ConsoleEntry::info(message));
+    }
+
+    pub(super) fn apply_env_allowlist(&mut self) {
+        let names = parse_name_list(&self.env_allowlist_input);
+        runtime::RUNTIME.set_env_allowlist(names.clone());
+        let message = if names.is_empty() {
+            "Environment allowlist cleared".to_string()
+        } else {
+            format!("Environment allowlist: {}", names.join(", "))
+        };
+        self.push_console_entry(ConsoleEntry::info(message));
+        self.push_snackbar("Runtime settings applied", SnackbarKind::Info);
+    }
+
+    pub(super) fn set_runtime_profile(&mut self, profile: RuntimeProfile) {
+        runtime::RUNTIME.set_profile(profile);
+        self.push_console_entry(ConsoleEntry::info(format!(
+            "Runtime profile set to {}",
+            profile.label()
+        )));
+    }
+}
+
+/// Numeric display settings, applied to return values and generator traces from the next run.
+fn number_format_ui(ui: &mut egui::Ui) {
+    let mut format = runtime::RUNTIME.number_format();
+    ui.label("Number display");
+    ui.horizontal(|ui| {
+        let mut fixed = format.precision.is_some();
+        ui.checkbox(&mut fixed, "Fixed decimals");
+        let mut precision = format.precision.unwrap_or(2);
+        ui.add_enabled(fixed, egui::DragValue::new(&mut precision).range(0..=12));
+        format.precision = fixed.then_some(precision);
+    });
+    ui.horizontal(|ui| {
+        let mut scientific = format.scientific_threshold.is_some();
+        ui.checkbox(&mut scientific, "Scientific from 1e");
+        let mut exponent = format
+            .scientific_threshold
+            .map(|threshold| threshold.log10().round() as i32)
+            .unwrap_or(9);
+        ui.add_enabled(
+            scientific,
+            egui::DragValue::new(&mut exponent).range(1..=18),
+        )
+        .on_hover_text("Also used for floats smaller than 1e-N");
+        format.scientific_threshold = scientific.then(|| 10f64.powi(exponent));
+    });
+    ui.checkbox(&mut format.thousands_separators, "Thousands separators");
+    if format != runtime::RUNTIME.number_format() {
+        runtime::RUNTIME.set_number_format(format);
+    }
+}
+
+pub(super) fn parse_name_list(input: &str) -> Vec<String> {
+    input
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
@@ -0,0 +1,506 @@
+use eframe::egui;
+use egui::{Color32, RichText};
+
+use super::{ConsoleEntry, ConsolePane, ExplorerApp, SnackbarKind, format_duration};
+use crate::{
+    examples::{
+        Example,
+        importer::{ImportReport, ImportSource},
+        paste::{PasteSource, PasteTarget, PastedExample},
+        tests::{self, ExampleTestSuite, TestSuiteResult},
+    },
+    runtime::{
+        self, RunOptions,
+        precompile::{DiskCache, PrecompiledScript},
+        repeat::RunSeries,
+    },
+    tasks::{self, Task, TaskStatus},
+};
+
+/// One suite's outcome: suite id, suite name, and the result or error message.
+type SuiteOutcome = (String, String, Result<TestSuiteResult, String>);
+
+/// Work started from the UI whose result is applied when the task finishes.
+pub(super) enum BackgroundJob {
+    Suites {
+        example_id: String,
+        title: String,
+        task: Task<Vec<SuiteOutcome>>,
+    },
+    CatalogRefresh(Task<()>),
+    /// Precompiles every example; yields how many compiled and how many failed.
+    Warmup(Task<(usize, usize)>),
+    RepeatedRuns {
+        title: String,
+        task: Task<RunSeries>,
+    },
+    KotoImport(Task<ImportReport>),
+    PasteImport(Task<PastedExample>),
+    /// Writes a file under `exports/`; yields the message to log once it's written.
+    Export {
+        /// What is exported, as in "Failed to export the view".
+        what: &'static str,
+        task: Task<String>,
+    },
+}
+
+impl ExplorerApp {
+    pub(super) fn start_suite_run(&mut self, example: &Example, suites: Vec<ExampleTestSuite>) {
+        self.active_console_pane = ConsolePane::Tests;
+        let label = if suites.len() == 1 {
+            format!("Running suite '{}'", suites[0].name)
+        } else {
+            format!("Running suites for '{}'", example.metadata.title)
+        };
+        let coverage = self.suite_coverage;
+        let max_failures = self.suite_failure_limit();
+
+        let task = tasks::spawn(label, move |context| {
+            // Suites run in parallel, each in a fresh runtime; cancelling stops new ones
+            // from starting.
+            context.set_steps(0, suites.len());
+            let results = tests::SuiteRunner::new()
+                .coverage(coverage)
+                .max_failures(max_failures)
+                .run_each(&suites, |finished| {
+                    context.set_steps(finished, suites.len());
+                    !context.is_cancelled()
+                });
+            context.check_cancelled()?;
+            let outcomes = suites
+                .iter()
+                .zip(results)
+                .filter_map(|(suite, result)| {
+                    let result = result?.map_err(|error| format!("{error:#}"));
+                    Some((suite.id.clone(), suite.name.clone(), result))
+                })
+                .collect();
+            Ok(outcomes)
+        });
+
+        self.background_jobs.push(BackgroundJob::Suites {
+            example_id: example.metadata.id.clone(),
+            title: example.metadata.title.clone(),
+            task,
+        });
+    }
+
+    /// Runs only the `cases` of `suite`, e.g. the ones that failed in `previous`, and folds
+    /// their new results into it.
+    pub(super) fn start_case_rerun(
+        &mut self,
+        example: &Example,
+        suite: ExampleTestSuite,
+        previous: TestSuiteResult,
+        cases: Vec<String>,
+    ) {
+        self.active_console_pane = ConsolePane::Tests;
+        let label = match cases.as_slice() {
+            [case] => format!("Running case '{case}' of '{}'", suite.name),
+            _ => format!("Re-running {} cases of '{}'", cases.len(), suite.name),
+        };
+        let coverage = self.suite_coverage;
+        let max_failures = self.suite_failure_limit();
+
+        let task = tasks::spawn(label, move |_| {
+            let result = tests::SuiteRunner::new()
+                .coverage(coverage)
+                .max_failures(max_failures)
+                .cases(cases)
+                .run(&suite)
+                .map(|rerun| previous.merge_rerun(rerun))
+                .map_err(|error| format!("{error:#}"));
+            Ok(vec![(suite.id, suite.name, result)])
+        });
+
+        self.background_jobs.push(BackgroundJob::Suites {
+            example_id: example.metadata.id.clone(),
+            title: example.metadata.title.clone(),
+            task,
+        });
+    }
+
+    /// The failure limit picked in the Tests pane, as passed to `SuiteRunner::max_failures`.
+    fn suite_failure_limit(&self) -> usize {
+        if self.suite_fail_fast {
+            1
+        } else {
+            self.suite_max_failures.unwrap_or(0)
+        }
+    }
+
+    pub(super) fn is_running_suites(&self, example_id: &str) -> bool {
+        self.background_jobs.iter().any(
+            |job| matches!(job, BackgroundJob::Suites { example_id: id, .. } if id == example_id),
+        )
+    }
+
+    pub(super) fn refresh_catalog_in_background(&mut self) {
+        let Some(library) = self.example_library else {
+            return;
+        };
+        let task = tasks::spawn("Refreshing example catalog", move |_| library.refresh());
+        self.background_jobs
+            .push(BackgroundJob::CatalogRefresh(task));
+    }
+
+    pub(super) fn import_koto_scripts_in_background(&mut self, source: ImportSource) {
+        let Some(library) = self.example_library else {
+            return;
+        };
+        let task = tasks::spawn("Importing Koto scripts", move |_| {
+            library.import_koto_scripts(&source)
+        });
+        self.background_jobs.push(BackgroundJob::KotoImport(task));
+    }
+
+    pub(super) fn is_importing_koto_scripts(&self) -> bool {
+        self.background_jobs
+            .iter()
+            .any(|job| matches!(job, BackgroundJob::KotoImport(_)))
+    }
+
+    /// Downloads the script first when `source` is a link, so slow hosts don't stall the UI.
+    pub(super) fn import_paste_in_background(&mut self, source: PasteSource, target: PasteTarget) {
+        let Some(library) = self.example_library else {
+            return;
+        };
+        let task = tasks::spawn("Importing pasted code", move |_| {
+            library.import_pasted(&source, &target)
+        });
+        self.background_jobs.push(BackgroundJob::PasteImport(task));
+    }
+
+    pub(super) fn is_importing_paste(&self) -> bool {
+        self.background_jobs
+            .iter()
+            .any(|job| matches!(job, BackgroundJob::PasteImport(_)))
+    }
+
+    /// Compiles every example in the background so that first runs skip compilation, and
+    /// records the outcome in the disk cache; scripts it says don't compile are skipped until
+    /// they change. In-memory
+    /// catalogs, as in UI tests, are left alone so the job doesn't race their runs.
+    pub(super) fn warm_compile_examples(&mut self) {
+        if self
+            .example_library
+            .and_then(|library| library.examples_dir())
+            .is_none()
+        {
+            return;
+        }
+        let scripts: Vec<String> = self
+            .examples
+            .iter()
+            .map(|example| example.script.clone())
+            .collect();
+        if scripts.is_empty() {
+            return;
+        }
+        let task = tasks::spawn("Precompiling examples", move |context| {
+            let disk_cache = DiskCache::default_location();
+            let (mut compiled, mut failed) = (0, 0);
+            for (index, script) in scripts.iter().enumerate() {
+                context.check_cancelled()?;
+                context.set_steps(index, scripts.len());
+                let cached = disk_cache.get(script);
+                if cached.as_ref().is_some_and(|artifact| !artifact.compiled()) {
+                    failed += 1;
+                    continue;
+                }
+                let artifact = match runtime::RUNTIME.precompile(script) {
+                    Ok(artifact) => {
+                        compiled += 1;
+                        artifact
+                    }
+                    Err(error) => {
+                        failed += 1;
+                        PrecompiledScript::failed(script, error)
+                    }
+                };
+                if cached.is_none() {
+                    disk_cache.store(&artifact)?;
+                }
+            }
+            context.set_steps(scripts.len(), scripts.len());
+            Ok((compiled, failed))
+        });
+        self.background_jobs.push(BackgroundJob::Warmup(task));
+    }
+
+    /// Runs `script` [`REPEATED_RUNS`](super::REPEATED_RUNS) times, starting with its compiled
+    /// chunk evicted so the first run pays for compilation.
+    pub(super) fn start_repeated_runs(
+        &mut self,
+        title: String,
+        script: String,
+        options: RunOptions,
+    ) {
+        let runs = super::REPEATED_RUNS;
+        self.active_console_pane = ConsolePane::Console;
+        self.push_console_entry(ConsoleEntry::info(format!(
+            "Running '{title}' {runs} times; output from these runs is not shown"
+        )));
+        let task = tasks::spawn(format!("Running '{title}' {runs}x"), move |context| {
+            runtime::RUNTIME.forget_compiled(&script)?;
+            let mut series = RunSeries::default();
+            for index in 0..runs {
+                context.check_cancelled()?;
+                context.set_steps(index, runs);
+                let output = runtime::RUNTIME.execute(&script, &options)?;
+                series.push(&output);
+            }
+            context.set_steps(runs, runs);
+            Ok(series)
+        });
+        self.background_jobs
+            .push(BackgroundJob::RepeatedRuns { title, task });
+    }
+
+    pub(super) fn is_repeating_runs(&self) -> bool {
+        self.background_jobs
+            .iter()
+            .any(|job| matches!(job, BackgroundJob::RepeatedRuns { .. }))
+    }
+
+    fn report_repeated_runs(&mut self, title: &str, series: &RunSeries) {
+        let Some(first) = series.first() else {
+            return;
+        };
+        let cached = if first.cache_hit { ", cached" } else { "" };
+        let mut message = format!(
+            "'{title}' first run: {} (compile {}{cached})",
+            format_duration(first.duration),
+            format_duration(first.compile_time)
+        );
+        if let (Some(warm), Some(compile)) = (series.warm_stats(), series.warm_compile_stats()) {
+            let hits = series
+                .runs
+                .iter()
+                .skip(1)
+                .filter(|run| run.cache_hit)
+                .count();
+            message.push_str(&format!(
+                "\nRuns 2–{}: min {}, median {}, max {} (median compile {}, {hits} cache hits)",
+                series.runs.len(),
+                format_duration(warm.min),
+                format_duration(warm.median),
+                format_duration(warm.max),
+                format_duration(compile.median),
+            ));
+        }
+        self.push_console_entry(ConsoleEntry::log(message));
+    }
+
+    /// Writes an export off the UI thread. `export` returns the message to log when it
+    /// succeeds; errors are logged by the task's completion event.
+    pub(super) fn export_in_background<F>(&mut self, what: &'static str, export: F)
+    where
+        F: FnOnce() -> anyhow::Result<String> + Send + 'static,
+    {
+        let task = tasks::spawn(format!("Exporting {what}"), move |_| export());
+        self.background_jobs
+            .push(BackgroundJob::Export { what, task });
+    }
+
+    pub(super) fn is_refreshing_catalog(&self) -> bool {
+        self.background_jobs
+            .iter()
+            .any(|job| matches!(job, BackgroundJob::CatalogRefresh(_)))
+    }
+
+    /// Applies the results of finished jobs and logs completion events.
+    pub(super) fn poll_background_jobs(&mut self) {
+        for event in tasks::take_events() {
+            let elapsed = format_duration(event.elapsed);
+            let entry = match event.status {
+                TaskStatus::Completed => {
+                    ConsoleEntry::log(format!("Task '{}' finished in {elapsed}", event.label))
+                }
+                TaskStatus::Failed(error) => {
+                    ConsoleEntry::error(format!("Task '{}' failed: {error}", event.label))
+                }
+                TaskStatus::Cancelled => {
+                    ConsoleEntry::info(format!("Task '{}' was cancelled", event.label))
+                }
+            };
+            self.push_console_entry(entry);
+        }
+
+        let mut pending = Vec::new();
+        for job in std::mem::take(&mut self.background_jobs) {
+            match job {
+                BackgroundJob::Suites {
+                    example_id,
+                    title,
+                    task,
+                } => match task.poll() {
+                    None => pending.push(BackgroundJob::Suites {
+                        example_id,
+                        title,
+                        task,
+                    }),
+                    Some(Ok(outcomes)) => {
+                        let several = outcomes.len() > 1;
+                        let mut any_failed = false;
+                        for (suite_id, suite_name, result) in outcomes {
+                            if !self.record_suite_result(
+                                &example_id,
+                                &suite_id,
+                                &suite_name,
+                                result,
+                            ) {
+                                any_failed = true;
+                            }
+                        }
+                        if several {
+                            self.finish_suite_run(&title, any_failed);
+                        }
+                    }
+                    Some(Err(_)) => {
+                        self.push_snackbar(
+                            format!("Test run for '{title}' stopped"),
+                            SnackbarKind::Info,
+                        );
+                    }
+                },
+                BackgroundJob::CatalogRefresh(task) => match task.poll() {
+                    None => pending.push(BackgroundJob::CatalogRefresh(task)),
+                    Some(result) => self.apply_catalog_refresh(result),
+                },
+                BackgroundJob::Warmup(task) => match task.poll() {
+                    None => pending.push(BackgroundJob::Warmup(task)),
+                    Some(Ok((compiled, failed))) => {
+                        let message = if failed == 0 {
+                            format!("Precompiled {compiled} examples")
+                        } else {
+                            format!("Precompiled {compiled} examples; {failed} have compile errors")
+                        };
+                        self.push_console_entry(ConsoleEntry::log(message));
+                    }
+                    // Failures are already reported through the task's completion event.
+                    Some(Err(_)) => {}
+                },
+                BackgroundJob::KotoImport(task) => match task.poll() {
+                    None => pending.push(BackgroundJob::KotoImport(task)),
+                    Some(Ok(report)) => {
+                        let mut message = format!(
+                            "Imported {} Koto scripts into the catalog",
+                            report.imported.len()
+                        );
+                        if !report.skipped.is_empty() {
+                            message.push_str(&format!(
+                                "; skipped {} that were already imported",
+                                report.skipped.len()
+                            ));
+                        }
+                        self.push_console_entry(ConsoleEntry::info(message));
+                        self.push_snackbar("Koto scripts imported", SnackbarKind::Success);
+                    }
+                    // Failures are already reported through the task's completion event.
+                    Some(Err(_)) => {}
+                },
+                BackgroundJob::PasteImport(task) => match task.poll() {
+                    None => pending.push(BackgroundJob::PasteImport(task)),
+                    Some(Ok(pasted)) => {
+                        self.apply_catalog_refresh(Ok(()));
+                        self.select_example(&pasted.id);
+                        match pasted.syntax_error {
+                            None => {
+                                self.push_console_entry(ConsoleEntry::info(format!(
+                                    "Imported '{}'; it compiles",
+                                    pasted.id
+                                )));
+                                self.push_snackbar("Code imported", SnackbarKind::Success);
+                            }
+                            Some(error) => {
+                                self.push_console_entry(ConsoleEntry::error(format!(
+                                    "Imported '{}', but it doesn't compile: {error}",
+                                    pasted.id
+                                )));
+                                self.push_snackbar(
+                                    "Code imported with syntax errors",
+                                    SnackbarKind::Error,
+                                );
+                            }
+                        }
+                    }
+                    // Failures are already reported through the task's completion event.
+                    Some(Err(_)) => {}
+                },
+                BackgroundJob::Export { what, task } => match task.poll() {
+                    None => pending.push(BackgroundJob::Export { what, task }),
+                    Some(Ok(message)) => {
+                        self.push_console_entry(ConsoleEntry::info(message));
+                        self.push_snackbar(format!("Exported {what}"), SnackbarKind::Success);
+                    }
+                    Some(Err(_)) => {
+                        self.push_snackbar(format!("Failed to export {what}"), SnackbarKind::Error);
+                    }
+                },
+                BackgroundJob::RepeatedRuns { title, task } => match task.poll() {
+                    None => pending.push(BackgroundJob::RepeatedRuns { title, task }),
+                    Some(Ok(series)) => self.report_repeated_runs(&title, &series),
+                    Some(Err(_)) => {}
+                },
+            }
+        }
+        // Jobs started while results were being applied are kept as well.
+        pending.append(&mut self.background_jobs);
+        self.background_jobs = pending;
+    }
+
+    pub(super) fn tasks_ui(&mut self, ui: &mut egui::Ui) {
+        let active = tasks::active();
+        let finished = tasks::finished();
+
+        egui::ScrollArea::vertical()
+            .id_salt("tasks_scroll")
+            .show(ui, |ui| {
+                ui.label(RichText::new("Active").strong());
+                if active.is_empty() {
+                    ui.label(RichText::new("No background work is running.").weak());
+                }
+                for task in &active {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(&task.label);
+                        if let Some(message) = &task.message {
+                            ui.label(RichText::new(message).small().weak());
+                        }
+                        ui.label(
+                            RichText::new(format!("{:.1} s", task.started.elapsed().as_secs_f32()))
+                                .small(),
+                        );
+                        if tasks::is_cancellable(task.id) && ui.small_button("Cancel").clicked() {
+                            tasks::cancel(task.id);
+                        }
+                    });
+                    if let Some(fraction) = task.fraction() {
+                        ui.add(egui::ProgressBar::new(fraction).show_percentage());
+                    }
+                }
+
+                ui.add_space(8.0);
+                ui.label(RichText::new("Finished").strong());
+                for task in &finished {
+                    let Some(outcome) = &task.finished else {
+                        continue;
+                    };
+                    ui.horizontal(|ui| {
+                        if outcome.succeeded {
+                            ui.colored_label(Color32::from_rgb(120, 200, 120), "✔");
+                        } else {
+                            ui.colored_label(Color32::from_rgb(220, 100, 100), "✖");
+                        }
+                        ui.label(&task.label);
+                        ui.label(
+                            RichText::new(format_duration(outcome.elapsed))
+                                .small()
+                                .weak(),
+                        );
+                    });
+                }
+            });
+    }
+}
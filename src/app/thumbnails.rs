@@ -0,0 +1,180 @@
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "image")]
+use anyhow::Context;
+use anyhow::anyhow;
+use eframe::egui;
+use egui::{Color32, Rect, RichText, Vec2};
+
+use super::{ExplorerApp, SnackbarKind};
+
+/// Captured thumbnails are scaled down to fit this size before they are saved.
+const MAX_THUMBNAIL_SIZE: u32 = 320;
+const SIDEBAR_THUMBNAIL_SIZE: Vec2 = Vec2::new(32.0, 24.0);
+const GALLERY_CARD_SIZE: Vec2 = Vec2::new(160.0, 120.0);
+
+/// A canvas screenshot that has been requested but not yet received.
+pub(super) struct ThumbnailCapture {
+    example_id: String,
+    path: PathBuf,
+    /// The canvas area within the screenshot, in points.
+    rect: Rect,
+}
+
+impl ExplorerApp {
+    /// Asks for a screenshot of the window; the canvas area is cropped out of it and saved as
+    /// the selected example's thumbnail once it arrives.
+    pub(super) fn capture_canvas_thumbnail(&mut self, ctx: &egui::Context, canvas_rect: Rect) {
+        let Some(example) = self.selected_example() else {
+            return;
+        };
+        self.thumbnail_capture = Some(ThumbnailCapture {
+            example_id: example.metadata.id.clone(),
+            path: example.thumbnail_capture_path(),
+            rect: canvas_rect,
+        });
+        ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot(Default::default()));
+    }
+
+    pub(super) fn poll_thumbnail_capture(&mut self, ctx: &egui::Context) {
+        if self.thumbnail_capture.is_none() {
+            return;
+        }
+        let screenshot = ctx.input(|input| {
+            input.events.iter().find_map(|event| match event {
+                egui::Event::Screenshot { image, .. } => Some(image.clone()),
+                _ => None,
+            })
+        });
+        let (Some(image), Some(capture)) = (screenshot, self.thumbnail_capture.take()) else {
+            return;
+        };
+        let cropped = image.region(&capture.rect, Some(ctx.pixels_per_point()));
+        match save_thumbnail(&cropped, &capture.path) {
+            Ok(()) => {
+                ctx.forget_image(&thumbnail_uri(&capture.path));
+                if let Some(example) = self
+                    .examples
+                    .iter_mut()
+                    .find(|example| example.metadata.id == capture.example_id)
+                {
+                    example.thumbnail = Some(capture.path.clone());
+                }
+                self.push_snackbar("Thumbnail saved", SnackbarKind::Success);
+            }
+            Err(error) => self.push_snackbar(
+                format!("Failed to save the thumbnail: {error:#}"),
+                SnackbarKind::Error,
+            ),
+        }
+    }
+
+    /// Shown when no example is selected: every example that passes the filters as a card,
+    /// with its thumbnail when it has one.
+    pub(super) fn example_gallery_ui(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Examples");
+        ui.label("Pick an example to get started.");
+        ui.add_space(8.0);
+        let mut selected = None;
+        egui::ScrollArea::vertical()
+            .id_salt("example_gallery")
+            .show(ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    for example in self
+                        .examples
+                        .iter()
+                        .filter(|example| self.passes_filters(example))
+                    {
+                        let response = ui
+                            .vertical(|ui| {
+                                ui.set_width(GALLERY_CARD_SIZE.x);
+                                let image = thumbnail_or_placeholder(
+                                    ui,
+                                    example.thumbnail.as_deref(),
+                                    GALLERY_CARD_SIZE,
+                                );
+                                ui.label(RichText::new(&example.metadata.title).strong());
+                                image
+                            })
+                            .inner;
+                        let response = response.on_hover_text(&example.metadata.description);
+                        if response.clicked() {
+                            selected = Some(example.metadata.id.clone());
+                        }
+                        ui.add_space(8.0);
+                    }
+                });
+            });
+        if let Some(example_id) = selected {
+            self.select_example(&example_id);
+        }
+    }
+}
+
+/// A small thumbnail for the sidebar list, or nothing for examples without one.
+pub(super) fn sidebar_thumbnail(ui: &mut egui::Ui, path: Option<&Path>) {
+    if let Some(path) = path.filter(|_| cfg!(feature = "image")) {
+        ui.add(
+            egui::Image::new(thumbnail_uri(path))
+                .fit_to_exact_size(SIDEBAR_THUMBNAIL_SIZE)
+                .corner_radius(2.0)
+                .show_loading_spinner(false),
+        );
+    }
+}
+
+/// Builds without the `image` feature can't decode thumbnails, so every card gets the
+/// placeholder.
+fn thumbnail_or_placeholder(ui: &mut egui::Ui, path: Option<&Path>, size: Vec2) -> egui::Response {
+    match path.filter(|_| cfg!(feature = "image")) {
+        Some(path) => ui.add(
+            egui::Image::new(thumbnail_uri(path))
+                .fit_to_exact_size(size)
+                .corner_radius(4.0)
+                .sense(egui::Sense::click()),
+        ),
+        None => {
+            let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click());
+            ui.painter()
+                .rect_filled(rect, 4.0, ui.visuals().faint_bg_color);
+            ui.painter().text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                "No preview",
+                egui::FontId::proportional(12.0),
+                Color32::GRAY,
+            );
+            response
+        }
+    }
+}
+
+fn thumbnail_uri(path: &Path) -> String {
+    format!("file://{}", path.display())
+}
+
+#[cfg(feature = "image")]
+fn save_thumbnail(image: &egui::ColorImage, path: &Path) -> anyhow::Result<()> {
+    let [width, height] = image.size;
+    if width == 0 || height == 0 {
+        return Err(anyhow!("the canvas is not visible"));
+    }
+    let full = image::RgbaImage::from_raw(width as u32, height as u32, image.as_raw().to_vec())
+        .context("the screenshot has an unexpected size")?;
+    let scale = (MAX_THUMBNAIL_SIZE as f32 / width.max(height) as f32).min(1.0);
+    let thumbnail = image::imageops::thumbnail(
+        &full,
+        ((width as f32 * scale) as u32).max(1),
+        ((height as f32 * scale) as u32).max(1),
+    );
+    thumbnail
+        .save(path)
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+#[cfg(not(feature = "image"))]
+fn save_thumbnail(_image: &egui::ColorImage, _path: &Path) -> anyhow::Result<()> {
+    Err(anyhow!(
+        "thumbnails need koto_learning built with the `image` feature"
+    ))
+}
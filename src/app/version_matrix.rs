@@ -0,0 +1,242 @@
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use eframe::egui;
+use egui::{Color32, Grid, RichText};
+
+use super::{ExplorerApp, format_duration};
+use crate::{
+    examples::{
+        Example,
+        tests::{ExampleTestSuite, SuiteRunner},
+    },
+    runtime::{
+        Runtime, scratch,
+        version_matrix::{self, BuildRun, KotoBuild, MatrixRow, VersionMatrix},
+    },
+    tasks::{self, Task},
+};
+
+const MATRIX_REPAINT_INTERVAL: Duration = Duration::from_millis(100);
+/// How long each build gets for one script before it's stopped.
+const MATRIX_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Runs the selected example and its test suites under every configured Koto build and lists
+/// where they differ from the embedded one.
+pub(super) struct VersionMatrixDialog {
+    example_id: String,
+    title: String,
+    builds: Vec<KotoBuild>,
+    task: Option<Task<Vec<MatrixRow>>>,
+    rows: Option<Vec<MatrixRow>>,
+    error: Option<String>,
+}
+
+impl ExplorerApp {
+    pub(super) fn open_version_matrix(&mut self) {
+        let Some(example) = self.selected_example().cloned() else {
+            return;
+        };
+        let (builds, error) = match VersionMatrix::load() {
+            Ok(matrix) => (matrix.builds, None),
+            Err(error) => (Vec::new(), Some(format!("{error:#}"))),
+        };
+        self.version_matrix = Some(VersionMatrixDialog {
+            example_id: example.metadata.id.clone(),
+            title: example.metadata.title.clone(),
+            builds,
+            task: None,
+            rows: None,
+            error,
+        });
+        self.start_version_matrix(&example);
+    }
+
+    fn start_version_matrix(&mut self, example: &Example) {
+        let Some(dialog) = &mut self.version_matrix else {
+            return;
+        };
+        if dialog.task.is_some() || dialog.builds.is_empty() {
+            return;
+        }
+        let builds = dialog.builds.clone();
+        let script = example.script.clone();
+        let suites = example.test_suites.clone();
+        let scratch_dir = scratch::scratch_dir_for(&example.metadata.id);
+        dialog.error = None;
+        dialog.task = Some(tasks::spawn(
+            format!(
+                "Running '{}' on {} Koto builds",
+                example.metadata.title,
+                builds.len()
+            ),
+            move |context| {
+                let steps = 1 + suites.len();
+                context.set_steps(0, steps);
+                let mut rows = vec![script_row(&builds, &script, &scratch_dir)?];
+                for (index, suite) in suites.iter().enumerate() {
+                    context.check_cancelled()?;
+                    context.set_steps(index + 1, steps);
+                    rows.push(suite_row(&builds, suite, &scratch_dir));
+                }
+                context.set_steps(steps, steps);
+                Ok(rows)
+            },
+        ));
+    }
+
+    pub(super) fn version_matrix_ui(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.version_matrix else {
+            return;
+        };
+        if let Some(result) = dialog.task.as_ref().and_then(|task| task.poll()) {
+            dialog.task = None;
+            match result {
+                Ok(rows) => dialog.rows = Some(rows),
+                Err(error) => dialog.error = Some(format!("{error:#}")),
+            }
+        } else if dialog.task.is_some() {
+            ctx.request_repaint_after(MATRIX_REPAINT_INTERVAL);
+        }
+
+        let example_id = dialog.example_id.clone();
+        let mut open = true;
+        let mut rerun = false;
+        egui::Window::new(format!("Koto version matrix: {}", dialog.title))
+            .open(&mut open)
+            .default_width(640.0)
+            .show(ctx, |ui| {
+                if dialog.builds.is_empty() {
+                    ui.label("No Koto builds are configured yet. List them in:");
+                    let path = VersionMatrix::config_path()
+                        .map(|path| path.display().to_string())
+                        .unwrap_or_else(|| "$KOTO_VERSION_MATRIX".to_string());
+                    ui.monospace(path);
+                    ui.monospace(
+                        r#"{ "builds": [{ "name": "koto 0.15", "command": "/path/to/koto" }] }"#,
+                    );
+                } else {
+                    ui.horizontal(|ui| {
+                        rerun = ui
+                            .add_enabled(dialog.task.is_none(), egui::Button::new("Run again"))
+                            .clicked();
+                        if dialog.task.is_some() {
+                            ui.spinner();
+                        }
+                    });
+                }
+                if let Some(error) = &dialog.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+                let Some(rows) = &dialog.rows else {
+                    return;
+                };
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(420.0)
+                    .show(ui, |ui| {
+                        for row in rows {
+                            matrix_row_ui(ui, row);
+                        }
+                    });
+            });
+        if rerun {
+            let example = self
+                .examples
+                .iter()
+                .find(|example| example.metadata.id == example_id)
+                .cloned();
+            if let Some(example) = example {
+                self.start_version_matrix(&example);
+            }
+        }
+        if !open {
+            self.version_matrix = None;
+        }
+    }
+}
+
+fn script_row(builds: &[KotoBuild], script: &str, scratch_dir: &Path) -> anyhow::Result<MatrixRow> {
+    let runtime = Runtime::new()?;
+    let start = Instant::now();
+    let result = runtime.execute_script_with_timeout(script, Some(MATRIX_TIMEOUT));
+    let duration = start.elapsed();
+    let baseline = match result {
+        Ok(output) => BuildRun::embedded(true, output.stdout, output.stderr, duration),
+        Err(error) => BuildRun::embedded(false, String::new(), format!("{error:#}"), duration),
+    };
+    Ok(MatrixRow {
+        target: "Script".to_string(),
+        compare_output: true,
+        baseline,
+        builds: run_builds(builds, script, false, scratch_dir),
+    })
+}
+
+fn suite_row(builds: &[KotoBuild], suite: &ExampleTestSuite, scratch_dir: &Path) -> MatrixRow {
+    let baseline = match SuiteRunner::new().timeout(MATRIX_TIMEOUT).run(suite) {
+        Ok(result) => BuildRun::embedded(
+            result.passed,
+            result.setup_stdout,
+            result.setup_stderr,
+            result.total_duration,
+        ),
+        Err(error) => {
+            BuildRun::embedded(false, String::new(), format!("{error:#}"), Duration::ZERO)
+        }
+    };
+    MatrixRow {
+        target: suite.name.clone(),
+        compare_output: false,
+        baseline,
+        builds: run_builds(builds, &suite.script, true, scratch_dir),
+    }
+}
+
+fn run_builds(
+    builds: &[KotoBuild],
+    script: &str,
+    suite: bool,
+    scratch_dir: &Path,
+) -> Vec<BuildRun> {
+    builds
+        .iter()
+        .map(|build| {
+            version_matrix::run_build(build, script, suite, scratch_dir, MATRIX_TIMEOUT)
+                .unwrap_or_else(|error| BuildRun::failed_to_start(build, &error))
+        })
+        .collect()
+}
+
+fn matrix_row_ui(ui: &mut egui::Ui, row: &MatrixRow) {
+    ui.label(RichText::new(&row.target).strong());
+    let differences = row.differences();
+    Grid::new(("version_matrix", &row.target))
+        .num_columns(4)
+        .striped(true)
+        .show(ui, |grid| {
+            for run in std::iter::once(&row.baseline).chain(&row.builds) {
+                grid.label(&run.build);
+                let (text, color) = if run.succeeded {
+                    ("passed", Color32::from_rgb(120, 200, 120))
+                } else {
+                    ("failed", Color32::from_rgb(220, 100, 100))
+                };
+                grid.label(RichText::new(text).color(color))
+                    .on_hover_text(format!("{}{}", run.stdout, run.stderr));
+                grid.label(format_duration(run.duration));
+                match differences.iter().find(|(build, _)| *build == run.build) {
+                    Some((_, change)) => {
+                        grid.label(RichText::new(change).color(Color32::from_rgb(220, 180, 80)));
+                    }
+                    None => {
+                        grid.label("");
+                    }
+                }
+                grid.end_row();
+            }
+        });
+    ui.add_space(6.0);
+}
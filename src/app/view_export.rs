@@ -0,0 +1,186 @@
+#[cfg(feature = "image")]
+use std::fmt::Write as _;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, anyhow};
+use eframe::egui;
+
+use super::{ExplorerApp, SnackbarKind, offscreen};
+
+/// JPEG quality for the page image in PDF exports.
+#[cfg(feature = "image")]
+const PDF_JPEG_QUALITY: u8 = 90;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum ViewExportFormat {
+    Png,
+    Pdf,
+}
+
+impl ViewExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Pdf => "pdf",
+        }
+    }
+}
+
+impl ExplorerApp {
+    /// Draws the example panel and console off screen, as they look in the window, and saves
+    /// the image under `exports/` in the background.
+    pub(super) fn export_view(&mut self, ctx: &egui::Context, format: ViewExportFormat) {
+        let (Some(example), Some(rect)) = (self.selected_example(), self.view_rect) else {
+            self.push_snackbar("Select an example to export its view", SnackbarKind::Info);
+            return;
+        };
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        let path = PathBuf::from("exports").join(format!(
+            "view_{}_{timestamp}.{}",
+            example.metadata.id,
+            format.extension()
+        ));
+        let image = offscreen::render(ctx, rect.size(), |ctx| self.view_export_ui(ctx));
+        let pixels_per_point = ctx.pixels_per_point();
+        self.export_in_background("the view", move || {
+            fs::create_dir_all("exports").context("failed to create exports/")?;
+            save_view(&image, format, pixels_per_point, &path)?;
+            Ok(format!("Exported the view to {}", path.display()))
+        });
+    }
+
+    /// The panels a view export shows: the console, when it's open, below the example panel.
+    fn view_export_ui(&mut self, ctx: &egui::Context) {
+        if let Some(height) = self.console_height {
+            egui::TopBottomPanel::bottom("console_panel")
+                .exact_height(height)
+                .show(ctx, |ui| self.console_ui(ui, ctx));
+        }
+        egui::CentralPanel::default().show(ctx, |ui| self.main_panel_ui(ui, ctx));
+    }
+}
+
+#[cfg(feature = "image")]
+fn save_view(
+    image: &egui::ColorImage,
+    format: ViewExportFormat,
+    pixels_per_point: f32,
+    path: &Path,
+) -> anyhow::Result<()> {
+    match format {
+        ViewExportFormat::Png => save_png(image, path),
+        ViewExportFormat::Pdf => save_pdf(image, pixels_per_point, path),
+    }
+}
+
+#[cfg(not(feature = "image"))]
+fn save_view(
+    _image: &egui::ColorImage,
+    _format: ViewExportFormat,
+    _pixels_per_point: f32,
+    _path: &Path,
+) -> anyhow::Result<()> {
+    Err(anyhow!(
+        "view exports need koto_learning built with the `image` feature"
+    ))
+}
+
+#[cfg(feature = "image")]
+fn rgba_image(image: &egui::ColorImage) -> anyhow::Result<image::RgbaImage> {
+    let [width, height] = image.size;
+    if width == 0 || height == 0 {
+        return Err(anyhow!("the view is not visible"));
+    }
+    image::RgbaImage::from_raw(width as u32, height as u32, image.as_raw().to_vec())
+        .context("the screenshot has an unexpected size")
+}
+
+#[cfg(feature = "image")]
+fn save_png(image: &egui::ColorImage, path: &Path) -> anyhow::Result<()> {
+    rgba_image(image)?
+        .save(path)
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Writes a one-page PDF holding the screenshot as a JPEG, sized so that one point in the UI
+/// is one point on the page.
+#[cfg(feature = "image")]
+fn save_pdf(image: &egui::ColorImage, pixels_per_point: f32, path: &Path) -> anyhow::Result<()> {
+    let rgb = image::DynamicImage::ImageRgba8(rgba_image(image)?).into_rgb8();
+    let mut jpeg = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, PDF_JPEG_QUALITY)
+        .encode_image(&rgb)
+        .context("failed to encode the page image")?;
+    let page_width = rgb.width() as f32 / pixels_per_point;
+    let page_height = rgb.height() as f32 / pixels_per_point;
+    let pdf = image_pdf(&jpeg, rgb.width(), rgb.height(), page_width, page_height);
+    fs::write(path, pdf).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// A minimal PDF: a catalog, one page, the JPEG as an image XObject, and a content stream
+/// that stretches it over the page.
+#[cfg(feature = "image")]
+fn image_pdf(jpeg: &[u8], width: u32, height: u32, page_width: f32, page_height: f32) -> Vec<u8> {
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::new();
+    let mut object = |pdf: &mut Vec<u8>, header: String, stream: Option<&[u8]>| {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n{header}\n", offsets.len()).as_bytes());
+        if let Some(stream) = stream {
+            pdf.extend_from_slice(b"stream\n");
+            pdf.extend_from_slice(stream);
+            pdf.extend_from_slice(b"\nendstream\n");
+        }
+        pdf.extend_from_slice(b"endobj\n");
+    };
+
+    let content = format!("q {page_width:.2} 0 0 {page_height:.2} 0 0 cm /Im0 Do Q");
+    object(&mut pdf, "<< /Type /Catalog /Pages 2 0 R >>".into(), None);
+    object(
+        &mut pdf,
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".into(),
+        None,
+    );
+    object(
+        &mut pdf,
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {page_width:.2} {page_height:.2}] \
+             /Resources << /XObject << /Im0 4 0 R >> >> /Contents 5 0 R >>"
+        ),
+        None,
+    );
+    object(
+        &mut pdf,
+        format!(
+            "<< /Type /XObject /Subtype /Image /Width {width} /Height {height} \
+             /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /DCTDecode /Length {} >>",
+            jpeg.len()
+        ),
+        Some(jpeg),
+    );
+    object(
+        &mut pdf,
+        format!("<< /Length {} >>", content.len()),
+        Some(content.as_bytes()),
+    );
+
+    let xref_offset = pdf.len();
+    let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1);
+    for offset in &offsets {
+        let _ = writeln!(xref, "{offset:010} 00000 n ");
+    }
+    let _ = write!(
+        xref,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref_offset}\n%%EOF\n",
+        offsets.len() + 1
+    );
+    pdf.extend_from_slice(xref.as_bytes());
+    pdf
+}
@@ -0,0 +1,122 @@
+use eframe::egui;
+use egui::{Color32, RichText};
+use koto::prelude::KValue;
+
+use super::{ConsoleEntry, ExplorerApp, example_session_id};
+use crate::runtime;
+
+const WATCH_HINT: &str =
+    "Evaluated after each run; `result` is the return value and exports are in scope";
+
+/// A Koto expression evaluated after every successful run of its example.
+pub(super) struct Watch {
+    expression: String,
+    /// The display text of the latest value, or the error it raised; `None` until a run.
+    value: Option<Result<String, String>>,
+}
+
+impl ExplorerApp {
+    /// Re-evaluates `example_id`'s watches against the exports of the run that just finished,
+    /// with `result` bound to its return value.
+    pub(super) fn evaluate_watches(&mut self, example_id: &str, value: Option<KValue>) {
+        let Some(watches) = self.watches.get_mut(example_id) else {
+            return;
+        };
+        if watches.is_empty() {
+            return;
+        }
+        let expressions: Vec<String> = watches
+            .iter()
+            .map(|watch| watch.expression.clone())
+            .collect();
+        let session = self.session_mode.then(|| example_session_id(example_id));
+        match runtime::RUNTIME.evaluate_watches(&expressions, value, session.as_deref()) {
+            Ok(results) => {
+                for (watch, result) in watches.iter_mut().zip(results) {
+                    watch.value = Some(result);
+                }
+            }
+            Err(error) => {
+                self.push_console_entry(ConsoleEntry::error(format!(
+                    "Failed to evaluate watch expressions: {error:#}"
+                )));
+            }
+        }
+    }
+
+    /// The example's watch expressions with their latest values, and a field to add more.
+    pub(super) fn watch_panel_ui(&mut self, ui: &mut egui::Ui, example_id: &str) {
+        let count = self.watches.get(example_id).map_or(0, Vec::len);
+        let header = if count == 0 {
+            "Watch expressions".to_string()
+        } else {
+            format!("Watch expressions ({count})")
+        };
+        egui::CollapsingHeader::new(header)
+            .id_salt(("watches", example_id))
+            .default_open(count > 0)
+            .show(ui, |ui| {
+                let mut remove = None;
+                if let Some(watches) = self.watches.get(example_id) {
+                    egui::Grid::new(("watch_grid", example_id))
+                        .num_columns(3)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (index, watch) in watches.iter().enumerate() {
+                                ui.monospace(&watch.expression);
+                                match &watch.value {
+                                    Some(Ok(value)) => {
+                                        ui.monospace(value);
+                                    }
+                                    Some(Err(error)) => {
+                                        let first_line = error.lines().next().unwrap_or_default();
+                                        ui.label(
+                                            RichText::new(first_line)
+                                                .monospace()
+                                                .color(Color32::from_rgb(220, 100, 100)),
+                                        )
+                                        .on_hover_text(error);
+                                    }
+                                    None => {
+                                        ui.label(RichText::new("run to evaluate").weak());
+                                    }
+                                }
+                                if ui.small_button("✖").on_hover_text("Remove").clicked() {
+                                    remove = Some(index);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                }
+                if let Some(index) = remove {
+                    if let Some(watches) = self.watches.get_mut(example_id) {
+                        watches.remove(index);
+                    }
+                }
+
+                ui.horizontal(|ui| {
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.watch_input)
+                            .code_editor()
+                            .hint_text("result.size()")
+                            .desired_width(220.0),
+                    );
+                    let entered = ui.input(|input| input.key_pressed(egui::Key::Enter));
+                    let submitted = response.lost_focus() && entered;
+                    if (ui.button("Add watch").clicked() || submitted)
+                        && !self.watch_input.trim().is_empty()
+                    {
+                        let expression = std::mem::take(&mut self.watch_input).trim().to_string();
+                        self.watches
+                            .entry(example_id.to_string())
+                            .or_default()
+                            .push(Watch {
+                                expression,
+                                value: None,
+                            });
+                    }
+                });
+                ui.label(RichText::new(WATCH_HINT).small().weak());
+            });
+    }
+}
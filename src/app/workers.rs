@@ -0,0 +1,75 @@
+use eframe::egui;
+use egui::RichText;
+
+use super::{ConsoleEntry, ConsoleKind, ExplorerApp};
+use crate::runtime::observer::OutputStream;
+
+/// What one `concurrent` worker has printed during the current run, in order.
+pub(super) struct WorkerStream {
+    name: String,
+    chunks: Vec<(OutputStream, String)>,
+}
+
+impl ExplorerApp {
+    pub(super) fn push_worker_output(
+        &mut self,
+        worker: String,
+        stream: OutputStream,
+        text: String,
+    ) {
+        let index = match self
+            .worker_streams
+            .iter()
+            .position(|existing| existing.name == worker)
+        {
+            Some(index) => index,
+            None => {
+                self.push_console_entry(ConsoleEntry::info(format!(
+                    "Worker '{worker}' started writing output; see the Workers tab"
+                )));
+                self.worker_streams.push(WorkerStream {
+                    name: worker,
+                    chunks: Vec::new(),
+                });
+                self.worker_streams.len() - 1
+            }
+        };
+        let chunks = &mut self.worker_streams[index].chunks;
+        match chunks.last_mut() {
+            Some((last, existing)) if *last == stream => existing.push_str(&text),
+            _ => chunks.push((stream, text)),
+        }
+    }
+
+    pub(super) fn workers_ui(&mut self, ui: &mut egui::Ui) {
+        if self.worker_streams.is_empty() {
+            ui.label("No worker output in the last run.");
+            ui.label(
+                RichText::new("Start workers with concurrent.spawn to see their output here.")
+                    .small(),
+            );
+            return;
+        }
+
+        egui::ScrollArea::vertical()
+            .id_salt("worker_streams")
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for worker in &self.worker_streams {
+                    egui::CollapsingHeader::new(RichText::new(&worker.name).strong())
+                        .id_salt(("worker_stream", &worker.name))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            for (stream, text) in &worker.chunks {
+                                let kind = match stream {
+                                    OutputStream::Stdout => ConsoleKind::Stdout,
+                                    OutputStream::Stderr => ConsoleKind::Stderr,
+                                };
+                                let color = kind.color(ui.visuals());
+                                ui.label(RichText::new(text.trim_end()).monospace().color(color));
+                            }
+                        });
+                }
+            });
+    }
+}
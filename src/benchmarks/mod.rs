@@ -25,6 +25,20 @@ pub struct BenchmarkMeasurement {
     pub std_dev_ms: Option<f64>,
 }
 
+impl BenchmarkMeasurement {
+    /// What the benchmark measures, judging by its id: Koto compilation, Koto execution
+    /// (runs reuse the compiled chunk), or a native Rust baseline.
+    pub fn phase(&self) -> &'static str {
+        if self.benchmark_id.contains("compile") {
+            "compile"
+        } else if self.benchmark_id.starts_with("rust_") {
+            "native"
+        } else {
+            "execute"
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct EstimateSummary {
     pub point_estimate_ms: f64,
@@ -53,7 +67,12 @@ struct ConfidenceInterval {
     upper_bound: f64,
 }
 
+/// Reads the Criterion results for `example_id`; always `None` without the `benchmarks`
+/// feature.
 pub fn load_example_summary(example_id: &str) -> Option<ExampleBenchmarkSummary> {
+    if !cfg!(feature = "benchmarks") {
+        return None;
+    }
     let base = Path::new("target").join("criterion").join(example_id);
     if !base.exists() {
         return None;
@@ -0,0 +1,68 @@
+//! Landing pages that introduce a category before its examples. Each lives in
+//! `categories/<category>/category.md` next to the example folders, where the folder name is
+//! the category in lowercase with anything but letters and digits replaced by `_`, e.g.
+//! `categories/data_formats/category.md` for "Data formats".
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+
+pub const CATEGORIES_DIR_NAME: &str = "categories";
+pub const CATEGORY_PAGE_FILE_NAME: &str = "category.md";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CategoryPage {
+    pub path: PathBuf,
+    pub markdown: String,
+}
+
+/// The folder name that holds `category`'s landing page.
+pub fn category_slug(category: &str) -> String {
+    category
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Where `category`'s landing page belongs in the catalog at `examples_dir`.
+pub fn page_path(examples_dir: &Path, category: &str) -> PathBuf {
+    examples_dir
+        .join(CATEGORIES_DIR_NAME)
+        .join(category_slug(category))
+        .join(CATEGORY_PAGE_FILE_NAME)
+}
+
+/// Reads every landing page in the catalog at `examples_dir`, keyed by folder name, i.e. by
+/// [`category_slug`]. Category folders without a `category.md` are skipped.
+pub fn load_pages(examples_dir: &Path) -> Result<BTreeMap<String, CategoryPage>> {
+    let mut pages = BTreeMap::new();
+    let dir = examples_dir.join(CATEGORIES_DIR_NAME);
+    if !dir.is_dir() {
+        return Ok(pages);
+    }
+    for entry in fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let entry = entry?;
+        let path = entry.path().join(CATEGORY_PAGE_FILE_NAME);
+        if !entry.file_type()?.is_dir() || !path.is_file() {
+            continue;
+        }
+        let markdown = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        pages.insert(
+            entry.file_name().to_string_lossy().to_string(),
+            CategoryPage { path, markdown },
+        );
+    }
+    Ok(pages)
+}
@@ -0,0 +1,113 @@
+//! Instructor tooling: packing a catalog for students without the reference solutions.
+//! Writing the zip needs the `archive` feature.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+#[cfg(feature = "archive")]
+use std::{fs::File, io::Write};
+
+use anyhow::{Context, Result};
+#[cfg(feature = "archive")]
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+#[cfg(feature = "archive")]
+use super::SOLUTION_FILE_NAME;
+use super::paste::SCRATCH_EXAMPLE_ID;
+
+/// Folder inside the zip that holds the catalog, so unpacking it gives one examples folder.
+#[cfg(feature = "archive")]
+const PACK_ROOT: &str = "examples";
+
+/// What went into a student pack.
+#[derive(Clone, Debug, Default)]
+pub struct StudentPackReport {
+    /// Number of example folders in the pack.
+    pub examples: usize,
+    /// Number of `solution.koto` files that were left out.
+    pub solutions_removed: usize,
+}
+
+/// Zips every example folder in `examples_dir` into `output`, leaving out solution files,
+/// hidden files, and the scratch example.
+pub fn export_student_pack(examples_dir: &Path, output: &Path) -> Result<StudentPackReport> {
+    let mut example_dirs: Vec<_> = fs::read_dir(examples_dir)
+        .with_context(|| format!("Failed to read {examples_dir:?}"))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.join("meta.json").is_file())
+        .filter(|path| !is_hidden(path) && !path.ends_with(SCRATCH_EXAMPLE_ID))
+        .collect();
+    if example_dirs.is_empty() {
+        anyhow::bail!("{} has no examples to pack", examples_dir.display());
+    }
+    example_dirs.sort();
+    write_pack(examples_dir, &example_dirs, output)
+}
+
+#[cfg(feature = "archive")]
+fn write_pack(
+    examples_dir: &Path,
+    example_dirs: &[PathBuf],
+    output: &Path,
+) -> Result<StudentPackReport> {
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("Failed to create {parent:?}"))?;
+    }
+    let file = File::create(output).with_context(|| format!("Failed to create {output:?}"))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut report = StudentPackReport::default();
+    for dir in example_dirs {
+        add_dir(&mut zip, options, examples_dir, dir, &mut report)?;
+        report.examples += 1;
+    }
+    zip.finish()
+        .with_context(|| format!("Failed to finish {output:?}"))?;
+    Ok(report)
+}
+
+#[cfg(not(feature = "archive"))]
+fn write_pack(_: &Path, _: &[PathBuf], _: &Path) -> Result<StudentPackReport> {
+    anyhow::bail!("Student packs need koto_learning built with the `archive` feature")
+}
+
+#[cfg(feature = "archive")]
+fn add_dir(
+    zip: &mut ZipWriter<File>,
+    options: SimpleFileOptions,
+    root: &Path,
+    dir: &Path,
+    report: &mut StudentPackReport,
+) -> Result<()> {
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read {dir:?}"))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| !is_hidden(path))
+        .collect();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            add_dir(zip, options, root, &path, report)?;
+            continue;
+        }
+        if path.ends_with(SOLUTION_FILE_NAME) {
+            report.solutions_removed += 1;
+            continue;
+        }
+        let relative = path.strip_prefix(root).unwrap_or(&path);
+        let name = format!(
+            "{PACK_ROOT}/{}",
+            relative.to_string_lossy().replace('\\', "/")
+        );
+        let contents = fs::read(&path).with_context(|| format!("Failed to read {path:?}"))?;
+        zip.start_file(name, options)?;
+        zip.write_all(&contents)?;
+    }
+    Ok(())
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
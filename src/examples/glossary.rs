@@ -0,0 +1,162 @@
+//! The catalog's glossary: Koto terms with a short definition and the examples that show them
+//! in use, read from `glossary.json` next to the example folders:
+//!
+//! ```json
+//! { "terms": [{ "term": "closure", "aliases": ["closures"],
+//!               "definition": "A function that captures values from where it was created.",
+//!               "examples": ["basics"] }] }
+//! ```
+
+use std::{cmp::Reverse, fs, path::Path};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+pub const GLOSSARY_FILE_NAME: &str = "glossary.json";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GlossaryTerm {
+    pub term: String,
+    pub definition: String,
+    /// Other spellings that link to this term, e.g. `closures` for `closure`.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Ids of the examples that show the term in use.
+    #[serde(default)]
+    pub examples: Vec<String>,
+}
+
+impl GlossaryTerm {
+    fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.term.as_str())
+            .chain(self.aliases.iter().map(String::as_str))
+            .filter(|name| !name.trim().is_empty())
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Glossary {
+    #[serde(default)]
+    pub terms: Vec<GlossaryTerm>,
+}
+
+/// A piece of text split up by [`Glossary::link_terms`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextSpan<'a> {
+    Plain(&'a str),
+    /// A mention of `term`, spelled as in the text.
+    Term {
+        text: &'a str,
+        term: &'a GlossaryTerm,
+    },
+}
+
+impl Glossary {
+    /// Loads the glossary of the catalog in `examples_dir`; a catalog without one has no terms.
+    pub fn load(examples_dir: &Path) -> Result<Self> {
+        let path = examples_dir.join(GLOSSARY_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Self::load_from(&path)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&text).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Looks a term up by its name or one of its aliases, ignoring case.
+    pub fn get(&self, name: &str) -> Option<&GlossaryTerm> {
+        self.terms.iter().find(|term| {
+            term.names()
+                .any(|candidate| candidate.eq_ignore_ascii_case(name.trim()))
+        })
+    }
+
+    /// The terms in alphabetical order.
+    pub fn sorted(&self) -> Vec<&GlossaryTerm> {
+        let mut terms: Vec<&GlossaryTerm> = self.terms.iter().collect();
+        terms.sort_by_key(|term| term.term.to_lowercase());
+        terms
+    }
+
+    /// Terms that `example_id` lists among its examples.
+    pub fn terms_for_example(&self, example_id: &str) -> Vec<&GlossaryTerm> {
+        self.sorted()
+            .into_iter()
+            .filter(|term| term.examples.iter().any(|id| id == example_id))
+            .collect()
+    }
+
+    /// Splits `text` into plain runs and mentions of glossary terms. Only whole words match,
+    /// ignoring case, and only the first mention of each term is linked so prose with a term
+    /// on every line doesn't turn into a wall of links. Longer names win over shorter ones
+    /// they contain, e.g. `test suite` over `test`.
+    pub fn link_terms<'a>(&'a self, text: &'a str) -> Vec<TextSpan<'a>> {
+        let mut names: Vec<(&str, &GlossaryTerm)> = self
+            .terms
+            .iter()
+            .flat_map(|term| term.names().map(move |name| (name, term)))
+            .collect();
+        names.sort_by_key(|(name, _)| Reverse(name.len()));
+
+        let mut mentions: Vec<(usize, usize, &GlossaryTerm)> = Vec::new();
+        for (name, term) in names {
+            if mentions
+                .iter()
+                .any(|(_, _, linked)| std::ptr::eq(*linked, term))
+            {
+                continue;
+            }
+            if let Some(start) = find_word(text, name, &mentions) {
+                mentions.push((start, start + name.len(), term));
+            }
+        }
+        mentions.sort_by_key(|(start, _, _)| *start);
+
+        let mut spans = Vec::new();
+        let mut position = 0;
+        for (start, end, term) in mentions {
+            if start > position {
+                spans.push(TextSpan::Plain(&text[position..start]));
+            }
+            spans.push(TextSpan::Term {
+                text: &text[start..end],
+                term,
+            });
+            position = end;
+        }
+        if position < text.len() {
+            spans.push(TextSpan::Plain(&text[position..]));
+        }
+        spans
+    }
+}
+
+/// The byte offset of the first whole-word, case-insensitive occurrence of `name` in `text`
+/// that doesn't overlap one of `taken`.
+fn find_word(text: &str, name: &str, taken: &[(usize, usize, &GlossaryTerm)]) -> Option<usize> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    text.char_indices().map(|(start, _)| start).find(|&start| {
+        let end = start + name.len();
+        if end > text.len() || !text.is_char_boundary(end) {
+            return false;
+        }
+        if !text[start..end].eq_ignore_ascii_case(name) {
+            return false;
+        }
+        let before = text[..start].chars().next_back();
+        let after = text[end..].chars().next();
+        !before.is_some_and(is_word)
+            && !after.is_some_and(is_word)
+            && !taken
+                .iter()
+                .any(|(taken_start, taken_end, _)| start < *taken_end && *taken_start < end)
+    })
+}
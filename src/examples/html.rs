@@ -0,0 +1,356 @@
+//! Standalone HTML pages for single examples, for printing or uploading to a learning
+//! platform. Everything, styles included, lives in the one file.
+
+use std::{fmt::Write as _, fs, path::Path};
+
+use anyhow::{Context, Result};
+
+use super::Example;
+use crate::language::symbols::KEYWORDS;
+
+const STYLE: &str = "\
+body { font-family: system-ui, sans-serif; max-width: 48rem; margin: 2rem auto; padding: 0 1rem; color: #222; line-height: 1.5; }
+h1 { margin-bottom: 0.2rem; }
+.description { color: #555; margin-top: 0; }
+.categories span { display: inline-block; margin-right: 0.4rem; padding: 0 0.5rem; border-radius: 0.6rem; background: #eef; font-size: 0.85rem; }
+pre { background: #f6f8fa; border: 1px solid #ddd; border-radius: 4px; padding: 0.75rem; overflow-x: auto; white-space: pre-wrap; }
+code { font-family: ui-monospace, monospace; font-size: 0.9rem; }
+.kw { color: #a626a4; font-weight: bold; }
+.str { color: #50a14f; }
+.num { color: #986801; }
+.comment { color: #8a8a8a; font-style: italic; }
+.meta { color: #4078f2; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.25rem 0.6rem; text-align: right; }
+th:first-child, td:first-child { text-align: left; }
+footer { margin-top: 2rem; color: #888; font-size: 0.8rem; }
+@media print { body { margin: 0; max-width: none; } pre { break-inside: avoid; } }
+";
+
+/// Output from a run of the example, shown under its code.
+#[derive(Clone, Debug, Default)]
+pub struct SampleOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub return_value: Option<String>,
+}
+
+/// Writes `example` as a standalone HTML page to `path`, including its docs when they are on
+/// disk and `output` when given.
+pub fn export_html(example: &Example, output: Option<&SampleOutput>, path: &Path) -> Result<()> {
+    let docs = match &example.docs {
+        Some(docs) => Some(
+            fs::read_to_string(&docs.path)
+                .with_context(|| format!("Failed to read {}", docs.path.display()))?,
+        ),
+        None => None,
+    };
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(path, example_html(example, docs.as_deref(), output))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// The page for `example`: title and description, `docs` rendered from markdown, the script
+/// with Koto highlighting, `output`, and the benchmark results.
+pub fn example_html(
+    example: &Example,
+    docs: Option<&str>,
+    output: Option<&SampleOutput>,
+) -> String {
+    let metadata = &example.metadata;
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>\n{STYLE}</style>\n</head>\n<body>\n",
+        escape(&metadata.title)
+    );
+    let _ = writeln!(html, "<h1>{}</h1>", escape(&metadata.title));
+    let _ = writeln!(
+        html,
+        "<p class=\"description\">{}</p>",
+        escape(&metadata.description)
+    );
+    if !metadata.categories.is_empty() {
+        html.push_str("<p class=\"categories\">");
+        for category in &metadata.categories {
+            let _ = write!(html, "<span>{}</span>", escape(category));
+        }
+        html.push_str("</p>\n");
+    }
+    if let Some(note) = &metadata.note {
+        let _ = writeln!(html, "<p><em>{}</em></p>", escape(note));
+    }
+
+    if let Some(docs) = docs {
+        html.push_str("<section class=\"docs\">\n");
+        html.push_str(&markdown_html(docs));
+        html.push_str("</section>\n");
+    }
+    if !metadata.how_it_works.is_empty() {
+        html.push_str("<h2>How it works</h2>\n<ul>\n");
+        for step in &metadata.how_it_works {
+            match step.lines {
+                Some((first, last)) => {
+                    let _ = writeln!(
+                        html,
+                        "<li>{} <small>(lines {first}–{last})</small></li>",
+                        inline_html(&step.text)
+                    );
+                }
+                None => {
+                    let _ = writeln!(html, "<li>{}</li>", inline_html(&step.text));
+                }
+            }
+        }
+        html.push_str("</ul>\n");
+    }
+
+    let _ = writeln!(
+        html,
+        "<h2>Code</h2>\n<pre><code>{}</code></pre>",
+        highlight_koto(&example.script)
+    );
+
+    if let Some(output) = output {
+        html.push_str("<h2>Sample output</h2>\n");
+        if !output.stdout.is_empty() {
+            let _ = writeln!(html, "<pre>{}</pre>", escape(output.stdout.trim_end()));
+        }
+        if !output.stderr.is_empty() {
+            let _ = writeln!(
+                html,
+                "<p>Standard error:</p>\n<pre>{}</pre>",
+                escape(output.stderr.trim_end())
+            );
+        }
+        if let Some(value) = &output.return_value {
+            let _ = writeln!(html, "<p>Return value: <code>{}</code></p>", escape(value));
+        }
+    }
+
+    if let Some(summary) = example
+        .benchmark_summary
+        .as_ref()
+        .filter(|summary| !summary.measurements.is_empty())
+    {
+        html.push_str(
+            "<h2>Benchmarks</h2>\n<table>\n<tr><th>Benchmark</th><th>Phase</th>\
+             <th>Mean (ms)</th><th>Range (ms)</th></tr>\n",
+        );
+        for measurement in &summary.measurements {
+            let name = match &measurement.parameter {
+                Some(parameter) => format!("{} ({parameter})", measurement.benchmark_id),
+                None => measurement.benchmark_id.clone(),
+            };
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{:.3}</td><td>{:.3} – {:.3}</td></tr>",
+                escape(&name),
+                measurement.phase(),
+                measurement.mean.point_estimate_ms,
+                measurement.mean.lower_bound_ms,
+                measurement.mean.upper_bound_ms,
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    let _ = writeln!(
+        html,
+        "<footer>Exported from Koto Learning Explorer · example <code>{}</code></footer>",
+        escape(&metadata.id)
+    );
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// The same subset of markdown the explorer renders: headings, bullets, fenced blocks, and
+/// paragraphs, plus inline code. Koto fences are highlighted like the script.
+pub fn markdown_html(text: &str) -> String {
+    let mut html = String::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut in_list = false;
+    let mut fence: Option<(bool, String)> = None;
+
+    let flush = |html: &mut String, paragraph: &mut Vec<&str>, in_list: &mut bool| {
+        if !paragraph.is_empty() {
+            let _ = writeln!(html, "<p>{}</p>", inline_html(&paragraph.join(" ")));
+            paragraph.clear();
+        }
+        if *in_list {
+            html.push_str("</ul>\n");
+            *in_list = false;
+        }
+    };
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if let Some(language) = trimmed.strip_prefix("```") {
+            match fence.take() {
+                Some((koto, code)) => {
+                    let code = if koto {
+                        highlight_koto(&code)
+                    } else {
+                        escape(&code).into_owned()
+                    };
+                    let _ = writeln!(html, "<pre><code>{}</code></pre>", code.trim_end());
+                }
+                None => {
+                    flush(&mut html, &mut paragraph, &mut in_list);
+                    let language = language.trim();
+                    fence = Some((language.is_empty() || language == "koto", String::new()));
+                }
+            }
+            continue;
+        }
+        if let Some((_, code)) = &mut fence {
+            code.push_str(line);
+            code.push('\n');
+            continue;
+        }
+
+        let heading = [("### ", "h4"), ("## ", "h3"), ("# ", "h2")]
+            .into_iter()
+            .find_map(|(prefix, tag)| trimmed.strip_prefix(prefix).map(|text| (tag, text)));
+        if let Some((tag, text)) = heading {
+            flush(&mut html, &mut paragraph, &mut in_list);
+            let _ = writeln!(html, "<{tag}>{}</{tag}>", inline_html(text));
+        } else if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+        {
+            if !paragraph.is_empty() {
+                flush(&mut html, &mut paragraph, &mut in_list);
+            }
+            if !in_list {
+                html.push_str("<ul>\n");
+                in_list = true;
+            }
+            let _ = writeln!(html, "<li>{}</li>", inline_html(item));
+        } else if trimmed.is_empty() {
+            flush(&mut html, &mut paragraph, &mut in_list);
+        } else {
+            if in_list {
+                flush(&mut html, &mut paragraph, &mut in_list);
+            }
+            paragraph.push(trimmed);
+        }
+    }
+    if let Some((_, code)) = fence {
+        let _ = writeln!(html, "<pre><code>{}</code></pre>", escape(code.trim_end()));
+    }
+    flush(&mut html, &mut paragraph, &mut in_list);
+    html
+}
+
+/// Escapes `text`, turning `backticked` spans into `<code>`.
+fn inline_html(text: &str) -> String {
+    text.split('`')
+        .enumerate()
+        .map(|(index, part)| {
+            if index % 2 == 1 {
+                format!("<code>{}</code>", escape(part))
+            } else {
+                escape(part).into_owned()
+            }
+        })
+        .collect()
+}
+
+/// Wraps keywords, strings, numbers, comments, and `@meta` keys in `<span>`s for the page's
+/// styles. It only needs to look right, so string interpolation isn't tracked.
+pub fn highlight_koto(script: &str) -> String {
+    let mut html = String::new();
+    let chars: Vec<char> = script.chars().collect();
+    let mut index = 0;
+    let span = |html: &mut String, class: &str, text: &str| {
+        let _ = write!(html, "<span class=\"{class}\">{}</span>", escape(text));
+    };
+
+    while index < chars.len() {
+        let start = index;
+        let c = chars[index];
+        if c == '#' {
+            let block = chars.get(index + 1) == Some(&'-');
+            index += 1;
+            while index < chars.len() {
+                if block && chars[index] == '#' && chars[index - 1] == '-' && index > start + 1 {
+                    index += 1;
+                    break;
+                }
+                if !block && chars[index] == '\n' {
+                    break;
+                }
+                index += 1;
+            }
+            span(&mut html, "comment", &collect(&chars[start..index]));
+        } else if c == '\'' || c == '"' {
+            index += 1;
+            while index < chars.len() && chars[index] != c {
+                if chars[index] == '\\' {
+                    index += 1;
+                }
+                index += 1;
+            }
+            index = (index + 1).min(chars.len());
+            span(&mut html, "str", &collect(&chars[start..index]));
+        } else if c.is_ascii_digit() {
+            while index < chars.len()
+                && (chars[index].is_ascii_alphanumeric()
+                    || chars[index] == '_'
+                    || chars[index] == '.')
+            {
+                // `1..10` is a range, not a float.
+                if chars[index] == '.' && chars.get(index + 1) == Some(&'.') {
+                    break;
+                }
+                index += 1;
+            }
+            span(&mut html, "num", &collect(&chars[start..index]));
+        } else if c.is_alphabetic() || c == '_' || c == '@' {
+            index += 1;
+            while index < chars.len() && (chars[index].is_alphanumeric() || chars[index] == '_') {
+                index += 1;
+            }
+            let word = collect(&chars[start..index]);
+            if c == '@' {
+                span(&mut html, "meta", &word);
+            } else if KEYWORDS.contains(&word.as_str()) {
+                span(&mut html, "kw", &word);
+            } else {
+                html.push_str(&escape(&word));
+            }
+        } else {
+            index += 1;
+            html.push_str(&escape(&collect(&chars[start..index])));
+        }
+    }
+    html
+}
+
+fn collect(chars: &[char]) -> String {
+    chars.iter().collect()
+}
+
+/// Escapes `text` for use in HTML or XML text and attribute values.
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,190 @@
+//! Bootstraps a catalog from the official Koto repository: its `.koto` example scripts and
+//! language tests become explorer examples with generated metadata.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{Context, Result};
+
+use super::slugify;
+
+pub const KOTO_REPOSITORY_URL: &str = "https://github.com/koto-lang/koto";
+
+/// Folders of a Koto checkout that hold scripts worth importing, relative to its root.
+const SCRIPT_DIRS: &[&str] = &["examples", "koto/tests", "crates/koto/tests"];
+
+/// Where to import from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ImportSource {
+    /// A local clone of the Koto repository.
+    Checkout(PathBuf),
+    /// A git URL, cloned into a temporary folder for the import.
+    Repository(String),
+}
+
+impl ImportSource {
+    /// Treats URLs (`https://…`, `git@…`, or anything ending in `.git`) as repositories and
+    /// everything else as a local path.
+    pub fn parse(text: &str) -> Self {
+        let text = text.trim();
+        if text.starts_with("https://")
+            || text.starts_with("http://")
+            || text.starts_with("git@")
+            || text.ends_with(".git")
+        {
+            Self::Repository(text.to_string())
+        } else {
+            Self::Checkout(PathBuf::from(text))
+        }
+    }
+}
+
+/// What an import added to the catalog.
+#[derive(Clone, Debug, Default)]
+pub struct ImportReport {
+    /// Ids of the examples that were created.
+    pub imported: Vec<String>,
+    /// Ids that already existed in the catalog and were left alone.
+    pub skipped: Vec<String>,
+}
+
+/// Imports the scripts of the Koto repository at `source` into `examples_dir`.
+pub fn import_koto_scripts(source: &ImportSource, examples_dir: &Path) -> Result<ImportReport> {
+    match source {
+        ImportSource::Checkout(root) => import_checkout(root, examples_dir, None),
+        ImportSource::Repository(url) => {
+            let clone_dir =
+                std::env::temp_dir().join(format!("koto_import_{}", uuid::Uuid::new_v4()));
+            let status = Command::new("git")
+                .args(["clone", "--depth", "1", "--quiet", url])
+                .arg(&clone_dir)
+                .status()
+                .context("Failed to start git; is it installed?")?;
+            if !status.success() {
+                anyhow::bail!("git clone {url} failed ({status})");
+            }
+            let result = import_checkout(&clone_dir, examples_dir, Some(url));
+            let _ = fs::remove_dir_all(&clone_dir);
+            result
+        }
+    }
+}
+
+fn import_checkout(root: &Path, examples_dir: &Path, url: Option<&str>) -> Result<ImportReport> {
+    let mut scripts = Vec::new();
+    for dir in SCRIPT_DIRS {
+        collect_scripts(&root.join(dir), &mut scripts)?;
+    }
+    if scripts.is_empty() {
+        anyhow::bail!(
+            "No Koto scripts found in {}; expected a checkout of {KOTO_REPOSITORY_URL}",
+            root.display()
+        );
+    }
+    scripts.sort();
+
+    let mut report = ImportReport::default();
+    for path in scripts {
+        let relative = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let id = format!(
+            "koto_{}",
+            slugify(
+                relative
+                    .trim_end_matches(".koto")
+                    .replace('/', " ")
+                    .as_str()
+            )
+        );
+        let example_dir = examples_dir.join(&id);
+        if example_dir.exists() {
+            report.skipped.push(id);
+            continue;
+        }
+        let script =
+            fs::read_to_string(&path).with_context(|| format!("Failed to read {path:?}"))?;
+        let metadata = generated_metadata(&id, &relative, &script, url);
+        fs::create_dir_all(&example_dir)
+            .with_context(|| format!("Failed to create {example_dir:?}"))?;
+        fs::write(
+            example_dir.join("meta.json"),
+            serde_json::to_string_pretty(&metadata)?,
+        )?;
+        fs::write(example_dir.join("script.koto"), script)?;
+        report.imported.push(id);
+    }
+    Ok(report)
+}
+
+fn collect_scripts(dir: &Path, scripts: &mut Vec<PathBuf>) -> Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {dir:?}"))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_scripts(&path, scripts)?;
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == "koto")
+        {
+            scripts.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn generated_metadata(
+    id: &str,
+    relative: &str,
+    script: &str,
+    url: Option<&str>,
+) -> serde_json::Value {
+    let mut title = Path::new(relative)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().replace('_', " "))
+        .unwrap_or_else(|| id.to_string());
+    if let Some(first) = title.get_mut(0..1) {
+        first.make_ascii_uppercase();
+    }
+    let category = if relative.starts_with("examples/") {
+        "Koto examples"
+    } else {
+        "Koto tests"
+    };
+    let description = leading_comment(script)
+        .unwrap_or_else(|| format!("`{relative}` from the Koto repository."));
+    let mut metadata = serde_json::json!({
+        "id": id,
+        "title": title,
+        "description": description,
+        "note": format!("Imported from {relative} in the Koto repository."),
+        "categories": ["Imported", category],
+    });
+    // Links only make sense for GitHub URLs, where the file's page is predictable.
+    if let Some(url) = url.filter(|url| url.starts_with("https://github.com/")) {
+        let base = url.trim_end_matches('/').trim_end_matches(".git");
+        metadata["documentation"] = serde_json::json!([{
+            "label": "View upstream",
+            "url": format!("{base}/blob/HEAD/{relative}"),
+        }]);
+    }
+    metadata
+}
+
+/// The script's opening `#` comment lines, joined into one sentence.
+fn leading_comment(script: &str) -> Option<String> {
+    let lines: Vec<&str> = script
+        .lines()
+        .take_while(|line| line.starts_with('#') && !line.starts_with("#-"))
+        .map(|line| line.trim_start_matches('#').trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+    (!lines.is_empty()).then(|| lines.join(" "))
+}
@@ -1,25 +1,42 @@
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     fs,
     path::{Path, PathBuf},
-    sync::{Arc, Mutex, RwLock},
-    time::SystemTime,
+    sync::{Arc, Mutex, RwLock, Weak},
+    thread,
+    time::{Duration, SystemTime},
 };
 
 use anyhow::{Context, Result};
 use notify::EventKind;
 use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 use crate::{
     benchmarks,
-    runtime::{logging, watcher},
+    runtime::{RunBinding, Runtime, logging, watcher},
+    tasks::{self, TaskContext},
 };
 
+pub mod categories;
+pub mod classroom;
+pub mod glossary;
+pub mod html;
+pub mod importer;
+pub mod notebook;
+pub mod paste;
+pub mod recent;
+pub mod samples;
+pub mod test_history;
 pub mod tests;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Picked up as an example's thumbnail when `meta.json` doesn't name one.
+pub const THUMBNAIL_FILE_NAME: &str = "thumbnail.png";
+/// An instructor's reference solution, kept next to the script and left out of student packs.
+pub const SOLUTION_FILE_NAME: &str = "solution.koto";
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct ExampleMetadata {
     #[serde(default)]
     pub id: String,
@@ -35,25 +52,153 @@ pub struct ExampleMetadata {
     pub categories: Vec<String>,
     #[serde(default)]
     pub documentation: Vec<ExampleLink>,
+    /// Paragraphs explaining the script, each optionally tied to the lines it describes.
     #[serde(default)]
-    pub how_it_works: Vec<String>,
+    pub how_it_works: Vec<HowItWorksStep>,
     #[serde(default)]
     pub inputs: Vec<ExampleInput>,
+    /// Default text the script reads through `io.stdin`.
+    #[serde(default)]
+    pub stdin: Option<String>,
+    /// Default positional arguments, available to the script as the `args` list.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Helpers added to the prelude while this example runs.
+    #[serde(default)]
+    pub bindings: ExampleBindings,
+    /// Environment variables the example may read and set through `env`, on top of the
+    /// allowlist in Settings.
+    #[serde(default)]
+    pub env: Vec<String>,
+    /// Long-lived example: after the script finishes, the callbacks it registered keep firing
+    /// until the user stops the run.
+    #[serde(default)]
+    pub daemon: bool,
+    /// Koto expression checked after every run, e.g. `result == 42`, with a pass/fail badge in
+    /// the run summary.
+    #[serde(default)]
+    pub assertion: Option<String>,
+    /// Optional cargo features the example needs, e.g. `["yaml"]`; builds without them hide
+    /// the example.
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// Host capabilities this example opts into; everything is off by default.
+    #[serde(default)]
+    pub permissions: ExamplePermissions,
+    /// Image shown in the catalog, relative to the example folder. Defaults to
+    /// `thumbnail.png` when that file exists.
+    #[serde(default)]
+    pub thumbnail: Option<String>,
     #[serde(default)]
     pub benchmarks: Option<ExampleResource>,
     #[serde(default)]
     pub tests: Option<ExampleResource>,
 }
 
+/// Capabilities an example's `meta.json` enables for its own runs.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ExamplePermissions {
+    /// Allows `process.run`, which starts external programs.
+    #[serde(default)]
+    pub process: bool,
+}
+
+/// Constants and aliases injected into the prelude for one example, so its script can skip
+/// setup lines.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ExampleBindings {
+    /// Names bound to JSON values, e.g. `"gravity": 9.81`.
+    #[serde(default)]
+    pub constants: BTreeMap<String, serde_json::Value>,
+    /// Names bound to existing prelude values, e.g. `"shout": "string.to_uppercase"`.
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+}
+
+impl ExampleBindings {
+    pub fn is_empty(&self) -> bool {
+        self.constants.is_empty() && self.aliases.is_empty()
+    }
+
+    /// The bindings as per-run options for [`crate::runtime::Runtime::execute`].
+    pub fn to_run_bindings(&self) -> Vec<(String, RunBinding)> {
+        let constants = self
+            .constants
+            .iter()
+            .map(|(name, value)| (name.clone(), RunBinding::Value(value.clone())));
+        let aliases = self
+            .aliases
+            .iter()
+            .map(|(name, path)| (name.clone(), RunBinding::Alias(path.clone())));
+        constants.chain(aliases).collect()
+    }
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct ExampleLink {
     pub label: String,
     pub url: String,
 }
 
+/// One paragraph of an example's "How it works" notes. Written in `meta.json` as a plain string,
+/// or as `{ "text": "...", "lines": [3, 7] }` to link it to lines 3 to 7 of the script.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "HowItWorksRepr", into = "HowItWorksRepr")]
+pub struct HowItWorksStep {
+    pub text: String,
+    /// First and last line described, counted from 1 and inclusive.
+    pub lines: Option<(usize, usize)>,
+}
+
+impl HowItWorksStep {
+    /// The linked lines as 0-based indices into `script`, clamped to its length, or `None`
+    /// when the step isn't linked or the range lies outside the script.
+    pub fn line_range(&self, script: &str) -> Option<std::ops::RangeInclusive<usize>> {
+        let (start, end) = self.lines?;
+        let first = start.min(end).max(1);
+        let last = start.max(end).min(script.lines().count());
+        (first <= last).then(|| first - 1..=last - 1)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum HowItWorksRepr {
+    Text(String),
+    Linked {
+        text: String,
+        #[serde(default)]
+        lines: Option<(usize, usize)>,
+    },
+}
+
+impl From<HowItWorksRepr> for HowItWorksStep {
+    fn from(repr: HowItWorksRepr) -> Self {
+        match repr {
+            HowItWorksRepr::Text(text) => Self { text, lines: None },
+            HowItWorksRepr::Linked { text, lines } => Self { text, lines },
+        }
+    }
+}
+
+impl From<HowItWorksStep> for HowItWorksRepr {
+    fn from(step: HowItWorksStep) -> Self {
+        match step.lines {
+            None => Self::Text(step.text),
+            lines => Self::Linked {
+                text: step.text,
+                lines,
+            },
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct ExampleInput {
     pub name: String,
+    /// What the script receives; the field's text is converted before each run.
+    #[serde(default, rename = "type")]
+    pub kind: InputType,
     #[serde(default)]
     pub label: Option<String>,
     #[serde(default)]
@@ -64,6 +209,72 @@ pub struct ExampleInput {
     pub placeholder: Option<String>,
 }
 
+/// The kinds of value an input can hold.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputType {
+    #[default]
+    String,
+    Number,
+    Bool,
+    /// Any JSON value, received by the script as the matching Koto value.
+    Json,
+}
+
+impl ExampleInput {
+    /// Converts the text in the input's field to the value the script receives.
+    pub fn coerce(&self, text: &str) -> Result<serde_json::Value> {
+        let trimmed = text.trim();
+        match self.kind {
+            InputType::String => Ok(serde_json::Value::String(text.to_string())),
+            InputType::Number => {
+                if let Ok(integer) = trimmed.parse::<i64>() {
+                    return Ok(integer.into());
+                }
+                trimmed
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(serde_json::Number::from_f64)
+                    .map(serde_json::Value::Number)
+                    .with_context(|| {
+                        format!("Input '{}' expects a number, found '{text}'", self.name)
+                    })
+            }
+            InputType::Bool => match trimmed.to_ascii_lowercase().as_str() {
+                "true" | "yes" | "on" | "1" => Ok(true.into()),
+                "false" | "no" | "off" | "0" | "" => Ok(false.into()),
+                _ => anyhow::bail!(
+                    "Input '{}' expects true or false, found '{text}'",
+                    self.name
+                ),
+            },
+            InputType::Json => serde_json::from_str(text)
+                .with_context(|| format!("Input '{}' expects JSON", self.name)),
+        }
+    }
+}
+
+impl ExampleMetadata {
+    /// The `input` map for a run: each declared input's value from `values`, or its default,
+    /// converted to its type.
+    pub fn coerce_inputs(
+        &self,
+        values: &HashMap<String, String>,
+    ) -> Result<serde_json::Map<String, serde_json::Value>> {
+        self.inputs
+            .iter()
+            .map(|input| {
+                let text = values
+                    .get(&input.name)
+                    .cloned()
+                    .or_else(|| input.default.clone())
+                    .unwrap_or_default();
+                Ok((input.name.clone(), input.coerce(&text)?))
+            })
+            .collect()
+    }
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct ExampleResource {
     #[serde(default)]
@@ -83,24 +294,63 @@ pub struct ExampleDocs {
 #[derive(Clone, Debug)]
 pub struct Example {
     pub metadata: ExampleMetadata,
+    /// The contents of `meta.json`, as stored on disk.
+    pub metadata_source: String,
     pub script: String,
     pub script_path: PathBuf,
     pub docs: Option<ExampleDocs>,
     pub loaded_at: SystemTime,
     pub benchmark_summary: Option<benchmarks::ExampleBenchmarkSummary>,
     pub test_suites: Vec<tests::ExampleTestSuite>,
+    /// Present for notebook examples, whose `script` is the concatenation of their code cells.
+    pub notebook: Option<notebook::Notebook>,
+    /// The example's thumbnail image, when it has one on disk.
+    pub thumbnail: Option<PathBuf>,
+    /// The instructor's `solution.koto`, when the example has one.
+    pub solution: Option<PathBuf>,
+}
+
+impl Example {
+    /// The file contents backing the example's script, as stored on disk.
+    pub fn source(&self) -> &str {
+        self.notebook
+            .as_ref()
+            .map_or(self.script.as_str(), |notebook| notebook.source.as_str())
+    }
+
+    pub fn metadata_path(&self) -> PathBuf {
+        self.script_path.with_file_name("meta.json")
+    }
+
+    /// Where a thumbnail captured from the canvas is saved: the file named by `thumbnail` in
+    /// `meta.json` if it is a PNG, otherwise `thumbnail.png` next to the script.
+    pub fn thumbnail_capture_path(&self) -> PathBuf {
+        match &self.metadata.thumbnail {
+            Some(relative) if relative.to_ascii_lowercase().ends_with(".png") => {
+                self.script_path.with_file_name(relative)
+            }
+            _ => self.script_path.with_file_name(THUMBNAIL_FILE_NAME),
+        }
+    }
 }
 
 pub struct ExampleLibrary {
     inner: Arc<ExampleLibraryInner>,
-    _watcher: Option<watcher::Watcher>,
 }
 
 struct ExampleLibraryInner {
-    examples_dir: PathBuf,
+    /// `None` for in-memory libraries, which never read from disk.
+    examples_dir: RwLock<Option<PathBuf>>,
+    /// Whether changes under `examples_dir` trigger reloads.
+    watch: bool,
+    watcher: Mutex<Option<watcher::Watcher>>,
+    /// Set while `examples_dir` doesn't exist; the last good snapshot is kept meanwhile.
+    directory_missing: AtomicBool,
     examples: RwLock<BTreeMap<String, Example>>,
     version: AtomicUsize,
     recent_changes: Mutex<Vec<ScriptChange>>,
+    /// Why the last reload failed or skipped examples; cleared by a clean reload.
+    reload_error: Mutex<Option<String>>,
 }
 
 #[derive(Clone, Debug)]
@@ -122,10 +372,23 @@ pub enum ScriptChangeKind {
         previous: Option<String>,
         current: Option<String>,
     },
+    /// The example's `meta.json` changed; both sides hold the raw file contents.
+    MetadataUpdated {
+        previous: Option<String>,
+        current: Option<String>,
+    },
+    /// The example's folder (and with it the id) changed. `example_id` and `path` refer to the
+    /// new location; reverting moves the folder back.
+    ExampleRenamed {
+        previous_id: String,
+        previous_path: PathBuf,
+    },
 }
 
 static GLOBAL_LIBRARY: OnceCell<ExampleLibrary> = OnceCell::new();
 
+const DIRECTORY_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 pub fn library() -> Result<&'static ExampleLibrary> {
     GLOBAL_LIBRARY.get_or_try_init(|| ExampleLibrary::new(default_examples_dir()))
 }
@@ -139,6 +402,52 @@ impl ExampleLibrary {
         Self::with_watcher(examples_dir, false)
     }
 
+    /// Builds a library from metadata and scripts without touching the filesystem.
+    ///
+    /// Examples without an id get one derived from their title. Refreshing an in-memory
+    /// library keeps its examples unchanged.
+    pub fn in_memory(examples: Vec<(ExampleMetadata, String)>) -> Self {
+        let examples: BTreeMap<String, Example> = examples
+            .into_iter()
+            .map(|(mut metadata, script)| {
+                if metadata.id.is_empty() {
+                    metadata.id = slugify(&metadata.title);
+                }
+                let example = Example {
+                    script,
+                    script_path: PathBuf::from(&metadata.id).join("script.koto"),
+                    metadata_source: serde_json::to_string_pretty(&metadata).unwrap_or_default(),
+                    metadata,
+                    docs: None,
+                    loaded_at: SystemTime::now(),
+                    benchmark_summary: None,
+                    test_suites: Vec::new(),
+                    notebook: None,
+                    thumbnail: None,
+                    solution: None,
+                };
+                (example.metadata.id.clone(), example)
+            })
+            .collect();
+
+        logging::with_runtime_subscriber(|| {
+            tracing::info!(
+                target: "runtime.examples",
+                count = examples.len(),
+                "In-memory example library initialized"
+            );
+        });
+
+        let inner = ExampleLibraryInner::new(None, false);
+        if let Ok(mut guard) = inner.examples.write() {
+            *guard = examples;
+        }
+        inner.version.store(1, Ordering::SeqCst);
+        Self {
+            inner: Arc::new(inner),
+        }
+    }
+
     pub fn refresh(&self) -> Result<()> {
         self.inner.reload()
     }
@@ -154,7 +463,9 @@ impl ExampleLibrary {
     pub fn get(&self, id: &str) -> Option<Example> {
         let guard = self.inner.examples.read().ok()?;
         let mut example = guard.get(id).cloned()?;
-        example.benchmark_summary = benchmarks::load_example_summary(&example.metadata.id);
+        if self.inner.is_on_disk() {
+            example.benchmark_summary = benchmarks::load_example_summary(&example.metadata.id);
+        }
         Some(example)
     }
 
@@ -166,20 +477,137 @@ impl ExampleLibrary {
         self.inner.revert_change(change)
     }
 
-    fn with_watcher(examples_dir: PathBuf, watch: bool) -> Result<Self> {
+    /// Describes why the catalog is failing to reload, e.g. after the watcher picked up an
+    /// invalid `meta.json`. `None` once a reload succeeds cleanly.
+    pub fn reload_error(&self) -> Option<String> {
+        self.inner
+            .reload_error
+            .lock()
+            .ok()
+            .and_then(|error| error.clone())
+    }
+
+    /// The folder the catalog is loaded from; `None` for in-memory libraries.
+    pub fn examples_dir(&self) -> Option<PathBuf> {
+        self.inner.examples_dir()
+    }
+
+    /// Whether the examples directory has disappeared. The last loaded examples stay available,
+    /// and watched libraries resume watching once the directory reappears.
+    pub fn is_directory_missing(&self) -> bool {
+        self.inner.directory_missing.load(Ordering::SeqCst)
+    }
+
+    /// Scaffolds a new example folder with a `meta.json` and a starter script, then reloads the
+    /// catalog. Returns the id of the new example, derived from `title`.
+    pub fn create_example(&self, title: &str) -> Result<String> {
+        let title = title.trim();
+        if title.is_empty() {
+            anyhow::bail!("An example needs a title");
+        }
+        let Some(examples_dir) = self.examples_dir() else {
+            anyhow::bail!("In-memory example libraries can't create examples");
+        };
+        let base = slugify(title);
+        let mut id = base.clone();
+        let mut suffix = 2;
+        while examples_dir.join(&id).exists() {
+            id = format!("{base}_{suffix}");
+            suffix += 1;
+        }
+        let example_dir = examples_dir.join(&id);
+        fs::create_dir_all(&example_dir)
+            .with_context(|| format!("Failed to create {example_dir:?}"))?;
+        let metadata = serde_json::json!({
+            "title": title,
+            "description": "Describe what this example demonstrates.",
+            "categories": ["Drafts"],
+        });
+        fs::write(
+            example_dir.join("meta.json"),
+            serde_json::to_string_pretty(&metadata)?,
+        )?;
+        fs::write(
+            example_dir.join("script.koto"),
+            "# Start experimenting here\nprint 'Hello, Koto!'\n",
+        )?;
+        self.refresh()?;
+        Ok(id)
+    }
+
+    /// Adds the starter examples to the library's folder; see [`samples::write_samples`].
+    pub fn init_samples(&self) -> Result<Vec<String>> {
+        let Some(examples_dir) = self.examples_dir() else {
+            anyhow::bail!("In-memory example libraries can't hold starter examples");
+        };
         fs::create_dir_all(&examples_dir)
-            .with_context(|| format!("Failed to ensure examples dir {examples_dir:?}"))?;
+            .with_context(|| format!("Failed to create {examples_dir:?}"))?;
+        let written = samples::write_samples(&examples_dir)?;
+        self.refresh()?;
+        Ok(written)
+    }
 
-        let inner = Arc::new(ExampleLibraryInner::new(examples_dir.clone())?);
+    /// Adds scripts from the Koto repository to the library's folder; see
+    /// [`importer::import_koto_scripts`].
+    pub fn import_koto_scripts(
+        &self,
+        source: &importer::ImportSource,
+    ) -> Result<importer::ImportReport> {
+        let Some(examples_dir) = self.examples_dir() else {
+            anyhow::bail!("In-memory example libraries can't import examples");
+        };
+        let report = importer::import_koto_scripts(source, &examples_dir)?;
+        self.refresh()?;
+        Ok(report)
+    }
 
-        let watcher = if watch {
-            let inner = Arc::clone(&inner);
-            Some(watcher::Watcher::new(examples_dir.clone(), move |event| {
-                handle_watch_event(&inner, event);
-            })?)
-        } else {
-            None
+    /// Packs the catalog for students; see [`classroom::export_student_pack`].
+    pub fn export_student_pack(&self, output: &Path) -> Result<classroom::StudentPackReport> {
+        let Some(examples_dir) = self.examples_dir() else {
+            anyhow::bail!("In-memory example libraries can't be exported");
         };
+        classroom::export_student_pack(&examples_dir, output)
+    }
+
+    /// Creates an example from pasted code or a link to it, checks that it compiles, and
+    /// reloads the catalog; see [`paste::write_pasted_example`].
+    pub fn import_pasted(
+        &self,
+        source: &paste::PasteSource,
+        target: &paste::PasteTarget,
+    ) -> Result<paste::PastedExample> {
+        let Some(examples_dir) = self.examples_dir() else {
+            anyhow::bail!("In-memory example libraries can't import examples");
+        };
+        let script = source.fetch()?;
+        if script.trim().is_empty() {
+            anyhow::bail!("There is no Koto code to import");
+        }
+        let syntax_error = Runtime::check_syntax(&script)
+            .err()
+            .map(|error| error.to_string());
+        let id = paste::write_pasted_example(&examples_dir, source, &script, target)?;
+        self.refresh()?;
+        Ok(paste::PastedExample { id, syntax_error })
+    }
+
+    /// Re-points the library at another folder, replacing the catalog and the watcher.
+    pub fn set_examples_dir(&self, examples_dir: PathBuf) -> Result<()> {
+        if !examples_dir.is_dir() {
+            anyhow::bail!("{} is not a directory", examples_dir.display());
+        }
+        self.inner.switch_dir(examples_dir)
+    }
+
+    fn with_watcher(examples_dir: PathBuf, watch: bool) -> Result<Self> {
+        fs::create_dir_all(&examples_dir)
+            .with_context(|| format!("Failed to ensure examples dir {examples_dir:?}"))?;
+
+        let inner = Arc::new(ExampleLibraryInner::new(Some(examples_dir.clone()), watch));
+        inner.reload()?;
+        if watch {
+            inner.start_watching()?;
+        }
 
         logging::with_runtime_subscriber(|| {
             tracing::info!(
@@ -190,43 +618,140 @@ impl ExampleLibrary {
             );
         });
 
-        Ok(Self {
-            inner,
-            _watcher: watcher,
-        })
+        Ok(Self { inner })
     }
 }
 
 impl ExampleLibraryInner {
-    fn new(examples_dir: PathBuf) -> Result<Self> {
-        let library = Self {
-            examples_dir,
+    fn new(examples_dir: Option<PathBuf>, watch: bool) -> Self {
+        Self {
+            examples_dir: RwLock::new(examples_dir),
+            watch,
+            watcher: Mutex::new(None),
+            directory_missing: AtomicBool::new(false),
             examples: RwLock::new(BTreeMap::new()),
             version: AtomicUsize::new(0),
             recent_changes: Mutex::new(Vec::new()),
+            reload_error: Mutex::new(None),
+        }
+    }
+
+    fn examples_dir(&self) -> Option<PathBuf> {
+        self.examples_dir.read().ok().and_then(|dir| dir.clone())
+    }
+
+    fn is_on_disk(&self) -> bool {
+        self.examples_dir
+            .read()
+            .map(|dir| dir.is_some())
+            .unwrap_or(false)
+    }
+
+    /// Watches the current examples directory, replacing any previous watcher. The handler only
+    /// holds a weak reference so the library can still be dropped.
+    fn start_watching(self: &Arc<Self>) -> Result<()> {
+        let Some(examples_dir) = self.examples_dir() else {
+            return Ok(());
         };
-        library.reload()?;
-        Ok(library)
+        let inner = Arc::downgrade(self);
+        let watcher = watcher::Watcher::new(examples_dir, move |event| {
+            if let Some(inner) = inner.upgrade() {
+                handle_watch_event(&inner, event);
+            }
+        })?;
+        if let Ok(mut guard) = self.watcher.lock() {
+            *guard = Some(watcher);
+        }
+        Ok(())
+    }
+
+    fn switch_dir(self: &Arc<Self>, examples_dir: PathBuf) -> Result<()> {
+        if !self.is_on_disk() {
+            anyhow::bail!("In-memory example libraries can't switch directories");
+        }
+        if let Ok(mut guard) = self.examples_dir.write() {
+            *guard = Some(examples_dir.clone());
+        }
+        self.directory_missing.store(false, Ordering::SeqCst);
+        // A different catalog isn't a set of edits, so don't report every example as changed.
+        if let Ok(mut examples) = self.examples.write() {
+            examples.clear();
+        }
+        if self.watch {
+            self.start_watching()?;
+        }
+        let result = self.reload();
+        self.take_recent_changes();
+        result?;
+        logging::with_runtime_subscriber(|| {
+            tracing::info!(
+                target: "runtime.examples",
+                path = %examples_dir.display(),
+                "Switched examples directory"
+            );
+        });
+        Ok(())
     }
 
-    fn reload(&self) -> Result<()> {
-        let new_examples = load_examples_from_dir(&self.examples_dir)?;
+    /// Keeps the current snapshot and reports the problem when the directory has gone away.
+    fn mark_directory_missing(self: &Arc<Self>, examples_dir: &Path) {
+        self.set_reload_error(Some(format!(
+            "Examples directory {} is missing; showing the last loaded examples",
+            examples_dir.display()
+        )));
+        if self.directory_missing.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        logging::with_runtime_subscriber(|| {
+            tracing::warn!(
+                target: "runtime.examples",
+                path = %examples_dir.display(),
+                "Examples directory is missing"
+            );
+        });
+        if self.watch {
+            spawn_directory_monitor(Arc::downgrade(self), examples_dir.to_path_buf());
+        }
+    }
+
+    fn reload(self: &Arc<Self>) -> Result<()> {
+        let Some(examples_dir) = self.examples_dir() else {
+            return Ok(());
+        };
+        if !examples_dir.is_dir() {
+            self.mark_directory_missing(&examples_dir);
+            return Ok(());
+        }
+        self.directory_missing.store(false, Ordering::SeqCst);
+        let mut issues = Vec::new();
+        let new_examples = match tasks::run_blocking("Reloading example catalog", |task| {
+            load_examples_from_dir(&examples_dir, task, &mut issues)
+        }) {
+            Ok(examples) => examples,
+            Err(error) => {
+                self.set_reload_error(Some(format!("{error:#}")));
+                return Err(error);
+            }
+        };
+        self.set_reload_error((!issues.is_empty()).then(|| issues.join("\n")));
         let count = new_examples.len();
         let mut changes = Vec::new();
         if let Ok(mut guard) = self.examples.write() {
             let old = std::mem::replace(&mut *guard, new_examples);
             changes = diff_examples(&old, &*guard);
         }
-        self.version.fetch_add(1, Ordering::SeqCst);
+        // Queue the changes before bumping the version so that consumers reacting to the new
+        // version see them, which renames rely on to keep the selection.
         if !changes.is_empty() {
             if let Ok(mut queue) = self.recent_changes.lock() {
                 queue.extend(changes);
             }
         }
+        self.version.fetch_add(1, Ordering::SeqCst);
         logging::with_runtime_subscriber(|| {
             tracing::info!(
                 target: "runtime.examples",
-                path = %self.examples_dir.display(),
+                path = %examples_dir.display(),
                 count,
                 "Reloaded examples"
             );
@@ -234,6 +759,12 @@ impl ExampleLibraryInner {
         Ok(())
     }
 
+    fn set_reload_error(&self, error: Option<String>) {
+        if let Ok(mut guard) = self.reload_error.lock() {
+            *guard = error;
+        }
+    }
+
     fn take_recent_changes(&self) -> Vec<ScriptChange> {
         self.recent_changes
             .lock()
@@ -249,9 +780,19 @@ impl ExampleLibraryInner {
             } => {
                 apply_revert(change.path.as_path(), previous)?;
             }
-            ScriptChangeKind::TestSuiteUpdated { previous, .. } => {
+            ScriptChangeKind::TestSuiteUpdated { previous, .. }
+            | ScriptChangeKind::MetadataUpdated { previous, .. } => {
                 apply_revert(change.path.as_path(), previous)?;
             }
+            ScriptChangeKind::ExampleRenamed { previous_path, .. } => {
+                if let (Some(current_dir), Some(previous_dir)) =
+                    (change.path.parent(), previous_path.parent())
+                {
+                    fs::rename(current_dir, previous_dir).with_context(|| {
+                        format!("Failed to rename {current_dir:?} back to {previous_dir:?}")
+                    })?;
+                }
+            }
         }
         Ok(())
     }
@@ -264,8 +805,10 @@ impl ExampleLibraryInner {
                     .values()
                     .cloned()
                     .map(|mut example| {
-                        example.benchmark_summary =
-                            benchmarks::load_example_summary(&example.metadata.id);
+                        if self.is_on_disk() {
+                            example.benchmark_summary =
+                                benchmarks::load_example_summary(&example.metadata.id);
+                        }
                         example
                     })
                     .collect()
@@ -295,23 +838,52 @@ fn apply_revert(path: &Path, previous: &Option<String>) -> Result<()> {
     Ok(())
 }
 
-fn diff_examples(
+/// Lists the changes between two loaded catalogs, detecting renamed example folders.
+pub fn diff_examples(
     old: &BTreeMap<String, Example>,
     new: &BTreeMap<String, Example>,
 ) -> Vec<ScriptChange> {
     let mut changes = Vec::new();
+    let renamed_from = detect_renames(old, new);
 
     for (id, new_example) in new {
-        match old.get(id) {
+        let previous = match renamed_from.get(id.as_str()) {
+            Some(old_example) => {
+                changes.push(ScriptChange {
+                    example_id: id.clone(),
+                    path: new_example.script_path.clone(),
+                    changed_at: SystemTime::now(),
+                    kind: ScriptChangeKind::ExampleRenamed {
+                        previous_id: old_example.metadata.id.clone(),
+                        previous_path: old_example.script_path.clone(),
+                    },
+                });
+                Some(*old_example)
+            }
+            None => old.get(id),
+        };
+        match previous {
             Some(old_example) => {
-                if old_example.script != new_example.script {
+                if old_example.source() != new_example.source() {
                     changes.push(ScriptChange {
                         example_id: id.clone(),
                         path: new_example.script_path.clone(),
                         changed_at: SystemTime::now(),
                         kind: ScriptChangeKind::ScriptUpdated {
-                            previous: Some(old_example.script.clone()),
-                            current: Some(new_example.script.clone()),
+                            previous: Some(old_example.source().to_string()),
+                            current: Some(new_example.source().to_string()),
+                        },
+                    });
+                }
+
+                if old_example.metadata_source != new_example.metadata_source {
+                    changes.push(ScriptChange {
+                        example_id: id.clone(),
+                        path: new_example.metadata_path(),
+                        changed_at: SystemTime::now(),
+                        kind: ScriptChangeKind::MetadataUpdated {
+                            previous: Some(old_example.metadata_source.clone()),
+                            current: Some(new_example.metadata_source.clone()),
                         },
                     });
                 }
@@ -380,7 +952,7 @@ fn diff_examples(
                     changed_at: SystemTime::now(),
                     kind: ScriptChangeKind::ScriptUpdated {
                         previous: None,
-                        current: Some(new_example.script.clone()),
+                        current: Some(new_example.source().to_string()),
                     },
                 });
                 for suite in &new_example.test_suites {
@@ -399,14 +971,18 @@ fn diff_examples(
         }
     }
 
+    let renamed: HashSet<&str> = renamed_from
+        .values()
+        .map(|example| example.metadata.id.as_str())
+        .collect();
     for (id, old_example) in old {
-        if !new.contains_key(id) {
+        if !new.contains_key(id) && !renamed.contains(id.as_str()) {
             changes.push(ScriptChange {
                 example_id: id.clone(),
                 path: old_example.script_path.clone(),
                 changed_at: SystemTime::now(),
                 kind: ScriptChangeKind::ScriptUpdated {
-                    previous: Some(old_example.script.clone()),
+                    previous: Some(old_example.source().to_string()),
                     current: None,
                 },
             });
@@ -428,6 +1004,53 @@ fn diff_examples(
     changes
 }
 
+/// Pairs examples that disappeared with ones that appeared when they look like the same folder
+/// under a new name: the same title, the same script source, or both. Returns the old example
+/// for each new id.
+fn detect_renames<'a>(
+    old: &'a BTreeMap<String, Example>,
+    new: &'a BTreeMap<String, Example>,
+) -> HashMap<&'a str, &'a Example> {
+    let removed: Vec<&Example> = old
+        .iter()
+        .filter(|(id, _)| !new.contains_key(*id))
+        .map(|(_, example)| example)
+        .collect();
+    let added: Vec<&Example> = new
+        .iter()
+        .filter(|(id, _)| !old.contains_key(*id))
+        .map(|(_, example)| example)
+        .collect();
+    if removed.is_empty() || added.is_empty() {
+        return HashMap::new();
+    }
+
+    let mut candidates = Vec::new();
+    for old_example in &removed {
+        for new_example in &added {
+            let same_title = old_example.metadata.title == new_example.metadata.title;
+            let same_source = !old_example.source().trim().is_empty()
+                && old_example.source() == new_example.source();
+            let score = usize::from(same_title) * 2 + usize::from(same_source);
+            if score > 0 {
+                candidates.push((score, *old_example, *new_example));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let mut renames = HashMap::new();
+    let mut matched_old = HashSet::new();
+    for (_, old_example, new_example) in candidates {
+        let new_id = new_example.metadata.id.as_str();
+        if renames.contains_key(new_id) || !matched_old.insert(old_example.metadata.id.as_str()) {
+            continue;
+        }
+        renames.insert(new_id, old_example);
+    }
+    renames
+}
+
 fn handle_watch_event(inner: &Arc<ExampleLibraryInner>, event: watcher::WatchEvent) {
     match event {
         watcher::WatchEvent::FileEvent { event, .. } if should_reload(&event.kind) => {
@@ -446,10 +1069,46 @@ fn handle_watch_event(inner: &Arc<ExampleLibraryInner>, event: watcher::WatchEve
             logging::with_runtime_subscriber(|| {
                 tracing::error!(target: "runtime.examples", %error, "File watcher error");
             });
+            inner.set_reload_error(Some(format!("File watcher error: {error}")));
         }
     }
 }
 
+/// Polls for a missing examples directory and resumes watching once it reappears. Stops when
+/// the library is dropped or has been pointed somewhere else.
+fn spawn_directory_monitor(inner: Weak<ExampleLibraryInner>, examples_dir: PathBuf) {
+    thread::spawn(move || {
+        loop {
+            thread::sleep(DIRECTORY_POLL_INTERVAL);
+            let Some(inner) = inner.upgrade() else {
+                return;
+            };
+            if !inner.directory_missing.load(Ordering::SeqCst)
+                || inner.examples_dir().as_ref() != Some(&examples_dir)
+            {
+                return;
+            }
+            if !examples_dir.is_dir() {
+                continue;
+            }
+            let result = inner.start_watching().and_then(|_| inner.reload());
+            logging::with_runtime_subscriber(|| match &result {
+                Ok(()) => tracing::info!(
+                    target: "runtime.examples",
+                    path = %examples_dir.display(),
+                    "Examples directory reappeared"
+                ),
+                Err(error) => tracing::error!(
+                    target: "runtime.examples",
+                    %error,
+                    "Failed to resume watching the examples directory"
+                ),
+            });
+            return;
+        }
+    });
+}
+
 fn should_reload(kind: &EventKind) -> bool {
     matches!(
         kind,
@@ -457,27 +1116,49 @@ fn should_reload(kind: &EventKind) -> bool {
     )
 }
 
-fn load_examples_from_dir(dir: &Path) -> Result<BTreeMap<String, Example>> {
+/// Loads every example folder in `dir`. Folders that can't be loaded are skipped; invalid
+/// metadata is also described in `issues` so it can be shown to the user.
+pub fn load_examples_from_dir(
+    dir: &Path,
+    task: &TaskContext,
+    issues: &mut Vec<String>,
+) -> Result<BTreeMap<String, Example>> {
     let mut examples = BTreeMap::new();
 
     if !dir.exists() {
         return Ok(examples);
     }
 
-    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {dir:?}"))? {
-        let entry = entry?;
-        if !entry.file_type()?.is_dir() {
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read {dir:?}"))?
+        .collect::<std::io::Result<Vec<_>>>()?;
+    let total = entries.len();
+    for (index, entry) in entries.into_iter().enumerate() {
+        task.set_steps(index, total);
+        // Category landing pages share the catalog folder but aren't examples.
+        if !entry.file_type()?.is_dir() || entry.file_name() == categories::CATEGORIES_DIR_NAME {
             continue;
         }
         let folder_name = entry.file_name().to_string_lossy().to_string();
         let example_dir = entry.path();
         let meta_path = example_dir.join("meta.json");
-        let script_path = example_dir.join("script.koto");
+        let mut script_path = example_dir.join("script.koto");
+        let notebook_path = example_dir.join(notebook::NOTEBOOK_FILE_NAME);
+
+        let mut notebook = None;
+        let script = if !script_path.exists() && notebook_path.exists() {
+            script_path = notebook_path.clone();
+            fs::read_to_string(&notebook_path).map(|source| {
+                let parsed = notebook::Notebook::parse(notebook_path, &source);
+                let script = parsed.script();
+                notebook = Some(parsed);
+                script
+            })
+        } else {
+            fs::read_to_string(&script_path)
+        };
 
-        match (
-            fs::read_to_string(&meta_path),
-            fs::read_to_string(&script_path),
-        ) {
+        match (fs::read_to_string(&meta_path), script) {
             (Ok(meta_content), Ok(script_content)) => {
                 match serde_json::from_str::<ExampleMetadata>(&meta_content) {
                     Ok(mut metadata) => {
@@ -516,15 +1197,22 @@ fn load_examples_from_dir(dir: &Path) -> Result<BTreeMap<String, Example>> {
                         if metadata.doc_url.is_none() {
                             metadata.doc_url = Some(format!("examples/{}/docs.md", metadata.id));
                         }
+                        let thumbnail = find_thumbnail(&example_dir, &metadata);
+                        let solution = Some(example_dir.join(SOLUTION_FILE_NAME))
+                            .filter(|path| path.is_file());
                         let benchmark_summary = benchmarks::load_example_summary(&metadata.id);
                         let example = Example {
                             script: script_content,
                             script_path: script_path.clone(),
                             metadata,
+                            metadata_source: meta_content,
                             docs,
                             loaded_at: SystemTime::now(),
                             benchmark_summary,
                             test_suites,
+                            notebook,
+                            thumbnail,
+                            solution,
                         };
                         examples.insert(example.metadata.id.clone(), example);
                     }
@@ -537,6 +1225,7 @@ fn load_examples_from_dir(dir: &Path) -> Result<BTreeMap<String, Example>> {
                                 "Failed to parse example metadata"
                             );
                         });
+                        issues.push(format!("Invalid {folder_name}/meta.json: {error}"));
                     }
                 }
             }
@@ -566,7 +1255,9 @@ fn load_examples_from_dir(dir: &Path) -> Result<BTreeMap<String, Example>> {
     Ok(examples)
 }
 
-fn default_examples_dir() -> PathBuf {
+/// `KOTO_EXAMPLES_DIR`, or an `examples` folder next to the executable or in the current
+/// directory.
+pub fn default_examples_dir() -> PathBuf {
     if let Ok(path) = std::env::var("KOTO_EXAMPLES_DIR") {
         return PathBuf::from(path);
     }
@@ -591,6 +1282,42 @@ fn default_examples_dir() -> PathBuf {
     PathBuf::from("examples")
 }
 
+/// The thumbnail named in `meta.json`, or `thumbnail.png`, if the file exists. A missing
+/// named file is logged rather than reported, since the example itself still works.
+fn find_thumbnail(example_dir: &Path, metadata: &ExampleMetadata) -> Option<PathBuf> {
+    let path = example_dir.join(metadata.thumbnail.as_deref().unwrap_or(THUMBNAIL_FILE_NAME));
+    if path.is_file() {
+        return Some(path);
+    }
+    if metadata.thumbnail.is_some() {
+        logging::with_runtime_subscriber(|| {
+            tracing::warn!(
+                target: "runtime.examples",
+                path = %path.display(),
+                "Example thumbnail not found",
+            );
+        });
+    }
+    None
+}
+
+fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('_') {
+            slug.push('_');
+        }
+    }
+    let slug = slug.trim_end_matches('_').to_string();
+    if slug.is_empty() {
+        "example".to_string()
+    } else {
+        slug
+    }
+}
+
 fn doc_summary(content: &str) -> String {
     for paragraph in content.split("\n\n") {
         let trimmed = paragraph.trim();
@@ -0,0 +1,105 @@
+//! Notebook examples: markdown interleaved with Koto code cells.
+//!
+//! A notebook is a `notebook.md` file. Fenced blocks tagged `koto` become code cells and
+//! everything between them becomes markdown cells. Other fenced blocks stay in the markdown.
+
+use std::path::PathBuf;
+
+pub const NOTEBOOK_FILE_NAME: &str = "notebook.md";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NotebookCell {
+    Markdown(String),
+    Code(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct Notebook {
+    pub path: PathBuf,
+    /// The raw notebook file, kept so changes can be reverted verbatim.
+    pub source: String,
+    pub cells: Vec<NotebookCell>,
+}
+
+impl Notebook {
+    pub fn parse(path: PathBuf, source: &str) -> Self {
+        Self {
+            path,
+            source: source.to_string(),
+            cells: parse_cells(source),
+        }
+    }
+
+    /// The code cells in order, as `(cell index, source)` pairs.
+    pub fn code_cells(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter_map(|(index, cell)| match cell {
+                NotebookCell::Code(code) => Some((index, code.as_str())),
+                NotebookCell::Markdown(_) => None,
+            })
+    }
+
+    /// Joins every code cell into a single script, used when the example is run as a whole.
+    pub fn script(&self) -> String {
+        let mut script = String::new();
+        for (_, code) in self.code_cells() {
+            script.push_str(code);
+            if !code.ends_with('\n') {
+                script.push('\n');
+            }
+        }
+        script
+    }
+}
+
+fn parse_cells(source: &str) -> Vec<NotebookCell> {
+    let mut cells = Vec::new();
+    let mut markdown = String::new();
+    let mut code: Option<String> = None;
+    let mut in_other_fence = false;
+
+    for line in source.lines() {
+        let trimmed = line.trim_start();
+        if let Some(cell) = code.as_mut() {
+            if trimmed.starts_with("```") {
+                cells.push(NotebookCell::Code(std::mem::take(cell)));
+                code = None;
+            } else {
+                cell.push_str(line);
+                cell.push('\n');
+            }
+            continue;
+        }
+
+        if let Some(info) = trimmed.strip_prefix("```") {
+            if in_other_fence {
+                in_other_fence = false;
+            } else if info.trim() == "koto" {
+                push_markdown(&mut cells, &mut markdown);
+                code = Some(String::new());
+                continue;
+            } else {
+                in_other_fence = true;
+            }
+        }
+        markdown.push_str(line);
+        markdown.push('\n');
+    }
+
+    // An unterminated code fence still yields its code.
+    if let Some(cell) = code {
+        cells.push(NotebookCell::Code(cell));
+    }
+    push_markdown(&mut cells, &mut markdown);
+    cells
+}
+
+fn push_markdown(cells: &mut Vec<NotebookCell>, markdown: &mut String) {
+    let text = markdown.trim();
+    if !text.is_empty() {
+        cells.push(NotebookCell::Markdown(text.to_string()));
+    }
+    markdown.clear();
+}
@@ -0,0 +1,156 @@
+//! Turns Koto code pasted from the clipboard, or a link to it, into an example: either the
+//! reusable scratch example or a new example in the catalog.
+
+use std::{fs, path::Path, process::Command};
+
+use anyhow::{Context, Result};
+
+use super::slugify;
+
+/// The example that scratch imports overwrite each time.
+pub const SCRATCH_EXAMPLE_ID: &str = "scratch";
+
+/// What was pasted.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PasteSource {
+    /// Koto code, used as-is.
+    Code(String),
+    /// A link to a script, downloaded with curl.
+    Url(String),
+}
+
+impl PasteSource {
+    /// Treats a single `http(s)://` line as a link and anything else as code.
+    pub fn parse(text: &str) -> Self {
+        let trimmed = text.trim();
+        let is_url = (trimmed.starts_with("https://") || trimmed.starts_with("http://"))
+            && !trimmed.contains(char::is_whitespace);
+        if is_url {
+            Self::Url(trimmed.to_string())
+        } else {
+            Self::Code(text.to_string())
+        }
+    }
+
+    /// The script text, downloading it first for links.
+    pub fn fetch(&self) -> Result<String> {
+        match self {
+            Self::Code(code) => Ok(code.clone()),
+            Self::Url(url) => {
+                let raw = raw_url(url);
+                let output = Command::new("curl")
+                    .args([
+                        "--fail",
+                        "--silent",
+                        "--show-error",
+                        "--location",
+                        "--max-time",
+                    ])
+                    .args(["30", &raw])
+                    .output()
+                    .context("Failed to start curl; is it installed?")?;
+                if !output.status.success() {
+                    anyhow::bail!(
+                        "Downloading {raw} failed: {}",
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    );
+                }
+                String::from_utf8(output.stdout)
+                    .with_context(|| format!("{raw} didn't return UTF-8 text"))
+            }
+        }
+    }
+
+    /// Where the example came from, for its note.
+    fn origin(&self) -> String {
+        match self {
+            Self::Code(_) => "Pasted from the clipboard.".to_string(),
+            Self::Url(url) => format!("Imported from {url}."),
+        }
+    }
+}
+
+/// Rewrites gist and GitHub file pages to the URL of their raw contents; other links are
+/// returned unchanged.
+pub fn raw_url(url: &str) -> String {
+    let url = url.trim().trim_end_matches('/');
+    if let Some(path) = url.strip_prefix("https://gist.github.com/") {
+        if !path.contains("/raw") {
+            return format!("https://gist.githubusercontent.com/{path}/raw");
+        }
+    }
+    if let Some(path) = url.strip_prefix("https://github.com/") {
+        if let Some((repo, file)) = path.split_once("/blob/") {
+            return format!("https://raw.githubusercontent.com/{repo}/{file}");
+        }
+    }
+    url.to_string()
+}
+
+/// Where a paste ends up.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PasteTarget {
+    /// Replaces the scratch example.
+    Scratch,
+    /// A new example with this title, alongside the rest of the catalog.
+    Library { title: String },
+}
+
+/// The example a paste created.
+#[derive(Clone, Debug)]
+pub struct PastedExample {
+    pub id: String,
+    /// The compile error, when the pasted script doesn't compile. The example is created
+    /// either way so the code can be fixed in the editor.
+    pub syntax_error: Option<String>,
+}
+
+/// Writes `script` as an example in `examples_dir` and returns its id.
+pub fn write_pasted_example(
+    examples_dir: &Path,
+    source: &PasteSource,
+    script: &str,
+    target: &PasteTarget,
+) -> Result<String> {
+    let (id, title, categories) = match target {
+        PasteTarget::Scratch => (
+            SCRATCH_EXAMPLE_ID.to_string(),
+            "Scratch".to_string(),
+            ["Scratch"],
+        ),
+        PasteTarget::Library { title } => {
+            let title = title.trim();
+            if title.is_empty() {
+                anyhow::bail!("An example needs a title");
+            }
+            let base = slugify(title);
+            let mut id = base.clone();
+            let mut suffix = 2;
+            while examples_dir.join(&id).exists() {
+                id = format!("{base}_{suffix}");
+                suffix += 1;
+            }
+            (id, title.to_string(), ["Imported"])
+        }
+    };
+    let example_dir = examples_dir.join(&id);
+    fs::create_dir_all(&example_dir)
+        .with_context(|| format!("Failed to create {example_dir:?}"))?;
+    let metadata = serde_json::json!({
+        "id": id,
+        "title": title,
+        "description": "Koto code imported with Import from clipboard/URL.",
+        "note": source.origin(),
+        "categories": categories,
+    });
+    fs::write(
+        example_dir.join("meta.json"),
+        serde_json::to_string_pretty(&metadata)?,
+    )?;
+    let mut script = script.to_string();
+    if !script.ends_with('\n') {
+        script.push('\n');
+    }
+    fs::write(example_dir.join("script.koto"), script)?;
+    Ok(id)
+}
@@ -0,0 +1,88 @@
+//! Remembers recently opened example roots so users can switch between catalogs quickly.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+const MAX_RECENT_CATALOGS: usize = 8;
+const RECENT_CATALOGS_FILE: &str = "recent_catalogs.json";
+
+/// Most recently used example roots, newest first, persisted as JSON in the user's config dir.
+#[derive(Clone, Debug, Default)]
+pub struct RecentCatalogs {
+    path: Option<PathBuf>,
+    entries: Vec<PathBuf>,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+struct RecentCatalogsFile {
+    #[serde(default)]
+    catalogs: Vec<PathBuf>,
+}
+
+impl RecentCatalogs {
+    /// Loads the list from `KOTO_RECENT_CATALOGS` if set, otherwise from the platform config
+    /// directory. A missing or unreadable file yields an empty list.
+    pub fn load() -> Self {
+        let path = std::env::var_os("KOTO_RECENT_CATALOGS")
+            .map(PathBuf::from)
+            .or_else(|| {
+                ProjectDirs::from("", "", "koto_learning")
+                    .map(|dirs| dirs.config_dir().join(RECENT_CATALOGS_FILE))
+            });
+        match path {
+            Some(path) => Self::load_from(path),
+            None => Self::default(),
+        }
+    }
+
+    pub fn load_from(path: PathBuf) -> Self {
+        let entries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str::<RecentCatalogsFile>(&text).ok())
+            .map(|file| file.catalogs)
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            entries,
+        }
+    }
+
+    pub fn entries(&self) -> &[PathBuf] {
+        &self.entries
+    }
+
+    /// Moves `dir` to the front of the list and saves it.
+    pub fn record(&mut self, dir: &Path) -> Result<()> {
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        self.entries.retain(|entry| entry != &dir);
+        self.entries.insert(0, dir);
+        self.entries.truncate(MAX_RECENT_CATALOGS);
+        self.save()
+    }
+
+    pub fn remove(&mut self, dir: &Path) -> Result<()> {
+        self.entries.retain(|entry| entry != dir);
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let file = RecentCatalogsFile {
+            catalogs: self.entries.clone(),
+        };
+        let json = serde_json::to_string_pretty(&file)?;
+        fs::write(path, json).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
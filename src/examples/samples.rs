@@ -0,0 +1,104 @@
+//! The starter catalog written by `--init-samples` and by the welcome screen, so a new
+//! examples folder doesn't greet learners with an empty library.
+
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+
+/// Each sample's folder name and its files, relative to that folder.
+const SAMPLES: &[(&str, &[(&str, &str)])] = &[
+    (
+        "hello_world",
+        &[
+            ("meta.json", include_str!("samples/hello_world/meta.json")),
+            (
+                "script.koto",
+                include_str!("samples/hello_world/script.koto"),
+            ),
+        ],
+    ),
+    (
+        "collections",
+        &[
+            ("meta.json", include_str!("samples/collections/meta.json")),
+            (
+                "script.koto",
+                include_str!("samples/collections/script.koto"),
+            ),
+        ],
+    ),
+    (
+        "iterators",
+        &[
+            ("meta.json", include_str!("samples/iterators/meta.json")),
+            ("script.koto", include_str!("samples/iterators/script.koto")),
+        ],
+    ),
+    (
+        "error_handling",
+        &[
+            (
+                "meta.json",
+                include_str!("samples/error_handling/meta.json"),
+            ),
+            (
+                "script.koto",
+                include_str!("samples/error_handling/script.koto"),
+            ),
+        ],
+    ),
+    (
+        "tests_demo",
+        &[
+            ("meta.json", include_str!("samples/tests_demo/meta.json")),
+            (
+                "script.koto",
+                include_str!("samples/tests_demo/script.koto"),
+            ),
+            (
+                "tests/helpers.koto",
+                include_str!("samples/tests_demo/tests/helpers.koto"),
+            ),
+        ],
+    ),
+    (
+        "benchmark_demo",
+        &[
+            (
+                "meta.json",
+                include_str!("samples/benchmark_demo/meta.json"),
+            ),
+            (
+                "script.koto",
+                include_str!("samples/benchmark_demo/script.koto"),
+            ),
+        ],
+    ),
+];
+
+/// The folder names of the starter examples, which are also their ids.
+pub fn sample_ids() -> impl Iterator<Item = &'static str> {
+    SAMPLES.iter().map(|(id, _)| *id)
+}
+
+/// Writes the starter examples into `examples_dir` and returns the ids it wrote. Samples whose
+/// folder already exists are skipped, so running this twice never overwrites a learner's edits.
+pub fn write_samples(examples_dir: &Path) -> Result<Vec<String>> {
+    let mut written = Vec::new();
+    for (id, files) in SAMPLES {
+        let example_dir = examples_dir.join(id);
+        if example_dir.exists() {
+            continue;
+        }
+        for (relative, contents) in *files {
+            let path = example_dir.join(relative);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {parent:?}"))?;
+            }
+            fs::write(&path, contents).with_context(|| format!("Failed to write {path:?}"))?;
+        }
+        written.push(id.to_string());
+    }
+    Ok(written)
+}
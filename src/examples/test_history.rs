@@ -0,0 +1,178 @@
+//! Remembers the outcome of recent test suite runs so streaks, slowdowns, and flaky suites are
+//! visible across sessions.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use super::tests::{TestStatus, TestSuiteResult};
+
+/// How many runs are kept per suite.
+pub const MAX_SUITE_RUNS: usize = 20;
+const TEST_HISTORY_FILE: &str = "test_history.json";
+
+/// One finished run of a suite.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SuiteRun {
+    pub passed: bool,
+    pub cases: usize,
+    pub failed_cases: usize,
+    pub duration_ms: f64,
+    /// Seconds since the Unix epoch when the run was recorded.
+    pub finished_at: u64,
+}
+
+impl SuiteRun {
+    pub fn from_result(result: &TestSuiteResult) -> Self {
+        let failed_cases = result
+            .cases
+            .iter()
+            .filter(|case| case.status == TestStatus::Failed)
+            .count();
+        Self {
+            passed: result.passed,
+            cases: result.cases.len(),
+            failed_cases,
+            duration_ms: result.total_duration.as_secs_f64() * 1000.0,
+            finished_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.duration_ms.max(0.0) / 1000.0)
+    }
+}
+
+/// The last [`MAX_SUITE_RUNS`] runs of every suite, oldest first, keyed by
+/// `<example id>::<suite id>` and persisted as JSON in the user's config dir.
+#[derive(Clone, Debug, Default)]
+pub struct TestHistory {
+    path: Option<PathBuf>,
+    suites: BTreeMap<String, VecDeque<SuiteRun>>,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+struct TestHistoryFile {
+    #[serde(default)]
+    suites: BTreeMap<String, VecDeque<SuiteRun>>,
+}
+
+impl TestHistory {
+    /// Loads the history from `KOTO_TEST_HISTORY` if set, otherwise from the platform config
+    /// directory. A missing or unreadable file yields an empty history.
+    pub fn load() -> Self {
+        let path = std::env::var_os("KOTO_TEST_HISTORY")
+            .map(PathBuf::from)
+            .or_else(|| {
+                ProjectDirs::from("", "", "koto_learning")
+                    .map(|dirs| dirs.config_dir().join(TEST_HISTORY_FILE))
+            });
+        match path {
+            Some(path) => Self::load_from(path),
+            None => Self::default(),
+        }
+    }
+
+    pub fn load_from(path: PathBuf) -> Self {
+        let suites = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str::<TestHistoryFile>(&text).ok())
+            .map(|file| file.suites)
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            suites,
+        }
+    }
+
+    /// The runs of `key`, oldest first.
+    pub fn runs(&self, key: &str) -> Vec<&SuiteRun> {
+        self.suites
+            .get(key)
+            .map(|runs| runs.iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Adds `result` as the latest run of `key` and saves the history.
+    pub fn record(&mut self, key: &str, result: &TestSuiteResult) -> Result<()> {
+        let runs = self.suites.entry(key.to_string()).or_default();
+        runs.push_back(SuiteRun::from_result(result));
+        while runs.len() > MAX_SUITE_RUNS {
+            runs.pop_front();
+        }
+        self.save()
+    }
+
+    /// Moves the runs of every suite of `previous_id` to `new_id`, after an example is renamed.
+    pub fn rename_example(&mut self, previous_id: &str, new_id: &str) -> Result<()> {
+        let prefix = format!("{previous_id}::");
+        let keys: Vec<String> = self
+            .suites
+            .keys()
+            .filter(|key| key.starts_with(&prefix))
+            .cloned()
+            .collect();
+        if keys.is_empty() {
+            return Ok(());
+        }
+        for key in keys {
+            if let Some(runs) = self.suites.remove(&key) {
+                let suite_id = &key[prefix.len()..];
+                self.suites.insert(format!("{new_id}::{suite_id}"), runs);
+            }
+        }
+        self.save()
+    }
+
+    /// How many of the latest runs of `key` in a row had the same outcome as the latest, and
+    /// whether that was a pass.
+    pub fn streak(&self, key: &str) -> Option<(bool, usize)> {
+        let runs = self.suites.get(key)?;
+        let latest = runs.back()?.passed;
+        let length = runs
+            .iter()
+            .rev()
+            .take_while(|run| run.passed == latest)
+            .count();
+        Some((latest, length))
+    }
+
+    /// Whether `key` both passed and failed more than once among its recent runs, i.e. its
+    /// outcome keeps flipping rather than breaking once and staying broken.
+    pub fn is_flaky(&self, key: &str) -> bool {
+        let Some(runs) = self.suites.get(key) else {
+            return false;
+        };
+        let flips = runs
+            .iter()
+            .zip(runs.iter().skip(1))
+            .filter(|(earlier, later)| earlier.passed != later.passed)
+            .count();
+        flips >= 2
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let file = TestHistoryFile {
+            suites: self.suites.clone(),
+        };
+        let json = serde_json::to_string_pretty(&file)?;
+        fs::write(path, json).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
@@ -1,13 +1,31 @@
 use std::{
+    collections::{BTreeMap, BTreeSet},
     fs,
+    num::NonZeroUsize,
     path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    thread,
     time::{Duration, Instant},
 };
 
 use anyhow::{Context, Result};
 use koto::prelude::*;
 
-use crate::runtime::{self, Runtime};
+use super::html::escape;
+use crate::runtime::{
+    self, Runtime, RuntimeProfile,
+    coverage::{self, LineCoverage},
+};
+
+/// Folder next to the suite scripts that holds the golden files written by `assert_snapshot`.
+pub const SNAPSHOTS_DIR_NAME: &str = "__snapshots__";
+/// Suffix of a snapshot waiting to be accepted, next to the `.snap` golden file it replaces.
+pub const PENDING_SNAPSHOT_SUFFIX: &str = ".snap.new";
+/// Folder next to the suite scripts that holds the files named in `# Fixtures:` headers.
+pub const FIXTURES_DIR_NAME: &str = "fixtures";
 
 #[derive(Clone, Debug)]
 pub struct ExampleTestSuite {
@@ -16,6 +34,53 @@ pub struct ExampleTestSuite {
     pub description: Option<String>,
     pub path: PathBuf,
     pub script: String,
+    /// Per-case limit from a `# Timeout:` header, e.g. `# Timeout: 500ms` or `# Timeout: 2s`.
+    pub timeout: Option<Duration>,
+    /// Lowercased labels from a `# Tags:` header, e.g. `# Tags: slow, io`.
+    pub tags: Vec<String>,
+    /// Files in `tests/fixtures/` named by a `# Fixtures:` header, e.g.
+    /// `# Fixtures: users.csv, config.json`. Suites read them from the `fixtures` map.
+    pub fixtures: Vec<String>,
+    /// A script of the example named by an `# Example:` header, e.g. `# Example: script.koto`.
+    /// It runs before the suite, and its exports are bound to `example` so tests can call it.
+    pub example: Option<String>,
+}
+
+/// Picks suites by their `# Tags:` header. Written as `slow, io` to run only suites tagged
+/// `slow` or `io`, or `!slow` to run everything except them; an empty filter runs every suite.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TagFilter {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl TagFilter {
+    /// Reads tags separated by commas or spaces; a leading `!` or `-` excludes a tag.
+    pub fn parse(text: &str) -> Self {
+        let mut filter = Self::default();
+        for tag in text
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|tag| !tag.is_empty())
+        {
+            match tag.strip_prefix(['!', '-']) {
+                Some(excluded) if !excluded.is_empty() => {
+                    filter.exclude.push(excluded.to_lowercase())
+                }
+                Some(_) => {}
+                None => filter.include.push(tag.to_lowercase()),
+            }
+        }
+        filter
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    pub fn matches(&self, suite: &ExampleTestSuite) -> bool {
+        let has = |wanted: &String| suite.tags.contains(wanted);
+        !self.exclude.iter().any(has) && (self.include.is_empty() || self.include.iter().any(has))
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -29,6 +94,13 @@ pub struct TestSuiteResult {
     pub cases: Vec<TestCaseResult>,
     pub total_duration: Duration,
     pub passed: bool,
+    /// New or changed snapshots from this run, waiting for [`accept_snapshots`].
+    pub pending_snapshots: Vec<PathBuf>,
+    /// Which lines of the suite's `# Example:` script ran, when the runner measures
+    /// [`SuiteRunner::coverage`].
+    pub coverage: Option<LineCoverage>,
+    /// Whether [`SuiteRunner::max_failures`] stopped the suite before all of its cases ran.
+    pub stopped_early: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -47,6 +119,49 @@ pub enum TestStatus {
     Failed,
 }
 
+impl TestSuiteResult {
+    /// Names of the cases that failed, in the order they ran.
+    pub fn failed_cases(&self) -> Vec<String> {
+        self.cases
+            .iter()
+            .filter(|case| case.status == TestStatus::Failed)
+            .map(|case| case.name.clone())
+            .collect()
+    }
+
+    /// Folds in `rerun`, a run of some of this suite's cases: their results replace the earlier
+    /// ones, and the rest of the cases keep theirs.
+    pub fn merge_rerun(mut self, rerun: TestSuiteResult) -> TestSuiteResult {
+        for case in rerun.cases {
+            match self
+                .cases
+                .iter_mut()
+                .find(|existing| existing.name == case.name)
+            {
+                Some(existing) => *existing = case,
+                None => self.cases.push(case),
+            }
+        }
+        self.setup_stdout = rerun.setup_stdout;
+        self.setup_stderr = rerun.setup_stderr;
+        self.total_duration = self.cases.iter().map(|case| case.duration).sum();
+        self.passed = self
+            .cases
+            .iter()
+            .all(|case| case.status == TestStatus::Passed);
+        self.pending_snapshots = rerun.pending_snapshots;
+        self.coverage = match (self.coverage, rerun.coverage) {
+            (Some(mut coverage), Some(rerun)) => {
+                coverage.merge(&rerun);
+                Some(coverage)
+            }
+            (coverage, rerun) => rerun.or(coverage),
+        };
+        self.stopped_early = rerun.stopped_early;
+        self
+    }
+}
+
 pub fn load_suites(example_dir: &Path) -> Result<Vec<ExampleTestSuite>> {
     let tests_dir = example_dir.join("tests");
     if !tests_dir.exists() {
@@ -85,6 +200,10 @@ pub fn load_suites(example_dir: &Path) -> Result<Vec<ExampleTestSuite>> {
             description: metadata.description,
             path,
             script,
+            timeout: metadata.timeout,
+            tags: metadata.tags,
+            fixtures: metadata.fixtures,
+            example: metadata.example,
         });
     }
 
@@ -93,57 +212,757 @@ pub fn load_suites(example_dir: &Path) -> Result<Vec<ExampleTestSuite>> {
     Ok(suites)
 }
 
-pub fn run_suite(suite: &ExampleTestSuite) -> Result<TestSuiteResult> {
-    runtime::logging::with_runtime_subscriber(|| {
-        tracing::info!(
-            target: "runtime.tests",
-            suite = suite.id.as_str(),
-            path = %suite.path.display(),
-            "Running test suite",
-        );
-    });
+/// Builds the runtime that suites run in, for embedders that need settings other than the
+/// defaults used by [`run_suite`].
+///
+/// ```ignore
+/// let runner = SuiteRunner::new()
+///     .timeout(Duration::from_secs(2))
+///     .deterministic(true)
+///     .host_module("fixtures", fixtures_module);
+/// let results = runner.run_all(&suites)?;
+/// ```
+#[derive(Clone, Default)]
+pub struct SuiteRunner {
+    timeout: Option<Duration>,
+    profile: RuntimeProfile,
+    deterministic: bool,
+    env_allowlist: Vec<String>,
+    host_modules: Vec<(String, KMap)>,
+    factory: Option<Arc<RuntimeFactory>>,
+    shared: Option<Arc<SharedRuntimes>>,
+    /// Whether the shared runtime's VM is rebuilt before each suite.
+    reset_shared: bool,
+    tags: TagFilter,
+    update_snapshots: bool,
+    threads: Option<NonZeroUsize>,
+    coverage: bool,
+    cases: Option<Vec<String>>,
+    max_failures: Option<NonZeroUsize>,
+}
 
-    let runtime = Runtime::new().context("Failed to initialize runtime for tests")?;
-    let execution = runtime
-        .execute_script(&suite.script)
-        .with_context(|| format!("Failed to evaluate test suite '{}'", suite.name))?;
+type RuntimeFactory = dyn Fn() -> Result<Runtime> + Send + Sync;
 
-    let cases = runtime.with_koto(|koto| execute_suite_cases(&runtime, koto, suite))?;
-    let total_duration = cases.iter().map(|case| case.duration).sum();
-    let passed = cases.iter().all(|case| case.status == TestStatus::Passed);
+impl SuiteRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-    runtime::logging::with_runtime_subscriber(|| {
-        tracing::info!(
-            target: "runtime.tests",
-            suite = suite.id.as_str(),
-            case_count = cases.len(),
+    /// Limits the setup script and every test case to `timeout`, for suites without a
+    /// `# Timeout:` header. Without either, cases run without a limit.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn profile(mut self, profile: RuntimeProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Runs suites with reproducible timing helpers; see [`Runtime::set_deterministic`].
+    pub fn deterministic(mut self, enabled: bool) -> Self {
+        self.deterministic = enabled;
+        self
+    }
+
+    pub fn env_allowlist<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.env_allowlist = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Registers an extra host module in every runtime the runner creates.
+    pub fn host_module(mut self, name: impl Into<String>, module: KMap) -> Self {
+        self.host_modules.push((name.into(), module));
+        self
+    }
+
+    /// Replaces `Runtime::new()` as the way runtimes are constructed. The runner's own
+    /// settings are applied to each runtime after the factory returns it.
+    pub fn runtime_factory<F>(mut self, factory: F) -> Self
+    where
+        F: Fn() -> Result<Runtime> + Send + Sync + 'static,
+    {
+        self.factory = Some(Arc::new(factory));
+        self
+    }
+
+    /// Runs every suite in one VM instead of a fresh VM per suite. Bindings left behind by a
+    /// suite stay visible to later ones, but each suite's exports are cleared before it runs.
+    /// Suites with different timeouts get a VM each, since the limit is part of the VM.
+    pub fn shared_vm(mut self, shared: bool) -> Self {
+        self.shared = shared.then(Arc::default);
+        self.reset_shared = false;
+        self
+    }
+
+    /// Runs every suite in one warm runtime, rebuilding its VM before each suite so no state
+    /// leaks between them. Skips setting up a runtime per suite, which adds up with many
+    /// small suites, but suites then run one at a time.
+    pub fn shared_runtime(mut self, shared: bool) -> Self {
+        self.shared = shared.then(Arc::default);
+        self.reset_shared = shared;
+        self
+    }
+
+    /// Makes [`SuiteRunner::run_all`] skip suites that `filter` doesn't match.
+    pub fn tags(mut self, filter: TagFilter) -> Self {
+        self.tags = filter;
+        self
+    }
+
+    /// Makes `assert_snapshot` overwrite golden files instead of comparing against them.
+    pub fn update_snapshots(mut self, update: bool) -> Self {
+        self.update_snapshots = update;
+        self
+    }
+
+    /// How many suites run at once, each in its own runtime. Defaults to the number of CPUs;
+    /// runners with [`SuiteRunner::shared_vm`] or [`SuiteRunner::shared_runtime`] always run
+    /// one suite at a time.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = NonZeroUsize::new(threads);
+        self
+    }
+
+    /// Measures which lines of the example script run, for suites with an `# Example:`
+    /// header: the script is loaded instrumented, so the lines its own run and the suite's
+    /// tests reach are recorded without running anything twice.
+    pub fn coverage(mut self, enabled: bool) -> Self {
+        self.coverage = enabled;
+        self
+    }
+
+    /// Runs only the `@test` cases named in `names`, e.g. the ones that failed last time; the
+    /// rest are left out of the results. Setup code and `@pre_test`/`@post_test` still run, and
+    /// naming a case the suite doesn't define fails the run.
+    pub fn cases<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.cases = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Stops at the first failing case, so later cases and suites don't run. The same as
+    /// `max_failures(1)`; `fail_fast(false)` removes the limit.
+    pub fn fail_fast(self, enabled: bool) -> Self {
+        self.max_failures(usize::from(enabled))
+    }
+
+    /// Stops once `count` cases have failed across all the suites of a run, counting suites
+    /// whose setup failed as one failure each. Later cases are left out of the results and
+    /// suites that haven't started are skipped. `0`, the default, means no limit.
+    pub fn max_failures(mut self, count: usize) -> Self {
+        self.max_failures = NonZeroUsize::new(count);
+        self
+    }
+
+    pub fn run(&self, suite: &ExampleTestSuite) -> Result<TestSuiteResult> {
+        self.run_counted(suite, &FailureBudget::new(self.max_failures))
+    }
+
+    fn run_counted(
+        &self,
+        suite: &ExampleTestSuite,
+        budget: &FailureBudget,
+    ) -> Result<TestSuiteResult> {
+        runtime::logging::with_runtime_subscriber(|| {
+            tracing::info!(
+                target: "runtime.tests",
+                suite = suite.id.as_str(),
+                path = %suite.path.display(),
+                "Running test suite",
+            );
+        });
+
+        let timeout = self.case_timeout(suite);
+        match &self.shared {
+            Some(shared) => {
+                let runtime = shared.get_or_try_init(timeout, || self.build_runtime(timeout))?;
+                if self.reset_shared {
+                    runtime.reset_vm()?;
+                } else {
+                    runtime.with_koto(|koto| {
+                        koto.exports().data_mut().clear();
+                        Ok(())
+                    })?;
+                }
+                self.run_in(&runtime, suite, budget)
+            }
+            None => self.run_in(&self.build_runtime(timeout)?, suite, budget),
+        }
+    }
+
+    /// Runs the suites that match the runner's tag filter, in parallel unless the VM is
+    /// shared, and returns their results in the order of `suites`.
+    pub fn run_all(&self, suites: &[ExampleTestSuite]) -> Result<Vec<TestSuiteResult>> {
+        let suites: Vec<ExampleTestSuite> = suites
+            .iter()
+            .filter(|suite| self.tags.matches(suite))
+            .cloned()
+            .collect();
+        self.run_each(&suites, |_| true)
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// Runs every suite in `suites` on a pool of threads and returns each outcome at its
+    /// suite's index. `progress` is called with the number of finished suites after each one;
+    /// returning `false` stops further suites from starting, and those are left as `None`, as
+    /// are the suites skipped after reaching [`SuiteRunner::max_failures`].
+    pub fn run_each<F>(
+        &self,
+        suites: &[ExampleTestSuite],
+        progress: F,
+    ) -> Vec<Option<Result<TestSuiteResult>>>
+    where
+        F: Fn(usize) -> bool + Sync,
+    {
+        let threads = if self.shared.is_some() {
+            1
+        } else {
+            self.threads
+                .or_else(|| thread::available_parallelism().ok())
+                .map_or(1, NonZeroUsize::get)
+                .min(suites.len())
+        };
+        let next = AtomicUsize::new(0);
+        let finished = AtomicUsize::new(0);
+        let stopped = AtomicBool::new(false);
+        let budget = FailureBudget::new(self.max_failures);
+        let outcomes: Vec<Mutex<Option<Result<TestSuiteResult>>>> =
+            suites.iter().map(|_| Mutex::new(None)).collect();
+
+        thread::scope(|scope| {
+            for _ in 0..threads {
+                scope.spawn(|| {
+                    while !stopped.load(Ordering::SeqCst) {
+                        let index = next.fetch_add(1, Ordering::SeqCst);
+                        let Some(suite) = suites.get(index) else {
+                            break;
+                        };
+                        let outcome = self.run_counted(suite, &budget);
+                        if outcome.is_err() {
+                            budget.record_failure();
+                        }
+                        *outcomes[index]
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner) = Some(outcome);
+                        if !progress(finished.fetch_add(1, Ordering::SeqCst) + 1)
+                            || budget.is_exhausted()
+                        {
+                            stopped.store(true, Ordering::SeqCst);
+                        }
+                    }
+                });
+            }
+        });
+
+        outcomes
+            .into_iter()
+            .map(|outcome| outcome.into_inner().unwrap_or_else(PoisonError::into_inner))
+            .collect()
+    }
+
+    /// Creates a runtime whose VM limits each call to `timeout`. The limit is set first, as
+    /// changing it rebuilds the VM and would drop the helpers the runner adds to the prelude.
+    fn build_runtime(&self, timeout: Option<Duration>) -> Result<Runtime> {
+        let runtime = match &self.factory {
+            Some(factory) => factory()?,
+            None => Runtime::new().context("Failed to initialize runtime for tests")?,
+        };
+        runtime.set_execution_timeout(timeout)?;
+        runtime.set_profile(self.profile);
+        runtime.set_deterministic(self.deterministic);
+        runtime.set_env_allowlist(self.env_allowlist.iter().cloned());
+        for (name, module) in &self.host_modules {
+            runtime.register_host_module(name, module.clone())?;
+        }
+        Ok(runtime)
+    }
+
+    /// Each case gets the full limit to itself, shared with its `@pre_test` and `@post_test`
+    /// hooks; see `run_cases`.
+    fn case_timeout(&self, suite: &ExampleTestSuite) -> Option<Duration> {
+        suite.timeout.or(self.timeout)
+    }
+
+    fn run_in(
+        &self,
+        runtime: &Runtime,
+        suite: &ExampleTestSuite,
+        budget: &FailureBudget,
+    ) -> Result<TestSuiteResult> {
+        let timeout = self.case_timeout(suite);
+        let snapshots = snapshot_dir(suite);
+        let assert_snapshot = assert_snapshot_function(snapshots, self.update_snapshots);
+        let fixtures = load_fixtures(suite)?;
+        let example = self.load_example(runtime, suite, timeout)?;
+        // The runtime was built with `timeout` as its limit, so running the setup script
+        // below doesn't rebuild the VM and drop these again.
+        runtime.with_koto(|koto| {
+            koto.prelude().insert("assert_snapshot", assert_snapshot);
+            if let Some(fixtures) = fixtures {
+                koto.prelude().insert("fixtures", fixtures);
+            }
+            if let Some(example) = &example {
+                koto.prelude().insert("example", example.exports.clone());
+            }
+            Ok(())
+        })?;
+        let execution = runtime
+            .execute_script_with_timeout(&suite.script, timeout)
+            .with_context(|| format!("Failed to evaluate test suite '{}'", suite.name))?;
+
+        let (cases, stopped_early) = runtime.with_koto(|koto| {
+            execute_suite_cases(runtime, koto, suite, timeout, self.cases.as_deref(), budget)
+        })?;
+        let total_duration = cases.iter().map(|case| case.duration).sum();
+        let passed = cases.iter().all(|case| case.status == TestStatus::Passed);
+
+        runtime::logging::with_runtime_subscriber(|| {
+            tracing::info!(
+                target: "runtime.tests",
+                suite = suite.id.as_str(),
+                case_count = cases.len(),
+                passed,
+                "Test suite finished",
+            );
+        });
+
+        Ok(TestSuiteResult {
+            suite_id: suite.id.clone(),
+            suite_name: suite.name.clone(),
+            description: suite.description.clone(),
+            path: suite.path.clone(),
+            setup_stdout: execution.stdout,
+            setup_stderr: execution.stderr,
+            cases,
+            total_duration,
             passed,
-            "Test suite finished",
+            pending_snapshots: pending_snapshots(suite),
+            coverage: example.and_then(|example| example.coverage.map(PendingCoverage::finish)),
+            stopped_early,
+        })
+    }
+
+    /// Runs the script named by `suite`'s `# Example:` header and collects its exports.
+    /// When the runner measures coverage, the script runs instrumented and its probes keep
+    /// recording while the suite's tests call into it.
+    fn load_example(
+        &self,
+        runtime: &Runtime,
+        suite: &ExampleTestSuite,
+        timeout: Option<Duration>,
+    ) -> Result<Option<LoadedExample>> {
+        let Some(path) = example_script_path(suite)? else {
+            return Ok(None);
+        };
+        let script = fs::read_to_string(&path).with_context(|| {
+            format!(
+                "Failed to read example script {} for suite '{}'",
+                path.display(),
+                suite.name
+            )
+        })?;
+        let (script, coverage) = if self.coverage {
+            let instrumented = coverage::instrument(&script);
+            let hits = Arc::new(Mutex::new(BTreeSet::new()));
+            let probe = coverage::probe(hits.clone());
+            runtime.with_koto(|koto| {
+                koto.prelude().insert(coverage::PROBE_NAME, probe);
+                Ok(())
+            })?;
+            let coverage = PendingCoverage {
+                lines: instrumented.lines,
+                hits,
+            };
+            (instrumented.script, Some(coverage))
+        } else {
+            (script, None)
+        };
+
+        runtime
+            .execute_script_with_timeout(&script, timeout)
+            .with_context(|| {
+                format!(
+                    "Failed to run example script {} for suite '{}'",
+                    path.display(),
+                    suite.name
+                )
+            })?;
+        // The next run replaces the VM's exports, so they're copied out.
+        let exports = runtime.with_koto(|koto| {
+            let exports = KMap::new();
+            for (key, value) in koto.exports().data().iter() {
+                exports.insert(key.clone(), value.clone());
+            }
+            Ok(exports)
+        })?;
+        Ok(Some(LoadedExample { exports, coverage }))
+    }
+}
+
+/// The exports of a suite's `# Example:` script, bound to `example` while the suite runs.
+struct LoadedExample {
+    exports: KMap,
+    coverage: Option<PendingCoverage>,
+}
+
+/// Lines hit so far by the probes of an instrumented example script.
+struct PendingCoverage {
+    lines: Vec<usize>,
+    hits: Arc<Mutex<BTreeSet<usize>>>,
+}
+
+impl PendingCoverage {
+    fn finish(self) -> LineCoverage {
+        let hit = self
+            .hits
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        LineCoverage::new(self.lines, hit)
+    }
+}
+
+/// Runs `suite` in a fresh runtime with default settings.
+pub fn run_suite(suite: &ExampleTestSuite) -> Result<TestSuiteResult> {
+    SuiteRunner::default().run(suite)
+}
+
+/// Runs only the `@test` cases of `suite` named in `cases`, in a fresh runtime with default
+/// settings. Naming a case the suite doesn't define is an error.
+pub fn run_suite_cases(suite: &ExampleTestSuite, cases: &[String]) -> Result<TestSuiteResult> {
+    SuiteRunner::default().cases(cases).run(suite)
+}
+
+pub fn run_suites(suites: &[ExampleTestSuite]) -> Result<Vec<TestSuiteResult>> {
+    SuiteRunner::default().run_all(suites)
+}
+
+/// Runs the suites that `filter` matches, each in a fresh runtime with default settings.
+pub fn run_suites_tagged(
+    suites: &[ExampleTestSuite],
+    filter: &TagFilter,
+) -> Result<Vec<TestSuiteResult>> {
+    SuiteRunner::default().tags(filter.clone()).run_all(suites)
+}
+
+/// Writes `results` as a JUnit XML report to `path`, for CI systems and editors.
+pub fn export_junit(results: &[TestSuiteResult], path: &Path) -> Result<()> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(path, junit_xml(results))
+        .with_context(|| format!("Failed to write JUnit report {}", path.display()))
+}
+
+/// A JUnit XML report with one `<testsuite>` per result and one `<testcase>` per case.
+pub fn junit_xml(results: &[TestSuiteResult]) -> String {
+    let failures = |result: &TestSuiteResult| {
+        result
+            .cases
+            .iter()
+            .filter(|case| case.status == TestStatus::Failed)
+            .count()
+    };
+    let tests: usize = results.iter().map(|result| result.cases.len()).sum();
+    let failed: usize = results.iter().map(failures).sum();
+    let time: Duration = results.iter().map(|result| result.total_duration).sum();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"koto_learning\" tests=\"{tests}\" failures=\"{failed}\" errors=\"0\" time=\"{:.3}\">\n",
+        time.as_secs_f64()
+    ));
+    for result in results {
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"0\" time=\"{:.3}\" file=\"{}\">\n",
+            escape(&result.suite_name),
+            result.cases.len(),
+            failures(result),
+            result.total_duration.as_secs_f64(),
+            escape(&result.path.display().to_string()),
+        ));
+        for case in &result.cases {
+            xml.push_str(&format!(
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                escape(&case.name),
+                escape(&result.suite_id),
+                case.duration.as_secs_f64(),
+            ));
+            let failure = (case.status == TestStatus::Failed)
+                .then(|| case.error.as_deref().unwrap_or("Test failed"));
+            if failure.is_none() && case.stdout.is_empty() && case.stderr.is_empty() {
+                xml.push_str("/>\n");
+                continue;
+            }
+            xml.push_str(">\n");
+            if let Some(message) = failure {
+                let summary = message.lines().next().unwrap_or_default();
+                xml.push_str(&format!(
+                    "      <failure message=\"{}\">{}</failure>\n",
+                    escape(summary),
+                    escape(message)
+                ));
+            }
+            if !case.stdout.is_empty() {
+                xml.push_str(&format!(
+                    "      <system-out>{}</system-out>\n",
+                    escape(&case.stdout)
+                ));
+            }
+            if !case.stderr.is_empty() {
+                xml.push_str(&format!(
+                    "      <system-err>{}</system-err>\n",
+                    escape(&case.stderr)
+                ));
+            }
+            xml.push_str("    </testcase>\n");
+        }
+        if !result.setup_stdout.is_empty() {
+            xml.push_str(&format!(
+                "    <system-out>{}</system-out>\n",
+                escape(&result.setup_stdout)
+            ));
+        }
+        if !result.setup_stderr.is_empty() {
+            xml.push_str(&format!(
+                "    <system-err>{}</system-err>\n",
+                escape(&result.setup_stderr)
+            ));
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+/// Where `assert_snapshot` keeps `suite`'s golden files: `tests/__snapshots__/<suite id>/`.
+pub fn snapshot_dir(suite: &ExampleTestSuite) -> PathBuf {
+    suite
+        .path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(SNAPSHOTS_DIR_NAME)
+        .join(&suite.id)
+}
+
+/// Where the script named in `suite`'s `# Example:` header is read from: the example folder
+/// that holds the suite's `tests/` folder. `None` without the header; names that would reach
+/// outside that folder are an error.
+pub fn example_script_path(suite: &ExampleTestSuite) -> Result<Option<PathBuf>> {
+    let Some(name) = suite.example.as_ref() else {
+        return Ok(None);
+    };
+    if !is_plain_file_name(name) {
+        anyhow::bail!(
+            "Invalid example script '{name}' in suite '{}'; name a file in the example folder",
+            suite.name
         );
-    });
+    }
+    let tests_dir = suite.path.parent().unwrap_or_else(|| Path::new("."));
+    Ok(Some(tests_dir.parent().unwrap_or(tests_dir).join(name)))
+}
+
+/// Where the files named in `suite`'s `# Fixtures:` header are read from.
+pub fn fixtures_dir(suite: &ExampleTestSuite) -> PathBuf {
+    suite
+        .path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(FIXTURES_DIR_NAME)
+}
 
-    Ok(TestSuiteResult {
-        suite_id: suite.id.clone(),
-        suite_name: suite.name.clone(),
-        description: suite.description.clone(),
-        path: suite.path.clone(),
-        setup_stdout: execution.stdout,
-        setup_stderr: execution.stderr,
-        cases,
-        total_duration,
-        passed,
+/// Reads `suite`'s fixture files into a map from file name to contents, or `None` when the
+/// suite doesn't declare any, so a host module named `fixtures` stays visible.
+fn load_fixtures(suite: &ExampleTestSuite) -> Result<Option<KMap>> {
+    if suite.fixtures.is_empty() {
+        return Ok(None);
+    }
+    let dir = fixtures_dir(suite);
+    let fixtures = KMap::new();
+    for name in &suite.fixtures {
+        if !is_plain_file_name(name) {
+            anyhow::bail!(
+                "Invalid fixture name '{name}' in suite '{}'; fixtures are files directly in {}",
+                suite.name,
+                dir.display()
+            );
+        }
+        let path = dir.join(name);
+        let contents = fs::read_to_string(&path).with_context(|| {
+            format!(
+                "Failed to read fixture '{name}' for suite '{}' from {}",
+                suite.name,
+                path.display()
+            )
+        })?;
+        fixtures.insert(name.as_str(), contents);
+    }
+    Ok(Some(fixtures))
+}
+
+/// The `.snap.new` files written for `suite` by failed snapshot assertions.
+pub fn pending_snapshots(suite: &ExampleTestSuite) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(snapshot_dir(suite)) else {
+        return Vec::new();
+    };
+    let mut pending: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.ends_with(PENDING_SNAPSHOT_SUFFIX))
+        })
+        .collect();
+    pending.sort();
+    pending
+}
+
+/// Makes each pending snapshot of `suite` its golden file; returns how many were accepted.
+pub fn accept_snapshots(suite: &ExampleTestSuite) -> Result<usize> {
+    let pending = pending_snapshots(suite);
+    for path in &pending {
+        let name = path.to_string_lossy();
+        let golden = PathBuf::from(format!(
+            "{}.snap",
+            name.trim_end_matches(PENDING_SNAPSHOT_SUFFIX)
+        ));
+        fs::rename(path, &golden)
+            .with_context(|| format!("Failed to accept snapshot {}", path.display()))?;
+    }
+    Ok(pending.len())
+}
+
+/// `assert_snapshot(name, value)`: compares `value` with the golden file for `name`, writing a
+/// pending `.snap.new` file when it is missing or different.
+fn assert_snapshot_function(dir: PathBuf, update: bool) -> KNativeFunction {
+    KNativeFunction::new(move |ctx: &mut CallContext| match ctx.args() {
+        [KValue::Str(name), value] => match check_snapshot(&dir, name, value, update) {
+            Ok(()) => Ok(KValue::Null),
+            Err(message) => runtime_error!("{message}"),
+        },
+        other => runtime_error!("Expected a snapshot name and a value, found {other:?}"),
     })
 }
 
-pub fn run_suites(suites: &[ExampleTestSuite]) -> Result<Vec<TestSuiteResult>> {
-    suites.iter().map(run_suite).collect()
+/// Whether `name` names a file directly inside a folder: letters, digits, `_`, `-`, and `.`,
+/// not starting with `.`, so it can't be absolute, contain `..`, or reach into another folder.
+fn is_plain_file_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+fn check_snapshot(dir: &Path, name: &str, value: &KValue, update: bool) -> Result<(), String> {
+    if !is_plain_file_name(name) {
+        return Err(format!(
+            "Invalid snapshot name '{name}'; use letters, digits, '_', '-', and '.'"
+        ));
+    }
+    let text = snapshot_text(value);
+    let golden = dir.join(format!("{name}.snap"));
+    let pending = dir.join(format!("{name}{PENDING_SNAPSHOT_SUFFIX}"));
+    let write = |path: &Path| {
+        fs::create_dir_all(dir)
+            .and_then(|_| fs::write(path, &text))
+            .map_err(|error| format!("Failed to write {}: {error}", path.display()))
+    };
+
+    if update {
+        write(&golden)?;
+        let _ = fs::remove_file(&pending);
+        return Ok(());
+    }
+    match fs::read_to_string(&golden) {
+        Ok(expected) if normalize_snapshot(&expected) == normalize_snapshot(&text) => {
+            let _ = fs::remove_file(&pending);
+            Ok(())
+        }
+        Ok(expected) => {
+            write(&pending)?;
+            Err(format!(
+                "Snapshot '{name}' doesn't match {}: {}",
+                golden.display(),
+                first_difference(&expected, &text)
+            ))
+        }
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            write(&pending)?;
+            Err(format!(
+                "New snapshot '{name}' written to {}; accept it to make it the golden file",
+                pending.display()
+            ))
+        }
+        Err(error) => Err(format!("Failed to read {}: {error}", golden.display())),
+    }
+}
+
+/// Strings are stored as they are; other values as pretty-printed JSON.
+fn snapshot_text(value: &KValue) -> String {
+    let mut text = match value {
+        KValue::Str(text) => text.to_string(),
+        other => serde_json::to_string_pretty(&runtime::value_to_json(other, 0))
+            .unwrap_or_else(|_| other.type_as_string().to_string()),
+    };
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+    text
+}
+
+/// Ignores line-ending style and trailing whitespace, which editors change freely.
+fn normalize_snapshot(text: &str) -> String {
+    text.lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end()
+        .to_string()
+}
+
+fn first_difference(expected: &str, actual: &str) -> String {
+    let expected = normalize_snapshot(expected);
+    let actual = normalize_snapshot(actual);
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    for line in 1.. {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(want), Some(got)) if want == got => continue,
+            (Some(want), Some(got)) => {
+                return format!("line {line} was `{want}`, now `{got}`");
+            }
+            (Some(want), None) => return format!("line {line} `{want}` is missing"),
+            (None, Some(got)) => return format!("line {line} `{got}` is new"),
+            (None, None) => break,
+        }
+    }
+    "the snapshots differ".to_string()
 }
 
 fn execute_suite_cases(
     runtime: &Runtime,
     koto: &mut Koto,
     suite: &ExampleTestSuite,
-) -> Result<Vec<TestCaseResult>> {
+    timeout: Option<Duration>,
+    only: Option<&[String]>,
+    budget: &FailureBudget,
+) -> Result<(Vec<TestCaseResult>, bool)> {
     let mut test_maps = Vec::new();
 
     for (key, value) in koto.exports().data().iter() {
@@ -168,10 +987,73 @@ fn execute_suite_cases(
         );
     });
 
-    run_cases(runtime, koto, &tests_map)
+    let (cases, stopped_early) = run_cases(runtime, koto, &tests_map, timeout, only, budget)?;
+    if !stopped_early {
+        if let Some(missing) = only
+            .unwrap_or_default()
+            .iter()
+            .find(|name| !cases.iter().any(|case| &case.name == *name))
+        {
+            anyhow::bail!("'{}' has no @test named '{missing}'", suite.name);
+        }
+    }
+    Ok((cases, stopped_early))
+}
+
+/// The runtimes of a runner with [`SuiteRunner::shared_vm`] or [`SuiteRunner::shared_runtime`],
+/// one per case timeout. Changing a runtime's limit rebuilds its VM, which would drop what
+/// earlier suites left behind along with the runner's helpers.
+#[derive(Default)]
+struct SharedRuntimes(Mutex<BTreeMap<Option<Duration>, Arc<Runtime>>>);
+
+impl SharedRuntimes {
+    fn get_or_try_init(
+        &self,
+        timeout: Option<Duration>,
+        build: impl FnOnce() -> Result<Runtime>,
+    ) -> Result<Arc<Runtime>> {
+        let mut runtimes = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(runtime) = runtimes.get(&timeout) {
+            return Ok(Arc::clone(runtime));
+        }
+        let runtime = Arc::new(build()?);
+        runtimes.insert(timeout, Arc::clone(&runtime));
+        Ok(runtime)
+    }
+}
+
+/// Failures counted toward [`SuiteRunner::max_failures`], shared by the suites of one run.
+struct FailureBudget {
+    limit: Option<NonZeroUsize>,
+    failures: AtomicUsize,
+}
+
+impl FailureBudget {
+    fn new(limit: Option<NonZeroUsize>) -> Self {
+        Self {
+            limit,
+            failures: AtomicUsize::new(0),
+        }
+    }
+
+    fn record_failure(&self) {
+        self.failures.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.limit
+            .is_some_and(|limit| self.failures.load(Ordering::SeqCst) >= limit.get())
+    }
 }
 
-fn run_cases(runtime: &Runtime, koto: &mut Koto, tests: &KMap) -> Result<Vec<TestCaseResult>> {
+fn run_cases(
+    runtime: &Runtime,
+    koto: &mut Koto,
+    tests: &KMap,
+    timeout: Option<Duration>,
+    only: Option<&[String]>,
+    budget: &FailureBudget,
+) -> Result<(Vec<TestCaseResult>, bool)> {
     use TestStatus::{Failed, Passed};
 
     let (pre_test, post_test, meta_entry_count) = match tests.meta_map() {
@@ -187,6 +1069,7 @@ fn run_cases(runtime: &Runtime, koto: &mut Koto, tests: &KMap) -> Result<Vec<Tes
     };
 
     let mut cases = Vec::new();
+    let mut stopped_early = false;
     let self_arg = KValue::Map(tests.clone());
 
     for index in 0..meta_entry_count {
@@ -199,38 +1082,58 @@ fn run_cases(runtime: &Runtime, koto: &mut Koto, tests: &KMap) -> Result<Vec<Tes
         let Some((MetaKey::Test(test_name), test_fn)) = meta_entry else {
             continue;
         };
+        if only.is_some_and(|names| !names.iter().any(|name| name == test_name.as_str())) {
+            continue;
+        }
+        if budget.is_exhausted() {
+            stopped_early = true;
+            break;
+        }
 
         let mut status = Passed;
         let mut error = None;
+        let mut timed_out = false;
         runtime.clear_output();
         let start = Instant::now();
-
-        if let Some(pre) = pre_test.clone() {
-            if let Err(message) = call_stage(koto, &self_arg, &pre) {
-                status = Failed;
-                error = Some(format!("pre-test failed: {message}"));
-            }
+        // The hooks and the case share one deadline, which host calls such as `host.sleep_ms`
+        // stop at; code that never calls into the host is stopped by the VM's own limit, which
+        // applies to each stage. Either way the case only times out if it was stopped.
+        if let Some(timeout) = timeout {
+            runtime.begin_deadline(timeout);
         }
-
-        if status == Passed {
-            if let Err(message) = call_stage(koto, &self_arg, &test_fn) {
+        let stages = [
+            pre_test.clone().map(|hook| (Some("pre-test"), hook)),
+            Some((None, test_fn)),
+            post_test.clone().map(|hook| (Some("post-test"), hook)),
+        ];
+        for (hook, function) in stages.into_iter().flatten() {
+            if let Err(message) = call_stage(koto, &self_arg, &function) {
                 status = Failed;
-                error = Some(message);
+                timed_out = runtime::is_execution_limit_error(&message);
+                error = Some(match hook {
+                    Some(hook) => format!("{hook} failed: {message}"),
+                    None => message,
+                });
+                break;
             }
         }
-
-        if status == Passed {
-            if let Some(post) = post_test.clone() {
-                if let Err(message) = call_stage(koto, &self_arg, &post) {
-                    status = Failed;
-                    error = Some(format!("post-test failed: {message}"));
-                }
-            }
+        if timeout.is_some() {
+            runtime.end_deadline();
         }
 
         let duration = start.elapsed();
+        if let Some(timeout) = timeout.filter(|_| timed_out) {
+            error = Some(format!(
+                "Timed out after {:.2}s (limit {:.2}s)",
+                duration.as_secs_f64(),
+                timeout.as_secs_f64()
+            ));
+        }
         let stdout = runtime.take_stdout();
         let stderr = runtime.take_stderr();
+        if status == Failed {
+            budget.record_failure();
+        }
 
         cases.push(TestCaseResult {
             name: test_name.to_string(),
@@ -242,7 +1145,7 @@ fn run_cases(runtime: &Runtime, koto: &mut Koto, tests: &KMap) -> Result<Vec<Tes
         });
     }
 
-    Ok(cases)
+    Ok((cases, stopped_early))
 }
 
 fn call_stage(koto: &mut Koto, instance: &KValue, function: &KValue) -> Result<(), String> {
@@ -266,6 +1169,10 @@ fn map_contains_tests(map: &KMap) -> bool {
 fn parse_metadata(script: &str, fallback_id: &str) -> SuiteMetadata {
     let mut name = None;
     let mut description = None;
+    let mut timeout = None;
+    let mut tags = Vec::new();
+    let mut fixtures = Vec::new();
+    let mut example = None;
 
     for line in script.lines() {
         let trimmed = line.trim();
@@ -280,16 +1187,55 @@ fn parse_metadata(script: &str, fallback_id: &str) -> SuiteMetadata {
             name = Some(rest.trim().to_string());
         } else if let Some(rest) = content.strip_prefix("Description:") {
             description = Some(rest.trim().to_string());
+        } else if let Some(rest) = content.strip_prefix("Timeout:") {
+            timeout = parse_timeout(rest);
+        } else if let Some(rest) = content.strip_prefix("Tags:") {
+            tags = rest
+                .split(',')
+                .map(|tag| tag.trim().to_lowercase())
+                .filter(|tag| !tag.is_empty())
+                .collect();
+        } else if let Some(rest) = content.strip_prefix("Fixtures:") {
+            fixtures = rest
+                .split(',')
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect();
+        } else if let Some(rest) = content.strip_prefix("Example:") {
+            example = Some(rest.trim().to_string()).filter(|name| !name.is_empty());
         }
     }
 
     SuiteMetadata {
         name: name.unwrap_or_else(|| fallback_id.to_string()),
         description,
+        timeout,
+        tags,
+        fixtures,
+        example,
     }
 }
 
+/// Reads `500ms`, `2s`, or a bare number of seconds such as `1.5`.
+fn parse_timeout(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    let (number, scale) = match text.strip_suffix("ms") {
+        Some(millis) => (millis, 0.001),
+        None => (text.strip_suffix('s').unwrap_or(text), 1.0),
+    };
+    number
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite() && *value > 0.0)
+        .map(|value| Duration::from_secs_f64(value * scale))
+}
+
 struct SuiteMetadata {
     name: String,
     description: Option<String>,
+    timeout: Option<Duration>,
+    tags: Vec<String>,
+    fixtures: Vec<String>,
+    example: Option<String>,
 }
@@ -0,0 +1,438 @@
+//! A minimal Language Server Protocol client for an external Koto language server.
+//!
+//! The client speaks JSON-RPC over the server's stdio. Messages are read on a background
+//! thread and applied when [`LspClient::poll`] is called, so the UI never blocks on the server.
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+use anyhow::{Context, Result, anyhow};
+use serde_json::{Value as JsonValue, json};
+
+use crate::runtime::logging;
+
+/// A diagnostic published by the language server for one document.
+#[derive(Clone, Debug)]
+pub struct LspDiagnostic {
+    /// Zero-based line number.
+    pub line: u32,
+    pub start_character: u32,
+    pub end_character: u32,
+    pub severity: LspSeverity,
+    pub message: String,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LspSeverity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+/// A location returned by a go-to-definition request.
+#[derive(Clone, Debug)]
+pub struct LspLocation {
+    pub path: PathBuf,
+    /// Zero-based line number.
+    pub line: u32,
+    pub character: u32,
+}
+
+/// The outcome of a request once the server has answered.
+#[derive(Clone, Debug)]
+pub enum LspResponse {
+    Hover(Option<String>),
+    Definition(Vec<LspLocation>),
+    Error(String),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum PendingRequest {
+    Initialize,
+    Hover,
+    Definition,
+    Shutdown,
+}
+
+pub struct LspClient {
+    command: String,
+    child: Child,
+    stdin: ChildStdin,
+    incoming: Receiver<JsonValue>,
+    next_id: u64,
+    pending: HashMap<u64, PendingRequest>,
+    responses: HashMap<u64, LspResponse>,
+    diagnostics: HashMap<PathBuf, Vec<LspDiagnostic>>,
+    versions: HashMap<PathBuf, i64>,
+    initialized: bool,
+}
+
+impl LspClient {
+    /// Launches the server described by `command_line` (program followed by arguments) and
+    /// sends the `initialize` handshake.
+    pub fn spawn(command_line: &str, root: &Path) -> Result<Self> {
+        let mut parts = command_line.split_whitespace();
+        let program = parts
+            .next()
+            .ok_or_else(|| anyhow!("No language server command configured"))?;
+        let mut child = Command::new(program)
+            .args(parts)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("Failed to start language server '{command_line}'"))?;
+
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow!("Language server stdin unavailable"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow!("Language server stdout unavailable"))?;
+
+        let (sender, incoming) = mpsc::channel();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            while let Some(message) = read_message(&mut reader) {
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut client = Self {
+            command: command_line.to_string(),
+            child,
+            stdin,
+            incoming,
+            next_id: 1,
+            pending: HashMap::new(),
+            responses: HashMap::new(),
+            diagnostics: HashMap::new(),
+            versions: HashMap::new(),
+            initialized: false,
+        };
+
+        let root_uri = path_to_uri(root);
+        client.request(
+            "initialize",
+            json!({
+                "processId": std::process::id(),
+                "rootUri": root_uri,
+                "capabilities": {
+                    "textDocument": {
+                        "hover": { "contentFormat": ["plaintext", "markdown"] },
+                        "publishDiagnostics": {},
+                        "definition": {}
+                    }
+                }
+            }),
+            PendingRequest::Initialize,
+        )?;
+
+        logging::with_runtime_subscriber(|| {
+            tracing::info!(target: "runtime.lsp", command = command_line, "Language server started");
+        });
+
+        Ok(client)
+    }
+
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    /// Returns `false` once the server process has exited.
+    pub fn is_running(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    /// Opens or updates a document on the server.
+    pub fn sync_document(&mut self, path: &Path, text: &str) -> Result<()> {
+        let uri = path_to_uri(path);
+        match self.versions.get_mut(path) {
+            Some(version) => {
+                *version += 1;
+                let version = *version;
+                self.notify(
+                    "textDocument/didChange",
+                    json!({
+                        "textDocument": { "uri": uri, "version": version },
+                        "contentChanges": [{ "text": text }]
+                    }),
+                )
+            }
+            None => {
+                self.versions.insert(path.to_path_buf(), 1);
+                self.notify(
+                    "textDocument/didOpen",
+                    json!({
+                        "textDocument": {
+                            "uri": uri,
+                            "languageId": "koto",
+                            "version": 1,
+                            "text": text
+                        }
+                    }),
+                )
+            }
+        }
+    }
+
+    /// Requests hover information; the answer arrives through [`Self::take_response`].
+    pub fn request_hover(&mut self, path: &Path, line: u32, character: u32) -> Result<u64> {
+        self.request(
+            "textDocument/hover",
+            position_params(path, line, character),
+            PendingRequest::Hover,
+        )
+    }
+
+    /// Requests the definition of the symbol at a position.
+    pub fn request_definition(&mut self, path: &Path, line: u32, character: u32) -> Result<u64> {
+        self.request(
+            "textDocument/definition",
+            position_params(path, line, character),
+            PendingRequest::Definition,
+        )
+    }
+
+    pub fn take_response(&mut self, id: u64) -> Option<LspResponse> {
+        self.responses.remove(&id)
+    }
+
+    pub fn diagnostics(&self, path: &Path) -> &[LspDiagnostic] {
+        self.diagnostics
+            .get(path)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Applies every message received since the last call.
+    pub fn poll(&mut self) {
+        while let Ok(message) = self.incoming.try_recv() {
+            self.handle_message(message);
+        }
+    }
+
+    pub fn shutdown(mut self) {
+        let _ = self.request("shutdown", JsonValue::Null, PendingRequest::Shutdown);
+        let _ = self.notify("exit", JsonValue::Null);
+        let _ = self.child.kill();
+        logging::with_runtime_subscriber(|| {
+            tracing::info!(target: "runtime.lsp", command = self.command.as_str(), "Language server stopped");
+        });
+    }
+
+    fn handle_message(&mut self, message: JsonValue) {
+        if let Some(id) = message.get("id").and_then(JsonValue::as_u64) {
+            if let Some(kind) = self.pending.remove(&id) {
+                self.handle_response(id, kind, &message);
+                return;
+            }
+        }
+
+        if message.get("method").and_then(JsonValue::as_str)
+            == Some("textDocument/publishDiagnostics")
+        {
+            if let Some(params) = message.get("params") {
+                self.handle_diagnostics(params);
+            }
+        }
+    }
+
+    fn handle_response(&mut self, id: u64, kind: PendingRequest, message: &JsonValue) {
+        if let Some(error) = message.get("error") {
+            let text = error
+                .get("message")
+                .and_then(JsonValue::as_str)
+                .unwrap_or("Unknown language server error")
+                .to_string();
+            self.responses.insert(id, LspResponse::Error(text));
+            return;
+        }
+
+        let result = message.get("result").cloned().unwrap_or(JsonValue::Null);
+        match kind {
+            PendingRequest::Initialize => {
+                self.initialized = true;
+                let _ = self.notify("initialized", json!({}));
+            }
+            PendingRequest::Hover => {
+                self.responses
+                    .insert(id, LspResponse::Hover(hover_text(&result)));
+            }
+            PendingRequest::Definition => {
+                self.responses
+                    .insert(id, LspResponse::Definition(locations(&result)));
+            }
+            PendingRequest::Shutdown => {}
+        }
+    }
+
+    fn handle_diagnostics(&mut self, params: &JsonValue) {
+        let Some(path) = params
+            .get("uri")
+            .and_then(JsonValue::as_str)
+            .and_then(uri_to_path)
+        else {
+            return;
+        };
+        let diagnostics = params
+            .get("diagnostics")
+            .and_then(JsonValue::as_array)
+            .map(|items| items.iter().filter_map(parse_diagnostic).collect())
+            .unwrap_or_default();
+        self.diagnostics.insert(path, diagnostics);
+    }
+
+    fn request(&mut self, method: &str, params: JsonValue, kind: PendingRequest) -> Result<u64> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.pending.insert(id, kind);
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))?;
+        Ok(id)
+    }
+
+    fn notify(&mut self, method: &str, params: JsonValue) -> Result<()> {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+    }
+
+    fn send(&mut self, message: JsonValue) -> Result<()> {
+        let body = serde_json::to_string(&message)?;
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{body}", body.len())
+            .and_then(|_| self.stdin.flush())
+            .context("Failed to write to language server")
+    }
+}
+
+impl Drop for LspClient {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+    }
+}
+
+fn read_message(reader: &mut impl BufRead) -> Option<JsonValue> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).ok()? == 0 {
+            return None;
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            content_length = value.trim().parse::<usize>().ok();
+        }
+    }
+
+    let mut body = vec![0; content_length?];
+    reader.read_exact(&mut body).ok()?;
+    serde_json::from_slice(&body).ok()
+}
+
+fn position_params(path: &Path, line: u32, character: u32) -> JsonValue {
+    json!({
+        "textDocument": { "uri": path_to_uri(path) },
+        "position": { "line": line, "character": character }
+    })
+}
+
+fn parse_diagnostic(value: &JsonValue) -> Option<LspDiagnostic> {
+    let range = value.get("range")?;
+    let start = range.get("start")?;
+    let end = range.get("end")?;
+    let severity = match value.get("severity").and_then(JsonValue::as_u64) {
+        Some(2) => LspSeverity::Warning,
+        Some(3) => LspSeverity::Information,
+        Some(4) => LspSeverity::Hint,
+        _ => LspSeverity::Error,
+    };
+    Some(LspDiagnostic {
+        line: start.get("line")?.as_u64()? as u32,
+        start_character: start.get("character")?.as_u64()? as u32,
+        end_character: end.get("character")?.as_u64()? as u32,
+        severity,
+        message: value.get("message")?.as_str()?.to_string(),
+    })
+}
+
+fn hover_text(result: &JsonValue) -> Option<String> {
+    let contents = result.get("contents")?;
+    let text = match contents {
+        JsonValue::String(text) => text.clone(),
+        JsonValue::Object(object) => object.get("value")?.as_str()?.to_string(),
+        JsonValue::Array(items) => items
+            .iter()
+            .filter_map(|item| match item {
+                JsonValue::String(text) => Some(text.clone()),
+                other => other
+                    .get("value")
+                    .and_then(JsonValue::as_str)
+                    .map(str::to_string),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => return None,
+    };
+    (!text.trim().is_empty()).then_some(text)
+}
+
+fn locations(result: &JsonValue) -> Vec<LspLocation> {
+    let items = match result {
+        JsonValue::Array(items) => items.clone(),
+        JsonValue::Null => Vec::new(),
+        single => vec![single.clone()],
+    };
+    items
+        .iter()
+        .filter_map(|item| {
+            let uri = item
+                .get("uri")
+                .or_else(|| item.get("targetUri"))?
+                .as_str()?;
+            let range = item.get("range").or_else(|| item.get("targetRange"))?;
+            let start = range.get("start")?;
+            Some(LspLocation {
+                path: uri_to_path(uri)?,
+                line: start.get("line")?.as_u64()? as u32,
+                character: start.get("character")?.as_u64()? as u32,
+            })
+        })
+        .collect()
+}
+
+pub fn path_to_uri(path: &Path) -> String {
+    let absolute = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let text = absolute.to_string_lossy().replace('\\', "/");
+    if text.starts_with('/') {
+        format!("file://{text}")
+    } else {
+        format!("file:///{text}")
+    }
+}
+
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let path = if path.len() > 2 && path.as_bytes()[2] == b':' {
+        // Windows URIs look like file:///C:/...
+        &path[1..]
+    } else {
+        path
+    };
+    Some(PathBuf::from(path))
+}
@@ -0,0 +1,373 @@
+//! Static knowledge about the Koto language used by the explorer's code views.
+
+use std::ops::Range;
+
+pub mod lsp;
+pub mod symbols;
+
+pub const DEFAULT_DOCS_BASE_URL: &str = "https://koto.dev/docs/0.16/core";
+
+/// Core library modules and the functions they export.
+pub const CORE_MODULES: &[(&str, &[&str])] = &[
+    (
+        "io",
+        &[
+            "create",
+            "current_dir",
+            "exists",
+            "extend_path",
+            "open",
+            "print",
+            "read_to_string",
+            "remove_file",
+            "stderr",
+            "stdin",
+            "stdout",
+            "temp_dir",
+        ],
+    ),
+    (
+        "iterator",
+        &[
+            "all",
+            "any",
+            "chain",
+            "chunks",
+            "consume",
+            "count",
+            "cycle",
+            "each",
+            "enumerate",
+            "find",
+            "flatten",
+            "fold",
+            "generate",
+            "intersperse",
+            "keep",
+            "last",
+            "max",
+            "min",
+            "min_max",
+            "next",
+            "next_back",
+            "once",
+            "peekable",
+            "position",
+            "product",
+            "repeat",
+            "reversed",
+            "skip",
+            "step",
+            "sum",
+            "take",
+            "to_list",
+            "to_map",
+            "to_string",
+            "to_tuple",
+            "windows",
+            "zip",
+        ],
+    ),
+    (
+        "koto",
+        &[
+            "args",
+            "copy",
+            "deep_copy",
+            "exports",
+            "hash",
+            "load",
+            "run",
+            "script_dir",
+            "script_path",
+            "size",
+            "type",
+        ],
+    ),
+    (
+        "list",
+        &[
+            "clear",
+            "contains",
+            "extend",
+            "fill",
+            "first",
+            "get",
+            "insert",
+            "is_empty",
+            "last",
+            "pop",
+            "push",
+            "remove",
+            "resize",
+            "resize_with",
+            "retain",
+            "reverse",
+            "sort",
+            "swap",
+            "to_tuple",
+            "transform",
+        ],
+    ),
+    (
+        "map",
+        &[
+            "clear",
+            "contains_key",
+            "extend",
+            "get",
+            "get_index",
+            "get_meta",
+            "insert",
+            "is_empty",
+            "keys",
+            "remove",
+            "sort",
+            "update",
+            "values",
+            "with_meta",
+        ],
+    ),
+    (
+        "number",
+        &[
+            "abs",
+            "acos",
+            "acosh",
+            "and",
+            "asin",
+            "asinh",
+            "atan",
+            "atan2",
+            "atanh",
+            "ceil",
+            "clamp",
+            "cos",
+            "cosh",
+            "degrees",
+            "e",
+            "exp",
+            "exp2",
+            "flip_bits",
+            "floor",
+            "infinity",
+            "is_nan",
+            "lerp",
+            "ln",
+            "log10",
+            "log2",
+            "max",
+            "min",
+            "nan",
+            "or",
+            "pi",
+            "pow",
+            "radians",
+            "recip",
+            "round",
+            "shift_left",
+            "shift_right",
+            "sin",
+            "sinh",
+            "sqrt",
+            "tan",
+            "tanh",
+            "tau",
+            "to_float",
+            "to_int",
+            "xor",
+        ],
+    ),
+    (
+        "os",
+        &["command", "name", "process_id", "start_timer", "time"],
+    ),
+    (
+        "range",
+        &[
+            "contains",
+            "end",
+            "expanded",
+            "intersection",
+            "is_inclusive",
+            "start",
+            "union",
+        ],
+    ),
+    (
+        "string",
+        &[
+            "bytes",
+            "chars",
+            "contains",
+            "ends_with",
+            "escape",
+            "format",
+            "from_bytes",
+            "is_empty",
+            "lines",
+            "replace",
+            "split",
+            "starts_with",
+            "strip_prefix",
+            "strip_suffix",
+            "to_lowercase",
+            "to_number",
+            "to_uppercase",
+            "trim",
+            "trim_end",
+            "trim_start",
+        ],
+    ),
+    (
+        "test",
+        &[
+            "assert",
+            "assert_eq",
+            "assert_ne",
+            "assert_near",
+            "run_tests",
+        ],
+    ),
+    (
+        "tuple",
+        &[
+            "contains",
+            "first",
+            "get",
+            "is_empty",
+            "last",
+            "sort_copy",
+            "to_list",
+        ],
+    ),
+];
+
+/// A `module.function` reference to the core library found in a script.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CoreReference {
+    pub module: &'static str,
+    pub function: &'static str,
+    /// Byte range of the full `module.function` text in the script.
+    pub span: Range<usize>,
+}
+
+impl CoreReference {
+    pub fn qualified_name(&self) -> String {
+        format!("{}.{}", self.module, self.function)
+    }
+
+    pub fn doc_url(&self, base_url: &str) -> String {
+        core_doc_url(base_url, self.module, Some(self.function))
+    }
+}
+
+/// Builds the koto.dev documentation URL for a core module or one of its functions.
+pub fn core_doc_url(base_url: &str, module: &str, function: Option<&str>) -> String {
+    let base = base_url.trim_end_matches('/');
+    match function {
+        Some(function) => format!("{base}/{module}/#{function}"),
+        None => format!("{base}/{module}/"),
+    }
+}
+
+pub fn core_module(name: &str) -> Option<(&'static str, &'static [&'static str])> {
+    CORE_MODULES
+        .iter()
+        .find(|(module, _)| *module == name)
+        .copied()
+}
+
+/// Finds every `module.function` reference to the core library, skipping comments and
+/// string literals.
+pub fn find_core_references(script: &str) -> Vec<CoreReference> {
+    let mut references = Vec::new();
+    for (word, span) in identifier_paths(script) {
+        let Some((module_name, function_name)) = word.split_once('.') else {
+            continue;
+        };
+        let Some((module, functions)) = core_module(module_name) else {
+            continue;
+        };
+        let function_name = function_name.split('.').next().unwrap_or_default();
+        if let Some(function) = functions.iter().find(|name| **name == function_name) {
+            let end = span.start + module_name.len() + 1 + function_name.len();
+            references.push(CoreReference {
+                module,
+                function,
+                span: span.start..end,
+            });
+        }
+    }
+    references
+}
+
+/// Returns the core reference that covers `offset`, if any.
+pub fn core_reference_at(script: &str, offset: usize) -> Option<CoreReference> {
+    find_core_references(script)
+        .into_iter()
+        .find(|reference| reference.span.contains(&offset))
+}
+
+/// Splits a script into dotted identifier paths (`a.b.c`) outside of comments and strings.
+fn identifier_paths(script: &str) -> Vec<(&str, Range<usize>)> {
+    let bytes = script.as_bytes();
+    let mut paths = Vec::new();
+    let mut index = 0;
+    let mut quote: Option<u8> = None;
+
+    while index < bytes.len() {
+        let byte = bytes[index];
+        if let Some(open) = quote {
+            if byte == b'\\' {
+                index += 2;
+                continue;
+            }
+            if byte == open {
+                quote = None;
+            }
+            index += 1;
+            continue;
+        }
+        match byte {
+            b'#' => {
+                while index < bytes.len() && bytes[index] != b'\n' {
+                    index += 1;
+                }
+            }
+            b'\'' | b'"' => {
+                quote = Some(byte);
+                index += 1;
+            }
+            _ if is_identifier_start(byte) && !follows_dot(bytes, index) => {
+                let start = index;
+                while index < bytes.len()
+                    && (is_identifier_continue(bytes[index])
+                        || (bytes[index] == b'.'
+                            && bytes
+                                .get(index + 1)
+                                .copied()
+                                .is_some_and(is_identifier_start)))
+                {
+                    index += 1;
+                }
+                paths.push((&script[start..index], start..index));
+            }
+            _ => index += 1,
+        }
+    }
+
+    paths
+}
+
+fn follows_dot(bytes: &[u8], index: usize) -> bool {
+    index > 0 && bytes[index - 1] == b'.'
+}
+
+fn is_identifier_start(byte: u8) -> bool {
+    byte.is_ascii_alphabetic() || byte == b'_'
+}
+
+fn is_identifier_continue(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
@@ -0,0 +1,577 @@
+use std::collections::BTreeMap;
+
+use super::{CORE_MODULES, identifier_paths};
+
+/// Signatures and one-line summaries for the most commonly taught core library functions.
+/// Functions without an entry still appear in the index, just without a signature.
+const CORE_SIGNATURES: &[(&str, &str, &str)] = &[
+    (
+        "io.print",
+        "|value...|",
+        "Prints values to stdout followed by a newline.",
+    ),
+    (
+        "io.read_to_string",
+        "|path: String| -> String",
+        "Reads a file into a string.",
+    ),
+    (
+        "iterator.all",
+        "|iterable, test: Function| -> Bool",
+        "True if every value passes the test.",
+    ),
+    (
+        "iterator.any",
+        "|iterable, test: Function| -> Bool",
+        "True if any value passes the test.",
+    ),
+    (
+        "iterator.chain",
+        "|first, second| -> Iterator",
+        "Yields values from both iterables in turn.",
+    ),
+    (
+        "iterator.count",
+        "|iterable| -> Number",
+        "Counts the values produced by the iterable.",
+    ),
+    (
+        "iterator.each",
+        "|iterable, function| -> Iterator",
+        "Maps each value through a function.",
+    ),
+    (
+        "iterator.enumerate",
+        "|iterable| -> Iterator",
+        "Pairs each value with its index.",
+    ),
+    (
+        "iterator.find",
+        "|iterable, test: Function| -> Any",
+        "Returns the first value that passes the test.",
+    ),
+    (
+        "iterator.fold",
+        "|iterable, initial, function| -> Any",
+        "Accumulates values with a reducer function.",
+    ),
+    (
+        "iterator.keep",
+        "|iterable, test: Function| -> Iterator",
+        "Keeps only values that pass the test.",
+    ),
+    (
+        "iterator.max",
+        "|iterable| -> Any",
+        "Returns the largest value.",
+    ),
+    (
+        "iterator.min",
+        "|iterable| -> Any",
+        "Returns the smallest value.",
+    ),
+    (
+        "iterator.skip",
+        "|iterable, n: Number| -> Iterator",
+        "Skips the first n values.",
+    ),
+    (
+        "iterator.sum",
+        "|iterable| -> Number",
+        "Adds all of the values together.",
+    ),
+    (
+        "iterator.take",
+        "|iterable, n: Number| -> Iterator",
+        "Yields at most n values.",
+    ),
+    (
+        "iterator.to_list",
+        "|iterable| -> List",
+        "Collects the values into a list.",
+    ),
+    (
+        "iterator.to_map",
+        "|iterable| -> Map",
+        "Collects key/value pairs into a map.",
+    ),
+    (
+        "iterator.to_tuple",
+        "|iterable| -> Tuple",
+        "Collects the values into a tuple.",
+    ),
+    (
+        "iterator.zip",
+        "|first, second| -> Iterator",
+        "Pairs values from two iterables.",
+    ),
+    (
+        "koto.copy",
+        "|value| -> Any",
+        "Makes a shallow copy of a value.",
+    ),
+    (
+        "koto.deep_copy",
+        "|value| -> Any",
+        "Makes a recursive copy of a value.",
+    ),
+    (
+        "koto.exports",
+        "|| -> Map",
+        "Returns the script's exported values.",
+    ),
+    (
+        "koto.type",
+        "|value| -> String",
+        "Returns the name of the value's type.",
+    ),
+    (
+        "list.contains",
+        "|list, value| -> Bool",
+        "True if the list contains the value.",
+    ),
+    (
+        "list.first",
+        "|list| -> Any",
+        "Returns the first element, or null.",
+    ),
+    (
+        "list.fold",
+        "|list, initial, function| -> Any",
+        "Accumulates the list with a reducer function.",
+    ),
+    (
+        "list.get",
+        "|list, index: Number, default?| -> Any",
+        "Returns the element at the index.",
+    ),
+    (
+        "list.insert",
+        "|list, index: Number, value| -> List",
+        "Inserts a value at the index.",
+    ),
+    (
+        "list.is_empty",
+        "|list| -> Bool",
+        "True if the list has no elements.",
+    ),
+    (
+        "list.last",
+        "|list| -> Any",
+        "Returns the last element, or null.",
+    ),
+    (
+        "list.pop",
+        "|list| -> Any",
+        "Removes and returns the last element.",
+    ),
+    (
+        "list.push",
+        "|list, value...| -> List",
+        "Appends values to the end of the list.",
+    ),
+    (
+        "list.remove",
+        "|list, index: Number| -> Any",
+        "Removes and returns the element at the index.",
+    ),
+    (
+        "list.retain",
+        "|list, test: Function| -> List",
+        "Keeps only elements that pass the test.",
+    ),
+    (
+        "list.reverse",
+        "|list| -> List",
+        "Reverses the list in place.",
+    ),
+    (
+        "list.sort",
+        "|list, key?: Function| -> List",
+        "Sorts the list in place.",
+    ),
+    (
+        "list.transform",
+        "|list, function| -> List",
+        "Replaces each element with the function's result.",
+    ),
+    (
+        "map.contains_key",
+        "|map, key| -> Bool",
+        "True if the map contains the key.",
+    ),
+    (
+        "map.get",
+        "|map, key, default?| -> Any",
+        "Returns the value for the key.",
+    ),
+    (
+        "map.insert",
+        "|map, key, value?| -> Any",
+        "Inserts a value, returning the previous one.",
+    ),
+    (
+        "map.keys",
+        "|map| -> Iterator",
+        "Iterates over the map's keys.",
+    ),
+    (
+        "map.remove",
+        "|map, key| -> Any",
+        "Removes a key, returning its value.",
+    ),
+    (
+        "map.update",
+        "|map, key, function| -> Any",
+        "Updates a value with a function.",
+    ),
+    (
+        "map.values",
+        "|map| -> Iterator",
+        "Iterates over the map's values.",
+    ),
+    (
+        "number.abs",
+        "|n: Number| -> Number",
+        "Returns the absolute value.",
+    ),
+    (
+        "number.clamp",
+        "|n: Number, min: Number, max: Number| -> Number",
+        "Limits n to the range.",
+    ),
+    (
+        "number.floor",
+        "|n: Number| -> Number",
+        "Rounds down to an integer.",
+    ),
+    (
+        "number.max",
+        "|a: Number, b: Number| -> Number",
+        "Returns the larger number.",
+    ),
+    (
+        "number.min",
+        "|a: Number, b: Number| -> Number",
+        "Returns the smaller number.",
+    ),
+    (
+        "number.pow",
+        "|n: Number, exponent: Number| -> Number",
+        "Raises n to a power.",
+    ),
+    (
+        "number.round",
+        "|n: Number| -> Number",
+        "Rounds to the nearest integer.",
+    ),
+    (
+        "number.sqrt",
+        "|n: Number| -> Number",
+        "Returns the square root.",
+    ),
+    (
+        "number.to_int",
+        "|n: Number| -> Number",
+        "Converts to an integer.",
+    ),
+    (
+        "os.time",
+        "|| -> DateTime",
+        "Returns the current date and time.",
+    ),
+    (
+        "os.start_timer",
+        "|| -> Timer",
+        "Starts a timer that reports elapsed seconds.",
+    ),
+    (
+        "range.contains",
+        "|range, n: Number| -> Bool",
+        "True if the range contains n.",
+    ),
+    (
+        "string.contains",
+        "|text, pattern: String| -> Bool",
+        "True if the text contains the pattern.",
+    ),
+    (
+        "string.format",
+        "|format: String, value...| -> String",
+        "Formats values into a template.",
+    ),
+    (
+        "string.lines",
+        "|text| -> Iterator",
+        "Iterates over the lines of the text.",
+    ),
+    (
+        "string.replace",
+        "|text, pattern: String, replacement: String| -> String",
+        "Replaces all matches.",
+    ),
+    (
+        "string.split",
+        "|text, pattern: String| -> Iterator",
+        "Splits the text on a pattern.",
+    ),
+    (
+        "string.starts_with",
+        "|text, prefix: String| -> Bool",
+        "True if the text starts with the prefix.",
+    ),
+    (
+        "string.to_lowercase",
+        "|text| -> String",
+        "Converts the text to lowercase.",
+    ),
+    (
+        "string.to_number",
+        "|text| -> Number",
+        "Parses the text as a number.",
+    ),
+    (
+        "string.to_uppercase",
+        "|text| -> String",
+        "Converts the text to uppercase.",
+    ),
+    (
+        "string.trim",
+        "|text| -> String",
+        "Removes surrounding whitespace.",
+    ),
+    (
+        "test.assert",
+        "|condition: Bool|",
+        "Throws if the condition is false.",
+    ),
+    (
+        "test.assert_eq",
+        "|a, b|",
+        "Throws if the values are not equal.",
+    ),
+    (
+        "test.assert_ne",
+        "|a, b|",
+        "Throws if the values are equal.",
+    ),
+    (
+        "test.assert_near",
+        "|a: Number, b: Number, epsilon?: Number|",
+        "Throws if the numbers differ.",
+    ),
+    (
+        "tuple.to_list",
+        "|tuple| -> List",
+        "Copies the tuple into a list.",
+    ),
+];
+
+/// Reserved words offered by completion.
+pub const KEYWORDS: &[&str] = &[
+    "and", "break", "catch", "continue", "debug", "else", "export", "false", "finally", "for",
+    "from", "if", "import", "in", "let", "loop", "match", "not", "null", "or", "return", "self",
+    "switch", "then", "throw", "true", "try", "until", "while", "yield",
+];
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SymbolKind {
+    CoreFunction,
+    CoreModule,
+    HostBinding,
+    Export,
+    Local,
+    Keyword,
+}
+
+impl SymbolKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::CoreFunction => "core function",
+            Self::CoreModule => "core module",
+            Self::HostBinding => "host binding",
+            Self::Export => "export",
+            Self::Local => "local",
+            Self::Keyword => "keyword",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Symbol {
+    /// The name as written in scripts, e.g. `list.fold` or `make_counter`.
+    pub name: String,
+    pub kind: SymbolKind,
+    pub signature: Option<String>,
+    pub summary: Option<String>,
+}
+
+/// Lookup table of names a script can refer to, used for hover help and completion.
+#[derive(Clone, Debug, Default)]
+pub struct SymbolIndex {
+    symbols: BTreeMap<String, Symbol>,
+}
+
+impl SymbolIndex {
+    /// Builds an index containing only the core library.
+    pub fn core() -> Self {
+        let mut index = Self::default();
+        for (module, functions) in CORE_MODULES {
+            index.insert(Symbol {
+                name: module.to_string(),
+                kind: SymbolKind::CoreModule,
+                signature: None,
+                summary: Some(format!("Koto core library `{module}` module")),
+            });
+            for function in *functions {
+                let name = format!("{module}.{function}");
+                let documented = CORE_SIGNATURES
+                    .iter()
+                    .find(|(qualified, _, _)| *qualified == name);
+                index.insert(Symbol {
+                    name,
+                    kind: SymbolKind::CoreFunction,
+                    signature: documented.map(|(_, signature, _)| signature.to_string()),
+                    summary: documented.map(|(_, _, summary)| summary.to_string()),
+                });
+            }
+        }
+        index
+    }
+
+    /// Builds an index containing the core library plus the top-level bindings of `script`.
+    pub fn for_script(script: &str) -> Self {
+        let mut index = Self::core();
+        for symbol in script_bindings(script) {
+            index.insert(symbol);
+        }
+        index
+    }
+
+    /// Adds names registered by the host runtime, such as `host.sleep_ms` or `serde`.
+    pub fn with_host_bindings<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        for name in names {
+            let name = name.into();
+            if !self.symbols.contains_key(&name) {
+                self.insert(Symbol {
+                    name,
+                    kind: SymbolKind::HostBinding,
+                    signature: None,
+                    summary: None,
+                });
+            }
+        }
+        self
+    }
+
+    pub fn insert(&mut self, symbol: Symbol) {
+        self.symbols.insert(symbol.name.clone(), symbol);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Symbol> {
+        self.symbols.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Symbol> {
+        self.symbols.values()
+    }
+
+    /// Returns completion candidates for `prefix`. Bare prefixes match top-level names and
+    /// keywords, while dotted prefixes such as `list.f` match members of that path.
+    pub fn completions(&self, prefix: &str, limit: usize) -> Vec<Symbol> {
+        if prefix.is_empty() {
+            return Vec::new();
+        }
+        let qualified = prefix.contains('.');
+        let mut matches: Vec<Symbol> = self
+            .symbols
+            .values()
+            .filter(|symbol| symbol.name.starts_with(prefix) && symbol.name != prefix)
+            .filter(|symbol| qualified || !symbol.name.contains('.'))
+            .cloned()
+            .collect();
+        if !qualified {
+            matches.extend(
+                KEYWORDS
+                    .iter()
+                    .filter(|keyword| keyword.starts_with(prefix) && **keyword != prefix)
+                    .map(|keyword| Symbol {
+                        name: keyword.to_string(),
+                        kind: SymbolKind::Keyword,
+                        signature: None,
+                        summary: None,
+                    }),
+            );
+        }
+        matches.sort_by(|a, b| a.name.len().cmp(&b.name.len()).then(a.name.cmp(&b.name)));
+        matches.truncate(limit);
+        matches
+    }
+
+    /// Resolves the identifier path under the byte `offset` in `script`. Hovering `list` in
+    /// `list.fold` resolves the module, while hovering `fold` resolves the function.
+    pub fn symbol_at(&self, script: &str, offset: usize) -> Option<&Symbol> {
+        let (path, span) = identifier_paths(script)
+            .into_iter()
+            .find(|(_, span)| span.start <= offset && offset < span.end)?;
+        let relative = offset - span.start;
+        let end = path[relative..]
+            .find('.')
+            .map_or(path.len(), |index| relative + index);
+        self.get(&path[..end])
+    }
+}
+
+/// Extracts top-level assignments (`name = ...`, `export name = ...`) from a script, using
+/// `#` comment lines directly above a binding as its summary.
+pub fn script_bindings(script: &str) -> Vec<Symbol> {
+    let mut bindings = Vec::new();
+    let mut comment_lines: Vec<&str> = Vec::new();
+
+    for line in script.lines() {
+        if line.starts_with(char::is_whitespace) || line.trim().is_empty() {
+            comment_lines.clear();
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            comment_lines.push(comment.trim());
+            continue;
+        }
+
+        let (kind, rest) = match line.strip_prefix("export ") {
+            Some(rest) => (SymbolKind::Export, rest),
+            None => (SymbolKind::Local, line),
+        };
+        if let Some((name, value)) = rest.split_once('=') {
+            let name = name.trim();
+            let value = value.trim_start();
+            let is_identifier = !name.is_empty()
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                && !value.starts_with('=');
+            if is_identifier {
+                let signature = function_signature(value);
+                let summary = (!comment_lines.is_empty()).then(|| comment_lines.join(" "));
+                bindings.push(Symbol {
+                    name: name.to_string(),
+                    kind,
+                    signature,
+                    summary,
+                });
+            }
+        }
+        comment_lines.clear();
+    }
+
+    bindings
+}
+
+/// Returns the `|args|` header of a function literal.
+fn function_signature(value: &str) -> Option<String> {
+    let params = value.strip_prefix('|')?;
+    let end = params.find('|')?;
+    Some(format!("|{}|", &params[..end]))
+}
@@ -1,4 +1,6 @@
 pub mod app;
 pub mod benchmarks;
 pub mod examples;
+pub mod language;
 pub mod runtime;
+pub mod tasks;
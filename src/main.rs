@@ -1,8 +1,48 @@
-use anyhow::{Result, anyhow};
+use std::path::PathBuf;
+
+use anyhow::{Result, anyhow, bail};
 use eframe::NativeOptions;
-use koto_learning::{app::ExplorerApp, runtime::logging};
+use koto_learning::{
+    app::ExplorerApp,
+    examples,
+    runtime::{logging, memory::TrackingAllocator},
+};
+
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+
+const USAGE: &str = "\
+Usage: koto_learning [--init-samples [DIR]] [--automation SCRIPT]
+
+  --init-samples [DIR]  Write the starter examples into DIR (default: the examples
+                        directory) and exit. Existing example folders are left alone.
+  --automation SCRIPT   Drive the explorer with a Koto script using the `app` module,
+                        then exit with status 0 if the script succeeded and 1 otherwise.";
 
 fn main() -> Result<()> {
+    let mut automation = None;
+    let mut args = std::env::args().skip(1).peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--init-samples" => {
+                let dir = match args.next_if(|next| !next.starts_with("--")) {
+                    Some(dir) => PathBuf::from(dir),
+                    None => examples::default_examples_dir(),
+                };
+                return init_samples(dir);
+            }
+            "--automation" => match args.next() {
+                Some(script) => automation = Some(PathBuf::from(script)),
+                None => bail!("--automation needs a script path\n\n{USAGE}"),
+            },
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            other => bail!("Unknown argument '{other}'\n\n{USAGE}"),
+        }
+    }
+
     logging::init_global()?;
     log::info!("Launching Koto Learning Explorer");
 
@@ -11,9 +51,31 @@ fn main() -> Result<()> {
     eframe::run_native(
         "Koto Learning Explorer",
         native_options,
-        Box::new(|cc| Ok(Box::new(ExplorerApp::new(cc)))),
+        Box::new(|cc| {
+            let mut app = ExplorerApp::new(cc);
+            if let Some(script) = automation {
+                app = app.with_automation(script);
+            }
+            Ok(Box::new(app))
+        }),
     )
     .map_err(|error| anyhow!("Failed to start UI: {error}"))?;
 
     Ok(())
 }
+
+fn init_samples(dir: PathBuf) -> Result<()> {
+    std::fs::create_dir_all(&dir)?;
+    let written = examples::samples::write_samples(&dir)?;
+    if written.is_empty() {
+        println!("{} already has every starter example", dir.display());
+    } else {
+        println!(
+            "Added {} starter examples to {}: {}",
+            written.len(),
+            dir.display(),
+            written.join(", ")
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,179 @@
+//! The `audio` module: short sine tones, so examples about timing and loops can be heard.
+//!
+//! Sound is off unless enabled with [`Runtime::set_audio_enabled`](super::Runtime::set_audio_enabled).
+//! Tones take the same time either way, so a script behaves identically with the speakers
+//! muted, and every tone is recorded in the run's output. Builds without the `audio` feature
+//! never play them.
+
+use std::{sync::Arc, time::Duration};
+
+use koto::{prelude::*, runtime::Result as KotoRuntimeResult};
+
+use super::{HostState, number_to_f64, number_to_i64, timers};
+
+const MIN_FREQUENCY: f64 = 20.0;
+const MAX_FREQUENCY: f64 = 20_000.0;
+const MAX_TONE_MS: i64 = 10_000;
+const DEFAULT_VOLUME: f32 = 0.2;
+const BEEP: (f32, u64) = (880.0, 120);
+
+/// A tone a script played.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tone {
+    pub frequency: f32,
+    pub duration: Duration,
+    pub volume: f32,
+}
+
+/// Builds the `audio` module.
+pub(super) fn audio_module(host: Arc<HostState>) -> KValue {
+    let module = KMap::default();
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "tone",
+        KNativeFunction::new(move |ctx: &mut CallContext| {
+            let (frequency, millis, volume) = match ctx.args() {
+                [KValue::Number(frequency), KValue::Number(millis)] => {
+                    (number_to_f64(frequency), number_to_i64(millis), DEFAULT_VOLUME)
+                }
+                [
+                    KValue::Number(frequency),
+                    KValue::Number(millis),
+                    KValue::Number(volume),
+                ] => (
+                    number_to_f64(frequency),
+                    number_to_i64(millis),
+                    number_to_f64(volume) as f32,
+                ),
+                other => {
+                    return runtime_error!(
+                        "Expected a frequency in Hz, a duration in milliseconds, and an optional volume, found {other:?}"
+                    );
+                }
+            };
+            if !(MIN_FREQUENCY..=MAX_FREQUENCY).contains(&frequency) {
+                return runtime_error!(
+                    "Expected a frequency from {MIN_FREQUENCY} to {MAX_FREQUENCY} Hz, found {frequency}"
+                );
+            }
+            if !(0..=MAX_TONE_MS).contains(&millis) {
+                return runtime_error!(
+                    "Expected a duration from 0 to {MAX_TONE_MS} ms, found {millis}"
+                );
+            }
+            if !(0.0..=1.0).contains(&volume) {
+                return runtime_error!("Expected a volume from 0 to 1, found {volume}");
+            }
+            play_tone(
+                &state,
+                Tone {
+                    frequency: frequency as f32,
+                    duration: Duration::from_millis(millis as u64),
+                    volume,
+                },
+            )
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "beep",
+        KNativeFunction::new(move |_ctx: &mut CallContext| {
+            play_tone(
+                &state,
+                Tone {
+                    frequency: BEEP.0,
+                    duration: Duration::from_millis(BEEP.1),
+                    volume: DEFAULT_VOLUME,
+                },
+            )
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "enabled",
+        KNativeFunction::new(move |_ctx: &mut CallContext| Ok(state.audio_enabled().into())),
+    );
+
+    module.into()
+}
+
+/// Records the tone, plays it when audio is enabled, and waits for it to finish.
+fn play_tone(host: &HostState, tone: Tone) -> KotoRuntimeResult<KValue> {
+    host.check_interrupts()?;
+    if let Ok(mut tones) = host.tones.lock() {
+        tones.push(tone);
+    }
+    #[cfg(feature = "audio")]
+    if host.audio_enabled() && !host.is_deterministic() {
+        playback::play(tone);
+    }
+    timers::sleep_cooperatively(host, tone.duration)?;
+    Ok(KValue::Null)
+}
+
+#[cfg(feature = "audio")]
+mod playback {
+    use std::{
+        sync::{
+            Mutex,
+            mpsc::{self, Sender},
+        },
+        thread,
+    };
+
+    use once_cell::sync::Lazy;
+    use rodio::{OutputStream, Sink, Source, source::SineWave};
+
+    use super::Tone;
+    use crate::runtime::logging;
+
+    /// Audio output devices can't move between threads, so one thread owns the output stream
+    /// and plays the tones it is sent. It starts on the first audible tone.
+    static PLAYER: Lazy<Mutex<Option<Sender<Tone>>>> = Lazy::new(|| Mutex::new(None));
+
+    pub(super) fn play(tone: Tone) {
+        let Ok(mut player) = PLAYER.lock() else {
+            return;
+        };
+        if let Some(sender) = player.as_ref() {
+            if sender.send(tone).is_ok() {
+                return;
+            }
+        }
+        let (sender, receiver) = mpsc::channel::<Tone>();
+        let started = thread::Builder::new()
+            .name("koto-audio".to_string())
+            .spawn(move || {
+                let (_stream, handle) = match OutputStream::try_default() {
+                    Ok(output) => output,
+                    Err(error) => {
+                        logging::with_runtime_subscriber(|| {
+                            tracing::warn!(target: "runtime.vm", %error, "No audio output device");
+                        });
+                        return;
+                    }
+                };
+                for tone in receiver {
+                    match Sink::try_new(&handle) {
+                        Ok(sink) => {
+                            sink.append(
+                                SineWave::new(tone.frequency)
+                                    .take_duration(tone.duration)
+                                    .amplify(tone.volume),
+                            );
+                            sink.detach();
+                        }
+                        Err(error) => logging::with_runtime_subscriber(|| {
+                            tracing::warn!(target: "runtime.vm", %error, "Failed to play a tone");
+                        }),
+                    }
+                }
+            });
+        if started.is_ok() && sender.send(tone).is_ok() {
+            *player = Some(sender);
+        }
+    }
+}
@@ -0,0 +1,167 @@
+//! A battery of scripts that try to reach outside the sandbox, run under every
+//! [`RuntimeProfile`] by [`Runtime::run_sandbox_audit`](super::Runtime::run_sandbox_audit).
+
+use std::time::Duration;
+
+use super::RuntimeProfile;
+
+/// Probes are tiny, so anything slower than this is stuck rather than working.
+pub(super) const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Profiles the audit runs every probe under.
+pub const AUDITED_PROFILES: [RuntimeProfile; 2] =
+    [RuntimeProfile::Standard, RuntimeProfile::Trusted];
+
+/// A script attempting one forbidden operation.
+#[derive(Clone, Copy, Debug)]
+pub struct AuditProbe {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub script: &'static str,
+    /// Whether the trusted profile is meant to permit the operation.
+    pub allowed_when_trusted: bool,
+    /// Text in the error that shows the sandbox, rather than something else, stopped the
+    /// script; `None` when nothing guards the operation yet.
+    pub blocked_marker: Option<&'static str>,
+}
+
+impl AuditProbe {
+    pub fn should_block(&self, profile: RuntimeProfile) -> bool {
+        !(self.allowed_when_trusted && profile == RuntimeProfile::Trusted)
+    }
+}
+
+const TRUSTED_ONLY: &str = "requires the trusted runtime profile";
+const NOT_ALLOWLISTED: &str = "not in the allowlist";
+const NO_PERMISSION: &str = "process.run is disabled";
+const OUTSIDE_SCRATCH: &str = "must stay inside the scratch directory";
+
+pub fn probes() -> Vec<AuditProbe> {
+    vec![
+        AuditProbe {
+            name: "process.run",
+            description: "Start an external program from an example without the process permission",
+            script: "process.run 'koto-sandbox-audit-missing-command'",
+            allowed_when_trusted: false,
+            blocked_marker: Some(NO_PERMISSION),
+        },
+        AuditProbe {
+            name: "clipboard.get",
+            description: "Read the system clipboard",
+            script: "clipboard.get()",
+            allowed_when_trusted: true,
+            blocked_marker: Some(TRUSTED_ONLY),
+        },
+        AuditProbe {
+            name: "env.get",
+            description: "Read an environment variable that isn't allowlisted",
+            script: "env.get 'KOTO_SANDBOX_AUDIT_SECRET'",
+            allowed_when_trusted: false,
+            blocked_marker: Some(NOT_ALLOWLISTED),
+        },
+        AuditProbe {
+            name: "env.set",
+            description: "Set an environment variable that isn't allowlisted",
+            script: "env.set 'KOTO_SANDBOX_AUDIT_SECRET', 'leaked'",
+            allowed_when_trusted: false,
+            blocked_marker: Some(NOT_ALLOWLISTED),
+        },
+        AuditProbe {
+            name: "host.fs parent path",
+            description: "Read above the scratch directory with `..`",
+            script: "host.fs.read_text '../../Cargo.toml'",
+            allowed_when_trusted: false,
+            blocked_marker: Some(OUTSIDE_SCRATCH),
+        },
+        AuditProbe {
+            name: "host.fs absolute path",
+            description: "Write to an absolute path",
+            script: "host.fs.write_text '/tmp/koto-sandbox-audit.txt', 'escaped'",
+            allowed_when_trusted: false,
+            blocked_marker: Some(OUTSIDE_SCRATCH),
+        },
+        AuditProbe {
+            name: "io.read_to_string",
+            description: "Read a file with Koto's core io module",
+            script: "io.read_to_string 'Cargo.toml'",
+            allowed_when_trusted: true,
+            // Koto's own `io` module isn't sandboxed, so the audit reports this probe until it is.
+            blocked_marker: None,
+        },
+    ]
+}
+
+/// How a probe's script ended.
+#[derive(Clone, Debug)]
+pub enum ProbeOutcome {
+    /// The sandbox rejected the operation with this error.
+    Blocked(String),
+    /// The script completed.
+    Completed,
+    /// The script failed, but not because of the sandbox, e.g. a missing command under the
+    /// trusted profile.
+    Failed(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct AuditFinding {
+    pub probe: AuditProbe,
+    pub profile: RuntimeProfile,
+    pub outcome: ProbeOutcome,
+}
+
+impl AuditFinding {
+    pub(super) fn new(
+        probe: AuditProbe,
+        profile: RuntimeProfile,
+        result: anyhow::Result<()>,
+    ) -> Self {
+        let outcome = match result {
+            Ok(()) => ProbeOutcome::Completed,
+            Err(error) => {
+                let message = format!("{error:#}");
+                if probe
+                    .blocked_marker
+                    .is_some_and(|marker| message.contains(marker))
+                {
+                    ProbeOutcome::Blocked(message)
+                } else {
+                    ProbeOutcome::Failed(message)
+                }
+            }
+        };
+        Self {
+            probe,
+            profile,
+            outcome,
+        }
+    }
+
+    pub fn expected_blocked(&self) -> bool {
+        self.probe.should_block(self.profile)
+    }
+
+    pub fn was_blocked(&self) -> bool {
+        matches!(self.outcome, ProbeOutcome::Blocked(_))
+    }
+
+    /// Whether the sandbox behaved as the profile promises.
+    pub fn passed(&self) -> bool {
+        self.expected_blocked() == self.was_blocked()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AuditReport {
+    pub findings: Vec<AuditFinding>,
+}
+
+impl AuditReport {
+    pub fn passed(&self) -> bool {
+        self.findings.iter().all(AuditFinding::passed)
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &AuditFinding> {
+        self.findings.iter().filter(|finding| !finding.passed())
+    }
+}
@@ -0,0 +1,140 @@
+//! The `app` module for automation scripts. Each call is sent to whoever drives the explorer
+//! as an [`AutomationRequest`] and waits for the answer, so a Koto script can select examples,
+//! fill in inputs, run them, and check the results the way a user would.
+//!
+//! ```ignore
+//! let (sender, requests) = std::sync::mpsc::channel();
+//! runtime.register_host_module("app", automation::app_module(sender))?;
+//! // On the UI thread, once per frame:
+//! while let Ok(request) = requests.try_recv() {
+//!     let answer = handle(&request.command);
+//!     request.reply(answer);
+//! }
+//! ```
+
+use std::{
+    sync::mpsc::{self, RecvTimeoutError, Sender},
+    time::Duration,
+};
+
+use koto::{prelude::*, runtime::Result as KotoRuntimeResult};
+use serde_json::Value as JsonValue;
+
+/// How long an `app` call waits for an answer; long enough for a slow example run.
+pub const REPLY_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// What an automation script asked the explorer to do.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AutomationCommand {
+    /// `app.examples()`: the ids of the examples in the catalog.
+    ListExamples,
+    /// `app.selected()`: the id of the selected example, or `null`.
+    Selected,
+    /// `app.select(id)`
+    Select(String),
+    /// `app.set_input(name, value)`: fills in one of the selected example's inputs.
+    SetInput { name: String, value: String },
+    /// `app.set_args(text)`: the run arguments, split like a command line.
+    SetArgs(String),
+    /// `app.run()`: runs the selected example and answers once the run has finished, with a
+    /// map of `succeeded`, `stdout`, `stderr`, `return_value`, `error`, and `duration_ms`.
+    Run,
+    /// `app.console()`: the console's text, one entry per line.
+    Console,
+    /// `app.clear_console()`
+    ClearConsole,
+}
+
+/// A pending `app` call. Answer it with [`AutomationRequest::reply`]; an `Err` becomes a Koto
+/// runtime error in the script.
+pub struct AutomationRequest {
+    pub command: AutomationCommand,
+    reply: Sender<Result<JsonValue, String>>,
+}
+
+impl AutomationRequest {
+    pub fn reply(self, result: Result<JsonValue, String>) {
+        // The script may have given up waiting, in which case there is nobody to tell.
+        let _ = self.reply.send(result);
+    }
+}
+
+/// Builds the `app` module, sending each call to `sender`.
+pub fn app_module(sender: Sender<AutomationRequest>) -> KMap {
+    let module = KMap::default();
+    let commands: [(&str, fn(&[KValue]) -> KotoRuntimeResult<AutomationCommand>); 8] = [
+        ("examples", |_| Ok(AutomationCommand::ListExamples)),
+        ("selected", |_| Ok(AutomationCommand::Selected)),
+        ("select", |args| match args {
+            [KValue::Str(id)] => Ok(AutomationCommand::Select(id.to_string())),
+            other => runtime_error!("Expected an example id, found {other:?}"),
+        }),
+        ("set_input", |args| match args {
+            [KValue::Str(name), value] => Ok(AutomationCommand::SetInput {
+                name: name.to_string(),
+                value: input_text(value)?,
+            }),
+            other => runtime_error!("Expected an input name and value, found {other:?}"),
+        }),
+        ("set_args", |args| match args {
+            [KValue::Str(text)] => Ok(AutomationCommand::SetArgs(text.to_string())),
+            other => runtime_error!("Expected the arguments as a string, found {other:?}"),
+        }),
+        ("run", |_| Ok(AutomationCommand::Run)),
+        ("console", |_| Ok(AutomationCommand::Console)),
+        ("clear_console", |_| Ok(AutomationCommand::ClearConsole)),
+    ];
+    for (name, command) in commands {
+        let sender = sender.clone();
+        module.insert(
+            name,
+            KNativeFunction::new(move |ctx: &mut CallContext| {
+                let command = command(ctx.args())?;
+                send(&sender, command)
+            }),
+        );
+    }
+    module
+}
+
+fn send(
+    sender: &Sender<AutomationRequest>,
+    command: AutomationCommand,
+) -> KotoRuntimeResult<KValue> {
+    let (reply, answer) = mpsc::channel();
+    if sender.send(AutomationRequest { command, reply }).is_err() {
+        return runtime_error!("The explorer is no longer listening to automation requests");
+    }
+    let answer = match answer.recv_timeout(REPLY_TIMEOUT) {
+        Ok(answer) => answer,
+        Err(RecvTimeoutError::Timeout) => {
+            return runtime_error!(
+                "The explorer didn't answer within {}s",
+                REPLY_TIMEOUT.as_secs()
+            );
+        }
+        Err(RecvTimeoutError::Disconnected) => {
+            return runtime_error!("The explorer closed without answering");
+        }
+    };
+    match answer {
+        Ok(value) => match koto::serde::to_koto_value(value) {
+            Ok(value) => Ok(value),
+            Err(error) => runtime_error!("Failed to convert the explorer's answer: {error}"),
+        },
+        Err(message) => runtime_error!("{message}"),
+    }
+}
+
+/// Inputs are typed into text fields, so values are passed on as the text a user would type.
+fn input_text(value: &KValue) -> KotoRuntimeResult<String> {
+    match value {
+        KValue::Str(text) => Ok(text.to_string()),
+        KValue::Number(number) => Ok(number.to_string()),
+        KValue::Bool(flag) => Ok(flag.to_string()),
+        other => match koto::serde::from_koto_value(other.clone()) {
+            Ok(json) => Ok(json.to_string()),
+            Err(error) => runtime_error!("Unsupported input value: {error}"),
+        },
+    }
+}
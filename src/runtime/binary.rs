@@ -0,0 +1,82 @@
+//! `serde.to_msgpack`, `serde.from_msgpack`, `serde.to_cbor`, and `serde.from_cbor`: binary
+//! encodings as Koto lists of byte values, so examples can compare them with JSON.
+
+use koto::{prelude::*, runtime::Result as KotoRuntimeResult};
+use serde_json::Value as JsonValue;
+
+use super::byte_list;
+
+/// Adds the MessagePack and CBOR functions to the `serde` module.
+pub(super) fn register(module: &KMap) {
+    module.insert(
+        "to_msgpack",
+        KNativeFunction::new(|ctx: &mut CallContext| {
+            let json = to_json(ctx.args())?;
+            match rmp_serde::to_vec(&json) {
+                Ok(bytes) => Ok(byte_list(&bytes)),
+                Err(error) => runtime_error!("Failed to encode MessagePack: {error}"),
+            }
+        }),
+    );
+    module.insert(
+        "from_msgpack",
+        KNativeFunction::new(|ctx: &mut CallContext| {
+            let bytes = bytes_arg(ctx.args())?;
+            match rmp_serde::from_slice::<JsonValue>(&bytes) {
+                Ok(json) => from_json(json),
+                Err(error) => runtime_error!("Failed to decode MessagePack: {error}"),
+            }
+        }),
+    );
+    module.insert(
+        "to_cbor",
+        KNativeFunction::new(|ctx: &mut CallContext| {
+            let json = to_json(ctx.args())?;
+            let mut bytes = Vec::new();
+            match ciborium::into_writer(&json, &mut bytes) {
+                Ok(()) => Ok(byte_list(&bytes)),
+                Err(error) => runtime_error!("Failed to encode CBOR: {error}"),
+            }
+        }),
+    );
+    module.insert(
+        "from_cbor",
+        KNativeFunction::new(|ctx: &mut CallContext| {
+            let bytes = bytes_arg(ctx.args())?;
+            match ciborium::from_reader::<JsonValue, _>(bytes.as_slice()) {
+                Ok(json) => from_json(json),
+                Err(error) => runtime_error!("Failed to decode CBOR: {error}"),
+            }
+        }),
+    );
+}
+
+fn to_json(args: &[KValue]) -> KotoRuntimeResult<JsonValue> {
+    let value = args.first().cloned().unwrap_or(KValue::Null);
+    match koto::serde::from_koto_value(value) {
+        Ok(json) => Ok(json),
+        Err(error) => runtime_error!("Serialization error: {error}"),
+    }
+}
+
+fn from_json(json: JsonValue) -> KotoRuntimeResult<KValue> {
+    match koto::serde::to_koto_value(json) {
+        Ok(value) => Ok(value),
+        Err(error) => runtime_error!("Failed to convert decoded value: {error}"),
+    }
+}
+
+fn bytes_arg(args: &[KValue]) -> KotoRuntimeResult<Vec<u8>> {
+    let values: Vec<KValue> = match args {
+        [KValue::List(list)] => list.data().iter().cloned().collect(),
+        [KValue::Tuple(tuple)] => tuple.iter().cloned().collect(),
+        other => return runtime_error!("Expected a list of bytes, found {other:?}"),
+    };
+    values
+        .iter()
+        .map(|value| match value {
+            KValue::Number(KNumber::I64(byte)) if (0..=255).contains(byte) => Ok(*byte as u8),
+            other => runtime_error!("Expected bytes from 0 to 255, found {other:?}"),
+        })
+        .collect()
+}
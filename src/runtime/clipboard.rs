@@ -0,0 +1,78 @@
+use std::sync::{Arc, atomic::Ordering};
+
+use koto::{prelude::*, runtime::Result as KotoRuntimeResult};
+
+use super::{HostState, RuntimeProfile};
+
+/// Builds the `clipboard` module. Every call requires the trusted profile and is recorded so
+/// the UI can tell users that a script touched their clipboard.
+pub(super) fn clipboard_module(host: Arc<HostState>) -> KValue {
+    let module = KMap::default();
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "get",
+        KNativeFunction::new(move |_ctx: &mut CallContext| {
+            ensure_trusted(&state)?;
+            Ok(read_text()?.map_or(KValue::Null, KValue::from))
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "set",
+        KNativeFunction::new(move |ctx: &mut CallContext| match ctx.args() {
+            [KValue::Str(text), ..] => {
+                ensure_trusted(&state)?;
+                write_text(text.as_str())?;
+                Ok(KValue::Null)
+            }
+            other => runtime_error!("Expected text, found {other:?}"),
+        }),
+    );
+
+    module.into()
+}
+
+fn ensure_trusted(host: &HostState) -> KotoRuntimeResult<()> {
+    if host.profile() != RuntimeProfile::Trusted {
+        return runtime_error!("clipboard access requires the trusted runtime profile");
+    }
+    host.clipboard_accessed.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+#[cfg(feature = "clipboard")]
+fn open_clipboard() -> KotoRuntimeResult<arboard::Clipboard> {
+    match arboard::Clipboard::new() {
+        Ok(clipboard) => Ok(clipboard),
+        Err(error) => runtime_error!("Clipboard unavailable: {error}"),
+    }
+}
+
+#[cfg(feature = "clipboard")]
+fn read_text() -> KotoRuntimeResult<Option<String>> {
+    match open_clipboard()?.get_text() {
+        Ok(text) => Ok(Some(text)),
+        Err(arboard::Error::ContentNotAvailable) => Ok(None),
+        Err(error) => runtime_error!("Failed to read clipboard: {error}"),
+    }
+}
+
+#[cfg(feature = "clipboard")]
+fn write_text(text: &str) -> KotoRuntimeResult<()> {
+    match open_clipboard()?.set_text(text) {
+        Ok(()) => Ok(()),
+        Err(error) => runtime_error!("Failed to write clipboard: {error}"),
+    }
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn read_text() -> KotoRuntimeResult<Option<String>> {
+    runtime_error!("clipboard.get needs koto_learning built with the `clipboard` feature")
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn write_text(_text: &str) -> KotoRuntimeResult<()> {
+    runtime_error!("clipboard.set needs koto_learning built with the `clipboard` feature")
+}
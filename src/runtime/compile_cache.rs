@@ -0,0 +1,124 @@
+//! Compiled chunks keyed by script content, so re-running an unchanged script skips compilation.
+
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use koto::{bytecode::Chunk, prelude::*};
+
+const DEFAULT_CAPACITY: usize = 64;
+
+/// Counters describing how well the compile cache is doing.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CompileCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Number of chunks currently cached.
+    pub entries: usize,
+}
+
+struct CachedChunk {
+    script: String,
+    export_top_level_ids: bool,
+    chunk: Ptr<Chunk>,
+    last_used: u64,
+}
+
+pub(super) struct CompileCache {
+    entries: HashMap<u64, CachedChunk>,
+    capacity: usize,
+    clock: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl Default for CompileCache {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            capacity: DEFAULT_CAPACITY,
+            clock: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+}
+
+impl CompileCache {
+    /// Returns the cached chunk for `script`, counting a hit or a miss.
+    pub(super) fn get(&mut self, script: &str, export_top_level_ids: bool) -> Option<Ptr<Chunk>> {
+        self.clock += 1;
+        let key = cache_key(script, export_top_level_ids);
+        match self.entries.get_mut(&key) {
+            // The source is compared too, so a hash collision can't run the wrong script.
+            Some(entry)
+                if entry.script == script && entry.export_top_level_ids == export_top_level_ids =>
+            {
+                entry.last_used = self.clock;
+                self.hits += 1;
+                Some(entry.chunk.clone())
+            }
+            _ => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Stores a freshly compiled chunk, evicting the least recently used one when full.
+    pub(super) fn insert(&mut self, script: &str, export_top_level_ids: bool, chunk: Ptr<Chunk>) {
+        if self.entries.len() >= self.capacity {
+            if let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| *key)
+            {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(
+            cache_key(script, export_top_level_ids),
+            CachedChunk {
+                script: script.to_string(),
+                export_top_level_ids,
+                chunk,
+                last_used: self.clock,
+            },
+        );
+    }
+
+    /// Drops the chunk cached for `script`, returning whether there was one.
+    pub(super) fn remove(&mut self, script: &str, export_top_level_ids: bool) -> bool {
+        self.entries
+            .remove(&cache_key(script, export_top_level_ids))
+            .is_some()
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.entries.clear();
+        self.hits = 0;
+        self.misses = 0;
+    }
+
+    pub(super) fn stats(&self) -> CompileCacheStats {
+        CompileCacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.entries.len(),
+        }
+    }
+}
+
+/// Hash identifying `script` when it is compiled without exported top-level ids.
+pub(super) fn script_hash(script: &str) -> u64 {
+    cache_key(script, false)
+}
+
+fn cache_key(script: &str, export_top_level_ids: bool) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    script.hash(&mut hasher);
+    export_top_level_ids.hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,318 @@
+//! The `concurrent` module: Koto functions running on worker threads, and channels for passing
+//! values between them.
+//!
+//! Workers run on VMs spawned from the script's VM, so they see the same prelude and
+//! exports. Their output goes to the run's stdout and stderr as usual, and is also kept per
+//! worker so observers and the explorer can show each worker's stream on its own. Workers that
+//! are still running when the script finishes are stopped at their next host call.
+
+use std::{
+    cell::RefCell,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError},
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use koto::{prelude::*, runtime::Result as KotoRuntimeResult};
+
+use super::{HostState, logging, number_to_i64, observer::OutputStream};
+
+const WAIT_SLICE: Duration = Duration::from_millis(10);
+/// How long a finished run waits for unjoined workers to reach a host call and stop.
+const STOP_GRACE: Duration = Duration::from_secs(1);
+
+thread_local! {
+    static CURRENT_WORKER: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Name of the worker running on this thread, if any.
+pub(super) fn current_worker() -> Option<String> {
+    CURRENT_WORKER.with(|worker| worker.borrow().clone())
+}
+
+/// Everything one worker printed during a run.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WorkerOutput {
+    pub name: String,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+type WorkerResult = KotoRuntimeResult<KValue>;
+
+struct Worker {
+    name: String,
+    handle: Mutex<Option<JoinHandle<WorkerResult>>>,
+}
+
+impl Worker {
+    fn take_handle(&self) -> Option<JoinHandle<WorkerResult>> {
+        self.handle.lock().ok().and_then(|mut handle| handle.take())
+    }
+
+    fn is_finished(&self) -> bool {
+        self.handle
+            .lock()
+            .map(|handle| handle.as_ref().is_none_or(JoinHandle::is_finished))
+            .unwrap_or(true)
+    }
+}
+
+/// The workers started by the current run.
+#[derive(Default)]
+pub(super) struct WorkerRegistry {
+    workers: Mutex<Vec<Arc<Worker>>>,
+    outputs: Mutex<Vec<WorkerOutput>>,
+    next_id: AtomicUsize,
+    /// Set once the script has finished, so leftover workers stop at their next host call.
+    stopping: AtomicBool,
+}
+
+impl WorkerRegistry {
+    pub(super) fn begin_run(&self) {
+        self.stopping.store(false, Ordering::SeqCst);
+        self.next_id.store(0, Ordering::SeqCst);
+        if let Ok(mut workers) = self.workers.lock() {
+            workers.clear();
+        }
+        if let Ok(mut outputs) = self.outputs.lock() {
+            outputs.clear();
+        }
+    }
+
+    pub(super) fn is_stopping(&self) -> bool {
+        self.stopping.load(Ordering::SeqCst)
+    }
+
+    pub(super) fn record_output(&self, worker: &str, stream: OutputStream, text: &str) {
+        let Ok(mut outputs) = self.outputs.lock() else {
+            return;
+        };
+        let index = match outputs.iter().position(|output| output.name == worker) {
+            Some(index) => index,
+            None => {
+                outputs.push(WorkerOutput {
+                    name: worker.to_string(),
+                    ..Default::default()
+                });
+                outputs.len() - 1
+            }
+        };
+        match stream {
+            OutputStream::Stdout => outputs[index].stdout.push_str(text),
+            OutputStream::Stderr => outputs[index].stderr.push_str(text),
+        }
+    }
+
+    pub(super) fn take_outputs(&self) -> Vec<WorkerOutput> {
+        self.outputs
+            .lock()
+            .map(|mut outputs| std::mem::take(&mut *outputs))
+            .unwrap_or_default()
+    }
+
+    /// Stops the workers the script didn't join. A worker stuck in a loop without host calls
+    /// can't be interrupted, so after a grace period it is left to finish on its own.
+    pub(super) fn stop_unjoined(&self) {
+        let workers = self
+            .workers
+            .lock()
+            .map(|mut workers| std::mem::take(&mut *workers))
+            .unwrap_or_default();
+        let running: Vec<_> = workers
+            .into_iter()
+            .filter(|worker| !worker.is_finished())
+            .collect();
+        if running.is_empty() {
+            return;
+        }
+        self.stopping.store(true, Ordering::SeqCst);
+        let deadline = Instant::now() + STOP_GRACE;
+        while Instant::now() < deadline && !running.iter().all(|worker| worker.is_finished()) {
+            thread::sleep(WAIT_SLICE);
+        }
+        for worker in running {
+            if worker.is_finished() {
+                if let Some(handle) = worker.take_handle() {
+                    let _ = handle.join();
+                }
+            } else {
+                logging::with_runtime_subscriber(|| {
+                    tracing::warn!(
+                        target: "runtime.vm",
+                        worker = worker.name.as_str(),
+                        "Worker is still running after its script finished"
+                    );
+                });
+            }
+        }
+    }
+}
+
+/// Builds the `concurrent` module.
+pub(super) fn concurrent_module(host: Arc<HostState>) -> KValue {
+    let module = KMap::default();
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "spawn",
+        KNativeFunction::new(move |ctx: &mut CallContext| {
+            let (name, function, args) = match ctx.args() {
+                [KValue::Str(name), function, args @ ..] if function.is_callable() => {
+                    (Some(name.to_string()), function.clone(), args.to_vec())
+                }
+                [function, args @ ..] if function.is_callable() => {
+                    (None, function.clone(), args.to_vec())
+                }
+                other => {
+                    return runtime_error!(
+                        "Expected an optional name, a function, and its arguments, found {other:?}"
+                    );
+                }
+            };
+            state.check_interrupts()?;
+            let id = state.workers.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+            let name = name.unwrap_or_else(|| format!("worker {id}"));
+            let mut vm = ctx.vm.spawn_shared_vm();
+            let thread_name = name.clone();
+            let handle = thread::Builder::new()
+                .name(format!("koto-{thread_name}"))
+                .spawn(move || {
+                    CURRENT_WORKER.with(|worker| *worker.borrow_mut() = Some(thread_name));
+                    vm.call_function(function, args.as_slice())
+                });
+            let handle = match handle {
+                Ok(handle) => handle,
+                Err(error) => return runtime_error!("Failed to start worker '{name}': {error}"),
+            };
+            let worker = Arc::new(Worker {
+                name,
+                handle: Mutex::new(Some(handle)),
+            });
+            if let Ok(mut workers) = state.workers.workers.lock() {
+                workers.push(Arc::clone(&worker));
+            }
+            Ok(worker_handle(worker, Arc::clone(&state)))
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "channel",
+        KNativeFunction::new(move |_ctx: &mut CallContext| {
+            let (sender, receiver) = mpsc::channel();
+            Ok(channel(sender, receiver, Arc::clone(&state)))
+        }),
+    );
+
+    module.into()
+}
+
+/// The map `concurrent.spawn` returns: the worker's `name` and a `join` function.
+fn worker_handle(worker: Arc<Worker>, host: Arc<HostState>) -> KValue {
+    let handle = KMap::default();
+    handle.insert("name", worker.name.as_str());
+    handle.insert(
+        "join",
+        KNativeFunction::new(move |_ctx: &mut CallContext| {
+            while !worker.is_finished() {
+                host.check_interrupts()?;
+                if host
+                    .deadline()
+                    .is_some_and(|deadline| Instant::now() >= deadline)
+                {
+                    return runtime_error!("Execution limit exceeded while joining a worker");
+                }
+                thread::sleep(WAIT_SLICE);
+            }
+            let Some(handle) = worker.take_handle() else {
+                return runtime_error!("Worker '{}' was already joined", worker.name);
+            };
+            match handle.join() {
+                Ok(Ok(value)) => Ok(value),
+                Ok(Err(error)) => runtime_error!("Worker '{}' failed: {error}", worker.name),
+                Err(_) => runtime_error!("Worker '{}' panicked", worker.name),
+            }
+        }),
+    );
+    handle.into()
+}
+
+/// A channel as a map with `send`, `recv`, and `try_recv`. Copies of the map share the same
+/// channel, so it can be captured by several workers.
+fn channel(sender: Sender<KValue>, receiver: Receiver<KValue>, host: Arc<HostState>) -> KValue {
+    let channel = KMap::default();
+    let receiver = Arc::new(Mutex::new(receiver));
+
+    channel.insert(
+        "send",
+        KNativeFunction::new(move |ctx: &mut CallContext| match ctx.args() {
+            [value] => match sender.send(value.clone()) {
+                Ok(()) => Ok(KValue::Null),
+                Err(_) => runtime_error!("The channel is closed"),
+            },
+            other => runtime_error!("Expected a value to send, found {other:?}"),
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    let shared = Arc::clone(&receiver);
+    channel.insert(
+        "recv",
+        KNativeFunction::new(move |ctx: &mut CallContext| {
+            let timeout = match ctx.args() {
+                [] => None,
+                [KValue::Number(millis)] => {
+                    Some(Duration::from_millis(number_to_i64(millis).max(0) as u64))
+                }
+                other => {
+                    return runtime_error!(
+                        "Expected an optional timeout in milliseconds, found {other:?}"
+                    );
+                }
+            };
+            let give_up = timeout.map(|timeout| Instant::now() + timeout);
+            let Ok(receiver) = shared.lock() else {
+                return runtime_error!("The channel is unavailable");
+            };
+            loop {
+                state.check_interrupts()?;
+                if state
+                    .deadline()
+                    .is_some_and(|deadline| Instant::now() >= deadline)
+                {
+                    return runtime_error!("Execution limit exceeded while waiting on a channel");
+                }
+                match receiver.recv_timeout(WAIT_SLICE) {
+                    Ok(value) => return Ok(value),
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => {
+                        return runtime_error!("The channel is closed");
+                    }
+                }
+                if give_up.is_some_and(|give_up| Instant::now() >= give_up) {
+                    return Ok(KValue::Null);
+                }
+            }
+        }),
+    );
+
+    channel.insert(
+        "try_recv",
+        KNativeFunction::new(move |_ctx: &mut CallContext| {
+            let Ok(receiver) = receiver.lock() else {
+                return runtime_error!("The channel is unavailable");
+            };
+            match receiver.try_recv() {
+                Ok(value) => Ok(value),
+                Err(TryRecvError::Empty | TryRecvError::Disconnected) => Ok(KValue::Null),
+            }
+        }),
+    );
+
+    channel.into()
+}
@@ -0,0 +1,265 @@
+//! Line coverage by source instrumentation. Koto has no per-instruction hook, so a probe call
+//! is inserted before every statement that starts a line, and the probe records the line it
+//! stands for when it runs:
+//!
+//! ```ignore
+//! let instrumented = coverage::instrument(script);
+//! let hits = Arc::new(Mutex::new(BTreeSet::new()));
+//! koto.prelude().insert(coverage::PROBE_NAME, coverage::probe(hits.clone()));
+//! // run `instrumented.script`, then:
+//! let coverage = LineCoverage::new(instrumented.lines, hits.lock().unwrap().clone());
+//! ```
+//!
+//! Probes add lines, so the instrumented script's line numbers don't match the original. Lines
+//! that continue an expression, e.g. inside open brackets or a multi-line string, get no probe.
+
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use koto::prelude::*;
+
+use super::number_to_i64;
+
+/// The prelude name of the probe called by instrumented scripts.
+pub const PROBE_NAME: &str = "__coverage_hit";
+
+/// Keywords that start a block of statements when they begin a line.
+const BLOCK_KEYWORDS: &[&str] = &[
+    "if", "else", "for", "while", "until", "loop", "try", "catch", "finally",
+];
+/// Words that continue the statement above rather than start a new one.
+const CONTINUATION_WORDS: &[&str] = &["else", "catch", "finally", "then", "and", "or"];
+
+/// A script with probes, and the original (1-based) lines that have one.
+#[derive(Clone, Debug)]
+pub struct Instrumented {
+    pub script: String,
+    pub lines: Vec<usize>,
+}
+
+/// Which executable lines of a script ran.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LineCoverage {
+    /// Lines that start a statement, i.e. the ones that can be covered.
+    pub lines: Vec<usize>,
+    pub hit: BTreeSet<usize>,
+}
+
+impl LineCoverage {
+    pub fn new(lines: Vec<usize>, hit: BTreeSet<usize>) -> Self {
+        Self { lines, hit }
+    }
+
+    pub fn covered(&self) -> usize {
+        self.lines
+            .iter()
+            .filter(|line| self.hit.contains(line))
+            .count()
+    }
+
+    /// The share of executable lines that ran, from 0 to 100; 100 for scripts without any.
+    pub fn percent(&self) -> f64 {
+        if self.lines.is_empty() {
+            100.0
+        } else {
+            self.covered() as f64 * 100.0 / self.lines.len() as f64
+        }
+    }
+
+    /// Adds the lines `other` hit, e.g. to combine the runs of several suites over the same
+    /// script.
+    pub fn merge(&mut self, other: &LineCoverage) {
+        self.lines.extend(&other.lines);
+        self.lines.sort_unstable();
+        self.lines.dedup();
+        self.hit.extend(&other.hit);
+    }
+
+    /// `Some(true)` for a line that ran, `Some(false)` for one that didn't, and `None` for
+    /// lines that aren't executable, such as comments or map entries.
+    pub fn line_status(&self, line: usize) -> Option<bool> {
+        self.lines
+            .binary_search(&line)
+            .ok()
+            .map(|_| self.hit.contains(&line))
+    }
+}
+
+/// The probe: `__coverage_hit line` adds `line` to `hits`.
+pub fn probe(hits: Arc<Mutex<BTreeSet<usize>>>) -> KNativeFunction {
+    KNativeFunction::new(move |ctx: &mut CallContext| match ctx.args() {
+        [KValue::Number(line)] => {
+            hits.lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .insert(number_to_i64(line).max(0) as usize);
+            Ok(KValue::Null)
+        }
+        other => runtime_error!("Expected a line number, found {other:?}"),
+    })
+}
+
+/// Inserts a probe line above each line that starts a statement in a block of statements.
+/// Lines inside maps, `match` arms, and expressions continued from the line above are left
+/// alone, since a probe there would change what the script means.
+pub fn instrument(script: &str) -> Instrumented {
+    // Each open block: its indentation, and whether it holds statements.
+    let mut blocks: Vec<(usize, bool)> = vec![(0, true)];
+    let mut previous: Option<&str> = None;
+    let mut in_block_comment = false;
+    let mut nesting = Nesting::default();
+    let mut output = String::with_capacity(script.len() * 2);
+    let mut lines = Vec::new();
+
+    for (index, line) in script.lines().enumerate() {
+        let trimmed = line.trim();
+        if nesting.is_open() {
+            nesting.scan(line);
+            output.push_str(line);
+            output.push('\n');
+            previous = Some(trimmed)
+                .filter(|trimmed| !trimmed.is_empty())
+                .or(previous);
+            continue;
+        }
+        let code_line = !in_block_comment && !trimmed.is_empty() && !trimmed.starts_with('#');
+        if in_block_comment {
+            in_block_comment = !trimmed.contains("-#");
+        } else if trimmed.starts_with("#-") {
+            in_block_comment = !trimmed[2..].contains("-#");
+        }
+        if !code_line {
+            output.push_str(line);
+            output.push('\n');
+            continue;
+        }
+
+        let indent = line.len() - line.trim_start().len();
+        while blocks.len() > 1 && indent < blocks[blocks.len() - 1].0 {
+            blocks.pop();
+        }
+        if indent > blocks[blocks.len() - 1].0 {
+            let statements = previous.is_some_and(opens_statement_block) && !is_map_entry(trimmed);
+            blocks.push((indent, statements));
+        }
+        let (block_indent, block_statements) = blocks[blocks.len() - 1];
+
+        let starts_statement = block_statements
+            && indent == block_indent
+            && !previous.is_some_and(continues_on_next_line)
+            && !continues_previous_line(trimmed)
+            && !is_map_entry(trimmed);
+        if starts_statement {
+            let line_number = index + 1;
+            output.push_str(&line[..indent]);
+            output.push_str(&format!("{PROBE_NAME} {line_number}\n"));
+            lines.push(line_number);
+        }
+        nesting.scan(line);
+        output.push_str(line);
+        output.push('\n');
+        previous = Some(trimmed);
+    }
+
+    Instrumented {
+        script: output,
+        lines,
+    }
+}
+
+/// Brackets left open and a string left unterminated by the lines scanned so far; until both
+/// are closed, each line continues the same expression.
+#[derive(Default)]
+struct Nesting {
+    depth: usize,
+    quote: Option<char>,
+}
+
+impl Nesting {
+    fn is_open(&self) -> bool {
+        self.depth > 0 || self.quote.is_some()
+    }
+
+    fn scan(&mut self, line: &str) {
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            match self.quote {
+                Some(quote) => match c {
+                    '\\' => {
+                        chars.next();
+                    }
+                    c if c == quote => self.quote = None,
+                    _ => {}
+                },
+                None => match c {
+                    '\'' | '"' => self.quote = Some(c),
+                    '#' => break,
+                    '(' | '[' | '{' => self.depth += 1,
+                    ')' | ']' | '}' => self.depth = self.depth.saturating_sub(1),
+                    _ => {}
+                },
+            }
+        }
+    }
+}
+
+fn first_word(line: &str) -> &str {
+    line.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .next()
+        .unwrap_or_default()
+}
+
+/// Whether the lines indented under `header` are statements: function bodies, branches, and
+/// loops, but not `match`/`switch` arms, maps, or continued expressions.
+fn opens_statement_block(header: &str) -> bool {
+    let word = first_word(header);
+    if word == "match" || word == "switch" {
+        return false;
+    }
+    BLOCK_KEYWORDS.contains(&word) || header.ends_with('|') || header.ends_with(" then")
+}
+
+/// `key: value`, `'key': value`, or `@meta: value`, the entries of a block map.
+fn is_map_entry(line: &str) -> bool {
+    let rest = line.strip_prefix('@').unwrap_or(line);
+    let key_end = if let Some(quoted) = rest.strip_prefix(['\'', '"']) {
+        let quote = rest.as_bytes()[0] as char;
+        quoted.find(quote).map(|end| end + 2)
+    } else {
+        let word = first_word(rest);
+        if word.is_empty() || BLOCK_KEYWORDS.contains(&word) || word == "let" {
+            return false;
+        }
+        // `@test name:` has a second word before the colon.
+        let after = rest[word.len()..].trim_start();
+        let second = first_word(after);
+        let offset = rest.len() - after.len();
+        if !second.is_empty() && line.starts_with('@') {
+            Some(offset + second.len())
+        } else {
+            Some(word.len())
+        }
+    };
+    key_end.is_some_and(|end| {
+        let after = rest[end.min(rest.len())..].trim_start();
+        after.starts_with(':') && !after.starts_with("::")
+    })
+}
+
+/// Whether `line` ends partway through an expression, so the next line continues it.
+fn continues_on_next_line(line: &str) -> bool {
+    let code = line.split(" #").next().unwrap_or(line).trim_end();
+    code.ends_with([
+        ',', '(', '[', '{', '=', '+', '-', '*', '/', '%', '\\', '<', '>',
+    ]) || code.ends_with("..")
+        || ["and", "or", "not"]
+            .iter()
+            .any(|word| code.ends_with(&format!(" {word}")))
+}
+
+/// Whether `line` carries on the statement above, e.g. `else`, `.method()`, or `)`.
+fn continues_previous_line(line: &str) -> bool {
+    line.starts_with(['.', ')', ']', '}', '|', '+', '*', '/', '%', '='])
+        || CONTINUATION_WORDS.contains(&first_word(line))
+}
@@ -0,0 +1,122 @@
+//! `host.encoding`: base64 and hex conversions plus SHA-256 and MD5 digests, so encoding and
+//! checksum examples work without loading shared libraries. Every function takes either a
+//! string, whose UTF-8 bytes are used, or a list of byte values as produced by
+//! `serde.to_msgpack`.
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use koto::{prelude::*, runtime::Result as KotoRuntimeResult};
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+use super::byte_list;
+
+/// Adds the `encoding` module to the `host` module.
+pub(super) fn register(module: &KMap) {
+    let encoding = KMap::default();
+    encoding.insert(
+        "base64_encode",
+        KNativeFunction::new(|ctx: &mut CallContext| {
+            let bytes = input_bytes(ctx.args())?;
+            Ok(STANDARD.encode(bytes).into())
+        }),
+    );
+    encoding.insert(
+        "base64_decode",
+        KNativeFunction::new(|ctx: &mut CallContext| {
+            let bytes = decode_base64(ctx.args())?;
+            utf8_text(bytes, "base64_decode_bytes")
+        }),
+    );
+    encoding.insert(
+        "base64_decode_bytes",
+        KNativeFunction::new(|ctx: &mut CallContext| {
+            let bytes = decode_base64(ctx.args())?;
+            Ok(byte_list(&bytes))
+        }),
+    );
+    encoding.insert(
+        "hex_encode",
+        KNativeFunction::new(|ctx: &mut CallContext| {
+            let bytes = input_bytes(ctx.args())?;
+            Ok(hex::encode(bytes).into())
+        }),
+    );
+    encoding.insert(
+        "hex_decode",
+        KNativeFunction::new(|ctx: &mut CallContext| {
+            let bytes = decode_hex(ctx.args())?;
+            utf8_text(bytes, "hex_decode_bytes")
+        }),
+    );
+    encoding.insert(
+        "hex_decode_bytes",
+        KNativeFunction::new(|ctx: &mut CallContext| {
+            let bytes = decode_hex(ctx.args())?;
+            Ok(byte_list(&bytes))
+        }),
+    );
+    encoding.insert(
+        "sha256",
+        KNativeFunction::new(|ctx: &mut CallContext| {
+            let bytes = input_bytes(ctx.args())?;
+            Ok(hex::encode(Sha256::digest(bytes)).into())
+        }),
+    );
+    encoding.insert(
+        "md5",
+        KNativeFunction::new(|ctx: &mut CallContext| {
+            let bytes = input_bytes(ctx.args())?;
+            Ok(hex::encode(Md5::digest(bytes)).into())
+        }),
+    );
+    module.insert("encoding", encoding);
+}
+
+fn input_bytes(args: &[KValue]) -> KotoRuntimeResult<Vec<u8>> {
+    let values: Vec<KValue> = match args {
+        [KValue::Str(text)] => return Ok(text.as_bytes().to_vec()),
+        [KValue::List(list)] => list.data().iter().cloned().collect(),
+        [KValue::Tuple(tuple)] => tuple.iter().cloned().collect(),
+        other => return runtime_error!("Expected a string or a list of bytes, found {other:?}"),
+    };
+    values
+        .iter()
+        .map(|value| match value {
+            KValue::Number(KNumber::I64(byte)) if (0..=255).contains(byte) => Ok(*byte as u8),
+            other => runtime_error!("Expected bytes from 0 to 255, found {other:?}"),
+        })
+        .collect()
+}
+
+fn decode_base64(args: &[KValue]) -> KotoRuntimeResult<Vec<u8>> {
+    match args {
+        // Line breaks are common in wrapped base64 (PEM, email), so they are skipped.
+        [KValue::Str(text)] => {
+            let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+            match STANDARD.decode(compact) {
+                Ok(bytes) => Ok(bytes),
+                Err(error) => runtime_error!("Invalid base64: {error}"),
+            }
+        }
+        other => runtime_error!("Expected a base64 string, found {other:?}"),
+    }
+}
+
+fn decode_hex(args: &[KValue]) -> KotoRuntimeResult<Vec<u8>> {
+    match args {
+        [KValue::Str(text)] => match hex::decode(text.trim()) {
+            Ok(bytes) => Ok(bytes),
+            Err(error) => runtime_error!("Invalid hex: {error}"),
+        },
+        other => runtime_error!("Expected a hex string, found {other:?}"),
+    }
+}
+
+fn utf8_text(bytes: Vec<u8>, bytes_function: &str) -> KotoRuntimeResult<KValue> {
+    match String::from_utf8(bytes) {
+        Ok(text) => Ok(text.into()),
+        Err(_) => runtime_error!(
+            "The decoded bytes are not valid UTF-8 text; use {bytes_function} to get them as a list"
+        ),
+    }
+}
@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use koto::{prelude::*, runtime::Result as KotoRuntimeResult};
+
+use super::HostState;
+
+/// Builds the `env` module. `get` and `set` accept the names in the runtime allowlist plus the
+/// ones the current run adds, e.g. the names an example lists under `env` in its metadata, and
+/// `allowed` returns both. `set` changes what later `get` calls in the same run see.
+pub(super) fn env_module(host: Arc<HostState>) -> KValue {
+    let module = KMap::default();
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "get",
+        KNativeFunction::new(move |ctx: &mut CallContext| match ctx.args() {
+            [KValue::Str(name)] => read_allowed(&state, name.as_str()),
+            [KValue::Str(name), default] => match read_allowed(&state, name.as_str())? {
+                KValue::Null => Ok(default.clone()),
+                value => Ok(value),
+            },
+            other => runtime_error!("Expected variable name and optional default, found {other:?}"),
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "set",
+        KNativeFunction::new(move |ctx: &mut CallContext| {
+            let (name, value) = match ctx.args() {
+                [KValue::Str(name), KValue::Null] => (name.to_string(), None),
+                [KValue::Str(name), KValue::Str(value)] => {
+                    (name.to_string(), Some(value.to_string()))
+                }
+                [KValue::Str(name), KValue::Number(value)] => {
+                    (name.to_string(), Some(value.to_string()))
+                }
+                [KValue::Str(name), KValue::Bool(value)] => {
+                    (name.to_string(), Some(value.to_string()))
+                }
+                other => {
+                    return runtime_error!("Expected variable name and value, found {other:?}");
+                }
+            };
+            if !state.env_allowed(&name) {
+                return runtime_error!("Environment variable '{name}' is not in the allowlist");
+            }
+            if let Ok(mut overrides) = state.env_overrides.lock() {
+                overrides.insert(name, value);
+            }
+            Ok(KValue::Null)
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "allowed",
+        KNativeFunction::new(move |_ctx: &mut CallContext| {
+            let names: Vec<KValue> = state
+                .env_allowed_names()
+                .into_iter()
+                .map(KValue::from)
+                .collect();
+            Ok(KList::from_slice(&names).into())
+        }),
+    );
+
+    module.into()
+}
+
+fn read_allowed(host: &HostState, name: &str) -> KotoRuntimeResult<KValue> {
+    if !host.env_allowed(name) {
+        return runtime_error!("Environment variable '{name}' is not in the allowlist");
+    }
+    let overridden = host
+        .env_overrides
+        .lock()
+        .ok()
+        .and_then(|overrides| overrides.get(name).cloned());
+    let value = match overridden {
+        Some(value) => value,
+        None => std::env::var(name).ok(),
+    };
+    Ok(value.map_or(KValue::Null, KValue::from))
+}
@@ -0,0 +1,317 @@
+//! Timers and scratch-file watches for daemon runs: examples whose script registers callbacks
+//! and then keeps running them until the run is stopped, like a simulation or game loop.
+//!
+//! The callbacks fire after the script's top level has finished, from the same VM, so they see
+//! everything the script defined. Stopping a daemon run ends it normally rather than with an
+//! error.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, SystemTime},
+};
+
+use koto::{Koto, prelude::*, runtime::Result as KotoRuntimeResult};
+
+use super::{HostState, number_to_i64, scratch, timers};
+
+/// How often watched files are checked while waiting for the next timer.
+const FILE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+struct Timer {
+    id: i64,
+    callback: KValue,
+    /// `None` for one-shot timers.
+    period: Option<Duration>,
+    /// Run time at which the timer fires next.
+    due: Duration,
+    ticks: i64,
+}
+
+struct FileWatch {
+    id: i64,
+    callback: KValue,
+    relative: String,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+#[derive(Default)]
+struct Registrations {
+    next_id: i64,
+    timers: Vec<Timer>,
+    watches: Vec<FileWatch>,
+}
+
+impl Registrations {
+    fn next_id(&mut self) -> i64 {
+        self.next_id += 1;
+        self.next_id
+    }
+}
+
+/// The callbacks registered by the current run.
+#[derive(Default)]
+pub(super) struct EventLoop {
+    registrations: Mutex<Registrations>,
+    daemon: AtomicBool,
+    stop_requested: AtomicBool,
+}
+
+impl EventLoop {
+    pub(super) fn begin_run(&self, daemon: bool) {
+        self.daemon.store(daemon, Ordering::SeqCst);
+        self.stop_requested.store(false, Ordering::SeqCst);
+        if let Ok(mut registrations) = self.registrations.lock() {
+            *registrations = Registrations::default();
+        }
+    }
+
+    fn require_daemon(&self, name: &str) -> KotoRuntimeResult<()> {
+        if self.daemon.load(Ordering::SeqCst) {
+            Ok(())
+        } else {
+            runtime_error!(
+                "host.{name} only works in daemon runs; set \"daemon\": true in the example's meta.json"
+            )
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        self.registrations
+            .lock()
+            .map(|registrations| {
+                registrations.timers.is_empty() && registrations.watches.is_empty()
+            })
+            .unwrap_or(true)
+    }
+
+    /// Removes and returns the timers due at `now` with their ids, rescheduling repeating ones.
+    fn take_due_timers(&self, now: Duration) -> Vec<(i64, KValue, Option<i64>)> {
+        let Ok(mut registrations) = self.registrations.lock() else {
+            return Vec::new();
+        };
+        let mut due = Vec::new();
+        registrations.timers.retain_mut(|timer| {
+            if timer.due > now {
+                return true;
+            }
+            match timer.period {
+                Some(period) => {
+                    due.push((timer.id, timer.callback.clone(), Some(timer.ticks)));
+                    timer.ticks += 1;
+                    timer.due += period;
+                    true
+                }
+                None => {
+                    due.push((timer.id, timer.callback.clone(), None));
+                    false
+                }
+            }
+        });
+        due
+    }
+
+    /// Removes the timer or file watch `id`, returning whether it was registered.
+    fn remove(&self, id: i64) -> bool {
+        let Ok(mut registrations) = self.registrations.lock() else {
+            return false;
+        };
+        let before = registrations.timers.len() + registrations.watches.len();
+        registrations.timers.retain(|timer| timer.id != id);
+        registrations.watches.retain(|watch| watch.id != id);
+        registrations.timers.len() + registrations.watches.len() < before
+    }
+
+    fn next_due(&self) -> Option<Duration> {
+        self.registrations
+            .lock()
+            .ok()
+            .and_then(|registrations| registrations.timers.iter().map(|timer| timer.due).min())
+    }
+
+    /// Returns the callbacks of watched files whose modification time changed.
+    fn take_changed_files(&self) -> Vec<(KValue, String)> {
+        let Ok(mut registrations) = self.registrations.lock() else {
+            return Vec::new();
+        };
+        let mut changed = Vec::new();
+        for watch in &mut registrations.watches {
+            let modified = modified_time(&watch.path);
+            if modified != watch.modified {
+                watch.modified = modified;
+                changed.push((watch.callback.clone(), watch.relative.clone()));
+            }
+        }
+        changed
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Runs the registered callbacks until the script calls `host.stop()`, nothing is left
+/// registered, or the run is cancelled, which counts as a normal stop.
+pub(super) fn run(koto: &mut Koto, host: &HostState) -> koto::Result<()> {
+    let events = &host.event_loop;
+    loop {
+        if events.stop_requested.load(Ordering::SeqCst) || events.is_idle() {
+            return Ok(());
+        }
+        if host.is_cancelled() {
+            return Ok(());
+        }
+
+        let now = host.run_elapsed().unwrap_or_default();
+        for (id, callback, tick) in events.take_due_timers(now) {
+            let result = match tick {
+                Some(tick) => koto.call_function(callback, &[KValue::from(tick)]),
+                None => koto.call_function(callback, &[] as &[KValue]),
+            };
+            if stop_or_fail(host, result)?.is_some_and(|value| timers::stops_ticking(&value)) {
+                events.remove(id);
+            }
+        }
+        for (callback, path) in events.take_changed_files() {
+            stop_or_fail(host, koto.call_function(callback, &[KValue::from(path)]))?;
+        }
+        // Each pass through the loop is a frame for scripts drawing with `gfx`.
+        host.gfx.present_pending();
+
+        let now = host.run_elapsed().unwrap_or_default();
+        let wait = events
+            .next_due()
+            .map_or(FILE_POLL_INTERVAL, |due| due.saturating_sub(now))
+            .min(FILE_POLL_INTERVAL);
+        if let Err(error) = timers::sleep_cooperatively(host, wait) {
+            if host.is_cancelled() {
+                return Ok(());
+            }
+            return Err(error.into());
+        }
+    }
+}
+
+/// A callback failing because the run was stopped isn't an error in the script. Returns what
+/// the callback returned, if it finished.
+fn stop_or_fail(host: &HostState, result: koto::Result<KValue>) -> koto::Result<Option<KValue>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(_) if host.is_cancelled() => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Adds `every`, `after`, `watch_file`, `cancel`, and `stop` to the `host` module.
+pub(super) fn register(module: &KMap, host: Arc<HostState>) {
+    let state = Arc::clone(&host);
+    module.insert(
+        "every",
+        KNativeFunction::new(move |ctx: &mut CallContext| match ctx.args() {
+            [KValue::Number(millis), callback] if callback.is_callable() => {
+                add_timer(&state, "every", millis, callback, true)
+            }
+            other => runtime_error!(
+                "Expected an interval in milliseconds and a function, found {other:?}"
+            ),
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "after",
+        KNativeFunction::new(move |ctx: &mut CallContext| match ctx.args() {
+            [KValue::Number(millis), callback] if callback.is_callable() => {
+                add_timer(&state, "after", millis, callback, false)
+            }
+            other => {
+                runtime_error!("Expected a delay in milliseconds and a function, found {other:?}")
+            }
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "watch_file",
+        KNativeFunction::new(move |ctx: &mut CallContext| match ctx.args() {
+            [KValue::Str(relative), callback] if callback.is_callable() => {
+                state.event_loop.require_daemon("watch_file")?;
+                let path = scratch::resolve(&state, relative)?;
+                let modified = modified_time(&path);
+                let Ok(mut registrations) = state.event_loop.registrations.lock() else {
+                    return runtime_error!("The event loop is unavailable");
+                };
+                let id = registrations.next_id();
+                registrations.watches.push(FileWatch {
+                    id,
+                    callback: callback.clone(),
+                    relative: relative.to_string(),
+                    path,
+                    modified,
+                });
+                Ok(id.into())
+            }
+            other => runtime_error!(
+                "Expected a path in the scratch directory and a function, found {other:?}"
+            ),
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "cancel",
+        KNativeFunction::new(move |ctx: &mut CallContext| match ctx.args() {
+            [KValue::Number(id)] => Ok(state.event_loop.remove(number_to_i64(id)).into()),
+            other => {
+                runtime_error!("Expected an id from every, after, or watch_file, found {other:?}")
+            }
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "stop",
+        KNativeFunction::new(move |_ctx: &mut CallContext| {
+            state
+                .event_loop
+                .stop_requested
+                .store(true, Ordering::SeqCst);
+            Ok(KValue::Null)
+        }),
+    );
+}
+
+fn add_timer(
+    host: &HostState,
+    name: &str,
+    millis: &KNumber,
+    callback: &KValue,
+    repeating: bool,
+) -> KotoRuntimeResult<KValue> {
+    host.event_loop.require_daemon(name)?;
+    let millis = number_to_i64(millis);
+    if millis < 0 || (repeating && millis == 0) {
+        return runtime_error!("Expected a positive number of milliseconds, found {millis}");
+    }
+    let period = Duration::from_millis(millis as u64);
+    let now = host.run_elapsed().unwrap_or_default();
+    let Ok(mut registrations) = host.event_loop.registrations.lock() else {
+        return runtime_error!("The event loop is unavailable");
+    };
+    let id = registrations.next_id();
+    registrations.timers.push(Timer {
+        id,
+        callback: callback.clone(),
+        period: repeating.then_some(period),
+        due: now + period,
+        ticks: 0,
+    });
+    Ok(id.into())
+}
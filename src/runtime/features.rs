@@ -0,0 +1,57 @@
+//! Optional subsystems chosen with cargo features. `cargo build --no-default-features` leaves
+//! a small learning runtime without native plugins, YAML and the other serialization formats,
+//! encodings, audio playback, clipboard access, images, profiling scopes, or benchmark
+//! summaries, and the explorer hides what's missing.
+
+/// Every optional feature and whether this build includes it.
+pub const FEATURES: [(&str, bool); 10] = [
+    ("plugins", cfg!(feature = "plugins")),
+    ("yaml", cfg!(feature = "yaml")),
+    ("profiling", cfg!(feature = "profiling")),
+    ("benchmarks", cfg!(feature = "benchmarks")),
+    ("audio", cfg!(feature = "audio")),
+    ("clipboard", cfg!(feature = "clipboard")),
+    ("image", cfg!(feature = "image")),
+    ("archive", cfg!(feature = "archive")),
+    ("serialization", cfg!(feature = "serialization")),
+    ("encoding", cfg!(feature = "encoding")),
+];
+
+/// Whether this build includes the feature called `name`; unknown names count as missing.
+pub fn is_enabled(name: &str) -> bool {
+    FEATURES
+        .iter()
+        .any(|(feature, enabled)| *feature == name && *enabled)
+}
+
+/// The names in `required` that this build lacks.
+pub fn missing<'a>(required: &'a [String]) -> Vec<&'a str> {
+    required
+        .iter()
+        .map(String::as_str)
+        .filter(|name| !is_enabled(name))
+        .collect()
+}
+
+/// Adds `names` to `module` as functions that explain which feature this build lacks, so a
+/// script calling `{module_name}.{name}` gets a clear error instead of a missing key.
+#[cfg(not(all(feature = "yaml", feature = "serialization", feature = "encoding")))]
+pub(super) fn register_unavailable(
+    module: &koto::prelude::KMap,
+    module_name: &'static str,
+    names: &[&'static str],
+    feature: &'static str,
+) {
+    use koto::prelude::*;
+
+    for &name in names {
+        module.insert(
+            name,
+            KNativeFunction::new(move |_: &mut CallContext| {
+                runtime_error!(
+                    "{module_name}.{name} needs koto_learning built with the `{feature}` feature"
+                )
+            }),
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use std::{sync::Arc, time::Duration};
+
+use koto::{prelude::*, runtime::Result as KotoRuntimeResult};
+
+use super::{HostState, value_preview};
+
+/// A single step recorded while a traced generator or iterator was driven by a script.
+#[derive(Clone, Debug)]
+pub struct GeneratorEvent {
+    pub label: String,
+    pub kind: GeneratorEventKind,
+    /// Time since the start of the run when the event was recorded.
+    pub offset: Duration,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GeneratorEventKind {
+    Resumed,
+    Yielded(String),
+    Finished,
+    Failed(String),
+}
+
+/// Adds `host.trace(label, iterable)`, which wraps an iterable so every resume and yield is
+/// recorded in the run's [`GeneratorEvent`] trace.
+pub(super) fn register(module: &KMap, host: Arc<HostState>) {
+    module.insert(
+        "trace",
+        KNativeFunction::new(move |ctx: &mut CallContext| {
+            let (label, iterable) = match ctx.args() {
+                [KValue::Str(label), iterable] => (label.to_string(), iterable.clone()),
+                [iterable] => ("generator".to_string(), iterable.clone()),
+                other => return runtime_error!("Expected label and iterable, found {other:?}"),
+            };
+            let inner = ctx.vm.make_iterator(iterable)?;
+            let traced = TracedIterator {
+                label,
+                inner,
+                host: Arc::clone(&host),
+                finished: false,
+            };
+            Ok(KIterator::new(traced).into())
+        }),
+    );
+}
+
+struct TracedIterator {
+    label: String,
+    inner: KIterator,
+    host: Arc<HostState>,
+    finished: bool,
+}
+
+impl TracedIterator {
+    fn record(&self, kind: GeneratorEventKind) {
+        self.host.record_generator_event(GeneratorEvent {
+            label: self.label.clone(),
+            kind,
+            offset: self.host.run_elapsed().unwrap_or_default(),
+        });
+    }
+}
+
+impl KotoIterator for TracedIterator {
+    fn make_copy(&self) -> KotoRuntimeResult<KIterator> {
+        Ok(KIterator::new(TracedIterator {
+            label: self.label.clone(),
+            inner: self.inner.make_copy()?,
+            host: Arc::clone(&self.host),
+            finished: self.finished,
+        }))
+    }
+}
+
+impl Iterator for TracedIterator {
+    type Item = KIteratorOutput;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        self.record(GeneratorEventKind::Resumed);
+        let output = self.inner.next();
+        match &output {
+            Some(KIteratorOutput::Value(value)) => {
+                let format = self.host.number_format();
+                self.record(GeneratorEventKind::Yielded(value_preview(value, &format)));
+            }
+            Some(KIteratorOutput::ValuePair(first, second)) => {
+                let format = self.host.number_format();
+                self.record(GeneratorEventKind::Yielded(format!(
+                    "({}, {})",
+                    value_preview(first, &format),
+                    value_preview(second, &format)
+                )));
+            }
+            Some(KIteratorOutput::Error(error)) => {
+                self.finished = true;
+                self.record(GeneratorEventKind::Failed(error.to_string()));
+            }
+            None => {
+                self.finished = true;
+                self.record(GeneratorEventKind::Finished);
+            }
+        }
+        output
+    }
+}
@@ -0,0 +1,412 @@
+//! The `gfx` module: scripts describe a frame as a list of simple shapes and present it, and
+//! the explorer draws the latest frame on its canvas. Combined with daemon runs' timers this is
+//! enough for pong or snake style game loops.
+
+use std::sync::{Arc, Mutex};
+
+use koto::{prelude::*, runtime::Result as KotoRuntimeResult};
+
+use super::{HostState, number_to_f64, observer::Observers};
+
+pub const DEFAULT_CANVAS_SIZE: (f32, f32) = (320.0, 240.0);
+/// A frame with more shapes than this is almost certainly a runaway loop.
+const MAX_SHAPES_PER_FRAME: usize = 10_000;
+const DEFAULT_TEXT_SIZE: f32 = 14.0;
+
+/// Red, green, blue, and alpha.
+pub type Rgba = [u8; 4];
+
+const BLACK: Rgba = [0, 0, 0, 255];
+const WHITE: Rgba = [255, 255, 255, 255];
+
+/// A shape in canvas coordinates, where (0, 0) is the top left corner.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    Rect {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        color: Rgba,
+    },
+    Circle {
+        x: f32,
+        y: f32,
+        radius: f32,
+        color: Rgba,
+    },
+    Line {
+        from: (f32, f32),
+        to: (f32, f32),
+        width: f32,
+        color: Rgba,
+    },
+    Text {
+        x: f32,
+        y: f32,
+        text: String,
+        size: f32,
+        color: Rgba,
+    },
+}
+
+/// Everything drawn between two `gfx.present()` calls.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    /// Counts the run's presented frames, starting at 1.
+    pub number: u64,
+    pub width: f32,
+    pub height: f32,
+    pub background: Rgba,
+    pub shapes: Vec<Shape>,
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self {
+            number: 0,
+            width: DEFAULT_CANVAS_SIZE.0,
+            height: DEFAULT_CANVAS_SIZE.1,
+            background: BLACK,
+            shapes: Vec::new(),
+        }
+    }
+}
+
+/// The frame being drawn and the last one presented.
+#[derive(Default)]
+pub(super) struct GfxState {
+    pending: Mutex<Frame>,
+    presented: Mutex<Option<Frame>>,
+    observers: Mutex<Option<Arc<Observers>>>,
+}
+
+impl GfxState {
+    pub(super) fn attach(&self, observers: Arc<Observers>) {
+        if let Ok(mut attached) = self.observers.lock() {
+            *attached = Some(observers);
+        }
+    }
+
+    pub(super) fn begin_run(&self) {
+        if let Ok(mut pending) = self.pending.lock() {
+            *pending = Frame::default();
+        }
+        if let Ok(mut presented) = self.presented.lock() {
+            *presented = None;
+        }
+    }
+
+    fn with_pending<T>(&self, f: impl FnOnce(&mut Frame) -> T) -> KotoRuntimeResult<T> {
+        match self.pending.lock() {
+            Ok(mut frame) => Ok(f(&mut frame)),
+            Err(_) => runtime_error!("The canvas is unavailable"),
+        }
+    }
+
+    fn push(&self, shape: Shape) -> KotoRuntimeResult<()> {
+        let pushed = self.with_pending(|frame| {
+            if frame.shapes.len() >= MAX_SHAPES_PER_FRAME {
+                return false;
+            }
+            frame.shapes.push(shape);
+            true
+        })?;
+        if pushed {
+            Ok(())
+        } else {
+            runtime_error!(
+                "A frame can hold at most {MAX_SHAPES_PER_FRAME} shapes; call gfx.present() to start a new one"
+            )
+        }
+    }
+
+    fn has_pending_shapes(&self) -> bool {
+        self.pending
+            .lock()
+            .map(|frame| !frame.shapes.is_empty())
+            .unwrap_or(false)
+    }
+
+    /// Shows the pending frame and starts an empty one with the same size and background.
+    pub(super) fn present(&self) -> u64 {
+        let Ok(mut pending) = self.pending.lock() else {
+            return 0;
+        };
+        let number = pending.number + 1;
+        let frame = Frame {
+            number,
+            shapes: std::mem::take(&mut pending.shapes),
+            ..pending.clone()
+        };
+        pending.number = number;
+        drop(pending);
+
+        let observers = self
+            .observers
+            .lock()
+            .ok()
+            .and_then(|observers| observers.clone());
+        if let Some(observers) = observers {
+            observers.frame(&frame);
+        }
+        if let Ok(mut presented) = self.presented.lock() {
+            *presented = Some(frame);
+        }
+        number
+    }
+
+    /// Presents shapes drawn since the last frame, so scripts that never call `gfx.present()`
+    /// still show what they drew.
+    pub(super) fn present_pending(&self) {
+        if self.has_pending_shapes() {
+            self.present();
+        }
+    }
+
+    pub(super) fn take_presented(&self) -> Option<Frame> {
+        self.presented
+            .lock()
+            .ok()
+            .and_then(|mut frame| frame.take())
+    }
+}
+
+/// Builds the `gfx` module.
+pub(super) fn gfx_module(host: Arc<HostState>) -> KValue {
+    let module = KMap::default();
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "canvas",
+        KNativeFunction::new(move |ctx: &mut CallContext| match ctx.args() {
+            [KValue::Number(width), KValue::Number(height)] => {
+                let (width, height) = (number_to_f32(width), number_to_f32(height));
+                if width <= 0.0 || height <= 0.0 {
+                    return runtime_error!(
+                        "Expected a positive canvas size, found {width} x {height}"
+                    );
+                }
+                state.gfx.with_pending(|frame| {
+                    frame.width = width;
+                    frame.height = height;
+                })?;
+                Ok(KValue::Null)
+            }
+            other => runtime_error!("Expected a width and a height, found {other:?}"),
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "size",
+        KNativeFunction::new(move |_ctx: &mut CallContext| {
+            let (width, height) = state
+                .gfx
+                .with_pending(|frame| (frame.width, frame.height))?;
+            Ok(KValue::Tuple(KTuple::from(vec![
+                f64::from(width).into(),
+                f64::from(height).into(),
+            ])))
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "clear",
+        KNativeFunction::new(move |ctx: &mut CallContext| {
+            let background = match ctx.args() {
+                [] => BLACK,
+                [color] => parse_color(color)?,
+                other => return runtime_error!("Expected an optional color, found {other:?}"),
+            };
+            state.gfx.with_pending(|frame| {
+                frame.shapes.clear();
+                frame.background = background;
+            })?;
+            Ok(KValue::Null)
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "rect",
+        KNativeFunction::new(move |ctx: &mut CallContext| match ctx.args() {
+            [
+                KValue::Number(x),
+                KValue::Number(y),
+                KValue::Number(width),
+                KValue::Number(height),
+                color @ ..,
+            ] => {
+                state.gfx.push(Shape::Rect {
+                    x: number_to_f32(x),
+                    y: number_to_f32(y),
+                    width: number_to_f32(width),
+                    height: number_to_f32(height),
+                    color: optional_color(color)?,
+                })?;
+                Ok(KValue::Null)
+            }
+            other => runtime_error!(
+                "Expected x, y, width, height, and an optional color, found {other:?}"
+            ),
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "circle",
+        KNativeFunction::new(move |ctx: &mut CallContext| match ctx.args() {
+            [
+                KValue::Number(x),
+                KValue::Number(y),
+                KValue::Number(radius),
+                color @ ..,
+            ] => {
+                state.gfx.push(Shape::Circle {
+                    x: number_to_f32(x),
+                    y: number_to_f32(y),
+                    radius: number_to_f32(radius),
+                    color: optional_color(color)?,
+                })?;
+                Ok(KValue::Null)
+            }
+            other => {
+                runtime_error!("Expected x, y, radius, and an optional color, found {other:?}")
+            }
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "line",
+        KNativeFunction::new(move |ctx: &mut CallContext| match ctx.args() {
+            [
+                KValue::Number(x1),
+                KValue::Number(y1),
+                KValue::Number(x2),
+                KValue::Number(y2),
+                color @ ..,
+            ] => {
+                state.gfx.push(Shape::Line {
+                    from: (number_to_f32(x1), number_to_f32(y1)),
+                    to: (number_to_f32(x2), number_to_f32(y2)),
+                    width: 1.0,
+                    color: optional_color(color)?,
+                })?;
+                Ok(KValue::Null)
+            }
+            other => {
+                runtime_error!("Expected x1, y1, x2, y2, and an optional color, found {other:?}")
+            }
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "text",
+        KNativeFunction::new(move |ctx: &mut CallContext| match ctx.args() {
+            [
+                KValue::Number(x),
+                KValue::Number(y),
+                KValue::Str(text),
+                color @ ..,
+            ] => {
+                state.gfx.push(Shape::Text {
+                    x: number_to_f32(x),
+                    y: number_to_f32(y),
+                    text: text.to_string(),
+                    size: DEFAULT_TEXT_SIZE,
+                    color: optional_color(color)?,
+                })?;
+                Ok(KValue::Null)
+            }
+            other => {
+                runtime_error!("Expected x, y, text, and an optional color, found {other:?}")
+            }
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "present",
+        KNativeFunction::new(move |_ctx: &mut CallContext| {
+            state.check_interrupts()?;
+            Ok((state.gfx.present() as i64).into())
+        }),
+    );
+
+    module.into()
+}
+
+fn number_to_f32(number: &KNumber) -> f32 {
+    number_to_f64(number) as f32
+}
+
+fn optional_color(args: &[KValue]) -> KotoRuntimeResult<Rgba> {
+    match args {
+        [] => Ok(WHITE),
+        [color] => parse_color(color),
+        other => runtime_error!("Expected a single color, found {other:?}"),
+    }
+}
+
+/// Colors are names like `'red'`, hex strings like `'#ff8800'` or `'#ff880080'`, or lists and
+/// tuples of 3 or 4 numbers from 0 to 255.
+fn parse_color(value: &KValue) -> KotoRuntimeResult<Rgba> {
+    let channels: Vec<KValue> = match value {
+        KValue::Str(text) => return parse_color_text(text),
+        KValue::List(list) => list.data().iter().cloned().collect(),
+        KValue::Tuple(tuple) => tuple.iter().cloned().collect(),
+        other => return runtime_error!("Expected a color, found {other:?}"),
+    };
+    let mut rgba = [0, 0, 0, 255];
+    if !(3..=4).contains(&channels.len()) {
+        return runtime_error!("Expected 3 or 4 color channels, found {}", channels.len());
+    }
+    for (channel, value) in rgba.iter_mut().zip(&channels) {
+        match value {
+            KValue::Number(number) => *channel = number_to_f32(number).clamp(0.0, 255.0) as u8,
+            other => {
+                return runtime_error!("Expected color channels to be numbers, found {other:?}");
+            }
+        }
+    }
+    Ok(rgba)
+}
+
+fn parse_color_text(text: &str) -> KotoRuntimeResult<Rgba> {
+    let named = match text {
+        "black" => Some(BLACK),
+        "white" => Some(WHITE),
+        "gray" | "grey" => Some([128, 128, 128, 255]),
+        "red" => Some([220, 60, 60, 255]),
+        "green" => Some([80, 190, 90, 255]),
+        "blue" => Some([70, 120, 230, 255]),
+        "yellow" => Some([240, 210, 60, 255]),
+        "orange" => Some([240, 150, 50, 255]),
+        "purple" => Some([160, 90, 210, 255]),
+        "cyan" => Some([70, 200, 220, 255]),
+        "magenta" => Some([220, 80, 200, 255]),
+        _ => None,
+    };
+    if let Some(color) = named {
+        return Ok(color);
+    }
+    let hex = text.strip_prefix('#').unwrap_or_default();
+    let channel = |index: usize| u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).ok();
+    let parsed = match hex.len() {
+        6 if hex.is_ascii() => (0..3).map(channel).collect::<Option<Vec<_>>>(),
+        8 if hex.is_ascii() => (0..4).map(channel).collect::<Option<Vec<_>>>(),
+        _ => None,
+    };
+    match parsed {
+        Some(channels) => {
+            let mut rgba = [0, 0, 0, 255];
+            rgba[..channels.len()].copy_from_slice(&channels);
+            Ok(rgba)
+        }
+        None => runtime_error!("Unknown color '{text}'; use a name like 'red' or '#rrggbb'"),
+    }
+}
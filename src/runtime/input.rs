@@ -0,0 +1,189 @@
+//! The `input` module: keyboard and pointer events from the explorer's canvas, queued for a
+//! running script to poll, usually once per tick of a daemon example.
+
+use std::{
+    collections::{BTreeSet, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use koto::{prelude::*, runtime::Result as KotoRuntimeResult};
+
+use super::HostState;
+
+/// Events beyond this are dropped, oldest first, when a script stops polling.
+const MAX_QUEUED_EVENTS: usize = 256;
+
+/// An input event in canvas coordinates. Keys use egui's names, e.g. `ArrowUp`, `Space`, `A`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InputEvent {
+    KeyDown(String),
+    KeyUp(String),
+    PointerMove { x: f32, y: f32 },
+    PointerDown { x: f32, y: f32, button: String },
+    PointerUp { x: f32, y: f32, button: String },
+}
+
+impl InputEvent {
+    fn to_koto(&self) -> KValue {
+        let map = KMap::default();
+        match self {
+            Self::KeyDown(key) | Self::KeyUp(key) => {
+                let kind = if matches!(self, Self::KeyDown(_)) {
+                    "key_down"
+                } else {
+                    "key_up"
+                };
+                map.insert("type", kind);
+                map.insert("key", key.as_str());
+            }
+            Self::PointerMove { x, y } => {
+                map.insert("type", "pointer_move");
+                map.insert("x", f64::from(*x));
+                map.insert("y", f64::from(*y));
+            }
+            Self::PointerDown { x, y, button } | Self::PointerUp { x, y, button } => {
+                let kind = if matches!(self, Self::PointerDown { .. }) {
+                    "pointer_down"
+                } else {
+                    "pointer_up"
+                };
+                map.insert("type", kind);
+                map.insert("x", f64::from(*x));
+                map.insert("y", f64::from(*y));
+                map.insert("button", button.as_str());
+            }
+        }
+        map.into()
+    }
+}
+
+/// Events waiting for the script, and what is currently held down.
+#[derive(Default)]
+pub(super) struct InputState {
+    queue: Mutex<VecDeque<InputEvent>>,
+    held: Mutex<BTreeSet<String>>,
+    pointer: Mutex<Option<(f32, f32)>>,
+}
+
+impl InputState {
+    pub(super) fn begin_run(&self) {
+        if let Ok(mut queue) = self.queue.lock() {
+            queue.clear();
+        }
+        if let Ok(mut held) = self.held.lock() {
+            held.clear();
+        }
+        if let Ok(mut pointer) = self.pointer.lock() {
+            *pointer = None;
+        }
+    }
+
+    pub(super) fn push(&self, event: InputEvent) {
+        match &event {
+            InputEvent::KeyDown(key) => self.set_held(key, true),
+            InputEvent::KeyUp(key) => self.set_held(key, false),
+            InputEvent::PointerDown { x, y, button } => {
+                self.set_held(&pointer_key(button), true);
+                self.set_pointer(*x, *y);
+            }
+            InputEvent::PointerUp { x, y, button } => {
+                self.set_held(&pointer_key(button), false);
+                self.set_pointer(*x, *y);
+            }
+            InputEvent::PointerMove { x, y } => self.set_pointer(*x, *y),
+        }
+        let Ok(mut queue) = self.queue.lock() else {
+            return;
+        };
+        // Only the latest position matters between two polls.
+        if matches!(event, InputEvent::PointerMove { .. })
+            && matches!(queue.back(), Some(InputEvent::PointerMove { .. }))
+        {
+            queue.pop_back();
+        }
+        if queue.len() >= MAX_QUEUED_EVENTS {
+            queue.pop_front();
+        }
+        queue.push_back(event);
+    }
+
+    fn set_held(&self, key: &str, down: bool) {
+        if let Ok(mut held) = self.held.lock() {
+            if down {
+                held.insert(key.to_string());
+            } else {
+                held.remove(key);
+            }
+        }
+    }
+
+    fn set_pointer(&self, x: f32, y: f32) {
+        if let Ok(mut pointer) = self.pointer.lock() {
+            *pointer = Some((x, y));
+        }
+    }
+
+    fn take_events(&self) -> Vec<InputEvent> {
+        self.queue
+            .lock()
+            .map(|mut queue| queue.drain(..).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Pointer buttons share the held set with keys, e.g. `pointer_primary`.
+fn pointer_key(button: &str) -> String {
+    format!("pointer_{button}")
+}
+
+/// Builds the `input` module.
+pub(super) fn input_module(host: Arc<HostState>) -> KValue {
+    let module = KMap::default();
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "events",
+        KNativeFunction::new(move |_ctx: &mut CallContext| {
+            state.check_interrupts()?;
+            let events: Vec<KValue> = state
+                .input
+                .take_events()
+                .iter()
+                .map(InputEvent::to_koto)
+                .collect();
+            Ok(KValue::List(KList::from_slice(&events)))
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "is_down",
+        KNativeFunction::new(move |ctx: &mut CallContext| match ctx.args() {
+            [KValue::Str(key)] => Ok(is_held(&state, key)?.into()),
+            other => runtime_error!("Expected a key name such as 'ArrowUp', found {other:?}"),
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "pointer",
+        KNativeFunction::new(move |_ctx: &mut CallContext| {
+            match state.input.pointer.lock().ok().and_then(|pointer| *pointer) {
+                Some((x, y)) => Ok(KValue::Tuple(KTuple::from(vec![
+                    f64::from(x).into(),
+                    f64::from(y).into(),
+                ]))),
+                None => Ok(KValue::Null),
+            }
+        }),
+    );
+
+    module.into()
+}
+
+fn is_held(host: &HostState, key: &str) -> KotoRuntimeResult<bool> {
+    match host.input.held.lock() {
+        Ok(held) => Ok(held.contains(key)),
+        Err(_) => runtime_error!("Input state is unavailable"),
+    }
+}
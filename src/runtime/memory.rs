@@ -0,0 +1,120 @@
+//! Allocation accounting for script runs.
+//!
+//! Koto has no allocation hooks, so memory limits rely on [`TrackingAllocator`] counting the
+//! bytes allocated by the thread that executes a script. Binaries and test crates opt in by
+//! installing it as the global allocator:
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOCATOR: koto_learning::runtime::memory::TrackingAllocator =
+//!     koto_learning::runtime::memory::TrackingAllocator;
+//! ```
+//!
+//! Without it, runs report no memory usage and limits are not enforced.
+//!
+//! Usage is counted per thread, so allocations made by `concurrent` workers don't count
+//! towards the run that spawned them.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// Wraps the system allocator and records per-thread usage while a run is being tracked.
+pub struct TrackingAllocator;
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static TRACKING: Cell<bool> = const { Cell::new(false) };
+    static CURRENT: Cell<isize> = const { Cell::new(0) };
+    static PEAK: Cell<isize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            record(layout.size() as isize);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            record(layout.size() as isize);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        record(-(layout.size() as isize));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            record(new_size as isize - layout.size() as isize);
+        }
+        new_ptr
+    }
+}
+
+fn record(delta: isize) {
+    if !INSTALLED.load(Ordering::Relaxed) {
+        INSTALLED.store(true, Ordering::Relaxed);
+    }
+    // `try_with` because allocations also happen while thread-locals are being torn down.
+    let _ = TRACKING.try_with(|tracking| {
+        if !tracking.get() {
+            return;
+        }
+        let _ = CURRENT.try_with(|current| {
+            let usage = current.get() + delta;
+            current.set(usage);
+            let _ = PEAK.try_with(|peak| peak.set(peak.get().max(usage)));
+        });
+    });
+}
+
+/// Whether [`TrackingAllocator`] is the global allocator, i.e. whether limits can be enforced.
+pub fn is_tracking_available() -> bool {
+    INSTALLED.load(Ordering::Relaxed)
+}
+
+/// Starts counting allocations made by the current thread from zero.
+pub(super) fn begin_tracking() {
+    CURRENT.with(|current| current.set(0));
+    PEAK.with(|peak| peak.set(0));
+    TRACKING.with(|tracking| tracking.set(true));
+}
+
+/// Stops counting and returns the peak number of bytes held since [`begin_tracking`].
+pub(super) fn end_tracking() -> usize {
+    TRACKING.with(|tracking| tracking.set(false));
+    PEAK.with(|peak| peak.get().max(0) as usize)
+}
+
+/// Bytes currently held by the tracked run on this thread, net of what it has freed.
+pub(super) fn current_usage() -> usize {
+    CURRENT.with(|current| current.get().max(0) as usize)
+}
+
+/// Renders a byte count with a binary unit, e.g. `1.5 MiB`.
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
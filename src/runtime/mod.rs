@@ -1,26 +1,76 @@
 use std::{
-    collections::HashMap,
-    ffi::{CStr, c_char},
+    collections::{BTreeMap, BTreeSet, HashMap},
     fs,
     path::{Path, PathBuf},
     sync::{
-        Arc, Mutex,
+        Arc, Mutex, RwLock,
         atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, TryRecvError},
     },
+    thread,
     time::{Duration, Instant, SystemTime},
 };
 
-use anyhow::{Context, anyhow};
-use koto::{Koto, KotoSettings, prelude::*, runtime::Result as KotoRuntimeResult};
-use libloading::Library;
+use anyhow::anyhow;
+use koto::{
+    CompileArgs, Koto, KotoSettings, bytecode::CompilerSettings, prelude::*,
+    runtime::Result as KotoRuntimeResult,
+};
 use once_cell::sync::Lazy;
 use serde_json::Value as JsonValue;
+#[cfg(feature = "yaml")]
 use serde_yaml::Value as YamlValue;
 use uuid::Uuid;
 
+#[cfg(feature = "plugins")]
+use anyhow::Context;
+#[cfg(feature = "plugins")]
+use libloading::Library;
+#[cfg(feature = "plugins")]
+use std::ffi::{CStr, c_char};
+
+use compile_cache::{CompileCache, CompileCacheStats};
+use observer::{ExecutionObserver, ExecutionStart, ObserverId, Observers, OutputStream};
+
 pub static RUNTIME: Lazy<Runtime> = Lazy::new(|| Runtime::new().expect("runtime init failed"));
 
+pub mod audio;
+pub mod audit;
+pub mod automation;
+#[cfg(feature = "serialization")]
+mod binary;
+pub mod clipboard;
+pub mod compile_cache;
+pub mod concurrent;
+pub mod coverage;
+#[cfg(feature = "encoding")]
+mod encoding;
+pub mod env;
+mod event_loop;
+pub mod features;
+pub mod generators;
+pub mod gfx;
+pub mod input;
+pub mod memory;
+pub mod number_format;
+pub mod observer;
+pub mod precompile;
+pub mod prelude;
+pub mod process;
+pub mod profile_compare;
+pub mod random;
+pub mod repeat;
+pub mod replay;
+mod schema;
+pub mod scratch;
+#[cfg(feature = "serialization")]
+mod tabular;
+pub mod timers;
+pub mod value_diff;
+pub mod version_matrix;
 pub mod watcher;
+#[cfg(feature = "serialization")]
+mod xml;
 
 #[derive(Clone, Copy)]
 pub struct Executor {
@@ -61,9 +111,14 @@ impl Default for Executor {
 
 pub struct Runtime {
     state: Mutex<RuntimeState>,
+    /// Compiles for [`Runtime::precompile`], separate from the VM so warming up scripts
+    /// doesn't wait for a running one.
+    compiler: Mutex<Koto>,
     stdout: BufferHandle,
     stderr: BufferHandle,
     profiling_enabled: Arc<AtomicBool>,
+    host_state: Arc<HostState>,
+    observers: Arc<Observers>,
 }
 
 #[derive(Clone, Debug)]
@@ -71,24 +126,391 @@ pub struct ExecutionOutput {
     pub return_value: Option<String>,
     pub stdout: String,
     pub stderr: String,
+    /// Total time of the run, i.e. `compile_time` plus `execute_time`.
     pub duration: Duration,
+    /// Time spent compiling, or looking the script up in the compile cache on a hit.
+    pub compile_time: Duration,
+    /// Time spent running the compiled chunk.
+    pub execute_time: Duration,
     pub value: Option<KValue>,
+    pub clipboard_accessed: bool,
+    pub generator_trace: Vec<generators::GeneratorEvent>,
+    /// Seed `host.random` started from, if the script used it; pass it to
+    /// [`Runtime::set_random_seed`] to replay the run's random choices.
+    pub random_seed: Option<u64>,
+    /// Most bytes the run held at once; `None` unless [`memory::TrackingAllocator`] is installed.
+    pub peak_memory: Option<usize>,
+    /// Whether the script's compiled chunk came from the compile cache.
+    pub cache_hit: bool,
+    /// Compile cache counters as of the end of this run.
+    pub compile_cache: CompileCacheStats,
+    /// Output of each `concurrent` worker the script started, which is also part of
+    /// `stdout` and `stderr`.
+    pub workers: Vec<concurrent::WorkerOutput>,
+    /// The last frame the script drew with `gfx`, if any.
+    pub frame: Option<gfx::Frame>,
+    /// Tones played through `audio`, whether or not audio output was enabled.
+    pub tones: Vec<audio::Tone>,
+}
+
+impl ExecutionOutput {
+    /// The return value as a JSON tree, or `None` when the script returned nothing. Maps
+    /// become objects (non-string keys are written as their display text), lists and tuples
+    /// become arrays, and integers stay integers. Values JSON can't hold, such as functions,
+    /// become strings like `"<Function>"`, and non-finite floats become `null`.
+    pub fn value_as_json(&self) -> Option<JsonValue> {
+        self.value.as_ref().map(|value| value_to_json(value, 0))
+    }
+
+    /// A JSON summary of the run for saving and comparing outside the app. `value` is the
+    /// return value as structured data, converted as by [`Self::value_as_json`], or `null`
+    /// when the script returned nothing; `return_value` is always its display text.
+    pub fn to_json(&self) -> JsonValue {
+        let value = self.value_as_json().unwrap_or(JsonValue::Null);
+        let workers: Vec<JsonValue> = self
+            .workers
+            .iter()
+            .map(|worker| {
+                serde_json::json!({
+                    "name": worker.name,
+                    "stdout": worker.stdout,
+                    "stderr": worker.stderr,
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "return_value": self.return_value,
+            "value": value,
+            "stdout": self.stdout,
+            "stderr": self.stderr,
+            "duration_ms": duration_millis(self.duration),
+            "compile_ms": duration_millis(self.compile_time),
+            "execute_ms": duration_millis(self.execute_time),
+            "cache_hit": self.cache_hit,
+            "random_seed": self.random_seed,
+            "peak_memory": self.peak_memory,
+            "clipboard_accessed": self.clipboard_accessed,
+            "workers": workers,
+        })
+    }
+}
+
+/// Nesting deeper than this is cut off, which also stops maps that contain themselves.
+const MAX_JSON_DEPTH: usize = 64;
+
+pub(crate) fn value_to_json(value: &KValue, depth: usize) -> JsonValue {
+    if depth > MAX_JSON_DEPTH {
+        return JsonValue::String("…".to_string());
+    }
+    match value {
+        KValue::Null => JsonValue::Null,
+        KValue::Bool(flag) => JsonValue::Bool(*flag),
+        KValue::Number(KNumber::I64(number)) => JsonValue::from(*number),
+        KValue::Number(KNumber::F64(number)) => {
+            serde_json::Number::from_f64(*number).map_or(JsonValue::Null, JsonValue::Number)
+        }
+        KValue::Str(text) => JsonValue::String(text.to_string()),
+        KValue::List(list) => list
+            .data()
+            .iter()
+            .map(|item| value_to_json(item, depth + 1))
+            .collect(),
+        KValue::Tuple(tuple) => tuple
+            .iter()
+            .map(|item| value_to_json(item, depth + 1))
+            .collect(),
+        KValue::Map(map) => JsonValue::Object(
+            map.data()
+                .iter()
+                .map(|(key, value)| (key.to_string(), value_to_json(value, depth + 1)))
+                .collect(),
+        ),
+        other => JsonValue::String(format!("<{}>", other.type_as_string())),
+    }
+}
+
+fn duration_millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Returned (inside the `anyhow::Error`) when a run reaches its execution limit. It keeps what
+/// the script wrote before it was stopped:
+///
+/// ```ignore
+/// if let Some(timeout) = error.downcast_ref::<TimeoutError>() {
+///     println!("got this far:\n{}", timeout.stdout);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct TimeoutError {
+    /// The error the VM stopped with.
+    pub message: String,
+    pub limit: Duration,
+    pub elapsed: Duration,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl std::fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (stopped after {:.2}s, limit {:.2}s)",
+            self.message,
+            self.elapsed.as_secs_f64(),
+            self.limit.as_secs_f64()
+        )
+    }
+}
+
+impl std::error::Error for TimeoutError {}
+
+/// The start of the error Koto raises when a call into the VM reaches its execution limit.
+const VM_TIMEOUT_MESSAGE: &str = "Execution timed out";
+/// The start of the errors host calls that wait, such as `host.sleep_ms`, raise when they
+/// reach the run's deadline.
+const HOST_TIMEOUT_MESSAGE: &str = "Execution limit exceeded";
+
+/// Whether `message`, a script error, says that the script was stopped for reaching its
+/// execution limit or deadline rather than failing on its own.
+pub fn is_execution_limit_error(message: &str) -> bool {
+    message.contains(VM_TIMEOUT_MESSAGE) || message.contains(HOST_TIMEOUT_MESSAGE)
+}
+
+/// Per-run settings for [`Runtime::execute`] and [`Runtime::execute_async`].
+#[derive(Clone, Debug, Default)]
+pub struct RunOptions {
+    pub timeout: Option<Duration>,
+    /// Runs the script in this session, keeping its top-level bindings for the next run.
+    pub session_id: Option<String>,
+    /// Exposed to the script as the `args` list of strings.
+    pub args: Vec<String>,
+    /// Extra prelude entries for this run only, added after the host bindings.
+    pub bindings: Vec<(String, RunBinding)>,
+    /// Environment variables this run may read and set through `env`, on top of the
+    /// runtime-wide allowlist.
+    pub env_allowlist: Vec<String>,
+    /// Keeps the run going after the script finishes, firing the callbacks it registered with
+    /// `host.every`, `host.after`, and `host.watch_file` until it is stopped.
+    pub daemon: bool,
+    /// Enables `process.run` for this run. Nothing else does: the explorer sets it for
+    /// examples that opt in when the profile is trusted or the user approves.
+    pub allow_process: bool,
+    /// Stops this run, and only this run, once set; see [`RunOptions::cancel_flag`].
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+/// A prelude entry added for a single run; see [`RunOptions::binding`].
+#[derive(Clone, Debug)]
+pub enum RunBinding {
+    /// A constant, converted from JSON.
+    Value(JsonValue),
+    /// Another name for an existing prelude value, e.g. `string.to_uppercase`.
+    Alias(String),
+}
+
+impl RunOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn binding(mut self, name: impl Into<String>, binding: RunBinding) -> Self {
+        self.bindings.push((name.into(), binding));
+        self
+    }
+
+    pub fn bindings(mut self, bindings: impl IntoIterator<Item = (String, RunBinding)>) -> Self {
+        self.bindings.extend(bindings);
+        self
+    }
+
+    pub fn daemon(mut self, daemon: bool) -> Self {
+        self.daemon = daemon;
+        self
+    }
+
+    pub fn allow_process(mut self, allow: bool) -> Self {
+        self.allow_process = allow;
+        self
+    }
+
+    /// Cancels the run when `flag` is set, including while it is still waiting for an earlier
+    /// run to release the VM. [`Runtime::execute_async`] gives each handle its own flag.
+    pub fn cancel_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(flag);
+        self
+    }
+
+    pub fn env_allowlist<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.env_allowlist = names.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+/// A script running on a background thread, returned by [`Runtime::execute_script_async`].
+pub struct ExecutionHandle {
+    receiver: Receiver<anyhow::Result<ExecutionOutput>>,
+    cancelled: Arc<AtomicBool>,
+    started: Instant,
+}
+
+impl ExecutionHandle {
+    /// Returns the result once the script has finished; `None` while it is still running.
+    pub fn poll(&self) -> Option<anyhow::Result<ExecutionOutput>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                Some(Err(anyhow!("Script thread ended without a result")))
+            }
+        }
+    }
+
+    /// Blocks until the script has finished.
+    pub fn wait(self) -> anyhow::Result<ExecutionOutput> {
+        self.receiver
+            .recv()
+            .unwrap_or_else(|_| Err(anyhow!("Script thread ended without a result")))
+    }
+
+    /// Asks the script to stop. The run ends with an error the next time the script calls into
+    /// the host (printing, `host.sleep_ms`, ...); a loop that never does keeps running until the
+    /// execution limit is reached. Daemon runs (see [`RunOptions::daemon`]) end normally
+    /// instead, since stopping is how they are meant to finish.
+    ///
+    /// Only this handle's run is affected: a run still queued behind another one ends as soon as
+    /// it gets the VM, and the run holding the VM keeps going.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        logging::with_runtime_subscriber(|| {
+            tracing::info!(target: "runtime.vm", "Cancellation requested");
+        });
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+/// Controls which privileged host modules scripts are allowed to use.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RuntimeProfile {
+    /// Only side-effect free helpers are available.
+    #[default]
+    Standard,
+    /// Enables modules that reach outside the sandbox, such as the clipboard.
+    Trusted,
+}
+
+impl RuntimeProfile {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Standard => "Standard",
+            Self::Trusted => "Trusted",
+        }
+    }
 }
 
 struct RuntimeState {
     koto: Koto,
     config: RuntimeConfig,
     host_bindings: HashMap<String, KValue>,
+    compile_cache: CompileCache,
+    /// Exported bindings of each session, swapped into the VM while the session runs.
+    sessions: HashMap<String, ValueMap>,
+    #[cfg(feature = "plugins")]
     shared_libraries: Vec<SharedLibrary>,
     profiling_flag: Arc<AtomicBool>,
+    host_state: Arc<HostState>,
+}
+
+/// State shared with host modules and consulted each time a script calls into them.
+#[derive(Default)]
+struct HostState {
+    env_allowlist: RwLock<BTreeSet<String>>,
+    /// Names the current run may use in addition to `env_allowlist`.
+    run_env_allowlist: RwLock<BTreeSet<String>>,
+    /// Values set by `env.set` during the current run; `None` hides a variable. The
+    /// process environment itself is never modified.
+    env_overrides: Mutex<BTreeMap<String, Option<String>>>,
+    profile: RwLock<RuntimeProfile>,
+    clipboard_accessed: AtomicBool,
+    /// Whether the current run may use `process.run`.
+    process_allowed: AtomicBool,
+    /// The current run's cancel flag, shared with its [`ExecutionHandle`] when it has one.
+    cancel_requested: RwLock<Arc<AtomicBool>>,
+    run_window: Mutex<Option<RunWindow>>,
+    generator_trace: Mutex<Vec<generators::GeneratorEvent>>,
+    /// When set, `host.sleep_ms` advances `virtual_clock` instead of waiting, and
+    /// `host.elapsed` reports that clock rather than wall time.
+    deterministic: AtomicBool,
+    virtual_clock: Mutex<Duration>,
+    /// Text served to `io.stdin`; every run reads it from the start.
+    stdin: Mutex<StdinBuffer>,
+    /// The only directory `host.fs` may touch; `None` disables the module.
+    scratch_dir: RwLock<Option<PathBuf>>,
+    number_format: RwLock<number_format::NumberFormat>,
+    random: Mutex<random::RandomState>,
+    random_used: AtomicBool,
+    /// Seed every run starts from; a fresh one is picked per run when unset.
+    random_seed: Mutex<Option<u64>>,
+    workers: concurrent::WorkerRegistry,
+    event_loop: event_loop::EventLoop,
+    gfx: gfx::GfxState,
+    input: input::InputState,
+    audio_enabled: AtomicBool,
+    tones: Mutex<Vec<audio::Tone>>,
+}
+
+#[derive(Default)]
+struct StdinBuffer {
+    text: String,
+    position: usize,
+}
+
+#[derive(Clone, Copy)]
+struct RunWindow {
+    started: Instant,
+    deadline: Option<Instant>,
+    memory_limit: Option<usize>,
 }
 
 #[derive(Clone, Default)]
 struct RuntimeConfig {
     execution_limit: Option<Duration>,
+    /// Maximum number of bytes a run may hold; see [`memory`].
+    memory_limit: Option<usize>,
     run_tests: bool,
 }
 
+#[cfg(feature = "plugins")]
 struct SharedLibrary {
     #[allow(dead_code)]
     library: Library,
@@ -97,15 +519,28 @@ struct SharedLibrary {
 #[derive(Clone)]
 struct BufferHandle {
     id: KString,
+    stream: OutputStream,
     buffer: Arc<Mutex<String>>,
+    observers: Arc<Observers>,
+    host_state: Arc<HostState>,
 }
 
 #[derive(Clone)]
 struct BufferFile {
     id: KString,
+    stream: OutputStream,
     buffer: Arc<Mutex<String>>,
+    observers: Arc<Observers>,
+    host_state: Arc<HostState>,
 }
 
+/// The readable counterpart of [`BufferFile`], backing `io.stdin`.
+#[derive(Clone)]
+struct StdinFile {
+    host_state: Arc<HostState>,
+}
+
+#[cfg(feature = "plugins")]
 #[repr(C)]
 struct RuntimeLibraryApi {
     runtime: *const Runtime,
@@ -116,21 +551,28 @@ impl Runtime {
     pub fn new() -> anyhow::Result<Self> {
         logging::init_global()?;
 
-        let stdout = BufferHandle::new("stdout");
-        let stderr = BufferHandle::new("stderr");
+        let observers = Arc::new(Observers::default());
+        let host_state = Arc::new(HostState::default());
+        host_state.gfx.attach(Arc::clone(&observers));
+        let stdout = BufferHandle::new("stdout", OutputStream::Stdout, &observers, &host_state);
+        let stderr = BufferHandle::new("stderr", OutputStream::Stderr, &observers, &host_state);
         let profiling_enabled = Arc::new(AtomicBool::new(false));
         let state = RuntimeState::new(
             RuntimeConfig::default(),
             &stdout,
             &stderr,
             &profiling_enabled,
+            &host_state,
         )?;
 
         Ok(Self {
             state: Mutex::new(state),
+            compiler: Mutex::new(Koto::default()),
             stdout,
             stderr,
             profiling_enabled,
+            host_state,
+            observers,
         })
     }
 
@@ -143,55 +585,395 @@ impl Runtime {
         script: &str,
         timeout: Option<Duration>,
     ) -> anyhow::Result<ExecutionOutput> {
+        self.execute(script, &RunOptions::new().timeout(timeout))
+    }
+
+    /// Runs `script` with `args` available to it as the `args` list, like command-line
+    /// arguments.
+    pub fn execute_script_with_args<I, S>(
+        &self,
+        script: &str,
+        args: I,
+    ) -> anyhow::Result<ExecutionOutput>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.execute(script, &RunOptions::new().args(args))
+    }
+
+    /// Runs `script` with each of `bindings` added to the prelude for this run, converted from
+    /// JSON to Koto values, so strings containing quotes or newlines need no escaping.
+    pub fn execute_script_with_bindings<I, S>(
+        &self,
+        script: &str,
+        bindings: I,
+    ) -> anyhow::Result<ExecutionOutput>
+    where
+        I: IntoIterator<Item = (S, JsonValue)>,
+        S: Into<String>,
+    {
+        let options = RunOptions::new().bindings(
+            bindings
+                .into_iter()
+                .map(|(name, value)| (name.into(), RunBinding::Value(value))),
+        );
+        self.execute(script, &options)
+    }
+
+    /// Runs `script` with every per-run setting in `options`.
+    pub fn execute(&self, script: &str, options: &RunOptions) -> anyhow::Result<ExecutionOutput> {
+        self.run_script(script, options)
+    }
+
+    /// Runs `script` on a background thread so the caller stays responsive. Other calls that
+    /// need the VM wait until the script has finished.
+    pub fn execute_script_async(
+        &'static self,
+        script: impl Into<String>,
+        timeout: Option<Duration>,
+    ) -> ExecutionHandle {
+        self.execute_async(script, RunOptions::new().timeout(timeout))
+    }
+
+    /// Background counterpart of [`Runtime::execute`].
+    pub fn execute_async(
+        &'static self,
+        script: impl Into<String>,
+        options: RunOptions,
+    ) -> ExecutionHandle {
+        let script = script.into();
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let options = options.cancel_flag(Arc::clone(&cancelled));
+        thread::spawn(move || {
+            let _ = sender.send(self.run_script(&script, &options));
+        });
+        ExecutionHandle {
+            receiver,
+            cancelled,
+            started: Instant::now(),
+        }
+    }
+
+    /// Runs `script` in the session `session_id`. Top-level bindings from earlier runs in the
+    /// same session are visible, and the ones this run defines are kept for the next, much like
+    /// a REPL. Sessions are independent of each other and of plain runs.
+    pub fn execute_in_session(
+        &self,
+        session_id: &str,
+        script: &str,
+    ) -> anyhow::Result<ExecutionOutput> {
+        self.execute(script, &RunOptions::new().session(session_id))
+    }
+
+    /// Background counterpart of [`Runtime::execute_in_session`].
+    pub fn execute_in_session_async(
+        &'static self,
+        session_id: impl Into<String>,
+        script: impl Into<String>,
+        timeout: Option<Duration>,
+    ) -> ExecutionHandle {
+        self.execute_async(
+            script,
+            RunOptions::new().session(session_id).timeout(timeout),
+        )
+    }
+
+    /// Rebuilds the VM so the next run starts without prelude changes or exports left behind
+    /// by earlier runs. Host bindings are registered again; sessions keep their bindings.
+    pub fn reset_vm(&self) -> anyhow::Result<()> {
+        let mut state = self.lock_state()?;
+        state.rebuild_vm(&self.stdout, &self.stderr);
+        logging::with_runtime_subscriber(|| {
+            tracing::info!(target: "runtime.vm", "VM rebuilt");
+        });
+        Ok(())
+    }
+
+    /// Forgets the bindings accumulated by `session_id`.
+    pub fn reset_session(&self, session_id: &str) -> anyhow::Result<()> {
+        let mut state = self.lock_state()?;
+        state.sessions.remove(session_id);
+        logging::with_runtime_subscriber(|| {
+            tracing::info!(target: "runtime.vm", session = session_id, "Session reset");
+        });
+        Ok(())
+    }
+
+    /// Names bound in `session_id` so far, sorted alphabetically.
+    pub fn session_bindings(&self, session_id: &str) -> anyhow::Result<Vec<String>> {
+        let state = self.lock_state()?;
+        let mut names: Vec<String> = state
+            .sessions
+            .get(session_id)
+            .map(|bindings| bindings.keys().map(|key| key.to_string()).collect())
+            .unwrap_or_default();
+        names.sort();
+        Ok(names)
+    }
+
+    /// Evaluates each of `expressions` against the exports of the last plain run (or the
+    /// bindings of `session_id`), with `result` bound to `value`, the run's return value.
+    /// Each expression gets its value's display text or the error it raised; exports and
+    /// session bindings are left as they were, and anything printed is discarded.
+    pub fn evaluate_watches(
+        &self,
+        expressions: &[String],
+        value: Option<KValue>,
+        session_id: Option<&str>,
+    ) -> anyhow::Result<Vec<Result<String, String>>> {
+        let mut state = self.lock_state()?;
+        let exports = match session_id {
+            Some(id) => state.sessions.get(id).cloned().unwrap_or_default(),
+            None => state.koto.exports().data().clone(),
+        };
+        let shadowed = state
+            .push_prelude_bindings(vec![("result".to_string(), value.unwrap_or(KValue::Null))]);
+
+        let results = expressions
+            .iter()
+            .map(|expression| {
+                *state.koto.exports().data_mut() = exports.clone();
+                let chunk = state
+                    .koto
+                    .compile(CompileArgs {
+                        script: expression,
+                        script_path: None,
+                        compiler_settings: CompilerSettings::default(),
+                    })
+                    .map_err(|error| error.to_string())?;
+                let value = state.koto.run(chunk).map_err(|error| error.to_string())?;
+                state
+                    .koto
+                    .value_to_string(value)
+                    .map_err(|error| error.to_string())
+            })
+            .collect();
+
+        *state.koto.exports().data_mut() = match session_id {
+            Some(_) => Default::default(),
+            None => exports,
+        };
+        state.pop_prelude_bindings(shadowed);
+        self.stdout.clear();
+        self.stderr.clear();
+        Ok(results)
+    }
+
+    fn run_script(&self, script: &str, options: &RunOptions) -> anyhow::Result<ExecutionOutput> {
+        let session_id = options.session_id.as_deref();
         logging::with_runtime_subscriber(|| {
             tracing::info!(target: "runtime.vm", len = script.len(), "Evaluating script");
         });
 
         let mut state = self.lock_state()?;
+        // Checked once the VM is ours, so a run cancelled while it waited for another to finish
+        // never starts.
+        let cancel = options.cancel.clone().unwrap_or_default();
+        if cancel.load(Ordering::SeqCst) {
+            return Err(anyhow!("Script execution was cancelled"));
+        }
+        let timeout = options.timeout;
         if state.config.execution_limit != timeout {
             state.config.execution_limit = timeout;
             state.rebuild_vm(&self.stdout, &self.stderr);
         }
+        let run_bindings = state.resolve_run_bindings(&options.bindings)?;
 
         self.stdout.clear();
         self.stderr.clear();
+        self.host_state
+            .begin_run(timeout, state.config.memory_limit, cancel);
+        self.host_state
+            .set_run_env_allowlist(&options.env_allowlist);
+        self.host_state.event_loop.begin_run(options.daemon);
+        self.host_state
+            .process_allowed
+            .store(options.allow_process, Ordering::SeqCst);
+        let initial_seed = self.host_state.random_seed_in_use();
+        let start_event = ExecutionStart {
+            script_len: script.len(),
+            timeout,
+            session: session_id.is_some(),
+        };
+        self.observers
+            .notify(|observer| observer.on_start(&start_event));
+
+        // The VM's exports hold the bindings of the session being run, and nothing otherwise.
+        let session_bindings = session_id
+            .and_then(|id| state.sessions.get(id).cloned())
+            .unwrap_or_default();
+        *state.koto.exports().data_mut() = session_bindings;
+        let args = options
+            .args
+            .iter()
+            .map(|arg| KValue::from(arg.as_str()))
+            .collect::<Vec<_>>();
+        state
+            .koto
+            .prelude()
+            .insert("args", KList::from_slice(&args));
+        let shadowed = state.push_prelude_bindings(run_bindings);
 
         let profiling_enabled = state.profiling_flag.load(Ordering::SeqCst);
         let start = Instant::now();
-        let result = if profiling_enabled {
-            profiling::scope!("koto_script");
-            state.koto.compile_and_run(script)
-        } else {
-            state.koto.compile_and_run(script)
+        memory::begin_tracking();
+        let export_top_level_ids = session_id.is_some();
+        let cached_chunk = state.compile_cache.get(script, export_top_level_ids);
+        let cache_hit = cached_chunk.is_some();
+        let chunk = match cached_chunk {
+            Some(chunk) => Ok(chunk),
+            None => {
+                let compiled = state.koto.compile(CompileArgs {
+                    script,
+                    script_path: None,
+                    compiler_settings: CompilerSettings {
+                        export_top_level_ids,
+                        ..Default::default()
+                    },
+                });
+                if let Ok(chunk) = &compiled {
+                    state
+                        .compile_cache
+                        .insert(script, export_top_level_ids, chunk.clone());
+                }
+                compiled
+            }
         };
+        let compile_time = start.elapsed();
+        let result = match chunk {
+            Ok(chunk) if profiling_enabled => {
+                #[cfg(feature = "profiling")]
+                profiling::scope!("koto_script");
+                state.koto.run(chunk)
+            }
+            Ok(chunk) => state.koto.run(chunk),
+            Err(error) => Err(error),
+        };
+        let result = match result {
+            Ok(value) if options.daemon => {
+                event_loop::run(&mut state.koto, &self.host_state).map(|()| value)
+            }
+            result => result,
+        };
+        self.host_state.workers.stop_unjoined();
+        self.host_state.gfx.present_pending();
+        let compile_cache = state.compile_cache.stats();
+        let peak_memory = memory::end_tracking();
         let duration = start.elapsed();
+        let execute_time = duration.saturating_sub(compile_time);
         let stdout = self.stdout.take();
         let stderr = self.stderr.take();
+        let clipboard_accessed = self.host_state.clipboard_accessed.load(Ordering::SeqCst);
+        let generator_trace = self.host_state.take_generator_trace();
+        let workers = self.host_state.workers.take_outputs();
+        let frame = self.host_state.gfx.take_presented();
+        let tones = self
+            .host_state
+            .tones
+            .lock()
+            .map(|mut tones| std::mem::take(&mut *tones))
+            .unwrap_or_default();
+        let random_seed = self
+            .host_state
+            .random_used
+            .load(Ordering::SeqCst)
+            .then_some(initial_seed);
+        let cancelled = self.host_state.is_cancelled();
+        self.host_state.end_run();
+        state.pop_prelude_bindings(shadowed);
+        if let Some(id) = session_id {
+            // Bindings made before an error are kept, as they would be in a REPL.
+            let bindings = std::mem::take(&mut *state.koto.exports().data_mut());
+            state.sessions.insert(id.to_string(), bindings);
+        }
+
+        // Scripts that never call into the host can only be caught once they finish, or once
+        // the time limit stops them, so whatever ended the run, going over the limit is what
+        // gets reported.
+        let memory_exceeded = state
+            .config
+            .memory_limit
+            .filter(|limit| peak_memory > *limit && !cancelled);
+        let result = match memory_exceeded {
+            Some(limit) => Err(memory_limit_message(peak_memory, limit)),
+            None => result.map_err(|error| error.to_string()),
+        };
+        let peak_memory = memory::is_tracking_available().then_some(peak_memory);
 
         match result {
             Ok(value) => {
                 let (output, value) = if matches!(value, KValue::Null) {
                     (None, None)
                 } else {
-                    let rendered = state.koto.value_to_string(value.clone())?;
+                    let format = self.host_state.number_format();
+                    let rendered = match (!format.is_default())
+                        .then(|| format.render(&value))
+                        .flatten()
+                    {
+                        Some(rendered) => rendered,
+                        None => state.koto.value_to_string(value.clone())?,
+                    };
                     (Some(rendered), Some(value))
                 };
                 logging::with_runtime_subscriber(|| {
-                    tracing::info!(target: "runtime.vm", elapsed_ms = duration.as_millis() as u64, "Script completed");
+                    tracing::info!(
+                        target: "runtime.vm",
+                        elapsed_ms = duration.as_millis() as u64,
+                        compile_us = compile_time.as_micros() as u64,
+                        execute_us = execute_time.as_micros() as u64,
+                        cache_hit,
+                        "Script completed"
+                    );
                 });
-                Ok(ExecutionOutput {
+                let output = ExecutionOutput {
                     return_value: output,
                     stdout,
                     stderr,
                     duration,
+                    compile_time,
+                    execute_time,
                     value,
-                })
+                    clipboard_accessed,
+                    generator_trace,
+                    random_seed,
+                    peak_memory,
+                    cache_hit,
+                    compile_cache,
+                    workers,
+                    frame,
+                    tones,
+                };
+                self.observers
+                    .notify(|observer| observer.on_complete(&output));
+                Ok(output)
             }
-            Err(error) => {
+            Err(message) => {
                 logging::with_runtime_subscriber(|| {
-                    tracing::error!(target: "runtime.vm", %error, "Script error");
+                    tracing::error!(target: "runtime.vm", error = %message, "Script error");
                 });
-                Err(anyhow!("{error}"))
+                self.observers
+                    .notify(|observer| observer.on_error(&message));
+                // Koto reports the limit as an ordinary runtime error, so it's recognised by its
+                // message, the same way test cases recognise it.
+                match timeout {
+                    Some(limit)
+                        if is_execution_limit_error(&message)
+                            && !cancelled
+                            && memory_exceeded.is_none() =>
+                    {
+                        Err(TimeoutError {
+                            message,
+                            limit,
+                            elapsed: duration,
+                            stdout,
+                            stderr,
+                        }
+                        .into())
+                    }
+                    _ => Err(anyhow!("{message}")),
+                }
             }
         }
     }
@@ -204,6 +986,24 @@ impl Runtime {
         f(&mut state.koto)
     }
 
+    /// Gives host calls made through [`Runtime::with_koto`] a deadline `limit` from now, the
+    /// way a run's timeout does, so waits such as `host.sleep_ms` fail once it passes. Lasts
+    /// until [`Runtime::end_deadline`].
+    pub fn begin_deadline(&self, limit: Duration) {
+        let started = Instant::now();
+        if let Ok(mut window) = self.host_state.run_window.lock() {
+            *window = Some(RunWindow {
+                started,
+                deadline: Some(started + limit),
+                memory_limit: None,
+            });
+        }
+    }
+
+    pub fn end_deadline(&self) {
+        self.host_state.end_run();
+    }
+
     pub fn clear_output(&self) {
         self.stdout.clear();
         self.stderr.clear();
@@ -231,6 +1031,218 @@ impl Runtime {
         Ok(())
     }
 
+    /// Aborts runs that hold more than `limit` bytes. The limit is checked whenever the script
+    /// calls into the host (printing, `host.sleep_ms`, ...) and once more when it finishes, so
+    /// a loop that allocates without calling into the host only ends at the run's timeout, if
+    /// it has one. Only the thread running the script is counted, not `concurrent` workers.
+    /// Requires [`memory::TrackingAllocator`] to be the global allocator.
+    pub fn set_memory_limit(&self, limit: Option<usize>) -> anyhow::Result<()> {
+        let mut state = self.lock_state()?;
+        state.config.memory_limit = limit;
+        let available = memory::is_tracking_available();
+        logging::with_runtime_subscriber(|| {
+            tracing::info!(target: "runtime.vm", limit_bytes = limit, "Memory limit updated");
+            if limit.is_some() && !available {
+                tracing::warn!(
+                    target: "runtime.vm",
+                    "Memory limit set without the tracking allocator; it will not be enforced"
+                );
+            }
+        });
+        Ok(())
+    }
+
+    pub fn memory_limit(&self) -> anyhow::Result<Option<usize>> {
+        Ok(self.lock_state()?.config.memory_limit)
+    }
+
+    pub fn compile_cache_stats(&self) -> anyhow::Result<CompileCacheStats> {
+        Ok(self.lock_state()?.compile_cache.stats())
+    }
+
+    /// Compiles `script` into the compile cache so its next run skips compilation, returning
+    /// an artifact that can be stored in a [`precompile::DiskCache`]. The runtime's own
+    /// compiler does the work, so warming up scripts doesn't hold up runs; only adding the
+    /// chunk to the cache waits for a running script to finish.
+    pub fn precompile(&self, script: &str) -> anyhow::Result<precompile::PrecompiledScript> {
+        let start = Instant::now();
+        let chunk = self
+            .compiler
+            .lock()
+            .map_err(|error| anyhow!("Failed to lock the compiler: {error}"))?
+            .compile(CompileArgs {
+                script,
+                script_path: None,
+                compiler_settings: CompilerSettings::default(),
+            })
+            .map_err(|error| anyhow!("{error}"))?;
+        let compile_time = start.elapsed();
+        self.lock_state()?
+            .compile_cache
+            .insert(script, false, chunk.clone());
+        Ok(precompile::PrecompiledScript::new(
+            script,
+            &chunk.bytes,
+            compile_time,
+        ))
+    }
+
+    /// Compiles `script` without running or caching it, e.g. to check pasted code. A
+    /// separate VM is used, so the check doesn't wait for a running script.
+    pub fn check_syntax(script: &str) -> anyhow::Result<()> {
+        Koto::default()
+            .compile(CompileArgs {
+                script,
+                script_path: None,
+                compiler_settings: CompilerSettings::default(),
+            })
+            .map(|_| ())
+            .map_err(|error| anyhow!("{error}"))
+    }
+
+    /// Drops the chunk cached for `script`, so its next run compiles it again. Returns
+    /// whether the script was cached.
+    pub fn forget_compiled(&self, script: &str) -> anyhow::Result<bool> {
+        Ok(self.lock_state()?.compile_cache.remove(script, false))
+    }
+
+    /// Drops every cached chunk, e.g. to measure cold compilation.
+    pub fn clear_compile_cache(&self) -> anyhow::Result<()> {
+        self.lock_state()?.compile_cache.clear();
+        logging::with_runtime_subscriber(|| {
+            tracing::info!(target: "runtime.vm", "Compile cache cleared");
+        });
+        Ok(())
+    }
+
+    /// Makes every run start `host.random` from `seed`. With `None`, runs use a fresh seed,
+    /// or [`random::DETERMINISTIC_SEED`] in deterministic mode.
+    pub fn set_random_seed(&self, seed: Option<u64>) {
+        if let Ok(mut current) = self.host_state.random_seed.lock() {
+            *current = seed;
+        }
+    }
+
+    /// Changes how numbers are shown in return values and generator traces.
+    pub fn set_number_format(&self, format: number_format::NumberFormat) {
+        if let Ok(mut current) = self.host_state.number_format.write() {
+            *current = format;
+        }
+    }
+
+    pub fn number_format(&self) -> number_format::NumberFormat {
+        self.host_state.number_format()
+    }
+
+    /// Sets the directory `host.fs` reads and writes in; `None` makes every `host.fs` call
+    /// fail. See [`scratch::scratch_dir_for`] for the per-example directories the explorer uses.
+    pub fn set_scratch_dir(&self, dir: Option<PathBuf>) {
+        logging::with_runtime_subscriber(|| {
+            tracing::info!(target: "runtime.vm", dir = ?dir, "Scratch directory updated");
+        });
+        if let Ok(mut scratch_dir) = self.host_state.scratch_dir.write() {
+            *scratch_dir = dir;
+        }
+    }
+
+    /// Runs every [`audit::probes`] script under each profile in [`audit::AUDITED_PROFILES`]
+    /// and reports which operations the sandbox blocked. The profile and scratch directory
+    /// are restored afterwards.
+    pub fn run_sandbox_audit(&self) -> audit::AuditReport {
+        let previous_profile = self.profile();
+        let previous_scratch_dir = self.host_state.scratch_dir();
+        self.set_scratch_dir(Some(scratch::scratch_dir_for("sandbox-audit")));
+
+        let options = RunOptions::new().timeout(Some(audit::PROBE_TIMEOUT));
+        let mut report = audit::AuditReport::default();
+        for profile in audit::AUDITED_PROFILES {
+            self.set_profile(profile);
+            for probe in audit::probes() {
+                let result = self.execute(probe.script, &options).map(|_| ());
+                report
+                    .findings
+                    .push(audit::AuditFinding::new(probe, profile, result));
+            }
+        }
+
+        self.set_profile(previous_profile);
+        self.set_scratch_dir(previous_scratch_dir);
+        let failures = report.failures().count();
+        logging::with_runtime_subscriber(|| {
+            tracing::info!(
+                target: "runtime.vm",
+                probes = report.findings.len(),
+                failures,
+                "Sandbox audit finished"
+            );
+        });
+        report
+    }
+
+    /// Runs `script` under `left` and then `right`, each in a freshly built VM so neither
+    /// sees globals left by the other. The profile and deterministic mode are restored
+    /// afterwards.
+    pub fn compare_setups(
+        &self,
+        script: &str,
+        options: &RunOptions,
+        left: profile_compare::RunSetup,
+        right: profile_compare::RunSetup,
+    ) -> anyhow::Result<profile_compare::ProfileComparison> {
+        let previous_profile = self.profile();
+        let previous_deterministic = self.is_deterministic();
+
+        let run = |setup: profile_compare::RunSetup| -> anyhow::Result<_> {
+            self.reset_vm()?;
+            self.set_profile(setup.profile);
+            self.set_deterministic(setup.deterministic);
+            Ok(profile_compare::SetupRun::new(
+                setup,
+                self.execute(script, options),
+            ))
+        };
+        let comparison = run(left).and_then(|left| {
+            Ok(profile_compare::ProfileComparison {
+                left,
+                right: run(right)?,
+            })
+        });
+
+        self.set_profile(previous_profile);
+        self.set_deterministic(previous_deterministic);
+        comparison
+    }
+
+    /// Queues a keyboard or pointer event for the running script's `input` module. Events sent
+    /// while nothing is running are dropped, so a run never sees input meant for an earlier one.
+    pub fn push_input_event(&self, event: input::InputEvent) {
+        let running = self
+            .host_state
+            .run_window
+            .lock()
+            .map(|window| window.is_some())
+            .unwrap_or(false);
+        if running {
+            self.host_state.input.push(event);
+        }
+    }
+
+    /// Sets the text scripts read through `io.stdin`. Every later run reads it from the
+    /// beginning; pass an empty string to give scripts an empty stdin.
+    pub fn set_stdin(&self, input: impl Into<String>) {
+        let input = input.into();
+        let len = input.len();
+        if let Ok(mut stdin) = self.host_state.stdin.lock() {
+            *stdin = StdinBuffer {
+                text: input,
+                position: 0,
+            };
+        }
+        logging::with_runtime_subscriber(|| {
+            tracing::info!(target: "runtime.vm", len, "Stdin updated");
+        });
+    }
+
     pub fn set_profiling_enabled(&self, enabled: bool) {
         self.profiling_enabled.store(enabled, Ordering::SeqCst);
         logging::with_runtime_subscriber(|| {
@@ -238,6 +1250,163 @@ impl Runtime {
         });
     }
 
+    /// Replaces the set of environment variables that scripts may read through `env.get`.
+    pub fn set_env_allowlist<I, S>(&self, names: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let names: BTreeSet<String> = names
+            .into_iter()
+            .map(Into::into)
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect();
+        let count = names.len();
+        if let Ok(mut guard) = self.host_state.env_allowlist.write() {
+            *guard = names;
+        }
+        logging::with_runtime_subscriber(|| {
+            tracing::info!(target: "runtime.vm", count, "Environment allowlist updated");
+        });
+    }
+
+    pub fn env_allowlist(&self) -> Vec<String> {
+        self.host_state.env_allowlist()
+    }
+
+    pub fn set_profile(&self, profile: RuntimeProfile) {
+        if let Ok(mut guard) = self.host_state.profile.write() {
+            *guard = profile;
+        }
+        logging::with_runtime_subscriber(|| {
+            tracing::info!(target: "runtime.vm", profile = profile.label(), "Runtime profile updated");
+        });
+    }
+
+    pub fn profile(&self) -> RuntimeProfile {
+        self.host_state.profile()
+    }
+
+    /// Makes timing helpers reproducible: `host.sleep_ms` returns immediately after advancing a
+    /// virtual clock, and `host.elapsed` reports that clock instead of wall time.
+    pub fn set_deterministic(&self, enabled: bool) {
+        self.host_state
+            .deterministic
+            .store(enabled, Ordering::SeqCst);
+        logging::with_runtime_subscriber(|| {
+            tracing::info!(target: "runtime.vm", enabled, "Deterministic mode updated");
+        });
+    }
+
+    pub fn is_deterministic(&self) -> bool {
+        self.host_state.is_deterministic()
+    }
+
+    /// Lets `audio` tones reach the speakers. Off by default, and always off in builds without
+    /// the `audio` feature; scripts take the same time either way.
+    pub fn set_audio_enabled(&self, enabled: bool) {
+        let enabled = enabled && cfg!(feature = "audio");
+        self.host_state
+            .audio_enabled
+            .store(enabled, Ordering::SeqCst);
+        logging::with_runtime_subscriber(|| {
+            tracing::info!(target: "runtime.vm", enabled, "Audio output updated");
+        });
+    }
+
+    pub fn audio_enabled(&self) -> bool {
+        self.host_state.audio_enabled()
+    }
+
+    /// Registers an observer that is notified about every subsequent run; see [`observer`].
+    pub fn add_observer(&self, observer: impl ExecutionObserver + 'static) -> ObserverId {
+        self.observers.add(Arc::new(observer))
+    }
+
+    /// Removes a previously registered observer, returning `false` if it wasn't registered.
+    pub fn remove_observer(&self, id: ObserverId) -> bool {
+        self.observers.remove(id)
+    }
+
+    /// Asks host functions that wait (such as `host.sleep_ms`) or write output to abort the
+    /// run currently holding the VM, whichever it is. Use [`ExecutionHandle::cancel`] to stop a
+    /// particular run.
+    pub fn request_cancel(&self) {
+        self.host_state.cancel_current_run();
+        logging::with_runtime_subscriber(|| {
+            tracing::info!(target: "runtime.vm", "Cancellation requested");
+        });
+    }
+
+    /// Lists every registered host binding along with the members of module bindings,
+    /// e.g. `host`, `host.sleep_ms`, `serde.to_json`.
+    pub fn host_binding_names(&self) -> anyhow::Result<Vec<String>> {
+        let state = self.lock_state()?;
+        let mut names = Vec::new();
+        for (name, value) in &state.host_bindings {
+            names.push(name.clone());
+            if let KValue::Map(module) = value {
+                for (key, _) in module.data().iter() {
+                    names.push(format!("{name}.{key}"));
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// Describes every top-level binding in the prelude, core library modules included,
+    /// sorted by name.
+    pub fn prelude_entries(&self) -> anyhow::Result<Vec<prelude::PreludeEntry>> {
+        let state = self.lock_state()?;
+        let mut entries: Vec<_> = state
+            .koto
+            .prelude()
+            .data()
+            .iter()
+            .map(|(key, value)| {
+                let name = key.to_string();
+                let source = if state.host_bindings.contains_key(&name) || name == "args" {
+                    prelude::PreludeSource::Host
+                } else {
+                    prelude::PreludeSource::Core
+                };
+                prelude::describe(&name, value, source)
+            })
+            .collect();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(entries)
+    }
+
+    /// Builds a script that calls the host function at `path` (e.g. `host.elapsed` or a
+    /// plugin's `greet`) with `args`, each of which is a Koto expression such as `42` or
+    /// `'text'`.
+    pub fn host_call_script(&self, path: &str, args: &[&str]) -> anyhow::Result<String> {
+        if !self.host_binding_names()?.iter().any(|name| name == path) {
+            return Err(anyhow!("'{path}' is not a registered host binding"));
+        }
+        let args = args
+            .iter()
+            .map(|arg| arg.trim())
+            .map(|arg| {
+                if arg.is_empty() {
+                    Err(anyhow!("Arguments can't be empty"))
+                } else {
+                    Ok(format!("({arg})"))
+                }
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(format!("{path}({})", args.join(", ")))
+    }
+
+    /// Calls a host function directly, e.g. to smoke-test a plugin's bindings; see
+    /// [`Runtime::host_call_script`].
+    pub fn call_host_function(&self, path: &str, args: &[&str]) -> anyhow::Result<ExecutionOutput> {
+        let script = self.host_call_script(path, args)?;
+        self.execute_script(&script)
+    }
+
     pub fn register_host_function<F>(&self, name: &str, function: F) -> anyhow::Result<()>
     where
         F: Fn(&mut CallContext) -> KotoRuntimeResult<KValue> + KotoSend + KotoSync + 'static,
@@ -260,6 +1429,7 @@ impl Runtime {
         Ok(())
     }
 
+    #[cfg(feature = "plugins")]
     pub fn load_shared_library(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
         let path = path.as_ref();
         let library = unsafe { Library::new(path) }
@@ -288,6 +1458,15 @@ impl Runtime {
         Ok(())
     }
 
+    /// Without the `plugins` feature native modules can't be loaded, so this always fails.
+    #[cfg(not(feature = "plugins"))]
+    pub fn load_shared_library(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        Err(anyhow!(
+            "Can't load {:?}: koto_learning was built without the `plugins` feature",
+            path.as_ref()
+        ))
+    }
+
     fn lock_state(&self) -> anyhow::Result<std::sync::MutexGuard<'_, RuntimeState>> {
         self.state
             .lock()
@@ -301,38 +1480,76 @@ impl RuntimeState {
         stdout: &BufferHandle,
         stderr: &BufferHandle,
         profiling_flag: &Arc<AtomicBool>,
+        host_state: &Arc<HostState>,
     ) -> anyhow::Result<Self> {
         let mut state = Self {
-            koto: Self::build_koto(&config, stdout, stderr),
+            koto: Self::build_koto(&config, stdout, stderr, host_state),
             config,
             host_bindings: HashMap::new(),
+            compile_cache: CompileCache::default(),
+            sessions: HashMap::new(),
+            #[cfg(feature = "plugins")]
             shared_libraries: Vec::new(),
             profiling_flag: profiling_flag.clone(),
+            host_state: host_state.clone(),
         };
         state.register_builtin_modules()?;
         Ok(state)
     }
 
-    fn build_koto(config: &RuntimeConfig, stdout: &BufferHandle, stderr: &BufferHandle) -> Koto {
+    fn build_koto(
+        config: &RuntimeConfig,
+        stdout: &BufferHandle,
+        stderr: &BufferHandle,
+        host_state: &Arc<HostState>,
+    ) -> Koto {
         let mut settings = KotoSettings::default();
         settings.run_tests = config.run_tests;
         if let Some(limit) = config.execution_limit {
             settings = settings.with_execution_limit(limit);
         }
         settings = settings
+            .with_stdin(StdinFile {
+                host_state: Arc::clone(host_state),
+            })
             .with_stdout(stdout.file())
             .with_stderr(stderr.file());
         Koto::with_settings(settings)
     }
 
     fn rebuild_vm(&mut self, stdout: &BufferHandle, stderr: &BufferHandle) {
-        self.koto = Self::build_koto(&self.config, stdout, stderr);
+        self.koto = Self::build_koto(&self.config, stdout, stderr, &self.host_state);
         self.apply_host_bindings();
     }
 
     fn register_builtin_modules(&mut self) -> anyhow::Result<()> {
-        self.register_host_value("host".to_string(), host_module(self.profiling_flag.clone()));
+        self.register_host_value(
+            "host".to_string(),
+            host_module(self.profiling_flag.clone(), self.host_state.clone()),
+        );
         self.register_host_value("serde".to_string(), serialization_module()?);
+        self.register_host_value("env".to_string(), env::env_module(self.host_state.clone()));
+        self.register_host_value(
+            "clipboard".to_string(),
+            clipboard::clipboard_module(self.host_state.clone()),
+        );
+        self.register_host_value(
+            "process".to_string(),
+            process::process_module(self.host_state.clone()),
+        );
+        self.register_host_value(
+            "concurrent".to_string(),
+            concurrent::concurrent_module(self.host_state.clone()),
+        );
+        self.register_host_value("gfx".to_string(), gfx::gfx_module(self.host_state.clone()));
+        self.register_host_value(
+            "input".to_string(),
+            input::input_module(self.host_state.clone()),
+        );
+        self.register_host_value(
+            "audio".to_string(),
+            audio::audio_module(self.host_state.clone()),
+        );
         Ok(())
     }
 
@@ -342,6 +1559,70 @@ impl RuntimeState {
         prelude.insert(name.as_str().into(), value);
     }
 
+    fn resolve_run_bindings(
+        &self,
+        bindings: &[(String, RunBinding)],
+    ) -> anyhow::Result<Vec<(String, KValue)>> {
+        bindings
+            .iter()
+            .map(|(name, binding)| {
+                let value = match binding {
+                    RunBinding::Value(json) => koto::serde::to_koto_value(json.clone())
+                        .map_err(|error| anyhow!("Invalid value for binding '{name}': {error}"))?,
+                    RunBinding::Alias(path) => self
+                        .resolve_prelude_path(path)
+                        .ok_or_else(|| anyhow!("Binding '{name}' refers to unknown '{path}'"))?,
+                };
+                Ok((name.clone(), value))
+            })
+            .collect()
+    }
+
+    /// Looks up a dotted path such as `string.to_uppercase` in the prelude.
+    fn resolve_prelude_path(&self, path: &str) -> Option<KValue> {
+        let mut segments = path.split('.');
+        let first = ValueKey::from(segments.next()?);
+        let mut value = self.koto.prelude().data().get(&first).cloned()?;
+        for segment in segments {
+            let KValue::Map(map) = &value else {
+                return None;
+            };
+            let next = map.data().get(&ValueKey::from(segment)).cloned()?;
+            value = next;
+        }
+        Some(value)
+    }
+
+    /// Adds per-run bindings to the prelude, returning what they replaced so that
+    /// [`Self::pop_prelude_bindings`] can restore it.
+    fn push_prelude_bindings(
+        &mut self,
+        bindings: Vec<(String, KValue)>,
+    ) -> Vec<(String, Option<KValue>)> {
+        let mut prelude = self.koto.prelude().data_mut();
+        bindings
+            .into_iter()
+            .map(|(name, value)| {
+                let previous = prelude.insert(name.as_str().into(), value);
+                (name, previous)
+            })
+            .collect()
+    }
+
+    fn pop_prelude_bindings(&mut self, shadowed: Vec<(String, Option<KValue>)>) {
+        let mut prelude = self.koto.prelude().data_mut();
+        for (name, previous) in shadowed.into_iter().rev() {
+            match previous {
+                Some(value) => {
+                    prelude.insert(name.as_str().into(), value);
+                }
+                None => {
+                    prelude.shift_remove(&ValueKey::from(name.as_str()));
+                }
+            }
+        }
+    }
+
     fn apply_host_bindings(&mut self) {
         let mut prelude = self.koto.prelude().data_mut();
         for (name, value) in &self.host_bindings {
@@ -350,18 +1631,228 @@ impl RuntimeState {
     }
 }
 
+impl HostState {
+    fn begin_run(
+        &self,
+        limit: Option<Duration>,
+        memory_limit: Option<usize>,
+        cancel: Arc<AtomicBool>,
+    ) {
+        self.clipboard_accessed.store(false, Ordering::SeqCst);
+        if let Ok(mut flag) = self.cancel_requested.write() {
+            *flag = cancel;
+        }
+        self.take_generator_trace();
+        if let Ok(mut clock) = self.virtual_clock.lock() {
+            *clock = Duration::ZERO;
+        }
+        if let Ok(mut stdin) = self.stdin.lock() {
+            stdin.position = 0;
+        }
+        let seed = self
+            .random_seed
+            .lock()
+            .ok()
+            .and_then(|seed| *seed)
+            .or_else(|| {
+                self.is_deterministic()
+                    .then_some(random::DETERMINISTIC_SEED)
+            })
+            .unwrap_or_else(random::fresh_seed);
+        if let Ok(mut rng) = self.random.lock() {
+            rng.reseed(seed);
+        }
+        self.random_used.store(false, Ordering::SeqCst);
+        self.workers.begin_run();
+        self.gfx.begin_run();
+        self.input.begin_run();
+        if let Ok(mut tones) = self.tones.lock() {
+            tones.clear();
+        }
+        let started = Instant::now();
+        if let Ok(mut window) = self.run_window.lock() {
+            *window = Some(RunWindow {
+                started,
+                deadline: limit.map(|limit| started + limit),
+                memory_limit,
+            });
+        }
+    }
+
+    fn end_run(&self) {
+        if let Ok(mut window) = self.run_window.lock() {
+            *window = None;
+        }
+        // A handle cancelled after its run ended mustn't reach calls made between runs.
+        if let Ok(mut flag) = self.cancel_requested.write() {
+            *flag = Arc::default();
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel_requested
+            .read()
+            .is_ok_and(|flag| flag.load(Ordering::SeqCst))
+    }
+
+    fn cancel_current_run(&self) {
+        if let Ok(flag) = self.cancel_requested.read() {
+            flag.store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn run_elapsed(&self) -> Option<Duration> {
+        if self.is_deterministic() {
+            return self.virtual_clock.lock().ok().map(|clock| *clock);
+        }
+        self.run_window
+            .lock()
+            .ok()
+            .and_then(|window| window.map(|window| window.started.elapsed()))
+    }
+
+    fn is_deterministic(&self) -> bool {
+        self.deterministic.load(Ordering::SeqCst)
+    }
+
+    fn audio_enabled(&self) -> bool {
+        self.audio_enabled.load(Ordering::SeqCst)
+    }
+
+    fn advance_virtual_clock(&self, duration: Duration) {
+        if let Ok(mut clock) = self.virtual_clock.lock() {
+            *clock += duration;
+        }
+    }
+
+    fn record_generator_event(&self, event: generators::GeneratorEvent) {
+        if let Ok(mut trace) = self.generator_trace.lock() {
+            trace.push(event);
+        }
+    }
+
+    fn take_generator_trace(&self) -> Vec<generators::GeneratorEvent> {
+        self.generator_trace
+            .lock()
+            .map(|mut trace| std::mem::take(&mut *trace))
+            .unwrap_or_default()
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.run_window
+            .lock()
+            .ok()
+            .and_then(|window| window.and_then(|window| window.deadline))
+    }
+
+    /// Called whenever a script interacts with the host, which is where a run can be stopped
+    /// early because it was cancelled or went over its memory limit.
+    fn check_interrupts(&self) -> KotoRuntimeResult<()> {
+        if self.is_cancelled() {
+            return runtime_error!("Script execution was cancelled");
+        }
+        if self.workers.is_stopping() && concurrent::current_worker().is_some() {
+            return runtime_error!("Worker stopped because its script finished");
+        }
+        let memory_limit = self
+            .run_window
+            .lock()
+            .ok()
+            .and_then(|window| window.and_then(|window| window.memory_limit));
+        if let Some(limit) = memory_limit {
+            let used = memory::current_usage();
+            if used > limit {
+                return runtime_error!("{}", memory_limit_message(used, limit));
+            }
+        }
+        Ok(())
+    }
+
+    fn random_seed_in_use(&self) -> u64 {
+        self.random.lock().map(|rng| rng.seed()).unwrap_or_default()
+    }
+
+    fn number_format(&self) -> number_format::NumberFormat {
+        self.number_format
+            .read()
+            .map(|format| *format)
+            .unwrap_or_default()
+    }
+
+    fn scratch_dir(&self) -> Option<PathBuf> {
+        self.scratch_dir.read().ok().and_then(|dir| dir.clone())
+    }
+
+    fn profile(&self) -> RuntimeProfile {
+        self.profile
+            .read()
+            .map(|profile| *profile)
+            .unwrap_or_default()
+    }
+
+    fn env_allowlist(&self) -> Vec<String> {
+        self.env_allowlist
+            .read()
+            .map(|names| names.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// The runtime allowlist together with the names the current run adds, sorted.
+    fn env_allowed_names(&self) -> Vec<String> {
+        let mut names: BTreeSet<String> = self.env_allowlist().into_iter().collect();
+        if let Ok(run_names) = self.run_env_allowlist.read() {
+            names.extend(run_names.iter().cloned());
+        }
+        names.into_iter().collect()
+    }
+
+    fn env_allowed(&self, name: &str) -> bool {
+        let in_list = |list: &RwLock<BTreeSet<String>>| {
+            list.read()
+                .map(|names| names.contains(name))
+                .unwrap_or(false)
+        };
+        in_list(&self.env_allowlist) || in_list(&self.run_env_allowlist)
+    }
+
+    /// Starts a run's view of the environment: its extra allowed names, and no overrides.
+    fn set_run_env_allowlist(&self, names: &[String]) {
+        if let Ok(mut allowlist) = self.run_env_allowlist.write() {
+            *allowlist = names
+                .iter()
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect();
+        }
+        if let Ok(mut overrides) = self.env_overrides.lock() {
+            overrides.clear();
+        }
+    }
+}
+
 impl BufferHandle {
-    fn new(id: &str) -> Self {
+    fn new(
+        id: &str,
+        stream: OutputStream,
+        observers: &Arc<Observers>,
+        host_state: &Arc<HostState>,
+    ) -> Self {
         Self {
             id: KString::from(id),
+            stream,
             buffer: Arc::new(Mutex::new(String::new())),
+            observers: Arc::clone(observers),
+            host_state: Arc::clone(host_state),
         }
     }
 
     fn file(&self) -> BufferFile {
         BufferFile {
             id: self.id.clone(),
+            stream: self.stream,
             buffer: Arc::clone(&self.buffer),
+            observers: Arc::clone(&self.observers),
+            host_state: Arc::clone(&self.host_state),
         }
     }
 
@@ -390,10 +1881,21 @@ impl KotoFile for BufferFile {
 
 impl KotoWrite for BufferFile {
     fn write(&self, bytes: &[u8]) -> KotoRuntimeResult<()> {
+        // Output is the most common host interaction, so it doubles as a cancellation point.
+        self.host_state.check_interrupts()?;
         let text = String::from_utf8_lossy(bytes);
         if let Ok(mut guard) = self.buffer.lock() {
             guard.push_str(&text);
         }
+        match concurrent::current_worker() {
+            Some(worker) => {
+                self.host_state
+                    .workers
+                    .record_output(&worker, self.stream, &text);
+                self.observers.worker_output(&worker, self.stream, &text);
+            }
+            None => self.observers.output(self.stream, &text),
+        }
         Ok(())
     }
 
@@ -409,7 +1911,45 @@ impl KotoWrite for BufferFile {
 
 impl KotoRead for BufferFile {}
 
-fn host_module(profiling_flag: Arc<AtomicBool>) -> KValue {
+impl KotoFile for StdinFile {
+    fn id(&self) -> KString {
+        KString::from("stdin")
+    }
+}
+
+impl KotoRead for StdinFile {
+    fn read_line(&self) -> KotoRuntimeResult<Option<String>> {
+        self.host_state.check_interrupts()?;
+        let Ok(mut stdin) = self.host_state.stdin.lock() else {
+            return runtime_error!("Failed to lock stdin");
+        };
+        let remaining = &stdin.text[stdin.position..];
+        if remaining.is_empty() {
+            return Ok(None);
+        }
+        let line_len = remaining
+            .find('\n')
+            .map(|index| index + 1)
+            .unwrap_or(remaining.len());
+        let line = remaining[..line_len].to_string();
+        stdin.position += line_len;
+        Ok(Some(line))
+    }
+
+    fn read_to_string(&self) -> KotoRuntimeResult<String> {
+        self.host_state.check_interrupts()?;
+        let Ok(mut stdin) = self.host_state.stdin.lock() else {
+            return runtime_error!("Failed to lock stdin");
+        };
+        let rest = stdin.text[stdin.position..].to_string();
+        stdin.position = stdin.text.len();
+        Ok(rest)
+    }
+}
+
+impl KotoWrite for StdinFile {}
+
+fn host_module(profiling_flag: Arc<AtomicBool>, host_state: Arc<HostState>) -> KValue {
     let module = KMap::default();
     module.insert("version", env!("CARGO_PKG_VERSION"));
     module.insert(
@@ -498,9 +2038,83 @@ fn host_module(profiling_flag: Arc<AtomicBool>) -> KValue {
     };
 
     module.insert("performance", performance);
+    #[cfg(feature = "encoding")]
+    encoding::register(&module);
+    #[cfg(not(feature = "encoding"))]
+    {
+        let encoding = KMap::default();
+        features::register_unavailable(
+            &encoding,
+            "host.encoding",
+            &[
+                "base64_encode",
+                "base64_decode",
+                "base64_decode_bytes",
+                "hex_encode",
+                "hex_decode",
+                "hex_decode_bytes",
+                "sha256",
+                "md5",
+            ],
+            "encoding",
+        );
+        module.insert("encoding", encoding);
+    }
+    timers::register(&module, host_state.clone());
+    random::register(&module, host_state.clone());
+    scratch::register(&module, host_state.clone());
+    event_loop::register(&module, host_state.clone());
+    generators::register(&module, host_state);
     module.into()
 }
 
+/// Renders a short, VM-free description of a value for traces and inspectors.
+fn value_preview(value: &KValue, format: &number_format::NumberFormat) -> String {
+    match value {
+        KValue::Null => "null".to_string(),
+        KValue::Bool(flag) => flag.to_string(),
+        KValue::Number(number) if format.is_default() => number.to_string(),
+        KValue::Number(number) => format.format(number),
+        KValue::Str(text) => format!("'{}'", text.as_str()),
+        KValue::List(list) => format!("[{} items]", list.len()),
+        KValue::Tuple(tuple) => format!("({} items)", tuple.len()),
+        KValue::Map(map) => format!("{{{} entries}}", map.len()),
+        other => format!("<{}>", other.type_as_string()),
+    }
+}
+
+fn memory_limit_message(used: usize, limit: usize) -> String {
+    format!(
+        "Memory limit exceeded: the script used {} (limit {})",
+        memory::format_bytes(used),
+        memory::format_bytes(limit)
+    )
+}
+
+fn number_to_i64(number: &KNumber) -> i64 {
+    match number {
+        KNumber::I64(value) => *value,
+        KNumber::F64(value) => value.trunc() as i64,
+    }
+}
+
+fn number_to_f64(number: &KNumber) -> f64 {
+    match number {
+        KNumber::I64(value) => *value as f64,
+        KNumber::F64(value) => *value,
+    }
+}
+
+/// Bytes as a Koto list of numbers, the form binary encodings and decoders return.
+#[cfg(any(feature = "serialization", feature = "encoding"))]
+fn byte_list(bytes: &[u8]) -> KValue {
+    let values: Vec<KValue> = bytes
+        .iter()
+        .map(|byte| KValue::from(*byte as i64))
+        .collect();
+    KValue::List(KList::from_slice(&values))
+}
+
 fn serialization_module() -> anyhow::Result<KValue> {
     let module = KMap::default();
     module.insert(
@@ -533,6 +2147,75 @@ fn serialization_module() -> anyhow::Result<KValue> {
             other => runtime_error!("Expected JSON string, found {other:?}"),
         }),
     );
+    register_yaml(&module);
+    #[cfg(feature = "serialization")]
+    {
+        register_ron(&module);
+        tabular::register(&module);
+        binary::register(&module);
+        xml::register(&module);
+    }
+    #[cfg(not(feature = "serialization"))]
+    features::register_unavailable(
+        &module,
+        "serde",
+        &[
+            "to_ron",
+            "from_ron",
+            "to_csv",
+            "from_csv",
+            "to_msgpack",
+            "from_msgpack",
+            "to_cbor",
+            "from_cbor",
+            "to_xml",
+            "from_xml",
+        ],
+        "serialization",
+    );
+    schema::register(&module);
+    Ok(module.into())
+}
+
+/// Adds `to_ron` and `from_ron` to the `serde` module.
+#[cfg(feature = "serialization")]
+fn register_ron(module: &KMap) {
+    module.insert(
+        "to_ron",
+        KNativeFunction::new(|ctx: &mut CallContext| {
+            let value = ctx.args().first().cloned().unwrap_or(KValue::Null);
+            let json: JsonValue = match koto::serde::from_koto_value(value) {
+                Ok(json) => json,
+                Err(error) => return runtime_error!("Serialization error: {error}"),
+            };
+            match ron::ser::to_string_pretty(&json, ron::ser::PrettyConfig::new()) {
+                Ok(text) => Ok(text.into()),
+                Err(error) => runtime_error!("Serialization error: {error}"),
+            }
+        }),
+    );
+    module.insert(
+        "from_ron",
+        KNativeFunction::new(|ctx: &mut CallContext| match ctx.args() {
+            [KValue::Str(text), ..] => {
+                // Going through JSON values turns structs into maps and tuples into lists.
+                let parsed: JsonValue = match ron::from_str(text) {
+                    Ok(parsed) => parsed,
+                    Err(error) => return runtime_error!("Failed to parse RON: {error}"),
+                };
+                match koto::serde::to_koto_value(parsed) {
+                    Ok(value) => Ok(value),
+                    Err(error) => runtime_error!("Failed to convert RON: {error}"),
+                }
+            }
+            other => runtime_error!("Expected RON string, found {other:?}"),
+        }),
+    );
+}
+
+/// Adds `to_yaml` and `from_yaml` to the `serde` module.
+#[cfg(feature = "yaml")]
+fn register_yaml(module: &KMap) {
     module.insert(
         "to_yaml",
         KNativeFunction::new(|ctx: &mut CallContext| {
@@ -567,9 +2250,15 @@ fn serialization_module() -> anyhow::Result<KValue> {
             other => runtime_error!("Expected YAML string, found {other:?}"),
         }),
     );
-    Ok(module.into())
 }
 
+/// Without the `yaml` feature the YAML helpers explain why they aren't available.
+#[cfg(not(feature = "yaml"))]
+fn register_yaml(module: &KMap) {
+    features::register_unavailable(module, "serde", &["to_yaml", "from_yaml"], "yaml");
+}
+
+#[cfg(feature = "plugins")]
 extern "C" fn register_script_trampoline(runtime: *const Runtime, script: *const c_char) -> bool {
     if runtime.is_null() || script.is_null() {
         return false;
@@ -0,0 +1,129 @@
+//! Display settings for numbers in return values and value previews.
+
+use koto::prelude::*;
+
+/// How numbers are rendered; the default leaves Koto's own formatting untouched.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NumberFormat {
+    /// Fixed number of decimal places for floats.
+    pub precision: Option<usize>,
+    /// Numbers whose magnitude is at least this (or, for floats, below its inverse) are
+    /// shown in scientific notation, e.g. `1.23e9`.
+    pub scientific_threshold: Option<f64>,
+    /// Groups the integer digits in threes, e.g. `1,234,567`.
+    pub thousands_separators: bool,
+}
+
+impl NumberFormat {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn format(&self, number: &KNumber) -> String {
+        match number {
+            KNumber::I64(value) => self.format_int(*value),
+            KNumber::F64(value) => self.format_float(*value),
+        }
+    }
+
+    fn format_int(&self, value: i64) -> String {
+        if self.is_scientific(value as f64, false) {
+            return self.scientific(value as f64);
+        }
+        let digits = value.unsigned_abs().to_string();
+        let sign = if value < 0 { "-" } else { "" };
+        format!("{sign}{}", self.group(&digits))
+    }
+
+    fn format_float(&self, value: f64) -> String {
+        if !value.is_finite() {
+            return value.to_string();
+        }
+        if self.is_scientific(value, true) {
+            return self.scientific(value);
+        }
+        let text = match self.precision {
+            Some(precision) => format!("{:.*}", precision, value.abs()),
+            // Koto always shows a decimal point for floats.
+            None if value.fract() == 0.0 => format!("{:.1}", value.abs()),
+            None => value.abs().to_string(),
+        };
+        let (integer, fraction) = text.split_once('.').unwrap_or((&text, ""));
+        let sign = if value.is_sign_negative() && value != 0.0 {
+            "-"
+        } else {
+            ""
+        };
+        if fraction.is_empty() {
+            format!("{sign}{}", self.group(integer))
+        } else {
+            format!("{sign}{}.{fraction}", self.group(integer))
+        }
+    }
+
+    fn is_scientific(&self, value: f64, is_float: bool) -> bool {
+        let Some(threshold) = self
+            .scientific_threshold
+            .filter(|threshold| *threshold > 0.0)
+        else {
+            return false;
+        };
+        let magnitude = value.abs();
+        magnitude >= threshold || (is_float && magnitude != 0.0 && magnitude < 1.0 / threshold)
+    }
+
+    fn scientific(&self, value: f64) -> String {
+        format!("{:.*e}", self.precision.unwrap_or(3), value)
+    }
+
+    fn group(&self, digits: &str) -> String {
+        if !self.thousands_separators {
+            return digits.to_string();
+        }
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+        for (index, digit) in digits.chars().enumerate() {
+            if index > 0 && (digits.len() - index) % 3 == 0 {
+                grouped.push(',');
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+
+    /// Renders `value` the way Koto would, but with numbers formatted by these settings.
+    /// Returns `None` for values that contain types only Koto knows how to display.
+    pub fn render(&self, value: &KValue) -> Option<String> {
+        match value {
+            KValue::Str(text) => Some(text.to_string()),
+            other => self.render_nested(other),
+        }
+    }
+
+    fn render_nested(&self, value: &KValue) -> Option<String> {
+        let rendered = match value {
+            KValue::Null => "null".to_string(),
+            KValue::Bool(flag) => flag.to_string(),
+            KValue::Number(number) => self.format(number),
+            KValue::Str(text) => format!("'{}'", text.as_str()),
+            KValue::List(list) => format!("[{}]", self.render_items(list.data().iter())?),
+            KValue::Tuple(tuple) => format!("({})", self.render_items(tuple.iter())?),
+            KValue::Map(map) if map.meta_map().is_none() => {
+                let entries = map
+                    .data()
+                    .iter()
+                    .map(|(key, value)| Some(format!("{key}: {}", self.render_nested(value)?)))
+                    .collect::<Option<Vec<_>>>()?;
+                format!("{{{}}}", entries.join(", "))
+            }
+            _ => return None,
+        };
+        Some(rendered)
+    }
+
+    fn render_items<'a>(&self, items: impl Iterator<Item = &'a KValue>) -> Option<String> {
+        let items = items
+            .map(|item| self.render_nested(item))
+            .collect::<Option<Vec<_>>>()?;
+        Some(items.join(", "))
+    }
+}
@@ -0,0 +1,272 @@
+//! Lifecycle callbacks for script runs.
+//!
+//! Observers registered with [`Runtime::add_observer`](super::Runtime::add_observer) are told
+//! when a run starts, receive stdout/stderr text as the script writes it, and see the final
+//! output or error. Callbacks run on the thread executing the script while the VM is locked,
+//! so they must not call back into the same runtime.
+
+use std::{
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicU64, Ordering},
+        mpsc::Sender,
+    },
+    time::Duration,
+};
+
+use super::{ExecutionOutput, gfx::Frame};
+
+/// Describes a run that is about to start.
+#[derive(Clone, Debug)]
+pub struct ExecutionStart {
+    pub script_len: usize,
+    pub timeout: Option<Duration>,
+    /// Whether the run exports its top-level bindings into the shared session.
+    pub session: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Receives execution lifecycle events. Every method has an empty default implementation.
+pub trait ExecutionObserver: Send + Sync {
+    fn on_start(&self, _start: &ExecutionStart) {}
+
+    fn on_stdout_chunk(&self, _chunk: &str) {}
+
+    fn on_stderr_chunk(&self, _chunk: &str) {}
+
+    /// Output written by a `concurrent` worker, instead of `on_stdout_chunk` or
+    /// `on_stderr_chunk`.
+    fn on_worker_chunk(&self, _worker: &str, _stream: OutputStream, _chunk: &str) {}
+
+    /// A frame the script presented with `gfx`.
+    fn on_frame(&self, _frame: &Frame) {}
+
+    fn on_complete(&self, _output: &ExecutionOutput) {}
+
+    fn on_error(&self, _error: &str) {}
+}
+
+/// Identifies a registered observer so it can be removed again.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ObserverId(u64);
+
+/// An event forwarded by [`ChannelObserver`].
+#[derive(Clone, Debug)]
+pub enum ExecutionEvent {
+    Started(ExecutionStart),
+    Output(OutputStream, String),
+    WorkerOutput {
+        worker: String,
+        stream: OutputStream,
+        text: String,
+    },
+    Frame(Frame),
+    Completed {
+        duration: Duration,
+        return_value: Option<String>,
+    },
+    Failed(String),
+}
+
+/// Forwards events over a channel, for consumers that handle them on another thread
+/// (such as the explorer's UI thread).
+pub struct ChannelObserver {
+    sender: Sender<ExecutionEvent>,
+}
+
+impl ChannelObserver {
+    pub fn new(sender: Sender<ExecutionEvent>) -> Self {
+        Self { sender }
+    }
+}
+
+impl ExecutionObserver for ChannelObserver {
+    fn on_start(&self, start: &ExecutionStart) {
+        let _ = self.sender.send(ExecutionEvent::Started(start.clone()));
+    }
+
+    fn on_stdout_chunk(&self, chunk: &str) {
+        let _ = self.sender.send(ExecutionEvent::Output(
+            OutputStream::Stdout,
+            chunk.to_string(),
+        ));
+    }
+
+    fn on_stderr_chunk(&self, chunk: &str) {
+        let _ = self.sender.send(ExecutionEvent::Output(
+            OutputStream::Stderr,
+            chunk.to_string(),
+        ));
+    }
+
+    fn on_worker_chunk(&self, worker: &str, stream: OutputStream, chunk: &str) {
+        let _ = self.sender.send(ExecutionEvent::WorkerOutput {
+            worker: worker.to_string(),
+            stream,
+            text: chunk.to_string(),
+        });
+    }
+
+    fn on_frame(&self, frame: &Frame) {
+        let _ = self.sender.send(ExecutionEvent::Frame(frame.clone()));
+    }
+
+    fn on_complete(&self, output: &ExecutionOutput) {
+        let _ = self.sender.send(ExecutionEvent::Completed {
+            duration: output.duration,
+            return_value: output.return_value.clone(),
+        });
+    }
+
+    fn on_error(&self, error: &str) {
+        let _ = self.sender.send(ExecutionEvent::Failed(error.to_string()));
+    }
+}
+
+type TextCallback = Box<dyn Fn(&str) + Send + Sync>;
+
+/// Closure-based observer for callers that only care about some events.
+///
+/// ```ignore
+/// runtime.add_observer(ExecutionCallbacks::new().on_stdout_chunk(|chunk| print!("{chunk}")));
+/// ```
+#[derive(Default)]
+pub struct ExecutionCallbacks {
+    start: Option<Box<dyn Fn(&ExecutionStart) + Send + Sync>>,
+    stdout: Option<TextCallback>,
+    stderr: Option<TextCallback>,
+    complete: Option<Box<dyn Fn(&ExecutionOutput) + Send + Sync>>,
+    error: Option<TextCallback>,
+}
+
+impl ExecutionCallbacks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn on_start(mut self, f: impl Fn(&ExecutionStart) + Send + Sync + 'static) -> Self {
+        self.start = Some(Box::new(f));
+        self
+    }
+
+    pub fn on_stdout_chunk(mut self, f: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        self.stdout = Some(Box::new(f));
+        self
+    }
+
+    pub fn on_stderr_chunk(mut self, f: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        self.stderr = Some(Box::new(f));
+        self
+    }
+
+    pub fn on_complete(mut self, f: impl Fn(&ExecutionOutput) + Send + Sync + 'static) -> Self {
+        self.complete = Some(Box::new(f));
+        self
+    }
+
+    pub fn on_error(mut self, f: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        self.error = Some(Box::new(f));
+        self
+    }
+}
+
+impl ExecutionObserver for ExecutionCallbacks {
+    fn on_start(&self, start: &ExecutionStart) {
+        if let Some(f) = &self.start {
+            f(start);
+        }
+    }
+
+    fn on_stdout_chunk(&self, chunk: &str) {
+        if let Some(f) = &self.stdout {
+            f(chunk);
+        }
+    }
+
+    fn on_stderr_chunk(&self, chunk: &str) {
+        if let Some(f) = &self.stderr {
+            f(chunk);
+        }
+    }
+
+    fn on_complete(&self, output: &ExecutionOutput) {
+        if let Some(f) = &self.complete {
+            f(output);
+        }
+    }
+
+    fn on_error(&self, error: &str) {
+        if let Some(f) = &self.error {
+            f(error);
+        }
+    }
+}
+
+/// The observers registered with one runtime, shared with its output buffers.
+#[derive(Default)]
+pub(super) struct Observers {
+    next_id: AtomicU64,
+    entries: RwLock<Vec<(ObserverId, Arc<dyn ExecutionObserver>)>>,
+}
+
+impl Observers {
+    pub(super) fn add(&self, observer: Arc<dyn ExecutionObserver>) -> ObserverId {
+        let id = ObserverId(self.next_id.fetch_add(1, Ordering::SeqCst));
+        if let Ok(mut entries) = self.entries.write() {
+            entries.push((id, observer));
+        }
+        id
+    }
+
+    pub(super) fn remove(&self, id: ObserverId) -> bool {
+        self.entries
+            .write()
+            .map(|mut entries| {
+                let before = entries.len();
+                entries.retain(|(entry_id, _)| *entry_id != id);
+                entries.len() != before
+            })
+            .unwrap_or(false)
+    }
+
+    /// Calls `f` for every observer. The list is copied first so observers may add or remove
+    /// observers from inside a callback.
+    pub(super) fn notify(&self, f: impl Fn(&dyn ExecutionObserver)) {
+        let observers: Vec<_> = match self.entries.read() {
+            Ok(entries) if !entries.is_empty() => entries
+                .iter()
+                .map(|(_, observer)| Arc::clone(observer))
+                .collect(),
+            _ => return,
+        };
+        for observer in observers {
+            f(observer.as_ref());
+        }
+    }
+
+    pub(super) fn output(&self, stream: OutputStream, chunk: &str) {
+        if chunk.is_empty() {
+            return;
+        }
+        self.notify(|observer| match stream {
+            OutputStream::Stdout => observer.on_stdout_chunk(chunk),
+            OutputStream::Stderr => observer.on_stderr_chunk(chunk),
+        });
+    }
+
+    pub(super) fn worker_output(&self, worker: &str, stream: OutputStream, chunk: &str) {
+        if chunk.is_empty() {
+            return;
+        }
+        self.notify(|observer| observer.on_worker_chunk(worker, stream, chunk));
+    }
+
+    pub(super) fn frame(&self, frame: &Frame) {
+        self.notify(|observer| observer.on_frame(frame));
+    }
+}
@@ -0,0 +1,122 @@
+//! Compiled script artifacts and an on-disk cache for them.
+//!
+//! [`Runtime::precompile`](super::Runtime::precompile) compiles a script into the in-memory
+//! compile cache, so its first run skips compilation, and returns a [`PrecompiledScript`]
+//! that can be written to a [`DiskCache`]. Koto only executes chunks compiled in-process, so
+//! the disk cache can't stand in for compilation. What it saves is re-validation: an artifact
+//! records whether a script compiled, keyed by the hash of its source and the Koto version,
+//! so a warm-up can skip scripts already known not to compile until they change.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+const KOTO_VERSION: &str = env!("KOTO_LEARNING_KOTO_VERSION");
+const DEFAULT_CACHE_DIR: &str = "target/koto-cache";
+
+/// A compiled script, or the error compiling it raised, serializable so it can be cached on
+/// disk.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PrecompiledScript {
+    /// Hash of the script source, also used as the file name in a [`DiskCache`].
+    pub hash: u64,
+    /// Version of Koto that compiled the script.
+    pub koto_version: String,
+    /// Empty when the script didn't compile.
+    pub bytecode: Vec<u8>,
+    pub compile_time: Duration,
+    /// Why the script didn't compile.
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl PrecompiledScript {
+    pub(super) fn new(script: &str, bytecode: &[u8], compile_time: Duration) -> Self {
+        Self {
+            hash: super::compile_cache::script_hash(script),
+            koto_version: KOTO_VERSION.to_string(),
+            bytecode: bytecode.to_vec(),
+            compile_time,
+            error: None,
+        }
+    }
+
+    /// Records that `script` failed to compile with `error`.
+    pub fn failed(script: &str, error: impl std::fmt::Display) -> Self {
+        Self {
+            hash: super::compile_cache::script_hash(script),
+            koto_version: KOTO_VERSION.to_string(),
+            bytecode: Vec::new(),
+            compile_time: Duration::ZERO,
+            error: Some(error.to_string()),
+        }
+    }
+
+    pub fn compiled(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Whether the artifact was made from `script` by this version of Koto.
+    pub fn is_current_for(&self, script: &str) -> bool {
+        self.koto_version == KOTO_VERSION && self.hash == super::compile_cache::script_hash(script)
+    }
+}
+
+/// Directory of [`PrecompiledScript`]s stored as JSON, one file per script.
+#[derive(Clone, Debug)]
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+impl DiskCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// `KOTO_CACHE_DIR` if set, otherwise `target/koto-cache`.
+    pub fn default_location() -> Self {
+        Self::new(
+            std::env::var_os("KOTO_CACHE_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_CACHE_DIR)),
+        )
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the cached artifact for `script`, ignoring stale or unreadable entries.
+    pub fn get(&self, script: &str) -> Option<PrecompiledScript> {
+        let path = self.artifact_path(super::compile_cache::script_hash(script));
+        let text = fs::read_to_string(path).ok()?;
+        serde_json::from_str::<PrecompiledScript>(&text)
+            .ok()
+            .filter(|artifact| artifact.is_current_for(script))
+    }
+
+    pub fn store(&self, artifact: &PrecompiledScript) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let path = self.artifact_path(artifact.hash);
+        let json = serde_json::to_string(artifact)?;
+        fs::write(&path, json).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir)
+                .with_context(|| format!("Failed to remove {}", self.dir.display()))?;
+        }
+        Ok(())
+    }
+
+    fn artifact_path(&self, hash: u64) -> PathBuf {
+        self.dir.join(format!("{hash:016x}.json"))
+    }
+}
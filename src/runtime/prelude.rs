@@ -0,0 +1,100 @@
+//! Describes what scripts can reach through the VM prelude, for the prelude inspector.
+
+use koto::prelude::*;
+
+/// Where a prelude binding comes from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PreludeSource {
+    /// Part of Koto's core library.
+    Core,
+    /// Registered by the explorer, the embedding application, or a plugin library.
+    Host,
+}
+
+impl PreludeSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Core => "Core",
+            Self::Host => "Host",
+        }
+    }
+}
+
+/// A top-level prelude binding, e.g. `serde` or `io`.
+#[derive(Clone, Debug)]
+pub struct PreludeEntry {
+    pub name: String,
+    /// Koto's name for the value's type, e.g. `Map` or `NativeFunction`.
+    pub type_name: String,
+    pub callable: bool,
+    pub source: PreludeSource,
+    pub description: &'static str,
+    /// Members of module bindings, sorted by name.
+    pub members: Vec<PreludeMember>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PreludeMember {
+    pub name: String,
+    pub type_name: String,
+    pub callable: bool,
+}
+
+pub(super) fn describe(name: &str, value: &KValue, source: PreludeSource) -> PreludeEntry {
+    let mut members = match value {
+        KValue::Map(module) => module
+            .data()
+            .iter()
+            .map(|(key, member)| PreludeMember {
+                name: key.to_string(),
+                type_name: member.type_as_string().to_string(),
+                callable: member.is_callable(),
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    members.sort_by(|a, b| a.name.cmp(&b.name));
+    PreludeEntry {
+        name: name.to_string(),
+        type_name: value.type_as_string().to_string(),
+        callable: value.is_callable(),
+        source,
+        description: description(name, source),
+        members,
+    }
+}
+
+fn description(name: &str, source: PreludeSource) -> &'static str {
+    match name {
+        "host" => "Explorer helpers: logging, timing, sleeping, intervals and generator tracing.",
+        "serde" => "Converts values to and from JSON, YAML and TOML.",
+        "env" => "Reads environment variables named in the runtime allowlist.",
+        "clipboard" => "Reads and writes the system clipboard (Trusted profile only).",
+        "process" => "Runs external programs (Trusted profile only).",
+        "input" => "Keyboard and pointer events from the explorer's canvas.",
+        "audio" => "Plays short tones (when audio output is enabled in the settings).",
+        "gfx" => "Draws shapes onto the explorer's canvas, one frame at a time.",
+        "concurrent" => "Runs functions on worker threads and passes values over channels.",
+        "args" => "Positional arguments passed to the current run.",
+        "io" => "Files, stdin/stdout and printing.",
+        "koto" => "Information about the running script and the Koto runtime.",
+        "iterator" => "Adapters and consumers shared by every iterable value.",
+        "list" => "Functions available on lists.",
+        "map" => "Functions available on maps.",
+        "number" => "Functions available on numbers.",
+        "os" => "Operating system information and time.",
+        "range" => "Functions available on ranges.",
+        "string" => "Functions available on strings.",
+        "test" => "Assertions for writing tests.",
+        "tuple" => "Functions available on tuples.",
+        "assert" | "assert_eq" | "assert_ne" | "assert_near" => {
+            "Fails the script if the check doesn't hold."
+        }
+        "print" => "Prints a formatted line to stdout.",
+        "type" => "Returns the name of a value's type.",
+        _ => match source {
+            PreludeSource::Core => "Part of the Koto core library.",
+            PreludeSource::Host => "Registered by the application or a plugin library.",
+        },
+    }
+}
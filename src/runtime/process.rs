@@ -0,0 +1,182 @@
+use std::{
+    io::Read,
+    process::{Command, Stdio},
+    sync::{Arc, atomic::Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use koto::{prelude::*, runtime::Result as KotoRuntimeResult};
+
+use super::{HostState, number_to_i64};
+
+const DEFAULT_PROCESS_TIMEOUT: Duration = Duration::from_secs(10);
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Builds the `process` module. `run` works only in runs given
+/// [`RunOptions::allow_process`](super::RunOptions::allow_process); the explorer allows it for
+/// examples that opt in with `"permissions": {"process": true}` when the profile is trusted or
+/// the user approves.
+pub(super) fn process_module(host: Arc<HostState>) -> KValue {
+    let module = KMap::default();
+
+    module.insert(
+        "run",
+        KNativeFunction::new(move |ctx: &mut CallContext| {
+            host.check_interrupts()?;
+            if !host.process_allowed.load(Ordering::SeqCst) {
+                return runtime_error!(
+                    "process.run is disabled; the example needs \"permissions\": {{\"process\": true}} in its meta.json, and the trusted profile or the user's approval"
+                );
+            }
+            ProcessRequest::from_args(ctx.args())?.run(&host)
+        }),
+    );
+
+    module.into()
+}
+
+struct ProcessRequest {
+    command: String,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+impl ProcessRequest {
+    fn from_args(args: &[KValue]) -> KotoRuntimeResult<Self> {
+        match args {
+            [KValue::Str(command)] => Ok(Self::new(command.as_str())),
+            [KValue::Str(command), KValue::List(args)] => {
+                Self::new(command.as_str()).with_args(args)
+            }
+            [
+                KValue::Str(command),
+                KValue::List(args),
+                KValue::Map(options),
+            ] => Self::new(command.as_str())
+                .with_args(args)?
+                .with_options(options),
+            other => runtime_error!(
+                "Expected command, optional argument list, and options, found {other:?}"
+            ),
+        }
+    }
+
+    fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            args: Vec::new(),
+            timeout: DEFAULT_PROCESS_TIMEOUT,
+        }
+    }
+
+    fn with_args(mut self, args: &KList) -> KotoRuntimeResult<Self> {
+        for arg in args.data().iter() {
+            match arg {
+                KValue::Str(text) => self.args.push(text.to_string()),
+                KValue::Number(n) => self.args.push(n.to_string()),
+                other => return runtime_error!("Expected string arguments, found {other:?}"),
+            }
+        }
+        Ok(self)
+    }
+
+    fn with_options(mut self, options: &KMap) -> KotoRuntimeResult<Self> {
+        match options.get("timeout_ms") {
+            Some(KValue::Number(n)) => {
+                let millis = number_to_i64(&n);
+                if millis <= 0 {
+                    return runtime_error!("timeout_ms must be positive, found {millis}");
+                }
+                self.timeout = Duration::from_millis(millis as u64);
+            }
+            Some(KValue::Null) | None => {}
+            Some(other) => return runtime_error!("Expected numeric timeout_ms, found {other:?}"),
+        }
+        Ok(self)
+    }
+
+    /// Starts the command and waits for it, killing it when it outlives its own timeout, or
+    /// when the run is cancelled or reaches its time limit while waiting.
+    fn run(self, host: &HostState) -> KotoRuntimeResult<KValue> {
+        let mut child = match Command::new(&self.command)
+            .args(&self.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(error) => return runtime_error!("Failed to start '{}': {error}", self.command),
+        };
+
+        let stdout_reader = child.stdout.take().map(spawn_reader);
+        let stderr_reader = child.stderr.take().map(spawn_reader);
+
+        let start = Instant::now();
+        let mut timed_out = false;
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break Some(status),
+                Ok(None) if start.elapsed() >= self.timeout => {
+                    timed_out = true;
+                    let _ = child.kill();
+                    break child.wait().ok();
+                }
+                Ok(None) => {
+                    let interrupted = host.check_interrupts().and_then(|_| {
+                        if host
+                            .deadline()
+                            .is_some_and(|deadline| Instant::now() >= deadline)
+                        {
+                            runtime_error!(
+                                "Execution limit exceeded while waiting for '{}'",
+                                self.command
+                            )
+                        } else {
+                            Ok(())
+                        }
+                    });
+                    if let Err(error) = interrupted {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                        return Err(error);
+                    }
+                    thread::sleep(POLL_INTERVAL);
+                }
+                Err(error) => {
+                    return runtime_error!("Failed to wait for '{}': {error}", self.command);
+                }
+            }
+        };
+
+        let stdout = stdout_reader.map(join_reader).unwrap_or_default();
+        let stderr = stderr_reader.map(join_reader).unwrap_or_default();
+
+        let result = KMap::default();
+        result.insert(
+            "exit_code",
+            match status.and_then(|status| status.code()) {
+                Some(code) => KValue::from(code as i64),
+                None => KValue::Null,
+            },
+        );
+        result.insert("stdout", stdout);
+        result.insert("stderr", stderr);
+        result.insert("timed_out", timed_out);
+        result.insert("duration_ms", start.elapsed().as_secs_f64() * 1000.0);
+        Ok(result.into())
+    }
+}
+
+pub(super) fn spawn_reader<R: Read + Send + 'static>(mut pipe: R) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = pipe.read_to_end(&mut buffer);
+        String::from_utf8_lossy(&buffer).into_owned()
+    })
+}
+
+pub(super) fn join_reader(handle: thread::JoinHandle<String>) -> String {
+    handle.join().unwrap_or_default()
+}
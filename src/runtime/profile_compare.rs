@@ -0,0 +1,112 @@
+//! Runs one script under two runtime setups with
+//! [`Runtime::compare_setups`](super::Runtime::compare_setups), to show how the environment
+//! changes what a script does.
+
+use super::{
+    ExecutionOutput, RuntimeProfile,
+    value_diff::{self, DiffKind, DiffLine},
+};
+
+/// The parts of the runtime environment a comparison varies.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RunSetup {
+    pub profile: RuntimeProfile,
+    /// Whether timing and `host.random` are made reproducible.
+    pub deterministic: bool,
+}
+
+impl RunSetup {
+    /// Every combination the explorer offers to compare.
+    pub const ALL: [RunSetup; 4] = [
+        RunSetup {
+            profile: RuntimeProfile::Standard,
+            deterministic: false,
+        },
+        RunSetup {
+            profile: RuntimeProfile::Standard,
+            deterministic: true,
+        },
+        RunSetup {
+            profile: RuntimeProfile::Trusted,
+            deterministic: false,
+        },
+        RunSetup {
+            profile: RuntimeProfile::Trusted,
+            deterministic: true,
+        },
+    ];
+
+    pub fn label(self) -> String {
+        if self.deterministic {
+            format!("{}, deterministic", self.profile.label())
+        } else {
+            self.profile.label().to_string()
+        }
+    }
+}
+
+/// What a script did under one setup.
+#[derive(Clone, Debug)]
+pub struct SetupRun {
+    pub setup: RunSetup,
+    pub stdout: String,
+    pub stderr: String,
+    pub return_value: Option<String>,
+    /// Why the run failed, if it did.
+    pub error: Option<String>,
+}
+
+impl SetupRun {
+    pub(super) fn new(setup: RunSetup, result: anyhow::Result<ExecutionOutput>) -> Self {
+        match result {
+            Ok(output) => Self {
+                setup,
+                stdout: output.stdout,
+                stderr: output.stderr,
+                return_value: output.return_value,
+                error: None,
+            },
+            Err(error) => Self {
+                setup,
+                stdout: String::new(),
+                stderr: String::new(),
+                return_value: None,
+                error: Some(format!("{error:#}")),
+            },
+        }
+    }
+
+    /// The run as the comparison lines it up: stdout, stderr, then the result or error.
+    pub fn transcript(&self) -> String {
+        let mut text = self.stdout.clone();
+        text.push_str(&self.stderr);
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        match (&self.error, &self.return_value) {
+            (Some(error), _) => text.push_str(&format!("error: {error}\n")),
+            (None, Some(value)) => text.push_str(&format!("=> {value}\n")),
+            (None, None) => {}
+        }
+        text
+    }
+}
+
+/// The same script run under two setups.
+#[derive(Clone, Debug)]
+pub struct ProfileComparison {
+    pub left: SetupRun,
+    pub right: SetupRun,
+}
+
+impl ProfileComparison {
+    /// How the right transcript differs from the left, line by line.
+    pub fn diff(&self) -> Vec<DiffLine> {
+        value_diff::diff_lines(&self.left.transcript(), &self.right.transcript())
+    }
+
+    /// Whether both setups printed and returned the same.
+    pub fn is_identical(&self) -> bool {
+        self.diff().iter().all(|line| line.kind == DiffKind::Same)
+    }
+}
@@ -0,0 +1,159 @@
+//! The `host.random` module: a small seedable generator so examples and tests can produce
+//! reproducible "random" output.
+
+use std::{
+    sync::{Arc, atomic::Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use koto::{prelude::*, runtime::Result as KotoRuntimeResult};
+
+use super::{HostState, number_to_f64, number_to_i64};
+
+/// Seed used for every run in deterministic mode unless one is configured.
+pub const DETERMINISTIC_SEED: u64 = 0x5eed;
+
+/// SplitMix64: tiny, fast, and identical on every platform, which is all reproducible
+/// teaching output needs.
+#[derive(Clone, Debug, Default)]
+pub(super) struct RandomState {
+    seed: u64,
+    state: u64,
+}
+
+impl RandomState {
+    pub(super) fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.state = seed;
+    }
+
+    pub(super) fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A float in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// An index in `0..len`; `len` must be non-zero.
+    fn next_index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+}
+
+/// A seed for runs that don't ask for a specific one.
+pub(super) fn fresh_seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or_default();
+    nanos ^ (uuid::Uuid::new_v4().as_u128() as u64)
+}
+
+/// Adds `host.random` to the `host` module.
+pub(super) fn register(module: &KMap, host: Arc<HostState>) {
+    let random = KMap::default();
+
+    let state = Arc::clone(&host);
+    random.insert(
+        "seed",
+        KNativeFunction::new(move |ctx: &mut CallContext| match ctx.args() {
+            [KValue::Number(n)] => {
+                with_rng(&state, |rng| rng.reseed(number_to_i64(n) as u64))?;
+                Ok(KValue::Null)
+            }
+            other => runtime_error!("Expected a seed number, found {other:?}"),
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    random.insert(
+        "uniform",
+        KNativeFunction::new(move |ctx: &mut CallContext| {
+            let (min, max) = match ctx.args() {
+                [] => (0.0, 1.0),
+                [KValue::Number(min), KValue::Number(max)] => {
+                    (number_to_f64(min), number_to_f64(max))
+                }
+                other => {
+                    return runtime_error!("Expected no arguments or min and max, found {other:?}");
+                }
+            };
+            let unit = with_rng(&state, RandomState::next_f64)?;
+            Ok((min + unit * (max - min)).into())
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    random.insert(
+        "int",
+        KNativeFunction::new(move |ctx: &mut CallContext| match ctx.args() {
+            [KValue::Number(min), KValue::Number(max)] => {
+                let (min, max) = (number_to_i64(min), number_to_i64(max));
+                if min > max {
+                    return runtime_error!("Expected min <= max, found {min} > {max}");
+                }
+                let span = (max as i128 - min as i128 + 1) as u128;
+                let offset = with_rng(&state, |rng| (rng.next_u64() as u128 % span) as i128)?;
+                Ok(KValue::Number(((min as i128 + offset) as i64).into()))
+            }
+            other => runtime_error!("Expected min and max integers, found {other:?}"),
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    random.insert(
+        "choice",
+        KNativeFunction::new(move |ctx: &mut CallContext| {
+            let items: Vec<KValue> = match ctx.args() {
+                [KValue::List(list)] => list.data().iter().cloned().collect(),
+                [KValue::Tuple(tuple)] => tuple.iter().cloned().collect(),
+                other => return runtime_error!("Expected a list or tuple, found {other:?}"),
+            };
+            if items.is_empty() {
+                return runtime_error!("Can't choose from an empty container");
+            }
+            let index = with_rng(&state, |rng| rng.next_index(items.len()))?;
+            Ok(items[index].clone())
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    random.insert(
+        "shuffle",
+        KNativeFunction::new(move |ctx: &mut CallContext| match ctx.args() {
+            [KValue::List(list)] => {
+                let len = list.len();
+                with_rng(&state, |rng| {
+                    let mut data = list.data_mut();
+                    // Fisher-Yates
+                    for i in (1..len).rev() {
+                        data.swap(i, rng.next_index(i + 1));
+                    }
+                })?;
+                Ok(KValue::List(list.clone()))
+            }
+            other => runtime_error!("Expected a list, found {other:?}"),
+        }),
+    );
+
+    module.insert("random", random);
+}
+
+fn with_rng<T>(host: &HostState, f: impl FnOnce(&mut RandomState) -> T) -> KotoRuntimeResult<T> {
+    host.check_interrupts()?;
+    host.random_used.store(true, Ordering::SeqCst);
+    match host.random.lock() {
+        Ok(mut rng) => Ok(f(&mut rng)),
+        Err(_) => runtime_error!("Random number generator is unavailable"),
+    }
+}
@@ -0,0 +1,81 @@
+//! Timings from running one script several times, to compare the cold first run with warm
+//! runs that reuse its compiled chunk.
+
+use std::time::Duration;
+
+use super::ExecutionOutput;
+
+/// Timing of a single run in a [`RunSeries`].
+#[derive(Clone, Copy, Debug)]
+pub struct RunTiming {
+    pub duration: Duration,
+    pub compile_time: Duration,
+    pub cache_hit: bool,
+}
+
+impl From<&ExecutionOutput> for RunTiming {
+    fn from(output: &ExecutionOutput) -> Self {
+        Self {
+            duration: output.duration,
+            compile_time: output.compile_time,
+            cache_hit: output.cache_hit,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DurationStats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl DurationStats {
+    /// Returns `None` for an empty slice. With an even count the median is the mean of the
+    /// two middle values.
+    pub fn from_durations(durations: &[Duration]) -> Option<Self> {
+        let mut sorted = durations.to_vec();
+        sorted.sort();
+        let (min, max) = (*sorted.first()?, *sorted.last()?);
+        let middle = sorted.len() / 2;
+        let median = if sorted.len() % 2 == 0 {
+            (sorted[middle - 1] + sorted[middle]) / 2
+        } else {
+            sorted[middle]
+        };
+        Some(Self { min, median, max })
+    }
+}
+
+/// Runs of the same script, in the order they happened.
+#[derive(Clone, Debug, Default)]
+pub struct RunSeries {
+    pub runs: Vec<RunTiming>,
+}
+
+impl RunSeries {
+    pub fn push(&mut self, output: &ExecutionOutput) {
+        self.runs.push(output.into());
+    }
+
+    pub fn first(&self) -> Option<&RunTiming> {
+        self.runs.first()
+    }
+
+    /// Statistics for every run after the first.
+    pub fn warm_stats(&self) -> Option<DurationStats> {
+        let durations: Vec<Duration> = self.runs.iter().skip(1).map(|run| run.duration).collect();
+        DurationStats::from_durations(&durations)
+    }
+
+    /// Compile (or cache lookup) times for every run after the first.
+    pub fn warm_compile_stats(&self) -> Option<DurationStats> {
+        let durations: Vec<Duration> = self
+            .runs
+            .iter()
+            .skip(1)
+            .map(|run| run.compile_time)
+            .collect();
+        DurationStats::from_durations(&durations)
+    }
+}
@@ -0,0 +1,110 @@
+//! Timestamped stdout/stderr of a run, so its output can be played back at the pace it was
+//! written:
+//!
+//! ```ignore
+//! let recorder = OutputRecorder::new();
+//! runtime.add_observer(recorder.clone());
+//! runtime.execute_script(script)?;
+//! let recording = recorder.take();
+//! let halfway = recording.text_at(recording.duration() / 2);
+//! ```
+
+use std::{
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use super::observer::{ExecutionObserver, ExecutionStart, OutputStream};
+
+/// A chunk of output and when it was written, relative to the start of the run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimedChunk {
+    pub at: Duration,
+    pub stream: OutputStream,
+    pub text: String,
+}
+
+/// The output of one run, in the order it was written.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OutputRecording {
+    pub chunks: Vec<TimedChunk>,
+}
+
+impl OutputRecording {
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// When the last chunk was written.
+    pub fn duration(&self) -> Duration {
+        self.chunks.last().map(|chunk| chunk.at).unwrap_or_default()
+    }
+
+    /// The chunks written by `at`.
+    pub fn chunks_at(&self, at: Duration) -> &[TimedChunk] {
+        let end = self.chunks.partition_point(|chunk| chunk.at <= at);
+        &self.chunks[..end]
+    }
+
+    /// Everything written by `at`, stdout and stderr interleaved.
+    pub fn text_at(&self, at: Duration) -> String {
+        self.chunks_at(at)
+            .iter()
+            .map(|chunk| chunk.text.as_str())
+            .collect()
+    }
+}
+
+/// Observer that records the output of the latest run; each run replaces the previous
+/// recording. Clones share the same recording.
+#[derive(Clone, Default)]
+pub struct OutputRecorder {
+    state: Arc<Mutex<RecorderState>>,
+}
+
+#[derive(Default)]
+struct RecorderState {
+    started: Option<Instant>,
+    recording: OutputRecording,
+}
+
+impl OutputRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes the recording made so far, leaving an empty one.
+    pub fn take(&self) -> OutputRecording {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        std::mem::take(&mut state.recording)
+    }
+
+    fn record(&self, stream: OutputStream, chunk: &str) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let at = state
+            .started
+            .map(|started| started.elapsed())
+            .unwrap_or_default();
+        state.recording.chunks.push(TimedChunk {
+            at,
+            stream,
+            text: chunk.to_string(),
+        });
+    }
+}
+
+impl ExecutionObserver for OutputRecorder {
+    fn on_start(&self, _start: &ExecutionStart) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.started = Some(Instant::now());
+        state.recording = OutputRecording::default();
+    }
+
+    fn on_stdout_chunk(&self, chunk: &str) {
+        self.record(OutputStream::Stdout, chunk);
+    }
+
+    fn on_stderr_chunk(&self, chunk: &str) {
+        self.record(OutputStream::Stderr, chunk);
+    }
+}
@@ -0,0 +1,469 @@
+//! `serde.validate`: checks a value against a JSON Schema and lists what doesn't match.
+//!
+//! The common validation keywords are supported: `type`, `enum`, `const`, the numeric, string,
+//! array, and object bounds, `properties`, `required`, `additionalProperties`, `items`,
+//! `allOf`, `anyOf`, `oneOf`, `not`, and local `$ref`s like `#/$defs/point`. Other keywords
+//! are ignored, as JSON Schema treats unknown keywords as annotations.
+
+use koto::{prelude::*, runtime::Result as KotoRuntimeResult};
+use serde_json::{Map, Value as JsonValue};
+
+/// Guards against schemas whose `$ref`s point back at themselves.
+const MAX_DEPTH: usize = 64;
+
+/// A place where the value doesn't match the schema.
+#[derive(Clone, Debug, PartialEq)]
+struct SchemaError {
+    /// A JSON pointer to the offending part of the value, `""` for the value itself.
+    path: String,
+    /// The schema keyword that failed, e.g. `required`.
+    keyword: String,
+    message: String,
+}
+
+/// Checks `value` against `schema`, returning every mismatch, or an error if the schema itself
+/// is malformed.
+fn validate(value: &JsonValue, schema: &JsonValue) -> Result<Vec<SchemaError>, String> {
+    let mut validator = Validator {
+        root: schema,
+        errors: Vec::new(),
+    };
+    validator.check(value, schema, "", 0)?;
+    Ok(validator.errors)
+}
+
+struct Validator<'a> {
+    root: &'a JsonValue,
+    errors: Vec<SchemaError>,
+}
+
+impl<'a> Validator<'a> {
+    fn fail(&mut self, path: &str, keyword: &str, message: String) {
+        self.errors.push(SchemaError {
+            path: path.to_string(),
+            keyword: keyword.to_string(),
+            message,
+        });
+    }
+
+    /// Whether `value` matches `schema`, without recording why not.
+    fn matches(
+        &self,
+        value: &JsonValue,
+        schema: &'a JsonValue,
+        depth: usize,
+    ) -> Result<bool, String> {
+        let mut probe = Validator {
+            root: self.root,
+            errors: Vec::new(),
+        };
+        probe.check(value, schema, "", depth)?;
+        Ok(probe.errors.is_empty())
+    }
+
+    fn check(
+        &mut self,
+        value: &JsonValue,
+        schema: &'a JsonValue,
+        path: &str,
+        depth: usize,
+    ) -> Result<(), String> {
+        if depth > MAX_DEPTH {
+            return Err("The schema nests too deeply; check it for circular $refs".into());
+        }
+        let schema = match schema {
+            JsonValue::Bool(true) => return Ok(()),
+            JsonValue::Bool(false) => {
+                self.fail(path, "false", "No value is allowed here".into());
+                return Ok(());
+            }
+            JsonValue::Object(schema) => schema,
+            other => return Err(format!("Expected a schema object, found {other}")),
+        };
+
+        if let Some(reference) = schema.get("$ref") {
+            let target = self.resolve(reference)?;
+            self.check(value, target, path, depth + 1)?;
+        }
+        if let Some(expected) = schema.get("type") {
+            self.check_type(value, expected, path)?;
+        }
+        if let Some(JsonValue::Array(options)) = schema.get("enum") {
+            if !options.contains(value) {
+                self.fail(
+                    path,
+                    "enum",
+                    format!("{value} is not one of {}", list(options)),
+                );
+            }
+        }
+        if let Some(expected) = schema.get("const") {
+            if value != expected {
+                self.fail(path, "const", format!("Expected {expected}, found {value}"));
+            }
+        }
+        match value {
+            JsonValue::Number(number) => {
+                if let Some(number) = number.as_f64() {
+                    self.check_number(number, schema, path);
+                }
+            }
+            JsonValue::String(text) => self.check_string(text, schema, path),
+            JsonValue::Array(items) => self.check_array(items, schema, path, depth)?,
+            JsonValue::Object(object) => self.check_object(object, schema, path, depth)?,
+            _ => {}
+        }
+        self.check_combinators(value, schema, path, depth)
+    }
+
+    fn resolve(&self, reference: &JsonValue) -> Result<&'a JsonValue, String> {
+        let pointer = match reference.as_str().and_then(|text| text.strip_prefix('#')) {
+            Some(pointer) => pointer,
+            None => {
+                return Err(format!(
+                    "Only local $refs like \"#/$defs/name\" are supported, found {reference}"
+                ));
+            }
+        };
+        self.root
+            .pointer(pointer)
+            .ok_or_else(|| format!("The schema has nothing at $ref {reference}"))
+    }
+
+    fn check_type(
+        &mut self,
+        value: &JsonValue,
+        expected: &JsonValue,
+        path: &str,
+    ) -> Result<(), String> {
+        let names: Vec<&str> = match expected {
+            JsonValue::String(name) => vec![name.as_str()],
+            JsonValue::Array(names) => names.iter().filter_map(JsonValue::as_str).collect(),
+            other => {
+                return Err(format!(
+                    "Expected \"type\" to be a name or a list, found {other}"
+                ));
+            }
+        };
+        if !names.iter().any(|name| has_type(value, name)) {
+            self.fail(
+                path,
+                "type",
+                format!(
+                    "Expected {}, found {}",
+                    names.join(" or "),
+                    type_name(value)
+                ),
+            );
+        }
+        Ok(())
+    }
+
+    fn check_number(&mut self, number: f64, schema: &Map<String, JsonValue>, path: &str) {
+        let bound = |keyword: &str| schema.get(keyword).and_then(JsonValue::as_f64);
+        if let Some(minimum) = bound("minimum") {
+            if number < minimum {
+                self.fail(path, "minimum", format!("{number} is less than {minimum}"));
+            }
+        }
+        if let Some(maximum) = bound("maximum") {
+            if number > maximum {
+                self.fail(
+                    path,
+                    "maximum",
+                    format!("{number} is greater than {maximum}"),
+                );
+            }
+        }
+        if let Some(minimum) = bound("exclusiveMinimum") {
+            if number <= minimum {
+                self.fail(
+                    path,
+                    "exclusiveMinimum",
+                    format!("{number} is not greater than {minimum}"),
+                );
+            }
+        }
+        if let Some(maximum) = bound("exclusiveMaximum") {
+            if number >= maximum {
+                self.fail(
+                    path,
+                    "exclusiveMaximum",
+                    format!("{number} is not less than {maximum}"),
+                );
+            }
+        }
+        if let Some(divisor) = bound("multipleOf") {
+            let quotient = number / divisor;
+            if divisor > 0.0 && (quotient - quotient.round()).abs() > 1e-9 {
+                self.fail(
+                    path,
+                    "multipleOf",
+                    format!("{number} is not a multiple of {divisor}"),
+                );
+            }
+        }
+    }
+
+    fn check_string(&mut self, text: &str, schema: &Map<String, JsonValue>, path: &str) {
+        let length = text.chars().count() as u64;
+        if let Some(min) = schema.get("minLength").and_then(JsonValue::as_u64) {
+            if length < min {
+                self.fail(
+                    path,
+                    "minLength",
+                    format!("Expected at least {min} characters, found {length}"),
+                );
+            }
+        }
+        if let Some(max) = schema.get("maxLength").and_then(JsonValue::as_u64) {
+            if length > max {
+                self.fail(
+                    path,
+                    "maxLength",
+                    format!("Expected at most {max} characters, found {length}"),
+                );
+            }
+        }
+    }
+
+    fn check_array(
+        &mut self,
+        items: &[JsonValue],
+        schema: &'a Map<String, JsonValue>,
+        path: &str,
+        depth: usize,
+    ) -> Result<(), String> {
+        let count = items.len() as u64;
+        if let Some(min) = schema.get("minItems").and_then(JsonValue::as_u64) {
+            if count < min {
+                self.fail(
+                    path,
+                    "minItems",
+                    format!("Expected at least {min} items, found {count}"),
+                );
+            }
+        }
+        if let Some(max) = schema.get("maxItems").and_then(JsonValue::as_u64) {
+            if count > max {
+                self.fail(
+                    path,
+                    "maxItems",
+                    format!("Expected at most {max} items, found {count}"),
+                );
+            }
+        }
+        if schema.get("uniqueItems") == Some(&JsonValue::Bool(true)) {
+            for (index, item) in items.iter().enumerate() {
+                if items[..index].contains(item) {
+                    self.fail(
+                        &child(path, &index.to_string()),
+                        "uniqueItems",
+                        format!("{item} appears more than once"),
+                    );
+                }
+            }
+        }
+        if let Some(item_schema) = schema.get("items") {
+            for (index, item) in items.iter().enumerate() {
+                self.check(
+                    item,
+                    item_schema,
+                    &child(path, &index.to_string()),
+                    depth + 1,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn check_object(
+        &mut self,
+        object: &Map<String, JsonValue>,
+        schema: &'a Map<String, JsonValue>,
+        path: &str,
+        depth: usize,
+    ) -> Result<(), String> {
+        if let Some(JsonValue::Array(required)) = schema.get("required") {
+            for name in required.iter().filter_map(JsonValue::as_str) {
+                if !object.contains_key(name) {
+                    self.fail(
+                        path,
+                        "required",
+                        format!("Missing required property '{name}'"),
+                    );
+                }
+            }
+        }
+        let count = object.len() as u64;
+        if let Some(min) = schema.get("minProperties").and_then(JsonValue::as_u64) {
+            if count < min {
+                self.fail(
+                    path,
+                    "minProperties",
+                    format!("Expected at least {min} properties, found {count}"),
+                );
+            }
+        }
+        if let Some(max) = schema.get("maxProperties").and_then(JsonValue::as_u64) {
+            if count > max {
+                self.fail(
+                    path,
+                    "maxProperties",
+                    format!("Expected at most {max} properties, found {count}"),
+                );
+            }
+        }
+        let properties = schema.get("properties").and_then(JsonValue::as_object);
+        for (name, property) in object {
+            let property_path = child(path, name);
+            match properties.and_then(|properties| properties.get(name)) {
+                Some(property_schema) => {
+                    self.check(property, property_schema, &property_path, depth + 1)?;
+                }
+                None => match schema.get("additionalProperties") {
+                    Some(JsonValue::Bool(false)) => self.fail(
+                        &property_path,
+                        "additionalProperties",
+                        format!("Unexpected property '{name}'"),
+                    ),
+                    Some(additional) => {
+                        self.check(property, additional, &property_path, depth + 1)?;
+                    }
+                    None => {}
+                },
+            }
+        }
+        Ok(())
+    }
+
+    fn check_combinators(
+        &mut self,
+        value: &JsonValue,
+        schema: &'a Map<String, JsonValue>,
+        path: &str,
+        depth: usize,
+    ) -> Result<(), String> {
+        if let Some(JsonValue::Array(schemas)) = schema.get("allOf") {
+            for sub_schema in schemas {
+                self.check(value, sub_schema, path, depth + 1)?;
+            }
+        }
+        if let Some(JsonValue::Array(schemas)) = schema.get("anyOf") {
+            let mut any = false;
+            for sub_schema in schemas {
+                any |= self.matches(value, sub_schema, depth + 1)?;
+            }
+            if !any {
+                self.fail(
+                    path,
+                    "anyOf",
+                    "Doesn't match any of the allowed schemas".into(),
+                );
+            }
+        }
+        if let Some(JsonValue::Array(schemas)) = schema.get("oneOf") {
+            let mut matching = 0;
+            for sub_schema in schemas {
+                if self.matches(value, sub_schema, depth + 1)? {
+                    matching += 1;
+                }
+            }
+            if matching != 1 {
+                self.fail(
+                    path,
+                    "oneOf",
+                    format!("Expected to match exactly one schema, matched {matching}"),
+                );
+            }
+        }
+        if let Some(sub_schema) = schema.get("not") {
+            if self.matches(value, sub_schema, depth + 1)? {
+                self.fail(path, "not", "Matches a schema it must not match".into());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn has_type(value: &JsonValue, name: &str) -> bool {
+    match name {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "string" => value.is_string(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        "number" => value.is_number(),
+        // 1.0 counts as an integer, as in JSON Schema.
+        "integer" => value
+            .as_f64()
+            .is_some_and(|number| value.is_i64() || value.is_u64() || number.fract() == 0.0),
+        _ => false,
+    }
+}
+
+fn type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "boolean",
+        JsonValue::Number(number) if number.is_f64() => "number",
+        JsonValue::Number(_) => "integer",
+        JsonValue::String(_) => "string",
+        JsonValue::Array(_) => "array",
+        JsonValue::Object(_) => "object",
+    }
+}
+
+/// Appends a JSON pointer segment, escaping `~` and `/` as the spec requires.
+fn child(path: &str, segment: &str) -> String {
+    format!("{path}/{}", segment.replace('~', "~0").replace('/', "~1"))
+}
+
+fn list(values: &[JsonValue]) -> String {
+    let values: Vec<String> = values.iter().map(JsonValue::to_string).collect();
+    format!("[{}]", values.join(", "))
+}
+
+/// Adds `validate` to the `serde` module.
+pub(super) fn register(module: &KMap) {
+    module.insert(
+        "validate",
+        KNativeFunction::new(|ctx: &mut CallContext| match ctx.args() {
+            [value, schema] => {
+                let value = to_json(value.clone())?;
+                let schema = match schema {
+                    KValue::Str(text) => match serde_json::from_str(text) {
+                        Ok(schema) => schema,
+                        Err(error) => return runtime_error!("Failed to parse the schema: {error}"),
+                    },
+                    other => to_json(other.clone())?,
+                };
+                match validate(&value, &schema) {
+                    Ok(errors) => {
+                        let errors: Vec<KValue> = errors.iter().map(error_to_koto).collect();
+                        Ok(KValue::List(KList::from_slice(&errors)))
+                    }
+                    Err(error) => runtime_error!("Invalid schema: {error}"),
+                }
+            }
+            other => runtime_error!(
+                "Expected a value and a schema as a map or JSON string, found {other:?}"
+            ),
+        }),
+    );
+}
+
+fn to_json(value: KValue) -> KotoRuntimeResult<JsonValue> {
+    match koto::serde::from_koto_value(value) {
+        Ok(json) => Ok(json),
+        Err(error) => runtime_error!("Serialization error: {error}"),
+    }
+}
+
+fn error_to_koto(error: &SchemaError) -> KValue {
+    let map = KMap::default();
+    map.insert("path", error.path.as_str());
+    map.insert("keyword", error.keyword.as_str());
+    map.insert("message", error.message.as_str());
+    map.into()
+}
@@ -0,0 +1,133 @@
+//! The sandboxed `host.fs` module, confined to a per-example scratch directory.
+
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+use koto::{prelude::*, runtime::Result as KotoRuntimeResult};
+
+use super::HostState;
+
+const DEFAULT_SCRATCH_ROOT: &str = "target/koto-scratch";
+
+/// The scratch directory used for `example_id`: a folder under `KOTO_SCRATCH_DIR` if set,
+/// otherwise under `target/koto-scratch`.
+pub fn scratch_dir_for(example_id: &str) -> PathBuf {
+    let root = std::env::var_os("KOTO_SCRATCH_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_SCRATCH_ROOT));
+    let name: String = example_id
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    root.join(name)
+}
+
+/// Adds the `fs` submodule to the `host` module. Paths are relative to the runtime's scratch
+/// directory, and anything that would leave it is rejected.
+pub(super) fn register(module: &KMap, host: Arc<HostState>) {
+    let fs_module = KMap::default();
+
+    let state = Arc::clone(&host);
+    fs_module.insert(
+        "read_text",
+        KNativeFunction::new(move |ctx: &mut CallContext| match ctx.args() {
+            [KValue::Str(path), ..] => {
+                let path = resolve(&state, path.as_str())?;
+                match fs::read_to_string(&path) {
+                    Ok(text) => Ok(text.into()),
+                    Err(error) => runtime_error!("Failed to read '{}': {error}", path.display()),
+                }
+            }
+            other => runtime_error!("Expected a path, found {other:?}"),
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    fs_module.insert(
+        "write_text",
+        KNativeFunction::new(move |ctx: &mut CallContext| match ctx.args() {
+            [KValue::Str(path), KValue::Str(text), ..] => {
+                let path = resolve(&state, path.as_str())?;
+                let written = path
+                    .parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|_| fs::write(&path, text.as_str()));
+                match written {
+                    Ok(()) => Ok(KValue::Null),
+                    Err(error) => runtime_error!("Failed to write '{}': {error}", path.display()),
+                }
+            }
+            other => runtime_error!("Expected a path and text, found {other:?}"),
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    fs_module.insert(
+        "list_dir",
+        KNativeFunction::new(move |ctx: &mut CallContext| {
+            let relative = match ctx.args() {
+                [] => ".",
+                [KValue::Str(path), ..] => path.as_str(),
+                other => return runtime_error!("Expected a path, found {other:?}"),
+            };
+            let path = resolve(&state, relative)?;
+            let entries = match fs::read_dir(&path) {
+                Ok(entries) => entries,
+                // A scratch directory nobody has written to yet is simply empty.
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound && relative == "." => {
+                    return Ok(KList::from_slice(&[]).into());
+                }
+                Err(error) => {
+                    return runtime_error!("Failed to list '{}': {error}", path.display());
+                }
+            };
+            let mut names: Vec<String> = entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect();
+            names.sort();
+            let names: Vec<KValue> = names.into_iter().map(KValue::from).collect();
+            Ok(KList::from_slice(&names).into())
+        }),
+    );
+
+    module.insert("fs", fs_module);
+}
+
+/// Maps a script-supplied relative path into the scratch directory.
+pub(super) fn resolve(host: &HostState, relative: &str) -> KotoRuntimeResult<PathBuf> {
+    host.check_interrupts()?;
+    let Some(root) = host.scratch_dir() else {
+        return runtime_error!("host.fs is unavailable: no scratch directory is configured");
+    };
+    let relative = Path::new(relative);
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        return runtime_error!(
+            "Path '{}' must stay inside the scratch directory",
+            relative.display()
+        );
+    }
+    let path = root.join(relative);
+    // Symlinks could still point outside, so existing paths are checked after resolving them.
+    if let (Ok(resolved), Ok(root)) = (path.canonicalize(), root.canonicalize()) {
+        if !resolved.starts_with(&root) {
+            return runtime_error!(
+                "Path '{}' must stay inside the scratch directory",
+                relative.display()
+            );
+        }
+    }
+    Ok(path)
+}
@@ -0,0 +1,202 @@
+//! `serde.from_csv` and `serde.to_csv`: CSV text to and from lists of Koto maps.
+
+use koto::{prelude::*, runtime::Result as KotoRuntimeResult};
+
+/// Adds the CSV functions to the `serde` module.
+pub(super) fn register(module: &KMap) {
+    module.insert(
+        "from_csv",
+        KNativeFunction::new(|ctx: &mut CallContext| match ctx.args() {
+            [KValue::Str(text)] => from_csv(text, &CsvOptions::default()),
+            [KValue::Str(text), KValue::Map(options)] => {
+                from_csv(text, &CsvOptions::from_map(options)?)
+            }
+            other => {
+                runtime_error!("Expected CSV string and optional options map, found {other:?}")
+            }
+        }),
+    );
+    module.insert(
+        "to_csv",
+        KNativeFunction::new(|ctx: &mut CallContext| match ctx.args() {
+            [KValue::List(rows)] => to_csv(rows),
+            other => runtime_error!("Expected a list of maps or lists, found {other:?}"),
+        }),
+    );
+}
+
+struct CsvOptions {
+    /// Treat the first row as column names and return maps; otherwise rows are lists.
+    headers: bool,
+    /// Turn cells that look like numbers into numbers.
+    numbers: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            headers: true,
+            numbers: true,
+        }
+    }
+}
+
+impl CsvOptions {
+    fn from_map(options: &KMap) -> KotoRuntimeResult<Self> {
+        let mut result = Self::default();
+        for (name, flag) in [
+            ("headers", &mut result.headers),
+            ("numbers", &mut result.numbers),
+        ] {
+            match options.get(name) {
+                Some(KValue::Bool(value)) => *flag = value,
+                Some(KValue::Null) | None => {}
+                Some(other) => {
+                    return runtime_error!("Expected {name} to be a bool, found {other:?}");
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+fn from_csv(text: &str, options: &CsvOptions) -> KotoRuntimeResult<KValue> {
+    let mut reader = ::csv::ReaderBuilder::new()
+        .has_headers(options.headers)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let headers: Vec<String> = if options.headers {
+        match reader.headers() {
+            Ok(headers) => headers.iter().map(str::to_string).collect(),
+            Err(error) => return runtime_error!("Failed to parse CSV: {error}"),
+        }
+    } else {
+        Vec::new()
+    };
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(error) => return runtime_error!("Failed to parse CSV: {error}"),
+        };
+        let cells = record.iter().map(|cell| cell_value(cell, options.numbers));
+        if options.headers {
+            let row = KMap::default();
+            for (header, cell) in headers.iter().zip(cells) {
+                row.insert(header.as_str(), cell);
+            }
+            rows.push(KValue::Map(row));
+        } else {
+            rows.push(KValue::List(KList::from_slice(&cells.collect::<Vec<_>>())));
+        }
+    }
+    Ok(KValue::List(KList::from_slice(&rows)))
+}
+
+/// Only plain decimals count as numbers, so values like `007`, `1e3` or `+5` stay strings.
+fn cell_value(cell: &str, numbers: bool) -> KValue {
+    if numbers && is_plain_decimal(cell) {
+        if let Ok(value) = cell.parse::<i64>() {
+            return value.into();
+        }
+        if let Ok(value) = cell.parse::<f64>() {
+            return value.into();
+        }
+    }
+    cell.into()
+}
+
+fn is_plain_decimal(cell: &str) -> bool {
+    let unsigned = cell.strip_prefix('-').unwrap_or(cell);
+    let (integer, fraction) = match unsigned.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (unsigned, None),
+    };
+    let all_digits =
+        |text: &str| !text.is_empty() && text.bytes().all(|byte| byte.is_ascii_digit());
+    all_digits(integer)
+        && (integer == "0" || !integer.starts_with('0'))
+        && fraction.is_none_or(all_digits)
+}
+
+/// Maps become rows under a header made from their keys, in order of first appearance; lists
+/// become rows as they are, without a header.
+fn to_csv(rows: &KList) -> KotoRuntimeResult<KValue> {
+    let rows = rows.data();
+    let mut records: Vec<Vec<String>> = Vec::with_capacity(rows.len() + 1);
+
+    if rows.iter().all(|row| matches!(row, KValue::Map(_))) {
+        let mut headers: Vec<String> = Vec::new();
+        for row in rows.iter() {
+            if let KValue::Map(map) = row {
+                for key in map.data().keys() {
+                    let key = key.to_string();
+                    if !headers.contains(&key) {
+                        headers.push(key);
+                    }
+                }
+            }
+        }
+        for row in rows.iter() {
+            if let KValue::Map(map) = row {
+                let record = headers
+                    .iter()
+                    .map(|header| {
+                        map.get(header.as_str())
+                            .map_or(Ok(String::new()), |value| cell_text(&value))
+                    })
+                    .collect::<KotoRuntimeResult<Vec<_>>>()?;
+                records.push(record);
+            }
+        }
+        if !headers.is_empty() {
+            records.insert(0, headers);
+        }
+    } else {
+        for row in rows.iter() {
+            let record = match row {
+                KValue::List(list) => list
+                    .data()
+                    .iter()
+                    .map(cell_text)
+                    .collect::<KotoRuntimeResult<Vec<_>>>()?,
+                KValue::Tuple(tuple) => tuple
+                    .iter()
+                    .map(cell_text)
+                    .collect::<KotoRuntimeResult<Vec<_>>>()?,
+                other => {
+                    return runtime_error!(
+                        "Expected every row to be a map, or every row to be a list, found {other:?}"
+                    );
+                }
+            };
+            records.push(record);
+        }
+    }
+
+    let mut writer = ::csv::WriterBuilder::new()
+        .flexible(true)
+        .from_writer(Vec::new());
+    for record in &records {
+        if let Err(error) = writer.write_record(record) {
+            return runtime_error!("Failed to write CSV: {error}");
+        }
+    }
+    match writer.into_inner() {
+        Ok(bytes) => Ok(String::from_utf8_lossy(&bytes).into_owned().into()),
+        Err(error) => runtime_error!("Failed to write CSV: {error}"),
+    }
+}
+
+fn cell_text(value: &KValue) -> KotoRuntimeResult<String> {
+    match value {
+        KValue::Null => Ok(String::new()),
+        KValue::Bool(flag) => Ok(flag.to_string()),
+        KValue::Number(number) => Ok(number.to_string()),
+        KValue::Str(text) => Ok(text.to_string()),
+        other => {
+            runtime_error!("CSV cells must be strings, numbers, bools, or null, found {other:?}")
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use std::{
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use koto::{prelude::*, runtime::Result as KotoRuntimeResult};
+
+use super::{HostState, number_to_i64};
+
+const SLEEP_SLICE: Duration = Duration::from_millis(10);
+
+/// Adds the timing helpers (`sleep_ms`, `interval`, `elapsed`) to the `host` module.
+pub(super) fn register(module: &KMap, host: Arc<HostState>) {
+    let state = Arc::clone(&host);
+    module.insert(
+        "sleep_ms",
+        KNativeFunction::new(move |ctx: &mut CallContext| match ctx.args() {
+            [KValue::Number(n), ..] => {
+                let millis = number_to_i64(n);
+                if millis < 0 {
+                    return runtime_error!("Expected non-negative duration, found {millis}");
+                }
+                sleep_cooperatively(&state, Duration::from_millis(millis as u64))?;
+                Ok(KValue::Null)
+            }
+            other => runtime_error!("Expected duration in milliseconds, found {other:?}"),
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "interval",
+        KNativeFunction::new(move |ctx: &mut CallContext| {
+            let (callback, millis, count) = match ctx.args() {
+                [callback, KValue::Number(millis), KValue::Number(count)]
+                    if callback.is_callable() =>
+                {
+                    (callback.clone(), number_to_i64(millis), number_to_i64(count))
+                }
+                other => {
+                    return runtime_error!(
+                        "Expected a function, an interval in milliseconds, and a count, found {other:?}"
+                    );
+                }
+            };
+            if millis < 0 || count < 0 {
+                return runtime_error!(
+                    "Expected non-negative interval and count, found {millis} and {count}"
+                );
+            }
+            let mut ticks = 0i64;
+            for tick in 0..count {
+                sleep_cooperatively(&state, Duration::from_millis(millis as u64))?;
+                let result = ctx.vm.call_function(callback.clone(), &[KValue::from(tick)])?;
+                ticks += 1;
+                if stops_ticking(&result) {
+                    break;
+                }
+            }
+            Ok(ticks.into())
+        }),
+    );
+
+    let state = Arc::clone(&host);
+    module.insert(
+        "elapsed",
+        KNativeFunction::new(move |_ctx: &mut CallContext| {
+            let elapsed = state.run_elapsed().unwrap_or_default();
+            Ok(elapsed.as_secs_f64().into())
+        }),
+    );
+}
+
+/// Whether a callback ticked by `host.interval` or `host.every` asked to stop. Both pass the
+/// tick number and stop when the callback returns `false`, which makes polling loops read
+/// naturally.
+pub(super) fn stops_ticking(result: &KValue) -> bool {
+    matches!(result, KValue::Bool(false))
+}
+
+/// Sleeps in short slices so cancellation requests and the execution and memory limits are honoured
+/// while the script is waiting. In deterministic mode the virtual clock is advanced instead.
+pub(super) fn sleep_cooperatively(host: &HostState, duration: Duration) -> KotoRuntimeResult<()> {
+    if host.is_deterministic() {
+        host.check_interrupts()?;
+        host.advance_virtual_clock(duration);
+        return Ok(());
+    }
+    let target = Instant::now() + duration;
+    loop {
+        host.check_interrupts()?;
+        let now = Instant::now();
+        if let Some(deadline) = host.deadline() {
+            if now >= deadline {
+                return runtime_error!("Execution limit exceeded while sleeping");
+            }
+        }
+        if now >= target {
+            return Ok(());
+        }
+        thread::sleep(SLEEP_SLICE.min(target - now));
+    }
+}
@@ -0,0 +1,81 @@
+//! Line diffs between rendered return values, so the explorer can show what an edit changed
+//! in a script's result.
+
+use serde_json::Value as JsonValue;
+
+/// Diffs bigger than this many line pairs skip the alignment and show the whole value as
+/// replaced, which keeps huge results from stalling the UI.
+const MAX_ALIGNED_CELLS: usize = 1_000_000;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DiffKind {
+    Same,
+    Removed,
+    Added,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DiffLine {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+/// The text a return value is compared by: nested values as pretty-printed JSON, one entry per
+/// line, and anything else as it is displayed.
+pub fn value_text(display: &str, value: Option<&JsonValue>) -> String {
+    match value {
+        Some(value @ (JsonValue::Object(_) | JsonValue::Array(_))) => {
+            serde_json::to_string_pretty(value).unwrap_or_else(|_| display.to_string())
+        }
+        _ => display.to_string(),
+    }
+}
+
+/// Aligns the lines of `old` and `new` along their longest common subsequence.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let line = |kind, text: &str| DiffLine {
+        kind,
+        text: text.to_string(),
+    };
+
+    if old.len().saturating_mul(new.len()) > MAX_ALIGNED_CELLS {
+        return old
+            .iter()
+            .map(|text| line(DiffKind::Removed, text))
+            .chain(new.iter().map(|text| line(DiffKind::Added, text)))
+            .collect();
+    }
+
+    // common[i][j] is the length of the longest common subsequence of old[i..] and new[j..].
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::with_capacity(old.len().max(new.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(line(DiffKind::Same, old[i]));
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            lines.push(line(DiffKind::Removed, old[i]));
+            i += 1;
+        } else {
+            lines.push(line(DiffKind::Added, new[j]));
+            j += 1;
+        }
+    }
+    lines.extend(old[i..].iter().map(|text| line(DiffKind::Removed, text)));
+    lines.extend(new[j..].iter().map(|text| line(DiffKind::Added, text)));
+    lines
+}
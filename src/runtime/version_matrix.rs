@@ -0,0 +1,219 @@
+//! Runs a script under other Koto builds, e.g. the CLI of an upcoming upstream release, and
+//! compares their behaviour and speed with the Koto embedded in the explorer.
+//!
+//! Builds are listed in `koto_versions.json` in the config directory, or in the file named by
+//! `KOTO_VERSION_MATRIX`:
+//!
+//! ```json
+//! { "builds": [{ "name": "koto 0.15", "command": "/opt/koto-0.15/bin/koto" }] }
+//! ```
+//!
+//! Each build is started as `<command> <args...> <script path>`, so any Koto CLI works. Host
+//! modules such as `host` only exist in the embedded build, so the matrix is meant for
+//! examples written in plain Koto.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use super::process::{join_reader, spawn_reader};
+
+const VERSION_MATRIX_FILE: &str = "koto_versions.json";
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+/// Runs this much slower than the embedded build are reported as regressions.
+pub const SLOWDOWN_THRESHOLD: f64 = 1.5;
+/// External builds pay for starting a process, so shorter runs aren't compared for speed.
+const MIN_COMPARED_DURATION: Duration = Duration::from_millis(200);
+/// Appended to a suite script so a CLI build runs its `tests` export.
+const RUN_TESTS_SNIPPET: &str = "\ntest.run_tests tests\n";
+
+/// A Koto build started as an external command.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct KotoBuild {
+    pub name: String,
+    pub command: PathBuf,
+    /// Passed before the script path.
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// The builds to compare against, as configured by the user.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct VersionMatrix {
+    #[serde(default)]
+    pub builds: Vec<KotoBuild>,
+}
+
+impl VersionMatrix {
+    /// Where the matrix is read from: `KOTO_VERSION_MATRIX` if set, otherwise the platform
+    /// config directory.
+    pub fn config_path() -> Option<PathBuf> {
+        std::env::var_os("KOTO_VERSION_MATRIX")
+            .map(PathBuf::from)
+            .or_else(|| {
+                ProjectDirs::from("", "", "koto_learning")
+                    .map(|dirs| dirs.config_dir().join(VERSION_MATRIX_FILE))
+            })
+    }
+
+    /// Loads the configured builds; a missing file means no builds.
+    pub fn load() -> Result<Self> {
+        match Self::config_path() {
+            Some(path) if path.exists() => Self::load_from(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&text).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+}
+
+/// What one build did with the script.
+#[derive(Clone, Debug)]
+pub struct BuildRun {
+    pub build: String,
+    pub succeeded: bool,
+    pub stdout: String,
+    pub stderr: String,
+    pub duration: Duration,
+}
+
+impl BuildRun {
+    /// A run of the Koto embedded in the explorer.
+    pub fn embedded(succeeded: bool, stdout: String, stderr: String, duration: Duration) -> Self {
+        Self {
+            build: format!("embedded (koto {})", env!("KOTO_LEARNING_KOTO_VERSION")),
+            succeeded,
+            stdout,
+            stderr,
+            duration,
+        }
+    }
+
+    /// A build that couldn't be run at all, e.g. because its command doesn't exist.
+    pub fn failed_to_start(build: &KotoBuild, error: &anyhow::Error) -> Self {
+        Self {
+            build: build.name.clone(),
+            succeeded: false,
+            stdout: String::new(),
+            stderr: format!("{error:#}"),
+            duration: Duration::ZERO,
+        }
+    }
+
+    /// How this run behaved differently from `baseline`, if it did. Output is only compared
+    /// when `compare_output` is set, since test runners format their reports differently.
+    pub fn behaviour_change(&self, baseline: &BuildRun, compare_output: bool) -> Option<String> {
+        match (baseline.succeeded, self.succeeded) {
+            (true, false) => Some("fails where the embedded build succeeds".to_string()),
+            (false, true) => Some("succeeds where the embedded build fails".to_string()),
+            _ if compare_output && self.stdout != baseline.stdout => {
+                Some("prints different output".to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// How many times slower than `baseline` this run was.
+    pub fn slowdown(&self, baseline: &BuildRun) -> f64 {
+        self.duration.as_secs_f64() / baseline.duration.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// One script run under the embedded build and every configured build.
+#[derive(Clone, Debug)]
+pub struct MatrixRow {
+    /// "Script", or the name of a test suite.
+    pub target: String,
+    /// Whether the builds are expected to print the same; false for test suites.
+    pub compare_output: bool,
+    pub baseline: BuildRun,
+    pub builds: Vec<BuildRun>,
+}
+
+impl MatrixRow {
+    /// Builds that behaved differently or ran noticeably slower, with what changed.
+    pub fn differences(&self) -> Vec<(&str, String)> {
+        self.builds
+            .iter()
+            .filter_map(|run| {
+                let change = run
+                    .behaviour_change(&self.baseline, self.compare_output)
+                    .or_else(|| {
+                        let slowdown = run.slowdown(&self.baseline);
+                        (run.duration >= MIN_COMPARED_DURATION && slowdown >= SLOWDOWN_THRESHOLD)
+                            .then(|| format!("runs {slowdown:.1}x slower"))
+                    })?;
+                Some((run.build.as_str(), change))
+            })
+            .collect()
+    }
+}
+
+/// Runs `script` with `build`, killing it after `timeout`. A suite script gets a call to
+/// `test.run_tests` on its `tests` export appended, so its cases run too.
+pub fn run_build(
+    build: &KotoBuild,
+    script: &str,
+    suite: bool,
+    scratch_dir: &Path,
+    timeout: Duration,
+) -> Result<BuildRun> {
+    fs::create_dir_all(scratch_dir)
+        .with_context(|| format!("Failed to create {}", scratch_dir.display()))?;
+    let path = scratch_dir.join("version_matrix.koto");
+    let mut source = script.to_string();
+    if suite {
+        source.push_str(RUN_TESTS_SNIPPET);
+    }
+    fs::write(&path, source).with_context(|| format!("Failed to write {}", path.display()))?;
+
+    let mut child = Command::new(&build.command)
+        .args(&build.args)
+        .arg(&path)
+        .current_dir(scratch_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to start '{}'", build.command.display()))?;
+    let stdout_reader = child.stdout.take().map(spawn_reader);
+    let stderr_reader = child.stderr.take().map(spawn_reader);
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if start.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            break None;
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+    let duration = start.elapsed();
+
+    let mut stderr = stderr_reader.map(join_reader).unwrap_or_default();
+    if status.is_none() {
+        stderr.push_str(&format!("Timed out after {:.2}s\n", timeout.as_secs_f64()));
+    }
+    Ok(BuildRun {
+        build: build.name.clone(),
+        succeeded: status.is_some_and(|status| status.success()),
+        stdout: stdout_reader.map(join_reader).unwrap_or_default(),
+        stderr,
+        duration,
+    })
+}
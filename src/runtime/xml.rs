@@ -0,0 +1,204 @@
+//! `serde.from_xml` and `serde.to_xml`. Elements map to Koto maps of the form
+//! `{name: 'item', attributes: {id: '1'}, children: [...]}`, where children are element maps or
+//! strings of text, so a document round-trips without a schema.
+
+use koto::{prelude::*, runtime::Result as KotoRuntimeResult};
+use quick_xml::{Reader, escape::escape, events::Event};
+use serde_json::{Map, Value as JsonValue};
+
+/// Adds `from_xml` and `to_xml` to the `serde` module.
+pub(super) fn register(module: &KMap) {
+    module.insert(
+        "from_xml",
+        KNativeFunction::new(|ctx: &mut CallContext| match ctx.args() {
+            [KValue::Str(text)] => match parse(text) {
+                Ok(root) => match koto::serde::to_koto_value(root) {
+                    Ok(value) => Ok(value),
+                    Err(error) => runtime_error!("Failed to convert XML: {error}"),
+                },
+                Err(error) => runtime_error!("Failed to parse XML: {error}"),
+            },
+            other => runtime_error!("Expected an XML string, found {other:?}"),
+        }),
+    );
+    module.insert(
+        "to_xml",
+        KNativeFunction::new(|ctx: &mut CallContext| {
+            let value = ctx.args().first().cloned().unwrap_or(KValue::Null);
+            let json = match koto::serde::from_koto_value(value) {
+                Ok(json) => json,
+                Err(error) => return runtime_error!("Serialization error: {error}"),
+            };
+            let mut out = String::new();
+            write_element(&json, 0, &mut out)?;
+            Ok(out.into())
+        }),
+    );
+}
+
+fn new_element(name: String, attributes: Map<String, JsonValue>) -> Map<String, JsonValue> {
+    let mut element = Map::new();
+    element.insert("name".into(), name.into());
+    element.insert("attributes".into(), JsonValue::Object(attributes));
+    element.insert("children".into(), JsonValue::Array(Vec::new()));
+    element
+}
+
+fn push_child(parent: &mut Map<String, JsonValue>, child: JsonValue) {
+    if let Some(JsonValue::Array(children)) = parent.get_mut("children") {
+        children.push(child);
+    }
+}
+
+/// Parses a document into its root element. Comments, processing instructions, and the
+/// declaration are dropped, and whitespace between elements is ignored.
+fn parse(text: &str) -> Result<JsonValue, String> {
+    let mut reader = Reader::from_str(text);
+    reader.config_mut().trim_text(true);
+    // Elements that have been opened but not yet closed, innermost last.
+    let mut open: Vec<Map<String, JsonValue>> = Vec::new();
+    let mut root = None;
+    let mut finish = |element: JsonValue, open: &mut Vec<Map<String, JsonValue>>| {
+        match open.last_mut() {
+            Some(parent) => push_child(parent, element),
+            None if root.is_none() => root = Some(element),
+            None => return Err("XML documents have a single root element".to_string()),
+        }
+        Ok(())
+    };
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|error| format!("{error} at byte {}", reader.error_position()))?;
+        let is_empty = matches!(event, Event::Empty(_));
+        match event {
+            Event::Start(start) | Event::Empty(start) => {
+                let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+                let mut attributes = Map::new();
+                for attribute in start.attributes() {
+                    let attribute = attribute.map_err(|error| error.to_string())?;
+                    let key = String::from_utf8_lossy(attribute.key.as_ref()).into_owned();
+                    let value = attribute
+                        .unescape_value()
+                        .map_err(|error| error.to_string())?;
+                    attributes.insert(key, value.into_owned().into());
+                }
+                let element = new_element(name, attributes);
+                if is_empty {
+                    finish(JsonValue::Object(element), &mut open)?;
+                } else {
+                    open.push(element);
+                }
+            }
+            Event::End(_) => {
+                if let Some(element) = open.pop() {
+                    finish(JsonValue::Object(element), &mut open)?;
+                }
+            }
+            Event::Text(text) => {
+                let text = text.unescape().map_err(|error| error.to_string())?;
+                match open.last_mut() {
+                    Some(parent) => push_child(parent, text.into_owned().into()),
+                    None => return Err(format!("Text outside the root element: '{text}'")),
+                }
+            }
+            Event::CData(data) => {
+                let text = String::from_utf8_lossy(&data.into_inner()).into_owned();
+                if let Some(parent) = open.last_mut() {
+                    push_child(parent, text.into());
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    if let Some(unclosed) = open.last() {
+        return Err(format!(
+            "<{}> is never closed",
+            unclosed["name"].as_str().unwrap_or_default()
+        ));
+    }
+    root.ok_or_else(|| "The document has no root element".to_string())
+}
+
+/// Writes an element map, indented by two spaces per level. Elements whose only child is
+/// text stay on one line.
+fn write_element(value: &JsonValue, depth: usize, out: &mut String) -> KotoRuntimeResult<()> {
+    let indent = "  ".repeat(depth);
+    let element = match value {
+        JsonValue::Object(element) => element,
+        other => return runtime_error!("Expected an element map with a name, found {other}"),
+    };
+    let name = match element.get("name") {
+        Some(JsonValue::String(name)) if is_valid_name(name) => name,
+        Some(other) => return runtime_error!("Invalid element name {other}"),
+        None => return runtime_error!("Expected an element map with a name, found {value}"),
+    };
+
+    out.push_str(&indent);
+    out.push('<');
+    out.push_str(name);
+    if let Some(attributes) = element.get("attributes") {
+        let Some(attributes) = attributes.as_object() else {
+            return runtime_error!("Expected the attributes of <{name}> to be a map");
+        };
+        for (key, value) in attributes {
+            if !is_valid_name(key) {
+                return runtime_error!("Invalid attribute name '{key}' on <{name}>");
+            }
+            out.push_str(&format!(
+                " {key}=\"{}\"",
+                escape(&scalar_text(name, value)?)
+            ));
+        }
+    }
+
+    let children = match element.get("children") {
+        Some(JsonValue::Array(children)) => children.as_slice(),
+        Some(JsonValue::Null) | None => &[],
+        Some(other) => {
+            return runtime_error!("Expected the children of <{name}> to be a list, found {other}");
+        }
+    };
+    match children {
+        [] => out.push_str("/>\n"),
+        [child] if !child.is_object() => {
+            out.push('>');
+            out.push_str(&escape(&scalar_text(name, child)?));
+            out.push_str(&format!("</{name}>\n"));
+        }
+        children => {
+            out.push_str(">\n");
+            for child in children {
+                if child.is_object() {
+                    write_element(child, depth + 1, out)?;
+                } else {
+                    out.push_str(&"  ".repeat(depth + 1));
+                    out.push_str(&escape(&scalar_text(name, child)?));
+                    out.push('\n');
+                }
+            }
+            out.push_str(&format!("{indent}</{name}>\n"));
+        }
+    }
+    Ok(())
+}
+
+/// Text for attribute values and text children; numbers and bools are written as-is.
+fn scalar_text(element: &str, value: &JsonValue) -> KotoRuntimeResult<String> {
+    match value {
+        JsonValue::String(text) => Ok(text.clone()),
+        JsonValue::Number(number) => Ok(number.to_string()),
+        JsonValue::Bool(flag) => Ok(flag.to_string()),
+        other => runtime_error!("Expected text in <{element}>, found {other}"),
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_' || first == ':')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | ':' | '-' | '.'))
+}
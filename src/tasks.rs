@@ -0,0 +1,335 @@
+//! Background jobs with progress, cancellation, and completion events.
+//!
+//! [`spawn`] runs a closure on a worker thread and registers it with the process-wide task
+//! list, so every job shows up in the busy indicator and the Tasks panel whichever thread it
+//! runs on. The caller keeps a [`Task`] to poll for the result, while [`take_events`] yields a
+//! [`TaskEvent`] for every job that finished since the last call. Work that has to stay on
+//! the calling thread, such as a catalog reload, is tracked the same way with [`run_blocking`].
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::{self, Receiver, TryRecvError},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::anyhow;
+use once_cell::sync::Lazy;
+
+use crate::runtime::logging;
+
+const MAX_FINISHED_TASKS: usize = 20;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+static ACTIVE: Lazy<Mutex<Vec<TaskSnapshot>>> = Lazy::new(Default::default);
+static FINISHED: Lazy<Mutex<VecDeque<TaskSnapshot>>> = Lazy::new(Default::default);
+static CANCEL_FLAGS: Lazy<Mutex<HashMap<u64, Arc<AtomicBool>>>> = Lazy::new(Default::default);
+static EVENTS: Lazy<Mutex<Vec<TaskEvent>>> = Lazy::new(Default::default);
+
+/// A point-in-time view of a tracked task.
+#[derive(Clone, Debug)]
+pub struct TaskSnapshot {
+    pub id: u64,
+    pub label: String,
+    /// Completed and total steps, when the job knows how much work remains.
+    pub steps: Option<(usize, usize)>,
+    pub message: Option<String>,
+    pub started: Instant,
+    pub finished: Option<TaskOutcome>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TaskOutcome {
+    pub succeeded: bool,
+    pub elapsed: Duration,
+}
+
+impl TaskSnapshot {
+    /// Progress between 0 and 1, if known.
+    pub fn fraction(&self) -> Option<f32> {
+        self.steps.map(|(completed, total)| {
+            if total == 0 {
+                1.0
+            } else {
+                completed as f32 / total as f32
+            }
+        })
+    }
+}
+
+/// Emitted once per job when it completes, fails, or is cancelled.
+#[derive(Clone, Debug)]
+pub struct TaskEvent {
+    pub id: u64,
+    pub label: String,
+    pub status: TaskStatus,
+    pub elapsed: Duration,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TaskStatus {
+    Completed,
+    Failed(String),
+    Cancelled,
+}
+
+/// Passed to a job so it can report progress and notice cancellation.
+pub struct TaskContext {
+    id: u64,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl TaskContext {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn set_steps(&self, completed: usize, total: usize) {
+        update(self.id, |task| {
+            task.steps = Some((completed.min(total), total))
+        });
+    }
+
+    pub fn set_message(&self, message: impl Into<String>) {
+        let message = message.into();
+        update(self.id, |task| task.message = Some(message));
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// The flag set when cancellation is requested, for work that watches it on its own, such
+    /// as a script run given it through [`RunOptions::cancel_flag`](crate::runtime::RunOptions::cancel_flag).
+    pub fn cancel_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.cancelled)
+    }
+
+    /// Returns an error once cancellation has been requested, for use with `?` between steps.
+    pub fn check_cancelled(&self) -> anyhow::Result<()> {
+        if self.is_cancelled() {
+            Err(anyhow!("Task was cancelled"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A running job whose result can be polled from the UI thread.
+pub struct Task<T> {
+    id: u64,
+    receiver: Receiver<anyhow::Result<T>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl<T> Task<T> {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Replaces the message shown with the job, e.g. to say that it's stopping.
+    pub fn set_message(&self, message: impl Into<String>) {
+        let message = message.into();
+        update(self.id, |task| task.message = Some(message));
+    }
+
+    /// Returns the result once the job has finished; `None` while it is still running.
+    pub fn poll(&self) -> Option<anyhow::Result<T>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(anyhow!("Task ended without a result"))),
+        }
+    }
+}
+
+/// Runs `job` on a worker thread, tracked under `label`.
+pub fn spawn<T, F>(label: impl Into<String>, job: F) -> Task<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&TaskContext) -> anyhow::Result<T> + Send + 'static,
+{
+    spawn_job(label.into(), true, job)
+}
+
+/// Like [`spawn`], but without a [`TaskEvent`] when the job ends, for callers that report the
+/// outcome themselves, such as script runs, which print their own summary.
+pub fn spawn_quietly<T, F>(label: impl Into<String>, job: F) -> Task<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&TaskContext) -> anyhow::Result<T> + Send + 'static,
+{
+    spawn_job(label.into(), false, job)
+}
+
+/// Runs `job` on the calling thread, tracked under `label` like a spawned job while it runs.
+/// The caller gets the result directly, so no [`TaskEvent`] is recorded.
+pub fn run_blocking<T>(
+    label: impl Into<String>,
+    job: impl FnOnce(&TaskContext) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    let (context, started) = start(label.into());
+    let result = job(&context);
+    finish(&context, started, &result);
+    result
+}
+
+fn spawn_job<T, F>(label: String, emit_event: bool, job: F) -> Task<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&TaskContext) -> anyhow::Result<T> + Send + 'static,
+{
+    let (context, started) = start(label.clone());
+    let id = context.id;
+    let cancelled = context.cancel_flag();
+
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let result = job(&context);
+        let status = finish(&context, started, &result);
+        if emit_event {
+            if let Ok(mut events) = EVENTS.lock() {
+                events.push(TaskEvent {
+                    id,
+                    label,
+                    status,
+                    elapsed: started.elapsed(),
+                });
+            }
+        }
+        let _ = sender.send(result);
+    });
+
+    Task {
+        id,
+        receiver,
+        cancelled,
+    }
+}
+
+/// Registers a job; it stays active until [`finish`] is called with its context.
+fn start(label: String) -> (TaskContext, Instant) {
+    let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+    let started = Instant::now();
+    logging::with_runtime_subscriber(|| {
+        tracing::debug!(target: "runtime.tasks", id, label = label.as_str(), "Task started");
+    });
+    if let Ok(mut active) = ACTIVE.lock() {
+        active.push(TaskSnapshot {
+            id,
+            label,
+            steps: None,
+            message: None,
+            started,
+            finished: None,
+        });
+    }
+    let cancelled = Arc::new(AtomicBool::new(false));
+    if let Ok(mut flags) = CANCEL_FLAGS.lock() {
+        flags.insert(id, cancelled.clone());
+    }
+    (TaskContext { id, cancelled }, started)
+}
+
+fn finish<T>(context: &TaskContext, started: Instant, result: &anyhow::Result<T>) -> TaskStatus {
+    let status = match (result, context.is_cancelled()) {
+        (_, true) => TaskStatus::Cancelled,
+        (Ok(_), false) => TaskStatus::Completed,
+        (Err(error), false) => TaskStatus::Failed(format!("{error:#}")),
+    };
+    if let Ok(mut flags) = CANCEL_FLAGS.lock() {
+        flags.remove(&context.id);
+    }
+    let Some(mut task) = ACTIVE.lock().ok().and_then(|mut active| {
+        let index = active.iter().position(|task| task.id == context.id)?;
+        Some(active.remove(index))
+    }) else {
+        return status;
+    };
+    let succeeded = status == TaskStatus::Completed;
+    let elapsed = started.elapsed();
+    task.finished = Some(TaskOutcome { succeeded, elapsed });
+    logging::with_runtime_subscriber(|| {
+        tracing::debug!(
+            target: "runtime.tasks",
+            id = context.id,
+            label = task.label.as_str(),
+            succeeded,
+            elapsed_ms = elapsed.as_millis() as u64,
+            "Task finished"
+        );
+    });
+    if let Ok(mut finished) = FINISHED.lock() {
+        finished.push_front(task);
+        finished.truncate(MAX_FINISHED_TASKS);
+    }
+    status
+}
+
+fn update(id: u64, f: impl FnOnce(&mut TaskSnapshot)) {
+    if let Ok(mut active) = ACTIVE.lock() {
+        if let Some(task) = active.iter_mut().find(|task| task.id == id) {
+            f(task);
+        }
+    }
+}
+
+/// Jobs that are still running, oldest first.
+pub fn active() -> Vec<TaskSnapshot> {
+    ACTIVE
+        .lock()
+        .map(|active| active.clone())
+        .unwrap_or_default()
+}
+
+/// Recently finished jobs, newest first.
+pub fn finished() -> Vec<TaskSnapshot> {
+    FINISHED
+        .lock()
+        .map(|finished| finished.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+pub fn is_busy() -> bool {
+    ACTIVE
+        .lock()
+        .map(|active| !active.is_empty())
+        .unwrap_or(false)
+}
+
+/// Requests cancellation of a running job by id; returns `false` if it isn't running.
+pub fn cancel(id: u64) -> bool {
+    CANCEL_FLAGS
+        .lock()
+        .ok()
+        .and_then(|flags| flags.get(&id).cloned())
+        .map(|flag| flag.store(true, Ordering::SeqCst))
+        .is_some()
+}
+
+pub fn is_cancellable(id: u64) -> bool {
+    CANCEL_FLAGS
+        .lock()
+        .map(|flags| flags.contains_key(&id))
+        .unwrap_or(false)
+}
+
+/// Drains the completion events recorded since the last call.
+pub fn take_events() -> Vec<TaskEvent> {
+    EVENTS
+        .lock()
+        .map(|mut events| events.drain(..).collect())
+        .unwrap_or_default()
+}
@@ -0,0 +1,472 @@
+mod support;
+
+use std::{fs, path::Path, time::Duration};
+
+use egui::accesskit::Role;
+use egui_kittest::kittest::Queryable;
+use koto_learning::examples::{ExampleInput, ExampleLibrary, ExampleMetadata, InputType};
+use support::{
+    TestExample, explorer, explorer_with_library, explorer_with_metadata, wait_for_label,
+};
+use tempfile::tempdir;
+
+const EXAMPLES: &[TestExample] = &[
+    TestExample {
+        id: "greeting",
+        title: "Greeting",
+        categories: &["basics"],
+        script: "print 'hello from kittest'\n40 + 2",
+    },
+    TestExample {
+        id: "lists",
+        title: "Lists",
+        categories: &["collections"],
+        script: "[1, 2, 3].size()",
+    },
+];
+
+/// Writes a `demo` example into `dir` and loads the folder into a library that the test
+/// refreshes itself, in place of the watcher.
+fn folder_library(dir: &Path, meta: &str, script: &str) -> &'static ExampleLibrary {
+    let example_dir = dir.join("demo");
+    fs::create_dir_all(&example_dir).unwrap();
+    fs::write(example_dir.join("meta.json"), meta).unwrap();
+    fs::write(example_dir.join("script.koto"), script).unwrap();
+    Box::leak(Box::new(
+        ExampleLibrary::new_unwatched(dir.to_path_buf()).expect("library"),
+    ))
+}
+
+#[test]
+fn the_first_example_is_selected_on_startup() {
+    let harness = explorer(EXAMPLES);
+    assert!(harness.query_by_label("About Greeting").is_some());
+    assert!(harness.query_by_label("About Lists").is_none());
+}
+
+#[test]
+fn clicking_an_example_in_the_sidebar_selects_it() {
+    let mut harness = explorer(EXAMPLES);
+    harness.get_by_label("Lists").click();
+    harness.run_steps(2);
+    assert!(harness.query_by_label("About Lists").is_some());
+    assert!(harness.query_by_label("About Greeting").is_none());
+}
+
+#[test]
+fn category_filters_hide_other_examples() {
+    let mut harness = explorer(EXAMPLES);
+    harness
+        .get_by_role_and_label(Role::CheckBox, "basics")
+        .click();
+    harness.run_steps(2);
+    assert!(harness.query_by_label("Filters: basics").is_some());
+    assert!(harness.query_by_label("Lists").is_none());
+
+    harness.get_by_label("Clear filters").click();
+    harness.run_steps(2);
+    assert!(harness.query_by_label("Lists").is_some());
+}
+
+#[test]
+fn running_an_example_shows_its_output_in_the_console() {
+    let mut harness = explorer(EXAMPLES);
+    harness.get_by_label("Run example").click();
+    wait_for_label(&mut harness, "Return value: 42", Duration::from_secs(10));
+    assert!(
+        harness
+            .query_by_label_contains("hello from kittest")
+            .is_some()
+    );
+}
+
+#[test]
+fn clearing_the_console_removes_its_entries() {
+    let mut harness = explorer(EXAMPLES);
+    assert!(
+        harness
+            .query_by_label("Ready to explore Koto scripts")
+            .is_some()
+    );
+    harness.get_by_label("Clear").click();
+    harness.run_steps(2);
+    assert!(
+        harness
+            .query_by_label("Ready to explore Koto scripts")
+            .is_none()
+    );
+}
+
+#[test]
+fn scoping_the_console_hides_other_examples_output() {
+    let mut harness = explorer(EXAMPLES);
+    harness.get_by_label("Run example").click();
+    wait_for_label(&mut harness, "Return value: 42", Duration::from_secs(10));
+
+    harness.get_by_label("Lists").click();
+    harness.run_steps(2);
+    harness.get_by_label("This example only").click();
+    harness.run_steps(2);
+    assert!(
+        harness
+            .query_by_label_contains("hello from kittest")
+            .is_none()
+    );
+
+    harness.get_by_label("This example only").click();
+    harness.run_steps(2);
+    assert!(
+        harness
+            .query_by_label_contains("hello from kittest")
+            .is_some()
+    );
+}
+
+#[test]
+fn repeated_console_messages_are_counted_instead_of_repeated() {
+    let metadata = ExampleMetadata {
+        id: "countdown".to_string(),
+        title: "Countdown".to_string(),
+        description: "About Countdown".to_string(),
+        categories: vec!["basics".to_string()],
+        inputs: vec![ExampleInput {
+            name: "start".to_string(),
+            kind: InputType::Number,
+            default: Some("ten".to_string()),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let mut harness = explorer_with_metadata(vec![(metadata, "input.start".to_string())]);
+
+    // Each click reports the same invalid input without running anything in between.
+    harness.get_by_label("Run example").click();
+    wait_for_label(&mut harness, "expects a number", Duration::from_secs(10));
+    let shown = harness
+        .query_all_by_label_contains("expects a number")
+        .count();
+    harness.get_by_label("Run example").click();
+    wait_for_label(&mut harness, "×2", Duration::from_secs(10));
+    assert_eq!(
+        harness
+            .query_all_by_label_contains("expects a number")
+            .count(),
+        shown
+    );
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn exporting_the_view_renders_it_off_screen() {
+    let mut harness = explorer(EXAMPLES);
+    harness.get_by_label("File").click();
+    harness.run_steps(2);
+    harness.get_by_label("Export view").click();
+    harness.run_steps(2);
+    harness.get_by_label("As PNG").click();
+    wait_for_label(
+        &mut harness,
+        "Exported the view to",
+        Duration::from_secs(10),
+    );
+
+    let message = harness
+        .get_by_label_contains("Exported the view to")
+        .label()
+        .expect("export message");
+    let path = message
+        .trim_start_matches("Exported the view to ")
+        .to_string();
+    let image = image::open(&path).expect("exported image").into_rgba8();
+    let _ = std::fs::remove_file(&path);
+    assert!(image.width() > 0 && image.height() > 0);
+    // The docs and code are drawn, not just the panel background.
+    let first = image.get_pixel(0, 0);
+    assert!(image.pixels().any(|pixel| pixel != first));
+}
+
+#[test]
+fn ctrl_enter_evaluates_the_cursor_line_inline() {
+    let mut harness = explorer(&[TestExample {
+        id: "bindings",
+        title: "Bindings",
+        categories: &["basics"],
+        script: "x = 20\nx + 1",
+    }]);
+    assert!(harness.query_by_label("Clear results").is_none());
+
+    harness
+        .get_by(|node| {
+            node.role() == Role::MultilineTextInput
+                && node.value().is_some_and(|value| value.contains("x + 1"))
+        })
+        .click();
+    harness.run_steps(2);
+    harness.key_press_modifiers(egui::Modifiers::COMMAND, egui::Key::Enter);
+    harness.run_steps(2);
+    assert!(harness.query_by_label("Clear results").is_some());
+
+    harness.get_by_label("Clear results").click();
+    harness.run_steps(2);
+    assert!(harness.query_by_label("Clear results").is_none());
+}
+
+#[test]
+fn presentation_mode_steps_through_examples_with_the_keyboard() {
+    let mut harness = explorer(EXAMPLES);
+    harness.get_by_label("Present").click();
+    harness.run_steps(2);
+    assert!(harness.query_by_label("1 / 2").is_some());
+    assert!(harness.query_by_label("About Greeting").is_some());
+
+    harness.key_press(egui::Key::ArrowRight);
+    harness.run_steps(2);
+    assert!(harness.query_by_label("2 / 2").is_some());
+    assert!(harness.query_by_label("About Lists").is_some());
+
+    // The last slide stays put rather than wrapping around.
+    harness.key_press(egui::Key::ArrowRight);
+    harness.run_steps(2);
+    assert!(harness.query_by_label("2 / 2").is_some());
+
+    harness.key_press(egui::Key::ArrowLeft);
+    harness.run_steps(2);
+    assert!(harness.query_by_label("1 / 2").is_some());
+
+    harness.key_press(egui::Key::R);
+    wait_for_label(&mut harness, "Return value: 42", Duration::from_secs(10));
+
+    harness.key_press(egui::Key::Escape);
+    harness.run_steps(2);
+    assert!(harness.query_by_label("1 / 2").is_none());
+    assert!(harness.query_by_label("Present").is_some());
+}
+
+#[test]
+fn hot_reload_reruns_once_changes_have_settled() {
+    let temp = tempdir().expect("temp dir");
+    let library = folder_library(
+        temp.path(),
+        r#"{"id":"demo","title":"Demo","description":"About Demo"}"#,
+        "x = 1\nprint 'run {x * 10}'",
+    );
+    let mut harness = explorer_with_library(library);
+    harness.get_by_label("Hot reload").click();
+    harness.run_steps(2);
+
+    // Two saves in quick succession: only the last one should run.
+    for version in [2, 3] {
+        fs::write(
+            temp.path().join("demo/script.koto"),
+            format!("x = {version}\nprint 'run {{x * 10}}'"),
+        )
+        .unwrap();
+        library.refresh().unwrap();
+        harness.run_steps(2);
+    }
+    assert!(harness.query_by_label_contains("run 30").is_none());
+
+    wait_for_label(&mut harness, "run 30", Duration::from_secs(10));
+    assert!(harness.query_by_label_contains("run 20").is_none());
+    assert_eq!(harness.query_all_by_label_contains("run 30").count(), 1);
+}
+
+/// Whether a single-line text field currently holds `value`.
+fn has_text_field(harness: &support::Explorer, value: &str) -> bool {
+    harness
+        .query_by(|node| node.role() == Role::TextInput && node.value().as_deref() == Some(value))
+        .is_some()
+}
+
+#[test]
+fn metadata_edits_update_inputs_without_losing_typed_values() {
+    let temp = tempdir().expect("temp dir");
+    let library = folder_library(
+        temp.path(),
+        r#"{"id":"demo","title":"Demo","description":"About Demo","inputs":[
+            {"name":"count","type":"number","default":"3"},
+            {"name":"name","type":"string","default":"Ada"}]}"#,
+        "input.count",
+    );
+    let mut harness = explorer_with_library(library);
+    harness
+        .get_by(|node| node.role() == Role::TextInput && node.value().as_deref() == Some("3"))
+        .type_text("0");
+    harness.run_steps(2);
+    assert!(has_text_field(&harness, "30"));
+
+    fs::write(
+        temp.path().join("demo/meta.json"),
+        r#"{"id":"demo","title":"Demo","description":"About Demo","inputs":[
+            {"name":"count","type":"number","default":"5"},
+            {"name":"name","type":"string","default":"Grace"},
+            {"name":"greeting","type":"string","default":"hi"}]}"#,
+    )
+    .unwrap();
+    library.refresh().unwrap();
+    harness.run_steps(2);
+
+    // The typed value stays, the untouched one follows its new default, and the new input
+    // appears, all without re-selecting the example.
+    assert!(has_text_field(&harness, "30"));
+    assert!(has_text_field(&harness, "Grace"));
+    assert!(has_text_field(&harness, "hi"));
+    assert!(!has_text_field(&harness, "Ada"));
+    // Reported in the console and as a snackbar.
+    assert!(
+        harness
+            .query_all_by_label_contains("metadata updated")
+            .next()
+            .is_some()
+    );
+}
+
+#[test]
+fn typed_input_values_survive_a_catalog_refresh() {
+    let temp = tempdir().expect("temp dir");
+    let library = folder_library(
+        temp.path(),
+        r#"{"id":"demo","title":"Demo","description":"About Demo","inputs":[
+            {"name":"count","type":"number","default":"3"}]}"#,
+        "input.count",
+    );
+    let mut harness = explorer_with_library(library);
+    harness
+        .get_by(|node| node.role() == Role::TextInput && node.value().as_deref() == Some("3"))
+        .type_text("0");
+    harness.run_steps(2);
+
+    // A script-only change leaves the metadata alone, so nothing should reset the field.
+    fs::write(temp.path().join("demo/script.koto"), "input.count + 1").unwrap();
+    library.refresh().unwrap();
+    harness.run_steps(2);
+    assert!(has_text_field(&harness, "30"));
+    assert!(!has_text_field(&harness, "3"));
+
+    harness.get_by_label("Run example").click();
+    wait_for_label(&mut harness, "Return value: 31", Duration::from_secs(10));
+}
+
+#[test]
+fn output_streams_into_the_console_while_a_script_runs() {
+    let mut harness = explorer(&[TestExample {
+        id: "slow",
+        title: "Slow",
+        categories: &["basics"],
+        script: "print 'first chunk'\nhost.sleep_ms 1000\nprint 'second chunk'",
+    }]);
+    harness.get_by_label("Run example").click();
+
+    // The first line shows up while the script is still sleeping.
+    wait_for_label(&mut harness, "first chunk", Duration::from_secs(10));
+    assert!(harness.query_by_label("Streaming output…").is_some());
+    assert!(harness.query_by_label_contains("second chunk").is_none());
+
+    // Later output continues the same entry rather than starting a new one.
+    wait_for_label(&mut harness, "second chunk", Duration::from_secs(10));
+    let entry = harness
+        .get_by_label_contains("second chunk")
+        .label()
+        .expect("console entry");
+    assert!(entry.contains("first chunk"));
+    harness.run_steps(2);
+    assert!(harness.query_by_label("Streaming output…").is_none());
+}
+
+#[test]
+fn the_about_window_shows_versions_and_copies_diagnostics() {
+    let mut harness = explorer(EXAMPLES);
+    harness.get_by_label("Help").click();
+    harness.run_steps(2);
+    harness.get_by_label("About").click();
+    harness.run_steps(2);
+    assert!(harness.query_by_label(env!("CARGO_PKG_VERSION")).is_some());
+
+    harness.get_by_label("Copy diagnostic info").click();
+    harness.step();
+    let copied = harness
+        .output()
+        .platform_output
+        .commands
+        .iter()
+        .find_map(|command| match command {
+            egui::OutputCommand::CopyText(text) => Some(text.clone()),
+            _ => None,
+        })
+        .expect("diagnostic info on the clipboard");
+    assert!(copied.starts_with(&format!("koto_learning {}", env!("CARGO_PKG_VERSION"))));
+    assert!(copied.contains("examples: 2 in "));
+}
+
+#[test]
+fn run_durations_are_shown_in_a_fitting_unit() {
+    let mut harness = explorer(&[TestExample {
+        id: "sleepy",
+        title: "Sleepy",
+        categories: &["basics"],
+        script: "host.sleep_ms 1100\n1",
+    }]);
+    harness.get_by_label("Run example").click();
+    wait_for_label(
+        &mut harness,
+        "Last execution succeeded",
+        Duration::from_secs(10),
+    );
+
+    let label = harness
+        .get_by_label_contains("Duration: ")
+        .label()
+        .expect("duration label");
+    let total = label
+        .trim_start_matches("Duration: ")
+        .split(" (")
+        .next()
+        .unwrap_or_default();
+    // A second or more reads in seconds, while compiling a two-line script takes far less
+    // and reads in µs or ms.
+    assert!(total.ends_with(" s") && total.starts_with("1."), "{label}");
+    let compile = label
+        .split("compile ")
+        .nth(1)
+        .and_then(|rest| rest.split([',', ')']).next())
+        .expect("compile time");
+    assert!(
+        compile.ends_with(" µs") || compile.ends_with(" ms"),
+        "{label}"
+    );
+}
+
+#[test]
+fn the_example_assertion_is_checked_after_each_run() {
+    let metadata = ExampleMetadata {
+        id: "answer".to_string(),
+        title: "Answer".to_string(),
+        description: "About Answer".to_string(),
+        categories: vec!["basics".to_string()],
+        assertion: Some("result == 42".to_string()),
+        ..Default::default()
+    };
+    let mut harness = explorer_with_metadata(vec![(metadata, "40 + 2".to_string())]);
+    assert!(harness.query_by_label("Run to check").is_some());
+
+    harness.get_by_label("Run example").click();
+    wait_for_label(&mut harness, "✔ Passed", Duration::from_secs(10));
+
+    // Editing the expression makes the badge stale until the next run checks it.
+    harness
+        .get_by(|node| {
+            node.role() == Role::TextInput && node.value().as_deref() == Some("result == 42")
+        })
+        .type_text("1");
+    harness.run_steps(2);
+    assert!(harness.query_by_label("✔ Passed").is_none());
+    assert!(harness.query_by_label("Run to check").is_some());
+
+    harness.get_by_label("Run example").click();
+    wait_for_label(&mut harness, "✖ Failed", Duration::from_secs(10));
+    assert!(harness.query_by_label("evaluated to false").is_some());
+
+    harness.get_by_label("Reset").click();
+    harness.run_steps(2);
+    assert!(has_text_field(&harness, "result == 42"));
+}
@@ -1,12 +1,57 @@
-use std::{fs, path::PathBuf, time::Duration};
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex, mpsc},
+    time::Duration,
+};
 
-use koto::prelude::runtime_error;
+use koto::prelude::{KMap, KValue, runtime_error};
 use koto_learning::{
-    examples::{ExampleLibrary, ScriptChangeKind, tests as example_tests},
-    runtime::Runtime,
+    examples::{
+        self, ExampleInput, ExampleLibrary, ExampleMetadata, InputType, SOLUTION_FILE_NAME,
+        ScriptChangeKind, categories,
+        glossary::{self, Glossary},
+        importer::{ImportSource, import_koto_scripts},
+        notebook::{Notebook, NotebookCell},
+        paste::{PasteSource, PasteTarget, SCRATCH_EXAMPLE_ID, raw_url},
+        recent::RecentCatalogs,
+        samples,
+        test_history::{self, TestHistory},
+        tests as example_tests,
+    },
+    language::{
+        self, lsp,
+        symbols::{SymbolIndex, SymbolKind},
+    },
+    runtime::{
+        RunBinding, RunOptions, Runtime, RuntimeProfile, TimeoutError, audit,
+        automation::{self, AutomationCommand, AutomationRequest},
+        coverage, features,
+        generators::GeneratorEventKind,
+        gfx::Shape,
+        input::InputEvent,
+        memory::TrackingAllocator,
+        number_format::NumberFormat,
+        observer::{ChannelObserver, ExecutionCallbacks, ExecutionEvent, OutputStream},
+        precompile::{DiskCache, PrecompiledScript},
+        prelude::PreludeSource,
+        profile_compare::RunSetup,
+        repeat::{DurationStats, RunSeries},
+        replay::OutputRecorder,
+        value_diff::{self, DiffKind},
+        version_matrix::{self, BuildRun, MatrixRow, VersionMatrix},
+    },
+    tasks,
 };
+use once_cell::sync::Lazy;
+use proptest::prelude::*;
+use serde_json::Value as JsonValue;
 use tempfile::tempdir;
 
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+
 #[test]
 fn example_library_loads_and_refreshes() {
     let temp = tempdir().expect("temp dir");
@@ -34,6 +79,242 @@ fn example_library_loads_and_refreshes() {
     assert!(refreshed.script.contains("1 + 1"));
 }
 
+#[test]
+fn example_thumbnails_are_found_by_name_or_default() {
+    let temp = tempdir().expect("temp dir");
+    for (folder, meta) in [
+        ("plain", r#"{"title":"Plain","description":"No picture"}"#),
+        (
+            "default",
+            r#"{"title":"Default","description":"thumbnail.png"}"#,
+        ),
+        (
+            "named",
+            r#"{"title":"Named","description":"Custom","thumbnail":"cover.jpg"}"#,
+        ),
+        (
+            "missing",
+            r#"{"title":"Missing","description":"Gone","thumbnail":"gone.png"}"#,
+        ),
+    ] {
+        let dir = temp.path().join(folder);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("meta.json"), meta).unwrap();
+        fs::write(dir.join("script.koto"), "1").unwrap();
+    }
+    fs::write(temp.path().join("default/thumbnail.png"), b"png").unwrap();
+    fs::write(temp.path().join("named/cover.jpg"), b"jpg").unwrap();
+
+    let library = ExampleLibrary::new_unwatched(temp.path().to_path_buf()).expect("library");
+    let thumbnail = |id: &str| library.get(id).expect(id).thumbnail;
+    assert_eq!(thumbnail("plain"), None);
+    assert_eq!(
+        thumbnail("default"),
+        Some(temp.path().join("default/thumbnail.png"))
+    );
+    assert_eq!(
+        thumbnail("named"),
+        Some(temp.path().join("named/cover.jpg"))
+    );
+    assert_eq!(thumbnail("missing"), None);
+
+    // Captures go to the named file only when it is a PNG.
+    let named = library.get("named").unwrap();
+    assert_eq!(
+        named.thumbnail_capture_path(),
+        temp.path().join("named/thumbnail.png")
+    );
+    let missing = library.get("missing").unwrap();
+    assert_eq!(
+        missing.thumbnail_capture_path(),
+        temp.path().join("missing/gone.png")
+    );
+}
+
+#[test]
+fn starter_samples_load_and_run() {
+    let temp = tempdir().expect("temp dir");
+    let written = samples::write_samples(temp.path()).expect("samples");
+    assert_eq!(written, samples::sample_ids().collect::<Vec<_>>());
+    assert!(samples::write_samples(temp.path()).unwrap().is_empty());
+
+    let library = ExampleLibrary::new_unwatched(temp.path().to_path_buf()).expect("library");
+    assert_eq!(library.snapshot().len(), written.len());
+    assert_eq!(library.get("tests_demo").unwrap().test_suites.len(), 1);
+
+    let runtime = Runtime::new().expect("runtime");
+    for example in library.snapshot() {
+        runtime
+            .execute_script(&example.script)
+            .unwrap_or_else(|error| panic!("{} failed: {error}", example.metadata.id));
+    }
+}
+
+#[test]
+fn koto_checkout_scripts_are_imported_once() {
+    let checkout = tempdir().expect("checkout");
+    let root = checkout.path();
+    fs::create_dir_all(root.join("examples")).unwrap();
+    fs::create_dir_all(root.join("koto/tests/nested")).unwrap();
+    fs::write(
+        root.join("examples/poetry.koto"),
+        "# Generates random poems.\n# Uses iterators.\nprint 'roses'",
+    )
+    .unwrap();
+    fs::write(root.join("koto/tests/nested/string_ops.koto"), "1 + 1").unwrap();
+    fs::write(root.join("koto/tests/README.md"), "not a script").unwrap();
+
+    let catalog = tempdir().expect("catalog");
+    let source = ImportSource::parse(&root.display().to_string());
+    assert_eq!(source, ImportSource::Checkout(root.to_path_buf()));
+    let report = import_koto_scripts(&source, catalog.path()).expect("import");
+    assert_eq!(
+        report.imported,
+        ["koto_examples_poetry", "koto_koto_tests_nested_string_ops"]
+    );
+
+    let library = ExampleLibrary::new_unwatched(catalog.path().to_path_buf()).expect("library");
+    let poetry = library.get("koto_examples_poetry").expect("imported");
+    assert_eq!(poetry.metadata.title, "Poetry");
+    assert_eq!(
+        poetry.metadata.description,
+        "Generates random poems. Uses iterators."
+    );
+    assert_eq!(poetry.metadata.categories, ["Imported", "Koto examples"]);
+    let test = library.get("koto_koto_tests_nested_string_ops").unwrap();
+    assert_eq!(test.metadata.title, "String ops");
+    assert_eq!(test.metadata.categories, ["Imported", "Koto tests"]);
+
+    let again = import_koto_scripts(&source, catalog.path()).expect("second import");
+    assert!(again.imported.is_empty());
+    assert_eq!(again.skipped.len(), 2);
+
+    let empty = tempdir().unwrap();
+    assert!(
+        import_koto_scripts(&ImportSource::Checkout(empty.path().into()), catalog.path()).is_err()
+    );
+    assert_eq!(
+        ImportSource::parse("https://github.com/koto-lang/koto"),
+        ImportSource::Repository("https://github.com/koto-lang/koto".into())
+    );
+}
+
+#[test]
+fn pasted_code_becomes_a_scratch_or_library_example() {
+    let catalog = tempdir().expect("catalog");
+    let library = ExampleLibrary::new_unwatched(catalog.path().to_path_buf()).expect("library");
+
+    let source = PasteSource::parse("print 'first'");
+    let pasted = library
+        .import_pasted(&source, &PasteTarget::Scratch)
+        .expect("scratch");
+    assert_eq!(pasted.id, SCRATCH_EXAMPLE_ID);
+    assert!(pasted.syntax_error.is_none());
+
+    // A second scratch import replaces the first, even when it doesn't compile.
+    let pasted = library
+        .import_pasted(&PasteSource::parse("x = (1 +"), &PasteTarget::Scratch)
+        .expect("broken scratch");
+    assert_eq!(pasted.id, SCRATCH_EXAMPLE_ID);
+    assert!(pasted.syntax_error.is_some());
+    let scratch = library.get(SCRATCH_EXAMPLE_ID).expect("scratch example");
+    assert_eq!(scratch.script, "x = (1 +\n");
+    assert_eq!(scratch.metadata.categories, ["Scratch"]);
+
+    let target = PasteTarget::Library {
+        title: "Pasted Demo".into(),
+    };
+    let first = library.import_pasted(&source, &target).expect("library");
+    let second = library.import_pasted(&source, &target).expect("library");
+    assert_eq!(
+        (first.id.as_str(), second.id.as_str()),
+        ("pasted_demo", "pasted_demo_2")
+    );
+    assert_eq!(
+        library.get("pasted_demo").unwrap().metadata.categories,
+        ["Imported"]
+    );
+    assert!(
+        library
+            .import_pasted(&PasteSource::parse("  \n"), &PasteTarget::Scratch)
+            .is_err()
+    );
+
+    assert_eq!(
+        PasteSource::parse(" https://example.com/demo.koto \n"),
+        PasteSource::Url("https://example.com/demo.koto".into())
+    );
+    assert!(matches!(
+        PasteSource::parse("# see https://example.com\nprint 1"),
+        PasteSource::Code(_)
+    ));
+    assert_eq!(
+        raw_url("https://gist.github.com/ada/abc123"),
+        "https://gist.githubusercontent.com/ada/abc123/raw"
+    );
+    assert_eq!(
+        raw_url("https://github.com/koto-lang/koto/blob/main/examples/poetry.koto"),
+        "https://raw.githubusercontent.com/koto-lang/koto/main/examples/poetry.koto"
+    );
+    assert_eq!(
+        raw_url("https://example.com/demo.koto"),
+        "https://example.com/demo.koto"
+    );
+    assert!(Runtime::check_syntax("print 'ok'").is_ok());
+}
+
+#[test]
+#[cfg(feature = "archive")]
+fn student_packs_leave_out_solutions() {
+    let catalog = tempdir().expect("catalog");
+    let root = catalog.path();
+    for (id, solution) in [("loops", true), ("maps", false), ("scratch", false)] {
+        let dir = root.join(id);
+        fs::create_dir_all(dir.join("tests")).unwrap();
+        fs::write(
+            dir.join("meta.json"),
+            format!(r#"{{"id": "{id}", "title": "{id}", "description": "d"}}"#),
+        )
+        .unwrap();
+        fs::write(dir.join("script.koto"), "# TODO\n").unwrap();
+        fs::write(dir.join("tests/check.koto"), "1").unwrap();
+        if solution {
+            fs::write(dir.join(SOLUTION_FILE_NAME), "print 'done'").unwrap();
+        }
+    }
+    fs::write(root.join("loops/.notes"), "private").unwrap();
+
+    let library = ExampleLibrary::new_unwatched(root.to_path_buf()).expect("library");
+    assert!(library.get("loops").unwrap().solution.is_some());
+    assert!(library.get("maps").unwrap().solution.is_none());
+
+    let output = tempdir().expect("output");
+    let pack = output.path().join("packs/student.zip");
+    let report = library.export_student_pack(&pack).expect("pack");
+    assert_eq!((report.examples, report.solutions_removed), (2, 1));
+
+    let archive = zip::ZipArchive::new(fs::File::open(&pack).unwrap()).expect("zip");
+    let mut names: Vec<&str> = archive.file_names().collect();
+    names.sort();
+    assert_eq!(
+        names,
+        [
+            "examples/loops/meta.json",
+            "examples/loops/script.koto",
+            "examples/loops/tests/check.koto",
+            "examples/maps/meta.json",
+            "examples/maps/script.koto",
+            "examples/maps/tests/check.koto",
+        ]
+    );
+
+    let empty = tempdir().unwrap();
+    assert!(
+        examples::classroom::export_student_pack(empty.path(), &output.path().join("empty.zip"))
+            .is_err()
+    );
+}
+
 #[test]
 fn runtime_executes_and_captures_output() {
     let runtime = Runtime::new().expect("runtime");
@@ -52,6 +333,39 @@ fn runtime_reports_script_errors() {
     assert!(error.to_string().contains("unknown_function"));
 }
 
+#[test]
+fn watch_expressions_see_exports_and_the_return_value() {
+    let runtime = Runtime::new().expect("runtime");
+    let output = runtime
+        .execute_script("export values = [1, 2, 3]\nvalues.size() * 10")
+        .expect("script execution");
+    let expressions = [
+        "export values = []",
+        "values.size()",
+        "result + 1",
+        "print 'ignored'",
+        "missing_name",
+    ]
+    .map(String::from);
+    let results = runtime
+        .evaluate_watches(&expressions, output.value, None)
+        .expect("watches");
+    // Each expression starts from the run's exports, so the first doesn't affect the second.
+    assert_eq!(results[1], Ok("3".to_string()));
+    assert_eq!(results[2], Ok("31".to_string()));
+    assert!(
+        results[4]
+            .as_ref()
+            .is_err_and(|error| error.contains("missing_name"))
+    );
+
+    let after = runtime
+        .evaluate_watches(&["values.size()".to_string()], None, None)
+        .expect("watches");
+    assert_eq!(after, vec![Ok("3".to_string())]);
+    assert!(runtime.take_stdout().is_empty());
+}
+
 #[test]
 fn runtime_supports_host_functions() {
     let runtime = Runtime::new().expect("runtime");
@@ -80,6 +394,50 @@ fn runtime_provides_serialization_helpers() {
     assert!(value.contains("greeting"));
 }
 
+#[test]
+fn optional_features_gate_examples_and_helpers() {
+    let required = vec!["yaml".to_string(), "teleport".to_string()];
+    let missing = features::missing(&required);
+    assert!(missing.contains(&"teleport"));
+    assert_eq!(missing.contains(&"yaml"), !cfg!(feature = "yaml"));
+
+    let runtime = Runtime::new().expect("runtime");
+    let result = runtime.execute_script("serde.to_yaml { answer: 42 }");
+    match result {
+        Ok(output) => {
+            assert!(features::is_enabled("yaml"));
+            assert!(
+                output
+                    .return_value
+                    .unwrap_or_default()
+                    .contains("answer: 42")
+            );
+        }
+        Err(error) => {
+            assert!(!features::is_enabled("yaml"));
+            assert!(format!("{error:#}").contains("`yaml` feature"));
+        }
+    }
+}
+
+#[test]
+fn missing_features_explain_which_one_to_enable() {
+    let runtime = Runtime::new().expect("runtime");
+    for (feature, script) in [
+        ("serialization", "serde.to_csv [{a: 1}]"),
+        ("serialization", "serde.from_ron '(a: 1)'"),
+        ("encoding", "host.encoding.sha256 'abc'"),
+    ] {
+        match runtime.execute_script(script) {
+            Ok(_) => assert!(features::is_enabled(feature), "{script}"),
+            Err(error) => {
+                assert!(!features::is_enabled(feature), "{script}: {error:#}");
+                assert!(format!("{error:#}").contains(&format!("`{feature}` feature")));
+            }
+        }
+    }
+}
+
 #[test]
 fn runtime_honors_execution_timeout_updates() {
     let runtime = Runtime::new().expect("runtime");
@@ -117,6 +475,10 @@ export tests =
         description: Some("Exercises pass/fail status and captured output.".to_string()),
         path: PathBuf::from("sample.koto"),
         script: script.to_string(),
+        timeout: None,
+        tags: Vec::new(),
+        fixtures: Vec::new(),
+        example: None,
     };
 
     let result = example_tests::run_suite(&suite).expect("suite run");
@@ -143,92 +505,3013 @@ export tests =
 }
 
 #[test]
-fn example_library_tracks_script_and_test_changes() {
-    let temp = tempdir().expect("temp dir");
-    let base = temp.path();
-    let example_dir = base.join("demo");
-    let tests_dir = example_dir.join("tests");
-    fs::create_dir_all(&tests_dir).unwrap();
+fn case_hooks_share_the_case_deadline() {
+    let dir = tempdir().expect("example dir");
+    fs::create_dir_all(dir.path().join("tests")).unwrap();
+    fs::write(
+        dir.path().join("tests/hooks.koto"),
+        "\
+# Title: Slow hooks
+# Timeout: 300ms
 
-    let initial_script = "print(\"hi\")\n1";
+export tests =
+  @pre_test: || host.sleep_ms 200
+  @test sleeps_past_the_deadline: || host.sleep_ms 200
+  @test fails_slowly: ||
+    host.sleep_ms 50
+    throw 'a real failure'
+",
+    )
+    .unwrap();
+    let suites = example_tests::load_suites(dir.path()).expect("suites");
+    let result = example_tests::SuiteRunner::new()
+        .run(&suites[0])
+        .expect("suite run");
+
+    let sleeps = &result.cases[0];
+    assert_eq!(sleeps.status, example_tests::TestStatus::Failed);
+    assert!(
+        sleeps
+            .error
+            .as_deref()
+            .is_some_and(|error| error.starts_with("Timed out")),
+        "{:?}",
+        sleeps.error
+    );
+    assert!(sleeps.duration < Duration::from_millis(390));
+
+    // Failing after most of the limit is still the case's own failure.
+    let fails = &result.cases[1];
+    assert_eq!(fails.status, example_tests::TestStatus::Failed);
+    assert!(
+        fails
+            .error
+            .as_deref()
+            .is_some_and(|error| error.contains("a real failure")),
+        "{:?}",
+        fails.error
+    );
+}
+
+#[test]
+fn hanging_cases_time_out_without_stopping_the_suite() {
+    let dir = tempdir().expect("example dir");
+    fs::create_dir_all(dir.path().join("tests")).unwrap();
     fs::write(
-        example_dir.join("meta.json"),
-        r#"{"id":"demo","title":"Demo","description":"Test example"}"#,
+        dir.path().join("tests/slow.koto"),
+        "\
+# Title: Slow suite
+# Timeout: 200ms
+
+export tests =
+  @test hangs: ||
+    while true
+      x = 1
+  @test runs_afterwards: || assert_eq 1 + 1, 2
+",
     )
     .unwrap();
-    fs::write(example_dir.join("script.koto"), initial_script).unwrap();
-    let suite_path = tests_dir.join("sample.koto");
-    fs::write(&suite_path, "tests =\n  @test pass: || 1\nexport tests\n").unwrap();
+    let suites = example_tests::load_suites(dir.path()).expect("suites");
+    assert_eq!(suites[0].timeout, Some(Duration::from_millis(200)));
 
-    let library = ExampleLibrary::new_unwatched(base.to_path_buf()).expect("library");
-    // Drain initial load notifications.
-    let _ = library.take_recent_changes();
+    // The header wins over the runner's own limit.
+    let runner = example_tests::SuiteRunner::new().timeout(Duration::from_secs(60));
+    let result = runner.run(&suites[0]).expect("suite run");
+    assert!(result.total_duration < Duration::from_secs(10));
+    let hangs = &result.cases[0];
+    assert_eq!(hangs.status, example_tests::TestStatus::Failed);
+    assert!(
+        hangs
+            .error
+            .as_deref()
+            .is_some_and(|error| error.starts_with("Timed out")),
+        "{:?}",
+        hangs.error
+    );
+    assert_eq!(result.cases[1].status, example_tests::TestStatus::Passed);
+}
 
-    // Modify the script file.
-    fs::write(example_dir.join("script.koto"), "print(\"hi\")\n2").unwrap();
-    library.refresh().unwrap();
-    let changes = library.take_recent_changes();
-    let script_change = changes
-        .iter()
-        .find(|change| matches!(change.kind, ScriptChangeKind::ScriptUpdated { .. }))
-        .cloned()
-        .expect("script change");
-    match &script_change.kind {
-        ScriptChangeKind::ScriptUpdated { previous, current } => {
-            assert!(
-                previous
-                    .as_ref()
-                    .map(|text| text.contains(initial_script))
-                    .unwrap_or(false)
-            );
-            assert!(
-                current
-                    .as_ref()
-                    .map(|text| text.contains("2"))
-                    .unwrap_or(false)
-            );
-        }
-        _ => unreachable!(),
-    }
+#[test]
+fn cases_that_finish_are_never_reported_as_timed_out() {
+    let dir = tempdir().expect("example dir");
+    fs::create_dir_all(dir.path().join("tests")).unwrap();
+    fs::write(
+        dir.path().join("tests/busy.koto"),
+        "\
+# Title: Busy suite
 
-    library.revert_change(&script_change).unwrap();
-    let reverted_script = fs::read_to_string(example_dir.join("script.koto")).unwrap();
-    assert!(reverted_script.contains(initial_script));
+export tests =
+  @pre_test: || host.sleep_ms 150
+  @test passes_slowly: ||
+    host.sleep_ms 150
+    assert_eq 1 + 1, 2
+",
+    )
+    .unwrap();
+    let suites = example_tests::load_suites(dir.path()).expect("suites");
+    assert_eq!(suites[0].timeout, None);
 
-    // Update the test suite file.
+    // Without a header or a runner limit, cases run for as long as they need.
+    let result = example_tests::SuiteRunner::new()
+        .run(&suites[0])
+        .expect("suite run");
+    assert_eq!(result.cases[0].status, example_tests::TestStatus::Passed);
+    assert_eq!(result.cases[0].error, None);
+    assert!(result.cases[0].duration >= Duration::from_millis(300));
+}
+
+#[test]
+fn tag_filters_pick_suites_by_their_tags_header() {
+    let dir = tempdir().expect("example dir");
+    fs::create_dir_all(dir.path().join("tests")).unwrap();
+    let suite = |title: &str, tags: &str| {
+        format!("# Title: {title}\n{tags}\nexport tests =\n  @test ok: || assert true\n")
+    };
     fs::write(
-        &suite_path,
-        "tests =\n  @test pass: || 1\n  @test another: || throw 'nope'\nexport tests\n",
+        dir.path().join("tests/a.koto"),
+        suite("A", "# Tags: Slow, io"),
     )
     .unwrap();
-    library.refresh().unwrap();
-    let changes = library.take_recent_changes();
-    let suite_change = changes
-        .into_iter()
-        .find(|change| matches!(change.kind, ScriptChangeKind::TestSuiteUpdated { .. }))
-        .expect("suite change");
-    match &suite_change.kind {
-        ScriptChangeKind::TestSuiteUpdated {
-            previous, current, ..
-        } => {
-            assert!(
-                previous
-                    .as_ref()
-                    .map(|text| text.contains("pass"))
-                    .unwrap_or(false)
-            );
-            assert!(
-                current
-                    .as_ref()
-                    .map(|text| text.contains("another"))
-                    .unwrap_or(false)
-            );
-        }
-        _ => unreachable!(),
+    fs::write(dir.path().join("tests/b.koto"), suite("B", "# Tags: io")).unwrap();
+    fs::write(dir.path().join("tests/c.koto"), suite("C", "")).unwrap();
+    let suites = example_tests::load_suites(dir.path()).expect("suites");
+    assert_eq!(suites[0].tags, ["slow", "io"]);
+    assert!(suites[2].tags.is_empty());
+
+    let filter = example_tests::TagFilter::parse("IO, !slow");
+    assert_eq!(filter.include, ["io"]);
+    assert_eq!(filter.exclude, ["slow"]);
+    let names = |results: Vec<example_tests::TestSuiteResult>| -> Vec<String> {
+        results
+            .into_iter()
+            .map(|result| result.suite_name)
+            .collect()
+    };
+    let results = example_tests::run_suites_tagged(&suites, &filter).expect("tagged run");
+    assert_eq!(names(results), ["B"]);
+
+    let fast = example_tests::SuiteRunner::new().tags(example_tests::TagFilter::parse("-slow"));
+    assert_eq!(names(fast.run_all(&suites).expect("fast run")), ["B", "C"]);
+
+    assert!(example_tests::TagFilter::parse(" , ").is_empty());
+    let everything = example_tests::run_suites_tagged(&suites, &Default::default());
+    assert_eq!(everything.expect("all suites").len(), 3);
+}
+
+#[test]
+fn snapshot_assertions_write_pending_files_until_accepted() {
+    let dir = tempdir().expect("example dir");
+    fs::create_dir_all(dir.path().join("tests")).unwrap();
+    let script_path = dir.path().join("tests/report.koto");
+    let write_suite = |total: i64| {
+        let script = format!(
+            "export tests =\n  @test report: ||\n    assert_snapshot 'totals', {{total: {total}, items: [1, 2]}}\n    assert_snapshot 'greeting', 'hi'\n"
+        );
+        fs::write(&script_path, script).unwrap();
+        example_tests::load_suites(dir.path())
+            .expect("suites")
+            .remove(0)
+    };
+
+    let suite = write_suite(3);
+    let result = example_tests::run_suite(&suite).expect("first run");
+    assert!(!result.passed);
+    let error = result.cases[0].error.as_deref().unwrap_or_default();
+    assert!(error.contains("New snapshot 'totals'"), "{error}");
+    assert_eq!(result.pending_snapshots.len(), 1);
+
+    // Each run stops at the first failing assertion, so accept until the case passes.
+    assert_eq!(example_tests::accept_snapshots(&suite).expect("accept"), 1);
+    let result = example_tests::run_suite(&suite).expect("second run");
+    assert_eq!(result.pending_snapshots.len(), 1);
+    example_tests::accept_snapshots(&suite).expect("accept greeting");
+    let result = example_tests::run_suite(&suite).expect("third run");
+    assert!(result.passed, "{:?}", result.cases[0].error);
+    assert!(result.pending_snapshots.is_empty());
+
+    let snapshots = example_tests::snapshot_dir(&suite);
+    assert_eq!(
+        fs::read_to_string(snapshots.join("greeting.snap")).unwrap(),
+        "hi\n"
+    );
+    let totals: JsonValue =
+        serde_json::from_str(&fs::read_to_string(snapshots.join("totals.snap")).unwrap())
+            .expect("json snapshot");
+    assert_eq!(totals, serde_json::json!({"total": 3, "items": [1, 2]}));
+
+    let suite = write_suite(4);
+    let result = example_tests::run_suite(&suite).expect("changed run");
+    let error = result.cases[0].error.as_deref().unwrap_or_default();
+    assert!(
+        error.contains("was `  \"total\": 3`, now `  \"total\": 4`"),
+        "{error}"
+    );
+    assert!(snapshots.join("totals.snap.new").exists());
+
+    let updated = example_tests::SuiteRunner::new()
+        .update_snapshots(true)
+        .run(&suite)
+        .expect("update run");
+    assert!(updated.passed);
+    assert!(updated.pending_snapshots.is_empty());
+}
+
+#[test]
+fn snapshot_assertions_work_with_suite_timeouts() {
+    let dir = tempdir().expect("example dir");
+    fs::create_dir_all(dir.path().join("tests")).unwrap();
+    fs::write(
+        dir.path().join("tests/timed.koto"),
+        "# Timeout: 3s\nexport tests =\n  @test report: || assert_snapshot 'answer', 42\n",
+    )
+    .unwrap();
+    let suite = example_tests::load_suites(dir.path())
+        .expect("suites")
+        .remove(0);
+    assert_eq!(suite.timeout, Some(Duration::from_secs(3)));
+
+    // A fresh runtime has no execution limit, so the suite's limit has to be applied before
+    // `assert_snapshot` is installed.
+    let result = example_tests::SuiteRunner::new()
+        .update_snapshots(true)
+        .run(&suite)
+        .expect("suite run");
+    assert!(result.passed, "{:?}", result.cases[0].error);
+    let snapshot = example_tests::snapshot_dir(&suite).join("answer.snap");
+    assert_eq!(fs::read_to_string(snapshot).unwrap(), "42\n");
+
+    let result = example_tests::run_suite(&suite).expect("checked run");
+    assert!(result.passed, "{:?}", result.cases[0].error);
+}
+
+#[test]
+fn coverage_records_which_example_lines_ran() {
+    let dir = tempdir().expect("example dir");
+    fs::create_dir_all(dir.path().join("tests")).unwrap();
+    let script = [
+        "export helper = |x|",
+        "  if x > 0",
+        "    'positive'",
+        "  else",
+        "    'other'",
+        "",
+        "export config =",
+        "  label: 'counter'",
+        "  limit: 2",
+        "",
+        "values = [",
+        "  1,",
+        "  2,",
+        "]",
+        "text = 'first",
+        "# still the string",
+        "last'",
+    ]
+    .join("\n");
+    fs::write(dir.path().join("script.koto"), &script).unwrap();
+    fs::write(
+        dir.path().join("tests/branches.koto"),
+        "# Example: script.koto\nexport tests =\n  @test positive: ||\n    assert_eq example.helper(example.config.limit), 'positive'\n",
+    )
+    .unwrap();
+    let suite = example_tests::load_suites(dir.path())
+        .expect("suites")
+        .remove(0);
+    assert_eq!(suite.example.as_deref(), Some("script.koto"));
+
+    // Map entries, `else`, and lines inside open brackets or strings continue other
+    // statements, so they have no probes.
+    let instrumented = coverage::instrument(&script);
+    assert_eq!(instrumented.lines, vec![1, 2, 3, 5, 7, 11, 15]);
+
+    let result = example_tests::SuiteRunner::new()
+        .coverage(true)
+        .run(&suite)
+        .expect("suite run");
+    assert!(result.passed, "{:?}", result.cases[0].error);
+    let coverage = result.coverage.expect("coverage");
+    assert_eq!(coverage.covered(), 6);
+    assert_eq!(coverage.line_status(3), Some(true));
+    assert_eq!(coverage.line_status(5), Some(false));
+    assert_eq!(coverage.line_status(8), None);
+    assert_eq!(coverage.line_status(12), None);
+
+    let plain = example_tests::run_suite(&suite).expect("plain run");
+    assert!(plain.passed, "{:?}", plain.cases[0].error);
+    assert!(plain.coverage.is_none());
+
+    // The header can only name a file in the example folder.
+    for name in ["../script.koto", "/etc/passwd", "tests/branches.koto"] {
+        let mut escaping = suite.clone();
+        escaping.example = Some(name.to_string());
+        assert!(
+            example_tests::example_script_path(&escaping).is_err(),
+            "{name}"
+        );
+        let error = example_tests::run_suite(&escaping).expect_err("escaping example");
+        assert!(
+            format!("{error:#}").contains("Invalid example script"),
+            "{name}: {error:#}"
+        );
     }
+}
 
-    library.revert_change(&suite_change).unwrap();
-    let reverted_suite = fs::read_to_string(&suite_path).unwrap();
-    assert!(reverted_suite.contains("@test pass"));
-    assert!(!reverted_suite.contains("another"));
+#[test]
+fn return_value_diffs_align_unchanged_lines() {
+    let old = value_diff::value_text(
+        "{a: 1, b: [1, 2]}",
+        Some(&serde_json::json!({"a": 1, "b": [1, 2]})),
+    );
+    let new = value_diff::value_text(
+        "{a: 1, b: [1, 3], c: true}",
+        Some(&serde_json::json!({"a": 1, "b": [1, 3], "c": true})),
+    );
+    let diff = value_diff::diff_lines(&old, &new);
+    let changes: Vec<(DiffKind, &str)> = diff
+        .iter()
+        .filter(|line| line.kind != DiffKind::Same)
+        .map(|line| (line.kind, line.text.trim()))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            (DiffKind::Removed, "2"),
+            (DiffKind::Removed, "]"),
+            (DiffKind::Added, "3"),
+            (DiffKind::Added, "],"),
+            (DiffKind::Added, "\"c\": true"),
+        ]
+    );
+
+    // Plain values are compared as displayed.
+    assert_eq!(
+        value_diff::value_text("42", Some(&serde_json::json!(42))),
+        "42"
+    );
+    assert!(
+        value_diff::diff_lines("same", "same")
+            .iter()
+            .all(|line| line.kind == DiffKind::Same)
+    );
+}
+
+#[test]
+fn test_history_keeps_recent_runs_across_loads() {
+    let dir = tempdir().expect("config dir");
+    let path = dir.path().join("history.json");
+    let result = |passed: bool, millis: u64| example_tests::TestSuiteResult {
+        suite_id: "counter".to_string(),
+        suite_name: "Counter".to_string(),
+        description: None,
+        path: PathBuf::from("tests/counter.koto"),
+        setup_stdout: String::new(),
+        setup_stderr: String::new(),
+        cases: Vec::new(),
+        total_duration: Duration::from_millis(millis),
+        passed,
+        pending_snapshots: Vec::new(),
+        coverage: None,
+        stopped_early: false,
+    };
+
+    let mut history = TestHistory::load_from(path.clone());
+    for (passed, millis) in [(true, 10), (false, 12), (true, 11), (true, 30)] {
+        history
+            .record("testing::counter", &result(passed, millis))
+            .expect("record");
+    }
+    let history = TestHistory::load_from(path.clone());
+    let runs = history.runs("testing::counter");
+    assert_eq!(runs.len(), 4);
+    assert_eq!(runs[3].duration(), Duration::from_millis(30));
+    assert_eq!(history.streak("testing::counter"), Some((true, 2)));
+    assert!(history.is_flaky("testing::counter"));
+
+    let mut history = history;
+    for _ in 0..test_history::MAX_SUITE_RUNS {
+        history
+            .record("testing::counter", &result(true, 5))
+            .expect("record");
+    }
+    assert_eq!(
+        history.runs("testing::counter").len(),
+        test_history::MAX_SUITE_RUNS
+    );
+    assert!(!history.is_flaky("testing::counter"));
+
+    history
+        .rename_example("testing", "testing_basics")
+        .expect("rename");
+    let history = TestHistory::load_from(path);
+    assert!(history.runs("testing::counter").is_empty());
+    assert_eq!(
+        history.streak("testing_basics::counter"),
+        Some((true, test_history::MAX_SUITE_RUNS))
+    );
+}
+
+#[test]
+fn failed_cases_can_be_rerun_on_their_own() {
+    let dir = tempdir().expect("example dir");
+    fs::create_dir_all(dir.path().join("tests")).unwrap();
+    let write_suite = |expected: i64| {
+        let script = format!(
+            "export tests =\n  @test first: || assert_eq 1, 1\n  @test second: ||\n    print 'second ran'\n    assert_eq 2, {expected}\n  @test third: || assert true\n"
+        );
+        fs::write(dir.path().join("tests/rerun.koto"), script).unwrap();
+        example_tests::load_suites(dir.path())
+            .expect("suites")
+            .remove(0)
+    };
+
+    let suite = write_suite(3);
+    let first = example_tests::run_suite(&suite).expect("first run");
+    assert_eq!(first.failed_cases(), vec!["second".to_string()]);
+
+    let suite = write_suite(2);
+    let rerun = example_tests::SuiteRunner::new()
+        .cases(first.failed_cases())
+        .run(&suite)
+        .expect("rerun");
+    assert_eq!(rerun.cases.len(), 1);
+    assert_eq!(rerun.cases[0].name, "second");
+    assert!(rerun.cases[0].stdout.contains("second ran"));
+
+    let merged = first.merge_rerun(rerun);
+    assert!(merged.passed);
+    let names: Vec<&str> = merged.cases.iter().map(|case| case.name.as_str()).collect();
+    assert_eq!(names, ["first", "second", "third"]);
+}
+
+#[test]
+fn single_cases_can_be_selected_by_name() {
+    let suite = example_tests::ExampleTestSuite {
+        id: "select".to_string(),
+        name: "Select".to_string(),
+        description: None,
+        path: PathBuf::from("select.koto"),
+        script: [
+            "export tests =",
+            "  @test slow: ||",
+            "    host.sleep_ms 2000",
+            "  @test quick: || print 'quick ran'",
+        ]
+        .join("\n"),
+        timeout: None,
+        tags: Vec::new(),
+        fixtures: Vec::new(),
+        example: None,
+    };
+
+    let result = example_tests::run_suite_cases(&suite, &["quick".to_string()]).expect("case runs");
+    assert!(result.passed);
+    assert_eq!(result.cases.len(), 1);
+    assert_eq!(result.cases[0].name, "quick");
+    assert!(result.total_duration < Duration::from_secs(2));
+
+    let error =
+        example_tests::run_suite_cases(&suite, &["missing".to_string()]).expect_err("unknown case");
+    assert!(format!("{error:#}").contains("no @test named 'missing'"));
+}
+
+#[test]
+fn junit_reports_list_cases_and_failures() {
+    let dir = tempdir().expect("example dir");
+    fs::create_dir_all(dir.path().join("tests")).unwrap();
+    fs::write(
+        dir.path().join("tests/checks.koto"),
+        "# Title: Checks & balances\nexport tests =\n  @test passes: || print 'ok'\n  @test fails: || assert_eq 1, 2\n",
+    )
+    .unwrap();
+    let suites = example_tests::load_suites(dir.path()).expect("suites");
+    let results = example_tests::run_suites(&suites).expect("run");
+
+    let path = dir.path().join("reports/junit.xml");
+    example_tests::export_junit(&results, &path).expect("export");
+    let xml = fs::read_to_string(&path).expect("report");
+    assert!(xml.starts_with("<?xml"));
+    assert!(xml.contains(r#"<testsuites name="koto_learning" tests="2" failures="1""#));
+    assert!(xml.contains(r#"<testsuite name="Checks &amp; balances" tests="2" failures="1""#));
+    assert!(xml.contains(r#"<testcase name="passes" classname="checks""#));
+    assert!(xml.contains("<system-out>ok\n</system-out>"));
+    assert_eq!(xml.matches("<failure message=").count(), 1);
+}
+
+#[test]
+fn suites_run_in_parallel_and_keep_their_order() {
+    let dir = tempdir().expect("example dir");
+    fs::create_dir_all(dir.path().join("tests")).unwrap();
+    for index in 0..4 {
+        fs::write(
+            dir.path().join(format!("tests/suite_{index}.koto")),
+            format!(
+                "# Title: Suite {index}\nexport tests =\n  @test waits: ||\n    host.sleep_ms 500\n    assert_eq {index}, {index}\n"
+            ),
+        )
+        .unwrap();
+    }
+    let suites = example_tests::load_suites(dir.path()).expect("suites");
+
+    let start = std::time::Instant::now();
+    let results = example_tests::SuiteRunner::new()
+        .threads(4)
+        .run_all(&suites)
+        .expect("parallel run");
+    assert!(
+        start.elapsed() < Duration::from_millis(1500),
+        "{:?}",
+        start.elapsed()
+    );
+    let names: Vec<_> = results
+        .iter()
+        .map(|result| result.suite_name.as_str())
+        .collect();
+    assert_eq!(names, ["Suite 0", "Suite 1", "Suite 2", "Suite 3"]);
+    assert!(results.iter().all(|result| result.passed));
+
+    // Stopping after the first finished suite leaves the rest unstarted.
+    let outcomes = example_tests::SuiteRunner::new()
+        .threads(1)
+        .run_each(&suites, |_| false);
+    assert!(outcomes[0].is_some());
+    assert!(outcomes[1..].iter().all(Option::is_none));
+}
+
+#[test]
+fn suite_runner_applies_custom_runtime_settings() {
+    let script = r#"
+export tests =
+  @test uses_host_module: ||
+    if fixtures.answer != 42 then throw 'unexpected fixture value'
+  @test sleeps_on_virtual_clock: ||
+    host.sleep_ms 60000
+    if host.elapsed() < 60 then throw 'virtual clock did not advance'
+"#;
+    let suite = example_tests::ExampleTestSuite {
+        id: "custom".to_string(),
+        name: "Custom runtime".to_string(),
+        description: None,
+        path: PathBuf::from("custom.koto"),
+        script: script.to_string(),
+        timeout: None,
+        tags: Vec::new(),
+        fixtures: Vec::new(),
+        example: None,
+    };
+
+    let fixtures = KMap::new();
+    fixtures.insert("answer", KValue::Number(42.into()));
+    let runner = example_tests::SuiteRunner::new()
+        .timeout(Duration::from_secs(5))
+        .deterministic(true)
+        .host_module("fixtures", fixtures)
+        .shared_vm(true);
+
+    let results = runner.run_all(&[suite.clone(), suite]).expect("suite runs");
+    assert_eq!(results.len(), 2);
+    for result in &results {
+        assert!(result.passed, "{:?}", result.cases);
+        assert!(result.total_duration < Duration::from_secs(5));
+    }
+}
+
+#[test]
+fn shared_runtime_resets_state_between_suites() {
+    let suite = |id: &str, script: &str| example_tests::ExampleTestSuite {
+        id: id.to_string(),
+        name: id.to_string(),
+        description: None,
+        path: PathBuf::from(format!("{id}.koto")),
+        script: script.to_string(),
+        timeout: None,
+        tags: Vec::new(),
+        fixtures: Vec::new(),
+        example: None,
+    };
+    let leaky = suite(
+        "leaky",
+        r#"
+string.leaked = 1
+export tests =
+  @test sets_prelude_value: || assert_eq string.leaked, 1
+"#,
+    );
+    let clean = suite(
+        "clean",
+        r#"
+export tests =
+  @test starts_clean: ||
+    if (map.contains_key string, 'leaked') then throw 'prelude change leaked'
+  @test keeps_host_modules: || assert_eq fixtures.answer, 42
+"#,
+    );
+
+    let fixtures = KMap::new();
+    fixtures.insert("answer", KValue::Number(42.into()));
+    let runner = example_tests::SuiteRunner::new()
+        .timeout(Duration::from_secs(5))
+        .host_module("fixtures", fixtures)
+        .shared_runtime(true);
+    let results = runner.run_all(&[leaky, clean]).expect("suites run");
+    assert!(
+        results.iter().all(|result| result.passed),
+        "{:?}",
+        results
+            .iter()
+            .map(|result| &result.cases)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn shared_vms_keep_state_across_suite_timeouts() {
+    let suite = |id: &str, timeout_ms: u64, script: &str| example_tests::ExampleTestSuite {
+        id: id.to_string(),
+        name: id.to_string(),
+        description: None,
+        path: PathBuf::from(format!("{id}.koto")),
+        script: script.to_string(),
+        timeout: Some(Duration::from_millis(timeout_ms)),
+        tags: Vec::new(),
+        fixtures: Vec::new(),
+        example: None,
+    };
+    let setter = suite(
+        "setter",
+        2000,
+        "string.marker = 1\nexport tests =\n  @test sets: || assert_eq string.marker, 1\n",
+    );
+    let other = suite(
+        "other",
+        3000,
+        "export tests =\n  @test runs: || assert_eq 1 + 1, 2\n",
+    );
+    let reader = suite(
+        "reader",
+        2000,
+        "export tests =\n  @test sees_marker: || assert_eq string.marker, 1\n",
+    );
+
+    // A suite with another timeout runs in its own VM instead of rebuilding the shared one.
+    let results = example_tests::SuiteRunner::new()
+        .shared_vm(true)
+        .run_all(&[setter, other, reader])
+        .expect("suites run");
+    assert_eq!(results.len(), 3);
+    assert!(
+        results.iter().all(|result| result.passed),
+        "{:?}",
+        results
+            .iter()
+            .map(|result| &result.cases)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn suite_runs_stop_after_the_failure_limit() {
+    let suite = |id: &str| example_tests::ExampleTestSuite {
+        id: id.to_string(),
+        name: id.to_string(),
+        description: None,
+        path: PathBuf::from(format!("{id}.koto")),
+        script: r#"
+export tests =
+  @test first: || assert false
+  @test second: || assert false
+  @test third: || assert false
+"#
+        .to_string(),
+        timeout: None,
+        tags: Vec::new(),
+        fixtures: Vec::new(),
+        example: None,
+    };
+
+    let result = example_tests::SuiteRunner::new()
+        .max_failures(2)
+        .run(&suite("limited"))
+        .expect("suite runs");
+    let names: Vec<&str> = result.cases.iter().map(|case| case.name.as_str()).collect();
+    assert_eq!(names, ["first", "second"]);
+    assert!(result.stopped_early);
+
+    // Fail fast stops at the first failure and skips the suites after it.
+    let results = example_tests::SuiteRunner::new()
+        .threads(1)
+        .fail_fast(true)
+        .run_all(&[suite("one"), suite("two")])
+        .expect("suites run");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].cases.len(), 1);
+
+    let result = example_tests::run_suite(&suite("unlimited")).expect("suite runs");
+    assert_eq!(result.cases.len(), 3);
+    assert!(!result.stopped_early);
+}
+
+#[test]
+fn suites_read_declared_fixture_files() {
+    let example = tempdir().expect("example dir");
+    let tests_dir = example.path().join("tests");
+    fs::create_dir_all(tests_dir.join(example_tests::FIXTURES_DIR_NAME)).expect("fixtures dir");
+    fs::write(
+        tests_dir.join("fixtures").join("users.csv"),
+        "name,age\nada,36\n",
+    )
+    .expect("fixture");
+    fs::write(
+        tests_dir.join("data.koto"),
+        r#"# Title: Data-driven
+# Fixtures: users.csv
+# Timeout: 2s
+export tests =
+  @test reads_rows: ||
+    rows = fixtures.get('users.csv').trim().lines().to_list()
+    assert_eq rows.size(), 2
+    assert_eq rows[1], 'ada,36'
+"#,
+    )
+    .expect("suite");
+
+    let suites = example_tests::load_suites(example.path()).expect("suites load");
+    assert_eq!(suites[0].fixtures, ["users.csv"]);
+    // The suite's own timeout mustn't drop `fixtures` from the prelude, with or without a
+    // shared runtime.
+    let result = example_tests::run_suite(&suites[0]).expect("suite runs");
+    assert!(result.passed, "{:?}", result.cases);
+    let result = example_tests::SuiteRunner::new()
+        .shared_vm(true)
+        .run(&suites[0])
+        .expect("shared suite runs");
+    assert!(result.passed, "{:?}", result.cases);
+
+    // A fixture that doesn't exist stops the suite with the missing path.
+    let mut missing = suites[0].clone();
+    missing.fixtures.push("absent.json".to_string());
+    let error = example_tests::run_suite(&missing).expect_err("missing fixture");
+    assert!(format!("{error:#}").contains("absent.json"), "{error:#}");
+
+    // Names that would reach outside the fixtures folder are refused before anything is read.
+    fs::write(example.path().join("secrets.json"), "{}").expect("secret");
+    for name in ["../../secrets.json", "/etc/passwd", "nested/users.csv"] {
+        let mut escaping = suites[0].clone();
+        escaping.fixtures = vec![name.to_string()];
+        let error = example_tests::run_suite(&escaping).expect_err("escaping fixture");
+        assert!(
+            format!("{error:#}").contains("Invalid fixture name"),
+            "{name}: {error:#}"
+        );
+    }
+}
+
+#[test]
+fn runtime_observers_receive_lifecycle_events() {
+    let runtime = Runtime::new().expect("runtime");
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    let record = |prefix: &'static str, log: &Arc<Mutex<Vec<String>>>| {
+        let log = Arc::clone(log);
+        move |text: &str| log.lock().unwrap().push(format!("{prefix}:{text}"))
+    };
+    let start_log = Arc::clone(&log);
+    let complete_log = Arc::clone(&log);
+    let id = runtime.add_observer(
+        ExecutionCallbacks::new()
+            .on_start(move |_| start_log.lock().unwrap().push("start".to_string()))
+            .on_stdout_chunk(record("out", &log))
+            .on_complete(move |output| {
+                complete_log
+                    .lock()
+                    .unwrap()
+                    .push(format!("complete:{:?}", output.return_value))
+            })
+            .on_error(record("error", &log)),
+    );
+
+    runtime
+        .execute_script("print 'hello'\n42")
+        .expect("script runs");
+    assert!(runtime.execute_script("throw 'nope'").is_err());
+
+    let events = log.lock().unwrap().clone();
+    assert_eq!(events[0], "start");
+    assert_eq!(events[1], "out:hello\n");
+    assert_eq!(events[2], "complete:Some(\"42\")");
+    assert_eq!(events[3], "start");
+    assert!(events[4].starts_with("error:") && events[4].contains("nope"));
+
+    assert!(runtime.remove_observer(id));
+    let (sender, receiver) = mpsc::channel();
+    runtime.add_observer(ChannelObserver::new(sender));
+    runtime
+        .execute_script("print 'again'")
+        .expect("script runs");
+    assert_eq!(log.lock().unwrap().len(), events.len());
+    let forwarded: Vec<_> = receiver.try_iter().collect();
+    assert!(matches!(
+        forwarded.first(),
+        Some(ExecutionEvent::Started(_))
+    ));
+    assert!(matches!(
+        forwarded.last(),
+        Some(ExecutionEvent::Completed { .. })
+    ));
+}
+
+#[test]
+fn output_recordings_replay_at_the_original_pace() {
+    let runtime = Runtime::new().expect("runtime");
+    let recorder = OutputRecorder::new();
+    runtime.add_observer(recorder.clone());
+
+    let script = [
+        "print 'first'",
+        "host.sleep_ms 50",
+        "io.stderr().write_line 'warning'",
+        "print 'second'",
+    ]
+    .join("\n");
+    runtime.execute_script(&script).expect("script runs");
+
+    let recording = recorder.take();
+    let streams: Vec<_> = recording.chunks.iter().map(|chunk| chunk.stream).collect();
+    assert_eq!(
+        streams,
+        [
+            OutputStream::Stdout,
+            OutputStream::Stderr,
+            OutputStream::Stdout
+        ]
+    );
+    assert!(recording.chunks[1].at >= Duration::from_millis(50));
+    assert!(recording.duration() >= recording.chunks[1].at);
+
+    assert_eq!(recording.text_at(Duration::ZERO), "");
+    assert_eq!(recording.text_at(recording.chunks[0].at), "first\n");
+    assert_eq!(
+        recording.text_at(recording.duration()),
+        "first\nwarning\nsecond\n"
+    );
+    assert!(recorder.take().is_empty());
+}
+
+#[test]
+fn version_matrix_reports_builds_that_behave_differently() {
+    let dir = tempdir().expect("config dir");
+    let config = dir.path().join("koto_versions.json");
+    fs::write(
+        &config,
+        r#"{ "builds": [{ "name": "missing", "command": "koto-version-matrix-missing" }] }"#,
+    )
+    .unwrap();
+    let matrix = VersionMatrix::load_from(&config).expect("matrix loads");
+    assert_eq!(matrix.builds.len(), 1);
+    assert!(matrix.builds[0].args.is_empty());
+
+    let build = &matrix.builds[0];
+    let error = version_matrix::run_build(
+        build,
+        "print 'hi'",
+        false,
+        &dir.path().join("scratch"),
+        Duration::from_secs(5),
+    )
+    .expect_err("missing command");
+    let missing = BuildRun::failed_to_start(build, &error);
+
+    let run = |build: &str, stdout: &str, millis: u64| BuildRun {
+        build: build.to_string(),
+        succeeded: true,
+        stdout: stdout.to_string(),
+        stderr: String::new(),
+        duration: Duration::from_millis(millis),
+    };
+    let row = MatrixRow {
+        target: "Script".to_string(),
+        compare_output: true,
+        baseline: BuildRun::embedded(
+            true,
+            "hi\n".to_string(),
+            String::new(),
+            Duration::from_millis(300),
+        ),
+        builds: vec![
+            missing,
+            run("same", "hi\n", 320),
+            run("changed", "hello\n", 300),
+            run("slow", "hi\n", 900),
+        ],
+    };
+    let differences = row.differences();
+    let builds: Vec<&str> = differences.iter().map(|(build, _)| *build).collect();
+    assert_eq!(builds, ["missing", "changed", "slow"]);
+    assert!(differences[0].1.contains("fails"));
+    assert_eq!(differences[2].1, "runs 3.0x slower");
+
+    let suite_row = MatrixRow {
+        compare_output: false,
+        ..row.clone()
+    };
+    assert_eq!(suite_row.differences().len(), 2);
+}
+
+#[test]
+fn async_execution_can_be_cancelled() {
+    let runtime: &'static Runtime = Box::leak(Box::new(Runtime::new().expect("runtime")));
+
+    let finished = runtime.execute_script_async("1 + 1", None);
+    assert_eq!(
+        finished
+            .wait()
+            .expect("script runs")
+            .return_value
+            .as_deref(),
+        Some("2")
+    );
+
+    let handle = runtime.execute_script_async(
+        "loop\n  print 'tick'\n  host.sleep_ms 5",
+        Some(Duration::from_secs(30)),
+    );
+    std::thread::sleep(Duration::from_millis(50));
+    assert!(handle.poll().is_none());
+    handle.cancel();
+    let error = handle.wait().expect_err("cancelled run fails");
+    assert!(error.to_string().contains("cancelled"));
+
+    // The runtime is usable again once the cancelled run has ended.
+    let output = runtime.execute_script("'after'").expect("script runs");
+    assert_eq!(output.return_value.as_deref(), Some("after"));
+}
+
+#[test]
+fn cancelling_a_queued_run_leaves_the_running_one_alone() {
+    let runtime: &'static Runtime = Box::leak(Box::new(Runtime::new().expect("runtime")));
+
+    let first = runtime.execute_script_async(
+        "host.sleep_ms 300\nprint 'first done'\n'first'",
+        Some(Duration::from_secs(30)),
+    );
+    // Let the first run take the VM, so the second one queues behind it.
+    std::thread::sleep(Duration::from_millis(50));
+    let second = runtime.execute_script_async("'second'", Some(Duration::from_secs(30)));
+    second.cancel();
+
+    let output = first.wait().expect("the first run isn't cancelled");
+    assert_eq!(output.return_value.as_deref(), Some("first"));
+    assert!(output.stdout.contains("first done"));
+    let error = second.wait().expect_err("the queued run is cancelled");
+    assert!(error.to_string().contains("cancelled"));
+
+    let output = runtime.execute_script("'after'").expect("script runs");
+    assert_eq!(output.return_value.as_deref(), Some("after"));
+}
+
+#[test]
+fn runtime_feeds_stdin_to_scripts() {
+    let runtime = Runtime::new().expect("runtime");
+    runtime.set_stdin("alice\nbob\ncarol\n");
+    let script = "stdin = io.stdin()\nfirst = stdin.read_line()\nrest = stdin.read_to_string()\n'{first}|{rest.trim()}'";
+    let output = runtime.execute_script(script).expect("script reads stdin");
+    assert_eq!(output.return_value.as_deref(), Some("alice|bob\ncarol"));
+
+    // Each run reads stdin from the beginning again.
+    let again = runtime.execute_script(script).expect("second run");
+    assert_eq!(again.return_value, output.return_value);
+
+    runtime.set_stdin("");
+    let empty = runtime
+        .execute_script("io.stdin().read_line()")
+        .expect("empty stdin");
+    assert!(empty.return_value.is_none());
+}
+
+#[test]
+fn runtime_exposes_positional_args() {
+    let runtime = Runtime::new().expect("runtime");
+    let output = runtime
+        .execute_script_with_args(
+            "'{args.size()}: {args[0]}, {args[1]}'",
+            ["first", "two words"],
+        )
+        .expect("script reads args");
+    assert_eq!(output.return_value.as_deref(), Some("2: first, two words"));
+
+    // Arguments don't leak into later runs.
+    let output = runtime
+        .execute_script("args.size()")
+        .expect("args is empty");
+    assert_eq!(output.return_value.as_deref(), Some("0"));
+}
+
+#[test]
+fn runtime_reset_vm_discards_leftover_global_state() {
+    let runtime = Runtime::new().expect("runtime");
+    runtime
+        .execute_script("string.shout = |s| s.to_uppercase()")
+        .expect("extend the string module");
+    let leftover = runtime
+        .execute_script("'hi'.shout()")
+        .expect("leftover state");
+    assert_eq!(leftover.return_value.as_deref(), Some("HI"));
+
+    runtime.reset_vm().expect("rebuild");
+    assert!(runtime.execute_script("'hi'.shout()").is_err());
+    // Host bindings survive the rebuild.
+    let output = runtime.execute_script("host.echo 7").expect("host binding");
+    assert_eq!(output.return_value.as_deref(), Some("7"));
+}
+
+#[test]
+fn runtime_reuses_compiled_scripts() {
+    let runtime = Runtime::new().expect("runtime");
+    let first = runtime.execute_script("x = 40\nx + 2").expect("first run");
+    assert!(!first.cache_hit);
+    let second = runtime.execute_script("x = 40\nx + 2").expect("second run");
+    assert!(second.cache_hit);
+    assert_eq!(second.return_value.as_deref(), Some("42"));
+    assert_eq!(second.compile_cache.hits, 1);
+    assert_eq!(second.compile_cache.entries, 1);
+
+    // Session runs compile with different settings, so they get their own entry.
+    let session = runtime
+        .execute_in_session("cache", "x = 40\nx + 2")
+        .expect("session run");
+    assert!(!session.cache_hit);
+
+    runtime.clear_compile_cache().unwrap();
+    assert_eq!(runtime.compile_cache_stats().unwrap().entries, 0);
+    assert!(!runtime.execute_script("x = 40\nx + 2").unwrap().cache_hit);
+}
+
+#[test]
+fn runtime_enforces_memory_limit() {
+    let runtime = Runtime::new().expect("runtime");
+    let output = runtime.execute_script("[1, 2, 3]").expect("script runs");
+    assert!(output.peak_memory.is_some());
+
+    runtime.set_memory_limit(Some(1024 * 1024)).unwrap();
+    let error = runtime
+        .execute_script("items = []\nfor i in 0..1000000\n  items.push i\n  print i")
+        .expect_err("limit stops the loop");
+    assert!(
+        error.to_string().contains("Memory limit exceeded"),
+        "{error}"
+    );
+
+    // Without host calls the limit is only noticed once the script finishes...
+    let error = runtime
+        .execute_script("(0..200000).to_list().size()")
+        .expect_err("limit is checked at the end");
+    assert!(
+        error.to_string().contains("Memory limit exceeded"),
+        "{error}"
+    );
+    // ...or once the time limit stops a loop that never finishes.
+    let error = runtime
+        .execute_script_with_timeout(
+            "items = []\nloop\n  items.push items.size()",
+            Some(Duration::from_millis(500)),
+        )
+        .expect_err("time limit stops the loop");
+    assert!(
+        error.to_string().contains("Memory limit exceeded"),
+        "{error}"
+    );
+    assert!(error.downcast_ref::<TimeoutError>().is_none());
+
+    runtime.set_memory_limit(None).unwrap();
+    let output = runtime
+        .execute_script("(0..200000).to_list().size()")
+        .expect("script runs without a limit");
+    assert_eq!(output.return_value.as_deref(), Some("200000"));
+}
+
+#[test]
+fn example_library_tracks_script_and_test_changes() {
+    let temp = tempdir().expect("temp dir");
+    let base = temp.path();
+    let example_dir = base.join("demo");
+    let tests_dir = example_dir.join("tests");
+    fs::create_dir_all(&tests_dir).unwrap();
+
+    let initial_script = "print(\"hi\")\n1";
+    fs::write(
+        example_dir.join("meta.json"),
+        r#"{"id":"demo","title":"Demo","description":"Test example"}"#,
+    )
+    .unwrap();
+    fs::write(example_dir.join("script.koto"), initial_script).unwrap();
+    let suite_path = tests_dir.join("sample.koto");
+    fs::write(&suite_path, "tests =\n  @test pass: || 1\nexport tests\n").unwrap();
+
+    let library = ExampleLibrary::new_unwatched(base.to_path_buf()).expect("library");
+    // Drain initial load notifications.
+    let _ = library.take_recent_changes();
+
+    // Modify the script file.
+    fs::write(example_dir.join("script.koto"), "print(\"hi\")\n2").unwrap();
+    library.refresh().unwrap();
+    let changes = library.take_recent_changes();
+    let script_change = changes
+        .iter()
+        .find(|change| matches!(change.kind, ScriptChangeKind::ScriptUpdated { .. }))
+        .cloned()
+        .expect("script change");
+    match &script_change.kind {
+        ScriptChangeKind::ScriptUpdated { previous, current } => {
+            assert!(
+                previous
+                    .as_ref()
+                    .map(|text| text.contains(initial_script))
+                    .unwrap_or(false)
+            );
+            assert!(
+                current
+                    .as_ref()
+                    .map(|text| text.contains("2"))
+                    .unwrap_or(false)
+            );
+        }
+        _ => unreachable!(),
+    }
+
+    library.revert_change(&script_change).unwrap();
+    let reverted_script = fs::read_to_string(example_dir.join("script.koto")).unwrap();
+    assert!(reverted_script.contains(initial_script));
+
+    // Update the test suite file.
+    fs::write(
+        &suite_path,
+        "tests =\n  @test pass: || 1\n  @test another: || throw 'nope'\nexport tests\n",
+    )
+    .unwrap();
+    library.refresh().unwrap();
+    let changes = library.take_recent_changes();
+    let suite_change = changes
+        .into_iter()
+        .find(|change| matches!(change.kind, ScriptChangeKind::TestSuiteUpdated { .. }))
+        .expect("suite change");
+    match &suite_change.kind {
+        ScriptChangeKind::TestSuiteUpdated {
+            previous, current, ..
+        } => {
+            assert!(
+                previous
+                    .as_ref()
+                    .map(|text| text.contains("pass"))
+                    .unwrap_or(false)
+            );
+            assert!(
+                current
+                    .as_ref()
+                    .map(|text| text.contains("another"))
+                    .unwrap_or(false)
+            );
+        }
+        _ => unreachable!(),
+    }
+
+    library.revert_change(&suite_change).unwrap();
+    let reverted_suite = fs::read_to_string(&suite_path).unwrap();
+    assert!(reverted_suite.contains("@test pass"));
+    assert!(!reverted_suite.contains("another"));
+
+    // Update the metadata.
+    let updated_meta =
+        r#"{"id":"demo","title":"Demo","description":"Updated","categories":["new"]}"#;
+    fs::write(example_dir.join("meta.json"), updated_meta).unwrap();
+    library.refresh().unwrap();
+    let meta_change = library
+        .take_recent_changes()
+        .into_iter()
+        .find(|change| matches!(change.kind, ScriptChangeKind::MetadataUpdated { .. }))
+        .expect("metadata change");
+    assert_eq!(meta_change.path, example_dir.join("meta.json"));
+    let snapshot = library.snapshot();
+    let demo = snapshot
+        .iter()
+        .find(|example| example.metadata.id == "demo")
+        .unwrap();
+    assert_eq!(demo.metadata.categories, vec!["new".to_string()]);
+
+    library.revert_change(&meta_change).unwrap();
+    let reverted_meta = fs::read_to_string(example_dir.join("meta.json")).unwrap();
+    assert!(reverted_meta.contains("Test example"));
+}
+
+#[test]
+fn example_library_reports_folder_renames() {
+    let temp = tempdir().expect("temp dir");
+    let base = temp.path();
+    let old_dir = base.join("demo");
+    fs::create_dir_all(&old_dir).unwrap();
+    fs::write(
+        old_dir.join("meta.json"),
+        r#"{"title":"Demo","description":"Test example"}"#,
+    )
+    .unwrap();
+    fs::write(old_dir.join("script.koto"), "1 + 1").unwrap();
+
+    let library = ExampleLibrary::new_unwatched(base.to_path_buf()).expect("library");
+    let _ = library.take_recent_changes();
+
+    let new_dir = base.join("demo_renamed");
+    fs::rename(&old_dir, &new_dir).unwrap();
+    library.refresh().unwrap();
+    let changes = library.take_recent_changes();
+    assert_eq!(changes.len(), 1, "{changes:?}");
+    match &changes[0].kind {
+        ScriptChangeKind::ExampleRenamed { previous_id, .. } => {
+            assert_eq!(previous_id, "demo");
+            assert_eq!(changes[0].example_id, "demo_renamed");
+        }
+        other => panic!("unexpected change {other:?}"),
+    }
+
+    library.revert_change(&changes[0]).unwrap();
+    assert!(old_dir.join("script.koto").exists());
+    assert!(!new_dir.exists());
+}
+
+#[test]
+fn example_library_can_be_pointed_at_another_folder() {
+    let first = tempdir().expect("temp dir");
+    let second = tempdir().expect("temp dir");
+    for (base, id) in [(first.path(), "first"), (second.path(), "second")] {
+        let dir = base.join(id);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("meta.json"),
+            format!(r#"{{"id":"{id}","title":"{id}","description":"Test example"}}"#),
+        )
+        .unwrap();
+        fs::write(dir.join("script.koto"), "1").unwrap();
+    }
+
+    let library = ExampleLibrary::new_unwatched(first.path().to_path_buf()).expect("library");
+    let version = library.version();
+    library
+        .set_examples_dir(second.path().to_path_buf())
+        .expect("switch folders");
+    assert_eq!(library.examples_dir(), Some(second.path().to_path_buf()));
+    let ids: Vec<_> = library
+        .snapshot()
+        .into_iter()
+        .map(|example| example.metadata.id)
+        .collect();
+    assert_eq!(ids, vec!["second".to_string()]);
+    assert_ne!(library.version(), version);
+    // Loading the new folder isn't reported as a batch of additions.
+    assert!(library.take_recent_changes().is_empty());
+
+    // Later refreshes read the new folder.
+    fs::write(second.path().join("second/script.koto"), "2").unwrap();
+    library.refresh().unwrap();
+    assert_eq!(library.get("second").expect("second").script, "2");
+
+    // A folder that doesn't exist leaves the current catalog in place.
+    assert!(
+        library
+            .set_examples_dir(second.path().join("missing"))
+            .is_err()
+    );
+    assert_eq!(library.examples_dir(), Some(second.path().to_path_buf()));
+    assert!(library.get("second").is_some());
+
+    assert!(
+        ExampleLibrary::in_memory(Vec::new())
+            .set_examples_dir(first.path().to_path_buf())
+            .is_err()
+    );
+}
+
+/// Mirrors the synthetic catalog in `benches/library.rs`, so a change that breaks what the
+/// benchmarks measure fails here rather than only when they're run.
+#[test]
+fn synthetic_benchmark_catalogs_load_diff_and_pass() {
+    let catalog = tempdir().expect("catalog");
+    for index in 0..20 {
+        let example_dir = catalog.path().join(format!("example_{index:04}"));
+        fs::create_dir_all(example_dir.join("tests")).unwrap();
+        fs::write(
+            example_dir.join("meta.json"),
+            format!(
+                r#"{{"id":"example_{index:04}","title":"Example {index}","description":"Synthetic example","categories":["Bench"]}}"#
+            ),
+        )
+        .unwrap();
+        fs::write(
+            example_dir.join("script.koto"),
+            format!("x = {index}\nprint 'example {{x}}'\nx * 2\n"),
+        )
+        .unwrap();
+        fs::write(
+            example_dir.join("tests").join("basics.koto"),
+            "export tests =\n  @test doubles: || assert_eq 2 * 2, 4\n",
+        )
+        .unwrap();
+    }
+
+    let mut issues = Vec::new();
+    let old = tasks::run_blocking("Load synthetic catalog", |task| {
+        examples::load_examples_from_dir(catalog.path(), task, &mut issues)
+    })
+    .expect("catalog loads");
+    assert_eq!(old.len(), 20);
+    assert!(issues.is_empty(), "{issues:?}");
+
+    let mut new = old.clone();
+    for example in new.values_mut().step_by(10) {
+        example.script.push_str("\n# edited\n");
+    }
+    let changes = examples::diff_examples(&old, &new);
+    assert_eq!(changes.len(), 2, "{changes:?}");
+    assert!(
+        changes
+            .iter()
+            .all(|change| matches!(change.kind, ScriptChangeKind::ScriptUpdated { .. }))
+    );
+
+    let suites: Vec<_> = old
+        .values()
+        .flat_map(|example| example.test_suites.clone())
+        .collect();
+    assert_eq!(suites.len(), 20);
+    for runner in [
+        example_tests::SuiteRunner::new().threads(1),
+        example_tests::SuiteRunner::new().shared_runtime(true),
+    ] {
+        let results = runner.run_all(&suites).expect("suites run");
+        assert!(results.iter().all(|result| result.passed));
+    }
+}
+
+#[test]
+fn example_library_reports_invalid_metadata_until_fixed() {
+    let temp = tempdir().expect("temp dir");
+    let base = temp.path();
+    let example_dir = base.join("demo");
+    fs::create_dir_all(&example_dir).unwrap();
+    let meta = r#"{"title":"Demo","description":"Test example"}"#;
+    fs::write(example_dir.join("meta.json"), meta).unwrap();
+    fs::write(example_dir.join("script.koto"), "1").unwrap();
+
+    let library = ExampleLibrary::new_unwatched(base.to_path_buf()).expect("library");
+    assert!(library.reload_error().is_none());
+
+    fs::write(example_dir.join("meta.json"), r#"{"title": "Demo","#).unwrap();
+    library.refresh().unwrap();
+    let error = library.reload_error().expect("reload error");
+    assert!(error.contains("demo/meta.json"), "{error}");
+
+    fs::write(example_dir.join("meta.json"), meta).unwrap();
+    library.refresh().unwrap();
+    assert!(library.reload_error().is_none());
+    assert_eq!(library.snapshot().len(), 1);
+}
+
+#[test]
+fn example_library_keeps_snapshot_when_directory_disappears() {
+    let temp = tempdir().expect("temp dir");
+    let base = temp.path().join("examples");
+    let example_dir = base.join("demo");
+    fs::create_dir_all(&example_dir).unwrap();
+    fs::write(
+        example_dir.join("meta.json"),
+        r#"{"title":"Demo","description":"Test example"}"#,
+    )
+    .unwrap();
+    fs::write(example_dir.join("script.koto"), "1").unwrap();
+
+    let library = ExampleLibrary::new_unwatched(base.clone()).expect("library");
+    fs::remove_dir_all(&base).unwrap();
+    library.refresh().unwrap();
+    assert!(library.is_directory_missing());
+    assert_eq!(library.snapshot().len(), 1);
+    let error = library.reload_error().expect("reload error");
+    assert!(error.contains("missing"), "{error}");
+
+    let other = temp.path().join("other");
+    fs::create_dir_all(other.join("second")).unwrap();
+    fs::write(
+        other.join("second").join("meta.json"),
+        r#"{"title":"Second","description":"Another example"}"#,
+    )
+    .unwrap();
+    fs::write(other.join("second").join("script.koto"), "2").unwrap();
+    library.set_examples_dir(other.clone()).unwrap();
+    assert!(!library.is_directory_missing());
+    assert!(library.reload_error().is_none());
+    assert_eq!(library.examples_dir(), Some(other));
+    assert!(library.take_recent_changes().is_empty());
+    let snapshot = library.snapshot();
+    assert_eq!(snapshot.len(), 1);
+    assert_eq!(snapshot[0].metadata.id, "second");
+}
+
+#[test]
+fn example_library_scaffolds_new_examples() {
+    let temp = tempdir().expect("temp dir");
+    let library = ExampleLibrary::new_unwatched(temp.path().to_path_buf()).expect("library");
+
+    let id = library.create_example("My First Loop").expect("created");
+    assert_eq!(id, "my_first_loop");
+    let example = library.get(&id).expect("new example is loaded");
+    assert_eq!(example.metadata.title, "My First Loop");
+    assert!(example.script.contains("print"));
+
+    let second = library.create_example("My First Loop").expect("created");
+    assert_eq!(second, "my_first_loop_2");
+    assert!(library.create_example("  ").is_err());
+}
+
+#[test]
+fn recent_catalogs_are_deduplicated_and_persisted() {
+    let temp = tempdir().expect("temp dir");
+    let file = temp.path().join("config").join("recent.json");
+    let course = temp.path().join("course");
+    let workspace = temp.path().join("workspace");
+    fs::create_dir_all(&course).unwrap();
+    fs::create_dir_all(&workspace).unwrap();
+
+    let mut recent = RecentCatalogs::load_from(file.clone());
+    assert!(recent.entries().is_empty());
+    recent.record(&course).unwrap();
+    recent.record(&workspace).unwrap();
+    recent.record(&course).unwrap();
+
+    let reloaded = RecentCatalogs::load_from(file.clone());
+    assert_eq!(
+        reloaded.entries(),
+        &[
+            course.canonicalize().unwrap(),
+            workspace.canonicalize().unwrap()
+        ]
+    );
+
+    let mut reloaded = reloaded;
+    reloaded.remove(&workspace.canonicalize().unwrap()).unwrap();
+    assert_eq!(RecentCatalogs::load_from(file).entries().len(), 1);
+}
+
+#[test]
+fn runtime_env_module_respects_allowlist() {
+    let runtime = Runtime::new().expect("runtime");
+    let blocked = runtime.execute_script("env.get 'CARGO_PKG_NAME'");
+    assert!(blocked.unwrap_err().to_string().contains("allowlist"));
+
+    runtime.set_env_allowlist(["CARGO_PKG_NAME"]);
+    let output = runtime
+        .execute_script("env.get 'CARGO_PKG_NAME'")
+        .expect("allowed variable");
+    assert_eq!(output.return_value.as_deref(), Some("koto_learning"));
+}
+
+#[test]
+fn runtime_blocks_clipboard_outside_trusted_profile() {
+    let runtime = Runtime::new().expect("runtime");
+    assert_eq!(runtime.profile(), RuntimeProfile::Standard);
+    let error = runtime.execute_script("clipboard.get()").unwrap_err();
+    assert!(error.to_string().contains("trusted"));
+}
+
+#[test]
+fn runtime_sleep_respects_execution_timeout() {
+    let runtime = Runtime::new().expect("runtime");
+    let output = runtime
+        .execute_script("host.sleep_ms 20\nhost.elapsed() >= 0.02")
+        .expect("sleep script");
+    assert_eq!(output.return_value.as_deref(), Some("true"));
+
+    let error = runtime
+        .execute_script_with_timeout("host.sleep_ms 5000", Some(Duration::from_millis(50)))
+        .unwrap_err();
+    assert!(error.to_string().contains("Execution limit"));
+}
+
+#[test]
+fn runtime_timeout_keeps_partial_output() {
+    let runtime = Runtime::new().expect("runtime");
+    let error = runtime
+        .execute_script_with_timeout(
+            "print 'started'\nhost.sleep_ms 5000\nprint 'finished'",
+            Some(Duration::from_millis(50)),
+        )
+        .unwrap_err();
+    let timeout = error.downcast_ref::<TimeoutError>().expect("timeout error");
+    assert_eq!(timeout.limit, Duration::from_millis(50));
+    assert!(timeout.elapsed >= timeout.limit);
+    assert!(timeout.stdout.contains("started"));
+    assert!(!timeout.stdout.contains("finished"));
+
+    // Other errors are not reported as timeouts.
+    let error = runtime
+        .execute_script_with_timeout("undefined_function()", Some(Duration::from_secs(5)))
+        .unwrap_err();
+    assert!(error.downcast_ref::<TimeoutError>().is_none());
+}
+
+#[test]
+fn runtime_records_traced_generator_steps() {
+    let runtime = Runtime::new().expect("runtime");
+    let script = "gen = ||\n  yield 1\n  yield 2\nhost.trace('pair', gen()).to_list()";
+    let output = runtime.execute_script(script).expect("generator script");
+    let yielded: Vec<_> = output
+        .generator_trace
+        .iter()
+        .filter_map(|event| match &event.kind {
+            GeneratorEventKind::Yielded(value) => Some(value.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(yielded, ["1", "2"]);
+    assert!(
+        output
+            .generator_trace
+            .iter()
+            .all(|event| event.label == "pair")
+    );
+    assert_eq!(
+        output.generator_trace.last().map(|event| &event.kind),
+        Some(&GeneratorEventKind::Finished)
+    );
+}
+
+#[test]
+fn language_detects_core_library_references() {
+    let script = "# list.fold in a comment\nx = list.fold [1, 2], 0, |a, b| a + b\ny = 'string.split'\nstring.split 'a b', ' '";
+    let references = language::find_core_references(script);
+    let names: Vec<_> = references
+        .iter()
+        .map(|reference| reference.qualified_name())
+        .collect();
+    assert_eq!(names, ["list.fold", "string.split"]);
+    assert_eq!(
+        references[0].doc_url("https://koto.dev/docs/0.16/core/"),
+        "https://koto.dev/docs/0.16/core/list/#fold"
+    );
+}
+
+#[test]
+fn symbol_index_resolves_core_and_script_bindings() {
+    let script =
+        "# Doubles a number.\nexport double = |n| n * 2\ntotal = list.fold [1], 0, |a, b| a + b\n";
+    let index = SymbolIndex::for_script(script);
+
+    let double = index.get("double").expect("export indexed");
+    assert_eq!(double.kind, SymbolKind::Export);
+    assert_eq!(double.signature.as_deref(), Some("|n|"));
+    assert_eq!(double.summary.as_deref(), Some("Doubles a number."));
+
+    let fold_offset = script.find("fold").unwrap();
+    let fold = index.symbol_at(script, fold_offset).expect("core symbol");
+    assert_eq!(fold.name, "list.fold");
+    assert!(fold.signature.is_some());
+
+    let module = index
+        .symbol_at(script, fold_offset - 2)
+        .expect("module symbol");
+    assert_eq!(module.kind, SymbolKind::CoreModule);
+}
+
+#[test]
+fn symbol_index_completes_keywords_core_and_host_names() {
+    let runtime = Runtime::new().expect("runtime");
+    let host_names = runtime.host_binding_names().expect("host names");
+    assert!(host_names.iter().any(|name| name == "host.sleep_ms"));
+
+    let index = SymbolIndex::for_script("counter = 0\n").with_host_bindings(host_names);
+    let names = |prefix: &str| -> Vec<String> {
+        index
+            .completions(prefix, 20)
+            .into_iter()
+            .map(|symbol| symbol.name)
+            .collect()
+    };
+
+    assert!(names("li").contains(&"list".to_string()));
+    assert!(names("yi").contains(&"yield".to_string()));
+    assert!(names("cou").contains(&"counter".to_string()));
+    assert!(names("list.fo").contains(&"list.fold".to_string()));
+    assert!(names("host.sle").contains(&"host.sleep_ms".to_string()));
+    assert!(!names("li").iter().any(|name| name.contains('.')));
+}
+
+#[test]
+fn lsp_uris_round_trip_and_missing_servers_fail_cleanly() {
+    let dir = tempdir().expect("temp dir");
+    let script = dir.path().join("script.koto");
+    fs::write(&script, "x = 1\n").expect("write script");
+
+    let uri = lsp::path_to_uri(&script);
+    assert!(uri.starts_with("file://"));
+    let round_trip = lsp::uri_to_path(&uri).expect("file uri");
+    assert_eq!(
+        round_trip.canonicalize().unwrap(),
+        script.canonicalize().unwrap()
+    );
+    assert!(lsp::uri_to_path("https://koto.dev").is_none());
+
+    let error = lsp::LspClient::spawn("koto-ls-does-not-exist --stdio", dir.path())
+        .err()
+        .expect("missing server");
+    assert!(error.to_string().contains("koto-ls-does-not-exist"));
+}
+
+#[test]
+fn notebook_examples_load_cells_and_share_session_state() {
+    let source =
+        "# Intro\n\n```koto\nx = 20\n```\n\n```text\nnot code\n```\n\n```koto\nx + 1\n```\n";
+    let notebook = Notebook::parse(PathBuf::from("notebook.md"), source);
+    assert_eq!(notebook.cells.len(), 4);
+    assert_eq!(
+        notebook.cells[1],
+        NotebookCell::Code("x = 20\n".to_string())
+    );
+    assert!(
+        matches!(&notebook.cells[2], NotebookCell::Markdown(text) if text.contains("not code"))
+    );
+    assert_eq!(notebook.script(), "x = 20\nx + 1\n");
+
+    let temp = tempdir().expect("temp dir");
+    let example_dir = temp.path().join("tour");
+    fs::create_dir_all(&example_dir).unwrap();
+    fs::write(
+        example_dir.join("meta.json"),
+        r#"{"title": "Tour", "description": "Notebook"}"#,
+    )
+    .unwrap();
+    fs::write(example_dir.join("notebook.md"), source).unwrap();
+    let library = ExampleLibrary::new_unwatched(temp.path().to_path_buf()).expect("library");
+    let example = library.get("tour").expect("notebook example");
+    assert_eq!(example.script, notebook.script());
+    assert_eq!(example.source(), source);
+    assert!(example.notebook.is_some());
+
+    let runtime = Runtime::new().expect("runtime");
+    let cells: Vec<_> = notebook
+        .code_cells()
+        .map(|(_, code)| code.to_string())
+        .collect();
+    runtime
+        .execute_in_session("tour", &cells[0])
+        .expect("first cell");
+    let output = runtime
+        .execute_in_session("tour", &cells[1])
+        .expect("second cell");
+    assert_eq!(output.return_value.as_deref(), Some("21"));
+
+    // Other sessions and plain runs don't see the session's bindings.
+    assert!(runtime.execute_in_session("other", &cells[1]).is_err());
+    assert!(runtime.execute_script(&cells[1]).is_err());
+
+    runtime.reset_session("tour").expect("reset");
+    assert!(runtime.execute_in_session("tour", &cells[1]).is_err());
+}
+
+#[test]
+fn blocking_tasks_report_progress_and_outcomes() {
+    let mut id = None;
+    let result = tasks::run_blocking("Importing pack", |task| {
+        id = Some(task.id());
+        task.set_steps(1, 4);
+        task.set_message("examples/basics");
+
+        // Tasks from other tests may be running as well, so only look at ours.
+        assert!(tasks::is_busy());
+        let active = tasks::active();
+        let snapshot = active
+            .iter()
+            .find(|snapshot| snapshot.id == task.id())
+            .expect("active task");
+        assert_eq!(snapshot.fraction(), Some(0.25));
+        assert_eq!(snapshot.message.as_deref(), Some("examples/basics"));
+        anyhow::bail!("the pack is damaged")
+    });
+    assert!(result.is_err());
+
+    let id = id.expect("task id");
+    assert!(tasks::active().iter().all(|snapshot| snapshot.id != id));
+    let finished = tasks::finished();
+    let snapshot = finished
+        .iter()
+        .find(|snapshot| snapshot.id == id)
+        .expect("finished task");
+    assert_eq!(snapshot.label, "Importing pack");
+    assert!(!snapshot.finished.as_ref().expect("outcome").succeeded);
+}
+
+#[test]
+fn background_tasks_report_results_and_cancellation() {
+    fn wait<T>(task: &tasks::Task<T>) -> anyhow::Result<T> {
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        loop {
+            if let Some(result) = task.poll() {
+                return result;
+            }
+            assert!(std::time::Instant::now() < deadline, "task timed out");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    let task = tasks::spawn("Adding numbers", |context| {
+        context.set_steps(1, 2);
+        Ok(40 + 2)
+    });
+    assert_eq!(wait(&task).expect("result"), 42);
+
+    let task = tasks::spawn("Waiting for cancel", |context| {
+        while !context.is_cancelled() {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        context.check_cancelled()
+    });
+    assert!(tasks::is_cancellable(task.id()));
+    assert!(tasks::cancel(task.id()));
+    assert!(wait(&task).is_err());
+
+    // Events from other tests may be interleaved, so only look at ours.
+    let cancelled_id = task.id();
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    let mut event = None;
+    while event.is_none() && std::time::Instant::now() < deadline {
+        event = tasks::take_events()
+            .into_iter()
+            .find(|event| event.id == cancelled_id);
+    }
+    assert_eq!(event.expect("event").status, tasks::TaskStatus::Cancelled);
+    assert!(!tasks::is_cancellable(cancelled_id));
+
+    // Cancelling a task stops a script run given the task's cancel flag.
+    static TASK_RUNTIME: Lazy<Runtime> = Lazy::new(|| Runtime::new().expect("runtime"));
+    let task = tasks::spawn_quietly("Running a loop", |task| {
+        let options = RunOptions::new().cancel_flag(task.cancel_flag());
+        TASK_RUNTIME.execute("loop\n  host.sleep_ms 5", &options)
+    });
+    std::thread::sleep(Duration::from_millis(50));
+    task.cancel();
+    let error = wait(&task).expect_err("the run is stopped");
+    assert!(error.to_string().contains("cancelled"), "{error}");
+}
+
+#[test]
+fn examples_export_as_standalone_html() {
+    let library = ExampleLibrary::in_memory(vec![(
+        ExampleMetadata {
+            id: "greeter".to_string(),
+            title: "Greeter <1>".to_string(),
+            description: "Says hi".to_string(),
+            categories: vec!["basics".to_string()],
+            ..Default::default()
+        },
+        "# Greets\nif true then print 'hi & bye'\n42".to_string(),
+    )]);
+    let example = library.snapshot().remove(0);
+    let docs = "# Greeter\n\nCall `greet` to say hi.\n\n- one\n- two\n\n```koto\nx = 1\n```";
+    let output = examples::html::SampleOutput {
+        stdout: "hi & bye\n".to_string(),
+        return_value: Some("42".to_string()),
+        ..Default::default()
+    };
+
+    let html = examples::html::example_html(&example, Some(docs), Some(&output));
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<title>Greeter &lt;1&gt;</title>"));
+    assert!(html.contains("<p>Call <code>greet</code> to say hi.</p>"));
+    assert!(html.contains("<ul>\n<li>one</li>\n<li>two</li>\n</ul>"));
+    assert!(html.contains(r#"<span class="comment"># Greets</span>"#));
+    assert!(html.contains(r#"<span class="kw">if</span>"#));
+    assert!(html.contains(r#"<span class="str">'hi &amp; bye'</span>"#));
+    assert!(html.contains(r#"x = <span class="num">1</span>"#));
+    assert!(html.contains("<pre>hi &amp; bye</pre>"));
+    assert!(html.contains("Return value: <code>42</code>"));
+    assert!(!html.contains("<h2>Benchmarks</h2>"));
+
+    let dir = tempdir().expect("export dir");
+    let path = dir.path().join("pages/greeter.html");
+    examples::html::export_html(&example, None, &path).expect("export");
+    let page = fs::read_to_string(path).expect("page");
+    assert!(!page.contains("Sample output"));
+}
+
+#[test]
+fn in_memory_library_serves_examples_without_files() {
+    let library = ExampleLibrary::in_memory(vec![
+        (
+            ExampleMetadata {
+                title: "Hello World".to_string(),
+                description: "Greets".to_string(),
+                ..Default::default()
+            },
+            "'hello'".to_string(),
+        ),
+        (
+            ExampleMetadata {
+                id: "sum".to_string(),
+                title: "Sum".to_string(),
+                description: "Adds".to_string(),
+                categories: vec!["math".to_string()],
+                ..Default::default()
+            },
+            "1 + 2".to_string(),
+        ),
+    ]);
+
+    let ids: Vec<_> = library
+        .snapshot()
+        .into_iter()
+        .map(|example| example.metadata.id)
+        .collect();
+    assert_eq!(ids, vec!["hello_world".to_string(), "sum".to_string()]);
+
+    let version = library.version();
+    library.refresh().expect("refresh is a no-op");
+    assert_eq!(library.version(), version);
+    assert!(library.take_recent_changes().is_empty());
+
+    let example = library.get("sum").expect("sum example");
+    assert!(!example.script_path.exists());
+    let runtime = Runtime::new().expect("runtime");
+    let output = runtime.execute_script(&example.script).expect("run");
+    assert_eq!(output.return_value.as_deref(), Some("3"));
+}
+
+#[test]
+fn runtime_lists_prelude_entries() {
+    let runtime = Runtime::new().expect("runtime");
+    runtime
+        .register_host_function("greet", |_ctx| Ok(KValue::Null))
+        .expect("register greet");
+    let entries = runtime.prelude_entries().expect("prelude entries");
+
+    let serde = entries
+        .iter()
+        .find(|entry| entry.name == "serde")
+        .expect("serde entry");
+    assert_eq!(serde.source, PreludeSource::Host);
+    assert!(serde.members.iter().any(|member| member.name == "to_json"));
+
+    let string = entries
+        .iter()
+        .find(|entry| entry.name == "string")
+        .expect("core string module");
+    assert_eq!(string.source, PreludeSource::Core);
+
+    let greet = entries
+        .iter()
+        .find(|entry| entry.name == "greet")
+        .expect("registered function");
+    assert_eq!(greet.source, PreludeSource::Host);
+    assert!(greet.members.is_empty());
+}
+
+#[test]
+fn runtime_calls_host_functions_directly() {
+    let runtime = Runtime::new().expect("runtime");
+    runtime
+        .register_host_function("add", |ctx| match ctx.args() {
+            [KValue::Number(a), KValue::Number(b)] => Ok(KValue::Number(a + b)),
+            other => runtime_error!("Expected two numbers, found {other:?}"),
+        })
+        .expect("register add");
+
+    let output = runtime
+        .call_host_function("add", &["40", "1 + 1"])
+        .expect("call add");
+    assert_eq!(output.return_value.as_deref(), Some("42"));
+
+    let error = runtime.call_host_function("missing", &[]).unwrap_err();
+    assert!(error.to_string().contains("not a registered host binding"));
+    let error = runtime.call_host_function("add", &["1", " "]).unwrap_err();
+    assert!(error.to_string().contains("can't be empty"));
+}
+
+#[test]
+fn runtime_precompiles_into_compile_cache_and_disk() {
+    let runtime = Runtime::new().expect("runtime");
+    let script = "[1, 2, 3].each(|n| n * 2).to_tuple()";
+    let artifact = runtime.precompile(script).expect("precompile");
+    assert!(!artifact.bytecode.is_empty());
+    assert!(artifact.compiled());
+    assert!(artifact.is_current_for(script));
+
+    let output = runtime.execute_script(script).expect("run");
+    assert!(output.cache_hit);
+    assert_eq!(output.return_value.as_deref(), Some("(2, 4, 6)"));
+
+    let dir = tempdir().expect("temp dir");
+    let cache = DiskCache::new(dir.path().join("koto-cache"));
+    assert!(cache.get(script).is_none());
+    cache.store(&artifact).expect("store artifact");
+    let loaded = cache.get(script).expect("cached artifact");
+    assert_eq!(loaded.bytecode, artifact.bytecode);
+    assert!(cache.get("1 + 1").is_none());
+
+    let broken = "x = (";
+    let error = runtime.precompile(broken).expect_err("syntax error");
+    cache
+        .store(&PrecompiledScript::failed(broken, &error))
+        .expect("store failure");
+    let loaded = cache.get(broken).expect("cached failure");
+    assert!(!loaded.compiled());
+    assert!(loaded.bytecode.is_empty());
+    // Changing the script makes the recorded failure stale.
+    assert!(cache.get("x = (1)").is_none());
+}
+
+#[test]
+fn runtime_injects_per_run_bindings() {
+    let runtime = Runtime::new().expect("runtime");
+    let options = RunOptions::new()
+        .binding("gravity", RunBinding::Value(serde_json::json!(9.81)))
+        .binding("shout", RunBinding::Alias("string.to_uppercase".into()))
+        .binding("serde", RunBinding::Value(serde_json::json!("shadowed")));
+    let output = runtime
+        .execute("'{shout \"hi\"} {gravity} {serde}'", &options)
+        .expect("bindings available");
+    assert_eq!(output.return_value.as_deref(), Some("HI 9.81 shadowed"));
+
+    // Bindings only last for their run, and shadowed entries are restored.
+    assert!(runtime.execute_script("gravity").is_err());
+    let output = runtime
+        .execute_script("type serde")
+        .expect("serde restored");
+    assert_eq!(output.return_value.as_deref(), Some("Map"));
+
+    let error = runtime
+        .execute(
+            "1",
+            &RunOptions::new().binding("oops", RunBinding::Alias("string.missing".into())),
+        )
+        .unwrap_err();
+    assert!(error.to_string().contains("unknown 'string.missing'"));
+}
+
+#[test]
+fn input_bindings_keep_quotes_and_newlines_intact() {
+    let runtime = Runtime::new().expect("runtime");
+    let tricky = "say \"hi\"\\n\nsecond line";
+    let output = runtime
+        .execute_script_with_bindings(
+            "input.name",
+            [("input", serde_json::json!({ "name": tricky }))],
+        )
+        .expect("input map available");
+    assert_eq!(output.return_value.as_deref(), Some(tricky));
+}
+
+#[test]
+fn typed_inputs_are_coerced_before_the_run() {
+    let input = |name: &str, kind: InputType, default: &str| ExampleInput {
+        name: name.to_string(),
+        kind,
+        default: Some(default.to_string()),
+        ..Default::default()
+    };
+    let metadata = ExampleMetadata {
+        title: "Inputs".to_string(),
+        inputs: vec![
+            input("count", InputType::Number, "3"),
+            input("ratio", InputType::Number, "0.5"),
+            input("loud", InputType::Bool, "false"),
+            input("config", InputType::Json, r#"{"tags": ["a", "b"]}"#),
+            input("name", InputType::String, "42"),
+        ],
+        ..Default::default()
+    };
+    let values = HashMap::from([("loud".to_string(), "yes".to_string())]);
+    let coerced = metadata.coerce_inputs(&values).expect("valid inputs");
+    assert_eq!(
+        serde_json::Value::Object(coerced.clone()),
+        serde_json::json!({
+            "count": 3,
+            "ratio": 0.5,
+            "loud": true,
+            "config": { "tags": ["a", "b"] },
+            "name": "42",
+        })
+    );
+
+    let runtime = Runtime::new().expect("runtime");
+    let output = runtime
+        .execute_script_with_bindings(
+            "'{input.count + 1} {input.loud} {input.config.tags.size()} {type input.name}'",
+            [("input", serde_json::Value::Object(coerced))],
+        )
+        .expect("typed inputs");
+    assert_eq!(output.return_value.as_deref(), Some("4 true 2 String"));
+
+    let values = HashMap::from([("count".to_string(), "three".to_string())]);
+    let error = metadata.coerce_inputs(&values).unwrap_err();
+    assert!(
+        error.to_string().contains("'count' expects a number"),
+        "{error}"
+    );
+
+    let meta: ExampleMetadata = serde_json::from_str(
+        r#"{"title":"T","description":"D","inputs":[{"name":"n","type":"number"}]}"#,
+    )
+    .unwrap();
+    assert_eq!(meta.inputs[0].kind, InputType::Number);
+}
+
+#[test]
+fn how_it_works_steps_link_to_script_lines() {
+    let meta: ExampleMetadata = serde_json::from_str(
+        r#"{"title":"T","description":"D","how_it_works":[
+            "Plain paragraph.",
+            {"text":"Loops over the scores.","lines":[2,3]},
+            {"text":"Runs past the end.","lines":[3,40]}
+        ]}"#,
+    )
+    .unwrap();
+    let script = "scores = [1, 2]\nfor score in scores\n  print score\n";
+    let steps = &meta.how_it_works;
+    assert_eq!(steps[0].text, "Plain paragraph.");
+    assert_eq!(steps[0].line_range(script), None);
+    assert_eq!(steps[1].line_range(script), Some(1..=2));
+    assert_eq!(steps[2].line_range(script), Some(2..=2));
+
+    // Unlinked steps are written back as plain strings.
+    let json = serde_json::to_value(&meta.how_it_works).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!([
+            "Plain paragraph.",
+            {"text": "Loops over the scores.", "lines": [2, 3]},
+            {"text": "Runs past the end.", "lines": [3, 40]},
+        ])
+    );
+}
+
+#[test]
+fn category_pages_load_from_the_catalog() {
+    let catalog = tempdir().expect("catalog");
+    assert!(
+        categories::load_pages(catalog.path())
+            .expect("no pages")
+            .is_empty()
+    );
+
+    let path = categories::page_path(catalog.path(), "Data formats");
+    assert!(path.ends_with("categories/data_formats/category.md"));
+    fs::create_dir_all(path.parent().unwrap()).expect("category dir");
+    fs::write(&path, "# Data formats\n\nJSON, YAML, and friends.\n").expect("page");
+    // A category folder without a page is skipped.
+    fs::create_dir_all(catalog.path().join("categories").join("empty")).expect("empty dir");
+
+    let pages = categories::load_pages(catalog.path()).expect("pages load");
+    assert_eq!(pages.keys().collect::<Vec<_>>(), ["data_formats"]);
+    assert!(pages["data_formats"].markdown.contains("JSON, YAML"));
+
+    // The pages folder isn't mistaken for an example.
+    let mut issues = Vec::new();
+    let examples = tasks::run_blocking("Load catalog", |task| {
+        examples::load_examples_from_dir(catalog.path(), task, &mut issues)
+    })
+    .expect("catalog loads");
+    assert!(examples.is_empty() && issues.is_empty());
+}
+
+#[test]
+fn glossary_links_first_mentions_of_terms() {
+    let catalog = tempdir().expect("catalog");
+    assert!(
+        Glossary::load(catalog.path())
+            .expect("no glossary")
+            .is_empty()
+    );
+    fs::write(
+        catalog.path().join(glossary::GLOSSARY_FILE_NAME),
+        r#"{"terms": [
+            {"term": "test", "definition": "One check."},
+            {"term": "test suite", "aliases": ["suites"], "definition": "A file of tests.",
+             "examples": ["testing"]}
+        ]}"#,
+    )
+    .expect("glossary");
+    let glossary = Glossary::load(catalog.path()).expect("glossary loads");
+
+    let spans = glossary.link_terms("Test suites hold a Test Suite; each test and suites again.");
+    let linked: Vec<(&str, &str)> = spans
+        .iter()
+        .filter_map(|span| match span {
+            glossary::TextSpan::Term { text, term } => Some((*text, term.term.as_str())),
+            glossary::TextSpan::Plain(_) => None,
+        })
+        .collect();
+    // "Test suites" isn't a whole-word match for "test suite", so only its "Test" links, and
+    // later mentions of either term stay plain.
+    assert_eq!(linked, [("Test", "test"), ("Test Suite", "test suite")]);
+    let text: String = spans
+        .iter()
+        .map(|span| match span {
+            glossary::TextSpan::Plain(text) | glossary::TextSpan::Term { text, .. } => *text,
+        })
+        .collect();
+    assert_eq!(
+        text,
+        "Test suites hold a Test Suite; each test and suites again."
+    );
+
+    assert_eq!(
+        glossary.get("SUITES").map(|term| term.term.as_str()),
+        Some("test suite")
+    );
+    let terms: Vec<&str> = glossary
+        .terms_for_example("testing")
+        .iter()
+        .map(|term| term.term.as_str())
+        .collect();
+    assert_eq!(terms, ["test suite"]);
+}
+
+#[test]
+fn return_values_convert_to_json_trees() {
+    let runtime = Runtime::new().expect("runtime");
+    let output = runtime
+        .execute_script("{count: 3, ratio: 0.5, tags: ('a', 'b'), nested: {ok: true, none: null}}")
+        .expect("run");
+    assert_eq!(
+        output.value_as_json(),
+        Some(serde_json::json!({
+            "count": 3,
+            "ratio": 0.5,
+            "tags": ["a", "b"],
+            "nested": {"ok": true, "none": null},
+        }))
+    );
+    assert!(output.value_as_json().unwrap()["count"].is_i64());
+
+    let output = runtime
+        .execute_script("{f: |x| x, nan: 0 / 0}")
+        .expect("run");
+    assert_eq!(
+        output.value_as_json(),
+        Some(serde_json::json!({"f": "<Function>", "nan": null}))
+    );
+
+    let output = runtime.execute_script("x = 1").expect("run");
+    assert_eq!(output.value_as_json(), Some(serde_json::json!(1)));
+}
+
+#[test]
+fn execution_output_exports_as_json() {
+    let runtime = Runtime::new().expect("runtime");
+    let output = runtime
+        .execute_script("print 'hi'\n{total: 3, tags: ['a']}")
+        .expect("run");
+    let json = output.to_json();
+    assert_eq!(json["stdout"], "hi\n");
+    assert_eq!(json["stderr"], "");
+    assert_eq!(
+        json["value"],
+        serde_json::json!({"total": 3, "tags": ["a"]})
+    );
+    assert!(
+        json["return_value"]
+            .as_str()
+            .is_some_and(|text| text.contains("total"))
+    );
+    assert!(
+        json["duration_ms"]
+            .as_f64()
+            .is_some_and(|millis| millis >= 0.0)
+    );
+
+    // Values without a JSON form export the same placeholders as the value tree, and the
+    // rest of the value is kept.
+    let output = runtime
+        .execute_script("{f: |x| x, nan: 0 / 0, ok: true}")
+        .expect("function");
+    let json = output.to_json();
+    assert_eq!(Some(json["value"].clone()), output.value_as_json());
+    assert_eq!(
+        json["value"],
+        serde_json::json!({"f": "<Function>", "nan": null, "ok": true})
+    );
+    assert!(json["return_value"].is_string());
+
+    let output = runtime.execute_script("x = 1\nnull").expect("null");
+    assert_eq!(output.to_json()["value"], serde_json::Value::Null);
+}
+
+#[test]
+fn host_fs_is_confined_to_the_scratch_dir() {
+    let runtime = Runtime::new().expect("runtime");
+    assert!(runtime.execute_script("host.fs.list_dir()").is_err());
+
+    let dir = tempdir().expect("temp dir");
+    let scratch = dir.path().join("scratch");
+    runtime.set_scratch_dir(Some(scratch.clone()));
+    let output = runtime
+        .execute_script("host.fs.list_dir().size()")
+        .expect("empty scratch dir");
+    assert_eq!(output.return_value.as_deref(), Some("0"));
+
+    let output = runtime
+        .execute_script(
+            "host.fs.write_text 'notes/hello.txt', 'hi'\nhost.fs.read_text('notes/hello.txt') + host.fs.list_dir('notes')[0]",
+        )
+        .expect("write and read");
+    assert_eq!(output.return_value.as_deref(), Some("hihello.txt"));
+    assert_eq!(
+        fs::read_to_string(scratch.join("notes/hello.txt")).expect("file on disk"),
+        "hi"
+    );
+
+    for script in [
+        "host.fs.read_text '../outside.txt'",
+        "host.fs.write_text '/tmp/outside.txt', 'x'",
+    ] {
+        let error = runtime.execute_script(script).unwrap_err();
+        assert!(error.to_string().contains("inside the scratch directory"));
+    }
+    runtime.set_scratch_dir(None);
+}
+
+#[test]
+fn runtime_applies_number_format_to_return_values() {
+    let runtime = Runtime::new().expect("runtime");
+    let script = "[1234567, 3.14159265, 'pi', {big: 12345678901.5}]";
+    let default = runtime.execute_script(script).expect("default format");
+
+    runtime.set_number_format(NumberFormat {
+        precision: Some(2),
+        scientific_threshold: Some(1e10),
+        thousands_separators: true,
+    });
+    let output = runtime.execute_script(script).expect("custom format");
+    assert_eq!(
+        output.return_value.as_deref(),
+        Some("[1,234,567, 3.14, 'pi', {big: 1.23e10}]")
+    );
+
+    runtime.set_number_format(NumberFormat::default());
+    let output = runtime.execute_script(script).expect("default again");
+    assert_eq!(output.return_value, default.return_value);
+}
+
+#[test]
+fn host_random_is_reproducible_from_its_seed() {
+    let runtime = Runtime::new().expect("runtime");
+    let script = "r = host.random\n(r.int(1, 6), r.uniform(), r.choice(['a', 'b', 'c']), r.shuffle([1, 2, 3, 4]))";
+    let first = runtime.execute_script(script).expect("random script");
+    let seed = first.random_seed.expect("seed reported");
+
+    runtime.set_random_seed(Some(seed));
+    let replay = runtime.execute_script(script).expect("replayed run");
+    assert_eq!(replay.return_value, first.return_value);
+    assert_eq!(replay.random_seed, Some(seed));
+    runtime.set_random_seed(None);
+
+    let seeded = "host.random.seed 42\n(host.random.int(0, 1000), host.random.int(0, 1000))";
+    let a = runtime.execute_script(seeded).expect("seeded run");
+    let b = runtime.execute_script(seeded).expect("seeded again");
+    assert_eq!(a.return_value, b.return_value);
+
+    let output = runtime.execute_script("1 + 1").expect("no randomness");
+    assert!(output.random_seed.is_none());
+    assert!(runtime.execute_script("host.random.int 5, 1").is_err());
+}
+
+#[test]
+fn execution_output_splits_compile_and_execute_time() {
+    let runtime = Runtime::new().expect("runtime");
+    let script = "fib = |n| if n < 2 then n else fib(n - 1) + fib(n - 2)\nfib 15";
+    let output = runtime.execute_script(script).expect("first run");
+    assert_eq!(output.compile_time + output.execute_time, output.duration);
+    assert!(output.execute_time > Duration::ZERO);
+
+    // A cache hit skips compilation, leaving only the lookup.
+    let cached = runtime.execute_script(script).expect("cached run");
+    assert!(cached.cache_hit);
+    assert!(cached.compile_time <= output.compile_time);
+    assert_eq!(cached.compile_time + cached.execute_time, cached.duration);
+}
+
+#[test]
+fn repeated_runs_compile_only_the_first_time() {
+    let runtime = Runtime::new().expect("runtime");
+    let script = "(1..50).each(|n| n * n).to_list().size()";
+    runtime.precompile(script).expect("precompile");
+    assert!(runtime.forget_compiled(script).expect("forget"));
+    assert!(!runtime.forget_compiled(script).expect("already forgotten"));
+
+    let mut series = RunSeries::default();
+    for _ in 0..4 {
+        series.push(&runtime.execute_script(script).expect("run"));
+    }
+    assert!(!series.first().expect("first run").cache_hit);
+    assert!(series.runs.iter().skip(1).all(|run| run.cache_hit));
+    let warm = series.warm_stats().expect("warm runs");
+    assert!(warm.min <= warm.median && warm.median <= warm.max);
+
+    let stats = DurationStats::from_durations(&[
+        Duration::from_millis(4),
+        Duration::from_millis(1),
+        Duration::from_millis(2),
+        Duration::from_millis(9),
+    ])
+    .expect("stats");
+    assert_eq!(stats.median, Duration::from_millis(3));
+    assert_eq!(stats.max, Duration::from_millis(9));
+    assert!(DurationStats::from_durations(&[]).is_none());
+}
+
+#[test]
+#[cfg(feature = "serialization")]
+fn serde_csv_round_trips_rows_as_maps() {
+    let runtime = Runtime::new().expect("runtime");
+    let script = r#"
+rows = serde.from_csv 'name,age,zip\nAda,36,02139\n"Lovelace, A",1.5,\n'
+(rows[0].name, rows[0].age, rows[0].zip, rows[1].name, rows[1].age, rows[1].zip)
+"#;
+    let output = runtime.execute_script(script).expect("from_csv");
+    assert_eq!(
+        output.return_value.as_deref(),
+        Some("('Ada', 36, '02139', 'Lovelace, A', 1.5, '')")
+    );
+
+    let output = runtime
+        .execute_script("serde.to_csv [{name: 'Ada', age: 36}, {name: 'Grace', lang: 'COBOL'}]")
+        .expect("to_csv");
+    assert_eq!(
+        output.return_value.as_deref(),
+        Some("name,age,lang\nAda,36,\nGrace,,COBOL\n")
+    );
+
+    let output = runtime
+        .execute_script("serde.from_csv '1,2\\n3,4', {headers: false, numbers: false}")
+        .expect("headerless");
+    assert_eq!(
+        output.return_value.as_deref(),
+        Some("[['1', '2'], ['3', '4']]")
+    );
+    assert!(runtime.execute_script("serde.to_csv [[1, [2]]]").is_err());
+}
+
+/// Arbitrary JSON documents: finite floats and `i64` integers only, since those are the
+/// numbers Koto can hold.
+fn json_value() -> impl Strategy<Value = JsonValue> {
+    let leaf = prop_oneof![
+        Just(JsonValue::Null),
+        any::<bool>().prop_map(JsonValue::Bool),
+        any::<i64>().prop_map(JsonValue::from),
+        (-1.0e12..1.0e12f64).prop_map(JsonValue::from),
+        "\\PC{0,12}".prop_map(JsonValue::String),
+    ];
+    leaf.prop_recursive(4, 32, 6, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..6).prop_map(JsonValue::Array),
+            prop::collection::btree_map("[a-z_]{1,8}", inner, 0..6)
+                .prop_map(|entries| JsonValue::Object(entries.into_iter().collect())),
+        ]
+    })
+}
+
+static SERDE_RUNTIME: Lazy<Runtime> = Lazy::new(|| Runtime::new().expect("runtime"));
+
+/// Passes `text` through a serde function pair in the runtime and parses the result.
+fn serde_round_trip(from: &str, to: &str, text: &str) -> Result<String, TestCaseError> {
+    let script = format!("serde.{to}(serde.{from} args[0])");
+    let output = SERDE_RUNTIME
+        .execute_script_with_args(&script, [text])
+        .map_err(|error| TestCaseError::fail(format!("{from}/{to} failed: {error:#}")))?;
+    Ok(output.return_value.unwrap_or_default())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn serde_json_round_trips_arbitrary_values(value in json_value()) {
+        let text = serde_json::to_string(&value).unwrap();
+        let round_tripped = serde_round_trip("from_json", "to_json", &text)?;
+        let parsed: JsonValue = serde_json::from_str(&round_tripped)
+            .map_err(|error| TestCaseError::fail(format!("to_json produced invalid JSON: {error}")))?;
+        prop_assert_eq!(parsed, value);
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn serde_yaml_round_trips_arbitrary_values(value in json_value()) {
+        let text = serde_yaml::to_string(&value).unwrap();
+        let round_tripped = serde_round_trip("from_yaml", "to_yaml", &text)?;
+        let parsed: JsonValue = serde_yaml::from_str(&round_tripped)
+            .map_err(|error| TestCaseError::fail(format!("to_yaml produced invalid YAML: {error}")))?;
+        prop_assert_eq!(parsed, value);
+    }
+}
+
+#[test]
+fn env_uses_the_per_run_allowlist() {
+    let runtime = Runtime::new().expect("runtime");
+    let script = "before = env.get 'KOTO_LEARNING_TEST_MODE', 'unset'\nenv.set 'KOTO_LEARNING_TEST_MODE', 'test'\n(before, env.get('KOTO_LEARNING_TEST_MODE'), env.allowed().contains 'KOTO_LEARNING_TEST_MODE')";
+    assert!(runtime.execute_script(script).is_err());
+
+    let options = RunOptions::new().env_allowlist(["KOTO_LEARNING_TEST_MODE"]);
+    let output = runtime.execute(script, &options).expect("allowed run");
+    assert_eq!(
+        output.return_value.as_deref(),
+        Some("('unset', 'test', true)")
+    );
+    assert!(std::env::var_os("KOTO_LEARNING_TEST_MODE").is_none());
+
+    // Overrides and per-run names don't outlive the run.
+    let output = runtime
+        .execute("env.get 'KOTO_LEARNING_TEST_MODE'", &options)
+        .expect("fresh run");
+    assert_eq!(output.return_value.as_deref(), Some("null"));
+    assert!(
+        runtime
+            .execute_script("env.get 'KOTO_LEARNING_TEST_MODE'")
+            .is_err()
+    );
+}
+
+#[test]
+fn sandbox_audit_runs_every_probe_under_every_profile() {
+    let runtime = Runtime::new().expect("runtime");
+    let report = runtime.run_sandbox_audit();
+    assert_eq!(
+        report.findings.len(),
+        audit::probes().len() * audit::AUDITED_PROFILES.len()
+    );
+    assert_eq!(runtime.profile(), RuntimeProfile::Standard);
+
+    let finding = |name: &str, profile: RuntimeProfile| {
+        report
+            .findings
+            .iter()
+            .find(|finding| finding.probe.name == name && finding.profile == profile)
+            .expect("finding")
+    };
+    for name in [
+        "process.run",
+        "clipboard.get",
+        "env.get",
+        "host.fs parent path",
+    ] {
+        assert!(finding(name, RuntimeProfile::Standard).passed(), "{name}");
+    }
+    let trusted = finding("clipboard.get", RuntimeProfile::Trusted);
+    assert!(!trusted.was_blocked() && trusted.passed());
+    // The trusted profile alone doesn't let a run start programs.
+    assert!(finding("process.run", RuntimeProfile::Trusted).was_blocked());
+    assert!(finding("env.set", RuntimeProfile::Trusted).was_blocked());
+}
+
+#[test]
+fn profile_comparison_diffs_output_across_setups() {
+    let runtime = Runtime::new().expect("runtime");
+    runtime.set_profile(RuntimeProfile::Trusted);
+    let script = "print 'sleeping'\nhost.sleep_ms 20\nhost.elapsed() == 0.02";
+    let [normal, deterministic] = [false, true].map(|deterministic| RunSetup {
+        profile: RuntimeProfile::Standard,
+        deterministic,
+    });
+
+    let comparison = runtime
+        .compare_setups(script, &RunOptions::new(), normal, deterministic)
+        .expect("comparison runs");
+    assert_eq!(comparison.left.transcript(), "sleeping\n=> false\n");
+    assert_eq!(comparison.right.transcript(), "sleeping\n=> true\n");
+    assert!(!comparison.is_identical());
+    let changed: Vec<_> = comparison
+        .diff()
+        .into_iter()
+        .filter(|line| line.kind != DiffKind::Same)
+        .map(|line| line.text)
+        .collect();
+    assert_eq!(changed, ["=> false", "=> true"]);
+
+    let failing = runtime
+        .compare_setups("throw 'nope'", &RunOptions::new(), normal, normal)
+        .expect("comparison runs");
+    assert!(
+        failing
+            .left
+            .error
+            .as_deref()
+            .is_some_and(|error| error.contains("nope"))
+    );
+    assert!(failing.is_identical());
+
+    assert_eq!(runtime.profile(), RuntimeProfile::Trusted);
+    assert!(!runtime.is_deterministic());
+}
+
+#[test]
+#[cfg(feature = "encoding")]
+fn host_encoding_converts_and_hashes() {
+    let runtime = Runtime::new().expect("runtime");
+    let script = "\
+encoding = host.encoding
+text = (encoding.base64_encode('koto'), encoding.base64_decode('a290\\nbw=='))
+hex = (encoding.hex_encode([0, 15, 255]), encoding.hex_decode_bytes('000fff'))
+digests = (encoding.sha256('abc'), encoding.md5(''))
+(text[0], text[1], hex[0], hex[1], digests[0], digests[1])";
+    let output = runtime.execute_script(script).expect("encoding");
+    assert_eq!(
+        output.return_value.as_deref(),
+        Some(
+            "(a290bw==, koto, 000fff, [0, 15, 255], \
+             ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad, \
+             d41d8cd98f00b204e9800998ecf8427e)"
+        )
+    );
+
+    assert!(
+        runtime
+            .execute_script("host.encoding.base64_decode '!!'")
+            .is_err()
+    );
+    assert!(
+        runtime
+            .execute_script("host.encoding.hex_decode 'ff'")
+            .is_err()
+    );
+    assert!(
+        runtime
+            .execute_script("host.encoding.hex_encode [256]")
+            .is_err()
+    );
+}
+
+#[test]
+fn host_interval_ticks_until_count_or_false() {
+    let runtime = Runtime::new().expect("runtime");
+    runtime.set_deterministic(true);
+    let output = runtime
+        .execute_script("ticks = []\nrecord = |i| ticks.push i\nn = host.interval record, 250, 3\n(n, ticks, host.elapsed())")
+        .expect("interval");
+    assert_eq!(output.return_value.as_deref(), Some("(3, [0, 1, 2], 0.75)"));
+
+    let output = runtime
+        .execute_script("keep_going = |i| i < 1\nhost.interval keep_going, 10, 100")
+        .expect("stops early");
+    assert_eq!(output.return_value.as_deref(), Some("2"));
+    runtime.set_deterministic(false);
+
+    let error = runtime
+        .execute_script_with_timeout(
+            "forever = |i| true\nhost.interval forever, 50, 1000",
+            Some(Duration::from_millis(200)),
+        )
+        .expect_err("timeout stops the interval");
+    assert!(error.to_string().contains("Execution limit"));
+    assert!(runtime.execute_script("host.interval 1, 10, 1").is_err());
+}
+
+#[test]
+fn concurrent_workers_join_and_share_channels() {
+    let runtime = Runtime::new().expect("runtime");
+    let script = "\
+results = concurrent.channel()
+square = |n|
+  print 'squaring {n}'
+  results.send n * n
+  n
+first = concurrent.spawn 'first', square, 3
+second = concurrent.spawn square, 4
+joined = (first.join(), second.join())
+received = [results.recv(), results.recv()].sort()
+(first.name, second.name, joined, received, results.try_recv())";
+    let output = runtime.execute_script(script).expect("workers");
+    assert_eq!(
+        output.return_value.as_deref(),
+        Some("('first', 'worker 2', (3, 4), [9, 16], null)")
+    );
+
+    let mut workers = output.workers.clone();
+    workers.sort_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(workers.len(), 2);
+    assert_eq!(workers[0].name, "first");
+    assert_eq!(workers[0].stdout, "squaring 3\n");
+    assert_eq!(workers[1].stdout, "squaring 4\n");
+    assert!(output.stdout.contains("squaring 3") && output.stdout.contains("squaring 4"));
+
+    let error = runtime
+        .execute_script("fail = || throw 'worker broke'\nconcurrent.spawn(fail).join()")
+        .expect_err("worker errors reach join");
+    assert!(error.to_string().contains("worker broke"));
+
+    let error = runtime
+        .execute_script_with_timeout(
+            "concurrent.channel().recv()",
+            Some(Duration::from_millis(200)),
+        )
+        .expect_err("timeout stops recv");
+    assert!(error.to_string().contains("Execution limit"));
+}
+
+#[test]
+fn daemon_runs_fire_callbacks_until_stopped() {
+    let runtime: &'static Runtime = Box::leak(Box::new(Runtime::new().expect("runtime")));
+    runtime.set_deterministic(true);
+    let script = "\
+ticks = []
+record = |tick|
+  ticks.push tick
+  if tick == 2 then host.stop()
+announce = || print 'after {host.elapsed()}'
+host.every 100, record
+host.after 150, announce
+ignored = host.after 1000, announce
+host.cancel ignored
+countdown = []
+count_down = |tick|
+  countdown.push tick
+  tick < 1
+host.every 40, count_down
+(ticks, countdown)";
+    let output = runtime
+        .execute(script, &RunOptions::new().daemon(true))
+        .expect("daemon run");
+    // `count_down` returned false on its second tick, which stopped it.
+    assert_eq!(output.return_value.as_deref(), Some("([0, 1, 2], [0, 1])"));
+    assert_eq!(output.stdout, "after 0.15\n");
+    runtime.set_deterministic(false);
+
+    let error = runtime
+        .execute_script("noop = |_| null\nhost.every 100, noop")
+        .expect_err("timers need a daemon run");
+    assert!(error.to_string().contains("daemon"));
+
+    let handle = runtime.execute_async(
+        "beat = |_| print 'beat'\nhost.every 5, beat\n'started'",
+        RunOptions::new().daemon(true),
+    );
+    std::thread::sleep(Duration::from_millis(50));
+    assert!(handle.poll().is_none());
+    handle.cancel();
+    let output = handle.wait().expect("stopping a daemon isn't an error");
+    assert_eq!(output.return_value.as_deref(), Some("started"));
+    assert!(output.stdout.contains("beat"));
+}
+
+#[test]
+fn cancelled_loops_without_host_calls_end_at_the_time_limit() {
+    let runtime: &'static Runtime = Box::leak(Box::new(Runtime::new().expect("runtime")));
+    let handle = runtime.execute_async(
+        "x = 0\nloop\n  x += 1",
+        RunOptions::new().timeout(Some(Duration::from_millis(300))),
+    );
+    std::thread::sleep(Duration::from_millis(50));
+    handle.cancel();
+    // The loop never calls into the host, so it only stops once the time limit is reached.
+    assert!(handle.poll().is_none());
+    assert!(handle.wait().is_err());
+    assert!(handle.elapsed() >= Duration::from_millis(300));
+    assert!(handle.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn process_run_needs_the_run_permission() {
+    let runtime = Runtime::new().expect("runtime");
+    let script = "result = process.run 'cargo', ['--version']\n(result.exit_code, result.stdout.starts_with 'cargo')";
+
+    runtime.set_profile(RuntimeProfile::Trusted);
+    let error = runtime
+        .execute_script(script)
+        .expect_err("the trusted profile alone isn't enough");
+    assert!(error.to_string().contains("process.run is disabled"));
+    runtime.set_profile(RuntimeProfile::Standard);
+
+    let output = runtime
+        .execute(script, &RunOptions::new().allow_process(true))
+        .expect("permitted run");
+    assert_eq!(output.return_value.as_deref(), Some("(0, true)"));
+
+    // The permission only lasts for the run it was given to.
+    assert!(runtime.execute_script(script).is_err());
+
+    let metadata: ExampleMetadata =
+        serde_json::from_str(r#"{"id": "tools", "permissions": {"process": true}}"#)
+            .expect("metadata");
+    assert!(metadata.permissions.process);
+    let metadata: ExampleMetadata = serde_json::from_str(r#"{"id": "plain"}"#).expect("metadata");
+    assert!(!metadata.permissions.process);
+}
+
+#[cfg(unix)]
+#[test]
+fn process_run_is_killed_at_the_run_time_limit() {
+    let runtime = Runtime::new().expect("runtime");
+    let options = RunOptions::new()
+        .allow_process(true)
+        .timeout(Some(Duration::from_millis(300)));
+
+    let start = std::time::Instant::now();
+    let error = runtime
+        .execute(
+            "process.run 'sleep', ['30'], { timeout_ms: 60000 }",
+            &options,
+        )
+        .expect_err("the child outlives the run");
+    assert!(
+        error
+            .to_string()
+            .contains("Execution limit exceeded while waiting for 'sleep'")
+    );
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn gfx_frames_reach_observers_and_the_output() {
+    let runtime = Runtime::new().expect("runtime");
+    let (sender, receiver) = mpsc::channel();
+    runtime.add_observer(ChannelObserver::new(sender));
+    let script = "\
+gfx.canvas 200, 100
+gfx.clear 'black'
+gfx.rect 10, 20, 30, 40, 'red'
+first = gfx.present()
+gfx.circle 50, 50, 5, '#00ff0080'
+gfx.line 0, 0, 200, 100, (10, 20, 30)
+gfx.text 4, 4, 'score: 1'
+(first, gfx.size())";
+    let output = runtime.execute_script(script).expect("drawing");
+    assert_eq!(output.return_value.as_deref(), Some("(1, (200.0, 100.0))"));
+
+    // Shapes drawn after the last present are shown when the script finishes.
+    let frame = output.frame.expect("last frame");
+    assert_eq!(frame.number, 2);
+    assert_eq!((frame.width, frame.height), (200.0, 100.0));
+    assert_eq!(frame.shapes.len(), 3);
+    assert!(matches!(
+        frame.shapes[0],
+        Shape::Circle {
+            color: [0, 255, 0, 128],
+            ..
+        }
+    ));
+    assert!(matches!(
+        frame.shapes[1],
+        Shape::Line {
+            color: [10, 20, 30, 255],
+            ..
+        }
+    ));
+
+    let frames: Vec<_> = receiver
+        .try_iter()
+        .filter_map(|event| match event {
+            ExecutionEvent::Frame(frame) => Some(frame),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].shapes.len(), 1);
+    assert!(matches!(
+        frames[0].shapes[0],
+        Shape::Rect { width: 30.0, .. }
+    ));
+
+    let output = runtime.execute_script("1 + 1").expect("plain run");
+    assert!(output.frame.is_none());
+    assert!(
+        runtime
+            .execute_script("gfx.rect 0, 0, 1, 1, 'chartreuse'")
+            .is_err()
+    );
+}
+
+#[test]
+fn input_events_reach_a_running_daemon() {
+    let runtime: &'static Runtime = Box::leak(Box::new(Runtime::new().expect("runtime")));
+    // Nothing is running, so this is dropped rather than leaking into the next run.
+    runtime.push_input_event(InputEvent::KeyDown("Q".to_string()));
+
+    let script = "\
+seen = []
+poll = |_|
+  for event in input.events()
+    seen.push event.type
+    if event.type == 'key_down' and event.key == 'Q'
+      seen.push input.is_down 'ArrowUp'
+      seen.push input.pointer()
+      host.stop()
+host.every 5, poll
+seen";
+    let options = RunOptions::new()
+        .daemon(true)
+        .timeout(Some(Duration::from_secs(5)));
+    let handle = runtime.execute_async(script, options);
+    std::thread::sleep(Duration::from_millis(50));
+    runtime.push_input_event(InputEvent::PointerMove { x: 10.0, y: 20.0 });
+    runtime.push_input_event(InputEvent::KeyDown("ArrowUp".to_string()));
+    runtime.push_input_event(InputEvent::KeyDown("Q".to_string()));
+    let output = handle.wait().expect("daemon run");
+    assert_eq!(
+        output.return_value.as_deref(),
+        Some("['pointer_move', 'key_down', 'key_down', true, (10.0, 20.0)]")
+    );
+}
+
+#[test]
+#[cfg(feature = "serialization")]
+fn serde_binary_formats_round_trip() {
+    let runtime = Runtime::new().expect("runtime");
+    let output = runtime
+        .execute_script("(serde.to_msgpack {a: 1}, serde.to_cbor {a: 1})")
+        .expect("encode");
+    assert_eq!(
+        output.return_value.as_deref(),
+        Some("([129, 161, 97, 1], [161, 97, 97, 1])")
+    );
+
+    let script = "\
+payload = {name: 'Ada', scores: [9, 10.5], active: true, note: null}
+msgpack = serde.from_msgpack serde.to_msgpack payload
+cbor = serde.from_cbor serde.to_cbor payload
+json_size = serde.to_json(payload).size()
+(msgpack == payload, cbor == payload, serde.to_msgpack(payload).size() < json_size)";
+    let output = runtime.execute_script(script).expect("round trip");
+    assert_eq!(output.return_value.as_deref(), Some("(true, true, true)"));
+
+    assert!(runtime.execute_script("serde.from_msgpack [300]").is_err());
+    assert!(runtime.execute_script("serde.from_cbor [255, 0]").is_err());
+}
+
+#[test]
+#[cfg(feature = "serialization")]
+fn serde_ron_reads_structs_and_round_trips() {
+    let runtime = Runtime::new().expect("runtime");
+    let script = r#"
+config = serde.from_ron '
+  // window settings
+  Window(title: "Pong", size: (800, 600), vsync: true, icon: None, speed: Some(1.5))
+'
+payload = {name: 'Ada', scores: [9, 10.5], active: true, note: null}
+again = serde.from_ron serde.to_ron payload
+(config.title, config.size, config.icon, config.speed, again == payload)
+"#;
+    let output = runtime.execute_script(script).expect("ron");
+    assert_eq!(
+        output.return_value.as_deref(),
+        Some("(Pong, [800, 600], null, 1.5, true)")
+    );
+
+    assert!(runtime.execute_script("serde.from_ron '(a: 1'").is_err());
+    assert!(runtime.execute_script("serde.from_ron '{1: 2}'").is_err());
+}
+
+#[test]
+#[cfg(feature = "serialization")]
+fn serde_xml_round_trips_elements() {
+    let runtime = Runtime::new().expect("runtime");
+    let script = r#"
+xml = '<?xml version="1.0"?>
+<feed version="2">
+  <!-- two items -->
+  <item id="1">Tom &amp; Jerry</item>
+  <item id="2"/>
+</feed>'
+feed = serde.from_xml xml
+first = feed.children[0]
+again = serde.from_xml serde.to_xml feed
+(feed.attributes.version, feed.children.size(), first.children[0], again == feed)
+"#;
+    let output = runtime.execute_script(script).expect("xml");
+    assert_eq!(
+        output.return_value.as_deref(),
+        Some("(2, 2, Tom & Jerry, true)")
+    );
+
+    let output = runtime
+        .execute_script("serde.to_xml {name: 'note', attributes: {n: 3}, children: ['a < b']}")
+        .expect("to_xml");
+    assert_eq!(
+        output.return_value.as_deref(),
+        Some("<note n=\"3\">a &lt; b</note>\n")
+    );
+
+    assert!(
+        runtime
+            .execute_script("serde.from_xml '<a><b></a>'")
+            .is_err()
+    );
+    assert!(runtime.execute_script("serde.from_xml '<a/><b/>'").is_err());
+    assert!(
+        runtime
+            .execute_script("serde.to_xml {children: []}")
+            .is_err()
+    );
+}
+
+#[test]
+fn serde_validate_reports_schema_errors() {
+    let runtime = Runtime::new().expect("runtime");
+    let script = "\
+schema =
+  type: 'object'
+  required: ['id', 'tags']
+  additionalProperties: false
+  properties:
+    id: {type: 'integer', minimum: 1}
+    tags: {type: 'array', items: {'$ref': '#/$defs/tag'}}
+  '$defs':
+    tag: {enum: ['a', 'b']}
+result = ''
+for error in serde.validate({id: 0.5, tags: ['a', 'c'], extra: 1}, schema)
+  result += '{error.path} {error.keyword};'
+valid = serde.validate {id: 2, tags: ['b']}, schema
+(result, valid.size(), (serde.validate {tags: []}, schema).first().message)";
+    let output = runtime.execute_script(script).expect("validate");
+    assert_eq!(
+        output.return_value.as_deref(),
+        Some(
+            "(/extra additionalProperties;/id type;/id minimum;/tags/1 enum;, 0, Missing required property 'id')"
+        )
+    );
+
+    let output = runtime
+        .execute_script("(serde.validate 1.0, '{\"type\": \"integer\"}').size()")
+        .expect("json schema");
+    assert_eq!(output.return_value.as_deref(), Some("0"));
+
+    let error = runtime
+        .execute_script("serde.validate 1, {'$ref': '#/$defs/missing'}")
+        .unwrap_err();
+    assert!(error.to_string().contains("Invalid schema"), "{error}");
+}
+
+#[test]
+fn audio_tones_are_recorded_and_keep_time() {
+    let runtime = Runtime::new().expect("runtime");
+    assert!(!runtime.audio_enabled());
+    runtime.set_deterministic(true);
+    let output = runtime
+        .execute_script("audio.tone 440, 250\naudio.tone 660, 250, 0.5\naudio.beep()\n(audio.enabled(), host.elapsed())")
+        .expect("tones");
+    assert_eq!(output.return_value.as_deref(), Some("(false, 0.62)"));
+    let frequencies: Vec<f32> = output.tones.iter().map(|tone| tone.frequency).collect();
+    assert_eq!(frequencies, [440.0, 660.0, 880.0]);
+    assert_eq!(output.tones[1].volume, 0.5);
+    assert_eq!(output.tones[2].duration, Duration::from_millis(120));
+    runtime.set_deterministic(false);
+
+    assert!(runtime.execute_script("audio.tone 5, 100").is_err());
+    assert!(runtime.execute_script("audio.tone 440, 100, 2").is_err());
+}
+
+#[test]
+fn app_module_sends_requests_and_returns_answers() {
+    let runtime = Runtime::new().expect("runtime");
+    let (sender, requests) = mpsc::channel::<AutomationRequest>();
+    runtime
+        .register_host_module("app", automation::app_module(sender))
+        .expect("register app");
+    let responder = std::thread::spawn(move || {
+        let mut commands = Vec::new();
+        for request in requests.iter().take(5) {
+            let answer = match &request.command {
+                AutomationCommand::ListExamples => Ok(serde_json::json!(["a", "b"])),
+                AutomationCommand::Select(id) if id == "missing" => {
+                    Err("There is no example with the id 'missing'".to_string())
+                }
+                AutomationCommand::Run => Ok(serde_json::json!({
+                    "succeeded": true,
+                    "stdout": "hi\n",
+                })),
+                _ => Ok(JsonValue::Null),
+            };
+            commands.push(request.command.clone());
+            request.reply(answer);
+        }
+        commands
+    });
+
+    let output = runtime
+        .execute_script(
+            "ids = app.examples()\napp.select 'b'\napp.set_input 'count', 3\nresult = app.run()\n(ids.size(), result.succeeded, result.stdout)",
+        )
+        .expect("automation script");
+    assert_eq!(output.return_value.as_deref(), Some("(2, true, hi\n)"));
+
+    let error = runtime.execute_script("app.select 'missing'").unwrap_err();
+    assert!(
+        error.to_string().contains("no example with the id"),
+        "{error}"
+    );
+
+    drop(runtime);
+    let commands = responder.join().expect("responder");
+    assert_eq!(
+        commands,
+        [
+            AutomationCommand::ListExamples,
+            AutomationCommand::Select("b".to_string()),
+            AutomationCommand::SetInput {
+                name: "count".to_string(),
+                value: "3".to_string(),
+            },
+            AutomationCommand::Run,
+            AutomationCommand::Select("missing".to_string()),
+        ]
+    );
 }
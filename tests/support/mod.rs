@@ -0,0 +1,104 @@
+//! Headless harness for driving `ExplorerApp` frame by frame with `egui_kittest`.
+
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use egui_kittest::{Harness, kittest::Queryable};
+use koto_learning::{
+    app::ExplorerApp,
+    examples::{ExampleLibrary, ExampleMetadata},
+};
+
+/// An example for [`explorer`]: its id, title, categories, and script.
+pub struct TestExample {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub categories: &'static [&'static str],
+    pub script: &'static str,
+}
+
+/// Held by every UI test while its explorer is alive: the app drives the process-wide
+/// `runtime::RUNTIME`, so two explorers running scripts at once would see each other's output.
+static UI_TEST_LOCK: Mutex<()> = Mutex::new(());
+
+/// A harness that keeps other UI tests waiting until it's dropped.
+pub struct Explorer {
+    harness: Harness<'static, ExplorerApp>,
+    _runtime: MutexGuard<'static, ()>,
+}
+
+impl Deref for Explorer {
+    type Target = Harness<'static, ExplorerApp>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.harness
+    }
+}
+
+impl DerefMut for Explorer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.harness
+    }
+}
+
+/// Starts the explorer on an in-memory catalog of `examples` and runs the first frames.
+pub fn explorer(examples: &[TestExample]) -> Explorer {
+    let examples = examples
+        .iter()
+        .map(|example| {
+            let metadata = ExampleMetadata {
+                id: example.id.to_string(),
+                title: example.title.to_string(),
+                description: format!("About {}", example.title),
+                categories: example.categories.iter().map(|c| c.to_string()).collect(),
+                ..Default::default()
+            };
+            (metadata, example.script.to_string())
+        })
+        .collect();
+    explorer_with_metadata(examples)
+}
+
+/// Like [`explorer`], for examples that need more of their `meta.json` than [`TestExample`]
+/// covers, such as inputs.
+pub fn explorer_with_metadata(examples: Vec<(ExampleMetadata, String)>) -> Explorer {
+    // The app keeps a `'static` reference, as it does to the shared library.
+    explorer_with_library(Box::leak(Box::new(ExampleLibrary::in_memory(examples))))
+}
+
+/// Starts the explorer on `library`, e.g. one loaded from a temporary folder so a test can
+/// change files and call [`ExampleLibrary::refresh`] in place of the watcher.
+pub fn explorer_with_library(library: &'static ExampleLibrary) -> Explorer {
+    // A test that panicked still released the runtime, so a poisoned lock is fine to take.
+    let runtime = UI_TEST_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut harness = Harness::builder()
+        .with_size(egui::vec2(1280.0, 900.0))
+        .build_eframe(|cc| ExplorerApp::with_library(cc, library));
+    harness.run_steps(2);
+    Explorer {
+        harness,
+        _runtime: runtime,
+    }
+}
+
+/// Steps the harness until a widget labelled `label` shows up, for output from background
+/// runs. Panics after `timeout`.
+pub fn wait_for_label(harness: &mut Harness<'_, ExplorerApp>, label: &str, timeout: Duration) {
+    let deadline = Instant::now() + timeout;
+    loop {
+        harness.step();
+        if harness.query_by_label_contains(label).is_some() {
+            return;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "'{label}' didn't appear within {timeout:?}"
+        );
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}